@@ -0,0 +1,912 @@
+// Copyright (c) 2020 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Generation of the Rust bindings from a `Registry`.
+//!
+//! The output follows the conventions of the hand-written `vk-sys`: the `Vk`, `vk` and `VK_`
+//! prefixes are removed, enumerations and flags are `u32` (or `u64`) type aliases with one
+//! constant per value, handles are `usize` (dispatchable) or `u64`, and commands are grouped in
+//! tables created with the `ptrs!` macro.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use crate::registry::CType;
+use crate::registry::Command;
+use crate::registry::Interface;
+use crate::registry::Member;
+use crate::registry::Registry;
+use crate::registry::TypeDef;
+use crate::registry::Value;
+
+const HEADER: &str = "\
+// Copyright (c) 2016 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+// This file is generated by `vk-sys-gen` from the Vulkan registry. Do not edit it by hand.
+
+#![doc(html_logo_url = \"https://raw.githubusercontent.com/vulkano-rs/vulkano/master/logo.png\")]
+
+#![allow(non_upper_case_globals)]
+#![allow(non_snake_case)]
+#![allow(non_camel_case_types)]
+
+use std::mem;
+use std::ffi::CStr;
+use std::fmt;
+use std::os::raw::c_char;
+use std::os::raw::c_void;
+use std::os::raw::c_ulong;
+use std::os::raw::c_double;
+use std::os::raw::c_int;
+
+pub const NULL_HANDLE: u64 = 0;
+";
+
+const PTRS_MACRO: &str = r#"macro_rules! ptrs {
+    ($struct_name:ident, { $($name:ident => ($($param_n:ident: $param_ty:ty),*) -> $ret:ty,)+ }) => (
+        pub struct $struct_name {
+            $(
+                pub $name: extern "system" fn($($param_ty),*) -> $ret,
+            )+
+        }
+
+        impl fmt::Debug for $struct_name {
+            #[inline]
+            fn fmt(&self, fmt: &mut fmt::Formatter) -> ::std::result::Result<(), fmt::Error> {
+                write!(fmt, "<Vulkan functions>")       // TODO:
+            }
+        }
+
+        unsafe impl Send for $struct_name {}
+        unsafe impl Sync for $struct_name {}
+
+        impl $struct_name {
+            pub fn load<F>(mut f: F) -> $struct_name
+                where F: FnMut(&CStr) -> *const c_void
+            {
+                $struct_name {
+                    $(
+                        $name: unsafe {
+                            extern "system" fn $name($(_: $param_ty),*) { panic!("function pointer `{}` not loaded", stringify!($name)) }
+                            let name = CStr::from_bytes_with_nul_unchecked(concat!("vk", stringify!($name), "\0").as_bytes());
+                            let val = f(name);
+                            if val.is_null() { mem::transmute($name as *const ()) } else { mem::transmute(val) }
+                        },
+                    )+
+                }
+            }
+
+            $(
+                #[inline]
+                pub unsafe fn $name(&self $(, $param_n: $param_ty)*) -> $ret {
+                    let ptr = self.$name;
+                    ptr($($param_n),*)
+                }
+            )+
+        }
+    )
+}
+"#;
+
+// C types that map directly to a Rust type. The boolean is false for types that can only be used
+// behind a pointer.
+const PRIMITIVES: &[(&str, &str, bool)] = &[
+    ("void", "c_void", false),
+    ("char", "c_char", true),
+    ("float", "f32", true),
+    ("double", "c_double", true),
+    ("int", "c_int", true),
+    ("size_t", "usize", true),
+    ("uint8_t", "u8", true),
+    ("uint16_t", "u16", true),
+    ("uint32_t", "u32", true),
+    ("uint64_t", "u64", true),
+    ("int8_t", "i8", true),
+    ("int16_t", "i16", true),
+    ("int32_t", "i32", true),
+    ("int64_t", "i64", true),
+];
+
+// Types of the platform headers. Other platform types can only be used behind a pointer.
+const PLATFORM_TYPES: &[(&str, &str)] = &[
+    ("Window", "c_ulong"),
+    ("VisualID", "c_ulong"),
+    ("RROutput", "c_ulong"),
+    ("xcb_window_t", "u32"),
+    ("xcb_visualid_t", "u32"),
+    ("zx_handle_t", "u32"),
+    ("DWORD", "u32"),
+    ("LPCWSTR", "*const u16"),
+    ("HINSTANCE", "*mut c_void"),
+    ("HWND", "*mut c_void"),
+    ("HMONITOR", "*mut c_void"),
+    ("HANDLE", "*mut c_void"),
+    ("GgpStreamDescriptor", "u32"),
+    ("GgpFrameToken", "u64"),
+];
+
+// How a C type can be used.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Kind {
+    // Can be used by value.
+    Sized,
+    // Can only be used behind a pointer.
+    Opaque,
+}
+
+/// Generates the content of `vk-sys/src/lib.rs` from the registry.
+pub fn generate(registry: &Registry) -> String {
+    Generator::new(registry).run()
+}
+
+struct Generator<'a> {
+    registry: &'a Registry,
+    types: HashMap<&'a str, &'a TypeDef>,
+    // Types that can't be generated, because they use unknown types by value.
+    skipped: HashSet<&'a str>,
+    // Core versions and extensions that are supported.
+    interfaces: Vec<&'a Interface>,
+    constants: Vec<Constant>,
+    out: String,
+}
+
+impl<'a> Generator<'a> {
+    fn new(registry: &'a Registry) -> Generator<'a> {
+        let types = registry.types.iter().map(|t| (t.name(), t)).collect();
+        let interfaces: Vec<&Interface> = registry
+            .features
+            .iter()
+            .chain(registry.extensions.iter())
+            .filter(|i| !i.disabled)
+            .collect();
+
+        let constants = collect_constants(registry, &interfaces);
+        let mut gen = Generator {
+            registry,
+            types,
+            skipped: HashSet::new(),
+            interfaces,
+            constants,
+            out: String::new(),
+        };
+        gen.compute_skipped();
+        gen
+    }
+
+    fn run(mut self) -> String {
+        self.out.push_str(HEADER);
+        self.constants();
+        self.types();
+        self.out.push('\n');
+        self.out.push_str(PTRS_MACRO);
+        self.tables();
+        self.out
+    }
+
+    // Returns how a C type can be used, or `None` if it is unknown.
+    fn kind(&self, name: &str) -> Option<Kind> {
+        if let Some(&(_, _, sized)) = PRIMITIVES.iter().find(|p| p.0 == name) {
+            return Some(if sized { Kind::Sized } else { Kind::Opaque });
+        }
+        if self.skipped.contains(name) {
+            return None;
+        }
+
+        match self.types.get(name) {
+            Some(TypeDef::Alias { alias, .. }) => self.kind(alias),
+            Some(&&TypeDef::Basetype { ty: None, .. }) => Some(Kind::Opaque),
+            Some(&&TypeDef::Basetype { ty: Some(ref ty), .. }) => {
+                if ty.pointers.is_empty() { self.kind(&ty.base) } else { Some(Kind::Sized) }
+            },
+            Some(TypeDef::Platform { name }) => {
+                if PLATFORM_TYPES.iter().any(|p| p.0 == name) {
+                    Some(Kind::Sized)
+                } else {
+                    Some(Kind::Opaque)
+                }
+            },
+            Some(_) => Some(Kind::Sized),
+            None => None,
+        }
+    }
+
+    // Returns true if the type can be used in a declaration.
+    fn usable(&self, ty: &CType) -> bool {
+        if ty.pointers.is_empty() {
+            self.kind(&ty.base) == Some(Kind::Sized)
+        } else {
+            true
+        }
+    }
+
+    // Finds the types that use unknown types by value, directly or not.
+    fn compute_skipped(&mut self) {
+        loop {
+            let mut new = Vec::new();
+            for ty in &self.registry.types {
+                if self.skipped.contains(ty.name()) {
+                    continue;
+                }
+                let ok = match *ty {
+                    TypeDef::Alias { ref alias, .. } => self.kind(alias).is_some(),
+                    TypeDef::Basetype { ty: Some(ref ty), .. } => self.usable(ty),
+                    TypeDef::Bitmask { ref flags, .. } => self.kind(flags).is_some(),
+                    TypeDef::Struct { ref members, .. } => {
+                        members.iter().all(|m| self.usable(&m.ty))
+                    },
+                    TypeDef::FuncPointer { ref ret, ref params, .. } => {
+                        (self.is_void(ret) || self.usable(ret)) &&
+                            params.iter().all(|p| self.usable(&p.ty))
+                    },
+                    _ => true,
+                };
+                if !ok {
+                    new.push(ty.name());
+                }
+            }
+
+            if new.is_empty() {
+                return;
+            }
+            self.skipped.extend(new);
+        }
+    }
+
+    fn is_void(&self, ty: &CType) -> bool {
+        ty.base == "void" && ty.pointers.is_empty()
+    }
+
+    // Returns the Rust name of a type.
+    fn base_name(&self, name: &str) -> String {
+        if let Some(p) = PRIMITIVES.iter().find(|p| p.0 == name) {
+            return p.1.to_owned();
+        }
+        if let Some(p) = PLATFORM_TYPES.iter().find(|p| p.0 == name) {
+            return p.1.to_owned();
+        }
+        if self.skipped.contains(name) {
+            return "c_void".to_owned();
+        }
+        match self.types.get(name) {
+            Some(&&TypeDef::Platform { .. }) | None => "c_void".to_owned(),
+            Some(_) => strip_type_prefix(name).to_owned(),
+        }
+    }
+
+    // Returns the Rust version of a C type.
+    fn rust_type(&self, ty: &CType) -> String {
+        let mut out = self.base_name(&ty.base);
+        for &is_const in &ty.pointers {
+            out = format!("*{} {}", if is_const { "const" } else { "mut" }, out);
+        }
+        for size in ty.array.iter().rev() {
+            let size = match size.parse::<u32>() {
+                Ok(n) => n.to_string(),
+                Err(_) => format!("{} as usize", strip_const_prefix(size)),
+            };
+            out = format!("[{}; {}]", out, size);
+        }
+        out
+    }
+
+    fn constants(&mut self) {
+        self.out.push('\n');
+        for c in self.constants.iter().filter(|c| c.group.is_none()) {
+            writeln!(self.out, "pub const {}: {} = {};", c.name, c.ty, c.value).unwrap();
+        }
+    }
+
+    fn types(&mut self) {
+        for ty in &self.registry.types {
+            if self.skipped.contains(ty.name()) {
+                continue;
+            }
+
+            match *ty {
+                TypeDef::Alias { ref name, ref alias } => {
+                    writeln!(self.out, "\npub type {} = {};", strip_type_prefix(name),
+                             strip_type_prefix(alias)).unwrap();
+                },
+                TypeDef::Basetype { ref name, ty: None } => {
+                    writeln!(self.out, "\npub type {} = c_void;", strip_type_prefix(name))
+                        .unwrap();
+                },
+                TypeDef::Basetype { ref name, ty: Some(ref ty) } => {
+                    let ty = self.rust_type(ty);
+                    writeln!(self.out, "\npub type {} = {};", strip_type_prefix(name), ty)
+                        .unwrap();
+                },
+                TypeDef::Bitmask { ref name, ref flags } => {
+                    writeln!(self.out, "\npub type {} = {};", strip_type_prefix(name),
+                             strip_type_prefix(flags)).unwrap();
+                },
+                TypeDef::Enum { ref name } => self.enumeration(name),
+                TypeDef::Handle { ref name, dispatchable } => {
+                    writeln!(self.out, "\npub type {} = {};", strip_type_prefix(name),
+                             if dispatchable { "usize" } else { "u64" }).unwrap();
+                },
+                TypeDef::Struct { ref name, union, ref members } => {
+                    self.structure(name, union, members);
+                },
+                TypeDef::FuncPointer { ref name, ref ret, ref params } => {
+                    let params = params
+                        .iter()
+                        .map(|p| self.rust_type(&p.ty))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    let ret = if self.is_void(ret) {
+                        String::new()
+                    } else {
+                        format!(" -> {}", self.rust_type(ret))
+                    };
+                    writeln!(self.out, "\npub type {} = extern \"system\" fn({}){};", name,
+                             params, ret).unwrap();
+                },
+                TypeDef::Platform { .. } => (),
+            }
+        }
+    }
+
+    fn enumeration(&mut self, name: &str) {
+        let wide = self.registry.enum_group(name).map(|g| g.wide).unwrap_or(false);
+        writeln!(self.out, "\npub type {} = {};", strip_type_prefix(name),
+                 if wide { "u64" } else { "u32" }).unwrap();
+        for c in self.constants.iter().filter(|c| c.group.as_deref() == Some(name)) {
+            writeln!(self.out, "pub const {}: {} = {};", c.name, c.ty, c.value).unwrap();
+        }
+    }
+
+    fn structure(&mut self, name: &str, union: bool, members: &[Member]) {
+        writeln!(self.out, "\n#[repr(C)]\n#[derive(Copy, Clone)]\npub {} {} {{",
+                 if union { "union" } else { "struct" }, strip_type_prefix(name)).unwrap();
+
+        let mut n = 0;
+        while n < members.len() {
+            let member = &members[n];
+
+            // Consecutive bitfields are merged into a single integer.
+            if let Some(mut width) = member.ty.bitfield {
+                let mut names = vec![member.name.as_str()];
+                while width < 32 && n + 1 < members.len() {
+                    match members[n + 1].ty.bitfield {
+                        Some(w) => {
+                            width += w;
+                            names.push(&members[n + 1].name);
+                            n += 1;
+                        },
+                        None => break,
+                    }
+                }
+                writeln!(self.out, "    pub {}: u32,", names.join("_and_")).unwrap();
+            } else {
+                writeln!(self.out, "    pub {}: {},", field_name(&member.name),
+                         self.rust_type(&member.ty)).unwrap();
+            }
+
+            n += 1;
+        }
+
+        self.out.push_str("}\n");
+    }
+
+    fn tables(&mut self) {
+        let commands: HashMap<&str, &Command> = self
+            .registry
+            .commands
+            .iter()
+            .map(|c| (c.name.as_str(), c))
+            .collect();
+
+        let mut seen = HashSet::new();
+        let mut tables: [Vec<&Command>; 4] = [Vec::new(), Vec::new(), Vec::new(), Vec::new()];
+        for interface in &self.interfaces {
+            for name in &interface.commands {
+                let cmd = match commands.get(name.as_str()) {
+                    Some(c) => *c,
+                    None => continue,
+                };
+                if !self.command_usable(cmd) || !seen.insert(name.as_str()) {
+                    continue;
+                }
+                tables[command_level(cmd) as usize].push(cmd);
+            }
+        }
+
+        self.table("Static", &tables[Level::Static as usize]);
+        self.table("EntryPoints", &tables[Level::Entry as usize]);
+        self.table("InstancePointers", &tables[Level::Instance as usize]);
+        self.table("DevicePointers", &tables[Level::Device as usize]);
+
+        // One table per extension, that can be loaded with `vkGetInstanceProcAddr`.
+        for interface in self.registry.extensions.iter().filter(|e| !e.disabled) {
+            let cmds = interface
+                .commands
+                .iter()
+                .filter_map(|name| commands.get(name.as_str()).cloned())
+                .filter(|cmd| self.command_usable(cmd))
+                .collect::<Vec<_>>();
+            self.table(&format!("{}Pointers", extension_struct_name(&interface.name)), &cmds);
+        }
+    }
+
+    fn command_usable(&self, cmd: &Command) -> bool {
+        (self.is_void(&cmd.ret) || self.usable(&cmd.ret)) &&
+            cmd.params.iter().all(|p| self.usable(&p.ty))
+    }
+
+    fn table(&mut self, name: &str, commands: &[&Command]) {
+        if commands.is_empty() {
+            return;
+        }
+
+        writeln!(self.out, "\nptrs!({}, {{", name).unwrap();
+        for cmd in commands {
+            let params = cmd
+                .params
+                .iter()
+                .map(|p| format!("{}: {}", field_name(&p.name), self.rust_type(&p.ty)))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let ret = if self.is_void(&cmd.ret) {
+                "()".to_owned()
+            } else {
+                self.rust_type(&cmd.ret)
+            };
+            writeln!(self.out, "    {} => ({}) -> {},", strip_command_prefix(&cmd.name), params,
+                     ret).unwrap();
+        }
+        self.out.push_str("});\n");
+    }
+}
+
+// Table in which a command is put.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Level {
+    Static = 0,
+    Entry = 1,
+    Instance = 2,
+    Device = 3,
+}
+
+fn command_level(cmd: &Command) -> Level {
+    match cmd.name.as_str() {
+        "vkGetInstanceProcAddr" => return Level::Static,
+        // Loaded from the instance, in order to load the device pointers.
+        "vkGetDeviceProcAddr" => return Level::Instance,
+        _ => (),
+    }
+
+    match cmd.params.first().map(|p| p.ty.base.as_str()) {
+        Some("VkInstance") | Some("VkPhysicalDevice") => Level::Instance,
+        Some("VkDevice") | Some("VkQueue") | Some("VkCommandBuffer") => Level::Device,
+        _ => Level::Entry,
+    }
+}
+
+// A constant to generate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Constant {
+    // Enumeration that the constant belongs to, or `None` for API and extension constants.
+    group: Option<String>,
+    name: String,
+    ty: String,
+    value: String,
+}
+
+// Gathers the constants of the enumerations and of the supported core versions and extensions.
+fn collect_constants(registry: &Registry, interfaces: &[&Interface]) -> Vec<Constant> {
+    let mut constants: Vec<Constant> = Vec::new();
+    let mut seen = HashSet::new();
+    let mut types_of = HashMap::new();
+
+    {
+        let mut add = |group: Option<&str>, wide: bool, name: &str, value: &Value| {
+            if !seen.insert(name.to_owned()) {
+                return;
+            }
+            let name = strip_const_prefix(name).to_owned();
+            if let Some((ty, value)) = const_value(value, wide, &types_of) {
+                types_of.insert(name.clone(), ty.clone());
+                constants.push(Constant {
+                    group: group.map(|g| g.to_owned()),
+                    name,
+                    ty,
+                    value,
+                });
+            }
+        };
+
+        for group in &registry.enums {
+            let group_name = if group.constants { None } else { Some(group.name.as_str()) };
+            for value in &group.values {
+                add(group_name, group.wide, &value.name, &value.value);
+            }
+        }
+
+        for interface in interfaces {
+            for e in &interface.enums {
+                let value = match e.value {
+                    Some(ref v) => v,
+                    None => continue,
+                };
+                let group = e.extends.as_deref();
+                let wide = group
+                    .and_then(|g| registry.enum_group(g))
+                    .map(|g| g.wide)
+                    .unwrap_or(false);
+                add(group, wide, &e.name, value);
+            }
+        }
+    }
+
+    // Drop the aliases whose target isn't generated, for example because it belongs to a
+    // disabled extension.
+    loop {
+        let names: HashSet<String> = constants.iter().map(|c| c.name.clone()).collect();
+        let before = constants.len();
+        constants.retain(|c| !is_identifier(&c.value) || names.contains(&c.value));
+        if constants.len() == before {
+            return constants;
+        }
+    }
+}
+
+// Returns the Rust type and value of a constant. `types_of` contains the types of the constants
+// already processed, for aliases.
+fn const_value(value: &Value, wide: bool, types_of: &HashMap<String, String>)
+               -> Option<(String, String)> {
+    let int_ty = if wide { "u64" } else { "u32" };
+
+    Some(match *value {
+        Value::Int(v) if v < 0 => {
+            (int_ty.to_owned(), format!("{}{} as {}", v, if wide { "i64" } else { "i32" }, int_ty))
+        },
+        Value::Int(v) => (int_ty.to_owned(), v.to_string()),
+        Value::Bit(bit) if wide => ("u64".to_owned(), format!("0x{:016x}", 1u64 << bit)),
+        Value::Bit(bit) => ("u32".to_owned(), format!("0x{:08x}", 1u32 << bit)),
+        Value::Str(ref s) => ("&str".to_owned(), format!("{:?}", s)),
+        Value::Alias(ref target) => {
+            let target = strip_const_prefix(target);
+            (types_of.get(target)?.clone(), target.to_owned())
+        },
+        Value::Typed { ref ty, ref value } => typed_value(ty, value)?,
+    })
+}
+
+// Converts a value of the `API Constants` group, such as `(~0U)` or `1000.0F`.
+fn typed_value(ty: &str, value: &str) -> Option<(String, String)> {
+    let value = value.trim_start_matches('(').trim_end_matches(')');
+    let (negate, value) = match value.strip_prefix('~') {
+        Some(v) => (true, v),
+        None => (false, value),
+    };
+
+    match ty {
+        "float" => Some(("f32".to_owned(), value.trim_end_matches('F').trim_end_matches('f')
+                                               .to_owned())),
+        "uint64_t" => {
+            let v: u64 = value.trim_end_matches("ULL").parse().ok()?;
+            let v = if negate { !v } else { v };
+            Some(("u64".to_owned(), if negate { format!("0x{:016x}", v) } else { v.to_string() }))
+        },
+        "uint32_t" | "int32_t" => {
+            let v: u32 = value.trim_end_matches('U').parse().ok()?;
+            let v = if negate { !v } else { v };
+            Some(("u32".to_owned(), if negate { format!("0x{:08x}", v) } else { v.to_string() }))
+        },
+        _ => None,
+    }
+}
+
+fn is_identifier(value: &str) -> bool {
+    value.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+}
+
+// `VkExtent2D` becomes `Extent2D`.
+fn strip_type_prefix(name: &str) -> &str {
+    name.strip_prefix("Vk").unwrap_or(name)
+}
+
+// `VK_FORMAT_UNDEFINED` becomes `FORMAT_UNDEFINED`.
+fn strip_const_prefix(name: &str) -> &str {
+    name.strip_prefix("VK_").unwrap_or(name)
+}
+
+// `vkCreateInstance` becomes `CreateInstance`.
+fn strip_command_prefix(name: &str) -> &str {
+    name.strip_prefix("vk").unwrap_or(name)
+}
+
+// Renames the members and parameters whose name is a Rust keyword.
+fn field_name(name: &str) -> &str {
+    match name {
+        "type" => "ty",
+        n => n,
+    }
+}
+
+// `VK_KHR_swapchain` becomes `KhrSwapchain`.
+fn extension_struct_name(name: &str) -> String {
+    strip_const_prefix(name)
+        .split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars.flat_map(|c| c.to_lowercase()))
+                                    .collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::generate;
+    use crate::registry::Registry;
+    use crate::xml;
+    use std::fs;
+    use std::path::Path;
+
+    const REGISTRY: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<registry>
+    <types>
+        <type requires="X11/Xlib.h" name="Display"/>
+        <type requires="X11/Xlib.h" name="Window"/>
+        <type requires="vk_video/vulkan_video_codec_h264std.h" name="StdVideoH264ProfileIdc"/>
+        <type category="basetype">typedef <type>uint32_t</type> <name>VkFlags</name>;</type>
+        <type category="basetype">typedef <type>uint32_t</type> <name>VkBool32</name>;</type>
+        <type category="basetype">struct <name>ANativeWindow</name>;</type>
+        <type requires="VkImageCreateFlagBits" category="bitmask">typedef <type>VkFlags</type> <name>VkImageCreateFlags</name>;</type>
+        <type category="handle" objtypeenum="VK_OBJECT_TYPE_INSTANCE"><type>VK_DEFINE_HANDLE</type>(<name>VkInstance</name>)</type>
+        <type category="handle" objtypeenum="VK_OBJECT_TYPE_DEVICE"><type>VK_DEFINE_HANDLE</type>(<name>VkDevice</name>)</type>
+        <type category="handle" objtypeenum="VK_OBJECT_TYPE_SWAPCHAIN_KHR"><type>VK_DEFINE_NON_DISPATCHABLE_HANDLE</type>(<name>VkSwapchainKHR</name>)</type>
+        <type name="VkStructureType" category="enum"/>
+        <type name="VkResult" category="enum"/>
+        <type name="VkImageCreateFlagBits" category="enum"/>
+        <type category="funcpointer">typedef void (VKAPI_PTR *<name>PFN_vkVoidFunction</name>)(void);</type>
+        <type category="funcpointer">typedef void* (VKAPI_PTR *<name>PFN_vkAllocationFunction</name>)(
+    <type>void</type>*                                       pUserData,
+    <type>size_t</type>                                      size);</type>
+        <type category="struct" name="VkApplicationInfo">
+            <member values="VK_STRUCTURE_TYPE_APPLICATION_INFO"><type>VkStructureType</type> <name>sType</name></member>
+            <member optional="true">const <type>void</type>*     <name>pNext</name></member>
+            <member>const <type>char</type>* const*      <name>ppEnabledLayerNames</name><comment>Ordered list</comment></member>
+            <member><type>char</type>                   <name>deviceName</name>[<enum>VK_MAX_PHYSICAL_DEVICE_NAME_SIZE</enum>]</member>
+            <member><type>float</type>                  <name>matrix</name>[3][4]</member>
+            <member><type>Window</type>                 <name>window</name></member>
+            <member><type>Display</type>*               <name>dpy</name></member>
+            <member><type>VkImageCreateFlags</type>     <name>type</name></member>
+        </type>
+        <type category="struct" name="VkApplicationInfo2KHR" alias="VkApplicationInfo"/>
+        <type category="struct" name="VkInstanceData">
+            <member><type>uint32_t</type> <name>instanceCustomIndex</name>:24</member>
+            <member><type>uint32_t</type> <name>mask</name>:8</member>
+        </type>
+        <type category="union" name="VkClearColorValue">
+            <member><type>float</type> <name>float32</name>[4]</member>
+            <member><type>uint32_t</type> <name>uint32</name>[4]</member>
+        </type>
+        <type category="struct" name="VkVideoProfile">
+            <member><type>StdVideoH264ProfileIdc</type> <name>profile</name></member>
+        </type>
+        <type category="struct" name="VkVideoProfileWrapper">
+            <member><type>VkVideoProfile</type> <name>profile</name></member>
+        </type>
+        <type category="struct" name="VkScStruct" api="vulkansc">
+            <member><type>uint32_t</type> <name>x</name></member>
+        </type>
+    </types>
+    <enums name="API Constants" type="constants">
+        <enum type="uint32_t" value="256" name="VK_MAX_PHYSICAL_DEVICE_NAME_SIZE"/>
+        <enum type="uint32_t" value="(~0U)" name="VK_REMAINING_MIP_LEVELS"/>
+        <enum type="uint64_t" value="(~0ULL)" name="VK_WHOLE_SIZE"/>
+        <enum type="float" value="1000.0F" name="VK_LOD_CLAMP_NONE"/>
+    </enums>
+    <enums name="VkStructureType" type="enum">
+        <enum value="0" name="VK_STRUCTURE_TYPE_APPLICATION_INFO"/>
+    </enums>
+    <enums name="VkResult" type="enum">
+        <enum value="0" name="VK_SUCCESS"/>
+        <enum value="-1" name="VK_ERROR_OUT_OF_HOST_MEMORY"/>
+    </enums>
+    <enums name="VkImageCreateFlagBits" type="bitmask">
+        <enum bitpos="0" name="VK_IMAGE_CREATE_SPARSE_BINDING_BIT"/>
+    </enums>
+    <commands>
+        <command>
+            <proto><type>PFN_vkVoidFunction</type> <name>vkGetInstanceProcAddr</name></proto>
+            <param optional="true"><type>VkInstance</type> <name>instance</name></param>
+            <param len="null-terminated">const <type>char</type>* <name>pName</name></param>
+        </command>
+        <command>
+            <proto><type>VkResult</type> <name>vkCreateInstance</name></proto>
+            <param>const <type>VkApplicationInfo</type>* <name>pInfo</name></param>
+            <param><type>VkInstance</type>* <name>pInstance</name></param>
+        </command>
+        <command>
+            <proto><type>void</type> <name>vkDestroyInstance</name></proto>
+            <param><type>VkInstance</type> <name>instance</name></param>
+        </command>
+        <command>
+            <proto><type>VkResult</type> <name>vkCreateSwapchainKHR</name></proto>
+            <param><type>VkDevice</type> <name>device</name></param>
+            <param><type>VkSwapchainKHR</type>* <name>pSwapchain</name></param>
+        </command>
+        <command name="vkCreateSwapchainEXT" alias="vkCreateSwapchainKHR"/>
+        <command>
+            <proto><type>void</type> <name>vkUseVideo</name></proto>
+            <param><type>VkDevice</type> <name>device</name></param>
+            <param><type>VkVideoProfile</type> <name>profile</name></param>
+        </command>
+    </commands>
+    <feature api="vulkan" name="VK_VERSION_1_0" number="1.0">
+        <require>
+            <command name="vkGetInstanceProcAddr"/>
+            <command name="vkCreateInstance"/>
+            <command name="vkDestroyInstance"/>
+            <command name="vkUseVideo"/>
+        </require>
+    </feature>
+    <extensions>
+        <extension name="VK_KHR_swapchain" number="2" type="device" supported="vulkan">
+            <require>
+                <enum value="70" name="VK_KHR_SWAPCHAIN_SPEC_VERSION"/>
+                <enum value="&quot;VK_KHR_swapchain&quot;" name="VK_KHR_SWAPCHAIN_EXTENSION_NAME"/>
+                <enum offset="0" extends="VkStructureType" name="VK_STRUCTURE_TYPE_SWAPCHAIN_CREATE_INFO_KHR"/>
+                <enum offset="4" extends="VkResult" dir="-" name="VK_ERROR_OUT_OF_DATE_KHR"/>
+                <enum bitpos="1" extends="VkImageCreateFlagBits" name="VK_IMAGE_CREATE_ALIAS_BIT"/>
+                <enum extends="VkImageCreateFlagBits" name="VK_IMAGE_CREATE_ALIAS_BIT_KHR" alias="VK_IMAGE_CREATE_ALIAS_BIT"/>
+                <command name="vkCreateSwapchainKHR"/>
+                <command name="vkCreateSwapchainEXT"/>
+            </require>
+        </extension>
+        <extension name="VK_NV_disabled" number="3" supported="disabled">
+            <require>
+                <enum offset="0" extends="VkStructureType" name="VK_STRUCTURE_TYPE_DISABLED_NV"/>
+                <enum extends="VkStructureType" name="VK_STRUCTURE_TYPE_DISABLED_ALIAS" alias="VK_STRUCTURE_TYPE_DISABLED_NV"/>
+            </require>
+        </extension>
+    </extensions>
+</registry>
+"#;
+
+    fn bindings() -> String {
+        let root = xml::parse(REGISTRY).unwrap();
+        generate(&Registry::load(&root).unwrap())
+    }
+
+    #[test]
+    fn constants() {
+        let out = bindings();
+        assert!(out.contains("pub const MAX_PHYSICAL_DEVICE_NAME_SIZE: u32 = 256;\n"));
+        assert!(out.contains("pub const REMAINING_MIP_LEVELS: u32 = 0xffffffff;\n"));
+        assert!(out.contains("pub const WHOLE_SIZE: u64 = 0xffffffffffffffff;\n"));
+        assert!(out.contains("pub const LOD_CLAMP_NONE: f32 = 1000.0;\n"));
+        assert!(out.contains("pub const KHR_SWAPCHAIN_SPEC_VERSION: u32 = 70;\n"));
+        assert!(out.contains("pub const KHR_SWAPCHAIN_EXTENSION_NAME: &str = \"VK_KHR_swapchain\";\n"));
+    }
+
+    #[test]
+    fn enums() {
+        let out = bindings();
+        assert!(out.contains("pub type StructureType = u32;\n\
+                              pub const STRUCTURE_TYPE_APPLICATION_INFO: u32 = 0;\n\
+                              pub const STRUCTURE_TYPE_SWAPCHAIN_CREATE_INFO_KHR: u32 = 1000001000;\n"));
+        assert!(out.contains("pub const ERROR_OUT_OF_HOST_MEMORY: u32 = -1i32 as u32;\n"));
+        assert!(out.contains("pub const ERROR_OUT_OF_DATE_KHR: u32 = -1000001004i32 as u32;\n"));
+        assert!(out.contains("pub const IMAGE_CREATE_SPARSE_BINDING_BIT: u32 = 0x00000001;\n"));
+        assert!(out.contains("pub const IMAGE_CREATE_ALIAS_BIT_KHR: u32 = IMAGE_CREATE_ALIAS_BIT;\n"));
+        assert!(!out.contains("DISABLED"));
+    }
+
+    #[test]
+    fn types() {
+        let out = bindings();
+        assert!(out.contains("pub type Flags = u32;\n"));
+        assert!(out.contains("pub type ANativeWindow = c_void;\n"));
+        assert!(out.contains("pub type ImageCreateFlags = Flags;\n"));
+        assert!(out.contains("pub type Instance = usize;\n"));
+        assert!(out.contains("pub type SwapchainKHR = u64;\n"));
+        assert!(out.contains("pub type PFN_vkVoidFunction = extern \"system\" fn();\n"));
+        assert!(out.contains("pub type PFN_vkAllocationFunction = extern \"system\" fn(*mut c_void, \
+                              usize) -> *mut c_void;\n"));
+        assert!(out.contains("pub type ApplicationInfo2KHR = ApplicationInfo;\n"));
+        assert!(!out.contains("ScStruct"));
+    }
+
+    #[test]
+    fn structs() {
+        let out = bindings();
+        assert!(out.contains("#[repr(C)]\n#[derive(Copy, Clone)]\npub struct ApplicationInfo {\n\
+                              \x20   pub sType: StructureType,\n\
+                              \x20   pub pNext: *const c_void,\n\
+                              \x20   pub ppEnabledLayerNames: *const *const c_char,\n\
+                              \x20   pub deviceName: [c_char; MAX_PHYSICAL_DEVICE_NAME_SIZE as usize],\n\
+                              \x20   pub matrix: [[f32; 4]; 3],\n\
+                              \x20   pub window: c_ulong,\n\
+                              \x20   pub dpy: *mut c_void,\n\
+                              \x20   pub ty: ImageCreateFlags,\n\
+                              }\n"));
+        assert!(out.contains("pub struct InstanceData {\n    pub instanceCustomIndex_and_mask: u32,\n}\n"));
+        assert!(out.contains("pub union ClearColorValue {\n"));
+    }
+
+    #[test]
+    fn unknown_types_are_skipped() {
+        let out = bindings();
+        assert!(!out.contains("VideoProfile"));
+        assert!(!out.contains("UseVideo"));
+    }
+
+    #[test]
+    fn tables() {
+        let out = bindings();
+        assert!(out.contains("ptrs!(Static, {\n    GetInstanceProcAddr => (instance: Instance, \
+                              pName: *const c_char) -> PFN_vkVoidFunction,\n});\n"));
+        assert!(out.contains("ptrs!(EntryPoints, {\n    CreateInstance => (pInfo: *const \
+                              ApplicationInfo, pInstance: *mut Instance) -> Result,\n});\n"));
+        assert!(out.contains("ptrs!(InstancePointers, {\n    DestroyInstance => (instance: \
+                              Instance) -> (),\n});\n"));
+        assert!(out.contains("ptrs!(DevicePointers, {\n    CreateSwapchainKHR => (device: Device, \
+                              pSwapchain: *mut SwapchainKHR) -> Result,\n    CreateSwapchainEXT => \
+                              (device: Device, pSwapchain: *mut SwapchainKHR) -> Result,\n});\n"));
+        assert!(out.contains("ptrs!(KhrSwapchainPointers, {\n"));
+    }
+
+    fn normalize(text: &str) -> String {
+        text.lines()
+            .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    // Generates the bindings of `VK_KHR_surface` and `VK_KHR_swapchain` from an excerpt of the
+    // registry and checks that the constants, types, structs and commands are the same as in
+    // `vk-sys`. The extension name and version constants aren't part of `vk-sys`.
+    #[test]
+    fn matches_vk_sys() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let source = fs::read_to_string(dir.join("tests/vk_subset.xml")).unwrap();
+        let out = normalize(&generate(&Registry::load(&xml::parse(&source).unwrap()).unwrap()));
+        let vk_sys = normalize(&fs::read_to_string(dir.join("../vk-sys/src/lib.rs")).unwrap());
+
+        let mut items = Vec::new();
+        let mut lines = out.lines();
+        while let Some(line) = lines.next() {
+            if line.starts_with("pub struct ") {
+                let fields = lines.by_ref().take_while(|&l| l != "}");
+                let block = Some(line).into_iter().chain(fields).collect::<Vec<_>>();
+                items.push(block.join("\n") + "\n}");
+            } else if (line.starts_with("pub const ") && !line.contains("_SPEC_VERSION:")
+                       && !line.contains("_EXTENSION_NAME:"))
+                      || line.starts_with("pub type ")
+                      || line.split(" => (").next().unwrap().chars().all(char::is_alphanumeric)
+            {
+                items.push(line.to_owned());
+            }
+        }
+
+        assert_eq!(items.len(), 91);
+        let missing = items.iter()
+            .filter(|item| !vk_sys.lines().any(|l| l == item.as_str()) &&
+                           !vk_sys.contains(&format!("\n{}\n", item)))
+            .collect::<Vec<_>>();
+        assert!(missing.is_empty(), "not in vk-sys: {:#?}", missing);
+    }
+}
@@ -0,0 +1,71 @@
+// Copyright (c) 2020 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Generates bindings in the style of `vk-sys` from the Vulkan registry.
+//!
+//! ```text
+//! cargo run -p vk-sys-gen -- path/to/vk.xml bindings.rs
+//! ```
+//!
+//! The first argument is the path to `vk.xml`, as found in the `xml` directory of the
+//! [Vulkan-Docs](https://github.com/KhronosGroup/Vulkan-Docs) repository. `vk.xml` isn't part of
+//! this repository. The second argument is the file to write. If it is omitted, the bindings are
+//! written to the standard output.
+//!
+//! The bindings contain all the core versions and all the extensions that are supported by
+//! Vulkan. The commands are grouped in the `Static`, `EntryPoints`, `InstancePointers` and
+//! `DevicePointers` tables like the hand-written bindings, and each extension also gets its own
+//! table, for example `KhrSwapchainPointers`.
+//!
+//! Structs that contain types of external headers by value (such as the video codec types) are
+//! skipped, as well as the commands that use them.
+//!
+//! `vk-sys/src/lib.rs` is still hand-written and isn't generated by this tool. Nothing checks the
+//! output for the full registry against it: the tests only run on excerpts of the registry.
+
+mod generator;
+mod registry;
+mod xml;
+
+use std::env;
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::process;
+
+use crate::registry::Registry;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.is_empty() || args.len() > 2 {
+        eprintln!("usage: vk-sys-gen <vk.xml> [output]");
+        process::exit(2);
+    }
+
+    if let Err(err) = run(&args[0], args.get(1)) {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+}
+
+fn run(input: &str, output: Option<&String>) -> Result<(), String> {
+    let source = fs::read_to_string(input).map_err(|err| format!("{}: {}", input, err))?;
+    let root = xml::parse(&source).map_err(|err| format!("{}: {}", input, err))?;
+    let registry = Registry::load(&root)?;
+    let bindings = generator::generate(&registry);
+
+    match output {
+        Some(path) => fs::write(path, bindings).map_err(|err| format!("{}: {}", path, err)),
+        None => {
+            io::stdout()
+                .write_all(bindings.as_bytes())
+                .map_err(|err| err.to_string())
+        },
+    }
+}
@@ -0,0 +1,632 @@
+// Copyright (c) 2020 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Model of the parts of `vk.xml` needed to generate the bindings.
+//!
+//! Everything whose `api` attribute doesn't include `vulkan` (for example Vulkan SC variants) is
+//! ignored while loading.
+
+use crate::xml::Element;
+use crate::xml::Node;
+
+/// Contents of the registry.
+#[derive(Debug, Default)]
+pub struct Registry {
+    /// Types, in the order of the registry.
+    pub types: Vec<TypeDef>,
+    /// Groups of constants, in the order of the registry.
+    pub enums: Vec<EnumGroup>,
+    pub commands: Vec<Command>,
+    /// Core versions.
+    pub features: Vec<Interface>,
+    pub extensions: Vec<Interface>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeDef {
+    /// Another name for an existing type.
+    Alias { name: String, alias: String },
+    /// A typedef of a C type, or an opaque type if `ty` is `None`.
+    Basetype { name: String, ty: Option<CType> },
+    /// A set of flags, whose underlying type is `flags` (`VkFlags` or `VkFlags64`).
+    Bitmask { name: String, flags: String },
+    /// An enumeration, whose values are in the `EnumGroup` of the same name.
+    Enum { name: String },
+    Handle { name: String, dispatchable: bool },
+    Struct { name: String, union: bool, members: Vec<Member> },
+    FuncPointer { name: String, ret: CType, params: Vec<Member> },
+    /// A type defined by a platform header, such as `Display` or `HWND`.
+    Platform { name: String },
+}
+
+impl TypeDef {
+    pub fn name(&self) -> &str {
+        match *self {
+            TypeDef::Alias { ref name, .. } |
+            TypeDef::Basetype { ref name, .. } |
+            TypeDef::Bitmask { ref name, .. } |
+            TypeDef::Enum { ref name } |
+            TypeDef::Handle { ref name, .. } |
+            TypeDef::Struct { ref name, .. } |
+            TypeDef::FuncPointer { ref name, .. } |
+            TypeDef::Platform { ref name } => name,
+        }
+    }
+}
+
+/// A C type as written in the registry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CType {
+    /// Name of the underlying type, for example `uint32_t` or `VkExtent2D`.
+    pub base: String,
+    /// Pointers applied to `base`, from the innermost to the outermost. `true` if the pointed
+    /// value is const.
+    pub pointers: Vec<bool>,
+    /// Array dimensions, from the outermost to the innermost. Either a number or the name of a
+    /// constant.
+    pub array: Vec<String>,
+    /// Width of the bitfield, if the member is a bitfield.
+    pub bitfield: Option<u32>,
+}
+
+impl CType {
+    /// Builds a type with no pointer and no array.
+    pub fn new(base: &str) -> CType {
+        CType {
+            base: base.to_owned(),
+            pointers: Vec::new(),
+            array: Vec::new(),
+            bitfield: None,
+        }
+    }
+}
+
+/// Struct member or function parameter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Member {
+    pub name: String,
+    pub ty: CType,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumGroup {
+    pub name: String,
+    /// True for the `API Constants` group, whose values have their own types.
+    pub constants: bool,
+    /// True if the values are 64 bits wide.
+    pub wide: bool,
+    pub values: Vec<EnumValue>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumValue {
+    pub name: String,
+    pub value: Value,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// Plain integer.
+    Int(i64),
+    /// A single bit, as a position.
+    Bit(u32),
+    /// A typed constant of the `API Constants` group, such as `(~0U)` or `1000.0F`.
+    Typed { ty: String, value: String },
+    Str(String),
+    /// Same value as another constant.
+    Alias(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Command {
+    pub name: String,
+    /// Name of the command that this one is an alias of. The signature is copied from it.
+    pub alias: Option<String>,
+    pub ret: CType,
+    pub params: Vec<Member>,
+}
+
+/// Core version or extension.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interface {
+    pub name: String,
+    /// Extension number, used to compute the values of the enums it adds.
+    pub number: Option<i64>,
+    /// `instance` or `device` for extensions.
+    pub kind: Option<String>,
+    /// True if the extension is not supported by Vulkan.
+    pub disabled: bool,
+    pub types: Vec<String>,
+    pub commands: Vec<String>,
+    pub enums: Vec<RequiredEnum>,
+}
+
+/// Enum required by a core version or extension.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequiredEnum {
+    pub name: String,
+    /// Group that the value is added to, or `None` for a standalone constant.
+    pub extends: Option<String>,
+    /// `None` if the enum is only a reference to a value defined elsewhere.
+    pub value: Option<Value>,
+}
+
+impl Registry {
+    /// Loads the registry from the root element of `vk.xml`.
+    pub fn load(root: &Element) -> Result<Registry, String> {
+        let mut registry = Registry::default();
+
+        for block in root.elements() {
+            match block.name.as_str() {
+                "types" => {
+                    for ty in block.elements_named("type").filter(|e| for_vulkan(e)) {
+                        if let Some(ty) = load_type(ty)? {
+                            registry.types.push(ty);
+                        }
+                    }
+                },
+                "enums" if for_vulkan(block) => registry.enums.push(load_enums(block)?),
+                "commands" => {
+                    for cmd in block.elements_named("command").filter(|e| for_vulkan(e)) {
+                        registry.commands.push(load_command(cmd)?);
+                    }
+                },
+                "feature" if for_vulkan(block) => registry.features.push(load_interface(block)?),
+                "extensions" => {
+                    for ext in block.elements_named("extension") {
+                        registry.extensions.push(load_interface(ext)?);
+                    }
+                },
+                _ => (),
+            }
+        }
+
+        resolve_command_aliases(&mut registry.commands)?;
+        Ok(registry)
+    }
+
+    /// Returns the group of constants with the given name.
+    pub fn enum_group(&self, name: &str) -> Option<&EnumGroup> {
+        self.enums.iter().find(|g| g.name == name)
+    }
+}
+
+// Returns true if the element is part of the Vulkan API, as opposed to Vulkan SC.
+fn for_vulkan(e: &Element) -> bool {
+    match e.attribute("api") {
+        Some(api) => api.split(',').any(|a| a == "vulkan"),
+        None => true,
+    }
+}
+
+// Token of a C declaration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    // Content of a `<type>` element.
+    Type(String),
+    // Content of a `<name>` element.
+    Name(String),
+    // Identifier or number in the text, or content of an `<enum>` element.
+    Ident(String),
+    Punct(char),
+}
+
+// Turns the content of an element into tokens. `<comment>` elements are ignored.
+fn tokenize(e: &Element, out: &mut Vec<Token>) {
+    for child in &e.children {
+        match *child {
+            Node::Element(ref c) => match c.name.as_str() {
+                "type" => out.push(Token::Type(c.text().trim().to_owned())),
+                "name" => out.push(Token::Name(c.text().trim().to_owned())),
+                "enum" => out.push(Token::Ident(c.text().trim().to_owned())),
+                "comment" => (),
+                _ => tokenize(c, out),
+            },
+            Node::Text(ref t) => tokenize_text(t, out),
+        }
+    }
+}
+
+fn tokenize_text(text: &str, out: &mut Vec<Token>) {
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if c.is_alphanumeric() || c == '_' {
+            let mut end = start + c.len_utf8();
+            while let Some(&(n, c)) = chars.peek() {
+                if !(c.is_alphanumeric() || c == '_') {
+                    break;
+                }
+                end = n + c.len_utf8();
+                chars.next();
+            }
+            out.push(Token::Ident(text[start .. end].to_owned()));
+        } else if !c.is_whitespace() {
+            out.push(Token::Punct(c));
+        }
+    }
+}
+
+// Parses a single declaration such as `const char* const* ppNames` or `float matrix[3][4]`.
+fn parse_declaration(tokens: &[Token]) -> Result<Member, String> {
+    let mut base = None;
+    let mut name = None;
+    let mut pointers = Vec::new();
+    let mut array = Vec::new();
+    let mut bitfield = None;
+    let mut is_const = false;
+
+    let mut iter = tokens.iter().peekable();
+    while let Some(token) = iter.next() {
+        match *token {
+            Token::Type(ref t) if base.is_none() => base = Some(t.clone()),
+            Token::Name(ref n) => name = Some(n.clone()),
+            Token::Ident(ref i) if i == "const" => is_const = true,
+            Token::Ident(ref i) if i == "struct" || i == "typedef" => (),
+            Token::Ident(ref i) if base.is_none() => base = Some(i.clone()),
+            Token::Ident(ref i) if name.is_none() => name = Some(i.clone()),
+            Token::Punct('*') => {
+                pointers.push(is_const);
+                is_const = false;
+            },
+            Token::Punct('[') => {
+                match iter.next() {
+                    Some(Token::Ident(size)) => array.push(size.clone()),
+                    _ => return Err("invalid array size".to_owned()),
+                }
+                if iter.next() != Some(&Token::Punct(']')) {
+                    return Err("invalid array size".to_owned());
+                }
+            },
+            Token::Punct(':') => {
+                match iter.next() {
+                    Some(Token::Ident(width)) => {
+                        bitfield = Some(width.parse().map_err(|_| "invalid bitfield")?);
+                    },
+                    _ => return Err("invalid bitfield".to_owned()),
+                }
+            },
+            _ => (),
+        }
+    }
+
+    match (base, name) {
+        (Some(base), Some(name)) => {
+            Ok(Member {
+                name,
+                ty: CType { base, pointers, array, bitfield },
+            })
+        },
+        _ => Err(format!("invalid declaration: {:?}", tokens)),
+    }
+}
+
+fn load_member(e: &Element) -> Result<Member, String> {
+    let mut tokens = Vec::new();
+    tokenize(e, &mut tokens);
+    parse_declaration(&tokens)
+}
+
+fn load_type(e: &Element) -> Result<Option<TypeDef>, String> {
+    let category = e.attribute("category");
+
+    if let Some(alias) = e.attribute("alias") {
+        let name = e.attribute("name").ok_or("alias without a name")?;
+        return Ok(Some(TypeDef::Alias { name: name.to_owned(), alias: alias.to_owned() }));
+    }
+
+    let mut tokens = Vec::new();
+    tokenize(e, &mut tokens);
+    let name = || -> Result<String, String> {
+        if let Some(name) = e.attribute("name") {
+            return Ok(name.to_owned());
+        }
+        tokens
+            .iter()
+            .filter_map(|t| match *t {
+                Token::Name(ref n) => Some(n.clone()),
+                _ => None,
+            })
+            .next()
+            .ok_or_else(|| format!("type without a name: {:?}", e))
+    };
+    let first_type = || {
+        tokens.iter().filter_map(|t| match *t {
+            Token::Type(ref t) => Some(t.clone()),
+            _ => None,
+        }).next()
+    };
+
+    let ty = match category {
+        Some("basetype") => {
+            let name = name()?;
+            let text = e.text();
+            let ty = if let Some(base) = first_type() {
+                Some(load_member(e).map(|m| m.ty).unwrap_or_else(|_| CType::new(&base)))
+            } else if text.contains("void*") {
+                Some(CType { pointers: vec![false], ..CType::new("void") })
+            } else {
+                None
+            };
+            TypeDef::Basetype { name, ty }
+        },
+        Some("bitmask") => {
+            let flags = first_type().ok_or("bitmask without a type")?;
+            TypeDef::Bitmask { name: name()?, flags }
+        },
+        Some("enum") => TypeDef::Enum { name: name()? },
+        Some("handle") => {
+            let dispatchable = first_type().as_deref() == Some("VK_DEFINE_HANDLE");
+            TypeDef::Handle { name: name()?, dispatchable }
+        },
+        Some("struct") | Some("union") => {
+            let members = e
+                .elements_named("member")
+                .filter(|m| for_vulkan(m))
+                .map(load_member)
+                .collect::<Result<Vec<_>, _>>()?;
+            TypeDef::Struct { name: name()?, union: category == Some("union"), members }
+        },
+        Some("funcpointer") => {
+            let name = name()?;
+            let (ret, params) = parse_funcpointer(&tokens)?;
+            TypeDef::FuncPointer { name, ret, params }
+        },
+        None => {
+            match (e.attribute("name"), e.attribute("requires")) {
+                (Some(name), Some(requires)) if requires.ends_with(".h") => {
+                    TypeDef::Platform { name: name.to_owned() }
+                },
+                _ => return Ok(None),
+            }
+        },
+        _ => return Ok(None),
+    };
+
+    Ok(Some(ty))
+}
+
+// Parses `typedef RET (VKAPI_PTR *NAME)(PARAMS);`.
+fn parse_funcpointer(tokens: &[Token]) -> Result<(CType, Vec<Member>), String> {
+    let open = tokens
+        .iter()
+        .position(|t| *t == Token::Punct('('))
+        .ok_or("invalid function pointer")?;
+    // The return type has no name, so give it an empty one.
+    let mut ret_tokens = tokens[.. open].to_vec();
+    ret_tokens.push(Token::Name(String::new()));
+    let ret = parse_declaration(&ret_tokens)?.ty;
+
+    let params_start = tokens[open ..]
+        .iter()
+        .position(|t| *t == Token::Punct(')'))
+        .map(|n| open + n + 2)
+        .ok_or("invalid function pointer")?;
+    let params_end = tokens
+        .iter()
+        .rposition(|t| *t == Token::Punct(')'))
+        .ok_or("invalid function pointer")?;
+
+    let mut params = Vec::new();
+    if params_start < params_end {
+        let list = &tokens[params_start .. params_end];
+        // `(void)` means no parameters.
+        if list != [Token::Type("void".to_owned())] && list != [Token::Ident("void".to_owned())] {
+            for param in list.split(|t| *t == Token::Punct(',')) {
+                params.push(parse_declaration(param)?);
+            }
+        }
+    }
+
+    Ok((ret, params))
+}
+
+fn load_value(e: &Element, in_enum: bool) -> Result<Option<Value>, String> {
+    if let Some(alias) = e.attribute("alias") {
+        return Ok(Some(Value::Alias(alias.to_owned())));
+    }
+    if let Some(bit) = e.attribute("bitpos") {
+        return Ok(Some(Value::Bit(bit.parse().map_err(|_| format!("bad bitpos {}", bit))?)));
+    }
+    let value = match e.attribute("value") {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+
+    if value.starts_with('"') {
+        return Ok(Some(Value::Str(value.trim_matches('"').to_owned())));
+    }
+    // Old registries refer to other constants through `value` instead of `alias`.
+    if value.starts_with("VK_") {
+        return Ok(Some(Value::Alias(value.to_owned())));
+    }
+    if let Some(ty) = e.attribute("type") {
+        return Ok(Some(Value::Typed { ty: ty.to_owned(), value: value.to_owned() }));
+    }
+    match parse_int(value) {
+        Some(v) => Ok(Some(Value::Int(v))),
+        None if !in_enum => {
+            // Constants of old registries have no `type` attribute.
+            let ty = if value.contains("ULL") {
+                "uint64_t"
+            } else if value.contains('F') || value.contains('.') {
+                "float"
+            } else {
+                "uint32_t"
+            };
+            Ok(Some(Value::Typed { ty: ty.to_owned(), value: value.to_owned() }))
+        },
+        None => Err(format!("bad value {}", value)),
+    }
+}
+
+fn parse_int(value: &str) -> Option<i64> {
+    match value.strip_prefix("0x") {
+        Some(hex) => i64::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+fn load_enums(e: &Element) -> Result<EnumGroup, String> {
+    let name = e.attribute("name").ok_or("enums without a name")?.to_owned();
+    let constants = e.attribute("type").is_none() || e.attribute("type") == Some("constants");
+    let wide = e.attribute("bitwidth") == Some("64");
+
+    let mut values = Vec::new();
+    for value in e.elements_named("enum").filter(|e| for_vulkan(e)) {
+        let name = value.attribute("name").ok_or("enum without a name")?.to_owned();
+        if let Some(v) = load_value(value, !constants)? {
+            values.push(EnumValue { name, value: v });
+        }
+    }
+
+    Ok(EnumGroup { name, constants, wide, values })
+}
+
+fn load_command(e: &Element) -> Result<Command, String> {
+    if let Some(alias) = e.attribute("alias") {
+        // The signature is filled by `resolve_command_aliases`.
+        let name = e.attribute("name").ok_or("command alias without a name")?.to_owned();
+        return Ok(Command {
+            name,
+            alias: Some(alias.to_owned()),
+            ret: CType::new("void"),
+            params: Vec::new(),
+        });
+    }
+
+    let proto = load_member(e.element("proto").ok_or("command without a proto")?)?;
+    let params = e
+        .elements_named("param")
+        .filter(|p| for_vulkan(p))
+        .map(load_member)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Command { name: proto.name, alias: None, ret: proto.ty, params })
+}
+
+// Copies the signature of the aliased commands into the aliases.
+fn resolve_command_aliases(commands: &mut [Command]) -> Result<(), String> {
+    for n in 0 .. commands.len() {
+        let mut target = match commands[n].alias {
+            Some(ref alias) => alias.clone(),
+            None => continue,
+        };
+
+        // Aliases can be chained.
+        let (ret, params) = loop {
+            let cmd = commands
+                .iter()
+                .find(|c| c.name == target)
+                .ok_or_else(|| format!("unknown command alias target {}", target))?;
+            match cmd.alias {
+                Some(ref next) => target = next.clone(),
+                None => break (cmd.ret.clone(), cmd.params.clone()),
+            }
+        };
+
+        commands[n].ret = ret;
+        commands[n].params = params;
+    }
+
+    Ok(())
+}
+
+fn load_interface(e: &Element) -> Result<Interface, String> {
+    let name = e.attribute("name").ok_or("feature or extension without a name")?.to_owned();
+    let disabled = match e.attribute("supported") {
+        Some(s) => !s.split(',').any(|a| a == "vulkan"),
+        None => false,
+    };
+    let number = e.attribute("number").and_then(parse_int);
+
+    let mut interface = Interface {
+        name,
+        number,
+        kind: e.attribute("type").map(|t| t.to_owned()),
+        disabled,
+        types: Vec::new(),
+        commands: Vec::new(),
+        enums: Vec::new(),
+    };
+
+    for require in e.elements_named("require").filter(|r| for_vulkan(r)) {
+        for item in require.elements().filter(|i| for_vulkan(i)) {
+            let item_name = match item.attribute("name") {
+                Some(n) => n.to_owned(),
+                None => continue,
+            };
+            match item.name.as_str() {
+                "type" => interface.types.push(item_name),
+                "command" => interface.commands.push(item_name),
+                "enum" => {
+                    let value = if let Some(offset) = item.attribute("offset") {
+                        let offset = parse_int(offset).ok_or("bad offset")?;
+                        let ext = item
+                            .attribute("extnumber")
+                            .and_then(parse_int)
+                            .or(number)
+                            .ok_or("offset without an extension number")?;
+                        let v = 1_000_000_000 + (ext - 1) * 1000 + offset;
+                        Some(Value::Int(if item.attribute("dir") == Some("-") { -v } else { v }))
+                    } else {
+                        load_value(item, item.attribute("extends").is_some())?
+                    };
+                    interface.enums.push(RequiredEnum {
+                        name: item_name,
+                        extends: item.attribute("extends").map(|e| e.to_owned()),
+                        value,
+                    });
+                },
+                _ => (),
+            }
+        }
+    }
+
+    Ok(interface)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_declaration;
+    use super::tokenize;
+    use super::CType;
+    use crate::xml;
+
+    fn decl(src: &str) -> CType {
+        let e = xml::parse(src).unwrap();
+        let mut tokens = Vec::new();
+        tokenize(&e, &mut tokens);
+        parse_declaration(&tokens).unwrap().ty
+    }
+
+    #[test]
+    fn pointers() {
+        let ty = decl("<m>const <type>char</type>* const* <name>ppNames</name></m>");
+        assert_eq!(ty.base, "char");
+        assert_eq!(ty.pointers, vec![true, true]);
+
+        let ty = decl("<m><type>void</type>** <name>ppData</name></m>");
+        assert_eq!(ty.pointers, vec![false, false]);
+    }
+
+    #[test]
+    fn arrays() {
+        let ty = decl("<m><type>char</type> <name>n</name>[<enum>VK_UUID_SIZE</enum>]</m>");
+        assert_eq!(ty.array, vec!["VK_UUID_SIZE".to_owned()]);
+
+        let ty = decl("<m><type>float</type> <name>matrix</name>[3][4]</m>");
+        assert_eq!(ty.array, vec!["3".to_owned(), "4".to_owned()]);
+    }
+
+    #[test]
+    fn bitfield() {
+        let ty = decl("<m><type>uint32_t</type> <name>mask</name>:8</m>");
+        assert_eq!(ty.bitfield, Some(8));
+    }
+}
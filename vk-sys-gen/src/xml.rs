@@ -0,0 +1,301 @@
+// Copyright (c) 2020 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Minimal XML reader.
+//!
+//! Supports what the Vulkan registry uses: elements, attributes, text, comments, processing
+//! instructions and the predefined and numeric character entities. DTDs and CDATA sections are
+//! not supported.
+
+use std::error;
+use std::fmt;
+
+/// An XML element.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Element {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<Node>,
+}
+
+/// A child of an element.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Node {
+    Element(Element),
+    Text(String),
+}
+
+impl Element {
+    /// Returns the value of the attribute with the given name.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Returns an iterator to the child elements.
+    pub fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|node| match *node {
+            Node::Element(ref e) => Some(e),
+            Node::Text(_) => None,
+        })
+    }
+
+    /// Returns an iterator to the child elements with the given name.
+    pub fn elements_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.elements().filter(move |e| e.name == name)
+    }
+
+    /// Returns the first child element with the given name.
+    pub fn element(&self, name: &str) -> Option<&Element> {
+        self.elements().find(|e| e.name == name)
+    }
+
+    /// Returns the concatenated text of this element and of all its descendants.
+    pub fn text(&self) -> String {
+        let mut out = String::new();
+        self.append_text(&mut out);
+        out
+    }
+
+    fn append_text(&self, out: &mut String) {
+        for child in &self.children {
+            match *child {
+                Node::Element(ref e) => e.append_text(out),
+                Node::Text(ref t) => out.push_str(t),
+            }
+        }
+    }
+}
+
+/// Error that can happen when parsing a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Line where the error happened, starting at 1.
+    pub line: usize,
+    pub message: &'static str,
+}
+
+impl error::Error for ParseError {}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// Parses a document and returns its root element.
+pub fn parse(input: &str) -> Result<Element, ParseError> {
+    let mut parser = Parser { input, pos: 0 };
+    parser.skip_misc()?;
+    let root = parser.element()?;
+    parser.skip_misc()?;
+    if parser.pos != input.len() {
+        return Err(parser.error("unexpected content after the root element"));
+    }
+    Ok(root)
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos ..]
+    }
+
+    fn error(&self, message: &'static str) -> ParseError {
+        let line = self.input[.. self.pos].matches('\n').count() + 1;
+        ParseError { line, message }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    // Skips everything up to and including `end`.
+    fn skip_past(&mut self, end: &str) -> Result<(), ParseError> {
+        match self.rest().find(end) {
+            Some(n) => {
+                self.pos += n + end.len();
+                Ok(())
+            },
+            None => Err(self.error("unterminated comment or processing instruction")),
+        }
+    }
+
+    // Skips whitespace, comments and processing instructions.
+    fn skip_misc(&mut self) -> Result<(), ParseError> {
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if self.rest().starts_with("<?") {
+                self.skip_past("?>")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn name(&mut self) -> Result<&'a str, ParseError> {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| c.is_whitespace() || c == '=' || c == '>' || c == '/')
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error("expected a name"));
+        }
+        self.pos += len;
+        Ok(&rest[.. len])
+    }
+
+    fn expect(&mut self, s: &str) -> Result<(), ParseError> {
+        if self.rest().starts_with(s) {
+            self.pos += s.len();
+            Ok(())
+        } else {
+            Err(self.error("unexpected character"))
+        }
+    }
+
+    fn element(&mut self) -> Result<Element, ParseError> {
+        self.expect("<")?;
+        let name = self.name()?.to_owned();
+        let mut attributes = Vec::new();
+
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("/>") {
+                self.pos += 2;
+                return Ok(Element { name, attributes, children: Vec::new() });
+            }
+            if self.rest().starts_with('>') {
+                self.pos += 1;
+                break;
+            }
+
+            let attr_name = self.name()?.to_owned();
+            self.skip_whitespace();
+            self.expect("=")?;
+            self.skip_whitespace();
+            let quote = match self.rest().chars().next() {
+                Some(q) if q == '"' || q == '\'' => q,
+                _ => return Err(self.error("expected a quoted attribute value")),
+            };
+            self.pos += 1;
+            let len = match self.rest().find(quote) {
+                Some(len) => len,
+                None => return Err(self.error("unterminated attribute value")),
+            };
+            let value = unescape(&self.rest()[.. len]).ok_or_else(|| self.error("bad entity"))?;
+            self.pos += len + 1;
+            attributes.push((attr_name, value));
+        }
+
+        let mut children = Vec::new();
+        loop {
+            let rest = self.rest();
+            if rest.starts_with("</") {
+                self.pos += 2;
+                if self.name()? != name {
+                    return Err(self.error("mismatched closing tag"));
+                }
+                self.skip_whitespace();
+                self.expect(">")?;
+                return Ok(Element { name, attributes, children });
+            } else if rest.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if rest.starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if rest.starts_with('<') {
+                children.push(Node::Element(self.element()?));
+            } else if rest.is_empty() {
+                return Err(self.error("unexpected end of document"));
+            } else {
+                let len = rest.find('<').unwrap_or(rest.len());
+                let text = unescape(&rest[.. len]).ok_or_else(|| self.error("bad entity"))?;
+                self.pos += len;
+                // Merge with the previous text, in case a comment was in between.
+                if let Some(&mut Node::Text(ref mut prev)) = children.last_mut() {
+                    prev.push_str(&text);
+                    continue;
+                }
+                children.push(Node::Text(text));
+            }
+        }
+    }
+}
+
+// Replaces the entities in `s`. Returns `None` if an entity is invalid.
+fn unescape(s: &str) -> Option<String> {
+    if !s.contains('&') {
+        return Some(s.to_owned());
+    }
+
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[.. start]);
+        let end = rest[start ..].find(';')? + start;
+        let entity = &rest[start + 1 .. end];
+        let c = match entity {
+            "lt" => '<',
+            "gt" => '>',
+            "amp" => '&',
+            "quot" => '"',
+            "apos" => '\'',
+            _ if entity.starts_with("#x") => {
+                std::char::from_u32(u32::from_str_radix(&entity[2 ..], 16).ok()?)?
+            },
+            _ if entity.starts_with('#') => {
+                std::char::from_u32(entity[1 ..].parse().ok()?)?
+            },
+            _ => return None,
+        };
+        out.push(c);
+        rest = &rest[end + 1 ..];
+    }
+    out.push_str(rest);
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+    use super::Node;
+
+    #[test]
+    fn basic() {
+        let doc = parse("<?xml version=\"1.0\"?>\n<!-- c --><a x='1' y=\"&lt;2&gt;\"><b/>t&amp;u<c>v</c></a>")
+            .unwrap();
+        assert_eq!(doc.name, "a");
+        assert_eq!(doc.attribute("x"), Some("1"));
+        assert_eq!(doc.attribute("y"), Some("<2>"));
+        assert_eq!(doc.elements().count(), 2);
+        assert_eq!(doc.children[1], Node::Text("t&u".to_owned()));
+        assert_eq!(doc.text(), "t&uv");
+    }
+
+    #[test]
+    fn comment_inside_text() {
+        let doc = parse("<a>x<!-- c -->y</a>").unwrap();
+        assert_eq!(doc.children, vec![Node::Text("xy".to_owned())]);
+    }
+
+    #[test]
+    fn mismatched_tag() {
+        let err = parse("<a>\n<b></c></a>").unwrap_err();
+        assert_eq!(err.line, 2);
+    }
+}
@@ -20,6 +20,7 @@ use std::os::raw::c_char;
 use std::os::raw::c_void;
 use std::os::raw::c_ulong;
 use std::os::raw::c_double;
+use std::os::raw::c_int;
 
 pub type Flags = u32;
 pub type Bool32 = u32;
@@ -69,6 +70,9 @@ pub const QUEUE_FAMILY_IGNORED: u32 = 0xffffffff;
 pub const SUBPASS_EXTERNAL: u32 = 0xffffffff;
 pub const MAX_PHYSICAL_DEVICE_NAME_SIZE: u32 = 256;
 pub const UUID_SIZE: u32 = 16;
+pub const LUID_SIZE_KHR: u32 = 8;
+pub const MAX_DRIVER_NAME_SIZE_KHR: u32 = 256;
+pub const MAX_DRIVER_INFO_SIZE_KHR: u32 = 256;
 pub const MAX_MEMORY_TYPES: u32 = 32;
 pub const MAX_MEMORY_HEAPS: u32 = 16;
 pub const MAX_EXTENSION_NAME_SIZE: u32 = 256;
@@ -104,6 +108,7 @@ pub const ERROR_INCOMPATIBLE_DISPLAY_KHR: u32 = -1000003001i32 as u32;
 pub const ERROR_VALIDATION_FAILED_EXT: u32 = -1000011001i32 as u32;
 pub const ERROR_INVALID_SHADER_NV: u32 = -1000012000i32 as u32;
 pub const ERROR_OUT_OF_POOL_MEMORY_KHR: u32 = -1000069000i32 as u32;
+pub const ERROR_INVALID_EXTERNAL_HANDLE_KHR: u32 = -1000072003i32 as u32;
 
 pub type StructureType = u32;
 pub const STRUCTURE_TYPE_APPLICATION_INFO: u32 = 0;
@@ -182,10 +187,31 @@ pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_MEMORY_PROPERTIES_2_KHR: u32 = 10000590
 pub const STRUCTURE_TYPE_SPARSE_IMAGE_FORMAT_PROPERTIES_2_KHR: u32 = 1000059007;
 pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_SPARSE_IMAGE_FORMAT_INFO_2_KHR: u32 = 1000059008;
 pub const STRUCTURE_TYPE_VI_SURFACE_CREATE_INFO_NN: u32 = 1000062000;
+pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_EXTERNAL_IMAGE_FORMAT_INFO_KHR: u32 = 1000071000;
+pub const STRUCTURE_TYPE_EXTERNAL_IMAGE_FORMAT_PROPERTIES_KHR: u32 = 1000071001;
+pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_EXTERNAL_BUFFER_INFO_KHR: u32 = 1000071002;
+pub const STRUCTURE_TYPE_EXTERNAL_BUFFER_PROPERTIES_KHR: u32 = 1000071003;
+pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_ID_PROPERTIES_KHR: u32 = 1000071004;
+pub const STRUCTURE_TYPE_EXTERNAL_MEMORY_BUFFER_CREATE_INFO_KHR: u32 = 1000072000;
+pub const STRUCTURE_TYPE_EXTERNAL_MEMORY_IMAGE_CREATE_INFO_KHR: u32 = 1000072001;
+pub const STRUCTURE_TYPE_EXPORT_MEMORY_ALLOCATE_INFO_KHR: u32 = 1000072002;
+pub const STRUCTURE_TYPE_IMPORT_MEMORY_FD_INFO_KHR: u32 = 1000074000;
+pub const STRUCTURE_TYPE_MEMORY_FD_PROPERTIES_KHR: u32 = 1000074001;
+pub const STRUCTURE_TYPE_MEMORY_GET_FD_INFO_KHR: u32 = 1000074002;
+pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_EXTERNAL_SEMAPHORE_INFO_KHR: u32 = 1000076000;
+pub const STRUCTURE_TYPE_EXTERNAL_SEMAPHORE_PROPERTIES_KHR: u32 = 1000076001;
+pub const STRUCTURE_TYPE_EXPORT_SEMAPHORE_CREATE_INFO_KHR: u32 = 1000077000;
+pub const STRUCTURE_TYPE_IMPORT_SEMAPHORE_FD_INFO_KHR: u32 = 1000079000;
+pub const STRUCTURE_TYPE_SEMAPHORE_GET_FD_INFO_KHR: u32 = 1000079001;
 pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_PUSH_DESCRIPTOR_PROPERTIES_KHR: u32 = 1000080000;
 pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_16BIT_STORAGE_FEATURES_KHR: u32 = 1000083000;
 pub const STRUCTURE_TYPE_PRESENT_REGIONS_KHR: u32 = 1000084000;
 pub const STRUCTURE_TYPE_DESCRIPTOR_UPDATE_TEMPLATE_CREATE_INFO_KHR: u32 = 1000085000;
+pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_EXTERNAL_FENCE_INFO_KHR: u32 = 1000112000;
+pub const STRUCTURE_TYPE_EXTERNAL_FENCE_PROPERTIES_KHR: u32 = 1000112001;
+pub const STRUCTURE_TYPE_EXPORT_FENCE_CREATE_INFO_KHR: u32 = 1000113000;
+pub const STRUCTURE_TYPE_IMPORT_FENCE_FD_INFO_KHR: u32 = 1000115000;
+pub const STRUCTURE_TYPE_FENCE_GET_FD_INFO_KHR: u32 = 1000115001;
 pub const STRUCTURE_TYPE_MEMORY_DEDICATED_REQUIREMENTS_KHR: u32 = 1000127000;
 pub const STRUCTURE_TYPE_MEMORY_DEDICATED_ALLOCATE_INFO_KHR: u32 = 1000127001;
 pub const STRUCTURE_TYPE_BUFFER_MEMORY_REQUIREMENTS_INFO_2_KHR: u32 = 1000146000;
@@ -193,6 +219,21 @@ pub const STRUCTURE_TYPE_IMAGE_MEMORY_REQUIREMENTS_INFO_2_KHR: u32 = 1000146001;
 pub const STRUCTURE_TYPE_IMAGE_SPARSE_MEMORY_REQUIREMENTS_INFO_2_KHR: u32 = 1000146002;
 pub const STRUCTURE_TYPE_MEMORY_REQUIREMENTS_2_KHR: u32 = 1000146003;
 pub const STRUCTURE_TYPE_SPARSE_IMAGE_MEMORY_REQUIREMENTS_2_KHR: u32 = 1000146004;
+pub const STRUCTURE_TYPE_DESCRIPTOR_SET_LAYOUT_BINDING_FLAGS_CREATE_INFO_EXT: u32 = 1000161000;
+pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_DESCRIPTOR_INDEXING_FEATURES_EXT: u32 = 1000161001;
+pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_DESCRIPTOR_INDEXING_PROPERTIES_EXT: u32 = 1000161002;
+pub const STRUCTURE_TYPE_DESCRIPTOR_SET_VARIABLE_DESCRIPTOR_COUNT_ALLOCATE_INFO_EXT: u32 = 1000161003;
+pub const STRUCTURE_TYPE_DESCRIPTOR_SET_VARIABLE_DESCRIPTOR_COUNT_LAYOUT_SUPPORT_EXT: u32 = 1000161004;
+pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_MAINTENANCE_3_PROPERTIES_KHR: u32 = 1000168000;
+pub const STRUCTURE_TYPE_DESCRIPTOR_SET_LAYOUT_SUPPORT_KHR: u32 = 1000168001;
+pub const STRUCTURE_TYPE_BIND_BUFFER_MEMORY_INFO_KHR: u32 = 1000157000;
+pub const STRUCTURE_TYPE_BIND_IMAGE_MEMORY_INFO_KHR: u32 = 1000157001;
+pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_MULTIVIEW_FEATURES_KHR: u32 = 1000053001;
+pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_SUBGROUP_PROPERTIES: u32 = 1000094000;
+pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_DRIVER_PROPERTIES_KHR: u32 = 1000196000;
+pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_TIMELINE_SEMAPHORE_FEATURES_KHR: u32 = 1000207000;
+pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_SCALAR_BLOCK_LAYOUT_FEATURES_EXT: u32 = 1000221000;
+pub const STRUCTURE_TYPE_HEADLESS_SURFACE_CREATE_INFO_EXT: u32 = 1000256000;
 
 pub type SystemAllocationScope = u32;
 pub const SYSTEM_ALLOCATION_SCOPE_COMMAND: u32 = 0;
@@ -835,6 +876,7 @@ pub type DescriptorSetLayoutCreateFlags = Flags;
 
 pub type DescriptorPoolCreateFlagBits = u32;
 pub const DESCRIPTOR_POOL_CREATE_FREE_DESCRIPTOR_SET_BIT: u32 = 0x00000001;
+pub const DESCRIPTOR_POOL_CREATE_UPDATE_AFTER_BIND_BIT_EXT: u32 = 0x00000002;
 pub type DescriptorPoolCreateFlags = Flags;
 pub type DescriptorPoolResetFlags = Flags;
 pub type FramebufferCreateFlags = Flags;
@@ -1030,6 +1072,39 @@ pub type IOSSurfaceCreateFlagsMVK = u32;
 
 pub type DescriptorSetLayoutCreateFlagBits = u32;
 pub const DESCRIPTOR_SET_LAYOUT_CREATE_PUSH_DESCRIPTOR_BIT_KHR: u32 = 0x00000001;
+pub const DESCRIPTOR_SET_LAYOUT_CREATE_UPDATE_AFTER_BIND_POOL_BIT_EXT: u32 = 0x00000002;
+
+pub type DescriptorBindingFlagBitsEXT = u32;
+pub const DESCRIPTOR_BINDING_UPDATE_AFTER_BIND_BIT_EXT: u32 = 0x00000001;
+pub const DESCRIPTOR_BINDING_UPDATE_UNUSED_WHILE_PENDING_BIT_EXT: u32 = 0x00000002;
+pub const DESCRIPTOR_BINDING_PARTIALLY_BOUND_BIT_EXT: u32 = 0x00000004;
+pub const DESCRIPTOR_BINDING_VARIABLE_DESCRIPTOR_COUNT_BIT_EXT: u32 = 0x00000008;
+pub type DescriptorBindingFlagsEXT = Flags;
+
+pub type SubgroupFeatureFlagBits = u32;
+pub const SUBGROUP_FEATURE_BASIC_BIT: u32 = 0x00000001;
+pub const SUBGROUP_FEATURE_VOTE_BIT: u32 = 0x00000002;
+pub const SUBGROUP_FEATURE_ARITHMETIC_BIT: u32 = 0x00000004;
+pub const SUBGROUP_FEATURE_BALLOT_BIT: u32 = 0x00000008;
+pub const SUBGROUP_FEATURE_SHUFFLE_BIT: u32 = 0x00000010;
+pub const SUBGROUP_FEATURE_SHUFFLE_RELATIVE_BIT: u32 = 0x00000020;
+pub const SUBGROUP_FEATURE_CLUSTERED_BIT: u32 = 0x00000040;
+pub const SUBGROUP_FEATURE_QUAD_BIT: u32 = 0x00000080;
+pub type SubgroupFeatureFlags = Flags;
+
+pub type DriverIdKHR = u32;
+pub const DRIVER_ID_AMD_PROPRIETARY_KHR: u32 = 1;
+pub const DRIVER_ID_AMD_OPEN_SOURCE_KHR: u32 = 2;
+pub const DRIVER_ID_MESA_RADV_KHR: u32 = 3;
+pub const DRIVER_ID_NVIDIA_PROPRIETARY_KHR: u32 = 4;
+pub const DRIVER_ID_INTEL_PROPRIETARY_WINDOWS_KHR: u32 = 5;
+pub const DRIVER_ID_INTEL_OPEN_SOURCE_MESA_KHR: u32 = 6;
+pub const DRIVER_ID_IMAGINATION_PROPRIETARY_KHR: u32 = 7;
+pub const DRIVER_ID_QUALCOMM_PROPRIETARY_KHR: u32 = 8;
+pub const DRIVER_ID_ARM_PROPRIETARY_KHR: u32 = 9;
+pub const DRIVER_ID_GOOGLE_SWIFTSHADER_KHR: u32 = 10;
+pub const DRIVER_ID_GGP_PROPRIETARY_KHR: u32 = 11;
+pub const DRIVER_ID_BROADCOM_PROPRIETARY_KHR: u32 = 12;
 
 pub type DescriptorUpdateTemplateTypeKHR = u32;
 pub const DESCRIPTOR_UPDATE_TEMPLATE_TYPE_DESCRIPTOR_SET_KHR: u32 = 0;
@@ -1039,6 +1114,56 @@ pub const DESCRIPTOR_UPDATE_TEMPLATE_TYPE_END_RANGE_KHR: u32 = DESCRIPTOR_UPDATE
 pub const DESCRIPTOR_UPDATE_TEMPLATE_TYPE_RANGE_SIZE_KHR: u32 = (DESCRIPTOR_UPDATE_TEMPLATE_TYPE_PUSH_DESCRIPTORS_KHR - DESCRIPTOR_UPDATE_TEMPLATE_TYPE_DESCRIPTOR_SET_KHR + 1);
 pub type DescriptorUpdateTemplateCreateFlagsKHR = Flags;
 
+pub type ExternalMemoryHandleTypeFlagBitsKHR = u32;
+pub const EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_FD_BIT_KHR: u32 = 0x00000001;
+pub const EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_WIN32_BIT_KHR: u32 = 0x00000002;
+pub const EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_WIN32_KMT_BIT_KHR: u32 = 0x00000004;
+pub const EXTERNAL_MEMORY_HANDLE_TYPE_D3D11_TEXTURE_BIT_KHR: u32 = 0x00000008;
+pub const EXTERNAL_MEMORY_HANDLE_TYPE_D3D11_TEXTURE_KMT_BIT_KHR: u32 = 0x00000010;
+pub const EXTERNAL_MEMORY_HANDLE_TYPE_D3D12_HEAP_BIT_KHR: u32 = 0x00000020;
+pub const EXTERNAL_MEMORY_HANDLE_TYPE_D3D12_RESOURCE_BIT_KHR: u32 = 0x00000040;
+pub const EXTERNAL_MEMORY_HANDLE_TYPE_DMA_BUF_BIT_EXT: u32 = 0x00000200;
+pub type ExternalMemoryHandleTypeFlagsKHR = Flags;
+
+pub type ExternalMemoryFeatureFlagBitsKHR = u32;
+pub const EXTERNAL_MEMORY_FEATURE_DEDICATED_ONLY_BIT_KHR: u32 = 0x00000001;
+pub const EXTERNAL_MEMORY_FEATURE_EXPORTABLE_BIT_KHR: u32 = 0x00000002;
+pub const EXTERNAL_MEMORY_FEATURE_IMPORTABLE_BIT_KHR: u32 = 0x00000004;
+pub type ExternalMemoryFeatureFlagsKHR = Flags;
+
+pub type ExternalSemaphoreHandleTypeFlagBitsKHR = u32;
+pub const EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_FD_BIT_KHR: u32 = 0x00000001;
+pub const EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_WIN32_BIT_KHR: u32 = 0x00000002;
+pub const EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_WIN32_KMT_BIT_KHR: u32 = 0x00000004;
+pub const EXTERNAL_SEMAPHORE_HANDLE_TYPE_D3D12_FENCE_BIT_KHR: u32 = 0x00000008;
+pub const EXTERNAL_SEMAPHORE_HANDLE_TYPE_SYNC_FD_BIT_KHR: u32 = 0x00000010;
+pub type ExternalSemaphoreHandleTypeFlagsKHR = Flags;
+
+pub type ExternalSemaphoreFeatureFlagBitsKHR = u32;
+pub const EXTERNAL_SEMAPHORE_FEATURE_EXPORTABLE_BIT_KHR: u32 = 0x00000001;
+pub const EXTERNAL_SEMAPHORE_FEATURE_IMPORTABLE_BIT_KHR: u32 = 0x00000002;
+pub type ExternalSemaphoreFeatureFlagsKHR = Flags;
+
+pub type SemaphoreImportFlagBitsKHR = u32;
+pub const SEMAPHORE_IMPORT_TEMPORARY_BIT_KHR: u32 = 0x00000001;
+pub type SemaphoreImportFlagsKHR = Flags;
+
+pub type ExternalFenceHandleTypeFlagBitsKHR = u32;
+pub const EXTERNAL_FENCE_HANDLE_TYPE_OPAQUE_FD_BIT_KHR: u32 = 0x00000001;
+pub const EXTERNAL_FENCE_HANDLE_TYPE_OPAQUE_WIN32_BIT_KHR: u32 = 0x00000002;
+pub const EXTERNAL_FENCE_HANDLE_TYPE_OPAQUE_WIN32_KMT_BIT_KHR: u32 = 0x00000004;
+pub const EXTERNAL_FENCE_HANDLE_TYPE_SYNC_FD_BIT_KHR: u32 = 0x00000008;
+pub type ExternalFenceHandleTypeFlagsKHR = Flags;
+
+pub type ExternalFenceFeatureFlagBitsKHR = u32;
+pub const EXTERNAL_FENCE_FEATURE_EXPORTABLE_BIT_KHR: u32 = 0x00000001;
+pub const EXTERNAL_FENCE_FEATURE_IMPORTABLE_BIT_KHR: u32 = 0x00000002;
+pub type ExternalFenceFeatureFlagsKHR = Flags;
+
+pub type FenceImportFlagBitsKHR = u32;
+pub const FENCE_IMPORT_TEMPORARY_BIT_KHR: u32 = 0x00000001;
+pub type FenceImportFlagsKHR = Flags;
+
 pub type PFN_vkAllocationFunction = extern "system" fn(*mut c_void, usize, usize, SystemAllocationScope) -> *mut c_void;
 pub type PFN_vkReallocationFunction = extern "system" fn(*mut c_void, *mut c_void, usize, usize, SystemAllocationScope) -> *mut c_void;
 pub type PFN_vkFreeFunction = extern "system" fn(*mut c_void, *mut c_void);
@@ -2545,6 +2670,15 @@ pub struct ViSurfaceCreateInfoNN {
     pub window: *const c_void,
 }
 
+pub type HeadlessSurfaceCreateFlagsEXT = Flags;
+
+#[repr(C)]
+pub struct HeadlessSurfaceCreateInfoEXT {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub flags: HeadlessSurfaceCreateFlagsEXT,
+}
+
 #[repr(C)]
 pub struct PhysicalDevicePushDescriptorPropertiesKHR {
     pub sType: StructureType,
@@ -2592,6 +2726,24 @@ pub struct MemoryDedicatedAllocateInfoKHR {
     pub buffer: Buffer,
 }
 
+#[repr(C)]
+pub struct BindBufferMemoryInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub buffer: Buffer,
+    pub memory: DeviceMemory,
+    pub memoryOffset: DeviceSize,
+}
+
+#[repr(C)]
+pub struct BindImageMemoryInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub image: Image,
+    pub memory: DeviceMemory,
+    pub memoryOffset: DeviceSize,
+}
+
 #[repr(C)]
 pub struct BufferMemoryRequirementsInfo2KHR {
     pub sType: StructureType,
@@ -2688,6 +2840,325 @@ pub struct DebugUtilsObjectNameInfoEXT {
     pub pObjectName: *const c_char,
 }
 
+#[repr(C)]
+pub struct ExternalMemoryPropertiesKHR {
+    pub externalMemoryFeatures: ExternalMemoryFeatureFlagsKHR,
+    pub exportFromImportedHandleTypes: ExternalMemoryHandleTypeFlagsKHR,
+    pub compatibleHandleTypes: ExternalMemoryHandleTypeFlagsKHR,
+}
+
+#[repr(C)]
+pub struct PhysicalDeviceExternalImageFormatInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub handleType: ExternalMemoryHandleTypeFlagBitsKHR,
+}
+
+#[repr(C)]
+pub struct ExternalImageFormatPropertiesKHR {
+    pub sType: StructureType,
+    pub pNext: *mut c_void,
+    pub externalMemoryProperties: ExternalMemoryPropertiesKHR,
+}
+
+#[repr(C)]
+pub struct PhysicalDeviceExternalBufferInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub flags: BufferCreateFlags,
+    pub usage: BufferUsageFlags,
+    pub handleType: ExternalMemoryHandleTypeFlagBitsKHR,
+}
+
+#[repr(C)]
+pub struct ExternalBufferPropertiesKHR {
+    pub sType: StructureType,
+    pub pNext: *mut c_void,
+    pub externalMemoryProperties: ExternalMemoryPropertiesKHR,
+}
+
+#[repr(C)]
+pub struct PhysicalDeviceIDPropertiesKHR {
+    pub sType: StructureType,
+    pub pNext: *mut c_void,
+    pub deviceUUID: [u8; UUID_SIZE as usize],
+    pub driverUUID: [u8; UUID_SIZE as usize],
+    pub deviceLUID: [u8; LUID_SIZE_KHR as usize],
+    pub deviceNodeMask: u32,
+    pub deviceLUIDValid: Bool32,
+}
+
+#[repr(C)]
+pub struct ExternalMemoryImageCreateInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub handleTypes: ExternalMemoryHandleTypeFlagsKHR,
+}
+
+#[repr(C)]
+pub struct ExternalMemoryBufferCreateInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub handleTypes: ExternalMemoryHandleTypeFlagsKHR,
+}
+
+#[repr(C)]
+pub struct ExportMemoryAllocateInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub handleTypes: ExternalMemoryHandleTypeFlagsKHR,
+}
+
+#[repr(C)]
+pub struct ImportMemoryFdInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub handleType: ExternalMemoryHandleTypeFlagBitsKHR,
+    pub fd: c_int,
+}
+
+#[repr(C)]
+pub struct MemoryFdPropertiesKHR {
+    pub sType: StructureType,
+    pub pNext: *mut c_void,
+    pub memoryTypeBits: u32,
+}
+
+#[repr(C)]
+pub struct MemoryGetFdInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub memory: DeviceMemory,
+    pub handleType: ExternalMemoryHandleTypeFlagBitsKHR,
+}
+
+#[repr(C)]
+pub struct PhysicalDeviceExternalSemaphoreInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub handleType: ExternalSemaphoreHandleTypeFlagBitsKHR,
+}
+
+#[repr(C)]
+pub struct ExternalSemaphorePropertiesKHR {
+    pub sType: StructureType,
+    pub pNext: *mut c_void,
+    pub exportFromImportedHandleTypes: ExternalSemaphoreHandleTypeFlagsKHR,
+    pub compatibleHandleTypes: ExternalSemaphoreHandleTypeFlagsKHR,
+    pub externalSemaphoreFeatures: ExternalSemaphoreFeatureFlagsKHR,
+}
+
+#[repr(C)]
+pub struct ExportSemaphoreCreateInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub handleTypes: ExternalSemaphoreHandleTypeFlagsKHR,
+}
+
+#[repr(C)]
+pub struct ImportSemaphoreFdInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub semaphore: Semaphore,
+    pub flags: SemaphoreImportFlagsKHR,
+    pub handleType: ExternalSemaphoreHandleTypeFlagBitsKHR,
+    pub fd: c_int,
+}
+
+#[repr(C)]
+pub struct SemaphoreGetFdInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub semaphore: Semaphore,
+    pub handleType: ExternalSemaphoreHandleTypeFlagBitsKHR,
+}
+
+#[repr(C)]
+pub struct PhysicalDeviceExternalFenceInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub handleType: ExternalFenceHandleTypeFlagBitsKHR,
+}
+
+#[repr(C)]
+pub struct ExternalFencePropertiesKHR {
+    pub sType: StructureType,
+    pub pNext: *mut c_void,
+    pub exportFromImportedHandleTypes: ExternalFenceHandleTypeFlagsKHR,
+    pub compatibleHandleTypes: ExternalFenceHandleTypeFlagsKHR,
+    pub externalFenceFeatures: ExternalFenceFeatureFlagsKHR,
+}
+
+#[repr(C)]
+pub struct ExportFenceCreateInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub handleTypes: ExternalFenceHandleTypeFlagsKHR,
+}
+
+#[repr(C)]
+pub struct ImportFenceFdInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub fence: Fence,
+    pub flags: FenceImportFlagsKHR,
+    pub handleType: ExternalFenceHandleTypeFlagBitsKHR,
+    pub fd: c_int,
+}
+
+#[repr(C)]
+pub struct FenceGetFdInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub fence: Fence,
+    pub handleType: ExternalFenceHandleTypeFlagBitsKHR,
+}
+
+#[repr(C)]
+pub struct DescriptorSetLayoutBindingFlagsCreateInfoEXT {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub bindingCount: u32,
+    pub pBindingFlags: *const DescriptorBindingFlagsEXT,
+}
+
+#[repr(C)]
+pub struct PhysicalDeviceDescriptorIndexingFeaturesEXT {
+    pub sType: StructureType,
+    pub pNext: *mut c_void,
+    pub shaderInputAttachmentArrayDynamicIndexing: Bool32,
+    pub shaderUniformTexelBufferArrayDynamicIndexing: Bool32,
+    pub shaderStorageTexelBufferArrayDynamicIndexing: Bool32,
+    pub shaderUniformBufferArrayNonUniformIndexing: Bool32,
+    pub shaderSampledImageArrayNonUniformIndexing: Bool32,
+    pub shaderStorageBufferArrayNonUniformIndexing: Bool32,
+    pub shaderStorageImageArrayNonUniformIndexing: Bool32,
+    pub shaderInputAttachmentArrayNonUniformIndexing: Bool32,
+    pub shaderUniformTexelBufferArrayNonUniformIndexing: Bool32,
+    pub shaderStorageTexelBufferArrayNonUniformIndexing: Bool32,
+    pub descriptorBindingUniformBufferUpdateAfterBind: Bool32,
+    pub descriptorBindingSampledImageUpdateAfterBind: Bool32,
+    pub descriptorBindingStorageImageUpdateAfterBind: Bool32,
+    pub descriptorBindingStorageBufferUpdateAfterBind: Bool32,
+    pub descriptorBindingUniformTexelBufferUpdateAfterBind: Bool32,
+    pub descriptorBindingStorageTexelBufferUpdateAfterBind: Bool32,
+    pub descriptorBindingUpdateUnusedWhilePending: Bool32,
+    pub descriptorBindingPartiallyBound: Bool32,
+    pub descriptorBindingVariableDescriptorCount: Bool32,
+    pub runtimeDescriptorArray: Bool32,
+}
+
+#[repr(C)]
+pub struct PhysicalDeviceDescriptorIndexingPropertiesEXT {
+    pub sType: StructureType,
+    pub pNext: *mut c_void,
+    pub maxUpdateAfterBindDescriptorsInAllPools: u32,
+    pub shaderUniformBufferArrayNonUniformIndexingNative: Bool32,
+    pub shaderSampledImageArrayNonUniformIndexingNative: Bool32,
+    pub shaderStorageBufferArrayNonUniformIndexingNative: Bool32,
+    pub shaderStorageImageArrayNonUniformIndexingNative: Bool32,
+    pub shaderInputAttachmentArrayNonUniformIndexingNative: Bool32,
+    pub robustBufferAccessUpdateAfterBind: Bool32,
+    pub quadDivergentImplicitLod: Bool32,
+    pub maxPerStageDescriptorUpdateAfterBindSamplers: u32,
+    pub maxPerStageDescriptorUpdateAfterBindUniformBuffers: u32,
+    pub maxPerStageDescriptorUpdateAfterBindStorageBuffers: u32,
+    pub maxPerStageDescriptorUpdateAfterBindSampledImages: u32,
+    pub maxPerStageDescriptorUpdateAfterBindStorageImages: u32,
+    pub maxPerStageDescriptorUpdateAfterBindInputAttachments: u32,
+    pub maxPerStageUpdateAfterBindResources: u32,
+    pub maxDescriptorSetUpdateAfterBindSamplers: u32,
+    pub maxDescriptorSetUpdateAfterBindUniformBuffers: u32,
+    pub maxDescriptorSetUpdateAfterBindUniformBuffersDynamic: u32,
+    pub maxDescriptorSetUpdateAfterBindStorageBuffers: u32,
+    pub maxDescriptorSetUpdateAfterBindStorageBuffersDynamic: u32,
+    pub maxDescriptorSetUpdateAfterBindSampledImages: u32,
+    pub maxDescriptorSetUpdateAfterBindStorageImages: u32,
+    pub maxDescriptorSetUpdateAfterBindInputAttachments: u32,
+}
+
+#[repr(C)]
+pub struct DescriptorSetVariableDescriptorCountAllocateInfoEXT {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub descriptorSetCount: u32,
+    pub pDescriptorCounts: *const u32,
+}
+
+#[repr(C)]
+pub struct DescriptorSetVariableDescriptorCountLayoutSupportEXT {
+    pub sType: StructureType,
+    pub pNext: *mut c_void,
+    pub maxVariableDescriptorCount: u32,
+}
+
+#[repr(C)]
+pub struct PhysicalDeviceMaintenance3PropertiesKHR {
+    pub sType: StructureType,
+    pub pNext: *mut c_void,
+    pub maxPerSetDescriptors: u32,
+    pub maxMemoryAllocationSize: DeviceSize,
+}
+
+#[repr(C)]
+pub struct DescriptorSetLayoutSupportKHR {
+    pub sType: StructureType,
+    pub pNext: *mut c_void,
+    pub supported: Bool32,
+}
+
+#[repr(C)]
+pub struct PhysicalDeviceMultiviewFeaturesKHR {
+    pub sType: StructureType,
+    pub pNext: *mut c_void,
+    pub multiview: Bool32,
+    pub multiviewGeometryShader: Bool32,
+    pub multiviewTessellationShader: Bool32,
+}
+
+#[repr(C)]
+pub struct PhysicalDeviceTimelineSemaphoreFeaturesKHR {
+    pub sType: StructureType,
+    pub pNext: *mut c_void,
+    pub timelineSemaphore: Bool32,
+}
+
+#[repr(C)]
+pub struct PhysicalDeviceScalarBlockLayoutFeaturesEXT {
+    pub sType: StructureType,
+    pub pNext: *mut c_void,
+    pub scalarBlockLayout: Bool32,
+}
+
+#[repr(C)]
+pub struct PhysicalDeviceSubgroupProperties {
+    pub sType: StructureType,
+    pub pNext: *mut c_void,
+    pub subgroupSize: u32,
+    pub supportedStages: ShaderStageFlags,
+    pub supportedOperations: SubgroupFeatureFlags,
+    pub quadOperationsInAllStages: Bool32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct ConformanceVersionKHR {
+    pub major: u8,
+    pub minor: u8,
+    pub subminor: u8,
+    pub patch: u8,
+}
+
+#[repr(C)]
+pub struct PhysicalDeviceDriverPropertiesKHR {
+    pub sType: StructureType,
+    pub pNext: *mut c_void,
+    pub driverID: DriverIdKHR,
+    pub driverName: [c_char; MAX_DRIVER_NAME_SIZE_KHR as usize],
+    pub driverInfo: [c_char; MAX_DRIVER_INFO_SIZE_KHR as usize],
+    pub conformanceVersion: ConformanceVersionKHR,
+}
+
 macro_rules! ptrs {
     ($struct_name:ident, { $($name:ident => ($($param_n:ident: $param_ty:ty),*) -> $ret:ty,)+ }) => (
         pub struct $struct_name {
@@ -2741,6 +3212,7 @@ ptrs!(EntryPoints, {
     CreateInstance => (pCreateInfo: *const InstanceCreateInfo, pAllocator: *const AllocationCallbacks, pInstance: *mut Instance) -> Result,
     EnumerateInstanceExtensionProperties => (pLayerName: *const c_char, pPropertyCount: *mut u32, pProperties: *mut ExtensionProperties) -> Result,
     EnumerateInstanceLayerProperties => (pPropertyCount: *mut u32, pProperties: *mut LayerProperties) -> Result,
+    EnumerateInstanceVersion => (pApiVersion: *mut u32) -> Result,
 });
 
 ptrs!(InstancePointers, {
@@ -2789,6 +3261,7 @@ ptrs!(InstancePointers, {
     GetPhysicalDeviceMetalFeaturesMVK => (physicalDevice: PhysicalDevice, pMetalFeatures: *mut MVKPhysicalDeviceMetalFeatures) -> Result,
     GetSwapchainPerformanceMVK => (device: Device, swapchain: SwapchainKHR, pSwapchainPerf: *mut MVKSwapchainPerformance) -> Result,
     CreateViSurfaceNN => (instance: Instance, pCreateInfo: *const ViSurfaceCreateInfoNN, pAllocator: *const AllocationCallbacks, pSurface: *mut SurfaceKHR) -> Result,
+    CreateHeadlessSurfaceEXT => (instance: Instance, pCreateInfo: *const HeadlessSurfaceCreateInfoEXT, pAllocator: *const AllocationCallbacks, pSurface: *mut SurfaceKHR) -> Result,
     GetPhysicalDeviceFeatures2KHR => (physicalDevice: PhysicalDevice, pFeatures: *mut PhysicalDeviceFeatures2KHR) -> (),
     GetPhysicalDeviceProperties2KHR => (physicalDevice: PhysicalDevice, pProperties: *mut PhysicalDeviceProperties2KHR) -> (),
     GetPhysicalDeviceFormatProperties2KHR => (physicalDevice: PhysicalDevice, pFormatProperties: *mut FormatProperties2KHR) -> (),
@@ -2796,6 +3269,9 @@ ptrs!(InstancePointers, {
     GetPhysicalDeviceQueueFamilyProperties2KHR => (physicalDevice: PhysicalDevice, pQueueFamilyPropertiesCount: *mut u32, pQueueFamilyProperties: *mut QueueFamilyProperties2KHR) -> (),
     GetPhysicalDeviceMemoryProperties2KHR => (physicalDevice: PhysicalDevice, pMemoryProperties: *mut PhysicalDeviceMemoryProperties2KHR) -> (),
     GetPhysicalDeviceSparseImageFormatProperties2KHR => (physicalDevice: PhysicalDevice, pFormatInfo: *const PhysicalDeviceSparseImageFormatInfo2KHR, pPropertyCount: *mut u32, pProperties: *mut SparseImageFormatProperties2KHR) -> (),
+    GetPhysicalDeviceExternalBufferPropertiesKHR => (physicalDevice: PhysicalDevice, pExternalBufferInfo: *const PhysicalDeviceExternalBufferInfoKHR, pExternalBufferProperties: *mut ExternalBufferPropertiesKHR) -> (),
+    GetPhysicalDeviceExternalSemaphorePropertiesKHR => (physicalDevice: PhysicalDevice, pExternalSemaphoreInfo: *const PhysicalDeviceExternalSemaphoreInfoKHR, pExternalSemaphoreProperties: *mut ExternalSemaphorePropertiesKHR) -> (),
+    GetPhysicalDeviceExternalFencePropertiesKHR => (physicalDevice: PhysicalDevice, pExternalFenceInfo: *const PhysicalDeviceExternalFenceInfoKHR, pExternalFenceProperties: *mut ExternalFencePropertiesKHR) -> (),
 });
 
 ptrs!(DevicePointers, {
@@ -2933,8 +3409,20 @@ ptrs!(DevicePointers, {
     CmdPushDescriptorSetWithTemplateKHR => (commandBuffer: CommandBuffer, descriptorUpdateTemplate: DescriptorUpdateTemplateKHR, layout: PipelineLayout, set: u32, pData: *const c_void) -> (),
     GetImageMemoryRequirements2KHR => (device: Device, pInfo: *const ImageMemoryRequirementsInfo2KHR, pMemoryRequirements: *mut MemoryRequirements2KHR) -> (),
     GetBufferMemoryRequirements2KHR => (device: Device, pInfo: *const BufferMemoryRequirementsInfo2KHR, pMemoryRequirements: *mut MemoryRequirements2KHR) -> (),
+    GetDescriptorSetLayoutSupportKHR => (device: Device, pCreateInfo: *const DescriptorSetLayoutCreateInfo, pSupport: *mut DescriptorSetLayoutSupportKHR) -> (),
+    BindBufferMemory2KHR => (device: Device, bindInfoCount: u32, pBindInfos: *const BindBufferMemoryInfoKHR) -> Result,
+    BindImageMemory2KHR => (device: Device, bindInfoCount: u32, pBindInfos: *const BindImageMemoryInfoKHR) -> Result,
     SetDebugUtilsObjectNameEXT => (device: Device, pNameInfo: *const DebugUtilsObjectNameInfoEXT) -> Result,
-    CmdBeginDebugUtilsLabelEXT => (commandBuffer: CommandBuffer, pLabelInfo: *const DebugUtilsLabelEXT) -> Result,
-    CmdEndDebugUtilsLabelEXT => (commandBuffer: CommandBuffer) -> Result,
-    CmdInsertDebugUtilsLabelEXT => (commandBuffer: CommandBuffer, pLabelInfo: *const DebugUtilsLabelEXT) -> Result,
+    CmdBeginDebugUtilsLabelEXT => (commandBuffer: CommandBuffer, pLabelInfo: *const DebugUtilsLabelEXT) -> (),
+    CmdEndDebugUtilsLabelEXT => (commandBuffer: CommandBuffer) -> (),
+    CmdInsertDebugUtilsLabelEXT => (commandBuffer: CommandBuffer, pLabelInfo: *const DebugUtilsLabelEXT) -> (),
+    QueueBeginDebugUtilsLabelEXT => (queue: Queue, pLabelInfo: *const DebugUtilsLabelEXT) -> (),
+    QueueEndDebugUtilsLabelEXT => (queue: Queue) -> (),
+    QueueInsertDebugUtilsLabelEXT => (queue: Queue, pLabelInfo: *const DebugUtilsLabelEXT) -> (),
+    GetMemoryFdKHR => (device: Device, pGetFdInfo: *const MemoryGetFdInfoKHR, pFd: *mut c_int) -> Result,
+    GetMemoryFdPropertiesKHR => (device: Device, handleType: ExternalMemoryHandleTypeFlagBitsKHR, fd: c_int, pMemoryFdProperties: *mut MemoryFdPropertiesKHR) -> Result,
+    ImportSemaphoreFdKHR => (device: Device, pImportSemaphoreFdInfo: *const ImportSemaphoreFdInfoKHR) -> Result,
+    GetSemaphoreFdKHR => (device: Device, pGetFdInfo: *const SemaphoreGetFdInfoKHR, pFd: *mut c_int) -> Result,
+    ImportFenceFdKHR => (device: Device, pImportFenceFdInfo: *const ImportFenceFdInfoKHR) -> Result,
+    GetFenceFdKHR => (device: Device, pGetFdInfo: *const FenceGetFdInfoKHR, pFd: *mut c_int) -> Result,
 });
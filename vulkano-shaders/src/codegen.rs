@@ -400,6 +400,19 @@ fn capability_requirement(cap: &Capability) -> DeviceRequirement {
             DeviceRequirement::Extensions(&["khr_16bit_storage"]),
         Capability::CapabilityStorageInputOutput16 =>
             DeviceRequirement::Extensions(&["khr_16bit_storage"]),
+        Capability::CapabilityShaderNonUniformEXT |
+        Capability::CapabilityRuntimeDescriptorArrayEXT |
+        Capability::CapabilityInputAttachmentArrayDynamicIndexingEXT |
+        Capability::CapabilityUniformTexelBufferArrayDynamicIndexingEXT |
+        Capability::CapabilityStorageTexelBufferArrayDynamicIndexingEXT |
+        Capability::CapabilityUniformBufferArrayNonUniformIndexingEXT |
+        Capability::CapabilitySampledImageArrayNonUniformIndexingEXT |
+        Capability::CapabilityStorageBufferArrayNonUniformIndexingEXT |
+        Capability::CapabilityStorageImageArrayNonUniformIndexingEXT |
+        Capability::CapabilityInputAttachmentArrayNonUniformIndexingEXT |
+        Capability::CapabilityUniformTexelBufferArrayNonUniformIndexingEXT |
+        Capability::CapabilityStorageTexelBufferArrayNonUniformIndexingEXT =>
+            DeviceRequirement::Extensions(&["ext_descriptor_indexing"]),
     }
 }
 
@@ -559,4 +572,43 @@ mod tests {
         let compile_defines = compile(None, &Path::new(""), need_defines, ShaderKind::Vertex, &empty_includes, &defines);
         compile_defines.expect("Setting shader macros did not work");
     }
+
+    #[test]
+    fn test_runtime_descriptor_array() {
+        let includes: [PathBuf;0] = [];
+        let defines: [(String, String);0] = [];
+        let comp = compile(None, &Path::new(""), "
+        #version 450
+        #extension GL_EXT_nonuniform_qualifier : require
+        layout(set = 0, binding = 0) uniform sampler2D textures[];
+        layout(location = 0) flat in uint index;
+        layout(location = 0) out vec4 color;
+        void main() {
+            color = texture(textures[nonuniformEXT(index)], vec2(0.0));
+        }
+        ", ShaderKind::Fragment, &includes, &defines).unwrap();
+        let doc = parse::parse_spirv(comp.as_binary()).unwrap();
+        let layout = descriptor_sets::write_descriptor_sets(&doc).to_string();
+        assert!(layout.contains("(0usize , 0usize) => true"));
+    }
+
+    #[test]
+    fn test_runtime_descriptor_array_of_arrays() {
+        let includes: [PathBuf;0] = [];
+        let defines: [(String, String);0] = [];
+        let comp = compile(None, &Path::new(""), "
+        #version 450
+        #extension GL_EXT_nonuniform_qualifier : require
+        layout(set = 0, binding = 3) uniform sampler2D textures[][2];
+        layout(location = 0) flat in uint index;
+        layout(location = 0) out vec4 color;
+        void main() {
+            color = texture(textures[nonuniformEXT(index)][0], vec2(0.0));
+        }
+        ", ShaderKind::Fragment, &includes, &defines).unwrap();
+        let doc = parse::parse_spirv(comp.as_binary()).unwrap();
+        let layout = descriptor_sets::write_descriptor_sets(&doc).to_string();
+        assert!(layout.contains("compile_error"));
+        assert!(layout.contains("`textures` (set 0, binding 3)"));
+    }
 }
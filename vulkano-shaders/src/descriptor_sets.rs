@@ -20,11 +20,14 @@ pub fn write_descriptor_sets(doc: &Spirv) -> TokenStream {
 
     // Finding all the descriptors.
     let mut descriptors = Vec::new();
+    // Descriptors that can't be described, reported as compile errors.
+    let mut errors = Vec::new();
     struct Descriptor {
         set: u32,
         binding: u32,
         desc_ty: TokenStream,
         array_count: u64,
+        variable_count: bool,
         readonly: bool,
     }
 
@@ -42,14 +45,23 @@ pub fn write_descriptor_sets(doc: &Spirv) -> TokenStream {
         // TODO: There was a previous todo here, I think it was asking for this to be implemented for member decorations? check git history
         let binding = doc.get_decoration_params(variable_id, Decoration::DecorationBinding).unwrap()[0];
 
+        // Vulkan has no way to describe a runtime-sized array of arrays of descriptors.
+        if is_runtime_array_of_arrays(doc, pointed_ty) {
+            let message = format!("Descriptor `{}` (set {}, binding {}) is a runtime-sized array \
+                                   of arrays, which is not supported",
+                                  name, set, binding);
+            errors.push(quote!{ compile_error!(#message); });
+            continue;
+        }
+
         // Find information about the kind of binding for this descriptor.
-        let (desc_ty, readonly, array_count) = descriptor_infos(doc, pointed_ty, storage_class, false)
+        let (desc_ty, readonly, array_count, variable_count) = descriptor_infos(doc, pointed_ty, storage_class, false)
             .expect(&format!(
                 "Couldn't find relevant type for uniform `{}` (type {}, maybe unimplemented)",
                 name,
                 pointed_ty
             ));
-        descriptors.push(Descriptor { desc_ty, set, binding, array_count, readonly });
+        descriptors.push(Descriptor { desc_ty, set, binding, array_count, variable_count, readonly });
     }
 
     // Looping to find all the push constant structs.
@@ -86,6 +98,17 @@ pub fn write_descriptor_sets(doc: &Spirv) -> TokenStream {
         })
         .collect::<Vec<_>>();
 
+    // Writing the body of the `is_runtime_array` method.
+    let runtime_array_body = descriptors
+        .iter()
+        .filter(|d| d.variable_count)
+        .map(|d| {
+            let set = d.set as usize;
+            let binding = d.binding as usize;
+            quote!{ (#set, #binding) => true, }
+        })
+        .collect::<Vec<_>>();
+
     let num_sets = descriptors.iter().fold(0, |s, d| cmp::max(s, d.set + 1)) as usize;
 
     // Writing the body of the `num_bindings_in_set` method.
@@ -116,6 +139,8 @@ pub fn write_descriptor_sets(doc: &Spirv) -> TokenStream {
     );
 
     quote!{
+        #( #errors )*
+
         #[derive(Debug, Clone)]
         pub struct Layout(pub ShaderStages);
 
@@ -139,6 +164,13 @@ pub fn write_descriptor_sets(doc: &Spirv) -> TokenStream {
                 }
             }
 
+            fn is_runtime_array(&self, set: usize, binding: usize) -> bool {
+                match (set, binding) {
+                    #( #runtime_array_body )*
+                    _ => false
+                }
+            }
+
             fn num_push_constants_ranges(&self) -> usize {
                 #num_push_constants_ranges_body
             }
@@ -174,12 +206,26 @@ fn pointer_variable_ty(doc: &Spirv, variable: u32) -> (u32, StorageClass) {
         .unwrap()
 }
 
+/// Returns true if `ty` is a runtime-sized array whose elements are arrays.
+fn is_runtime_array_of_arrays(doc: &Spirv, ty: u32) -> bool {
+    doc.instructions.iter().any(|i| match i {
+        &Instruction::TypeRuntimeArray { result_id, type_id } if result_id == ty => {
+            doc.instructions.iter().any(|i| match i {
+                &Instruction::TypeArray { result_id, .. } => result_id == type_id,
+                _ => false,
+            })
+        }
+        _ => false,
+    })
+}
+
 /// Returns a `DescriptorDescTy` constructor, a bool indicating whether the descriptor is
-/// read-only, and the number of array elements.
+/// read-only, the number of array elements, and a bool indicating whether the array is
+/// runtime-sized. For runtime-sized arrays the number of elements is the minimum of 1.
 ///
 /// See also section 14.5.2 of the Vulkan specs: Descriptor Set Interface
 fn descriptor_infos(doc: &Spirv, pointed_ty: u32, pointer_storage: StorageClass, force_combined_image_sampled: bool)
-    -> Option<(TokenStream, bool, u64)>
+    -> Option<(TokenStream, bool, u64, bool)>
 {
     doc.instructions.iter().filter_map(|i| {
         match i {
@@ -198,7 +244,7 @@ fn descriptor_infos(doc: &Spirv, pointed_ty: u32, pointer_storage: StorageClass,
                     })
                 };
 
-                Some((desc, true, 1))
+                Some((desc, true, 1, false))
             }
             &Instruction::TypeImage { result_id, ref dim, arrayed, ms, sampled, ref format, .. }
                 if result_id == pointed_ty =>
@@ -228,7 +274,7 @@ fn descriptor_infos(doc: &Spirv, pointed_ty: u32, pointer_storage: StorageClass,
                             }
                         };
 
-                        Some((desc, true, 1))
+                        Some((desc, true, 1, false))
                     }
                     Dim::DimBuffer => {
                         // We are a texel buffer.
@@ -240,7 +286,7 @@ fn descriptor_infos(doc: &Spirv, pointed_ty: u32, pointer_storage: StorageClass,
                             }
                         };
 
-                        Some((desc, true, 1))
+                        Some((desc, true, 1, false))
                     }
                     _ => {
                         // We are a sampled or storage image.
@@ -267,7 +313,7 @@ fn descriptor_infos(doc: &Spirv, pointed_ty: u32, pointer_storage: StorageClass,
                             })
                         };
 
-                        Some((desc, true, 1))
+                        Some((desc, true, 1, false))
                     }
                 }
             }
@@ -277,10 +323,10 @@ fn descriptor_infos(doc: &Spirv, pointed_ty: u32, pointer_storage: StorageClass,
 
             &Instruction::TypeSampler { result_id } if result_id == pointed_ty => {
                 let desc = quote!{ DescriptorDescTy::Sampler };
-                Some((desc, true, 1))
+                Some((desc, true, 1, false))
             }
             &Instruction::TypeArray { result_id, type_id, length_id } if result_id == pointed_ty => {
-                let (desc, readonly, arr) = match descriptor_infos(doc, type_id, pointer_storage.clone(), false) {
+                let (desc, readonly, arr, _) = match descriptor_infos(doc, type_id, pointer_storage.clone(), false) {
                     None => return None,
                     Some(v) => v,
                 };
@@ -293,7 +339,16 @@ fn descriptor_infos(doc: &Spirv, pointed_ty: u32, pointer_storage: StorageClass,
                     }
                 }).next().expect("failed to find array length");
                 let len = len.iter().rev().fold(0, |a, &b| (a << 32) | b as u64);
-                Some((desc, readonly, len))
+                Some((desc, readonly, len, false))
+            }
+            &Instruction::TypeRuntimeArray { result_id, type_id } if result_id == pointed_ty => {
+                let (desc, readonly, arr, _) = match descriptor_infos(doc, type_id, pointer_storage.clone(), false) {
+                    None => return None,
+                    Some(v) => v,
+                };
+                // Runtime-sized arrays of arrays are rejected by `write_descriptor_sets`.
+                debug_assert_eq!(arr, 1);
+                Some((desc, readonly, 1, true))
             }
             _ => None, // TODO: other types
         }
@@ -338,6 +338,7 @@ enumeration! {
         DecorationNoContraction = 42,
         DecorationInputAttachmentIndex = 43,
         DecorationAlignment = 44,
+        DecorationNonUniformEXT = 5300,
     } Decoration;
 
     typedef enum BuiltIn_ {
@@ -551,5 +552,17 @@ enumeration! {
         CapabilityStorageUniform16 = 4434,
         CapabilityStoragePushConstant16 = 4435,
         CapabilityStorageInputOutput16 = 4436,
+        CapabilityShaderNonUniformEXT = 5301,
+        CapabilityRuntimeDescriptorArrayEXT = 5302,
+        CapabilityInputAttachmentArrayDynamicIndexingEXT = 5303,
+        CapabilityUniformTexelBufferArrayDynamicIndexingEXT = 5304,
+        CapabilityStorageTexelBufferArrayDynamicIndexingEXT = 5305,
+        CapabilityUniformBufferArrayNonUniformIndexingEXT = 5306,
+        CapabilitySampledImageArrayNonUniformIndexingEXT = 5307,
+        CapabilityStorageBufferArrayNonUniformIndexingEXT = 5308,
+        CapabilityStorageImageArrayNonUniformIndexingEXT = 5309,
+        CapabilityInputAttachmentArrayNonUniformIndexingEXT = 5310,
+        CapabilityUniformTexelBufferArrayNonUniformIndexingEXT = 5311,
+        CapabilityStorageTexelBufferArrayNonUniformIndexingEXT = 5312,
     } Capability;
 }
@@ -0,0 +1,122 @@
+use std::sync::Arc;
+
+use raw_window_handle::HasRawWindowHandle;
+use raw_window_handle::RawWindowHandle;
+use vulkano::instance::Instance;
+use vulkano::swapchain::Surface;
+use vulkano::swapchain::SurfaceCreationError;
+
+#[cfg(target_os = "macos")]
+use cocoa::appkit::{NSView, NSWindow};
+#[cfg(target_os = "macos")]
+use cocoa::base::id as cocoa_id;
+#[cfg(target_os = "macos")]
+use metal::CoreAnimationLayer;
+#[cfg(target_os = "macos")]
+use objc::runtime::YES;
+
+#[cfg(target_os = "macos")]
+use std::mem;
+
+use crate::SafeBorrow;
+
+/// Create a surface from the window type `W`, which must give access to a window implementing
+/// `HasRawWindowHandle`. The surface borrows the window to prevent it from being dropped before
+/// the surface.
+///
+/// This works with any window library that supports `raw-window-handle`, such as `winit` or
+/// SDL2. The instance must have been created with the surface extension of the platform, see
+/// `required_extensions`.
+///
+/// Returns `SurfaceCreationError::UnsupportedWindowHandle` if the window handle is for a platform
+/// that vulkano can't create surfaces for, such as the web.
+pub fn create_surface_from_handle<W, H>(
+    window: W, instance: Arc<Instance>,
+) -> Result<Arc<Surface<W>>, SurfaceCreationError>
+where
+    W: SafeBorrow<H>,
+    H: HasRawWindowHandle,
+{
+    let handle = window.borrow().raw_window_handle();
+    unsafe { handle_to_surface(instance, handle, window) }
+}
+
+unsafe fn handle_to_surface<W>(
+    instance: Arc<Instance>, handle: RawWindowHandle, win: W,
+) -> Result<Arc<Surface<W>>, SurfaceCreationError> {
+    match handle {
+        #[cfg(target_os = "android")]
+        RawWindowHandle::Android(handle) => {
+            Surface::from_anativewindow(instance, handle.a_native_window as *const (), win)
+        },
+
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        ))]
+        RawWindowHandle::Wayland(handle) => Surface::from_wayland(
+            instance,
+            handle.display as *const (),
+            handle.surface as *const (),
+            win,
+        ),
+
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        ))]
+        RawWindowHandle::Xlib(handle) => {
+            Surface::from_xlib(instance, handle.display as *const (), handle.window, win)
+        },
+
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        ))]
+        RawWindowHandle::Xcb(handle) => {
+            Surface::from_xcb(instance, handle.connection as *const (), handle.window, win)
+        },
+
+        #[cfg(target_os = "windows")]
+        RawWindowHandle::Windows(handle) => Surface::from_hwnd(
+            instance,
+            handle.hinstance as *const (),
+            handle.hwnd as *const (),
+            win,
+        ),
+
+        #[cfg(target_os = "macos")]
+        RawWindowHandle::MacOS(handle) => {
+            let wnd: cocoa_id = mem::transmute(handle.ns_window);
+            let layer = CoreAnimationLayer::new();
+
+            layer.set_edge_antialiasing_mask(0);
+            layer.set_presents_with_transaction(false);
+            layer.remove_all_animations();
+
+            let view = wnd.contentView();
+
+            layer.set_contents_scale(view.backingScaleFactor());
+            view.setLayer(mem::transmute(layer.as_ref())); // Bombs here with out of memory
+            view.setWantsLayer(YES);
+
+            Surface::from_macos_moltenvk(instance, handle.ns_view as *const (), win)
+        },
+
+        #[cfg(target_os = "ios")]
+        RawWindowHandle::IOS(handle) => {
+            Surface::from_ios_moltenvk(instance, handle.ui_view as *const (), win)
+        },
+
+        _ => Err(SurfaceCreationError::UnsupportedWindowHandle),
+    }
+}
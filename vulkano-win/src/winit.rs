@@ -0,0 +1,88 @@
+use std::error;
+use std::fmt;
+use std::sync::Arc;
+
+use vulkano::instance::Instance;
+use vulkano::swapchain::Surface;
+use vulkano::swapchain::SurfaceCreationError;
+use winit::window::Window;
+use winit::window::WindowBuilder;
+use winit::error::OsError as WindowCreationError;
+use winit::event_loop::EventLoopWindowTarget;
+
+use crate::create_surface_from_handle;
+use crate::SafeBorrow;
+
+/// Create a surface from the window type `W`. The surface borrows the window
+/// to prevent it from being dropped before the surface.
+pub fn create_vk_surface<W>(
+    window: W, instance: Arc<Instance>
+) -> Result<Arc<Surface<W>>, SurfaceCreationError>
+where
+    W: SafeBorrow<Window>,
+{
+    create_surface_from_handle::<W, Window>(window, instance)
+}
+
+pub trait VkSurfaceBuild<E> {
+    fn build_vk_surface(
+        self, event_loop: &EventLoopWindowTarget<E>, instance: Arc<Instance>,
+    ) -> Result<Arc<Surface<Window>>, CreationError>;
+}
+
+impl<E> VkSurfaceBuild<E> for WindowBuilder {
+    fn build_vk_surface(
+        self, event_loop: &EventLoopWindowTarget<E>, instance: Arc<Instance>,
+    ) -> Result<Arc<Surface<Window>>, CreationError> {
+        let window = self.build(event_loop)?;
+        Ok(create_vk_surface(window, instance)?)
+    }
+}
+
+/// Error that can happen when creating a window.
+#[derive(Debug)]
+pub enum CreationError {
+    /// Error when creating the surface.
+    SurfaceCreationError(SurfaceCreationError),
+    /// Error when creating the window.
+    WindowCreationError(WindowCreationError),
+}
+
+impl error::Error for CreationError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            CreationError::SurfaceCreationError(_) => "error while creating the surface",
+            CreationError::WindowCreationError(_) => "error while creating the window",
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&dyn error::Error> {
+        match *self {
+            CreationError::SurfaceCreationError(ref err) => Some(err),
+            CreationError::WindowCreationError(ref err) => Some(err),
+        }
+    }
+}
+
+impl fmt::Display for CreationError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<SurfaceCreationError> for CreationError {
+    #[inline]
+    fn from(err: SurfaceCreationError) -> CreationError {
+        CreationError::SurfaceCreationError(err)
+    }
+}
+
+impl From<WindowCreationError> for CreationError {
+    #[inline]
+    fn from(err: WindowCreationError) -> CreationError {
+        CreationError::WindowCreationError(err)
+    }
+}
@@ -39,13 +39,16 @@ use device::Device;
 use device::DeviceOwned;
 use memory::DeviceMemory;
 use memory::DeviceMemoryAllocError;
+use memory::ExternalMemoryHandleType;
 use memory::MemoryRequirements;
+use sync::QueueFamilyOwnership;
 use sync::Sharing;
 
 use Error;
 use OomError;
 use VulkanObject;
 use check_errors;
+use version::Version;
 use vk;
 
 /// Data storage in a GPU-accessible location.
@@ -54,6 +57,10 @@ pub struct UnsafeBuffer {
     device: Arc<Device>,
     size: usize,
     usage: vk::BufferUsageFlags,
+    // True if the buffer was created with `Sharing::Concurrent`.
+    concurrent: bool,
+    // Queue family ownership of the buffer, for buffers that aren't concurrent.
+    ownership: QueueFamilyOwnership,
 }
 
 impl UnsafeBuffer {
@@ -70,6 +77,46 @@ impl UnsafeBuffer {
                              sharing: Sharing<I>, sparse: SparseLevel)
                              -> Result<(UnsafeBuffer, MemoryRequirements), BufferCreationError>
         where I: Iterator<Item = u32>
+    {
+        UnsafeBuffer::new_impl(device, size, usage, sharing, sparse,
+                               ExternalMemoryHandleType::none())
+    }
+
+    /// Same as `new`, but the memory bound to the buffer can be exported or imported with the
+    /// given handle types.
+    ///
+    /// The `VK_KHR_external_memory` extension must be enabled on the device unless it uses Vulkan
+    /// 1.1. The memory must then be allocated with `DeviceMemory::alloc_exportable` or
+    /// `DeviceMemory::import_fd` using the same handle types.
+    ///
+    /// # Panic
+    ///
+    /// - Panics for the same reasons as `new`.
+    /// - Panics if `handle_types` is empty.
+    ///
+    pub unsafe fn with_external_memory<'a, I>(device: Arc<Device>, size: usize,
+                                              usage: BufferUsage, sharing: Sharing<I>,
+                                              sparse: SparseLevel,
+                                              handle_types: ExternalMemoryHandleType)
+                                              -> Result<(UnsafeBuffer, MemoryRequirements),
+                                                        BufferCreationError>
+        where I: Iterator<Item = u32>
+    {
+        assert!(!handle_types.is_empty());
+
+        if device.api_version() < Version::V1_1 && !device.loaded_extensions().khr_external_memory {
+            let err = DeviceMemoryAllocError::MissingExtension { name: "VK_KHR_external_memory" };
+            return Err(BufferCreationError::AllocError(err));
+        }
+
+        UnsafeBuffer::new_impl(device, size, usage, sharing, sparse, handle_types)
+    }
+
+    unsafe fn new_impl<'a, I>(device: Arc<Device>, size: usize, usage: BufferUsage,
+                              sharing: Sharing<I>, sparse: SparseLevel,
+                              external_handle_types: ExternalMemoryHandleType)
+                              -> Result<(UnsafeBuffer, MemoryRequirements), BufferCreationError>
+        where I: Iterator<Item = u32>
     {
         let vk = device.pointers();
 
@@ -102,15 +149,33 @@ impl UnsafeBuffer {
             return Err(BufferCreationError::SparseResidencyAliasedFeatureNotEnabled);
         }
 
+        let concurrent = match sharing {
+            Sharing::Exclusive => false,
+            Sharing::Concurrent(_) => true,
+        };
+
         let buffer = {
             let (sh_mode, sh_indices) = match sharing {
                 Sharing::Exclusive => (vk::SHARING_MODE_EXCLUSIVE, SmallVec::<[u32; 8]>::new()),
                 Sharing::Concurrent(ids) => (vk::SHARING_MODE_CONCURRENT, ids.collect()),
             };
 
+            let external_infos = if !external_handle_types.is_empty() {
+                Some(vk::ExternalMemoryBufferCreateInfoKHR {
+                         sType: vk::STRUCTURE_TYPE_EXTERNAL_MEMORY_BUFFER_CREATE_INFO_KHR,
+                         pNext: ptr::null(),
+                         handleTypes: external_handle_types.to_bits(),
+                     })
+            } else {
+                None
+            };
+
             let infos = vk::BufferCreateInfo {
                 sType: vk::STRUCTURE_TYPE_BUFFER_CREATE_INFO,
-                pNext: ptr::null(),
+                pNext: external_infos
+                    .as_ref()
+                    .map(|i| i as *const vk::ExternalMemoryBufferCreateInfoKHR)
+                    .unwrap_or(ptr::null()) as *const _,
                 flags: sparse.to_flags(),
                 size: size as u64,
                 usage: usage_bits,
@@ -133,14 +198,18 @@ impl UnsafeBuffer {
                 al * (1 + (val - 1) / al)
             }
 
-            let mut output = if device.loaded_extensions().khr_get_memory_requirements2 {
+            let mut output = if device.api_version() >= Version::V1_1 ||
+                device.loaded_extensions().khr_get_memory_requirements2
+            {
                 let infos = vk::BufferMemoryRequirementsInfo2KHR {
                     sType: vk::STRUCTURE_TYPE_BUFFER_MEMORY_REQUIREMENTS_INFO_2_KHR,
                     pNext: ptr::null_mut(),
                     buffer: buffer,
                 };
 
-                let mut output2 = if device.loaded_extensions().khr_dedicated_allocation {
+                let mut output2 = if device.api_version() >= Version::V1_1 ||
+                    device.loaded_extensions().khr_dedicated_allocation
+                {
                     Some(vk::MemoryDedicatedRequirementsKHR {
                              sType: vk::STRUCTURE_TYPE_MEMORY_DEDICATED_REQUIREMENTS_KHR,
                              pNext: ptr::null(),
@@ -166,8 +235,11 @@ impl UnsafeBuffer {
 
                 let mut out = MemoryRequirements::from_vulkan_reqs(output.memoryRequirements);
                 if let Some(output2) = output2 {
-                    debug_assert_eq!(output2.requiresDedicatedAllocation, 0);
-                    out.prefer_dedicated = output2.prefersDedicatedAllocation != 0;
+                    // Only external memory can require a dedicated allocation.
+                    debug_assert!(output2.requiresDedicatedAllocation == 0 ||
+                                      !external_handle_types.is_empty());
+                    out.prefer_dedicated = output2.prefersDedicatedAllocation != 0 ||
+                        output2.requiresDedicatedAllocation != 0;
                 }
                 out
 
@@ -205,6 +277,8 @@ impl UnsafeBuffer {
             device: device.clone(),
             size: size as usize,
             usage: usage_bits,
+            concurrent,
+            ownership: QueueFamilyOwnership::default(),
         };
 
         Ok((obj, mem_reqs))
@@ -253,6 +327,18 @@ impl UnsafeBuffer {
         self.size
     }
 
+    /// Returns true if the buffer was created with `Sharing::Concurrent`.
+    #[inline]
+    pub fn concurrent_sharing(&self) -> bool {
+        self.concurrent
+    }
+
+    // Returns the queue family ownership of the buffer.
+    #[inline]
+    pub(crate) fn ownership(&self) -> &QueueFamilyOwnership {
+        &self.ownership
+    }
+
     #[inline]
     pub fn usage_transfer_source(&self) -> bool {
         (self.usage & vk::BUFFER_USAGE_TRANSFER_SRC_BIT) != 0
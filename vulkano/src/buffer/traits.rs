@@ -7,6 +7,7 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+use std::ffi::CStr;
 use std::ops::Range;
 use std::hash::Hash;
 use std::hash::Hasher;
@@ -19,6 +20,7 @@ use image::ImageAccess;
 use memory::Content;
 use sync::AccessError;
 
+use OomError;
 use SafeDeref;
 
 /// Trait for objects that represent a way for the GPU to have access to a buffer or a slice of a
@@ -138,6 +140,16 @@ pub unsafe trait BufferAccess: DeviceOwned {
     ///
     /// Must only be called once per previous lock.
     unsafe fn unlock(&self);
+
+    /// Assigns a human-readable name to the underlying buffer object for debugging purposes.
+    ///
+    /// If `self` is only a slice of a buffer, the name applies to the whole buffer. Does nothing
+    /// if the `VK_EXT_debug_utils` extension isn't enabled on the instance.
+    #[inline]
+    fn set_name(&self, name: &CStr) -> Result<(), OomError> {
+        let buffer = self.inner().buffer;
+        buffer.device().set_object_name(buffer, name)
+    }
 }
 
 /// Inner information about a buffer.
@@ -17,6 +17,7 @@ use vk;
 /// Some methods are provided to build `BufferUsage` structs for some common situations. However
 /// there is no restriction in the combination of BufferUsages that can be enabled.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct BufferUsage {
     pub transfer_source: bool,
     pub transfer_destination: bool,
@@ -211,3 +212,14 @@ impl BitOr for BufferUsage {
         }
     }
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use buffer::BufferUsage;
+
+    #[test]
+    fn serde_round_trip() {
+        assert_serde_round_trip!(BufferUsage::all());
+        assert_serde_round_trip!(BufferUsage::vertex_buffer_transfer_destination());
+    }
+}
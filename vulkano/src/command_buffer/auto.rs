@@ -8,6 +8,7 @@
 // according to those terms.
 
 use std::error;
+use std::ffi::CStr;
 use std::fmt;
 use std::iter;
 use std::mem;
@@ -44,6 +45,9 @@ use command_buffer::sys::UnsafeCommandBufferBuilderImageBlit;
 use command_buffer::sys::UnsafeCommandBufferBuilderImageCopy;
 use command_buffer::validity::*;
 use descriptor::descriptor_set::DescriptorSetsCollection;
+use descriptor::descriptor_set::PersistentDescriptorSetBuildError;
+use descriptor::descriptor_set::PersistentDescriptorSetBuilder;
+use descriptor::descriptor_set::PersistentDescriptorSetResources;
 use descriptor::pipeline_layout::PipelineLayoutAbstract;
 use device::Device;
 use device::DeviceOwned;
@@ -102,6 +106,12 @@ pub struct AutoCommandBufferBuilder<P = StandardCommandPoolBuilder> {
 
     // Flags passed when creating the command buffer.
     flags: Flags,
+
+    // Id of the queue family the command buffer is created for.
+    queue_family: u32,
+
+    // Number of debug labels that have been begun and not yet ended.
+    label_depth: u32,
 }
 
 impl AutoCommandBufferBuilder<StandardCommandPoolBuilder> {
@@ -393,12 +403,20 @@ impl AutoCommandBufferBuilder<StandardCommandPoolBuilder> {
                    secondary_cb,
                    subpass_secondary: false,
                    flags,
+                   queue_family: queue_family.id(),
+                   label_depth: 0,
                })
         }
     }
 }
 
 impl<P> AutoCommandBufferBuilder<P> {
+    // True if the debug label commands can be recorded.
+    #[inline]
+    fn debug_utils_enabled(&self) -> bool {
+        self.inner.device().instance().loaded_extensions().ext_debug_utils
+    }
+
     #[inline]
     fn ensure_outside_render_pass(&self) -> Result<(), AutoCommandBufferBuilderContextError> {
         if self.render_pass.is_none() {
@@ -457,6 +475,10 @@ impl<P> AutoCommandBufferBuilder<P> {
             return Err(AutoCommandBufferBuilderContextError::ForbiddenInsideRenderPass.into());
         }
 
+        if self.label_depth != 0 {
+            return Err(AutoCommandBufferBuilderContextError::UnbalancedDebugLabels.into());
+        }
+
         let submit_state = match self.flags {
             Flags::None => {
                 SubmitState::ExclusiveUse { in_use: AtomicBool::new(false) }
@@ -1303,6 +1325,66 @@ impl<P> AutoCommandBufferBuilder<P> {
         }
     }
 
+    /// Adds a command that opens a debug label region, which tools such as debuggers and
+    /// validation layers use to group the commands that follow.
+    ///
+    /// Every call must be matched by a call to `end_label` before the command buffer is built.
+    /// If the `VK_EXT_debug_utils` extension isn't enabled on the instance, this does nothing
+    /// apart from keeping track of the label.
+    #[inline]
+    pub fn begin_label(mut self, name: &CStr, color: [f32; 4])
+                       -> Result<Self, AutoCommandBufferBuilderContextError> {
+        if !self.graphics_allowed && !self.compute_allowed {
+            return Err(AutoCommandBufferBuilderContextError::NotSupportedByQueueFamily);
+        }
+
+        if self.debug_utils_enabled() {
+            unsafe {
+                self.inner.debug_marker_begin(name, color);
+            }
+        }
+
+        self.label_depth += 1;
+        Ok(self)
+    }
+
+    /// Adds a command that closes the debug label region opened by the last call to
+    /// `begin_label`.
+    #[inline]
+    pub fn end_label(mut self) -> Result<Self, AutoCommandBufferBuilderContextError> {
+        if self.label_depth == 0 {
+            return Err(AutoCommandBufferBuilderContextError::UnbalancedDebugLabels);
+        }
+
+        if self.debug_utils_enabled() {
+            unsafe {
+                self.inner.debug_marker_end();
+            }
+        }
+
+        self.label_depth -= 1;
+        Ok(self)
+    }
+
+    /// Adds a command that inserts a single debug label.
+    ///
+    /// If the `VK_EXT_debug_utils` extension isn't enabled on the instance, this does nothing.
+    #[inline]
+    pub fn insert_label(mut self, name: &CStr, color: [f32; 4])
+                        -> Result<Self, AutoCommandBufferBuilderContextError> {
+        if !self.graphics_allowed && !self.compute_allowed {
+            return Err(AutoCommandBufferBuilderContextError::NotSupportedByQueueFamily);
+        }
+
+        if self.debug_utils_enabled() {
+            unsafe {
+                self.inner.debug_marker_insert(name, color);
+            }
+        }
+
+        Ok(self)
+    }
+
     /// Adds a command that jumps to the next subpass of the current render pass.
     #[inline]
     pub fn next_subpass(mut self, secondary: bool)
@@ -1342,6 +1424,126 @@ impl<P> AutoCommandBufferBuilder<P> {
         }
     }
 
+    /// Adds a command that pushes descriptors to a push descriptor set of a pipeline layout.
+    ///
+    /// `descriptors` is built the same way as a `PersistentDescriptorSet`, by calling
+    /// `PersistentDescriptorSet::start` with the layout of the push descriptor set and then
+    /// `add_buffer`, `add_sampled_image`, etc. No descriptor set is allocated.
+    ///
+    /// If `graphics` is true, the descriptors are pushed for the graphics pipelines, otherwise for
+    /// the compute pipelines. The set must have been created as a push descriptor set, for example
+    /// with `PipelineLayoutDescPushDescriptor`. The `VK_KHR_push_descriptor` extension must be
+    /// enabled on the device.
+    ///
+    /// The pushed descriptors stay valid for all the following draw or dispatch commands that use
+    /// a compatible pipeline layout, until they are pushed again.
+    #[inline]
+    pub fn push_descriptor_set<Pl, R>(mut self, graphics: bool, pipeline_layout: Pl, set_num: u32,
+                                      descriptors: PersistentDescriptorSetBuilder<R>)
+                                      -> Result<Self, PushDescriptorSetError>
+        where Pl: PipelineLayoutAbstract + Send + Sync + 'static,
+              R: PersistentDescriptorSetResources + Send + Sync + 'static
+    {
+        unsafe {
+            if graphics && !self.graphics_allowed || !graphics && !self.compute_allowed {
+                return Err(AutoCommandBufferBuilderContextError::NotSupportedByQueueFamily.into());
+            }
+
+            let (layout, writes, resources) = descriptors.into_push_descriptors()?;
+            check_push_descriptor_set(self.device(), &pipeline_layout, set_num, &layout)?;
+            self.inner.push_descriptor_set(graphics,
+                                           pipeline_layout,
+                                           set_num,
+                                           layout,
+                                           writes,
+                                           resources)?;
+            Ok(self)
+        }
+    }
+
+    /// Adds a command that releases the ownership of a buffer from the queue family of this
+    /// command buffer to `destination`.
+    ///
+    /// This must be the last use of the buffer in this command buffer. Before using the buffer
+    /// on a queue of `destination`, a command buffer submitted to it must call
+    /// `acquire_buffer_ownership` with the queue family of this command buffer as source.
+    ///
+    /// This is only possible for buffers that were created with an exclusive sharing mode, and
+    /// the buffer must cover the whole of its underlying `UnsafeBuffer`. Using the buffer on
+    /// another queue family than the one that acquired it last is an error when submitting.
+    #[inline]
+    pub fn release_buffer_ownership<B>(mut self, buffer: B, destination: QueueFamily)
+                                       -> Result<Self, OwnershipTransferError>
+        where B: BufferAccess + Send + Sync + 'static
+    {
+        unsafe {
+            self.ensure_outside_render_pass()?;
+            check_buffer_ownership_transfer(self.device(), &buffer, self.queue_family,
+                                            destination)?;
+            self.inner
+                .release_buffer_ownership(buffer, self.queue_family, destination.id())?;
+            Ok(self)
+        }
+    }
+
+    /// Adds a command that acquires the ownership of a buffer that was released by `source` to
+    /// the queue family of this command buffer.
+    ///
+    /// This must be the first use of the buffer in this command buffer.
+    #[inline]
+    pub fn acquire_buffer_ownership<B>(mut self, buffer: B, source: QueueFamily)
+                                       -> Result<Self, OwnershipTransferError>
+        where B: BufferAccess + Send + Sync + 'static
+    {
+        unsafe {
+            self.ensure_outside_render_pass()?;
+            check_buffer_ownership_transfer(self.device(), &buffer, self.queue_family, source)?;
+            self.inner
+                .acquire_buffer_ownership(buffer, source.id(), self.queue_family)?;
+            Ok(self)
+        }
+    }
+
+    /// Adds a command that releases the ownership of an image from the queue family of this
+    /// command buffer to `destination`.
+    ///
+    /// The image is transitioned to its final layout requirement before being released. The
+    /// image must have been created with an exclusive sharing mode. See also
+    /// `release_buffer_ownership`.
+    #[inline]
+    pub fn release_image_ownership<I>(mut self, image: I, destination: QueueFamily)
+                                      -> Result<Self, OwnershipTransferError>
+        where I: ImageAccess + Send + Sync + 'static
+    {
+        unsafe {
+            self.ensure_outside_render_pass()?;
+            check_image_ownership_transfer(self.device(), &image, self.queue_family,
+                                           destination)?;
+            self.inner
+                .release_image_ownership(image, self.queue_family, destination.id())?;
+            Ok(self)
+        }
+    }
+
+    /// Adds a command that acquires the ownership of an image that was released by `source` to
+    /// the queue family of this command buffer.
+    ///
+    /// The image is expected to be in its initial layout requirement. This must be the first use
+    /// of the image in this command buffer.
+    #[inline]
+    pub fn acquire_image_ownership<I>(mut self, image: I, source: QueueFamily)
+                                      -> Result<Self, OwnershipTransferError>
+        where I: ImageAccess + Send + Sync + 'static
+    {
+        unsafe {
+            self.ensure_outside_render_pass()?;
+            check_image_ownership_transfer(self.device(), &image, self.queue_family, source)?;
+            self.inner
+                .acquire_image_ownership(image, source.id(), self.queue_family)?;
+            Ok(self)
+        }
+    }
+
     /// Adds a command that writes data to a buffer.
     ///
     /// If `data` is larger than the buffer, only the part of `data` that fits is written. If the
@@ -1749,6 +1951,19 @@ err_gen!(ExecuteCommandsError {
              SyncCommandBufferBuilderError,
          });
 
+err_gen!(OwnershipTransferError {
+             AutoCommandBufferBuilderContextError,
+             CheckOwnershipTransferError,
+             SyncCommandBufferBuilderError,
+         });
+
+err_gen!(PushDescriptorSetError {
+             AutoCommandBufferBuilderContextError,
+             PersistentDescriptorSetBuildError,
+             CheckPushDescriptorSetError,
+             SyncCommandBufferBuilderError,
+         });
+
 err_gen!(UpdateBufferError {
              AutoCommandBufferBuilderContextError,
              CheckUpdateBufferError,
@@ -1781,6 +1996,9 @@ pub enum AutoCommandBufferBuilderContextError {
     /// Tried to use a graphics pipeline whose render pass is incompatible with the current render
     /// pass.
     IncompatibleRenderPass,
+    /// Tried to end a debug label that wasn't begun, or to build a command buffer with debug
+    /// labels that weren't ended.
+    UnbalancedDebugLabels,
 }
 
 impl error::Error for AutoCommandBufferBuilderContextError {
@@ -1816,6 +2034,10 @@ impl error::Error for AutoCommandBufferBuilderContextError {
                 "tried to use a graphics pipeline whose render pass is incompatible with the \
                  current render pass"
             },
+            AutoCommandBufferBuilderContextError::UnbalancedDebugLabels => {
+                "tried to end a debug label that wasn't begun, or to build a command buffer with \
+                 debug labels that weren't ended"
+            },
         }
     }
 }
@@ -1826,3 +2048,249 @@ impl fmt::Display for AutoCommandBufferBuilderContextError {
         write!(fmt, "{}", error::Error::description(self))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use buffer::BufferUsage;
+    use buffer::DeviceLocalBuffer;
+    use command_buffer::AutoCommandBufferBuilder;
+    use command_buffer::AutoCommandBufferBuilderContextError;
+    use command_buffer::BuildError;
+    use command_buffer::CommandBuffer;
+    use command_buffer::CommandBufferExecError;
+    use format::Format;
+    use image::Dimensions;
+    use image::StorageImage;
+    use std::ffi::CString;
+    use std::sync::Arc;
+    use std::sync::Barrier;
+    use std::thread;
+    use sync::AccessError;
+    use sync::GpuFuture;
+
+    #[test]
+    fn balanced_labels() {
+        let (device, queue) = mock_dev_and_queue!();
+        let name = CString::new("pass").unwrap();
+
+        AutoCommandBufferBuilder::new(device, queue.family())
+            .unwrap()
+            .begin_label(&name, [1.0; 4])
+            .unwrap()
+            .insert_label(&name, [1.0; 4])
+            .unwrap()
+            .end_label()
+            .unwrap()
+            .build()
+            .unwrap();
+    }
+
+    #[test]
+    fn end_label_without_begin() {
+        let (device, queue) = mock_dev_and_queue!();
+
+        match AutoCommandBufferBuilder::new(device, queue.family()).unwrap().end_label() {
+            Err(AutoCommandBufferBuilderContextError::UnbalancedDebugLabels) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn unended_label() {
+        let (device, queue) = mock_dev_and_queue!();
+        let name = CString::new("pass").unwrap();
+
+        let builder = AutoCommandBufferBuilder::new(device, queue.family())
+            .unwrap()
+            .begin_label(&name, [1.0; 4])
+            .unwrap();
+
+        match builder.build() {
+            Err(BuildError::AutoCommandBufferBuilderContextError(
+                AutoCommandBufferBuilderContextError::UnbalancedDebugLabels)) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn buffer_ownership_transfer() {
+        let (device, queue, transfer_queue) = mock_dev_and_transfer_queue!();
+        let buffer = DeviceLocalBuffer::<u32>::new(device.clone(),
+                                                   BufferUsage::all(),
+                                                   Some(queue.family()))
+            .unwrap();
+
+        let release = AutoCommandBufferBuilder::new(device.clone(), queue.family())
+            .unwrap()
+            .release_buffer_ownership(buffer.clone(), transfer_queue.family())
+            .unwrap()
+            .build()
+            .unwrap();
+        let acquire = AutoCommandBufferBuilder::new(device.clone(), transfer_queue.family())
+            .unwrap()
+            .acquire_buffer_ownership(buffer.clone(), queue.family())
+            .unwrap()
+            .build()
+            .unwrap();
+
+        release
+            .execute(queue.clone())
+            .unwrap()
+            .then_signal_semaphore_and_flush()
+            .unwrap()
+            .then_execute(transfer_queue.clone(), acquire)
+            .unwrap()
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+
+        // The buffer now belongs to the transfer queue family.
+        let fill = AutoCommandBufferBuilder::new(device.clone(), queue.family())
+            .unwrap()
+            .fill_buffer(buffer.clone(), 0)
+            .unwrap()
+            .build()
+            .unwrap();
+        match fill.execute(queue.clone()) {
+            Err(CommandBufferExecError::AccessError {
+                    error: AccessError::WrongQueueFamilyOwner { owner, requested },
+                    ..
+                }) => {
+                assert_eq!(owner, transfer_queue.family().id());
+                assert_eq!(requested, queue.family().id());
+            },
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn use_released_buffer_after_future_dropped() {
+        let (device, queue, transfer_queue) = mock_dev_and_transfer_queue!();
+        let buffer = DeviceLocalBuffer::<u32>::new(device.clone(),
+                                                   BufferUsage::all(),
+                                                   Some(queue.family()))
+            .unwrap();
+
+        AutoCommandBufferBuilder::new(device.clone(), queue.family())
+            .unwrap()
+            .release_buffer_ownership(buffer.clone(), transfer_queue.family())
+            .unwrap()
+            .build()
+            .unwrap()
+            .execute(queue.clone())
+            .unwrap()
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+
+        let fill = AutoCommandBufferBuilder::new(device.clone(), queue.family())
+            .unwrap()
+            .fill_buffer(buffer.clone(), 0)
+            .unwrap()
+            .build()
+            .unwrap();
+        match fill.execute(queue.clone()) {
+            Err(CommandBufferExecError::AccessError {
+                    error: AccessError::QueueFamilyOwnershipReleased { .. },
+                    ..
+                }) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn acquire_twice_concurrently() {
+        let (device, queue, transfer_queue) = mock_dev_and_transfer_queue!();
+        let buffer = DeviceLocalBuffer::<u32>::new(device.clone(),
+                                                   BufferUsage::all(),
+                                                   Some(queue.family()))
+            .unwrap();
+
+        AutoCommandBufferBuilder::new(device.clone(), queue.family())
+            .unwrap()
+            .release_buffer_ownership(buffer.clone(), transfer_queue.family())
+            .unwrap()
+            .build()
+            .unwrap()
+            .execute(queue.clone())
+            .unwrap()
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+
+        // Both command buffers acquire the ownership released above, but only one of them can be
+        // submitted.
+        let barrier = Arc::new(Barrier::new(2));
+        let threads = (0 .. 2)
+            .map(|_| {
+                let acquire = AutoCommandBufferBuilder::new(device.clone(),
+                                                            transfer_queue.family())
+                    .unwrap()
+                    .acquire_buffer_ownership(buffer.clone(), queue.family())
+                    .unwrap()
+                    .build()
+                    .unwrap();
+                let transfer_queue = transfer_queue.clone();
+                let barrier = barrier.clone();
+                thread::spawn(move || {
+                    barrier.wait();
+                    acquire
+                        .execute(transfer_queue)
+                        .map(|future| future.then_signal_fence_and_flush().unwrap().wait(None))
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut results = threads.into_iter().map(|thread| thread.join().unwrap());
+        match (results.next().unwrap(), results.next().unwrap()) {
+            (Ok(Ok(())), Err(CommandBufferExecError::AccessError { .. })) |
+            (Err(CommandBufferExecError::AccessError { .. }), Ok(Ok(()))) => (),
+            _ => panic!(),
+        }
+
+        // The ownership can't be acquired again.
+        let acquire = AutoCommandBufferBuilder::new(device.clone(), transfer_queue.family())
+            .unwrap()
+            .acquire_buffer_ownership(buffer.clone(), queue.family())
+            .unwrap()
+            .build()
+            .unwrap();
+        match acquire.execute(transfer_queue.clone()) {
+            Err(CommandBufferExecError::AccessError {
+                    error: AccessError::QueueFamilyOwnershipNotReleased,
+                    ..
+                }) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn acquire_without_release() {
+        let (device, queue, transfer_queue) = mock_dev_and_transfer_queue!();
+        let image = StorageImage::new(device.clone(),
+                                      Dimensions::Dim2d {
+                                          width: 4,
+                                          height: 4,
+                                      },
+                                      Format::R8G8B8A8Unorm,
+                                      Some(queue.family()))
+            .unwrap();
+
+        let acquire = AutoCommandBufferBuilder::new(device.clone(), transfer_queue.family())
+            .unwrap()
+            .acquire_image_ownership(image.clone(), queue.family())
+            .unwrap()
+            .build()
+            .unwrap();
+        match acquire.execute(transfer_queue.clone()) {
+            Err(CommandBufferExecError::AccessError {
+                    error: AccessError::QueueFamilyOwnershipNotReleased,
+                    ..
+                }) => (),
+            _ => panic!(),
+        }
+    }
+}
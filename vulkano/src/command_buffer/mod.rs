@@ -90,12 +90,17 @@ pub use self::auto::DrawIndexedIndirectError;
 pub use self::auto::DrawIndirectError;
 pub use self::auto::ExecuteCommandsError;
 pub use self::auto::FillBufferError;
+pub use self::auto::OwnershipTransferError;
+pub use self::auto::PushDescriptorSetError;
 pub use self::auto::UpdateBufferError;
 pub use self::state_cacher::StateCacher;
 pub use self::state_cacher::StateCacherOutcome;
 pub use self::traits::CommandBuffer;
 pub use self::traits::CommandBufferExecError;
 pub use self::traits::CommandBufferExecFuture;
+pub use self::upload::UploadError;
+pub use self::upload::UploadFuture;
+pub use self::upload::UploadManager;
 
 use pipeline::viewport::Scissor;
 use pipeline::viewport::Viewport;
@@ -110,6 +115,7 @@ pub mod validity;
 mod auto;
 mod state_cacher;
 mod traits;
+mod upload;
 
 #[repr(C)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -24,6 +24,7 @@ use VulkanObject;
 use check_errors;
 use device::Device;
 use device::DeviceOwned;
+use version::Version;
 use vk;
 
 /// Low-level implementation of a command pool.
@@ -135,13 +136,16 @@ impl UnsafeCommandPool {
     ///
     /// Command buffers allocated from the pool are not affected by trimming.
     ///
-    /// This function is supported only if the `VK_KHR_maintenance1` extension was enabled at
-    /// device creation. Otherwise an error is returned.
+    /// This function is supported only if the device uses Vulkan 1.1 or if the
+    /// `VK_KHR_maintenance1` extension was enabled at device creation. Otherwise an error is
+    /// returned.
     /// Since this operation is purely an optimization it is legitimate to call this function and
     /// simply ignore any possible error.
     pub fn trim(&self) -> Result<(), CommandPoolTrimError> {
         unsafe {
-            if !self.device.loaded_extensions().khr_maintenance1 {
+            if self.device.api_version() < Version::V1_1 &&
+                !self.device.loaded_extensions().khr_maintenance1
+            {
                 return Err(CommandPoolTrimError::Maintenance1ExtensionNotEnabled);
             }
 
@@ -318,21 +322,21 @@ mod tests {
 
     #[test]
     fn basic_create() {
-        let (device, queue) = gfx_dev_and_queue!();
+        let (device, queue) = mock_dev_and_queue!();
         let _ = UnsafeCommandPool::new(device, queue.family(), false, false).unwrap();
     }
 
     #[test]
     fn queue_family_getter() {
-        let (device, queue) = gfx_dev_and_queue!();
+        let (device, queue) = mock_dev_and_queue!();
         let pool = UnsafeCommandPool::new(device, queue.family(), false, false).unwrap();
         assert_eq!(pool.queue_family().id(), queue.family().id());
     }
 
     #[test]
     fn panic_if_not_match_family() {
-        let (device, _) = gfx_dev_and_queue!();
-        let (_, queue) = gfx_dev_and_queue!();
+        let (device, _) = mock_dev_and_queue!();
+        let (_, queue) = mock_dev_and_queue!();
 
         assert_should_panic!("Device doesn't match physical device when creating a command pool", {
             let _ = UnsafeCommandPool::new(device, queue.family(), false, false);
@@ -341,7 +345,7 @@ mod tests {
 
     #[test]
     fn check_maintenance_when_trim() {
-        let (device, queue) = gfx_dev_and_queue!();
+        let (device, queue) = mock_dev_and_queue!();
         let pool = UnsafeCommandPool::new(device, queue.family(), false, false).unwrap();
 
         match pool.trim() {
@@ -355,7 +359,7 @@ mod tests {
 
     #[test]
     fn basic_alloc() {
-        let (device, queue) = gfx_dev_and_queue!();
+        let (device, queue) = mock_dev_and_queue!();
         let pool = UnsafeCommandPool::new(device, queue.family(), false, false).unwrap();
         let iter = pool.alloc_command_buffers(false, 12).unwrap();
         assert_eq!(iter.count(), 12);
@@ -299,12 +299,13 @@ impl From<Error> for SubmitCommandBufferError {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use mock::Recorder;
     use std::time::Duration;
     use sync::Fence;
 
     #[test]
     fn empty_submit() {
-        let (device, queue) = gfx_dev_and_queue!();
+        let (device, queue) = mock_dev_and_queue!();
         let builder = SubmitCommandBufferBuilder::new();
         builder.submit(&queue).unwrap();
     }
@@ -312,7 +313,7 @@ mod tests {
     #[test]
     fn signal_fence() {
         unsafe {
-            let (device, queue) = gfx_dev_and_queue!();
+            let (device, queue) = mock_dev_and_queue!();
 
             let fence = Fence::alloc(device.clone()).unwrap();
             assert!(!fence.ready().unwrap());
@@ -323,13 +324,17 @@ mod tests {
             builder.submit(&queue).unwrap();
             fence.wait(Some(Duration::from_secs(5))).unwrap();
             assert!(fence.ready().unwrap());
+
+            let submissions = Recorder::of(device.instance()).unwrap().submissions();
+            assert_eq!(submissions.len(), 1);
+            assert_eq!(submissions[0].fence, fence.internal_object());
         }
     }
 
     #[test]
     fn has_fence() {
         unsafe {
-            let (device, queue) = gfx_dev_and_queue!();
+            let (device, queue) = mock_dev_and_queue!();
 
             let fence = Fence::alloc(device.clone()).unwrap();
 
@@ -343,7 +348,7 @@ mod tests {
     #[test]
     fn merge_both_have_fences() {
         unsafe {
-            let (device, _) = gfx_dev_and_queue!();
+            let (device, _) = mock_dev_and_queue!();
 
             let fence1 = Fence::alloc(device.clone()).unwrap();
             let fence2 = Fence::alloc(device.clone()).unwrap();
@@ -153,6 +153,14 @@ pub enum SyncCommandBufferBuilderError {
         command2_param: Cow<'static, str>,
         command2_offset: usize,
     },
+
+    /// A resource was used after its queue family ownership has been released, or its ownership
+    /// was acquired after it has already been used by the command buffer.
+    QueueFamilyOwnership {
+        command_name: &'static str,
+        command_param: Cow<'static, str>,
+        command_offset: usize,
+    },
 }
 
 impl error::Error for SyncCommandBufferBuilderError {
@@ -162,6 +170,9 @@ impl error::Error for SyncCommandBufferBuilderError {
             SyncCommandBufferBuilderError::Conflict { .. } => {
                 "unsolvable conflict"
             },
+            SyncCommandBufferBuilderError::QueueFamilyOwnership { .. } => {
+                "invalid use of a resource relative to its queue family ownership transfer"
+            },
         }
     }
 }
@@ -362,6 +373,14 @@ struct ResourceState {
 
     // Current layout at this stage of the building.
     current_layout: ImageLayout,
+
+    // If the command buffer acquires the queue family ownership of the resource, contains the
+    // source and destination queue families. The acquire is always the first use of the resource.
+    acquire: Option<(u32, u32)>,
+
+    // If the command buffer releases the queue family ownership of the resource, contains the
+    // source and destination queue families. The release is always the last use of the resource.
+    release: Option<(u32, u32)>,
 }
 
 impl ResourceState {
@@ -375,6 +394,8 @@ impl ResourceState {
             exclusive: self.exclusive_any,
             initial_layout: self.initial_layout,
             final_layout: self.current_layout,
+            acquire: self.acquire,
+            release: self.release,
         }
     }
 }
@@ -506,6 +527,20 @@ impl<P> SyncCommandBufferBuilder<P> {
 
             // Situation where this resource was used before in this command buffer.
             Entry::Occupied(entry) => {
+                // The resource can't be used anymore once its ownership has been released.
+                if entry.get().release.is_some() {
+                    let commands_lock = self.commands.lock().unwrap();
+                    let cmd = &commands_lock.commands[latest_command_id];
+                    return Err(SyncCommandBufferBuilderError::QueueFamilyOwnership {
+                                   command_name: cmd.name(),
+                                   command_param: match resource_ty {
+                                       KeyTy::Buffer => cmd.buffer_name(resource_index),
+                                       KeyTy::Image => cmd.image_name(resource_index),
+                                   },
+                                   command_offset: latest_command_id,
+                               });
+                }
+
                 // `collision_cmd_id` contains the ID of the command that we are potentially
                 // colliding with.
                 let collision_cmd_ids = entry.key().command_ids.borrow().clone();
@@ -708,6 +743,8 @@ impl<P> SyncCommandBufferBuilder<P> {
                     exclusive: actually_exclusive,
                     initial_layout: actual_start_layout,
                     current_layout: end_layout,     // TODO: what if we reach the end with Undefined? that's not correct?
+                    acquire: None,
+                    release: None,
                 });
             },
         }
@@ -716,6 +753,198 @@ impl<P> SyncCommandBufferBuilder<P> {
         Ok(())
     }
 
+    // Call this when the previous command entered into `commands` transfers the queue family
+    // ownership of a resource, either by releasing it (`release` is true) or by acquiring it.
+    //
+    // Contrary to `prev_cmd_resource`, the pipeline barrier that performs the transfer is recorded
+    // immediately after flushing all the pending commands. A release must be the last use of the
+    // resource in the command buffer, and an acquire must be the first one.
+    pub(super) fn prev_cmd_ownership_transfer(&mut self, resource_ty: KeyTy,
+                                              resource_index: usize, release: bool,
+                                              source: u32, destination: u32)
+                                              -> Result<(), SyncCommandBufferBuilderError> {
+        let latest_command_id = {
+            let commands_lock = self.commands.lock().unwrap();
+            debug_assert!(commands_lock.commands.len() >= 1);
+            debug_assert!(commands_lock.latest_render_pass_enter.is_none());
+            commands_lock.commands.len() - 1
+        };
+
+        let key = BuilderKey {
+            commands: self.commands.clone(),
+            command_ids: RefCell::new(vec![latest_command_id]),
+            resource_ty,
+            resource_index,
+        };
+
+        // Stages, accesses and layout of the resource before the transfer, if it was already used
+        // by this command buffer.
+        let prev_state = match self.resources.get(&key) {
+            Some(state) if release && state.release.is_none() => {
+                Some((state.stages, state.access, state.current_layout))
+            },
+            None => None,
+            Some(_) => {
+                let commands_lock = self.commands.lock().unwrap();
+                let cmd = &commands_lock.commands[latest_command_id];
+                return Err(SyncCommandBufferBuilderError::QueueFamilyOwnership {
+                               command_name: cmd.name(),
+                               command_param: match resource_ty {
+                                   KeyTy::Buffer => cmd.buffer_name(resource_index),
+                                   KeyTy::Image => cmd.image_name(resource_index),
+                               },
+                               command_offset: latest_command_id,
+                           });
+            },
+        };
+
+        unsafe {
+            // Flush the pending barrier and all the commands, including the transfer itself.
+            self.inner.pipeline_barrier(&self.pending_barrier);
+            self.pending_barrier = UnsafeCommandBufferBuilderPipelineBarrier::new();
+
+            let mut commands_lock = self.commands.lock().unwrap();
+            let start = commands_lock.first_unflushed;
+            let end = commands_lock.commands.len();
+            for command in &mut commands_lock.commands[start .. end] {
+                command.send(&mut self.inner);
+            }
+            commands_lock.first_unflushed = end;
+        }
+
+        let (src_stages, src_access) = match prev_state {
+            Some((stages, access, _)) => (stages, access),
+            None if release => {
+                (PipelineStages { all_commands: true, ..PipelineStages::none() },
+                 AccessFlagBits { memory_write: true, ..AccessFlagBits::none() })
+            },
+            None => {
+                (PipelineStages { top_of_pipe: true, ..PipelineStages::none() },
+                 AccessFlagBits::none())
+            },
+        };
+        let (dst_stages, dst_access) = if release {
+            (PipelineStages { bottom_of_pipe: true, ..PipelineStages::none() },
+             AccessFlagBits::none())
+        } else {
+            (PipelineStages { all_commands: true, ..PipelineStages::none() },
+             AccessFlagBits { memory_read: true, memory_write: true, ..AccessFlagBits::none() })
+        };
+
+        // The layout of an image during the transfer must be the same on both queues, therefore
+        // we use the layout requirements of the image.
+        let layout = unsafe {
+            let commands_lock = self.commands.lock().unwrap();
+            let cmd = &commands_lock.commands[latest_command_id];
+            let mut barrier = UnsafeCommandBufferBuilderPipelineBarrier::new();
+
+            match resource_ty {
+                KeyTy::Buffer => {
+                    let buf = cmd.buffer(resource_index);
+                    barrier.add_buffer_memory_barrier(buf, src_stages, src_access, dst_stages,
+                                                      dst_access, true,
+                                                      Some((source, destination)), 0,
+                                                      buf.size());
+                    self.inner.pipeline_barrier(&barrier);
+                    ImageLayout::Undefined
+                },
+
+                KeyTy::Image => {
+                    let img = cmd.image(resource_index);
+                    let layout = if release {
+                        img.final_layout_requirement()
+                    } else {
+                        img.initial_layout_requirement()
+                    };
+
+                    let current_layout = match prev_state {
+                        Some((_, _, current_layout)) => current_layout,
+                        None if !img.is_layout_initialized() => {
+                            if img.preinitialized_layout() {
+                                ImageLayout::Preinitialized
+                            } else {
+                                ImageLayout::Undefined
+                            }
+                        },
+                        None => img.initial_layout_requirement(),
+                    };
+
+                    // Releasing the image must not change its layout, so we transition it
+                    // beforehand if necessary.
+                    if release && current_layout != layout {
+                        let mut transition = UnsafeCommandBufferBuilderPipelineBarrier::new();
+                        transition.add_image_memory_barrier(img,
+                                                            0 .. img.mipmap_levels(),
+                                                            0 .. img.dimensions().array_layers(),
+                                                            src_stages,
+                                                            src_access,
+                                                            PipelineStages {
+                                                                all_commands: true,
+                                                                ..PipelineStages::none()
+                                                            },
+                                                            AccessFlagBits {
+                                                                memory_write: true,
+                                                                ..AccessFlagBits::none()
+                                                            },
+                                                            true,
+                                                            None,
+                                                            current_layout,
+                                                            layout);
+                        self.inner.pipeline_barrier(&transition);
+                    }
+
+                    barrier.add_image_memory_barrier(img,
+                                                     0 .. img.mipmap_levels(),
+                                                     0 .. img.dimensions().array_layers(),
+                                                     src_stages,
+                                                     src_access,
+                                                     dst_stages,
+                                                     dst_access,
+                                                     true,
+                                                     Some((source, destination)),
+                                                     layout,
+                                                     layout);
+                    self.inner.pipeline_barrier(&barrier);
+                    img.layout_initialized();
+                    layout
+                },
+            }
+        };
+
+        // Update state.
+        match self.resources.entry(key) {
+            Entry::Occupied(entry) => {
+                debug_assert!(release);
+                entry.key().command_ids.borrow_mut().push(latest_command_id);
+                let entry = entry.into_mut();
+                entry.stages = dst_stages;
+                entry.access = dst_access;
+                entry.exclusive_any = true;
+                entry.exclusive = true;
+                entry.current_layout = layout;
+                entry.release = Some((source, destination));
+            },
+            Entry::Vacant(entry) => {
+                entry.insert(ResourceState {
+                    stages: if release {
+                        dst_stages
+                    } else {
+                        PipelineStages { top_of_pipe: true, ..PipelineStages::none() }
+                    },
+                    access: AccessFlagBits::none(),
+                    exclusive_any: true,
+                    exclusive: true,
+                    initial_layout: layout,
+                    current_layout: layout,
+                    acquire: if release { None } else { Some((source, destination)) },
+                    release: if release { Some((source, destination)) } else { None },
+                });
+            },
+        }
+
+        Ok(())
+    }
+
     /// Builds the command buffer and turns it into a `SyncCommandBuffer`.
     #[inline]
     pub fn build(mut self) -> Result<SyncCommandBuffer<P::Alloc>, OomError>
@@ -745,6 +974,11 @@ impl<P> SyncCommandBufferBuilder<P> {
                         continue;
                     }
 
+                    // The layout of a released image has already been handled by the release.
+                    if state.release.is_some() {
+                        continue;
+                    }
+
                     let img = commands_lock.commands[key.command_ids.borrow()[0]].image(key.resource_index);
                     let requested_layout = img.final_layout_requirement();
                     if requested_layout == state.current_layout {
@@ -840,6 +1074,14 @@ struct ResourceFinalState {
 
     // Layout the image will be in at the end of the command buffer.
     final_layout: ImageLayout, // TODO: maybe wrap in an Option to mean that the layout doesn't change? because of buffers?
+
+    // Queue family ownership acquired at the start of the command buffer, as
+    // `(source, destination)`.
+    acquire: Option<(u32, u32)>,
+
+    // Queue family ownership released at the end of the command buffer, as
+    // `(source, destination)`.
+    release: Option<(u32, u32)>,
 }
 
 /// Equivalent to `Command`, but with less methods. Typically contains less things than the
@@ -1110,6 +1352,17 @@ impl<P> SyncCommandBuffer<P> {
                     let cmd = &commands_lock[command_ids[0]];
                     let buf = cmd.buffer(resource_index);
 
+                    let ownership = buf.inner().buffer.ownership();
+                    if let Err(err) = ownership.check(queue.family().id(), entry.acquire) {
+                        ret_value = Err(CommandBufferExecError::AccessError {
+                                            error: err,
+                                            command_name: cmd.name().into(),
+                                            command_param: cmd.buffer_name(resource_index),
+                                            command_offset: command_ids[0],
+                                        });
+                        break;
+                    }
+
                     // Because try_gpu_lock needs to be called first,
                     // this should never return Ok without first returning Err
                     let prev_err = match future.check_buffer_access(&buf, entry.exclusive, queue) {
@@ -1144,6 +1397,17 @@ impl<P> SyncCommandBuffer<P> {
                     let cmd = &commands_lock[command_ids[0]];
                     let img = cmd.image(resource_index);
 
+                    let ownership = img.inner().image.ownership();
+                    if let Err(err) = ownership.check(queue.family().id(), entry.acquire) {
+                        ret_value = Err(CommandBufferExecError::AccessError {
+                                            error: err,
+                                            command_name: cmd.name().into(),
+                                            command_param: cmd.image_name(resource_index),
+                                            command_offset: command_ids[0],
+                                        });
+                        break;
+                    }
+
                     let prev_err = match future.check_image_access(img, entry.initial_layout,
                                                                    entry.exclusive, queue)
                     {
@@ -1174,6 +1438,67 @@ impl<P> SyncCommandBuffer<P> {
             }
         }
 
+        // Record the queue family ownership transfers. Another submission may have transferred
+        // the ownership of a resource since it was checked above, so the ownerships are checked
+        // again while they are locked, and are only unlocked once every transfer is recorded.
+        // They are locked in the order of their addresses, so that two submissions can't
+        // deadlock.
+        if ret_value.is_ok() {
+            let mut transfers = Vec::new();
+            for (key, entry) in self.resources.iter() {
+                if entry.acquire.is_none() && entry.release.is_none() {
+                    continue;
+                }
+
+                let (command_ids, resource_ty, resource_index) = match *key {
+                    CbKey::Command {
+                        ref command_ids,
+                        resource_ty,
+                        resource_index,
+                        ..
+                    } => {
+                        (command_ids, resource_ty, resource_index)
+                    },
+                    _ => unreachable!(),
+                };
+
+                let cmd = &commands_lock[command_ids[0]];
+                let (ownership, command_param) = match resource_ty {
+                    KeyTy::Buffer => (cmd.buffer(resource_index).inner().buffer.ownership(),
+                                      cmd.buffer_name(resource_index)),
+                    KeyTy::Image => (cmd.image(resource_index).inner().image.ownership(),
+                                     cmd.image_name(resource_index)),
+                };
+                transfers.push((ownership, entry, command_ids[0], command_param));
+            }
+
+            transfers.sort_by_key(|&(ownership, ..)| ownership as *const _ as usize);
+            let mut locks = transfers
+                .iter()
+                .map(|&(ownership, ..)| ownership.lock())
+                .collect::<Vec<_>>();
+
+            for (lock, &(_, entry, command_id, ref command_param)) in
+                locks.iter().zip(transfers.iter())
+            {
+                if let Err(err) = lock.check(queue.family().id(), entry.acquire) {
+                    ret_value = Err(CommandBufferExecError::AccessError {
+                                        error: err,
+                                        command_name: commands_lock[command_id].name().into(),
+                                        command_param: command_param.clone(),
+                                        command_offset: command_id,
+                                    });
+                    break;
+                }
+            }
+
+            if ret_value.is_ok() {
+                for (lock, &(_, entry, ..)) in locks.iter_mut().zip(transfers.iter()) {
+                    lock.transfer(entry.acquire, entry.release);
+                }
+            }
+        }
+
         // If we are going to return an error, we have to unlock all the resources we locked above.
         if let Err(_) = ret_value {
             for key in self.resources.keys().take(locked_resources) {
@@ -1265,8 +1590,7 @@ impl<P> SyncCommandBuffer<P> {
     pub fn check_buffer_access(
         &self, buffer: &dyn BufferAccess, exclusive: bool, queue: &Queue)
         -> Result<Option<(PipelineStages, AccessFlagBits)>, AccessCheckError> {
-        // TODO: check the queue family
-
+        // The queue family ownership of the resource is checked by `lock_submit`.
         if let Some(value) = self.resources.get(&CbKey::BufferRef(buffer)) {
             if !value.exclusive && exclusive {
                 return Err(AccessCheckError::Unknown);
@@ -1285,8 +1609,7 @@ impl<P> SyncCommandBuffer<P> {
     pub fn check_image_access(
         &self, image: &dyn ImageAccess, layout: ImageLayout, exclusive: bool, queue: &Queue)
         -> Result<Option<(PipelineStages, AccessFlagBits)>, AccessCheckError> {
-        // TODO: check the queue family
-
+        // The queue family ownership of the resource is checked by `lock_submit`.
         if let Some(value) = self.resources.get(&CbKey::ImageRef(image)) {
             if layout != ImageLayout::Undefined && value.final_layout != layout {
                 return Err(AccessCheckError::Denied(AccessError::UnexpectedImageLayout {
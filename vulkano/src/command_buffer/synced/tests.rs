@@ -20,7 +20,7 @@ use device::Device;
 #[test]
 fn basic_creation() {
     unsafe {
-        let (device, queue) = gfx_dev_and_queue!();
+        let (device, queue) = mock_dev_and_queue!();
         let pool = Device::standard_command_pool(&device, queue.family());
         SyncCommandBufferBuilder::new(&pool, Kind::primary(), Flags::None).unwrap();
     }
@@ -29,7 +29,7 @@ fn basic_creation() {
 #[test]
 fn basic_conflict() {
     unsafe {
-        let (device, queue) = gfx_dev_and_queue!();
+        let (device, queue) = mock_dev_and_queue!();
 
         let pool = Device::standard_command_pool(&device, queue.family());
         let mut sync = SyncCommandBufferBuilder::new(&pool, Kind::primary(), Flags::None).unwrap();
@@ -42,3 +42,39 @@ fn basic_conflict() {
         };
     }
 }
+
+#[test]
+fn use_after_ownership_release() {
+    unsafe {
+        let (device, queue) = mock_dev_and_queue!();
+
+        let pool = Device::standard_command_pool(&device, queue.family());
+        let mut sync = SyncCommandBufferBuilder::new(&pool, Kind::primary(), Flags::None).unwrap();
+
+        let buf = CpuAccessibleBuffer::from_data(device, BufferUsage::all(), false, 0u32).unwrap();
+
+        sync.release_buffer_ownership(buf.clone(), 0, 1).unwrap();
+        match sync.copy_buffer(buf.clone(), buf.clone(), iter::once((0, 0, 4))) {
+            Err(SyncCommandBufferBuilderError::QueueFamilyOwnership { .. }) => (),
+            _ => panic!(),
+        };
+    }
+}
+
+#[test]
+fn acquire_ownership_after_use() {
+    unsafe {
+        let (device, queue) = mock_dev_and_queue!();
+
+        let pool = Device::standard_command_pool(&device, queue.family());
+        let mut sync = SyncCommandBufferBuilder::new(&pool, Kind::primary(), Flags::None).unwrap();
+
+        let buf = CpuAccessibleBuffer::from_data(device, BufferUsage::all(), false, 0u32).unwrap();
+
+        sync.fill_buffer(buf.clone(), 0);
+        match sync.acquire_buffer_ownership(buf.clone(), 1, 0) {
+            Err(SyncCommandBufferBuilderError::QueueFamilyOwnership { .. }) => (),
+            _ => panic!(),
+        };
+    }
+}
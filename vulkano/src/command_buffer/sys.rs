@@ -24,7 +24,9 @@ use command_buffer::pool::CommandPool;
 use command_buffer::pool::CommandPoolAlloc;
 use command_buffer::pool::CommandPoolBuilderAlloc;
 use descriptor::descriptor::ShaderStages;
+use descriptor::descriptor_set::DescriptorWrite;
 use descriptor::descriptor_set::UnsafeDescriptorSet;
+use descriptor::descriptor_set::with_raw_writes;
 use descriptor::pipeline_layout::PipelineLayoutAbstract;
 use device::Device;
 use device::DeviceOwned;
@@ -1272,6 +1274,45 @@ impl<P> UnsafeCommandBufferBuilder<P> {
                             data as *const D as *const _);
     }
 
+    /// Calls `vkCmdPushDescriptorSetKHR` on the builder.
+    ///
+    /// Does nothing if the list of writes is empty.
+    #[inline]
+    pub unsafe fn push_descriptor_set<Pl, I>(&mut self, graphics: bool, pipeline_layout: &Pl,
+                                             set_num: u32, writes: I)
+        where Pl: ?Sized + PipelineLayoutAbstract,
+              I: Iterator<Item = DescriptorWrite>
+    {
+        let vk = self.device().pointers();
+        let cmd = self.internal_object();
+
+        debug_assert!(self.device().loaded_extensions().khr_push_descriptor);
+        debug_assert!(pipeline_layout
+                          .descriptor_set_layout(set_num as usize)
+                          .map(|l| l.is_push_descriptor())
+                          .unwrap_or(false));
+
+        let bind_point = if graphics {
+            vk::PIPELINE_BIND_POINT_GRAPHICS
+        } else {
+            vk::PIPELINE_BIND_POINT_COMPUTE
+        };
+
+        // The `dstSet` member of the writes is ignored for push descriptors.
+        with_raw_writes(0, writes, |raw_writes| {
+            if raw_writes.is_empty() {
+                return;
+            }
+
+            vk.CmdPushDescriptorSetKHR(cmd,
+                                       bind_point,
+                                       pipeline_layout.sys().internal_object(),
+                                       set_num,
+                                       raw_writes.len() as u32,
+                                       raw_writes.as_ptr());
+        });
+    }
+
     /// Calls `vkCmdResetEvent` on the builder.
     #[inline]
     pub unsafe fn reset_event(&mut self, event: &Event, stages: PipelineStages) {
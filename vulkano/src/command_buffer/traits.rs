@@ -9,12 +9,14 @@
 
 use std::borrow::Cow;
 use std::error;
+use std::ffi::CStr;
 use std::fmt;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
 
+use OomError;
 use SafeDeref;
 use VulkanObject;
 use buffer::BufferAccess;
@@ -42,6 +44,14 @@ pub unsafe trait CommandBuffer: DeviceOwned {
     /// Returns the underlying `UnsafeCommandBuffer` of this command buffer.
     fn inner(&self) -> &UnsafeCommandBuffer<Self::PoolAlloc>;
 
+    /// Assigns a human-readable name to this command buffer for debugging purposes.
+    ///
+    /// Does nothing if the `VK_EXT_debug_utils` extension isn't enabled on the instance.
+    #[inline]
+    fn set_name(&self, name: &CStr) -> Result<(), OomError> {
+        self.device().set_object_name(self.inner(), name)
+    }
+
     /*/// Returns the queue family of the command buffer.
     #[inline]
     fn queue_family(&self) -> QueueFamily
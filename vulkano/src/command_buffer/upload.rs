@@ -0,0 +1,811 @@
+// Copyright (c) 2020 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Batched uploads of immutable resources on a dedicated transfer queue.
+//!
+//! `ImmutableBuffer::from_iter` and `ImmutableImage::from_iter` each submit their own command
+//! buffer on the queue that is passed to them. When uploading lots of resources, for example
+//! while streaming a level, this adds many small submissions to a queue that is usually also
+//! used for rendering.
+//!
+//! The `UploadManager` instead records all the uploads in a single command buffer that is
+//! submitted on a transfer queue, and hands over the queue family ownership of the uploaded
+//! resources to the queue that will use them.
+//!
+//! ```
+//! use vulkano::buffer::BufferUsage;
+//! use vulkano::command_buffer::UploadManager;
+//! use vulkano::sync::GpuFuture;
+//!
+//! # let transfer_queue: std::sync::Arc<vulkano::device::Queue> = return;
+//! # let graphics_queue: std::sync::Arc<vulkano::device::Queue> = return;
+//! let mut uploads = UploadManager::new(transfer_queue, graphics_queue).unwrap();
+//!
+//! let (vertices, vertices_future) =
+//!     uploads.buffer_from_iter((0 .. 128).map(|n| n as f32), BufferUsage::vertex_buffer())
+//!            .unwrap();
+//! let (indices, indices_future) =
+//!     uploads.buffer_from_iter((0 .. 128).map(|n| n as u32), BufferUsage::index_buffer())
+//!            .unwrap();
+//!
+//! // Both uploads are submitted together.
+//! uploads.flush().unwrap();
+//!
+//! // Submit your operations that use `vertices` and `indices` after the futures.
+//! let _future = vertices_future.join(indices_future);
+//! ```
+
+use std::error;
+use std::fmt;
+use std::iter;
+use std::mem;
+use std::ptr;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use OomError;
+use VulkanObject;
+use buffer::BufferAccess;
+use buffer::BufferSlice;
+use buffer::BufferUsage;
+use buffer::CpuBufferPool;
+use buffer::ImmutableBuffer;
+use buffer::TypedBufferAccess;
+use buffer::cpu_pool::CpuBufferPoolChunk;
+use buffer::immutable::ImmutableBufferInitialization;
+use command_buffer::AutoCommandBufferBuilder;
+use command_buffer::BuildError;
+use command_buffer::CommandBufferExecError;
+use command_buffer::CopyBufferError;
+use command_buffer::CopyBufferImageError;
+use command_buffer::OwnershipTransferError;
+use command_buffer::submit::SubmitAnyBuilder;
+use command_buffer::validity::CheckCopyBufferImageTy;
+use command_buffer::validity::check_buffer_ownership_transfer;
+use command_buffer::validity::check_copy_buffer;
+use command_buffer::validity::check_copy_buffer_image;
+use command_buffer::validity::check_image_ownership_transfer;
+use device::Device;
+use device::DeviceOwned;
+use device::Queue;
+use format::AcceptsPixels;
+use format::Format;
+use format::FormatDesc;
+use image::Dimensions;
+use image::ImageAccess;
+use image::ImageCreationError;
+use image::ImageLayout;
+use image::ImageUsage;
+use image::ImmutableImage;
+use image::MipmapsCount;
+use memory::DeviceMemoryAllocError;
+use memory::pool::StdMemoryPool;
+use sync::AccessCheckError;
+use sync::AccessFlagBits;
+use sync::FenceSignalFuture;
+use sync::FlushError;
+use sync::GpuFuture;
+use sync::PipelineStages;
+use sync::now;
+
+// Unit of allocation of the staging ring. Copies from a buffer to an image require the offset in
+// the buffer to be a multiple of the texel block size of the format, which can be 1, 2, 3, 4, 6,
+// 8, 12, 16, 24 or 32 bytes. Using blocks of 96 bytes guarantees that all the chunks are properly
+// aligned whatever the format.
+type StagingBlock = [u8; 96];
+
+// Future of a submitted batch, shared between all the `UploadFuture`s of this batch.
+type BatchFuture = Arc<FenceSignalFuture<Box<dyn GpuFuture>>>;
+
+/// Records uploads to immutable resources and submits them in batches on a transfer queue.
+///
+/// Each upload copies the data to a staging ring, then records a copy from the staging ring to
+/// the new resource. All the uploads recorded since the previous batch are submitted at once
+/// when `flush()` is called, or when one of the returned futures is used for the first time.
+///
+/// If the transfer queue and the destination queue belong to different queue families, the
+/// resources are created with an exclusive sharing mode and their ownership is transferred to
+/// the queue family of the destination queue as part of the upload.
+pub struct UploadManager {
+    // Queue on which the copies are performed.
+    transfer_queue: Arc<Queue>,
+
+    // Queue that will use the uploaded resources.
+    destination_queue: Arc<Queue>,
+
+    // Staging ring that holds the data until the copies are finished.
+    staging: CpuBufferPool<StagingBlock>,
+
+    // Batch that is currently being recorded.
+    batch: Arc<UploadBatch>,
+}
+
+impl UploadManager {
+    /// Builds a new `UploadManager` that performs the copies on `transfer_queue`, and whose
+    /// uploaded resources are meant to be used on `destination_queue`.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the two queues don't belong to the same device.
+    ///
+    pub fn new(transfer_queue: Arc<Queue>, destination_queue: Arc<Queue>)
+               -> Result<UploadManager, OomError> {
+        assert_eq!(transfer_queue.device().internal_object(),
+                   destination_queue.device().internal_object());
+
+        let staging = CpuBufferPool::upload(transfer_queue.device().clone());
+        let batch = Arc::new(UploadBatch::new(&transfer_queue, &destination_queue)?);
+
+        Ok(UploadManager {
+               transfer_queue,
+               destination_queue,
+               staging,
+               batch,
+           })
+    }
+
+    /// Returns the queue on which the copies are performed.
+    #[inline]
+    pub fn transfer_queue(&self) -> &Arc<Queue> {
+        &self.transfer_queue
+    }
+
+    /// Returns the queue that will use the uploaded resources.
+    #[inline]
+    pub fn destination_queue(&self) -> &Arc<Queue> {
+        &self.destination_queue
+    }
+
+    /// Builds an `ImmutableBuffer` from some data.
+    ///
+    /// This function returns two objects: the newly-created buffer, and a future representing
+    /// the upload. In order to be allowed to use the buffer, you must submit your operation on
+    /// the destination queue after this future.
+    pub fn buffer_from_data<T>(&mut self, data: T, usage: BufferUsage)
+                               -> Result<(Arc<ImmutableBuffer<T>>, UploadFuture), UploadError>
+        where T: Send + Sync + 'static
+    {
+        let source = self.stage(iter::once(data))?.index(0).unwrap();
+        let (buffer, init) = unsafe { self.create_buffer(mem::size_of::<T>(), usage)? };
+        self.upload_buffer(source, buffer, init)
+    }
+
+    /// Builds an `ImmutableBuffer` from an iterator of data.
+    ///
+    /// See also `buffer_from_data`.
+    pub fn buffer_from_iter<T, D>(&mut self, data: D, usage: BufferUsage)
+                                  -> Result<(Arc<ImmutableBuffer<[T]>>, UploadFuture),
+                                            UploadError>
+        where D: ExactSizeIterator<Item = T>,
+              T: Send + Sync + 'static
+    {
+        let source = self.stage(data)?;
+        let (buffer, init) = unsafe { self.create_buffer(source.size(), usage)? };
+        self.upload_buffer(source, buffer, init)
+    }
+
+    /// Builds an `ImmutableImage` from an iterator of pixels.
+    ///
+    /// The image can be sampled and is in the `ShaderReadOnlyOptimal` layout once uploaded, like
+    /// with `ImmutableImage::from_iter`. See also `buffer_from_data`.
+    pub fn image_from_iter<P, I, F>(&mut self, iter: I, dimensions: Dimensions, format: F)
+                                    -> Result<(Arc<ImmutableImage<F>>, UploadFuture),
+                                              UploadError>
+        where P: Send + Sync + Clone + 'static,
+              F: FormatDesc + AcceptsPixels<P> + 'static + Send + Sync,
+              I: ExactSizeIterator<Item = P>,
+              Format: AcceptsPixels<P>
+    {
+        let source = self.stage(iter)?;
+
+        let usage = ImageUsage {
+            transfer_destination: true,
+            sampled: true,
+            ..ImageUsage::none()
+        };
+
+        let (image, init) = ImmutableImage::uninitialized(self.transfer_queue.device().clone(),
+                                                          dimensions,
+                                                          format,
+                                                          MipmapsCount::One,
+                                                          usage,
+                                                          ImageLayout::ShaderReadOnlyOptimal,
+                                                          iter::once(self.transfer_queue
+                                                                         .family()))?;
+
+        let transfer_queue = self.transfer_queue.clone();
+        let destination_queue = self.destination_queue.clone();
+        let device = transfer_queue.device();
+        let transfer_family = transfer_queue.family();
+        let destination_family = destination_queue.family();
+        let transfers_ownership = transfer_family.id() != destination_family.id();
+
+        // The commands are checked before being recorded, so that an invalid upload doesn't
+        // discard the other uploads of the batch.
+        check_copy_buffer_image(device,
+                                &source,
+                                &init,
+                                CheckCopyBufferImageTy::BufferToImage,
+                                [0, 0, 0],
+                                dimensions.width_height_depth(),
+                                0,
+                                dimensions.array_layers_with_cube(),
+                                0)
+            .map_err(CopyBufferImageError::from)?;
+        if transfers_ownership {
+            check_image_ownership_transfer(device, &image, transfer_family.id(),
+                                           destination_family)
+                .map_err(OwnershipTransferError::from)?;
+            check_image_ownership_transfer(device, &image, destination_family.id(),
+                                           transfer_family)
+                .map_err(OwnershipTransferError::from)?;
+        }
+
+        let batch = self.batch()?;
+        let mut state = batch.state.lock().unwrap();
+
+        let mut cb = state
+            .transfer
+            .take()
+            .unwrap()
+            .copy_buffer_to_image_dimensions(source,
+                                             init,
+                                             [0, 0, 0],
+                                             dimensions.width_height_depth(),
+                                             0,
+                                             dimensions.array_layers_with_cube(),
+                                             0)
+            .unwrap_or_else(|err| unchecked_error(err));
+
+        if transfers_ownership {
+            cb = cb.release_image_ownership(image.clone(), destination_family)
+                .unwrap_or_else(|err| unchecked_error(err));
+            let acquire = state
+                .acquire
+                .take()
+                .unwrap()
+                .acquire_image_ownership(image.clone(), transfer_family)
+                .unwrap_or_else(|err| unchecked_error(err));
+            state.acquire = Some(acquire);
+        }
+
+        state.transfer = Some(cb);
+        state.num_uploads += 1;
+        drop(state);
+
+        Ok((image, UploadFuture { batch }))
+    }
+
+    /// Submits all the uploads recorded since the previous flush.
+    ///
+    /// Does nothing if no upload has been recorded, or if the uploads have already been
+    /// submitted by using one of their futures.
+    pub fn flush(&mut self) -> Result<(), FlushError> {
+        if self.batch.state.lock().unwrap().num_uploads == 0 {
+            return Ok(());
+        }
+
+        self.batch.future()?;
+        Ok(())
+    }
+
+    // Returns the batch that is currently being recorded, starting a new one if the current batch
+    // has already been submitted.
+    fn batch(&mut self) -> Result<Arc<UploadBatch>, OomError> {
+        if self.batch.state.lock().unwrap().future.is_some() {
+            self.batch = Arc::new(UploadBatch::new(&self.transfer_queue, &self.destination_queue)?);
+        }
+
+        Ok(self.batch.clone())
+    }
+
+    // Writes `data` to the staging ring.
+    fn stage<T, D>(&self, data: D)
+                   -> Result<BufferSlice<[T], CpuBufferPoolChunk<StagingBlock, Arc<StdMemoryPool>>>,
+                             DeviceMemoryAllocError>
+        where D: ExactSizeIterator<Item = T>,
+              T: Send + Sync + 'static
+    {
+        let mut data = data.collect::<Vec<_>>();
+        let len = data.len();
+        let size = len * mem::size_of::<T>();
+
+        let mut blocks = vec![[0; 96]; (size + mem::size_of::<StagingBlock>() - 1) /
+                                       mem::size_of::<StagingBlock>()];
+        unsafe {
+            ptr::copy_nonoverlapping(data.as_ptr() as *const u8,
+                                     blocks.as_mut_ptr() as *mut u8,
+                                     size);
+            // The content of `data` now belongs to the staging buffer.
+            data.set_len(0);
+        }
+
+        let chunk = self.staging.chunk(blocks.into_iter())?;
+        unsafe {
+            Ok(BufferSlice::from_typed_buffer_access(chunk)
+                   .reinterpret::<[u8]>()
+                   .slice(0 .. size)
+                   .unwrap()
+                   .reinterpret::<[T]>())
+        }
+    }
+
+    // Builds the buffer that will receive an upload.
+    unsafe fn create_buffer<T: ?Sized>(&self, size: usize, usage: BufferUsage)
+                                       -> Result<(Arc<ImmutableBuffer<T>>,
+                                                  ImmutableBufferInitialization<T>),
+                                                 DeviceMemoryAllocError> {
+        // We automatically set `transfer_destination` to true in order to avoid annoying errors.
+        let usage = BufferUsage {
+            transfer_destination: true,
+            ..usage
+        };
+
+        ImmutableBuffer::raw(self.transfer_queue.device().clone(),
+                             size,
+                             usage,
+                             iter::once(self.transfer_queue.family()))
+    }
+
+    // Records the copy from `source` to `buffer`, and the ownership transfer if necessary.
+    fn upload_buffer<S, T: ?Sized>(&mut self, source: S, buffer: Arc<ImmutableBuffer<T>>,
+                                   init: ImmutableBufferInitialization<T>)
+                                   -> Result<(Arc<ImmutableBuffer<T>>, UploadFuture), UploadError>
+        where S: TypedBufferAccess<Content = T> + Send + Sync + 'static,
+              T: Send + Sync + 'static
+    {
+        let transfer_queue = self.transfer_queue.clone();
+        let destination_queue = self.destination_queue.clone();
+        let device = transfer_queue.device();
+        let transfer_family = transfer_queue.family();
+        let destination_family = destination_queue.family();
+        let transfers_ownership = transfer_family.id() != destination_family.id();
+
+        // See `image_from_iter`.
+        check_copy_buffer(device, &source, &init).map_err(CopyBufferError::from)?;
+        if transfers_ownership {
+            check_buffer_ownership_transfer(device, &buffer, transfer_family.id(),
+                                            destination_family)
+                .map_err(OwnershipTransferError::from)?;
+            check_buffer_ownership_transfer(device, &buffer, destination_family.id(),
+                                            transfer_family)
+                .map_err(OwnershipTransferError::from)?;
+        }
+
+        let batch = self.batch()?;
+        let mut state = batch.state.lock().unwrap();
+
+        let mut cb = state
+            .transfer
+            .take()
+            .unwrap()
+            .copy_buffer(source, init)
+            .unwrap_or_else(|err| unchecked_error(err));
+
+        if transfers_ownership {
+            cb = cb.release_buffer_ownership(buffer.clone(), destination_family)
+                .unwrap_or_else(|err| unchecked_error(err));
+            let acquire = state
+                .acquire
+                .take()
+                .unwrap()
+                .acquire_buffer_ownership(buffer.clone(), transfer_family)
+                .unwrap_or_else(|err| unchecked_error(err));
+            state.acquire = Some(acquire);
+        }
+
+        state.transfer = Some(cb);
+        state.num_uploads += 1;
+        drop(state);
+
+        Ok((buffer, UploadFuture { batch }))
+    }
+}
+
+// Uploads that are submitted together.
+struct UploadBatch {
+    transfer_queue: Arc<Queue>,
+    destination_queue: Arc<Queue>,
+    state: Mutex<UploadBatchState>,
+}
+
+struct UploadBatchState {
+    // Command buffer that performs the copies and releases the ownership of the resources.
+    // `None` once submitted.
+    transfer: Option<AutoCommandBufferBuilder>,
+
+    // Command buffer that acquires the ownership of the resources on the destination queue.
+    // `None` if the two queues belong to the same family, or once submitted.
+    acquire: Option<AutoCommandBufferBuilder>,
+
+    // Number of uploads recorded in the batch.
+    num_uploads: usize,
+
+    // Result of the submission of the batch. `None` if not submitted yet.
+    future: Option<Result<BatchFuture, FlushError>>,
+}
+
+impl UploadBatch {
+    fn new(transfer_queue: &Arc<Queue>, destination_queue: &Arc<Queue>)
+           -> Result<UploadBatch, OomError> {
+        let device = transfer_queue.device();
+
+        let transfer = AutoCommandBufferBuilder::primary_one_time_submit(device.clone(),
+                                                                         transfer_queue.family())?;
+        let acquire = if transfer_queue.family().id() != destination_queue.family().id() {
+            Some(AutoCommandBufferBuilder::primary_one_time_submit(device.clone(),
+                                                                   destination_queue.family())?)
+        } else {
+            None
+        };
+
+        Ok(UploadBatch {
+               transfer_queue: transfer_queue.clone(),
+               destination_queue: destination_queue.clone(),
+               state: Mutex::new(UploadBatchState {
+                                     transfer: Some(transfer),
+                                     acquire,
+                                     num_uploads: 0,
+                                     future: None,
+                                 }),
+           })
+    }
+
+    // Returns the future of the batch, submitting the batch if this wasn't done yet.
+    fn future(&self) -> Result<BatchFuture, FlushError> {
+        let mut state = self.state.lock().unwrap();
+
+        if let Some(ref result) = state.future {
+            return result.clone();
+        }
+
+        let result = self.submit(&mut state);
+        state.future = Some(result.clone());
+        result
+    }
+
+    fn submit(&self, state: &mut UploadBatchState) -> Result<BatchFuture, FlushError> {
+        let transfer = state.transfer.take().unwrap().build().map_err(build_error)?;
+        let mut future: Box<dyn GpuFuture> =
+            Box::new(now(self.transfer_queue.device().clone())
+                         .then_execute(self.transfer_queue.clone(), transfer)
+                         .map_err(exec_error)?);
+
+        if let Some(acquire) = state.acquire.take() {
+            let acquire = acquire.build().map_err(build_error)?;
+            future = Box::new(future
+                                  .then_signal_semaphore()
+                                  .then_execute(self.destination_queue.clone(), acquire)
+                                  .map_err(exec_error)?);
+        }
+
+        Ok(Arc::new(future.then_signal_fence_and_flush()?))
+    }
+}
+
+fn build_error(err: BuildError) -> FlushError {
+    match err {
+        BuildError::OomError(err) => FlushError::OomError(err),
+        // The command buffers never enter a render pass.
+        BuildError::AutoCommandBufferBuilderContextError(_) => unreachable!(),
+    }
+}
+
+fn exec_error(err: CommandBufferExecError) -> FlushError {
+    match err {
+        CommandBufferExecError::AccessError { error, .. } => FlushError::AccessError(error),
+        // The command buffers are only submitted once.
+        CommandBufferExecError::OneTimeSubmitAlreadySubmitted |
+        CommandBufferExecError::ExclusiveAlreadyInUse => unreachable!(),
+    }
+}
+
+// Recording the commands of an upload can only fail for the reasons checked beforehand: the
+// command buffers never enter a render pass, and the uploaded resources are new and therefore
+// can't conflict with the other commands of the batch.
+fn unchecked_error<E: fmt::Debug>(err: E) -> ! {
+    unreachable!("unexpected error while recording an upload: {:?}", err)
+}
+
+/// Error that can happen when uploading a resource with an `UploadManager`.
+#[derive(Debug, Clone)]
+pub enum UploadError {
+    /// Not enough memory to start a new batch.
+    OomError(OomError),
+
+    /// Allocating memory for the staging data or the buffer failed.
+    AllocError(DeviceMemoryAllocError),
+
+    /// Creating the image failed.
+    ImageCreationError(ImageCreationError),
+
+    /// The copy to the buffer is invalid.
+    CopyBufferError(CopyBufferError),
+
+    /// The copy to the image is invalid, for example because the number of pixels doesn't match
+    /// the dimensions.
+    CopyBufferImageError(CopyBufferImageError),
+
+    /// The queue family ownership of the resource can't be transferred to the destination queue.
+    OwnershipTransferError(OwnershipTransferError),
+}
+
+impl error::Error for UploadError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            UploadError::OomError(_) => "not enough memory",
+            UploadError::AllocError(_) => "allocating memory failed",
+            UploadError::ImageCreationError(_) => "creating the image failed",
+            UploadError::CopyBufferError(_) => "the copy to the buffer is invalid",
+            UploadError::CopyBufferImageError(_) => "the copy to the image is invalid",
+            UploadError::OwnershipTransferError(_) => {
+                "the queue family ownership of the resource can't be transferred"
+            },
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&dyn error::Error> {
+        match *self {
+            UploadError::OomError(ref err) => Some(err),
+            UploadError::AllocError(ref err) => Some(err),
+            UploadError::ImageCreationError(ref err) => Some(err),
+            UploadError::CopyBufferError(ref err) => Some(err),
+            UploadError::CopyBufferImageError(ref err) => Some(err),
+            UploadError::OwnershipTransferError(ref err) => Some(err),
+        }
+    }
+}
+
+impl fmt::Display for UploadError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<OomError> for UploadError {
+    #[inline]
+    fn from(err: OomError) -> UploadError {
+        UploadError::OomError(err)
+    }
+}
+
+impl From<DeviceMemoryAllocError> for UploadError {
+    #[inline]
+    fn from(err: DeviceMemoryAllocError) -> UploadError {
+        UploadError::AllocError(err)
+    }
+}
+
+impl From<ImageCreationError> for UploadError {
+    #[inline]
+    fn from(err: ImageCreationError) -> UploadError {
+        UploadError::ImageCreationError(err)
+    }
+}
+
+impl From<CopyBufferError> for UploadError {
+    #[inline]
+    fn from(err: CopyBufferError) -> UploadError {
+        UploadError::CopyBufferError(err)
+    }
+}
+
+impl From<CopyBufferImageError> for UploadError {
+    #[inline]
+    fn from(err: CopyBufferImageError) -> UploadError {
+        UploadError::CopyBufferImageError(err)
+    }
+}
+
+impl From<OwnershipTransferError> for UploadError {
+    #[inline]
+    fn from(err: OwnershipTransferError) -> UploadError {
+        UploadError::OwnershipTransferError(err)
+    }
+}
+
+/// Future representing the upload of a resource by an `UploadManager`.
+///
+/// All the futures of the resources uploaded in the same batch represent the same submission.
+/// If the batch hasn't been submitted with `UploadManager::flush()` yet, it is submitted the
+/// first time this future is used.
+pub struct UploadFuture {
+    batch: Arc<UploadBatch>,
+}
+
+unsafe impl GpuFuture for UploadFuture {
+    #[inline]
+    fn cleanup_finished(&mut self) {
+        if let Some(Ok(ref future)) = self.batch.state.lock().unwrap().future {
+            future.clone().cleanup_finished();
+        }
+    }
+
+    #[inline]
+    unsafe fn build_submission(&self) -> Result<SubmitAnyBuilder, FlushError> {
+        // The future of the batch is flushed as soon as it is created, therefore it never
+        // returns a submission.
+        let future = self.batch.future()?;
+        match future.build_submission()? {
+            SubmitAnyBuilder::Empty => (),
+            _ => unreachable!(),
+        }
+        Ok(SubmitAnyBuilder::Empty)
+    }
+
+    #[inline]
+    fn flush(&self) -> Result<(), FlushError> {
+        self.batch.future()?.flush()
+    }
+
+    #[inline]
+    unsafe fn signal_finished(&self) {
+        if let Ok(future) = self.batch.future() {
+            future.signal_finished();
+        }
+    }
+
+    #[inline]
+    fn queue_change_allowed(&self) -> bool {
+        match self.batch.future() {
+            Ok(future) => future.queue_change_allowed(),
+            Err(_) => true,
+        }
+    }
+
+    #[inline]
+    fn queue(&self) -> Option<Arc<Queue>> {
+        match self.batch.future() {
+            Ok(future) => future.queue(),
+            Err(_) => None,
+        }
+    }
+
+    #[inline]
+    fn check_buffer_access(&self, buffer: &dyn BufferAccess, exclusive: bool, queue: &Queue)
+                           -> Result<Option<(PipelineStages, AccessFlagBits)>, AccessCheckError> {
+        match self.batch.future() {
+            Ok(future) => future.check_buffer_access(buffer, exclusive, queue),
+            // The error will be returned by `build_submission`.
+            Err(_) => Err(AccessCheckError::Unknown),
+        }
+    }
+
+    #[inline]
+    fn check_image_access(&self, image: &dyn ImageAccess, layout: ImageLayout, exclusive: bool,
+                          queue: &Queue)
+                          -> Result<Option<(PipelineStages, AccessFlagBits)>, AccessCheckError> {
+        match self.batch.future() {
+            Ok(future) => future.check_image_access(image, layout, exclusive, queue),
+            // The error will be returned by `build_submission`.
+            Err(_) => Err(AccessCheckError::Unknown),
+        }
+    }
+}
+
+unsafe impl DeviceOwned for UploadFuture {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        self.batch.transfer_queue.device()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::BufferUsage;
+    use buffer::CpuAccessibleBuffer;
+    use command_buffer::AutoCommandBufferBuilder;
+    use command_buffer::CommandBuffer;
+    use command_buffer::CommandBufferExecError;
+    use command_buffer::UploadError;
+    use command_buffer::UploadManager;
+    use format::Format;
+    use image::Dimensions;
+    use sync::AccessError;
+    use sync::GpuFuture;
+
+    #[test]
+    fn batched_buffers() {
+        let (device, queue) = gfx_dev_and_queue!();
+        let mut uploads = UploadManager::new(queue.clone(), queue.clone()).unwrap();
+
+        let (_, future1) = uploads.buffer_from_iter(0 .. 64u32, BufferUsage::all()).unwrap();
+        let (_, future2) = uploads.buffer_from_data(12u8, BufferUsage::all()).unwrap();
+
+        future1
+            .join(future2)
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+    }
+
+    #[test]
+    fn cross_family_buffer() {
+        let (device, queue, transfer_queue) = mock_dev_and_transfer_queue!();
+        let mut uploads = UploadManager::new(transfer_queue.clone(), queue.clone()).unwrap();
+
+        let (buffer, future) = uploads.buffer_from_iter(0 .. 64u32, BufferUsage::all()).unwrap();
+        let destination = CpuAccessibleBuffer::from_iter(device.clone(),
+                                                         BufferUsage::all(),
+                                                         false,
+                                                         0 .. 64u32)
+            .unwrap();
+
+        // The ownership of the buffer has been transferred to the destination queue.
+        let copy = AutoCommandBufferBuilder::new(device.clone(), queue.family())
+            .unwrap()
+            .copy_buffer(buffer.clone(), destination)
+            .unwrap()
+            .build()
+            .unwrap();
+        future
+            .then_execute(queue.clone(), copy)
+            .unwrap()
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+
+        let fill = AutoCommandBufferBuilder::new(device.clone(), transfer_queue.family())
+            .unwrap()
+            .fill_buffer(buffer, 0)
+            .unwrap()
+            .build()
+            .unwrap();
+        match fill.execute(transfer_queue.clone()) {
+            Err(CommandBufferExecError::AccessError {
+                    error: AccessError::WrongQueueFamilyOwner { .. },
+                    ..
+                }) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn image_upload() {
+        let (_, queue, transfer_queue) = mock_dev_and_transfer_queue!();
+        let mut uploads = UploadManager::new(transfer_queue, queue).unwrap();
+
+        let dimensions = Dimensions::Dim2d {
+            width: 4,
+            height: 4,
+        };
+        let (_, future) = uploads
+            .image_from_iter((0 .. 16).map(|_| [0u8; 4]), dimensions, Format::R8G8B8A8Unorm)
+            .unwrap();
+
+        future.then_signal_fence_and_flush().unwrap().wait(None).unwrap();
+    }
+
+    #[test]
+    fn invalid_image_keeps_batch() {
+        let (_, queue, transfer_queue) = mock_dev_and_transfer_queue!();
+        let mut uploads = UploadManager::new(transfer_queue, queue).unwrap();
+
+        let (_, future) = uploads.buffer_from_data(12u32, BufferUsage::all()).unwrap();
+
+        // Not enough pixels for the dimensions of the image.
+        let dimensions = Dimensions::Dim2d {
+            width: 4,
+            height: 4,
+        };
+        match uploads.image_from_iter((0 .. 8).map(|_| [0u8; 4]), dimensions,
+                                      Format::R8G8B8A8Unorm) {
+            Err(UploadError::CopyBufferImageError(_)) => (),
+            _ => panic!(),
+        }
+
+        uploads.flush().unwrap();
+        future.then_signal_fence_and_flush().unwrap().wait(None).unwrap();
+    }
+}
@@ -7,6 +7,7 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+use std::cmp;
 use std::error;
 use std::fmt;
 
@@ -25,11 +26,27 @@ pub fn check_descriptor_sets_validity<Pl, D>(pipeline: &Pl, descriptor_sets: &D)
     // a problem if the descriptor sets provide more elements than expected.
 
     for set_num in 0 .. pipeline.num_sets() {
+        let provided_layout = pipeline.provided_set_layout(set_num);
+
+        // The descriptors of a push descriptor set are provided separately with
+        // `push_descriptor_set`.
+        if provided_layout
+            .as_ref()
+            .map(|layout| layout.is_push_descriptor())
+            .unwrap_or(false)
+        {
+            continue;
+        }
+
+        let variable_binding = provided_layout
+            .as_ref()
+            .and_then(|layout| layout.variable_descriptor_count_binding());
+
         for binding_num in 0 .. pipeline.num_bindings_in_set(set_num).unwrap_or(0) {
             let set_desc = descriptor_sets.descriptor(set_num, binding_num);
             let pipeline_desc = pipeline.descriptor(set_num, binding_num);
 
-            let (set_desc, pipeline_desc) = match (set_desc, pipeline_desc) {
+            let (set_desc, mut pipeline_desc) = match (set_desc, pipeline_desc) {
                 (Some(s), Some(p)) => (s, p),
                 (None, Some(_)) =>
                     return Err(CheckDescriptorSetsValidityError::MissingDescriptor {
@@ -40,6 +57,13 @@ pub fn check_descriptor_sets_validity<Pl, D>(pipeline: &Pl, descriptor_sets: &D)
                 (None, None) => continue,
             };
 
+            // The number of descriptors of a binding with a variable descriptor count is chosen
+            // when allocating the set, and can be anything up to the count of the layout.
+            if variable_binding == Some(binding_num) {
+                pipeline_desc.array_count = cmp::min(pipeline_desc.array_count,
+                                                     set_desc.array_count);
+            }
+
             if let Err(err) = set_desc.is_superset_of(&pipeline_desc) {
                 return Err(CheckDescriptorSetsValidityError::IncompatibleDescriptor {
                                error: err,
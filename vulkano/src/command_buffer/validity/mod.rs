@@ -20,7 +20,10 @@ pub use self::dispatch::{CheckDispatchError, check_dispatch};
 pub use self::dynamic_state::{CheckDynamicStateValidityError, check_dynamic_state_validity};
 pub use self::fill_buffer::{CheckFillBufferError, check_fill_buffer};
 pub use self::index_buffer::{CheckIndexBuffer, CheckIndexBufferError, check_index_buffer};
+pub use self::ownership_transfer::{CheckOwnershipTransferError, check_buffer_ownership_transfer,
+                                   check_image_ownership_transfer};
 pub use self::push_constants::{CheckPushConstantsValidityError, check_push_constants_validity};
+pub use self::push_descriptor_set::{CheckPushDescriptorSetError, check_push_descriptor_set};
 pub use self::update_buffer::{CheckUpdateBufferError, check_update_buffer};
 pub use self::vertex_buffers::{CheckVertexBuffer, CheckVertexBufferError, check_vertex_buffers};
 
@@ -34,6 +37,8 @@ mod dispatch;
 mod dynamic_state;
 mod fill_buffer;
 mod index_buffer;
+mod ownership_transfer;
 mod push_constants;
+mod push_descriptor_set;
 mod update_buffer;
 mod vertex_buffers;
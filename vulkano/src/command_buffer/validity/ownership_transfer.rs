@@ -0,0 +1,188 @@
+// Copyright (c) 2020 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::error;
+use std::fmt;
+
+use VulkanObject;
+use buffer::BufferAccess;
+use device::Device;
+use image::ImageAccess;
+use instance::QueueFamily;
+
+/// Checks whether a queue family ownership transfer command of a buffer is valid.
+///
+/// `queue_family` is the id of the queue family the command buffer is created for, and `other`
+/// is the queue family the ownership is released to or acquired from.
+///
+/// # Panic
+///
+/// - Panics if `other` doesn't belong to the physical device of `device`.
+///
+pub fn check_buffer_ownership_transfer<B>(device: &Device, buffer: &B, queue_family: u32,
+                                          other: QueueFamily)
+                                          -> Result<(), CheckOwnershipTransferError>
+    where B: ?Sized + BufferAccess
+{
+    let inner = buffer.inner();
+
+    if inner.buffer.concurrent_sharing() {
+        return Err(CheckOwnershipTransferError::ConcurrentSharing);
+    }
+
+    // The ownership is tracked for the whole `UnsafeBuffer`.
+    if inner.offset != 0 || buffer.size() != inner.buffer.size() {
+        return Err(CheckOwnershipTransferError::PartialBuffer);
+    }
+
+    check_queue_families(device, queue_family, other)
+}
+
+/// Checks whether a queue family ownership transfer command of an image is valid.
+///
+/// See `check_buffer_ownership_transfer`.
+///
+/// # Panic
+///
+/// - Panics if `other` doesn't belong to the physical device of `device`.
+///
+pub fn check_image_ownership_transfer<I>(device: &Device, image: &I, queue_family: u32,
+                                         other: QueueFamily)
+                                         -> Result<(), CheckOwnershipTransferError>
+    where I: ?Sized + ImageAccess
+{
+    if image.inner().image.concurrent_sharing() {
+        return Err(CheckOwnershipTransferError::ConcurrentSharing);
+    }
+
+    check_queue_families(device, queue_family, other)
+}
+
+fn check_queue_families(device: &Device, queue_family: u32, other: QueueFamily)
+                        -> Result<(), CheckOwnershipTransferError> {
+    assert_eq!(other.physical_device().internal_object(),
+               device.physical_device().internal_object());
+
+    if other.id() == queue_family {
+        return Err(CheckOwnershipTransferError::SameQueueFamily);
+    }
+
+    Ok(())
+}
+
+/// Error that can happen when attempting to add a queue family ownership transfer command.
+#[derive(Debug, Copy, Clone)]
+pub enum CheckOwnershipTransferError {
+    /// The ownership can't be transferred to or from the queue family of the command buffer.
+    SameQueueFamily,
+    /// The resource was created with `Sharing::Concurrent`, and therefore doesn't have a queue
+    /// family owner.
+    ConcurrentSharing,
+    /// The buffer doesn't cover the whole of its underlying `UnsafeBuffer`.
+    PartialBuffer,
+}
+
+impl error::Error for CheckOwnershipTransferError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            CheckOwnershipTransferError::SameQueueFamily => {
+                "the ownership can't be transferred to or from the queue family of the command \
+                 buffer"
+            },
+            CheckOwnershipTransferError::ConcurrentSharing => {
+                "the resource was created with a concurrent sharing mode"
+            },
+            CheckOwnershipTransferError::PartialBuffer => {
+                "the buffer doesn't cover the whole of its underlying buffer object"
+            },
+        }
+    }
+}
+
+impl fmt::Display for CheckOwnershipTransferError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use buffer::BufferUsage;
+    use buffer::CpuAccessibleBuffer;
+    use buffer::DeviceLocalBuffer;
+    use format::Format;
+    use image::Dimensions;
+    use image::StorageImage;
+
+    #[test]
+    fn same_queue_family() {
+        let (device, queue) = mock_dev_and_queue!();
+        let buffer = CpuAccessibleBuffer::from_data(device.clone(), BufferUsage::all(), false, 0u32)
+            .unwrap();
+
+        match check_buffer_ownership_transfer(&device, &buffer, queue.family().id(),
+                                              queue.family()) {
+            Err(CheckOwnershipTransferError::SameQueueFamily) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn concurrent_buffer() {
+        let (device, queue, transfer_queue) = mock_dev_and_transfer_queue!();
+        let buffer = DeviceLocalBuffer::<u32>::new(device.clone(),
+                                                   BufferUsage::all(),
+                                                   vec![queue.family(), transfer_queue.family()])
+            .unwrap();
+
+        match check_buffer_ownership_transfer(&device, &buffer, queue.family().id(),
+                                              transfer_queue.family()) {
+            Err(CheckOwnershipTransferError::ConcurrentSharing) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn concurrent_image() {
+        let (device, queue, transfer_queue) = mock_dev_and_transfer_queue!();
+        let image = StorageImage::new(device.clone(),
+                                      Dimensions::Dim2d {
+                                          width: 4,
+                                          height: 4,
+                                      },
+                                      Format::R8G8B8A8Unorm,
+                                      vec![queue.family(), transfer_queue.family()])
+            .unwrap();
+
+        match check_image_ownership_transfer(&device, &image, queue.family().id(),
+                                             transfer_queue.family()) {
+            Err(CheckOwnershipTransferError::ConcurrentSharing) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn partial_buffer() {
+        let (device, queue, transfer_queue) = mock_dev_and_transfer_queue!();
+        let buffer = CpuAccessibleBuffer::from_iter(device.clone(),
+                                                    BufferUsage::all(),
+                                                    false,
+                                                    0 .. 4u32)
+            .unwrap();
+
+        match check_buffer_ownership_transfer(&device, &buffer.slice(1 .. 2).unwrap(),
+                                              queue.family().id(), transfer_queue.family()) {
+            Err(CheckOwnershipTransferError::PartialBuffer) => (),
+            _ => panic!(),
+        }
+    }
+}
@@ -0,0 +1,244 @@
+// Copyright (c) 2020 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::error;
+use std::fmt;
+
+use VulkanObject;
+use descriptor::descriptor::DescriptorDescSupersetError;
+use descriptor::descriptor_set::DescriptorSetDesc;
+use descriptor::descriptor_set::UnsafeDescriptorSetLayout;
+use descriptor::pipeline_layout::PipelineLayoutAbstract;
+use device::Device;
+use device::DeviceOwned;
+
+/// Checks whether descriptors built for `layout` can be pushed to the set `set_num` of
+/// `pipeline_layout`.
+///
+/// # Panic
+///
+/// - Panics if the pipeline layout or the descriptor set layout weren't created with `device`.
+///
+pub fn check_push_descriptor_set<Pl>(device: &Device, pipeline_layout: &Pl, set_num: u32,
+                                     layout: &UnsafeDescriptorSetLayout)
+                                     -> Result<(), CheckPushDescriptorSetError>
+    where Pl: ?Sized + PipelineLayoutAbstract
+{
+    assert_eq!(pipeline_layout.device().internal_object(),
+               device.internal_object());
+    assert_eq!(layout.device().internal_object(), device.internal_object());
+
+    if !device.loaded_extensions().khr_push_descriptor {
+        return Err(CheckPushDescriptorSetError::ExtensionNotEnabled);
+    }
+
+    let expected = match pipeline_layout.descriptor_set_layout(set_num as usize) {
+        Some(l) if l.is_push_descriptor() => l,
+        _ => return Err(CheckPushDescriptorSetError::NotPushDescriptorSet { set_num }),
+    };
+
+    // Same rules as with `check_descriptor_sets_validity`: the provided descriptors must be a
+    // superset of what the pipeline layout expects.
+    for binding_num in 0 .. expected.num_bindings() {
+        let expected_desc = match expected.descriptor(binding_num) {
+            Some(d) => d,
+            None => continue,
+        };
+
+        let desc = match layout.descriptor(binding_num) {
+            Some(d) => d,
+            None => return Err(CheckPushDescriptorSetError::MissingDescriptor {
+                                   binding_num: binding_num,
+                               }),
+        };
+
+        if let Err(err) = desc.is_superset_of(&expected_desc) {
+            return Err(CheckPushDescriptorSetError::IncompatibleDescriptor {
+                           error: err,
+                           binding_num: binding_num,
+                       });
+        }
+    }
+
+    Ok(())
+}
+
+/// Error that can happen when attempting to add a `push_descriptor_set` command.
+#[derive(Debug, Clone)]
+pub enum CheckPushDescriptorSetError {
+    /// The `VK_KHR_push_descriptor` extension must be enabled on the device.
+    ExtensionNotEnabled,
+    /// The set of the pipeline layout wasn't created as a push descriptor set.
+    NotPushDescriptorSet {
+        /// The index of the set.
+        set_num: u32,
+    },
+    /// A descriptor expected by the pipeline layout is missing.
+    MissingDescriptor {
+        /// The binding number of the descriptor.
+        binding_num: usize,
+    },
+    /// A provided descriptor is not compatible with what the pipeline layout expects.
+    IncompatibleDescriptor {
+        /// The reason why the two descriptors aren't compatible.
+        error: DescriptorDescSupersetError,
+        /// The binding number of the descriptor.
+        binding_num: usize,
+    },
+}
+
+impl error::Error for CheckPushDescriptorSetError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            CheckPushDescriptorSetError::ExtensionNotEnabled => {
+                "the `VK_KHR_push_descriptor` extension must be enabled"
+            },
+            CheckPushDescriptorSetError::NotPushDescriptorSet { .. } => {
+                "the set of the pipeline layout wasn't created as a push descriptor set"
+            },
+            CheckPushDescriptorSetError::MissingDescriptor { .. } => {
+                "a descriptor expected by the pipeline layout is missing"
+            },
+            CheckPushDescriptorSetError::IncompatibleDescriptor { .. } => {
+                "a provided descriptor is not compatible with what the pipeline layout expects"
+            },
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&dyn error::Error> {
+        match *self {
+            CheckPushDescriptorSetError::IncompatibleDescriptor { ref error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for CheckPushDescriptorSetError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use buffer::BufferUsage;
+    use buffer::CpuAccessibleBuffer;
+    use command_buffer::AutoCommandBufferBuilder;
+    use command_buffer::CommandBuffer;
+    use descriptor::descriptor::DescriptorBufferDesc;
+    use descriptor::descriptor::DescriptorDesc;
+    use descriptor::descriptor::DescriptorDescTy;
+    use descriptor::descriptor::ShaderStages;
+    use descriptor::descriptor_set::PersistentDescriptorSet;
+    use descriptor::pipeline_layout::PipelineLayoutDesc;
+    use descriptor::pipeline_layout::PipelineLayoutDescPushDescriptor;
+    use descriptor::pipeline_layout::RuntimePipelineDesc;
+    use mock::Recorder;
+    use std::iter;
+    use std::sync::Arc;
+
+    fn uniform_buffer() -> DescriptorDesc {
+        DescriptorDesc {
+            ty: DescriptorDescTy::Buffer(DescriptorBufferDesc {
+                                             dynamic: Some(false),
+                                             storage: false,
+                                         }),
+            array_count: 1,
+            stages: ShaderStages::all(),
+            readonly: true,
+        }
+    }
+
+    fn desc() -> RuntimePipelineDesc {
+        RuntimePipelineDesc::new(iter::once(iter::once(Some(uniform_buffer()))), iter::empty())
+            .unwrap()
+    }
+
+    #[test]
+    fn extension_not_enabled() {
+        let (device, _) = mock_dev_and_queue!();
+
+        let pipeline_layout = desc().build(device.clone()).unwrap();
+        let layout = UnsafeDescriptorSetLayout::new(device.clone(),
+                                                    iter::once(Some(uniform_buffer())))
+            .unwrap();
+
+        match check_push_descriptor_set(&device, &pipeline_layout, 0, &layout) {
+            Err(CheckPushDescriptorSetError::ExtensionNotEnabled) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn not_push_descriptor_set() {
+        let (device, _) = mock_dev_and_queue!(extensions: [khr_push_descriptor]);
+
+        let pipeline_layout = desc().build(device.clone()).unwrap();
+        let layout = UnsafeDescriptorSetLayout::push_descriptor(device.clone(),
+                                                                iter::once(Some(uniform_buffer())))
+            .unwrap();
+
+        match check_push_descriptor_set(&device, &pipeline_layout, 0, &layout) {
+            Err(CheckPushDescriptorSetError::NotPushDescriptorSet { set_num: 0 }) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn missing_descriptor() {
+        let (device, _) = mock_dev_and_queue!(extensions: [khr_push_descriptor]);
+
+        let pipeline_layout = PipelineLayoutDescPushDescriptor::new(device.clone(), desc(), 0)
+            .unwrap()
+            .build(device.clone())
+            .unwrap();
+        let layout = UnsafeDescriptorSetLayout::push_descriptor(device.clone(), iter::empty())
+            .unwrap();
+
+        match check_push_descriptor_set(&device, &pipeline_layout, 0, &layout) {
+            Err(CheckPushDescriptorSetError::MissingDescriptor { binding_num: 0 }) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn push_descriptors() {
+        let (device, queue) = mock_dev_and_queue!(extensions: [khr_push_descriptor]);
+        let recorder = Recorder::of(device.instance()).unwrap();
+
+        let pipeline_layout = Arc::new(PipelineLayoutDescPushDescriptor::new(device.clone(),
+                                                                             desc(),
+                                                                             0)
+                                           .unwrap()
+                                           .build(device.clone())
+                                           .unwrap());
+        let layout = pipeline_layout.descriptor_set_layout(0).unwrap().clone();
+        let buffer = CpuAccessibleBuffer::from_data(device.clone(),
+                                                    BufferUsage::uniform_buffer(),
+                                                    false,
+                                                    0u32)
+            .unwrap();
+        let descriptors = PersistentDescriptorSet::start(layout).add_buffer(buffer).unwrap();
+
+        let command_buffer = AutoCommandBufferBuilder::new(device.clone(), queue.family())
+            .unwrap()
+            .push_descriptor_set(false, pipeline_layout, 0, descriptors)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        assert_eq!(recorder.commands(command_buffer.inner().internal_object()),
+                   Some(vec!["vkCmdPushDescriptorSetKHR"]));
+        assert!(recorder.errors().is_empty());
+    }
+}
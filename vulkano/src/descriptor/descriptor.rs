@@ -623,6 +623,7 @@ impl From<ShaderStagesSupersetError> for DescriptorDescSupersetError {
 /// Describes which shader stages have access to a descriptor.
 // TODO: add example with BitOr
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ShaderStages {
     /// `True` means that the descriptor will be used by the vertex shader.
     pub vertex: bool,
@@ -745,6 +746,18 @@ impl ShaderStages {
         }
         result
     }
+
+    #[inline]
+    pub(crate) fn from_vulkan_bits(val: vk::ShaderStageFlags) -> ShaderStages {
+        ShaderStages {
+            vertex: (val & vk::SHADER_STAGE_VERTEX_BIT) != 0,
+            tessellation_control: (val & vk::SHADER_STAGE_TESSELLATION_CONTROL_BIT) != 0,
+            tessellation_evaluation: (val & vk::SHADER_STAGE_TESSELLATION_EVALUATION_BIT) != 0,
+            geometry: (val & vk::SHADER_STAGE_GEOMETRY_BIT) != 0,
+            fragment: (val & vk::SHADER_STAGE_FRAGMENT_BIT) != 0,
+            compute: (val & vk::SHADER_STAGE_COMPUTE_BIT) != 0,
+        }
+    }
 }
 
 impl BitOr for ShaderStages {
@@ -801,3 +814,18 @@ impl fmt::Display for ShaderStagesSupersetError {
         write!(fmt, "{}", error::Error::description(self))
     }
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use descriptor::descriptor::ShaderStages;
+
+    #[test]
+    fn serde_round_trip() {
+        assert_serde_round_trip!(ShaderStages::all());
+        assert_serde_round_trip!(ShaderStages {
+            vertex: true,
+            fragment: true,
+            .. ShaderStages::none()
+        });
+    }
+}
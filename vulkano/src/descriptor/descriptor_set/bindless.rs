@@ -0,0 +1,565 @@
+// Copyright (c) 2020 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::iter;
+use std::ptr;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use OomError;
+use VulkanObject;
+use buffer::BufferAccess;
+use descriptor::descriptor::DescriptorDesc;
+use descriptor::descriptor::DescriptorDescTy;
+use descriptor::descriptor_set::DescriptorPoolAllocError;
+use descriptor::descriptor_set::DescriptorSet;
+use descriptor::descriptor_set::DescriptorSetDesc;
+use descriptor::descriptor_set::DescriptorWrite;
+use descriptor::descriptor_set::DescriptorsCount;
+use descriptor::descriptor_set::PersistentDescriptorSetError;
+use descriptor::descriptor_set::UnsafeDescriptorPool;
+use descriptor::descriptor_set::UnsafeDescriptorSet;
+use descriptor::descriptor_set::UnsafeDescriptorSetLayout;
+use descriptor::descriptor_set::persistent::MissingBufferUsage;
+use descriptor::descriptor_set::persistent::image_match_desc;
+use descriptor::descriptor_set::with_raw_writes;
+use device::Device;
+use device::DeviceOwned;
+use image::ImageViewAccess;
+use sampler::Sampler;
+
+/// A descriptor set whose descriptors can be written individually at any time, including after
+/// the set has been bound in a command buffer.
+///
+/// This is meant to be used with layouts created with
+/// `UnsafeDescriptorSetLayout::with_binding_flags`, for example through
+/// `PipelineLayoutDescBindless`. If the last binding of the layout has the
+/// `variable_descriptor_count` flag, its number of descriptors is chosen when creating the set.
+///
+/// # Synchronization
+///
+/// **Vulkano doesn't synchronize the resources of a `BindlessDescriptorSet`.** Contrary to
+/// `PersistentDescriptorSet`, the set reports no buffer and no image to the command buffers that
+/// use it, as its content can change after it has been bound. This means that:
+///
+/// - No pipeline barrier is inserted between the commands that access a resource through the
+///   set and the other commands that access the same resource. You must separate them yourself,
+///   for example by submitting them in different command buffers and waiting on a semaphore or a
+///   fence in between.
+/// - No image layout transition is performed. The images must already be in the layout that
+///   their descriptor expects when the set is used.
+/// - Futures don't know that a submission uses the resources, so they don't prevent the CPU or
+///   another queue from accessing them while the GPU is still using them.
+/// - The resources are only kept alive for as long as they are written in the set, and not until
+///   the command buffers that use them have finished executing.
+///
+/// This is why writing the set is unsafe.
+pub struct BindlessDescriptorSet {
+    inner: UnsafeDescriptorSet,
+    layout: Arc<UnsafeDescriptorSetLayout>,
+    variable_count: u32,
+    // The resources currently written in the set, indexed by binding and array element.
+    resources: Mutex<HashMap<(u32, u32), Box<dyn Any + Send + Sync>>>,
+    // The pool is dropped after `inner`, which frees the set.
+    pool: UnsafeDescriptorPool,
+}
+
+impl BindlessDescriptorSet {
+    /// Allocates a new descriptor set with the given layout.
+    ///
+    /// `variable_count` is the number of descriptors of the binding that has the
+    /// `variable_descriptor_count` flag. It is ignored if no binding has this flag.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `variable_count` is larger than the `array_count` of the variable binding.
+    /// - Panics if the layout has no descriptor.
+    ///
+    pub fn new(layout: Arc<UnsafeDescriptorSetLayout>, variable_count: u32)
+               -> Result<BindlessDescriptorSet, OomError> {
+        let variable_binding = layout.variable_descriptor_count_binding();
+
+        let mut count = DescriptorsCount::zero();
+        for binding in 0 .. layout.num_bindings() {
+            let desc = match layout.descriptor(binding) {
+                Some(d) => d,
+                None => continue,
+            };
+
+            let num = if variable_binding == Some(binding) {
+                assert!(variable_count <= desc.array_count,
+                        "the variable descriptor count is larger than the maximum of the binding");
+                variable_count
+            } else {
+                desc.array_count
+            };
+
+            count.add_num(desc.ty.ty().unwrap(), num);
+        }
+
+        let device = layout.device().clone();
+        let mut pool = if layout.is_update_after_bind() {
+            UnsafeDescriptorPool::update_after_bind(device, &count, 1, false)?
+        } else {
+            UnsafeDescriptorPool::new(device, &count, 1, false)?
+        };
+
+        let inner = unsafe {
+            let result = if variable_binding.is_some() {
+                pool.alloc_variable(iter::once((&*layout, variable_count)))
+            } else {
+                pool.alloc(iter::once(&*layout))
+            };
+
+            match result {
+                Ok(mut sets) => sets.next().unwrap(),
+                Err(DescriptorPoolAllocError::OutOfHostMemory) => {
+                    return Err(OomError::OutOfHostMemory);
+                },
+                Err(DescriptorPoolAllocError::OutOfDeviceMemory) => {
+                    return Err(OomError::OutOfDeviceMemory);
+                },
+                // The pool was created with exactly enough space for this set.
+                Err(DescriptorPoolAllocError::FragmentedPool) => unreachable!(),
+                Err(DescriptorPoolAllocError::OutOfPoolMemory) => unreachable!(),
+            }
+        };
+
+        Ok(BindlessDescriptorSet {
+               inner: inner,
+               layout: layout,
+               variable_count: variable_count,
+               resources: Mutex::new(HashMap::new()),
+               pool: pool,
+           })
+    }
+
+    /// Returns the layout of the set.
+    #[inline]
+    pub fn layout(&self) -> &Arc<UnsafeDescriptorSetLayout> {
+        &self.layout
+    }
+
+    /// Returns the number of descriptors of the binding with a variable descriptor count.
+    #[inline]
+    pub fn variable_count(&self) -> u32 {
+        self.variable_count
+    }
+
+    /// Writes a uniform or storage buffer at the given binding and array element.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the buffer doesn't have the same device as the descriptor set.
+    ///
+    /// # Safety
+    ///
+    /// - The descriptor that is overwritten must not be in use by a command buffer that is
+    ///   pending execution, unless the binding has the `update_unused_while_pending` flag and the
+    ///   descriptor isn't used by the shaders.
+    /// - The descriptor must not be overwritten while a command buffer that uses the set is
+    ///   being recorded, unless the binding has the `update_after_bind` flag.
+    /// - Accesses to the buffer from the shaders must be synchronized manually.
+    ///
+    pub unsafe fn write_buffer<T>(&self, binding: u32, array_element: u32, buffer: T)
+                                  -> Result<(), PersistentDescriptorSetError>
+        where T: BufferAccess + Send + Sync + 'static
+    {
+        assert_eq!(self.device().internal_object(),
+                   buffer.inner().buffer.device().internal_object());
+
+        let desc = self.checked_descriptor(binding, array_element)?;
+
+        let write = match desc.ty {
+            DescriptorDescTy::Buffer(ref buffer_desc) => {
+                if buffer_desc.storage {
+                    if !buffer.inner().buffer.usage_storage_buffer() {
+                        return Err(PersistentDescriptorSetError::MissingBufferUsage(
+                                   MissingBufferUsage::StorageBuffer));
+                    }
+
+                    DescriptorWrite::storage_buffer(binding, array_element, &buffer)
+                } else {
+                    if !buffer.inner().buffer.usage_uniform_buffer() {
+                        return Err(PersistentDescriptorSetError::MissingBufferUsage(
+                                   MissingBufferUsage::UniformBuffer));
+                    }
+
+                    DescriptorWrite::uniform_buffer(binding, array_element, &buffer)
+                }
+            },
+            ref d => {
+                return Err(PersistentDescriptorSetError::WrongDescriptorTy {
+                               expected: d.ty().unwrap(),
+                           });
+            },
+        };
+
+        self.write(binding, array_element, write, Box::new(buffer));
+        Ok(())
+    }
+
+    /// Writes a sampled or storage image at the given binding and array element.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the image view doesn't have the same device as the descriptor set.
+    ///
+    /// # Safety
+    ///
+    /// Same as `write_buffer`. In addition, the image must be in the layout returned by the
+    /// `descriptor_set_*_layout` methods of `ImageViewAccess` when the set is used.
+    ///
+    pub unsafe fn write_image<T>(&self, binding: u32, array_element: u32, image_view: T)
+                                 -> Result<(), PersistentDescriptorSetError>
+        where T: ImageViewAccess + Send + Sync + 'static
+    {
+        assert_eq!(self.device().internal_object(),
+                   image_view.parent().inner().image.device().internal_object());
+
+        let desc = self.checked_descriptor(binding, array_element)?;
+
+        let write = match desc.ty {
+            DescriptorDescTy::Image(ref desc) => {
+                image_match_desc(&image_view, &desc)?;
+
+                if desc.sampled {
+                    DescriptorWrite::sampled_image(binding, array_element, &image_view)
+                } else {
+                    DescriptorWrite::storage_image(binding, array_element, &image_view)
+                }
+            },
+            ty => {
+                return Err(PersistentDescriptorSetError::WrongDescriptorTy {
+                               expected: ty.ty().unwrap(),
+                           });
+            },
+        };
+
+        self.write(binding, array_element, write, Box::new(image_view));
+        Ok(())
+    }
+
+    /// Writes an image view with a sampler at the given binding and array element.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the image view or the sampler doesn't have the same device as the descriptor set.
+    ///
+    /// # Safety
+    ///
+    /// Same as `write_image`.
+    ///
+    pub unsafe fn write_sampled_image<T>(&self, binding: u32, array_element: u32, image_view: T,
+                                         sampler: Arc<Sampler>)
+                                         -> Result<(), PersistentDescriptorSetError>
+        where T: ImageViewAccess + Send + Sync + 'static
+    {
+        assert_eq!(self.device().internal_object(),
+                   image_view.parent().inner().image.device().internal_object());
+        assert_eq!(self.device().internal_object(), sampler.device().internal_object());
+
+        let desc = self.checked_descriptor(binding, array_element)?;
+
+        if !image_view.can_be_sampled(&sampler) {
+            return Err(PersistentDescriptorSetError::IncompatibleImageViewSampler);
+        }
+
+        let write = match desc.ty {
+            DescriptorDescTy::CombinedImageSampler(ref desc) => {
+                image_match_desc(&image_view, &desc)?;
+                DescriptorWrite::combined_image_sampler(binding,
+                                                        array_element,
+                                                        &sampler,
+                                                        &image_view)
+            },
+            ty => {
+                return Err(PersistentDescriptorSetError::WrongDescriptorTy {
+                               expected: ty.ty().unwrap(),
+                           });
+            },
+        };
+
+        self.write(binding, array_element, write, Box::new((image_view, sampler)));
+        Ok(())
+    }
+
+    /// Writes a sampler at the given binding and array element.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the sampler doesn't have the same device as the descriptor set.
+    ///
+    /// # Safety
+    ///
+    /// Same as `write_buffer`.
+    ///
+    pub unsafe fn write_sampler(&self, binding: u32, array_element: u32, sampler: Arc<Sampler>)
+                                -> Result<(), PersistentDescriptorSetError> {
+        assert_eq!(self.device().internal_object(), sampler.device().internal_object());
+
+        let desc = self.checked_descriptor(binding, array_element)?;
+
+        let write = match desc.ty {
+            DescriptorDescTy::Sampler => {
+                DescriptorWrite::sampler(binding, array_element, &sampler)
+            },
+            ty => {
+                return Err(PersistentDescriptorSetError::WrongDescriptorTy {
+                               expected: ty.ty().unwrap(),
+                           });
+            },
+        };
+
+        self.write(binding, array_element, write, Box::new(sampler));
+        Ok(())
+    }
+
+    /// Releases the resource written at the given binding and array element, if any.
+    ///
+    /// The descriptor itself is left as it is, which means that it must no longer be accessed
+    /// by the shaders. This is only valid if the binding has the `partially_bound` flag.
+    ///
+    /// # Safety
+    ///
+    /// - The resource must not be in use by a command buffer that is pending execution.
+    ///
+    pub unsafe fn clear(&self, binding: u32, array_element: u32) {
+        self.resources.lock().unwrap().remove(&(binding, array_element));
+    }
+
+    // Returns the descriptor of `binding`, checking that `array_element` is in range.
+    fn checked_descriptor(&self, binding: u32, array_element: u32)
+                          -> Result<DescriptorDesc, PersistentDescriptorSetError> {
+        let desc = match self.descriptor(binding as usize) {
+            Some(d) => d,
+            None => return Err(PersistentDescriptorSetError::EmptyExpected),
+        };
+
+        if array_element >= desc.array_count {
+            return Err(PersistentDescriptorSetError::ArrayOutOfBounds);
+        }
+
+        Ok(desc)
+    }
+
+    // Writes the descriptor and replaces the resource that was previously written in its place.
+    unsafe fn write(&self, binding: u32, array_element: u32, write: DescriptorWrite,
+                    resource: Box<dyn Any + Send + Sync>) {
+        // The lock also provides the external synchronization required by
+        // `vkUpdateDescriptorSets`.
+        let mut resources = self.resources.lock().unwrap();
+
+        let device = self.device();
+        let vk = device.pointers();
+        with_raw_writes(self.inner.internal_object(), iter::once(write), |raw_writes| {
+            vk.UpdateDescriptorSets(device.internal_object(),
+                                    raw_writes.len() as u32,
+                                    raw_writes.as_ptr(),
+                                    0,
+                                    ptr::null());
+        });
+
+        resources.insert((binding, array_element), resource);
+    }
+}
+
+unsafe impl DescriptorSet for BindlessDescriptorSet {
+    #[inline]
+    fn inner(&self) -> &UnsafeDescriptorSet {
+        &self.inner
+    }
+
+    // The resources aren't reported, as they can be replaced after the set has been bound. See
+    // the "Synchronization" section of the documentation of `BindlessDescriptorSet`.
+    #[inline]
+    fn num_buffers(&self) -> usize {
+        0
+    }
+
+    #[inline]
+    fn buffer(&self, _: usize) -> Option<(&dyn BufferAccess, u32)> {
+        None
+    }
+
+    #[inline]
+    fn num_images(&self) -> usize {
+        0
+    }
+
+    #[inline]
+    fn image(&self, _: usize) -> Option<(&dyn ImageViewAccess, u32)> {
+        None
+    }
+}
+
+unsafe impl DescriptorSetDesc for BindlessDescriptorSet {
+    #[inline]
+    fn num_bindings(&self) -> usize {
+        self.layout.num_bindings()
+    }
+
+    #[inline]
+    fn descriptor(&self, binding: usize) -> Option<DescriptorDesc> {
+        let mut desc = self.layout.descriptor(binding)?;
+        if self.layout.variable_descriptor_count_binding() == Some(binding) {
+            desc.array_count = self.variable_count;
+        }
+        Some(desc)
+    }
+}
+
+unsafe impl DeviceOwned for BindlessDescriptorSet {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        self.layout.device()
+    }
+}
+
+impl fmt::Debug for BindlessDescriptorSet {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        fmt.debug_struct("BindlessDescriptorSet")
+            .field("inner", &self.inner)
+            .field("layout", &self.layout)
+            .field("variable_count", &self.variable_count)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use command_buffer::AutoCommandBufferBuilder;
+    use descriptor::descriptor::DescriptorDesc;
+    use descriptor::descriptor::DescriptorDescTy;
+    use descriptor::descriptor::ShaderStages;
+    use descriptor::descriptor_set::DescriptorBindingFlags;
+    use descriptor::descriptor_set::PersistentDescriptorSetError;
+    use descriptor::descriptor_set::UnsafeDescriptorSetLayout;
+    use descriptor::pipeline_layout::PipelineLayoutAbstract;
+    use descriptor::pipeline_layout::PipelineLayoutDesc;
+    use descriptor::pipeline_layout::PipelineLayoutDescBindless;
+    use descriptor::pipeline_layout::PipelineLayoutDescPcRange;
+    use mock::Recorder;
+    use pipeline::ComputePipeline;
+    use pipeline::shader::ShaderModule;
+    use sampler::Sampler;
+    use std::ffi::CStr;
+    use std::iter;
+    use std::sync::Arc;
+    use super::BindlessDescriptorSet;
+
+    // Layout of a compute shader with a runtime-sized array of samplers.
+    #[derive(Debug, Copy, Clone)]
+    struct RuntimeArrayLayout;
+
+    unsafe impl PipelineLayoutDesc for RuntimeArrayLayout {
+        fn num_sets(&self) -> usize {
+            1
+        }
+
+        fn num_bindings_in_set(&self, set: usize) -> Option<usize> {
+            match set {
+                0 => Some(1),
+                _ => None,
+            }
+        }
+
+        fn descriptor(&self, set: usize, binding: usize) -> Option<DescriptorDesc> {
+            match (set, binding) {
+                (0, 0) => Some(DescriptorDesc {
+                                   ty: DescriptorDescTy::Sampler,
+                                   array_count: 1,
+                                   stages: ShaderStages {
+                                       compute: true,
+                                       .. ShaderStages::none()
+                                   },
+                                   readonly: true,
+                               }),
+                _ => None,
+            }
+        }
+
+        fn is_runtime_array(&self, set: usize, binding: usize) -> bool {
+            set == 0 && binding == 0
+        }
+
+        fn num_push_constants_ranges(&self) -> usize {
+            0
+        }
+
+        fn push_constants_range(&self, _: usize) -> Option<PipelineLayoutDescPcRange> {
+            None
+        }
+    }
+
+    #[test]
+    fn dispatch_with_smaller_variable_count() {
+        let (device, queue) = mock_dev_and_queue!(extensions: [ext_descriptor_indexing]);
+
+        let desc = PipelineLayoutDescBindless::new(device.clone(), RuntimeArrayLayout, 0, 16,
+                                                   DescriptorBindingFlags::none())
+            .unwrap();
+        let pipeline = unsafe {
+            // The mock doesn't look at the code of the module.
+            let module = ShaderModule::from_words(device.clone(), &[0x07230203]).unwrap();
+            let name = CStr::from_bytes_with_nul(b"main\0").unwrap();
+            let shader = module.compute_entry_point(name, RuntimeArrayLayout);
+            let layout = desc.build(device.clone()).unwrap();
+            Arc::new(ComputePipeline::with_pipeline_layout(device.clone(), &shader, &(), layout)
+                         .unwrap())
+        };
+
+        // The set only has 4 of the 16 descriptors that the layout allows.
+        let layout = pipeline.layout().descriptor_set_layout(0).unwrap().clone();
+        let set = Arc::new(BindlessDescriptorSet::new(layout, 4).unwrap());
+
+        AutoCommandBufferBuilder::new(device.clone(), queue.family())
+            .unwrap()
+            .dispatch([1, 1, 1], pipeline, set, ())
+            .unwrap();
+    }
+
+    #[test]
+    fn write_sampler() {
+        let (device, _) = mock_dev_and_queue!();
+        let recorder = Recorder::of(device.instance()).unwrap();
+
+        let desc = DescriptorDesc {
+            ty: DescriptorDescTy::Sampler,
+            array_count: 4,
+            stages: ShaderStages::all_graphics(),
+            readonly: true,
+        };
+        let layout = UnsafeDescriptorSetLayout::new(device.clone(), iter::once(Some(desc)))
+            .unwrap();
+        let set = BindlessDescriptorSet::new(Arc::new(layout), 0).unwrap();
+        let sampler = Sampler::simple_repeat_linear(device.clone());
+
+        unsafe {
+            set.write_sampler(0, 3, sampler.clone()).unwrap();
+
+            match set.write_sampler(0, 4, sampler.clone()) {
+                Err(PersistentDescriptorSetError::ArrayOutOfBounds) => (),
+                _ => panic!(),
+            }
+
+            match set.write_sampler(1, 0, sampler) {
+                Err(PersistentDescriptorSetError::EmptyExpected) => (),
+                _ => panic!(),
+            }
+        }
+
+        let calls = recorder.calls();
+        assert_eq!(calls.iter().filter(|&&c| c == "vkUpdateDescriptorSets").count(), 1);
+        assert!(recorder.errors().is_empty());
+    }
+}
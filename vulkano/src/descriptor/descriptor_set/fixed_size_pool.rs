@@ -22,6 +22,7 @@ use descriptor::descriptor_set::DescriptorPoolAlloc;
 use descriptor::descriptor_set::DescriptorPoolAllocError;
 use descriptor::descriptor_set::DescriptorSet;
 use descriptor::descriptor_set::DescriptorSetDesc;
+use descriptor::descriptor_set::DescriptorUpdateTemplate;
 use descriptor::descriptor_set::UnsafeDescriptorPool;
 use descriptor::descriptor_set::UnsafeDescriptorSet;
 use descriptor::descriptor_set::UnsafeDescriptorSetLayout;
@@ -30,6 +31,7 @@ use device::Device;
 use device::DeviceOwned;
 use image::ImageViewAccess;
 use sampler::Sampler;
+use version::Version;
 
 /// Pool of descriptor sets of a specific capacity and that are automatically reclaimed.
 ///
@@ -73,6 +75,8 @@ use sampler::Sampler;
 #[derive(Clone)]
 pub struct FixedSizeDescriptorSetsPool {
     layout: Arc<UnsafeDescriptorSetLayout>,
+    // Template used to write the descriptors, if `VK_KHR_descriptor_update_template` is enabled.
+    template: Option<Arc<DescriptorUpdateTemplate<[u8]>>>,
     // We hold a local implementation of the `DescriptorPool` trait for our own purpose. Since we
     // don't want to expose this trait impl in our API, we use a separate struct.
     pool: LocalPool,
@@ -85,8 +89,23 @@ impl FixedSizeDescriptorSetsPool {
     {
         let device = layout.device().clone();
 
+        // Writing through a template is only an optimization, so we silently fall back to regular
+        // writes if the template can't be created.
+        let template = if (device.api_version() >= Version::V1_1 ||
+                             device.loaded_extensions().khr_descriptor_update_template) &&
+            !layout.is_push_descriptor()
+        {
+            DescriptorUpdateTemplate::packed(layout.clone())
+                .ok()
+                .and_then(|t| t)
+                .map(Arc::new)
+        } else {
+            None
+        };
+
         FixedSizeDescriptorSetsPool {
             layout,
+            template,
             pool: LocalPool {
                 device: device,
                 next_capacity: 3,
@@ -317,7 +336,9 @@ impl<'a, R> FixedSizeDescriptorSetBuilder<'a, R>
     /// Builds a `FixedSizeDescriptorSet` from the builder.
     #[inline]
     pub fn build(self) -> Result<FixedSizeDescriptorSet<R>, PersistentDescriptorSetBuildError> {
-        let inner = self.inner.build_with_pool(&mut self.pool.pool)?;
+        let template = self.pool.template.as_ref().map(|t| &**t);
+        let inner = self.inner
+            .build_with_pool_and_template(&mut self.pool.pool, template)?;
         Ok(FixedSizeDescriptorSet { inner: inner })
     }
 
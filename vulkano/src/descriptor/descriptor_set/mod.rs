@@ -32,6 +32,10 @@
 //!   a safe way. A Vulkan descriptor set is inherently unsafe, so we need safe wrappers around
 //!   them.
 //! - The `SimpleDescriptorSet` type is a default implementation of the `DescriptorSet` trait.
+//! - The `RuntimeDescriptorSet` type is an implementation of the `DescriptorSet` trait whose list
+//!   of resources is only known at runtime.
+//! - The `BindlessDescriptorSet` type is an implementation of the `DescriptorSet` trait whose
+//!   descriptors can be written individually after the set has been bound.
 //! - The `DescriptorSetsCollection` trait is implemented on collections of types that implement
 //!   `DescriptorSet`. It is what you pass to the draw functions.
 
@@ -45,6 +49,7 @@ use device::DeviceOwned;
 use descriptor::descriptor::DescriptorDesc;
 use image::ImageViewAccess;
 
+pub use self::bindless::BindlessDescriptorSet;
 pub use self::collection::DescriptorSetsCollection;
 pub use self::fixed_size_pool::FixedSizeDescriptorSet;
 pub use self::fixed_size_pool::FixedSizeDescriptorSetBuilder;
@@ -59,6 +64,8 @@ pub use self::persistent::PersistentDescriptorSetBuilderArray;
 pub use self::persistent::PersistentDescriptorSetError;
 pub use self::persistent::PersistentDescriptorSetImg;
 pub use self::persistent::PersistentDescriptorSetSampler;
+pub use self::runtime::RuntimeDescriptorSet;
+pub use self::runtime::RuntimeDescriptorSetBuilder;
 pub use self::std_pool::StdDescriptorPool;
 pub use self::std_pool::StdDescriptorPoolAlloc;
 pub use self::sys::DescriptorPool;
@@ -69,15 +76,30 @@ pub use self::sys::DescriptorsCount;
 pub use self::sys::UnsafeDescriptorPool;
 pub use self::sys::UnsafeDescriptorPoolAllocIter;
 pub use self::sys::UnsafeDescriptorSet;
+pub use self::unsafe_layout::DescriptorBindingFlags;
+pub use self::unsafe_layout::DescriptorSetLayoutCreationError;
 pub use self::unsafe_layout::UnsafeDescriptorSetLayout;
-
+pub use self::update_template::DescriptorBufferInfo;
+pub use self::update_template::DescriptorImageInfo;
+pub use self::update_template::DescriptorTexelBufferInfo;
+pub use self::update_template::DescriptorUpdateData;
+pub use self::update_template::DescriptorUpdateTemplate;
+pub use self::update_template::DescriptorUpdateTemplateCreationError;
+pub use self::update_template::DescriptorUpdateTemplateEntry;
+
+pub(crate) use self::persistent::PersistentDescriptorSetResources;
+pub(crate) use self::sys::with_raw_writes;
+
+mod bindless;
 pub mod collection;
 
 mod fixed_size_pool;
 mod persistent;
+mod runtime;
 mod std_pool;
 mod sys;
 mod unsafe_layout;
+mod update_template;
 
 /// Trait for objects that contain a collection of resources that will be accessible by shaders.
 ///
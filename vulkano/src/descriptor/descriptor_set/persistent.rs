@@ -16,6 +16,7 @@ use std::sync::Arc;
 use OomError;
 use VulkanObject;
 use buffer::BufferAccess;
+use buffer::BufferView;
 use buffer::BufferViewRef;
 use descriptor::descriptor::DescriptorDesc;
 use descriptor::descriptor::DescriptorDescTy;
@@ -27,6 +28,7 @@ use descriptor::descriptor_set::DescriptorPool;
 use descriptor::descriptor_set::DescriptorPoolAlloc;
 use descriptor::descriptor_set::DescriptorSet;
 use descriptor::descriptor_set::DescriptorSetDesc;
+use descriptor::descriptor_set::DescriptorUpdateTemplate;
 use descriptor::descriptor_set::DescriptorWrite;
 use descriptor::descriptor_set::StdDescriptorPoolAlloc;
 use descriptor::descriptor_set::UnsafeDescriptorSet;
@@ -195,29 +197,43 @@ impl<R> PersistentDescriptorSetBuilder<R>
     ///
     /// Panics if the pool doesn't have the same device as the descriptor set layout.
     ///
+    #[inline]
     pub fn build_with_pool<P>(
         self, pool: &mut P)
         -> Result<PersistentDescriptorSet<R, P::Alloc>, PersistentDescriptorSetBuildError>
         where P: ?Sized + DescriptorPool
+    {
+        self.build_with_pool_and_template(pool, None)
+    }
+
+    /// Same as `build_with_pool`, but writes the descriptors with the given packed update
+    /// template if there is one.
+    ///
+    /// The template must have been created with `DescriptorUpdateTemplate::packed` from the
+    /// layout of the builder.
+    pub(crate) fn build_with_pool_and_template<P>(
+        self, pool: &mut P, template: Option<&DescriptorUpdateTemplate<[u8]>>)
+        -> Result<PersistentDescriptorSet<R, P::Alloc>, PersistentDescriptorSetBuildError>
+        where P: ?Sized + DescriptorPool
     {
         assert_eq!(self.layout.device().internal_object(),
                    pool.device().internal_object());
 
-        let expected_desc = self.layout.num_bindings();
-
-        if expected_desc > self.binding_id {
-            return Err(PersistentDescriptorSetBuildError::MissingDescriptors {
-                           expected: expected_desc as u32,
-                           obtained: self.binding_id as u32,
-                       });
-        }
-
-        debug_assert_eq!(expected_desc, self.binding_id);
+        self.check_complete()?;
 
         let set = unsafe {
             let mut set = pool.alloc(&self.layout)?;
-            set.inner_mut()
-                .write(pool.device(), self.writes.into_iter());
+            match template {
+                Some(template) => {
+                    debug_assert_eq!(template.layout().internal_object(),
+                                     self.layout.internal_object());
+                    template.write_packed(set.inner_mut(), self.writes.into_iter());
+                },
+                None => {
+                    set.inner_mut()
+                        .write(pool.device(), self.writes.into_iter());
+                },
+            }
             set
         };
 
@@ -228,6 +244,32 @@ impl<R> PersistentDescriptorSetBuilder<R>
            })
     }
 
+    /// Checks that all the descriptors of the set were provided, then splits the builder into
+    /// its layout, the writes to perform and the resources to keep alive.
+    ///
+    /// Used to push the descriptors into a command buffer instead of writing them to a set.
+    pub(crate) fn into_push_descriptors(
+        self)
+        -> Result<(Arc<UnsafeDescriptorSetLayout>, Vec<DescriptorWrite>, R),
+                  PersistentDescriptorSetBuildError> {
+        self.check_complete()?;
+        Ok((self.layout, self.writes, self.resources))
+    }
+
+    fn check_complete(&self) -> Result<(), PersistentDescriptorSetBuildError> {
+        let expected_desc = self.layout.num_bindings();
+
+        if expected_desc > self.binding_id {
+            return Err(PersistentDescriptorSetBuildError::MissingDescriptors {
+                           expected: expected_desc as u32,
+                           obtained: self.binding_id as u32,
+                       });
+        }
+
+        debug_assert_eq!(expected_desc, self.binding_id);
+        Ok(())
+    }
+
     /// Call this function if the next element of the set is an array in order to set the value of
     /// each element.
     ///
@@ -395,60 +437,11 @@ impl<R> PersistentDescriptorSetBuilderArray<R>
         -> Result<PersistentDescriptorSetBuilderArray<(R, PersistentDescriptorSetBuf<T>)>, PersistentDescriptorSetError>
         where T: BufferAccess
     {
-        assert_eq!(self.builder.layout.device().internal_object(),
-                   buffer.inner().buffer.device().internal_object());
-
-        if self.array_element as u32 >= self.desc.array_count {
-            return Err(PersistentDescriptorSetError::ArrayOutOfBounds);
-        }
-
-        self.builder.writes.push(match self.desc.ty {
-            DescriptorDescTy::Buffer(ref buffer_desc) => {
-                // Note that the buffer content is not checked. This is technically not unsafe as
-                // long as the data in the buffer has no invalid memory representation (ie. no
-                // bool, no enum, no pointer, no str) and as long as the robust buffer access
-                // feature is enabled.
-                // TODO: this is not checked ^
-
-                // TODO: eventually shouldn't be an assert ; for now robust_buffer_access is always
-                //       enabled so this assert should never fail in practice, but we put it anyway
-                //       in case we forget to adjust this code
-                assert!(self.builder
-                            .layout
-                            .device()
-                            .enabled_features()
-                            .robust_buffer_access);
-
-                if buffer_desc.storage {
-                    if !buffer.inner().buffer.usage_storage_buffer() {
-                        return Err(PersistentDescriptorSetError::MissingBufferUsage(
-                                   MissingBufferUsage::StorageBuffer));
-                    }
-
-                    unsafe {
-                        DescriptorWrite::storage_buffer(self.builder.binding_id as u32,
-                                                        self.array_element as u32,
-                                                        &buffer)
-                    }
-                } else {
-                    if !buffer.inner().buffer.usage_uniform_buffer() {
-                        return Err(PersistentDescriptorSetError::MissingBufferUsage(
-                                   MissingBufferUsage::UniformBuffer));
-                    }
-
-                    unsafe {
-                        DescriptorWrite::uniform_buffer(self.builder.binding_id as u32,
-                                                        self.array_element as u32,
-                                                        &buffer)
-                    }
-                }
-            },
-            ref d => {
-                return Err(PersistentDescriptorSetError::WrongDescriptorTy {
-                               expected: d.ty().unwrap(),
-                           });
-            },
-        });
+        self.builder.writes.push(buffer_write(&self.builder.layout,
+                                              &self.desc,
+                                              self.builder.binding_id,
+                                              self.array_element,
+                                              &buffer)?);
 
         Ok(PersistentDescriptorSetBuilderArray {
                builder: PersistentDescriptorSetBuilder {
@@ -478,43 +471,11 @@ impl<R> PersistentDescriptorSetBuilderArray<R>
         -> Result<PersistentDescriptorSetBuilderArray<(R, PersistentDescriptorSetBufView<T>)>, PersistentDescriptorSetError>
         where T: BufferViewRef
     {
-        assert_eq!(self.builder.layout.device().internal_object(),
-                   view.view().device().internal_object());
-
-        if self.array_element as u32 >= self.desc.array_count {
-            return Err(PersistentDescriptorSetError::ArrayOutOfBounds);
-        }
-
-        self.builder.writes.push(match self.desc.ty {
-            DescriptorDescTy::TexelBuffer { storage, .. } => {
-                if storage {
-                    // TODO: storage_texel_buffer_atomic
-
-                    if !view.view().storage_texel_buffer() {
-                        return Err(PersistentDescriptorSetError::MissingBufferUsage(
-                                   MissingBufferUsage::StorageTexelBuffer));
-                    }
-
-                    DescriptorWrite::storage_texel_buffer(self.builder.binding_id as u32,
-                                                          self.array_element as u32,
-                                                          view.view())
-                } else {
-                    if !view.view().uniform_texel_buffer() {
-                        return Err(PersistentDescriptorSetError::MissingBufferUsage(
-                                   MissingBufferUsage::UniformTexelBuffer));
-                    }
-
-                    DescriptorWrite::uniform_texel_buffer(self.builder.binding_id as u32,
-                                                          self.array_element as u32,
-                                                          view.view())
-                }
-            },
-            ref d => {
-                return Err(PersistentDescriptorSetError::WrongDescriptorTy {
-                               expected: d.ty().unwrap(),
-                           });
-            },
-        });
+        self.builder.writes.push(buffer_view_write(&self.builder.layout,
+                                                   &self.desc,
+                                                   self.builder.binding_id,
+                                                   self.array_element,
+                                                   view.view())?);
 
         Ok(PersistentDescriptorSetBuilderArray {
                builder: PersistentDescriptorSetBuilder {
@@ -544,82 +505,11 @@ impl<R> PersistentDescriptorSetBuilderArray<R>
         -> Result<PersistentDescriptorSetBuilderArray<(R, PersistentDescriptorSetImg<T>)>, PersistentDescriptorSetError>
         where T: ImageViewAccess
     {
-        assert_eq!(self.builder.layout.device().internal_object(),
-                   image_view.parent().inner().image.device().internal_object());
-
-        if self.array_element as u32 >= self.desc.array_count {
-            return Err(PersistentDescriptorSetError::ArrayOutOfBounds);
-        }
-
-        let desc = match self.builder
-            .layout
-            .descriptor(self.builder.binding_id) {
-            Some(d) => d,
-            None => return Err(PersistentDescriptorSetError::EmptyExpected),
-        };
-
-        self.builder.writes.push(match desc.ty {
-            DescriptorDescTy::Image(ref desc) => {
-                image_match_desc(&image_view, &desc)?;
-
-                if desc.sampled {
-                    DescriptorWrite::sampled_image(self.builder.binding_id as u32,
-                                                   self.array_element as u32,
-                                                   &image_view)
-                } else {
-                    DescriptorWrite::storage_image(self.builder.binding_id as u32,
-                                                   self.array_element as u32,
-                                                   &image_view)
-                }
-            },
-            DescriptorDescTy::InputAttachment {
-                multisampled,
-                array_layers,
-            } => {
-                if !image_view.parent().inner().image.usage_input_attachment() {
-                    return Err(PersistentDescriptorSetError::MissingImageUsage(
-                                   MissingImageUsage::InputAttachment));
-                }
-
-                if multisampled && image_view.samples() == 1 {
-                    return Err(PersistentDescriptorSetError::ExpectedMultisampled);
-                } else if !multisampled && image_view.samples() != 1 {
-                    return Err(PersistentDescriptorSetError::UnexpectedMultisampled);
-                }
-
-                let image_layers = image_view.dimensions().array_layers();
-
-                match array_layers {
-                    DescriptorImageDescArray::NonArrayed => {
-                        if image_layers != 1 {
-                            return Err(PersistentDescriptorSetError::ArrayLayersMismatch {
-                                           expected: 1,
-                                           obtained: image_layers,
-                                       });
-                        }
-                    },
-                    DescriptorImageDescArray::Arrayed { max_layers: Some(max_layers) } => {
-                        if image_layers > max_layers {
-                            // TODO: is this correct? "max" layers? or is it in fact min layers?
-                            return Err(PersistentDescriptorSetError::ArrayLayersMismatch {
-                                           expected: max_layers,
-                                           obtained: image_layers,
-                                       });
-                        }
-                    },
-                    DescriptorImageDescArray::Arrayed { max_layers: None } => {},
-                };
-
-                DescriptorWrite::input_attachment(self.builder.binding_id as u32,
-                                                  self.array_element as u32,
-                                                  &image_view)
-            },
-            ty => {
-                return Err(PersistentDescriptorSetError::WrongDescriptorTy {
-                               expected: ty.ty().unwrap(),
-                           });
-            },
-        });
+        self.builder.writes.push(image_write(&self.builder.layout,
+                                             &self.desc,
+                                             self.builder.binding_id,
+                                             self.array_element,
+                                             &image_view)?);
 
         Ok(PersistentDescriptorSetBuilderArray {
                builder: PersistentDescriptorSetBuilder {
@@ -649,40 +539,12 @@ impl<R> PersistentDescriptorSetBuilderArray<R>
         -> Result<PersistentDescriptorSetBuilderArray<((R, PersistentDescriptorSetImg<T>), PersistentDescriptorSetSampler)>, PersistentDescriptorSetError>
         where T: ImageViewAccess
     {
-        assert_eq!(self.builder.layout.device().internal_object(),
-                   image_view.parent().inner().image.device().internal_object());
-        assert_eq!(self.builder.layout.device().internal_object(),
-                   sampler.device().internal_object());
-
-        if self.array_element as u32 >= self.desc.array_count {
-            return Err(PersistentDescriptorSetError::ArrayOutOfBounds);
-        }
-
-        let desc = match self.builder
-            .layout
-            .descriptor(self.builder.binding_id) {
-            Some(d) => d,
-            None => return Err(PersistentDescriptorSetError::EmptyExpected),
-        };
-
-        if !image_view.can_be_sampled(&sampler) {
-            return Err(PersistentDescriptorSetError::IncompatibleImageViewSampler);
-        }
-
-        self.builder.writes.push(match desc.ty {
-            DescriptorDescTy::CombinedImageSampler(ref desc) => {
-                image_match_desc(&image_view, &desc)?;
-                DescriptorWrite::combined_image_sampler(self.builder.binding_id as u32,
-                                                        self.array_element as u32,
-                                                        &sampler,
-                                                        &image_view)
-            },
-            ty => {
-                return Err(PersistentDescriptorSetError::WrongDescriptorTy {
-                               expected: ty.ty().unwrap(),
-                           });
-            },
-        });
+        self.builder.writes.push(sampled_image_write(&self.builder.layout,
+                                                     &self.desc,
+                                                     self.builder.binding_id,
+                                                     self.array_element,
+                                                     &image_view,
+                                                     &sampler)?);
 
         Ok(PersistentDescriptorSetBuilderArray {
                builder: PersistentDescriptorSetBuilder {
@@ -712,32 +574,11 @@ impl<R> PersistentDescriptorSetBuilderArray<R>
     pub fn add_sampler(mut self, sampler: Arc<Sampler>)
         -> Result<PersistentDescriptorSetBuilderArray<(R, PersistentDescriptorSetSampler)>, PersistentDescriptorSetError>
     {
-        assert_eq!(self.builder.layout.device().internal_object(),
-                   sampler.device().internal_object());
-
-        if self.array_element as u32 >= self.desc.array_count {
-            return Err(PersistentDescriptorSetError::ArrayOutOfBounds);
-        }
-
-        let desc = match self.builder
-            .layout
-            .descriptor(self.builder.binding_id) {
-            Some(d) => d,
-            None => return Err(PersistentDescriptorSetError::EmptyExpected),
-        };
-
-        self.builder.writes.push(match desc.ty {
-            DescriptorDescTy::Sampler => {
-                DescriptorWrite::sampler(self.builder.binding_id as u32,
-                                         self.array_element as u32,
-                                         &sampler)
-            },
-            ty => {
-                return Err(PersistentDescriptorSetError::WrongDescriptorTy {
-                               expected: ty.ty().unwrap(),
-                           });
-            },
-        });
+        self.builder.writes.push(sampler_write(&self.builder.layout,
+                                               &self.desc,
+                                               self.builder.binding_id,
+                                               self.array_element,
+                                               &sampler)?);
 
         Ok(PersistentDescriptorSetBuilderArray {
                builder: PersistentDescriptorSetBuilder {
@@ -753,8 +594,249 @@ impl<R> PersistentDescriptorSetBuilderArray<R>
     }
 }
 
+// The functions below check whether a resource can be written to the element `array_element` of
+// the binding `binding_id`, whose description is `desc`, and return the corresponding write.
+//
+// They are shared between `PersistentDescriptorSetBuilderArray` and
+// `RuntimeDescriptorSetBuilder`.
+
+pub(super) fn buffer_write<T>(layout: &UnsafeDescriptorSetLayout, desc: &DescriptorDesc,
+                              binding_id: usize, array_element: usize, buffer: &T)
+                              -> Result<DescriptorWrite, PersistentDescriptorSetError>
+    where T: ?Sized + BufferAccess
+{
+    assert_eq!(layout.device().internal_object(),
+               buffer.inner().buffer.device().internal_object());
+
+    if array_element as u32 >= desc.array_count {
+        return Err(PersistentDescriptorSetError::ArrayOutOfBounds);
+    }
+
+    Ok(match desc.ty {
+        DescriptorDescTy::Buffer(ref buffer_desc) => {
+            // Note that the buffer content is not checked. This is technically not unsafe as
+            // long as the data in the buffer has no invalid memory representation (ie. no
+            // bool, no enum, no pointer, no str) and as long as the robust buffer access
+            // feature is enabled.
+            // TODO: this is not checked ^
+
+            // TODO: eventually shouldn't be an assert ; for now robust_buffer_access is always
+            //       enabled so this assert should never fail in practice, but we put it anyway
+            //       in case we forget to adjust this code
+            assert!(layout.device().enabled_features().robust_buffer_access);
+
+            if buffer_desc.storage {
+                if !buffer.inner().buffer.usage_storage_buffer() {
+                    return Err(PersistentDescriptorSetError::MissingBufferUsage(
+                               MissingBufferUsage::StorageBuffer));
+                }
+
+                unsafe {
+                    DescriptorWrite::storage_buffer(binding_id as u32,
+                                                    array_element as u32,
+                                                    buffer)
+                }
+            } else {
+                if !buffer.inner().buffer.usage_uniform_buffer() {
+                    return Err(PersistentDescriptorSetError::MissingBufferUsage(
+                               MissingBufferUsage::UniformBuffer));
+                }
+
+                unsafe {
+                    DescriptorWrite::uniform_buffer(binding_id as u32,
+                                                    array_element as u32,
+                                                    buffer)
+                }
+            }
+        },
+        ref d => {
+            return Err(PersistentDescriptorSetError::WrongDescriptorTy {
+                           expected: d.ty().unwrap(),
+                       });
+        },
+    })
+}
+
+pub(super) fn buffer_view_write<F, B>(layout: &UnsafeDescriptorSetLayout, desc: &DescriptorDesc,
+                                      binding_id: usize, array_element: usize,
+                                      view: &BufferView<F, B>)
+                                      -> Result<DescriptorWrite, PersistentDescriptorSetError>
+    where B: BufferAccess
+{
+    assert_eq!(layout.device().internal_object(),
+               view.device().internal_object());
+
+    if array_element as u32 >= desc.array_count {
+        return Err(PersistentDescriptorSetError::ArrayOutOfBounds);
+    }
+
+    Ok(match desc.ty {
+        DescriptorDescTy::TexelBuffer { storage, .. } => {
+            if storage {
+                // TODO: storage_texel_buffer_atomic
+
+                if !view.storage_texel_buffer() {
+                    return Err(PersistentDescriptorSetError::MissingBufferUsage(
+                               MissingBufferUsage::StorageTexelBuffer));
+                }
+
+                DescriptorWrite::storage_texel_buffer(binding_id as u32,
+                                                      array_element as u32,
+                                                      view)
+            } else {
+                if !view.uniform_texel_buffer() {
+                    return Err(PersistentDescriptorSetError::MissingBufferUsage(
+                               MissingBufferUsage::UniformTexelBuffer));
+                }
+
+                DescriptorWrite::uniform_texel_buffer(binding_id as u32,
+                                                      array_element as u32,
+                                                      view)
+            }
+        },
+        ref d => {
+            return Err(PersistentDescriptorSetError::WrongDescriptorTy {
+                           expected: d.ty().unwrap(),
+                       });
+        },
+    })
+}
+
+pub(super) fn image_write<T>(layout: &UnsafeDescriptorSetLayout, desc: &DescriptorDesc,
+                             binding_id: usize, array_element: usize, image_view: &T)
+                             -> Result<DescriptorWrite, PersistentDescriptorSetError>
+    where T: ?Sized + ImageViewAccess
+{
+    assert_eq!(layout.device().internal_object(),
+               image_view.parent().inner().image.device().internal_object());
+
+    if array_element as u32 >= desc.array_count {
+        return Err(PersistentDescriptorSetError::ArrayOutOfBounds);
+    }
+
+    Ok(match desc.ty {
+        DescriptorDescTy::Image(ref desc) => {
+            image_match_desc(image_view, &desc)?;
+
+            if desc.sampled {
+                DescriptorWrite::sampled_image(binding_id as u32,
+                                               array_element as u32,
+                                               image_view)
+            } else {
+                DescriptorWrite::storage_image(binding_id as u32,
+                                               array_element as u32,
+                                               image_view)
+            }
+        },
+        DescriptorDescTy::InputAttachment {
+            multisampled,
+            array_layers,
+        } => {
+            if !image_view.parent().inner().image.usage_input_attachment() {
+                return Err(PersistentDescriptorSetError::MissingImageUsage(
+                               MissingImageUsage::InputAttachment));
+            }
+
+            if multisampled && image_view.samples() == 1 {
+                return Err(PersistentDescriptorSetError::ExpectedMultisampled);
+            } else if !multisampled && image_view.samples() != 1 {
+                return Err(PersistentDescriptorSetError::UnexpectedMultisampled);
+            }
+
+            let image_layers = image_view.dimensions().array_layers();
+
+            match array_layers {
+                DescriptorImageDescArray::NonArrayed => {
+                    if image_layers != 1 {
+                        return Err(PersistentDescriptorSetError::ArrayLayersMismatch {
+                                       expected: 1,
+                                       obtained: image_layers,
+                                   });
+                    }
+                },
+                DescriptorImageDescArray::Arrayed { max_layers: Some(max_layers) } => {
+                    if image_layers > max_layers {
+                        // TODO: is this correct? "max" layers? or is it in fact min layers?
+                        return Err(PersistentDescriptorSetError::ArrayLayersMismatch {
+                                       expected: max_layers,
+                                       obtained: image_layers,
+                                   });
+                    }
+                },
+                DescriptorImageDescArray::Arrayed { max_layers: None } => {},
+            };
+
+            DescriptorWrite::input_attachment(binding_id as u32,
+                                              array_element as u32,
+                                              image_view)
+        },
+        ref ty => {
+            return Err(PersistentDescriptorSetError::WrongDescriptorTy {
+                           expected: ty.ty().unwrap(),
+                       });
+        },
+    })
+}
+
+pub(super) fn sampled_image_write<T>(layout: &UnsafeDescriptorSetLayout, desc: &DescriptorDesc,
+                                     binding_id: usize, array_element: usize, image_view: &T,
+                                     sampler: &Arc<Sampler>)
+                                     -> Result<DescriptorWrite, PersistentDescriptorSetError>
+    where T: ?Sized + ImageViewAccess
+{
+    assert_eq!(layout.device().internal_object(),
+               image_view.parent().inner().image.device().internal_object());
+    assert_eq!(layout.device().internal_object(),
+               sampler.device().internal_object());
+
+    if array_element as u32 >= desc.array_count {
+        return Err(PersistentDescriptorSetError::ArrayOutOfBounds);
+    }
+
+    if !image_view.can_be_sampled(sampler) {
+        return Err(PersistentDescriptorSetError::IncompatibleImageViewSampler);
+    }
+
+    Ok(match desc.ty {
+        DescriptorDescTy::CombinedImageSampler(ref desc) => {
+            image_match_desc(image_view, &desc)?;
+            DescriptorWrite::combined_image_sampler(binding_id as u32,
+                                                    array_element as u32,
+                                                    sampler,
+                                                    image_view)
+        },
+        ref ty => {
+            return Err(PersistentDescriptorSetError::WrongDescriptorTy {
+                           expected: ty.ty().unwrap(),
+                       });
+        },
+    })
+}
+
+pub(super) fn sampler_write(layout: &UnsafeDescriptorSetLayout, desc: &DescriptorDesc,
+                            binding_id: usize, array_element: usize, sampler: &Arc<Sampler>)
+                            -> Result<DescriptorWrite, PersistentDescriptorSetError> {
+    assert_eq!(layout.device().internal_object(),
+               sampler.device().internal_object());
+
+    if array_element as u32 >= desc.array_count {
+        return Err(PersistentDescriptorSetError::ArrayOutOfBounds);
+    }
+
+    Ok(match desc.ty {
+        DescriptorDescTy::Sampler => {
+            DescriptorWrite::sampler(binding_id as u32, array_element as u32, sampler)
+        },
+        ref ty => {
+            return Err(PersistentDescriptorSetError::WrongDescriptorTy {
+                           expected: ty.ty().unwrap(),
+                       });
+        },
+    })
+}
+
 // Checks whether an image view matches the descriptor.
-fn image_match_desc<I>(image_view: &I, desc: &DescriptorImageDesc)
+pub(super) fn image_match_desc<I>(image_view: &I, desc: &DescriptorImageDesc)
                        -> Result<(), PersistentDescriptorSetError>
     where I: ?Sized + ImageViewAccess
 {
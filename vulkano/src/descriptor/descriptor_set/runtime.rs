@@ -0,0 +1,553 @@
+// Copyright (c) 2020 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::sync::Arc;
+
+use VulkanObject;
+use buffer::BufferAccess;
+use buffer::BufferViewRef;
+use descriptor::descriptor::DescriptorDesc;
+use descriptor::descriptor_set::DescriptorPool;
+use descriptor::descriptor_set::DescriptorPoolAlloc;
+use descriptor::descriptor_set::DescriptorSet;
+use descriptor::descriptor_set::DescriptorSetDesc;
+use descriptor::descriptor_set::DescriptorWrite;
+use descriptor::descriptor_set::PersistentDescriptorSetBuildError;
+use descriptor::descriptor_set::PersistentDescriptorSetError;
+use descriptor::descriptor_set::StdDescriptorPoolAlloc;
+use descriptor::descriptor_set::UnsafeDescriptorSet;
+use descriptor::descriptor_set::UnsafeDescriptorSetLayout;
+use descriptor::descriptor_set::persistent::buffer_view_write;
+use descriptor::descriptor_set::persistent::buffer_write;
+use descriptor::descriptor_set::persistent::image_write;
+use descriptor::descriptor_set::persistent::sampled_image_write;
+use descriptor::descriptor_set::persistent::sampler_write;
+use device::Device;
+use device::DeviceOwned;
+use image::ImageViewAccess;
+use sampler::Sampler;
+
+/// An immutable descriptor set whose list of resources is only known at runtime.
+///
+/// This is the same as a `PersistentDescriptorSet`, except that the resources are stored in
+/// boxes instead of being encoded in a template parameter. This makes it possible to build
+/// descriptor sets in a loop, for example from a material definition loaded from a file, and to
+/// name the type of the set.
+///
+/// The same checks as for `PersistentDescriptorSet` are performed.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+/// use vulkano::descriptor::descriptor_set::DescriptorSet;
+/// use vulkano::descriptor::descriptor_set::RuntimeDescriptorSet;
+/// # use vulkano::descriptor::descriptor_set::UnsafeDescriptorSetLayout;
+/// # use vulkano::sampler::Sampler;
+/// # let layout: Arc<UnsafeDescriptorSetLayout> = return;
+/// # let samplers: Vec<Arc<Sampler>> = return;
+///
+/// let mut builder = RuntimeDescriptorSet::start(layout.clone());
+///
+/// builder.enter_array().unwrap();
+/// for sampler in samplers {
+///     builder.add_sampler(sampler).unwrap();
+/// }
+/// builder.leave_array().unwrap();
+///
+/// let set: Arc<dyn DescriptorSet + Send + Sync> = Arc::new(builder.build().unwrap());
+/// ```
+pub struct RuntimeDescriptorSet<P = StdDescriptorPoolAlloc> {
+    inner: P,
+    resources: RuntimeDescriptorSetResources,
+    layout: Arc<UnsafeDescriptorSetLayout>,
+}
+
+impl RuntimeDescriptorSet {
+    /// Starts the process of building a `RuntimeDescriptorSet`. Returns a builder.
+    pub fn start(layout: Arc<UnsafeDescriptorSetLayout>) -> RuntimeDescriptorSetBuilder {
+        let cap = layout.num_bindings();
+
+        RuntimeDescriptorSetBuilder {
+            layout: layout,
+            binding_id: 0,
+            array: None,
+            writes: Vec::with_capacity(cap),
+            resources: RuntimeDescriptorSetResources {
+                buffers: Vec::new(),
+                images: Vec::new(),
+                samplers: Vec::new(),
+            },
+        }
+    }
+}
+
+unsafe impl<P> DescriptorSet for RuntimeDescriptorSet<P>
+    where P: DescriptorPoolAlloc
+{
+    #[inline]
+    fn inner(&self) -> &UnsafeDescriptorSet {
+        self.inner.inner()
+    }
+
+    #[inline]
+    fn num_buffers(&self) -> usize {
+        self.resources.buffers.len()
+    }
+
+    #[inline]
+    fn buffer(&self, index: usize) -> Option<(&dyn BufferAccess, u32)> {
+        self.resources.buffers.get(index).map(|&(ref buffer, num)| {
+            let buffer = match *buffer {
+                RuntimeBuffer::Buffer(ref buffer) => &**buffer as &dyn BufferAccess,
+                RuntimeBuffer::View(ref view) => view.buffer(),
+            };
+            (buffer, num)
+        })
+    }
+
+    #[inline]
+    fn num_images(&self) -> usize {
+        self.resources.images.len()
+    }
+
+    #[inline]
+    fn image(&self, index: usize) -> Option<(&dyn ImageViewAccess, u32)> {
+        self.resources
+            .images
+            .get(index)
+            .map(|&(ref image, num)| (&**image as &dyn ImageViewAccess, num))
+    }
+}
+
+unsafe impl<P> DescriptorSetDesc for RuntimeDescriptorSet<P> {
+    #[inline]
+    fn num_bindings(&self) -> usize {
+        self.layout.num_bindings()
+    }
+
+    #[inline]
+    fn descriptor(&self, binding: usize) -> Option<DescriptorDesc> {
+        self.layout.descriptor(binding)
+    }
+}
+
+unsafe impl<P> DeviceOwned for RuntimeDescriptorSet<P> {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        self.layout.device()
+    }
+}
+
+impl<P> PartialEq for RuntimeDescriptorSet<P>
+    where P: DescriptorPoolAlloc
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.inner().internal_object() == other.inner().internal_object() &&
+        self.device() == other.device()
+    }
+}
+
+impl<P> Eq for RuntimeDescriptorSet<P>
+    where P: DescriptorPoolAlloc
+{}
+
+impl<P> Hash for RuntimeDescriptorSet<P>
+    where P: DescriptorPoolAlloc
+{
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.inner().internal_object().hash(state);
+        self.device().hash(state);
+    }
+}
+
+/// Prototype of a `RuntimeDescriptorSet`.
+///
+/// Contrary to `PersistentDescriptorSetBuilder`, the methods of this builder take `&mut self`
+/// and the builder keeps the same type no matter which resources are added. Arrays are filled by
+/// calling `enter_array`, adding the elements, then calling `leave_array`.
+///
+/// If a method returns an error, the builder is left unchanged.
+pub struct RuntimeDescriptorSetBuilder {
+    // The descriptor set layout.
+    layout: Arc<UnsafeDescriptorSetLayout>,
+    // Binding currently being filled.
+    binding_id: usize,
+    // If we're in an array, the description of the binding and the next array element.
+    array: Option<(DescriptorDesc, usize)>,
+    // The writes to perform on a descriptor set in order to put the resources in it.
+    writes: Vec<DescriptorWrite>,
+    // Holds the resources alive.
+    resources: RuntimeDescriptorSetResources,
+}
+
+impl RuntimeDescriptorSetBuilder {
+    /// Builds a `RuntimeDescriptorSet` from the builder.
+    #[inline]
+    pub fn build(self)
+                 -> Result<RuntimeDescriptorSet<StdDescriptorPoolAlloc>,
+                           PersistentDescriptorSetBuildError> {
+        let mut pool = Device::standard_descriptor_pool(self.layout.device());
+        self.build_with_pool(&mut pool)
+    }
+
+    /// Builds a `RuntimeDescriptorSet` from the builder.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the pool doesn't have the same device as the descriptor set layout.
+    ///
+    pub fn build_with_pool<P>(
+        self, pool: &mut P)
+        -> Result<RuntimeDescriptorSet<P::Alloc>, PersistentDescriptorSetBuildError>
+        where P: ?Sized + DescriptorPool
+    {
+        assert_eq!(self.layout.device().internal_object(),
+                   pool.device().internal_object());
+
+        let expected_desc = self.layout.num_bindings();
+
+        if expected_desc > self.binding_id {
+            return Err(PersistentDescriptorSetBuildError::MissingDescriptors {
+                           expected: expected_desc as u32,
+                           obtained: self.binding_id as u32,
+                       });
+        }
+
+        debug_assert!(self.array.is_none());
+
+        let set = unsafe {
+            let mut set = pool.alloc(&self.layout)?;
+            set.inner_mut()
+                .write(pool.device(), self.writes.into_iter());
+            set
+        };
+
+        Ok(RuntimeDescriptorSet {
+               inner: set,
+               resources: self.resources,
+               layout: self.layout,
+           })
+    }
+
+    /// Call this function if the next element of the set is an array in order to set the value of
+    /// each element.
+    ///
+    /// Returns an error if the descriptor is empty.
+    ///
+    /// # Panic
+    ///
+    /// Panics if already in an array.
+    ///
+    pub fn enter_array(&mut self) -> Result<&mut RuntimeDescriptorSetBuilder,
+                                            PersistentDescriptorSetError> {
+        assert!(self.array.is_none(), "already in an array");
+
+        let desc = match self.layout.descriptor(self.binding_id) {
+            Some(d) => d,
+            None => return Err(PersistentDescriptorSetError::EmptyExpected),
+        };
+
+        self.array = Some((desc, 0));
+        Ok(self)
+    }
+
+    /// Leaves the array. Call this once you added all the elements of the array.
+    ///
+    /// # Panic
+    ///
+    /// Panics if not in an array.
+    ///
+    pub fn leave_array(&mut self) -> Result<&mut RuntimeDescriptorSetBuilder,
+                                            PersistentDescriptorSetError> {
+        let (array_count, array_element) = match self.array {
+            Some((ref desc, array_element)) => (desc.array_count, array_element),
+            None => panic!("not in an array"),
+        };
+
+        if array_count > array_element as u32 {
+            return Err(PersistentDescriptorSetError::MissingArrayElements {
+                           expected: array_count,
+                           obtained: array_element as u32,
+                       });
+        }
+
+        self.array = None;
+        self.binding_id += 1;
+        Ok(self)
+    }
+
+    /// Skips the current descriptor if it is empty.
+    ///
+    /// # Panic
+    ///
+    /// Panics if in an array.
+    ///
+    pub fn add_empty(&mut self) -> Result<&mut RuntimeDescriptorSetBuilder,
+                                          PersistentDescriptorSetError> {
+        assert!(self.array.is_none(), "can't add an empty descriptor to an array");
+
+        match self.layout.descriptor(self.binding_id) {
+            None => (),
+            Some(desc) => return Err(PersistentDescriptorSetError::WrongDescriptorTy {
+                                         expected: desc.ty.ty().unwrap(),
+                                     }),
+        }
+
+        self.binding_id += 1;
+        Ok(self)
+    }
+
+    /// Binds a buffer as the next descriptor or array element.
+    ///
+    /// An error is returned if the buffer isn't compatible with the descriptor.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the buffer doesn't have the same device as the descriptor set layout.
+    ///
+    pub fn add_buffer<T>(&mut self, buffer: T)
+                         -> Result<&mut RuntimeDescriptorSetBuilder, PersistentDescriptorSetError>
+        where T: BufferAccess + Send + Sync + 'static
+    {
+        let num = self.push_write(|layout, desc, binding_id, array_element| {
+            buffer_write(layout, desc, binding_id, array_element, &buffer)
+        })?;
+
+        self.resources.buffers.push((RuntimeBuffer::Buffer(Box::new(buffer)), num));
+        Ok(self)
+    }
+
+    /// Binds a buffer view as the next descriptor or array element.
+    ///
+    /// An error is returned if the buffer isn't compatible with the descriptor.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the buffer view doesn't have the same device as the descriptor set layout.
+    ///
+    pub fn add_buffer_view<T>(&mut self, view: T)
+                              -> Result<&mut RuntimeDescriptorSetBuilder,
+                                        PersistentDescriptorSetError>
+        where T: BufferViewRef + Send + Sync + 'static
+    {
+        let num = self.push_write(|layout, desc, binding_id, array_element| {
+            buffer_view_write(layout, desc, binding_id, array_element, view.view())
+        })?;
+
+        self.resources.buffers.push((RuntimeBuffer::View(Box::new(view)), num));
+        Ok(self)
+    }
+
+    /// Binds an image view as the next descriptor or array element.
+    ///
+    /// An error is returned if the image view isn't compatible with the descriptor.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the image view doesn't have the same device as the descriptor set layout.
+    ///
+    pub fn add_image<T>(&mut self, image_view: T)
+                        -> Result<&mut RuntimeDescriptorSetBuilder, PersistentDescriptorSetError>
+        where T: ImageViewAccess + Send + Sync + 'static
+    {
+        let num = self.push_write(|layout, desc, binding_id, array_element| {
+            image_write(layout, desc, binding_id, array_element, &image_view)
+        })?;
+
+        self.resources.images.push((Box::new(image_view), num));
+        Ok(self)
+    }
+
+    /// Binds an image view with a sampler as the next descriptor or array element.
+    ///
+    /// An error is returned if the image view isn't compatible with the descriptor.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the image or the sampler doesn't have the same device as the descriptor set layout.
+    ///
+    pub fn add_sampled_image<T>(&mut self, image_view: T, sampler: Arc<Sampler>)
+                                -> Result<&mut RuntimeDescriptorSetBuilder,
+                                          PersistentDescriptorSetError>
+        where T: ImageViewAccess + Send + Sync + 'static
+    {
+        let num = self.push_write(|layout, desc, binding_id, array_element| {
+            sampled_image_write(layout, desc, binding_id, array_element, &image_view, &sampler)
+        })?;
+
+        self.resources.images.push((Box::new(image_view), num));
+        self.resources.samplers.push(sampler);
+        Ok(self)
+    }
+
+    /// Binds a sampler as the next descriptor or array element.
+    ///
+    /// An error is returned if the sampler isn't compatible with the descriptor.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the sampler doesn't have the same device as the descriptor set layout.
+    ///
+    pub fn add_sampler(&mut self, sampler: Arc<Sampler>)
+                       -> Result<&mut RuntimeDescriptorSetBuilder, PersistentDescriptorSetError> {
+        self.push_write(|layout, desc, binding_id, array_element| {
+            sampler_write(layout, desc, binding_id, array_element, &sampler)
+        })?;
+
+        self.resources.samplers.push(sampler);
+        Ok(self)
+    }
+
+    // Builds the write of the next descriptor with `f`, and advances to the next array element
+    // or binding. Returns the binding that was written.
+    //
+    // Outside of an array, this behaves like entering the array, adding one element, then
+    // leaving it.
+    fn push_write<F>(&mut self, f: F) -> Result<u32, PersistentDescriptorSetError>
+        where F: FnOnce(&UnsafeDescriptorSetLayout, &DescriptorDesc, usize, usize)
+                        -> Result<DescriptorWrite, PersistentDescriptorSetError>
+    {
+        let binding_id = self.binding_id;
+
+        match self.array {
+            Some((ref desc, ref mut array_element)) => {
+                let write = f(&self.layout, desc, binding_id, *array_element)?;
+                self.writes.push(write);
+                *array_element += 1;
+            },
+            None => {
+                let desc = match self.layout.descriptor(binding_id) {
+                    Some(d) => d,
+                    None => return Err(PersistentDescriptorSetError::EmptyExpected),
+                };
+
+                let write = f(&self.layout, &desc, binding_id, 0)?;
+
+                if desc.array_count > 1 {
+                    return Err(PersistentDescriptorSetError::MissingArrayElements {
+                                   expected: desc.array_count,
+                                   obtained: 1,
+                               });
+                }
+
+                self.writes.push(write);
+                self.binding_id += 1;
+            },
+        }
+
+        Ok(binding_id as u32)
+    }
+}
+
+// The resources held by a `RuntimeDescriptorSet`, along with the binding they are bound to.
+struct RuntimeDescriptorSetResources {
+    buffers: Vec<(RuntimeBuffer, u32)>,
+    images: Vec<(Box<dyn ImageViewAccess + Send + Sync>, u32)>,
+    samplers: Vec<Arc<Sampler>>,
+}
+
+enum RuntimeBuffer {
+    Buffer(Box<dyn BufferAccess + Send + Sync>),
+    View(Box<dyn RuntimeBufferView>),
+}
+
+// `BufferViewRef` has associated types and thus can't be turned into a trait object.
+trait RuntimeBufferView: Send + Sync {
+    fn buffer(&self) -> &dyn BufferAccess;
+}
+
+impl<T> RuntimeBufferView for T
+    where T: BufferViewRef + Send + Sync
+{
+    #[inline]
+    fn buffer(&self) -> &dyn BufferAccess {
+        self.view().buffer()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use descriptor::descriptor::DescriptorDesc;
+    use descriptor::descriptor::DescriptorDescTy;
+    use descriptor::descriptor::ShaderStages;
+    use descriptor::descriptor_set::DescriptorSet;
+    use descriptor::descriptor_set::PersistentDescriptorSetBuildError;
+    use descriptor::descriptor_set::PersistentDescriptorSetError;
+    use descriptor::descriptor_set::UnsafeDescriptorSetLayout;
+    use sampler::Sampler;
+    use std::iter;
+    use std::sync::Arc;
+    use super::RuntimeDescriptorSet;
+
+    #[test]
+    fn samplers_in_loop() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let desc = DescriptorDesc {
+            ty: DescriptorDescTy::Sampler,
+            array_count: 3,
+            stages: ShaderStages::all_graphics(),
+            readonly: true,
+        };
+        let layout = UnsafeDescriptorSetLayout::new(device.clone(), iter::once(Some(desc)))
+            .unwrap();
+        let layout = Arc::new(layout);
+
+        let mut builder = RuntimeDescriptorSet::start(layout);
+        builder.enter_array().unwrap();
+        for _ in 0 .. 3 {
+            builder
+                .add_sampler(Sampler::simple_repeat_linear(device.clone()))
+                .unwrap();
+        }
+
+        match builder.add_sampler(Sampler::simple_repeat_linear(device.clone())) {
+            Err(PersistentDescriptorSetError::ArrayOutOfBounds) => (),
+            _ => panic!(),
+        }
+
+        builder.leave_array().unwrap();
+
+        let set: Arc<dyn DescriptorSet + Send + Sync> = Arc::new(builder.build().unwrap());
+        assert_eq!(set.num_buffers(), 0);
+        assert_eq!(set.num_images(), 0);
+    }
+
+    #[test]
+    fn missing_descriptors() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let desc = DescriptorDesc {
+            ty: DescriptorDescTy::Sampler,
+            array_count: 2,
+            stages: ShaderStages::all_graphics(),
+            readonly: true,
+        };
+        let layout = UnsafeDescriptorSetLayout::new(device.clone(), iter::once(Some(desc)))
+            .unwrap();
+
+        let mut builder = RuntimeDescriptorSet::start(Arc::new(layout));
+
+        // Not entering the array requires the binding to have exactly one element.
+        match builder.add_sampler(Sampler::simple_repeat_linear(device.clone())) {
+            Err(PersistentDescriptorSetError::MissingArrayElements { expected: 2, obtained: 1 }) => (),
+            _ => panic!(),
+        }
+
+        match builder.build() {
+            Err(PersistentDescriptorSetBuildError::MissingDescriptors { expected: 1, obtained: 0 }) => (),
+            _ => panic!(),
+        }
+    }
+}
@@ -21,6 +21,7 @@ use buffer::BufferAccess;
 use buffer::BufferInner;
 use buffer::BufferView;
 use descriptor::descriptor::DescriptorType;
+use descriptor::descriptor_set::DescriptorUpdateTemplate;
 use descriptor::descriptor_set::UnsafeDescriptorSetLayout;
 use device::Device;
 use device::DeviceOwned;
@@ -92,18 +93,24 @@ macro_rules! descriptors_count {
             /// Adds one descriptor of the given type to the count.
             #[inline]
             pub fn add_one(&mut self, ty: DescriptorType) {
+                self.add_num(ty, 1);
+            }
+
+            /// Adds `num` descriptors of the given type to the count.
+            #[inline]
+            pub fn add_num(&mut self, ty: DescriptorType, num: u32) {
                 match ty {
-                    DescriptorType::Sampler => self.sampler += 1,
-                    DescriptorType::CombinedImageSampler => self.combined_image_sampler += 1,
-                    DescriptorType::SampledImage => self.sampled_image += 1,
-                    DescriptorType::StorageImage => self.storage_image += 1,
-                    DescriptorType::UniformTexelBuffer => self.uniform_texel_buffer += 1,
-                    DescriptorType::StorageTexelBuffer => self.storage_texel_buffer += 1,
-                    DescriptorType::UniformBuffer => self.uniform_buffer += 1,
-                    DescriptorType::StorageBuffer => self.storage_buffer += 1,
-                    DescriptorType::UniformBufferDynamic => self.uniform_buffer_dynamic += 1,
-                    DescriptorType::StorageBufferDynamic => self.storage_buffer_dynamic += 1,
-                    DescriptorType::InputAttachment => self.input_attachment += 1,
+                    DescriptorType::Sampler => self.sampler += num,
+                    DescriptorType::CombinedImageSampler => self.combined_image_sampler += num,
+                    DescriptorType::SampledImage => self.sampled_image += num,
+                    DescriptorType::StorageImage => self.storage_image += num,
+                    DescriptorType::UniformTexelBuffer => self.uniform_texel_buffer += num,
+                    DescriptorType::StorageTexelBuffer => self.storage_texel_buffer += num,
+                    DescriptorType::UniformBuffer => self.uniform_buffer += num,
+                    DescriptorType::StorageBuffer => self.storage_buffer += num,
+                    DescriptorType::UniformBufferDynamic => self.uniform_buffer_dynamic += num,
+                    DescriptorType::StorageBufferDynamic => self.storage_buffer_dynamic += num,
+                    DescriptorType::InputAttachment => self.input_attachment += num,
                 };
             }
         }
@@ -248,6 +255,31 @@ impl UnsafeDescriptorPool {
     pub fn new(device: Arc<Device>, count: &DescriptorsCount, max_sets: u32,
                free_descriptor_set_bit: bool)
                -> Result<UnsafeDescriptorPool, OomError> {
+        UnsafeDescriptorPool::new_impl(device, count, max_sets, free_descriptor_set_bit, false)
+    }
+
+    /// Initializes a new pool from which descriptor sets whose layout has bindings with the
+    /// `update_after_bind` flag can be allocated.
+    ///
+    /// The parameters are the same as with `new`.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the `VK_EXT_descriptor_indexing` extension isn't enabled on the device.
+    /// - Panics if all the descriptors count are 0.
+    /// - Panics if `max_sets` is 0.
+    ///
+    pub fn update_after_bind(device: Arc<Device>, count: &DescriptorsCount, max_sets: u32,
+                             free_descriptor_set_bit: bool)
+                             -> Result<UnsafeDescriptorPool, OomError> {
+        assert!(device.loaded_extensions().ext_descriptor_indexing,
+                "the `VK_EXT_descriptor_indexing` extension must be enabled");
+        UnsafeDescriptorPool::new_impl(device, count, max_sets, free_descriptor_set_bit, true)
+    }
+
+    fn new_impl(device: Arc<Device>, count: &DescriptorsCount, max_sets: u32,
+                free_descriptor_set_bit: bool, update_after_bind: bool)
+                -> Result<UnsafeDescriptorPool, OomError> {
         let vk = device.pointers();
 
         assert_ne!(max_sets, 0, "The maximum number of sets can't be 0");
@@ -286,14 +318,18 @@ impl UnsafeDescriptorPool {
                 "All the descriptors count of a pool are 0");
 
         let pool = unsafe {
+            let mut flags = 0;
+            if free_descriptor_set_bit {
+                flags |= vk::DESCRIPTOR_POOL_CREATE_FREE_DESCRIPTOR_SET_BIT;
+            }
+            if update_after_bind {
+                flags |= vk::DESCRIPTOR_POOL_CREATE_UPDATE_AFTER_BIND_BIT_EXT;
+            }
+
             let infos = vk::DescriptorPoolCreateInfo {
                 sType: vk::STRUCTURE_TYPE_DESCRIPTOR_POOL_CREATE_INFO,
                 pNext: ptr::null(),
-                flags: if free_descriptor_set_bit {
-                    vk::DESCRIPTOR_POOL_CREATE_FREE_DESCRIPTOR_SET_BIT
-                } else {
-                    0
-                },
+                flags: flags,
                 maxSets: max_sets,
                 poolSizeCount: pool_sizes.len() as u32,
                 pPoolSizes: pool_sizes.as_ptr(),
@@ -348,11 +384,46 @@ impl UnsafeDescriptorPool {
                  })
             .collect();
 
-        self.alloc_impl(&layouts)
+        self.alloc_impl(&layouts, None)
+    }
+
+    /// Same as `alloc`, but also passes the number of descriptors of the binding that has the
+    /// `variable_descriptor_count` flag, one for each layout.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if one of the layouts wasn't created with the same device as the pool.
+    ///
+    /// # Safety
+    ///
+    /// See also the `alloc` function.
+    ///
+    /// - Each number of descriptors must not exceed the `array_count` of the variable binding
+    ///   of its layout.
+    ///
+    #[inline]
+    pub unsafe fn alloc_variable<'l, I>(&mut self, layouts: I)
+                                        -> Result<UnsafeDescriptorPoolAllocIter,
+                                                  DescriptorPoolAllocError>
+        where I: IntoIterator<Item = (&'l UnsafeDescriptorSetLayout, u32)>
+    {
+        let (layouts, counts): (SmallVec<[_; 8]>, SmallVec<[_; 8]>) = layouts
+            .into_iter()
+            .map(|(l, count)| {
+                     assert_eq!(self.device.internal_object(),
+                                l.device().internal_object(),
+                                "Tried to allocate from a pool with a set layout of a different \
+                                 device");
+                     (l.internal_object(), count)
+                 })
+            .unzip();
+
+        self.alloc_impl(&layouts, Some(&counts))
     }
 
     // Actual implementation of `alloc`. Separated so that it is not inlined.
-    unsafe fn alloc_impl(&mut self, layouts: &SmallVec<[vk::DescriptorSetLayout; 8]>)
+    unsafe fn alloc_impl(&mut self, layouts: &SmallVec<[vk::DescriptorSetLayout; 8]>,
+                         variable_counts: Option<&SmallVec<[u32; 8]>>)
                          -> Result<UnsafeDescriptorPoolAllocIter, DescriptorPoolAllocError> {
         let num = layouts.len();
 
@@ -360,9 +431,21 @@ impl UnsafeDescriptorPool {
             return Ok(UnsafeDescriptorPoolAllocIter { sets: vec![].into_iter() });
         }
 
+        let variable_infos = variable_counts.map(|counts| {
+            vk::DescriptorSetVariableDescriptorCountAllocateInfoEXT {
+                sType: vk::STRUCTURE_TYPE_DESCRIPTOR_SET_VARIABLE_DESCRIPTOR_COUNT_ALLOCATE_INFO_EXT,
+                pNext: ptr::null(),
+                descriptorSetCount: counts.len() as u32,
+                pDescriptorCounts: counts.as_ptr(),
+            }
+        });
+
         let infos = vk::DescriptorSetAllocateInfo {
             sType: vk::STRUCTURE_TYPE_DESCRIPTOR_SET_ALLOCATE_INFO,
-            pNext: ptr::null(),
+            pNext: match variable_infos {
+                Some(ref infos) => infos as *const _ as *const _,
+                None => ptr::null(),
+            },
             descriptorPool: self.pool,
             descriptorSetCount: layouts.len() as u32,
             pSetLayouts: layouts.as_ptr(),
@@ -569,6 +652,52 @@ impl UnsafeDescriptorSet {
     {
         let vk = device.pointers();
 
+        with_raw_writes(self.set, writes, |raw_writes| {
+            // It is forbidden to call `vkUpdateDescriptorSets` with 0 writes, so we need to
+            // perform this emptiness check.
+            if !raw_writes.is_empty() {
+                vk.UpdateDescriptorSets(device.internal_object(),
+                                        raw_writes.len() as u32,
+                                        raw_writes.as_ptr(),
+                                        0,
+                                        ptr::null());
+            }
+        });
+    }
+
+    /// Modifies a descriptor set with the descriptors contained in `data`, as described by
+    /// `template`.
+    ///
+    /// # Safety
+    ///
+    /// Same as `write`. In addition:
+    ///
+    /// - The template must have been created with the layout of this set.
+    /// - The handles in `data` must be valid.
+    ///
+    pub unsafe fn write_with_template<T>(&mut self, device: &Device,
+                                         template: &DescriptorUpdateTemplate<T>, data: &T)
+        where T: ?Sized
+    {
+        debug_assert_eq!(device.internal_object(), template.device().internal_object());
+
+        let vk = device.pointers();
+        vk.UpdateDescriptorSetWithTemplateKHR(device.internal_object(),
+                                              self.set,
+                                              template.internal_object(),
+                                              data as *const T as *const _);
+    }
+}
+
+/// Turns a list of `DescriptorWrite`s into a list of `vk::WriteDescriptorSet` targeting
+/// `dst_set`, and passes it to `f`.
+///
+/// The `vk::WriteDescriptorSet` structs contain pointers to temporary arrays, which is why they
+/// are only available from within the closure.
+pub(crate) unsafe fn with_raw_writes<I, F, R>(dst_set: vk::DescriptorSet, writes: I, f: F) -> R
+    where I: Iterator<Item = DescriptorWrite>,
+          F: FnOnce(&[vk::WriteDescriptorSet]) -> R
+{
         // In this function, we build 4 arrays: one array of image descriptors (image_descriptors),
         // one for buffer descriptors (buffer_descriptors), one for buffer view descriptors
         // (buffer_views_descriptors), and one for the final list of writes (raw_writes).
@@ -601,7 +730,7 @@ impl UnsafeDescriptorSet {
             raw_writes.push(vk::WriteDescriptorSet {
                                 sType: vk::STRUCTURE_TYPE_WRITE_DESCRIPTOR_SET,
                                 pNext: ptr::null(),
-                                dstSet: self.set,
+                                dstSet: dst_set,
                                 dstBinding: indiv_write.binding,
                                 dstArrayElement: indiv_write.first_array_element,
                                 descriptorCount: indiv_write.inner.len() as u32,
@@ -638,62 +767,10 @@ impl UnsafeDescriptorSet {
             }
 
             for elem in indiv_write.inner.iter() {
-                match *elem {
-                    DescriptorWriteInner::UniformBuffer(buffer, offset, size) |
-                    DescriptorWriteInner::DynamicUniformBuffer(buffer, offset, size) => {
-                        buffer_descriptors.push(vk::DescriptorBufferInfo {
-                                                    buffer: buffer,
-                                                    offset: offset as u64,
-                                                    range: size as u64,
-                                                });
-                    },
-                    DescriptorWriteInner::StorageBuffer(buffer, offset, size) |
-                    DescriptorWriteInner::DynamicStorageBuffer(buffer, offset, size) => {
-                        buffer_descriptors.push(vk::DescriptorBufferInfo {
-                                                    buffer: buffer,
-                                                    offset: offset as u64,
-                                                    range: size as u64,
-                                                });
-                    },
-                    DescriptorWriteInner::Sampler(sampler) => {
-                        image_descriptors.push(vk::DescriptorImageInfo {
-                                                   sampler: sampler,
-                                                   imageView: 0,
-                                                   imageLayout: 0,
-                                               });
-                    },
-                    DescriptorWriteInner::CombinedImageSampler(sampler, view, layout) => {
-                        image_descriptors.push(vk::DescriptorImageInfo {
-                                                   sampler: sampler,
-                                                   imageView: view,
-                                                   imageLayout: layout,
-                                               });
-                    },
-                    DescriptorWriteInner::StorageImage(view, layout) => {
-                        image_descriptors.push(vk::DescriptorImageInfo {
-                                                   sampler: 0,
-                                                   imageView: view,
-                                                   imageLayout: layout,
-                                               });
-                    },
-                    DescriptorWriteInner::SampledImage(view, layout) => {
-                        image_descriptors.push(vk::DescriptorImageInfo {
-                                                   sampler: 0,
-                                                   imageView: view,
-                                                   imageLayout: layout,
-                                               });
-                    },
-                    DescriptorWriteInner::InputAttachment(view, layout) => {
-                        image_descriptors.push(vk::DescriptorImageInfo {
-                                                   sampler: 0,
-                                                   imageView: view,
-                                                   imageLayout: layout,
-                                               });
-                    },
-                    DescriptorWriteInner::UniformTexelBuffer(view) |
-                    DescriptorWriteInner::StorageTexelBuffer(view) => {
-                        buffer_views_descriptors.push(view);
-                    },
+                match elem.raw_info() {
+                    RawDescriptorInfo::Image(info) => image_descriptors.push(info),
+                    RawDescriptorInfo::Buffer(info) => buffer_descriptors.push(info),
+                    RawDescriptorInfo::BufferView(view) => buffer_views_descriptors.push(view),
                 }
             }
         }
@@ -717,16 +794,7 @@ impl UnsafeDescriptorSet {
             };
         }
 
-        // It is forbidden to call `vkUpdateDescriptorSets` with 0 writes, so we need to perform
-        // this emptiness check.
-        if !raw_writes.is_empty() {
-            vk.UpdateDescriptorSets(device.internal_object(),
-                                    raw_writes.len() as u32,
-                                    raw_writes.as_ptr(),
-                                    0,
-                                    ptr::null());
-        }
-    }
+        f(&raw_writes)
 }
 
 unsafe impl VulkanObject for UnsafeDescriptorSet {
@@ -772,6 +840,59 @@ enum DescriptorWriteInner {
     InputAttachment(vk::ImageView, vk::ImageLayout),
 }
 
+impl DescriptorWriteInner {
+    // Returns the raw info that Vulkan expects for this element.
+    fn raw_info(&self) -> RawDescriptorInfo {
+        match *self {
+            DescriptorWriteInner::UniformBuffer(buffer, offset, size) |
+            DescriptorWriteInner::StorageBuffer(buffer, offset, size) |
+            DescriptorWriteInner::DynamicUniformBuffer(buffer, offset, size) |
+            DescriptorWriteInner::DynamicStorageBuffer(buffer, offset, size) => {
+                RawDescriptorInfo::Buffer(vk::DescriptorBufferInfo {
+                                              buffer: buffer,
+                                              offset: offset as u64,
+                                              range: size as u64,
+                                          })
+            },
+            DescriptorWriteInner::Sampler(sampler) => {
+                RawDescriptorInfo::Image(vk::DescriptorImageInfo {
+                                             sampler: sampler,
+                                             imageView: 0,
+                                             imageLayout: 0,
+                                         })
+            },
+            DescriptorWriteInner::CombinedImageSampler(sampler, view, layout) => {
+                RawDescriptorInfo::Image(vk::DescriptorImageInfo {
+                                             sampler: sampler,
+                                             imageView: view,
+                                             imageLayout: layout,
+                                         })
+            },
+            DescriptorWriteInner::StorageImage(view, layout) |
+            DescriptorWriteInner::SampledImage(view, layout) |
+            DescriptorWriteInner::InputAttachment(view, layout) => {
+                RawDescriptorInfo::Image(vk::DescriptorImageInfo {
+                                             sampler: 0,
+                                             imageView: view,
+                                             imageLayout: layout,
+                                         })
+            },
+            DescriptorWriteInner::UniformTexelBuffer(view) |
+            DescriptorWriteInner::StorageTexelBuffer(view) => {
+                RawDescriptorInfo::BufferView(view)
+            },
+        }
+    }
+}
+
+// The raw description of a single descriptor, as found in the `pImageInfo`, `pBufferInfo` and
+// `pTexelBufferView` arrays of a write or in the data of a descriptor update template.
+pub(crate) enum RawDescriptorInfo {
+    Image(vk::DescriptorImageInfo),
+    Buffer(vk::DescriptorBufferInfo),
+    BufferView(vk::BufferView),
+}
+
 macro_rules! smallvec {
     ($elem:expr) => ({ let mut s = SmallVec::new(); s.push($elem); s });
 }
@@ -779,7 +900,7 @@ macro_rules! smallvec {
 impl DescriptorWrite {
     #[inline]
     pub fn storage_image<I>(binding: u32, array_element: u32, image: &I) -> DescriptorWrite
-        where I: ImageViewAccess
+        where I: ?Sized + ImageViewAccess
     {
         DescriptorWrite {
             binding: binding,
@@ -803,7 +924,7 @@ impl DescriptorWrite {
 
     #[inline]
     pub fn sampled_image<I>(binding: u32, array_element: u32, image: &I) -> DescriptorWrite
-        where I: ImageViewAccess
+        where I: ?Sized + ImageViewAccess
     {
         DescriptorWrite {
             binding: binding,
@@ -820,7 +941,7 @@ impl DescriptorWrite {
     pub fn combined_image_sampler<I>(binding: u32, array_element: u32, sampler: &Arc<Sampler>,
                                      image: &I)
                                      -> DescriptorWrite
-        where I: ImageViewAccess
+        where I: ?Sized + ImageViewAccess
     {
         DescriptorWrite {
             binding: binding,
@@ -868,7 +989,7 @@ impl DescriptorWrite {
 
     #[inline]
     pub unsafe fn uniform_buffer<B>(binding: u32, array_element: u32, buffer: &B) -> DescriptorWrite
-        where B: BufferAccess
+        where B: ?Sized + BufferAccess
     {
         let size = buffer.size();
         let BufferInner { buffer, offset } = buffer.inner();
@@ -901,7 +1022,7 @@ impl DescriptorWrite {
 
     #[inline]
     pub unsafe fn storage_buffer<B>(binding: u32, array_element: u32, buffer: &B) -> DescriptorWrite
-        where B: BufferAccess
+        where B: ?Sized + BufferAccess
     {
         let size = buffer.size();
         let BufferInner { buffer, offset } = buffer.inner();
@@ -935,7 +1056,7 @@ impl DescriptorWrite {
     #[inline]
     pub unsafe fn dynamic_uniform_buffer<B>(binding: u32, array_element: u32, buffer: &B)
                                             -> DescriptorWrite
-        where B: BufferAccess
+        where B: ?Sized + BufferAccess
     {
         let size = buffer.size();
         let BufferInner { buffer, offset } = buffer.inner();
@@ -967,7 +1088,7 @@ impl DescriptorWrite {
     #[inline]
     pub unsafe fn dynamic_storage_buffer<B>(binding: u32, array_element: u32, buffer: &B)
                                             -> DescriptorWrite
-        where B: BufferAccess
+        where B: ?Sized + BufferAccess
     {
         let size = buffer.size();
         let BufferInner { buffer, offset } = buffer.inner();
@@ -998,7 +1119,7 @@ impl DescriptorWrite {
 
     #[inline]
     pub fn input_attachment<I>(binding: u32, array_element: u32, image: &I) -> DescriptorWrite
-        where I: ImageViewAccess
+        where I: ?Sized + ImageViewAccess
     {
         DescriptorWrite {
             binding: binding,
@@ -1013,6 +1134,23 @@ impl DescriptorWrite {
         }
     }
 
+    /// Returns the binding number of this write.
+    #[inline]
+    pub(crate) fn binding(&self) -> u32 {
+        self.binding
+    }
+
+    /// Returns the raw infos of the descriptors of this write, starting at
+    /// `first_array_element`.
+    #[inline]
+    pub(crate) fn raw_infos<'a>(&'a self) -> impl ExactSizeIterator<Item = (u32, RawDescriptorInfo)> + 'a {
+        let first = self.first_array_element;
+        self.inner
+            .iter()
+            .enumerate()
+            .map(move |(i, elem)| (first + i as u32, elem.raw_info()))
+    }
+
     /// Returns the type corresponding to this write.
     #[inline]
     pub fn ty(&self) -> DescriptorType {
@@ -1044,11 +1182,13 @@ mod tests {
     use descriptor::descriptor_set::DescriptorsCount;
     use descriptor::descriptor_set::UnsafeDescriptorPool;
     use descriptor::descriptor_set::UnsafeDescriptorSetLayout;
+    use mock::Recorder;
     use std::iter;
+    use vk;
 
     #[test]
     fn pool_create() {
-        let (device, _) = gfx_dev_and_queue!();
+        let (device, _) = mock_dev_and_queue!();
         let desc = DescriptorsCount {
             uniform_buffer: 1,
             ..DescriptorsCount::zero()
@@ -1059,7 +1199,7 @@ mod tests {
 
     #[test]
     fn zero_max_set() {
-        let (device, _) = gfx_dev_and_queue!();
+        let (device, _) = mock_dev_and_queue!();
         let desc = DescriptorsCount {
             uniform_buffer: 1,
             ..DescriptorsCount::zero()
@@ -1072,7 +1212,7 @@ mod tests {
 
     #[test]
     fn zero_descriptors() {
-        let (device, _) = gfx_dev_and_queue!();
+        let (device, _) = mock_dev_and_queue!();
 
         assert_should_panic!("All the descriptors count of a pool are 0", {
             let _ = UnsafeDescriptorPool::new(device, &DescriptorsCount::zero(), 10, false);
@@ -1081,7 +1221,7 @@ mod tests {
 
     #[test]
     fn basic_alloc() {
-        let (device, _) = gfx_dev_and_queue!();
+        let (device, _) = mock_dev_and_queue!();
 
         let layout = DescriptorDesc {
             ty: DescriptorDescTy::Buffer(DescriptorBufferDesc {
@@ -1101,17 +1241,22 @@ mod tests {
             ..DescriptorsCount::zero()
         };
 
+        let recorder = Recorder::of(device.instance()).unwrap();
         let mut pool = UnsafeDescriptorPool::new(device, &desc, 10, false).unwrap();
         unsafe {
             let sets = pool.alloc(iter::once(&set_layout)).unwrap();
             assert_eq!(sets.count(), 1);
         }
+        assert_eq!(recorder.live_count(vk::OBJECT_TYPE_DESCRIPTOR_SET), 1);
+
+        drop(pool);
+        assert_eq!(recorder.live_count(vk::OBJECT_TYPE_DESCRIPTOR_SET), 0);
     }
 
     #[test]
     fn alloc_diff_device() {
-        let (device1, _) = gfx_dev_and_queue!();
-        let (device2, _) = gfx_dev_and_queue!();
+        let (device1, _) = mock_dev_and_queue!();
+        let (device2, _) = mock_dev_and_queue!();
 
         let layout = DescriptorDesc {
             ty: DescriptorDescTy::Buffer(DescriptorBufferDesc {
@@ -1144,7 +1289,7 @@ mod tests {
 
     #[test]
     fn alloc_zero() {
-        let (device, _) = gfx_dev_and_queue!();
+        let (device, _) = mock_dev_and_queue!();
 
         let desc = DescriptorsCount {
             uniform_buffer: 1,
@@ -8,6 +8,7 @@
 // according to those terms.
 
 use smallvec::SmallVec;
+use std::error;
 use std::fmt;
 use std::mem::MaybeUninit;
 use std::ptr;
@@ -19,6 +20,7 @@ use check_errors;
 use vk;
 
 use descriptor::descriptor::DescriptorDesc;
+use descriptor::descriptor::DescriptorType;
 use descriptor::descriptor_set::DescriptorsCount;
 use descriptor::descriptor_set::DescriptorSetDesc;
 use device::Device;
@@ -38,6 +40,10 @@ pub struct UnsafeDescriptorSetLayout {
     descriptors: SmallVec<[Option<DescriptorDesc>; 32]>,
     // Number of descriptors.
     descriptors_count: DescriptorsCount,
+    // True if the layout was created for push descriptors.
+    push_descriptor: bool,
+    // Flags of each binding. Empty if the layout was created without binding flags.
+    binding_flags: SmallVec<[DescriptorBindingFlags; 32]>,
 }
 
 impl UnsafeDescriptorSetLayout {
@@ -50,13 +56,106 @@ impl UnsafeDescriptorSetLayout {
                   -> Result<UnsafeDescriptorSetLayout, OomError>
         where I: IntoIterator<Item = Option<DescriptorDesc>>
     {
+        let descriptors = descriptors.into_iter().map(|d| (d, DescriptorBindingFlags::none()));
+        UnsafeDescriptorSetLayout::new_impl(device, descriptors, false, false)
+    }
+
+    /// Builds a new `UnsafeDescriptorSetLayout` with the given descriptors and per-binding flags.
+    ///
+    /// The descriptors are passed the same way as with `new`, each with the flags of its binding.
+    /// If any binding has the `update_after_bind` flag, then descriptor sets with this layout
+    /// must be allocated from a pool created with `UnsafeDescriptorPool::update_after_bind`.
+    ///
+    /// The corresponding descriptor indexing features, such as
+    /// `Features::descriptor_binding_partially_bound`, must be enabled on the device.
+    ///
+    /// Returns an error if the `VK_EXT_descriptor_indexing` extension isn't enabled, if a binding
+    /// other than the last one has the `variable_descriptor_count` flag, or if a dynamic buffer
+    /// has the `update_after_bind` flag.
+    pub fn with_binding_flags<I>(device: Arc<Device>, descriptors: I)
+                                 -> Result<UnsafeDescriptorSetLayout,
+                                           DescriptorSetLayoutCreationError>
+        where I: IntoIterator<Item = (Option<DescriptorDesc>, DescriptorBindingFlags)>
+    {
+        if !device.loaded_extensions().ext_descriptor_indexing {
+            return Err(DescriptorSetLayoutCreationError::MissingExtension {
+                           name: "VK_EXT_descriptor_indexing",
+                       });
+        }
+
         let descriptors = descriptors.into_iter().collect::<SmallVec<[_; 32]>>();
+        for (binding, &(ref desc, flags)) in descriptors.iter().enumerate() {
+            let desc = match *desc {
+                Some(ref d) => d,
+                None => continue,
+            };
+
+            if flags.variable_descriptor_count && binding != descriptors.len() - 1 {
+                return Err(DescriptorSetLayoutCreationError::VariableDescriptorCountNotLast {
+                               binding: binding as u32,
+                           });
+            }
+
+            if flags.update_after_bind && is_dynamic_buffer(desc) {
+                return Err(DescriptorSetLayoutCreationError::DynamicBufferUpdateAfterBind {
+                               binding: binding as u32,
+                           });
+            }
+        }
+
+        Ok(UnsafeDescriptorSetLayout::new_impl(device, descriptors, false, true)?)
+    }
+
+    /// Builds a new `UnsafeDescriptorSetLayout` whose descriptors are pushed directly into a
+    /// command buffer with `push_descriptor_set`, instead of being allocated from a pool.
+    ///
+    /// The descriptors are passed the same way as with `new`.
+    ///
+    /// Returns an error if the `VK_KHR_push_descriptor` extension isn't enabled or if one of the
+    /// descriptors is a dynamic buffer.
+    pub fn push_descriptor<I>(device: Arc<Device>, descriptors: I)
+                              -> Result<UnsafeDescriptorSetLayout,
+                                        DescriptorSetLayoutCreationError>
+        where I: IntoIterator<Item = Option<DescriptorDesc>>
+    {
+        if !device.loaded_extensions().khr_push_descriptor {
+            return Err(DescriptorSetLayoutCreationError::MissingExtension {
+                           name: "VK_KHR_push_descriptor",
+                       });
+        }
+
+        let descriptors = descriptors
+            .into_iter()
+            .map(|d| (d, DescriptorBindingFlags::none()))
+            .collect::<SmallVec<[_; 32]>>();
+        for (binding, &(ref desc, _)) in descriptors.iter().enumerate() {
+            if desc.as_ref().map_or(false, is_dynamic_buffer) {
+                return Err(DescriptorSetLayoutCreationError::DynamicBufferInPushDescriptor {
+                               binding: binding as u32,
+                           });
+            }
+        }
+
+        Ok(UnsafeDescriptorSetLayout::new_impl(device, descriptors, true, false)?)
+    }
+
+    fn new_impl<I>(device: Arc<Device>, descriptors: I, push_descriptor: bool,
+                   with_binding_flags: bool)
+                   -> Result<UnsafeDescriptorSetLayout, OomError>
+        where I: IntoIterator<Item = (Option<DescriptorDesc>, DescriptorBindingFlags)>
+    {
+        let (descriptors, binding_flags): (SmallVec<[_; 32]>, SmallVec<[_; 32]>) =
+            descriptors.into_iter().unzip();
         let mut descriptors_count = DescriptorsCount::zero();
+        let mut update_after_bind = false;
+        let mut raw_binding_flags: SmallVec<[vk::DescriptorBindingFlagsEXT; 32]> =
+            SmallVec::new();
 
         let bindings = descriptors
             .iter()
+            .zip(binding_flags.iter())
             .enumerate()
-            .filter_map(|(binding, desc)| {
+            .filter_map(|(binding, (desc, flags))| {
                 let desc = match desc {
                     Some(d) => d,
                     None => return None,
@@ -67,6 +166,8 @@ impl UnsafeDescriptorSetLayout {
 
                 let ty = desc.ty.ty().unwrap(); // TODO: shouldn't panic
                 descriptors_count.add_one(ty);
+                update_after_bind |= flags.update_after_bind;
+                raw_binding_flags.push(flags.into_vulkan_bits());
 
                 Some(vk::DescriptorSetLayoutBinding {
                          binding: binding as u32,
@@ -81,10 +182,29 @@ impl UnsafeDescriptorSetLayout {
         // Note that it seems legal to have no descriptor at all in the set.
 
         let layout = unsafe {
+            let binding_flags_infos = vk::DescriptorSetLayoutBindingFlagsCreateInfoEXT {
+                sType: vk::STRUCTURE_TYPE_DESCRIPTOR_SET_LAYOUT_BINDING_FLAGS_CREATE_INFO_EXT,
+                pNext: ptr::null(),
+                bindingCount: raw_binding_flags.len() as u32,
+                pBindingFlags: raw_binding_flags.as_ptr(),
+            };
+
+            let mut flags = 0;
+            if push_descriptor {
+                flags |= vk::DESCRIPTOR_SET_LAYOUT_CREATE_PUSH_DESCRIPTOR_BIT_KHR;
+            }
+            if update_after_bind {
+                flags |= vk::DESCRIPTOR_SET_LAYOUT_CREATE_UPDATE_AFTER_BIND_POOL_BIT_EXT;
+            }
+
             let infos = vk::DescriptorSetLayoutCreateInfo {
                 sType: vk::STRUCTURE_TYPE_DESCRIPTOR_SET_LAYOUT_CREATE_INFO,
-                pNext: ptr::null(),
-                flags: 0, // reserved
+                pNext: if with_binding_flags {
+                    &binding_flags_infos as *const _ as *const _
+                } else {
+                    ptr::null()
+                },
+                flags: flags,
                 bindingCount: bindings.len() as u32,
                 pBindings: bindings.as_ptr(),
             };
@@ -103,6 +223,12 @@ impl UnsafeDescriptorSetLayout {
                device: device,
                descriptors: descriptors,
                descriptors_count: descriptors_count,
+               push_descriptor: push_descriptor,
+               binding_flags: if with_binding_flags {
+                   binding_flags
+               } else {
+                   SmallVec::new()
+               },
            })
     }
 
@@ -111,6 +237,149 @@ impl UnsafeDescriptorSetLayout {
     pub fn descriptors_count(&self) -> &DescriptorsCount {
         &self.descriptors_count
     }
+
+    /// Returns true if the layout was created with `push_descriptor`.
+    #[inline]
+    pub fn is_push_descriptor(&self) -> bool {
+        self.push_descriptor
+    }
+
+    /// Returns the flags of a binding. Returns empty flags if the binding is out of range or if
+    /// the layout wasn't created with `with_binding_flags`.
+    #[inline]
+    pub fn binding_flags(&self, binding: usize) -> DescriptorBindingFlags {
+        self.binding_flags
+            .get(binding)
+            .cloned()
+            .unwrap_or(DescriptorBindingFlags::none())
+    }
+
+    /// Returns true if one of the bindings has the `update_after_bind` flag, in which case
+    /// descriptor sets must be allocated from an update-after-bind pool.
+    #[inline]
+    pub fn is_update_after_bind(&self) -> bool {
+        self.binding_flags.iter().any(|f| f.update_after_bind)
+    }
+
+    /// Returns the binding that has a variable descriptor count, if any.
+    #[inline]
+    pub fn variable_descriptor_count_binding(&self) -> Option<usize> {
+        self.binding_flags.iter().position(|f| f.variable_descriptor_count)
+    }
+}
+
+// Returns true if the descriptor is a dynamic uniform or storage buffer.
+fn is_dynamic_buffer(desc: &DescriptorDesc) -> bool {
+    match desc.ty.ty() {
+        Some(DescriptorType::UniformBufferDynamic) |
+        Some(DescriptorType::StorageBufferDynamic) => true,
+        _ => false,
+    }
+}
+
+/// Error that can happen when creating an `UnsafeDescriptorSetLayout` with binding flags or for
+/// push descriptors.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DescriptorSetLayoutCreationError {
+    /// Not enough memory.
+    OomError(OomError),
+    /// A device extension required for this operation is not enabled.
+    MissingExtension { name: &'static str },
+    /// A binding other than the last one has the `variable_descriptor_count` flag.
+    VariableDescriptorCountNotLast { binding: u32 },
+    /// A dynamic buffer has the `update_after_bind` flag.
+    DynamicBufferUpdateAfterBind { binding: u32 },
+    /// A dynamic buffer is part of a push descriptor set layout.
+    DynamicBufferInPushDescriptor { binding: u32 },
+}
+
+impl error::Error for DescriptorSetLayoutCreationError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            DescriptorSetLayoutCreationError::OomError(_) => "not enough memory available",
+            DescriptorSetLayoutCreationError::MissingExtension { .. } =>
+                "a device extension required for this operation is not enabled",
+            DescriptorSetLayoutCreationError::VariableDescriptorCountNotLast { .. } =>
+                "only the last binding can have a variable descriptor count",
+            DescriptorSetLayoutCreationError::DynamicBufferUpdateAfterBind { .. } =>
+                "dynamic buffers can't be updated after bind",
+            DescriptorSetLayoutCreationError::DynamicBufferInPushDescriptor { .. } =>
+                "dynamic buffers can't be used in a push descriptor set layout",
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&dyn error::Error> {
+        match *self {
+            DescriptorSetLayoutCreationError::OomError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for DescriptorSetLayoutCreationError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<OomError> for DescriptorSetLayoutCreationError {
+    #[inline]
+    fn from(err: OomError) -> DescriptorSetLayoutCreationError {
+        DescriptorSetLayoutCreationError::OomError(err)
+    }
+}
+
+/// Flags of a binding of a descriptor set layout.
+///
+/// These flags require the `VK_EXT_descriptor_indexing` extension and are passed to
+/// `UnsafeDescriptorSetLayout::with_binding_flags`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct DescriptorBindingFlags {
+    /// The descriptors of the binding can be updated after the descriptor set has been bound in
+    /// a command buffer, and before the command buffer is submitted.
+    pub update_after_bind: bool,
+    /// The descriptors of the binding that aren't used by the shaders can be updated while a
+    /// command buffer that uses the descriptor set is pending.
+    pub update_unused_while_pending: bool,
+    /// The descriptors of the binding that aren't used by the shaders don't need to be valid.
+    pub partially_bound: bool,
+    /// The binding has a number of descriptors chosen when the descriptor set is allocated,
+    /// with `array_count` as the upper bound. Only allowed on the last binding.
+    pub variable_descriptor_count: bool,
+}
+
+impl DescriptorBindingFlags {
+    /// Builds a `DescriptorBindingFlags` with all values set to false.
+    #[inline]
+    pub fn none() -> DescriptorBindingFlags {
+        DescriptorBindingFlags {
+            update_after_bind: false,
+            update_unused_while_pending: false,
+            partially_bound: false,
+            variable_descriptor_count: false,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn into_vulkan_bits(self) -> vk::DescriptorBindingFlagsEXT {
+        let mut result = 0;
+        if self.update_after_bind {
+            result |= vk::DESCRIPTOR_BINDING_UPDATE_AFTER_BIND_BIT_EXT;
+        }
+        if self.update_unused_while_pending {
+            result |= vk::DESCRIPTOR_BINDING_UPDATE_UNUSED_WHILE_PENDING_BIT_EXT;
+        }
+        if self.partially_bound {
+            result |= vk::DESCRIPTOR_BINDING_PARTIALLY_BOUND_BIT_EXT;
+        }
+        if self.variable_descriptor_count {
+            result |= vk::DESCRIPTOR_BINDING_VARIABLE_DESCRIPTOR_COUNT_BIT_EXT;
+        }
+        result
+    }
 }
 
 unsafe impl DescriptorSetDesc for UnsafeDescriptorSetLayout {
@@ -167,19 +436,33 @@ mod tests {
     use descriptor::descriptor::DescriptorDesc;
     use descriptor::descriptor::DescriptorDescTy;
     use descriptor::descriptor::ShaderStages;
+    use descriptor::descriptor_set::DescriptorBindingFlags;
+    use descriptor::descriptor_set::DescriptorSetLayoutCreationError;
     use descriptor::descriptor_set::DescriptorsCount;
     use descriptor::descriptor_set::UnsafeDescriptorSetLayout;
     use std::iter;
 
+    fn dynamic_buffer() -> DescriptorDesc {
+        DescriptorDesc {
+            ty: DescriptorDescTy::Buffer(DescriptorBufferDesc {
+                                             dynamic: Some(true),
+                                             storage: false,
+                                         }),
+            array_count: 1,
+            stages: ShaderStages::all_graphics(),
+            readonly: true,
+        }
+    }
+
     #[test]
     fn empty() {
-        let (device, _) = gfx_dev_and_queue!();
+        let (device, _) = mock_dev_and_queue!();
         let _layout = UnsafeDescriptorSetLayout::new(device, iter::empty());
     }
 
     #[test]
     fn basic_create() {
-        let (device, _) = gfx_dev_and_queue!();
+        let (device, _) = mock_dev_and_queue!();
 
         let layout = DescriptorDesc {
             ty: DescriptorDescTy::Buffer(DescriptorBufferDesc {
@@ -199,4 +482,79 @@ mod tests {
                        ..DescriptorsCount::zero()
                    });
     }
+
+    #[test]
+    fn binding_flags_require_extension() {
+        let (device, _) = mock_dev_and_queue!();
+
+        match UnsafeDescriptorSetLayout::with_binding_flags(device, iter::empty()) {
+            Err(DescriptorSetLayoutCreationError::MissingExtension {
+                    name: "VK_EXT_descriptor_indexing",
+                }) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn variable_descriptor_count_not_last() {
+        let (device, _) = mock_dev_and_queue!(extensions: [ext_descriptor_indexing]);
+
+        let flags = DescriptorBindingFlags {
+            variable_descriptor_count: true,
+            ..DescriptorBindingFlags::none()
+        };
+        let descriptors = vec![(Some(dynamic_buffer()), flags),
+                               (Some(dynamic_buffer()), DescriptorBindingFlags::none())];
+        match UnsafeDescriptorSetLayout::with_binding_flags(device, descriptors) {
+            Err(DescriptorSetLayoutCreationError::VariableDescriptorCountNotLast {
+                    binding: 0,
+                }) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn dynamic_buffer_update_after_bind() {
+        let (device, _) = mock_dev_and_queue!(extensions: [ext_descriptor_indexing]);
+
+        let flags = DescriptorBindingFlags {
+            update_after_bind: true,
+            ..DescriptorBindingFlags::none()
+        };
+        let descriptors = vec![(None, flags), (Some(dynamic_buffer()), flags)];
+        match UnsafeDescriptorSetLayout::with_binding_flags(device, descriptors) {
+            Err(DescriptorSetLayoutCreationError::DynamicBufferUpdateAfterBind {
+                    binding: 1,
+                }) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn push_descriptor_requires_extension() {
+        let (device, _) = mock_dev_and_queue!();
+
+        match UnsafeDescriptorSetLayout::push_descriptor(device, iter::empty()) {
+            Err(DescriptorSetLayoutCreationError::MissingExtension {
+                    name: "VK_KHR_push_descriptor",
+                }) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn push_descriptor_dynamic_buffer() {
+        let (device, _) = mock_dev_and_queue!(extensions: [khr_push_descriptor]);
+
+        let descriptors = vec![None, Some(dynamic_buffer())];
+        match UnsafeDescriptorSetLayout::push_descriptor(device.clone(), descriptors) {
+            Err(DescriptorSetLayoutCreationError::DynamicBufferInPushDescriptor { binding }) => {
+                assert_eq!(binding, 1);
+            },
+            _ => panic!(),
+        }
+
+        let layout = UnsafeDescriptorSetLayout::push_descriptor(device, iter::empty()).unwrap();
+        assert!(layout.is_push_descriptor());
+    }
 }
@@ -0,0 +1,695 @@
+// Copyright (c) 2020 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Descriptor update templates.
+//!
+//! Writing descriptors with `UnsafeDescriptorSet::write` requires building a list of
+//! `VkWriteDescriptorSet` structs every time. A descriptor update template instead describes
+//! once and for all where the descriptors of a set are located in a block of memory, and the
+//! Vulkan implementation then reads them directly from there.
+//!
+//! The block of memory is usually a `#[repr(C)]` struct made of `DescriptorImageInfo`,
+//! `DescriptorBufferInfo` and `DescriptorTexelBufferInfo` fields, that implements the
+//! `DescriptorUpdateData` trait.
+
+use smallvec::SmallVec;
+use std::error;
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem;
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::slice;
+use std::sync::Arc;
+
+use buffer::BufferAccess;
+use buffer::BufferInner;
+use buffer::BufferView;
+use descriptor::descriptor::DescriptorType;
+use descriptor::descriptor_set::DescriptorSetDesc;
+use descriptor::descriptor_set::DescriptorWrite;
+use descriptor::descriptor_set::UnsafeDescriptorSet;
+use descriptor::descriptor_set::UnsafeDescriptorSetLayout;
+use descriptor::descriptor_set::sys::RawDescriptorInfo;
+use device::Device;
+use device::DeviceOwned;
+use image::ImageViewAccess;
+use sampler::Sampler;
+
+use Error;
+use OomError;
+use VulkanObject;
+use check_errors;
+use version::Version;
+use vk;
+
+/// Describes where a range of descriptors of a binding is located in the data passed to a
+/// descriptor update template.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DescriptorUpdateTemplateEntry {
+    /// The binding to write.
+    pub binding: u32,
+    /// The first array element of the binding to write.
+    pub first_array_element: u32,
+    /// The number of consecutive array elements to write.
+    pub descriptor_count: u32,
+    /// Offset in bytes of the first descriptor within the data.
+    pub offset: usize,
+    /// Distance in bytes between two consecutive descriptors within the data.
+    pub stride: usize,
+}
+
+/// Trait for `#[repr(C)]` types that contain descriptors to write with a descriptor update
+/// template.
+///
+/// # Safety
+///
+/// The entries returned by `entries()` must point to fields of `Self` whose type matches the
+/// descriptor type of the binding: `DescriptorImageInfo` for samplers, images and input
+/// attachments, `DescriptorBufferInfo` for buffers and `DescriptorTexelBufferInfo` for texel
+/// buffers.
+///
+/// # Example
+///
+/// ```
+/// use std::mem;
+/// use vulkano::descriptor::descriptor_set::DescriptorBufferInfo;
+/// use vulkano::descriptor::descriptor_set::DescriptorImageInfo;
+/// use vulkano::descriptor::descriptor_set::DescriptorUpdateData;
+/// use vulkano::descriptor::descriptor_set::DescriptorUpdateTemplateEntry;
+///
+/// #[repr(C)]
+/// struct Material {
+///     params: DescriptorBufferInfo,
+///     textures: [DescriptorImageInfo; 2],
+/// }
+///
+/// unsafe impl DescriptorUpdateData for Material {
+///     fn entries() -> Vec<DescriptorUpdateTemplateEntry> {
+///         vec![
+///             DescriptorUpdateTemplateEntry {
+///                 binding: 0,
+///                 first_array_element: 0,
+///                 descriptor_count: 1,
+///                 offset: 0,
+///                 stride: mem::size_of::<DescriptorBufferInfo>(),
+///             },
+///             DescriptorUpdateTemplateEntry {
+///                 binding: 1,
+///                 first_array_element: 0,
+///                 descriptor_count: 2,
+///                 offset: mem::size_of::<DescriptorBufferInfo>(),
+///                 stride: mem::size_of::<DescriptorImageInfo>(),
+///             },
+///         ]
+///     }
+/// }
+/// ```
+pub unsafe trait DescriptorUpdateData {
+    /// Returns the location of the descriptors within `Self`.
+    fn entries() -> Vec<DescriptorUpdateTemplateEntry>;
+}
+
+/// A sampler, image or input attachment descriptor, for use with descriptor update templates.
+///
+/// While it is safe to build a `DescriptorImageInfo`, it only holds the handles of the objects and
+/// doesn't keep them alive.
+#[repr(C)]
+pub struct DescriptorImageInfo {
+    raw: vk::DescriptorImageInfo,
+}
+
+impl DescriptorImageInfo {
+    /// Builds a sampler descriptor.
+    #[inline]
+    pub fn sampler(sampler: &Arc<Sampler>) -> DescriptorImageInfo {
+        DescriptorImageInfo {
+            raw: vk::DescriptorImageInfo {
+                sampler: sampler.internal_object(),
+                imageView: 0,
+                imageLayout: 0,
+            },
+        }
+    }
+
+    /// Builds a sampled image descriptor.
+    #[inline]
+    pub fn sampled_image<I>(image: &I) -> DescriptorImageInfo
+        where I: ?Sized + ImageViewAccess
+    {
+        DescriptorImageInfo {
+            raw: vk::DescriptorImageInfo {
+                sampler: 0,
+                imageView: image.inner().internal_object(),
+                imageLayout: image.descriptor_set_sampled_image_layout() as u32,
+            },
+        }
+    }
+
+    /// Builds a storage image descriptor.
+    #[inline]
+    pub fn storage_image<I>(image: &I) -> DescriptorImageInfo
+        where I: ?Sized + ImageViewAccess
+    {
+        DescriptorImageInfo {
+            raw: vk::DescriptorImageInfo {
+                sampler: 0,
+                imageView: image.inner().internal_object(),
+                imageLayout: image.descriptor_set_storage_image_layout() as u32,
+            },
+        }
+    }
+
+    /// Builds a combined image and sampler descriptor.
+    #[inline]
+    pub fn combined_image_sampler<I>(sampler: &Arc<Sampler>, image: &I) -> DescriptorImageInfo
+        where I: ?Sized + ImageViewAccess
+    {
+        DescriptorImageInfo {
+            raw: vk::DescriptorImageInfo {
+                sampler: sampler.internal_object(),
+                imageView: image.inner().internal_object(),
+                imageLayout: image.descriptor_set_combined_image_sampler_layout() as u32,
+            },
+        }
+    }
+
+    /// Builds an input attachment descriptor.
+    #[inline]
+    pub fn input_attachment<I>(image: &I) -> DescriptorImageInfo
+        where I: ?Sized + ImageViewAccess
+    {
+        DescriptorImageInfo {
+            raw: vk::DescriptorImageInfo {
+                sampler: 0,
+                imageView: image.inner().internal_object(),
+                imageLayout: image.descriptor_set_input_attachment_layout() as u32,
+            },
+        }
+    }
+}
+
+/// A uniform or storage buffer descriptor, for use with descriptor update templates.
+///
+/// While it is safe to build a `DescriptorBufferInfo`, it only holds the handle of the buffer and
+/// doesn't keep it alive.
+#[repr(C)]
+pub struct DescriptorBufferInfo {
+    raw: vk::DescriptorBufferInfo,
+}
+
+impl DescriptorBufferInfo {
+    /// Builds a buffer descriptor that covers the whole of `buffer`.
+    #[inline]
+    pub fn new<B>(buffer: &B) -> DescriptorBufferInfo
+        where B: ?Sized + BufferAccess
+    {
+        let size = buffer.size();
+        let BufferInner { buffer, offset } = buffer.inner();
+
+        DescriptorBufferInfo {
+            raw: vk::DescriptorBufferInfo {
+                buffer: buffer.internal_object(),
+                offset: offset as u64,
+                range: size as u64,
+            },
+        }
+    }
+}
+
+/// A uniform or storage texel buffer descriptor, for use with descriptor update templates.
+///
+/// While it is safe to build a `DescriptorTexelBufferInfo`, it only holds the handle of the
+/// buffer view and doesn't keep it alive.
+#[repr(C)]
+pub struct DescriptorTexelBufferInfo {
+    raw: vk::BufferView,
+}
+
+impl DescriptorTexelBufferInfo {
+    /// Builds a texel buffer descriptor.
+    #[inline]
+    pub fn new<F, B>(view: &BufferView<F, B>) -> DescriptorTexelBufferInfo
+        where B: BufferAccess
+    {
+        DescriptorTexelBufferInfo { raw: view.internal_object() }
+    }
+}
+
+/// Describes how to write the descriptors of a set from data of type `T`.
+///
+/// Use `UnsafeDescriptorSet::write_with_template` to perform the write.
+pub struct DescriptorUpdateTemplate<T: ?Sized> {
+    template: vk::DescriptorUpdateTemplateKHR,
+    layout: Arc<UnsafeDescriptorSetLayout>,
+    entries: SmallVec<[DescriptorUpdateTemplateEntry; 8]>,
+    marker: PhantomData<fn(&T)>,
+}
+
+impl<T> DescriptorUpdateTemplate<T>
+    where T: DescriptorUpdateData
+{
+    /// Builds a template that writes descriptor sets of the given layout from a `T`.
+    ///
+    /// The device must use Vulkan 1.1, or the `VK_KHR_descriptor_update_template` extension must
+    /// be enabled on it.
+    pub fn new(layout: Arc<UnsafeDescriptorSetLayout>)
+               -> Result<DescriptorUpdateTemplate<T>, DescriptorUpdateTemplateCreationError> {
+        DescriptorUpdateTemplate::new_impl(layout, T::entries(), Some(mem::size_of::<T>()))
+    }
+}
+
+impl DescriptorUpdateTemplate<[u8]> {
+    /// Builds a template that expects the descriptors of every binding of the layout, packed one
+    /// after the other in binding order.
+    ///
+    /// Returns `None` if the layout has no descriptor.
+    pub(crate) fn packed(layout: Arc<UnsafeDescriptorSetLayout>)
+                         -> Result<Option<DescriptorUpdateTemplate<[u8]>>,
+                                   DescriptorUpdateTemplateCreationError> {
+        let mut entries = Vec::new();
+        let mut offset = 0;
+
+        for binding in 0 .. layout.num_bindings() {
+            let desc = match layout.descriptor(binding) {
+                Some(d) => d,
+                None => continue,
+            };
+
+            let stride = info_size(desc.ty.ty().unwrap());
+            entries.push(DescriptorUpdateTemplateEntry {
+                             binding: binding as u32,
+                             first_array_element: 0,
+                             descriptor_count: desc.array_count,
+                             offset: offset,
+                             stride: stride,
+                         });
+            offset += stride * desc.array_count as usize;
+        }
+
+        if entries.is_empty() {
+            return Ok(None);
+        }
+
+        DescriptorUpdateTemplate::new_impl(layout, entries, None).map(Some)
+    }
+
+    /// Writes a descriptor set with a packed template, turning the writes into packed data.
+    ///
+    /// # Safety
+    ///
+    /// Same as `UnsafeDescriptorSet::write_with_template`. In addition, the writes must cover
+    /// all the descriptors of the layout.
+    pub(crate) unsafe fn write_packed<I>(&self, set: &mut UnsafeDescriptorSet, writes: I)
+        where I: IntoIterator<Item = DescriptorWrite>
+    {
+        let size = self.entries
+            .last()
+            .map(|e| e.offset + e.stride * e.descriptor_count as usize)
+            .unwrap_or(0);
+
+        // Using `u64`s so that the infos are properly aligned.
+        let mut data: SmallVec<[u64; 64]> = SmallVec::from_elem(0, (size + 7) / 8);
+        let base = data.as_mut_ptr() as *mut u8;
+
+        for write in writes {
+            let entry = self.entries
+                .iter()
+                .find(|e| e.binding == write.binding())
+                .expect("write to a binding that isn't part of the template");
+
+            for (array_element, info) in write.raw_infos() {
+                debug_assert!(array_element < entry.descriptor_count);
+                let ptr = base.add(entry.offset + entry.stride * array_element as usize);
+
+                match info {
+                    RawDescriptorInfo::Image(info) => {
+                        ptr::write(ptr as *mut vk::DescriptorImageInfo, info)
+                    },
+                    RawDescriptorInfo::Buffer(info) => {
+                        ptr::write(ptr as *mut vk::DescriptorBufferInfo, info)
+                    },
+                    RawDescriptorInfo::BufferView(view) => {
+                        ptr::write(ptr as *mut vk::BufferView, view)
+                    },
+                }
+            }
+        }
+
+        let device = self.layout.device();
+        set.write_with_template(device, self, slice::from_raw_parts(base, size));
+    }
+}
+
+impl<T: ?Sized> DescriptorUpdateTemplate<T> {
+    fn new_impl<I>(layout: Arc<UnsafeDescriptorSetLayout>, entries: I, data_size: Option<usize>)
+                   -> Result<DescriptorUpdateTemplate<T>, DescriptorUpdateTemplateCreationError>
+        where I: IntoIterator<Item = DescriptorUpdateTemplateEntry>
+    {
+        let device = layout.device().clone();
+
+        if device.api_version() < Version::V1_1 &&
+            !device.loaded_extensions().khr_descriptor_update_template
+        {
+            return Err(DescriptorUpdateTemplateCreationError::MissingExtension {
+                           name: "VK_KHR_descriptor_update_template",
+                       });
+        }
+
+        assert!(!layout.is_push_descriptor(),
+                "descriptor update templates for push descriptors are not supported");
+
+        let entries: SmallVec<[_; 8]> = entries.into_iter().collect();
+
+        let raw_entries = entries
+            .iter()
+            .map(|entry| {
+                let desc = match layout.descriptor(entry.binding as usize) {
+                    Some(d) => d,
+                    None => {
+                        return Err(DescriptorUpdateTemplateCreationError::EmptyBinding {
+                                       binding: entry.binding,
+                                   });
+                    },
+                };
+
+                if entry.first_array_element + entry.descriptor_count > desc.array_count {
+                    return Err(DescriptorUpdateTemplateCreationError::ArrayOutOfBounds {
+                                   binding: entry.binding,
+                               });
+                }
+
+                let ty = desc.ty.ty().unwrap();
+
+                if let Some(data_size) = data_size {
+                    let end = entry.offset +
+                        entry.stride * entry.descriptor_count.saturating_sub(1) as usize +
+                        info_size(ty);
+                    if end > data_size {
+                        return Err(DescriptorUpdateTemplateCreationError::DataOutOfBounds {
+                                       binding: entry.binding,
+                                   });
+                    }
+                }
+
+                Ok(vk::DescriptorUpdateTemplateEntryKHR {
+                       dstBinding: entry.binding,
+                       dstArrayElement: entry.first_array_element,
+                       descriptorCount: entry.descriptor_count,
+                       descriptorType: ty as u32,
+                       offset: entry.offset,
+                       stride: entry.stride,
+                   })
+            })
+            .collect::<Result<SmallVec<[_; 8]>, _>>()?;
+
+        let template = unsafe {
+            let infos = vk::DescriptorUpdateTemplateCreateInfoKHR {
+                sType: vk::STRUCTURE_TYPE_DESCRIPTOR_UPDATE_TEMPLATE_CREATE_INFO_KHR,
+                pNext: ptr::null(),
+                flags: 0, // reserved
+                descriptorUpdateEntryCount: raw_entries.len() as u32,
+                pDescriptorUpdateEntries: raw_entries.as_ptr(),
+                templateType: vk::DESCRIPTOR_UPDATE_TEMPLATE_TYPE_DESCRIPTOR_SET_KHR,
+                descriptorSetLayout: layout.internal_object(),
+                pipelineBindPoint: 0, // ignored
+                pipelineLayout: 0, // ignored
+                set: 0, // ignored
+            };
+
+            let vk = device.pointers();
+            let mut output = MaybeUninit::uninit();
+            check_errors(vk.CreateDescriptorUpdateTemplateKHR(device.internal_object(),
+                                                              &infos,
+                                                              ptr::null(),
+                                                              output.as_mut_ptr()))?;
+            output.assume_init()
+        };
+
+        Ok(DescriptorUpdateTemplate {
+               template: template,
+               layout: layout,
+               entries: entries,
+               marker: PhantomData,
+           })
+    }
+
+    /// Returns the layout the template was created with.
+    #[inline]
+    pub fn layout(&self) -> &Arc<UnsafeDescriptorSetLayout> {
+        &self.layout
+    }
+
+    /// Returns the entries the template was created with.
+    #[inline]
+    pub fn entries(&self) -> &[DescriptorUpdateTemplateEntry] {
+        &self.entries
+    }
+}
+
+// Returns the size of the info of a single descriptor of the given type in the template data.
+fn info_size(ty: DescriptorType) -> usize {
+    match ty {
+        DescriptorType::Sampler |
+        DescriptorType::CombinedImageSampler |
+        DescriptorType::SampledImage |
+        DescriptorType::StorageImage |
+        DescriptorType::InputAttachment => mem::size_of::<DescriptorImageInfo>(),
+        DescriptorType::UniformBuffer |
+        DescriptorType::StorageBuffer |
+        DescriptorType::UniformBufferDynamic |
+        DescriptorType::StorageBufferDynamic => mem::size_of::<DescriptorBufferInfo>(),
+        DescriptorType::UniformTexelBuffer |
+        DescriptorType::StorageTexelBuffer => mem::size_of::<DescriptorTexelBufferInfo>(),
+    }
+}
+
+unsafe impl<T: ?Sized> DeviceOwned for DescriptorUpdateTemplate<T> {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        self.layout.device()
+    }
+}
+
+unsafe impl<T: ?Sized> VulkanObject for DescriptorUpdateTemplate<T> {
+    type Object = vk::DescriptorUpdateTemplateKHR;
+
+    const TYPE: vk::ObjectType = vk::OBJECT_TYPE_DESCRIPTOR_UPDATE_TEMPLATE_KHR;
+
+    #[inline]
+    fn internal_object(&self) -> vk::DescriptorUpdateTemplateKHR {
+        self.template
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for DescriptorUpdateTemplate<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        fmt.debug_struct("DescriptorUpdateTemplate")
+            .field("raw", &self.template)
+            .field("layout", &self.layout)
+            .field("entries", &self.entries)
+            .finish()
+    }
+}
+
+impl<T: ?Sized> Drop for DescriptorUpdateTemplate<T> {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe {
+            let device = self.layout.device();
+            let vk = device.pointers();
+            vk.DestroyDescriptorUpdateTemplateKHR(device.internal_object(),
+                                                  self.template,
+                                                  ptr::null());
+        }
+    }
+}
+
+/// Error that can happen when creating a `DescriptorUpdateTemplate`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DescriptorUpdateTemplateCreationError {
+    /// Not enough memory.
+    OomError(OomError),
+    /// A device extension required for this operation is not enabled.
+    MissingExtension { name: &'static str },
+    /// An entry refers to a binding that is empty in the layout.
+    EmptyBinding { binding: u32 },
+    /// An entry writes past the end of the array of its binding.
+    ArrayOutOfBounds { binding: u32 },
+    /// An entry reads past the end of the data.
+    DataOutOfBounds { binding: u32 },
+}
+
+impl error::Error for DescriptorUpdateTemplateCreationError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            DescriptorUpdateTemplateCreationError::OomError(_) => "not enough memory available",
+            DescriptorUpdateTemplateCreationError::MissingExtension { .. } =>
+                "a device extension required for this operation is not enabled",
+            DescriptorUpdateTemplateCreationError::EmptyBinding { .. } =>
+                "an entry refers to a binding that is empty in the layout",
+            DescriptorUpdateTemplateCreationError::ArrayOutOfBounds { .. } =>
+                "an entry writes past the end of the array of its binding",
+            DescriptorUpdateTemplateCreationError::DataOutOfBounds { .. } =>
+                "an entry reads past the end of the data",
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&dyn error::Error> {
+        match *self {
+            DescriptorUpdateTemplateCreationError::OomError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for DescriptorUpdateTemplateCreationError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<OomError> for DescriptorUpdateTemplateCreationError {
+    #[inline]
+    fn from(err: OomError) -> DescriptorUpdateTemplateCreationError {
+        DescriptorUpdateTemplateCreationError::OomError(err)
+    }
+}
+
+impl From<Error> for DescriptorUpdateTemplateCreationError {
+    #[inline]
+    fn from(err: Error) -> DescriptorUpdateTemplateCreationError {
+        match err {
+            err @ Error::OutOfHostMemory => {
+                DescriptorUpdateTemplateCreationError::OomError(OomError::from(err))
+            },
+            err @ Error::OutOfDeviceMemory => {
+                DescriptorUpdateTemplateCreationError::OomError(OomError::from(err))
+            },
+            _ => panic!("unexpected error: {:?}", err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use descriptor::descriptor::DescriptorDesc;
+    use descriptor::descriptor::DescriptorDescTy;
+    use descriptor::descriptor::ShaderStages;
+    use descriptor::descriptor_set::DescriptorsCount;
+    use descriptor::descriptor_set::UnsafeDescriptorPool;
+    use descriptor::descriptor_set::UnsafeDescriptorSetLayout;
+    use device::Device;
+    use mock::Recorder;
+    use sampler::Sampler;
+    use std::iter;
+    use std::mem;
+    use std::sync::Arc;
+    use vk;
+    use super::DescriptorImageInfo;
+    use super::DescriptorUpdateData;
+    use super::DescriptorUpdateTemplate;
+    use super::DescriptorUpdateTemplateCreationError;
+    use super::DescriptorUpdateTemplateEntry;
+
+    #[repr(C)]
+    struct Samplers {
+        samplers: [DescriptorImageInfo; 2],
+    }
+
+    unsafe impl DescriptorUpdateData for Samplers {
+        fn entries() -> Vec<DescriptorUpdateTemplateEntry> {
+            vec![DescriptorUpdateTemplateEntry {
+                     binding: 0,
+                     first_array_element: 0,
+                     descriptor_count: 2,
+                     offset: 0,
+                     stride: mem::size_of::<DescriptorImageInfo>(),
+                 }]
+        }
+    }
+
+    // Claims two samplers, but only has room for one.
+    #[repr(C)]
+    struct TooSmall {
+        sampler: DescriptorImageInfo,
+    }
+
+    unsafe impl DescriptorUpdateData for TooSmall {
+        fn entries() -> Vec<DescriptorUpdateTemplateEntry> {
+            Samplers::entries()
+        }
+    }
+
+    fn samplers_layout(device: &Arc<Device>) -> Arc<UnsafeDescriptorSetLayout> {
+        let desc = DescriptorDesc {
+            ty: DescriptorDescTy::Sampler,
+            array_count: 2,
+            stages: ShaderStages::all_graphics(),
+            readonly: true,
+        };
+        Arc::new(UnsafeDescriptorSetLayout::new(device.clone(), iter::once(Some(desc))).unwrap())
+    }
+
+    #[test]
+    fn missing_extension() {
+        let (device, _) = mock_dev_and_queue!();
+
+        match DescriptorUpdateTemplate::<Samplers>::new(samplers_layout(&device)) {
+            Err(DescriptorUpdateTemplateCreationError::MissingExtension { .. }) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn data_out_of_bounds() {
+        let (device, _) = mock_dev_and_queue!(extensions: [khr_descriptor_update_template]);
+
+        match DescriptorUpdateTemplate::<TooSmall>::new(samplers_layout(&device)) {
+            Err(DescriptorUpdateTemplateCreationError::DataOutOfBounds { binding: 0 }) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn write_with_template() {
+        let (device, _) = mock_dev_and_queue!(extensions: [khr_descriptor_update_template]);
+        let recorder = Recorder::of(device.instance()).unwrap();
+
+        let layout = samplers_layout(&device);
+        let template = DescriptorUpdateTemplate::<Samplers>::new(layout.clone()).unwrap();
+
+        let count = DescriptorsCount {
+            sampler: 2,
+            ..DescriptorsCount::zero()
+        };
+        let mut pool = UnsafeDescriptorPool::new(device.clone(), &count, 1, false).unwrap();
+        let sampler = Sampler::simple_repeat_linear(device.clone());
+        let data = Samplers {
+            samplers: [
+                DescriptorImageInfo::sampler(&sampler),
+                DescriptorImageInfo::sampler(&sampler),
+            ],
+        };
+
+        unsafe {
+            let mut set = pool.alloc(iter::once(&*layout)).unwrap().next().unwrap();
+            set.write_with_template(&device, &template, &data);
+        }
+
+        assert!(recorder.calls().contains(&"vkUpdateDescriptorSetWithTemplateKHR"));
+        assert!(recorder.errors().is_empty());
+
+        drop(template);
+        assert_eq!(recorder.live_count(vk::OBJECT_TYPE_DESCRIPTOR_UPDATE_TEMPLATE_KHR), 0);
+    }
+}
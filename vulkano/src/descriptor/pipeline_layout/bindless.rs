@@ -0,0 +1,139 @@
+// Copyright (c) 2020 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::sync::Arc;
+
+use descriptor::descriptor::DescriptorDesc;
+use descriptor::descriptor_set::DescriptorBindingFlags;
+use descriptor::descriptor_set::DescriptorSetDesc;
+use descriptor::descriptor_set::DescriptorSetLayoutCreationError;
+use descriptor::descriptor_set::UnsafeDescriptorSetLayout;
+use descriptor::pipeline_layout::PipelineLayoutDesc;
+use descriptor::pipeline_layout::PipelineLayoutDescPcRange;
+use device::Device;
+
+/// Wraps around a `PipelineLayoutDesc` and turns one of its sets into a set that can be used
+/// with a `BindlessDescriptorSet`.
+///
+/// Every binding of the set gets the given binding flags. Bindings that are runtime-sized arrays
+/// in the shaders get `max_variable_count` elements, and if the last binding is one of them it
+/// also gets the `variable_descriptor_count` flag.
+///
+/// # Example
+///
+/// ```ignore
+/// let flags = DescriptorBindingFlags {
+///     update_after_bind: true,
+///     partially_bound: true,
+///     .. DescriptorBindingFlags::none()
+/// };
+/// let desc = PipelineLayoutDescBindless::new(device.clone(), fs.layout().clone(), 0, 1024,
+///                                            flags)?;
+/// let pipeline_layout = Arc::new(desc.build(device.clone())?);
+/// let set = BindlessDescriptorSet::new(pipeline_layout.descriptor_set_layout(0).unwrap().clone(),
+///                                      256)?;
+/// ```
+pub struct PipelineLayoutDescBindless<T> {
+    inner: T,
+    set: usize,
+    layout: Arc<UnsafeDescriptorSetLayout>,
+}
+
+impl<T> PipelineLayoutDescBindless<T>
+    where T: PipelineLayoutDesc
+{
+    /// Builds a new description, creating the descriptor set layout for set `set`.
+    ///
+    /// Returns an error if the layout can't be created, for example if the
+    /// `VK_EXT_descriptor_indexing` extension isn't enabled on the device.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `set` is out of range of `inner`.
+    ///
+    pub fn new(device: Arc<Device>, inner: T, set: usize, max_variable_count: u32,
+               flags: DescriptorBindingFlags)
+               -> Result<PipelineLayoutDescBindless<T>, DescriptorSetLayoutCreationError> {
+        assert!(set < inner.num_sets());
+
+        let layout = {
+            let num_bindings = inner.num_bindings_in_set(set).unwrap_or(0);
+            let descriptors = (0 .. num_bindings).map(|binding| {
+                let mut flags = DescriptorBindingFlags {
+                    variable_descriptor_count: false,
+                    .. flags
+                };
+
+                let desc = inner.descriptor(set, binding).map(|mut desc| {
+                    if inner.is_runtime_array(set, binding) {
+                        desc.array_count = max_variable_count;
+                        flags.variable_descriptor_count = binding == num_bindings - 1;
+                    }
+                    desc
+                });
+
+                (desc, flags)
+            });
+            Arc::new(UnsafeDescriptorSetLayout::with_binding_flags(device, descriptors)?)
+        };
+
+        Ok(PipelineLayoutDescBindless {
+               inner: inner,
+               set: set,
+               layout: layout,
+           })
+    }
+}
+
+unsafe impl<T> PipelineLayoutDesc for PipelineLayoutDescBindless<T>
+    where T: PipelineLayoutDesc
+{
+    #[inline]
+    fn num_sets(&self) -> usize {
+        self.inner.num_sets()
+    }
+
+    #[inline]
+    fn num_bindings_in_set(&self, set: usize) -> Option<usize> {
+        self.inner.num_bindings_in_set(set)
+    }
+
+    #[inline]
+    fn descriptor(&self, set: usize, binding: usize) -> Option<DescriptorDesc> {
+        if set == self.set {
+            self.layout.descriptor(binding)
+        } else {
+            self.inner.descriptor(set, binding)
+        }
+    }
+
+    #[inline]
+    fn provided_set_layout(&self, set: usize) -> Option<Arc<UnsafeDescriptorSetLayout>> {
+        if set == self.set {
+            Some(self.layout.clone())
+        } else {
+            self.inner.provided_set_layout(set)
+        }
+    }
+
+    #[inline]
+    fn is_runtime_array(&self, set: usize, binding: usize) -> bool {
+        self.inner.is_runtime_array(set, binding)
+    }
+
+    #[inline]
+    fn num_push_constants_ranges(&self) -> usize {
+        self.inner.num_push_constants_ranges()
+    }
+
+    #[inline]
+    fn push_constants_range(&self, num: usize) -> Option<PipelineLayoutDescPcRange> {
+        self.inner.push_constants_range(num)
+    }
+}
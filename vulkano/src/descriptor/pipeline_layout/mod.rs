@@ -49,8 +49,10 @@
 //!
 //! TODO: write this section
 
+pub use self::bindless::PipelineLayoutDescBindless;
 pub use self::empty::EmptyPipelineDesc;
 pub use self::limits_check::PipelineLayoutLimitsError;
+pub use self::push_descriptor::PipelineLayoutDescPushDescriptor;
 pub use self::runtime_desc::RuntimePipelineDesc;
 pub use self::runtime_desc::RuntimePipelineDescError;
 pub use self::sys::PipelineLayout;
@@ -67,8 +69,10 @@ pub use self::union::PipelineLayoutDescUnion;
 
 pub(crate) use self::tweaks::PipelineLayoutDescTweaks;
 
+mod bindless;
 mod empty;
 mod limits_check;
+mod push_descriptor;
 mod runtime_desc;
 mod sys;
 mod traits;
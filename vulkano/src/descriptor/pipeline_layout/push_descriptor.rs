@@ -0,0 +1,183 @@
+// Copyright (c) 2020 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::sync::Arc;
+
+use descriptor::descriptor::DescriptorDesc;
+use descriptor::descriptor_set::DescriptorSetLayoutCreationError;
+use descriptor::descriptor_set::UnsafeDescriptorSetLayout;
+use descriptor::pipeline_layout::PipelineLayoutDesc;
+use descriptor::pipeline_layout::PipelineLayoutDescPcRange;
+use device::Device;
+
+/// Wraps around a `PipelineLayoutDesc` and turns one of its sets into a push descriptor set.
+///
+/// The descriptors of the set are then provided with
+/// `AutoCommandBufferBuilder::push_descriptor_set` instead of being bound as a descriptor set.
+/// The push descriptor set must be the last set of the layout, so that the descriptor sets
+/// passed to the draw and dispatch commands don't need to skip over it. Creating a
+/// `PipelineLayout` with a push descriptor set at another index returns
+/// `PipelineLayoutCreationError::PushDescriptorSetNotLast`.
+///
+/// # Example
+///
+/// ```ignore
+/// let last_set = vs.layout().num_sets() - 1;
+/// let desc = PipelineLayoutDescPushDescriptor::new(device.clone(), vs.layout().clone(),
+///                                                  last_set)?;
+/// let pipeline_layout = Arc::new(desc.build(device.clone())?);
+/// ```
+pub struct PipelineLayoutDescPushDescriptor<T> {
+    inner: T,
+    set: usize,
+    layout: Arc<UnsafeDescriptorSetLayout>,
+}
+
+impl<T> PipelineLayoutDescPushDescriptor<T>
+    where T: PipelineLayoutDesc
+{
+    /// Builds a new description, creating the push descriptor set layout for set `set`.
+    ///
+    /// Returns an error if the layout can't be created, for example if the
+    /// `VK_KHR_push_descriptor` extension isn't enabled on the device.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `set` isn't the last set of `inner`.
+    ///
+    pub fn new(device: Arc<Device>, inner: T, set: usize)
+               -> Result<PipelineLayoutDescPushDescriptor<T>, DescriptorSetLayoutCreationError> {
+        assert_eq!(set + 1,
+                   inner.num_sets(),
+                   "the push descriptor set must be the last set of the layout");
+
+        let layout = {
+            let num_bindings = inner.num_bindings_in_set(set).unwrap_or(0);
+            let descriptors = (0 .. num_bindings).map(|binding| inner.descriptor(set, binding));
+            Arc::new(UnsafeDescriptorSetLayout::push_descriptor(device, descriptors)?)
+        };
+
+        Ok(PipelineLayoutDescPushDescriptor {
+               inner: inner,
+               set: set,
+               layout: layout,
+           })
+    }
+
+    /// Returns the index of the push descriptor set.
+    #[inline]
+    pub fn push_descriptor_set(&self) -> usize {
+        self.set
+    }
+}
+
+unsafe impl<T> PipelineLayoutDesc for PipelineLayoutDescPushDescriptor<T>
+    where T: PipelineLayoutDesc
+{
+    #[inline]
+    fn num_sets(&self) -> usize {
+        self.inner.num_sets()
+    }
+
+    #[inline]
+    fn num_bindings_in_set(&self, set: usize) -> Option<usize> {
+        self.inner.num_bindings_in_set(set)
+    }
+
+    #[inline]
+    fn descriptor(&self, set: usize, binding: usize) -> Option<DescriptorDesc> {
+        self.inner.descriptor(set, binding)
+    }
+
+    #[inline]
+    fn provided_set_layout(&self, set: usize) -> Option<Arc<UnsafeDescriptorSetLayout>> {
+        if set == self.set {
+            Some(self.layout.clone())
+        } else {
+            self.inner.provided_set_layout(set)
+        }
+    }
+
+    #[inline]
+    fn is_runtime_array(&self, set: usize, binding: usize) -> bool {
+        self.inner.is_runtime_array(set, binding)
+    }
+
+    #[inline]
+    fn num_push_constants_ranges(&self) -> usize {
+        self.inner.num_push_constants_ranges()
+    }
+
+    #[inline]
+    fn push_constants_range(&self, num: usize) -> Option<PipelineLayoutDescPcRange> {
+        self.inner.push_constants_range(num)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::iter;
+
+    use descriptor::descriptor::DescriptorBufferDesc;
+    use descriptor::descriptor::DescriptorDesc;
+    use descriptor::descriptor::DescriptorDescTy;
+    use descriptor::descriptor::ShaderStages;
+    use descriptor::pipeline_layout::PipelineLayoutCreationError;
+    use descriptor::pipeline_layout::PipelineLayoutDesc;
+    use descriptor::pipeline_layout::PipelineLayoutDescPushDescriptor;
+    use descriptor::pipeline_layout::RuntimePipelineDesc;
+
+    fn desc(num_sets: usize) -> RuntimePipelineDesc {
+        let buffer = DescriptorDesc {
+            ty: DescriptorDescTy::Buffer(DescriptorBufferDesc {
+                                             dynamic: Some(false),
+                                             storage: false,
+                                         }),
+            array_count: 1,
+            stages: ShaderStages::all(),
+            readonly: true,
+        };
+
+        RuntimePipelineDesc::new((0 .. num_sets).map(|_| Some(Some(buffer.clone()))),
+                                 iter::empty())
+            .unwrap()
+    }
+
+    #[test]
+    fn last_set() {
+        let (device, _) = mock_dev_and_queue!(extensions: [khr_push_descriptor]);
+        let desc = PipelineLayoutDescPushDescriptor::new(device.clone(), desc(2), 1).unwrap();
+        let layout = desc.build(device).unwrap();
+        assert!(layout.provided_set_layout(1).unwrap().is_push_descriptor());
+    }
+
+    #[test]
+    fn not_last_set_panic() {
+        let (device, _) = mock_dev_and_queue!(extensions: [khr_push_descriptor]);
+        assert_should_panic!("the push descriptor set must be the last set of the layout", {
+            let _ = PipelineLayoutDescPushDescriptor::new(device, desc(2), 0);
+        });
+    }
+
+    #[test]
+    fn not_last_set_layout_creation() {
+        let (device, _) = mock_dev_and_queue!(extensions: [khr_push_descriptor]);
+
+        // The union provides the push descriptor set layout of the first set to a layout that
+        // has two sets.
+        let desc = PipelineLayoutDescPushDescriptor::new(device.clone(), desc(1), 0)
+            .unwrap()
+            .union(desc(2));
+
+        match desc.build(device) {
+            Err(PipelineLayoutCreationError::PushDescriptorSetNotLast) => (),
+            _ => panic!(),
+        }
+    }
+}
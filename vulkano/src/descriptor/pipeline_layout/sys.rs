@@ -77,6 +77,17 @@ impl<L> PipelineLayout<L>
             layouts
         };
 
+        // Descriptor sets are bound starting from set 0, so a push descriptor set anywhere else
+        // than at the end would be overlapped by the bound sets.
+        if layouts
+            .iter()
+            .rev()
+            .skip(1)
+            .any(|layout| layout.is_push_descriptor())
+        {
+            return Err(PipelineLayoutCreationError::PushDescriptorSetNotLast);
+        }
+
         // Grab the list of `vkDescriptorSetLayout` objects from `layouts`.
         let layouts_ids = layouts
             .iter()
@@ -203,6 +214,16 @@ unsafe impl<D> PipelineLayoutDesc for PipelineLayout<D>
         self.desc.descriptor(set, binding)
     }
 
+    #[inline]
+    fn provided_set_layout(&self, set: usize) -> Option<Arc<UnsafeDescriptorSetLayout>> {
+        self.layouts.get(set).cloned()
+    }
+
+    #[inline]
+    fn is_runtime_array(&self, set: usize, binding: usize) -> bool {
+        self.desc.is_runtime_array(set, binding)
+    }
+
     #[inline]
     fn num_push_constants_ranges(&self) -> usize {
         self.desc.num_push_constants_ranges()
@@ -271,6 +292,8 @@ pub enum PipelineLayoutCreationError {
     /// One of the push constants range didn't obey the rules. The list of stages must not be
     /// empty, the size must not be 0, and the size must be a multiple or 4.
     InvalidPushConstant,
+    /// A push descriptor set was not the last set of the layout.
+    PushDescriptorSetNotLast,
 }
 
 impl error::Error for PipelineLayoutCreationError {
@@ -286,6 +309,9 @@ impl error::Error for PipelineLayoutCreationError {
             PipelineLayoutCreationError::InvalidPushConstant => {
                 "one of the push constants range didn't obey the rules"
             },
+            PipelineLayoutCreationError::PushDescriptorSetNotLast => {
+                "a push descriptor set was not the last set of the layout"
+            },
         }
     }
 
@@ -347,14 +373,14 @@ mod tests {
 
     #[test]
     fn empty() {
-        let (device, _) = gfx_dev_and_queue!();
+        let (device, _) = mock_dev_and_queue!();
         let _layout = PipelineLayout::new(&device, iter::empty(), iter::empty()).unwrap();
     }
 
     #[test]
     fn wrong_device_panic() {
-        let (device1, _) = gfx_dev_and_queue!();
-        let (device2, _) = gfx_dev_and_queue!();
+        let (device1, _) = mock_dev_and_queue!();
+        let (device2, _) = mock_dev_and_queue!();
 
         let set = match UnsafeDescriptorSetLayout::raw(device1, iter::empty()) {
             Ok(s) => Arc::new(s),
@@ -368,7 +394,7 @@ mod tests {
 
     #[test]
     fn invalid_push_constant_stages() {
-        let (device, _) = gfx_dev_and_queue!();
+        let (device, _) = mock_dev_and_queue!();
 
         let push_constant = (0, 8, ShaderStages::none());
 
@@ -380,7 +406,7 @@ mod tests {
 
     #[test]
     fn invalid_push_constant_size1() {
-        let (device, _) = gfx_dev_and_queue!();
+        let (device, _) = mock_dev_and_queue!();
 
         let push_constant = (0, 0, ShaderStages::all_graphics());
 
@@ -392,7 +418,7 @@ mod tests {
 
     #[test]
     fn invalid_push_constant_size2() {
-        let (device, _) = gfx_dev_and_queue!();
+        let (device, _) = mock_dev_and_queue!();
 
         let push_constant = (0, 11, ShaderStages::all_graphics());
 
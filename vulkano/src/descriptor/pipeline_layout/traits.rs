@@ -80,6 +80,16 @@ pub unsafe trait PipelineLayoutDesc {
         None
     }
 
+    /// Returns true if the given binding of the given set is a runtime-sized array in the shaders
+    /// (`OpTypeRuntimeArray`). In that case the `array_count` of its descriptor is only the
+    /// minimum number of elements.
+    ///
+    /// The default implementation returns `false`.
+    #[inline]
+    fn is_runtime_array(&self, _set: usize, _binding: usize) -> bool {
+        false
+    }
+
     /// Returns the number of push constant ranges of the layout.
     fn num_push_constants_ranges(&self) -> usize;
 
@@ -151,6 +161,16 @@ unsafe impl<T> PipelineLayoutDesc for T
         (**self).descriptor(set, binding)
     }
 
+    #[inline]
+    fn provided_set_layout(&self, set: usize) -> Option<Arc<UnsafeDescriptorSetLayout>> {
+        (**self).provided_set_layout(set)
+    }
+
+    #[inline]
+    fn is_runtime_array(&self, set: usize, binding: usize) -> bool {
+        (**self).is_runtime_array(set, binding)
+    }
+
     #[inline]
     fn num_push_constants_ranges(&self) -> usize {
         (**self).num_push_constants_ranges()
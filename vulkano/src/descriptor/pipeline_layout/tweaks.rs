@@ -10,9 +10,11 @@
 use descriptor::descriptor::DescriptorDesc;
 use descriptor::descriptor::DescriptorDescTy;
 use descriptor::descriptor::DescriptorBufferDesc;
+use descriptor::descriptor_set::UnsafeDescriptorSetLayout;
 use descriptor::pipeline_layout::PipelineLayoutDesc;
 use descriptor::pipeline_layout::PipelineLayoutDescPcRange;
 use fnv::FnvHashSet;
+use std::sync::Arc;
 
 /// Transforms a `PipelineLayoutDesc`.
 ///
@@ -69,6 +71,12 @@ unsafe impl<T> PipelineLayoutDesc for PipelineLayoutDescTweaks<T>
         })
     }
 
+    #[inline]
+    fn provided_set_layout(&self, set: usize) -> Option<Arc<UnsafeDescriptorSetLayout>> { self.inner.provided_set_layout(set) }
+
+    #[inline]
+    fn is_runtime_array(&self, set: usize, binding: usize) -> bool { self.inner.is_runtime_array(set, binding) }
+
     #[inline]
     fn num_push_constants_ranges(&self) -> usize { self.inner.num_push_constants_ranges() }
 
@@ -72,6 +72,11 @@ unsafe impl<A, B> PipelineLayoutDesc for PipelineLayoutDescUnion<A, B>
             .or(self.b.provided_set_layout(set))
     }
 
+    #[inline]
+    fn is_runtime_array(&self, set: usize, binding: usize) -> bool {
+        self.a.is_runtime_array(set, binding) || self.b.is_runtime_array(set, binding)
+    }
+
     #[inline]
     fn num_push_constants_ranges(&self) -> usize {
         // We simply call `push_constants_range` repeatedly to determine when it is over.
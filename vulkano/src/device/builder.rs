@@ -0,0 +1,566 @@
+// Copyright (c) 2020 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use smallvec::SmallVec;
+use std::error;
+use std::fmt;
+use std::sync::Arc;
+
+use device::Device;
+use device::DeviceCreationError;
+use device::DeviceExtensions;
+use device::Queue;
+use features::Features;
+use instance::PhysicalDevice;
+use swapchain::CapabilitiesError;
+use swapchain::Surface;
+
+/// Builds a `Device` from the roles that its queues are going to play.
+///
+/// Instead of passing queue families to `Device::new`, you request roles with a priority, and the
+/// builder picks the queue families according to the following policy:
+///
+/// - The graphics queue uses the first family that supports graphics operations. If a present
+///   queue is requested as well, a family that supports both graphics and presenting is
+///   preferred.
+/// - The present queue uses the family of the graphics queue if it can present to the surface,
+///   and the first family that can present otherwise.
+/// - The async compute queue preferably uses a family that supports compute but not graphics
+///   operations, then any other compute family than the one of the graphics queue, and falls back
+///   to the family of the graphics queue.
+/// - The transfer queue preferably uses a family that only supports transfers, then any family
+///   that isn't used by the other roles, and falls back to the families of the compute and
+///   graphics queues.
+///
+/// Roles that end up in the same family share the same queue, whose priority is the highest
+/// priority of these roles.
+///
+/// The requested extensions and features are checked against the physical device before
+/// creating the device, and everything that is missing is reported at once.
+///
+/// # Example
+///
+/// ```no_run
+/// use vulkano::device::DeviceBuilder;
+///
+/// # let physical: vulkano::instance::PhysicalDevice = return;
+/// # let surface: std::sync::Arc<vulkano::swapchain::Surface<()>> = return;
+/// let (device, queues) = DeviceBuilder::new(physical)
+///     .graphics(1.0)
+///     .present(&surface, 1.0)
+///     .transfer(0.5)
+///     .build()
+///     .unwrap();
+///
+/// let graphics_queue = queues.graphics.unwrap();
+/// let transfer_queue = queues.transfer.unwrap();
+/// ```
+pub struct DeviceBuilder<'a> {
+    physical_device: PhysicalDevice<'a>,
+    features: Features,
+    extensions: DeviceExtensions,
+    graphics: Option<f32>,
+    // Families that can present to the surface, and priority.
+    present: Option<(Result<SmallVec<[u32; 8]>, CapabilitiesError>, f32)>,
+    compute: Option<f32>,
+    transfer: Option<f32>,
+}
+
+impl<'a> DeviceBuilder<'a> {
+    /// Starts building a device for the given physical device, with no queue, extension or
+    /// feature.
+    #[inline]
+    pub fn new(physical_device: PhysicalDevice<'a>) -> DeviceBuilder<'a> {
+        DeviceBuilder {
+            physical_device,
+            features: Features::none(),
+            extensions: DeviceExtensions::none(),
+            graphics: None,
+            present: None,
+            compute: None,
+            transfer: None,
+        }
+    }
+
+    /// Sets the features to enable on the device.
+    #[inline]
+    pub fn features(mut self, features: &Features) -> DeviceBuilder<'a> {
+        self.features = features.clone();
+        self
+    }
+
+    /// Sets the extensions to enable on the device.
+    #[inline]
+    pub fn extensions(mut self, extensions: &DeviceExtensions) -> DeviceBuilder<'a> {
+        self.extensions = *extensions;
+        self
+    }
+
+    /// Requests a queue for graphics operations.
+    #[inline]
+    pub fn graphics(mut self, priority: f32) -> DeviceBuilder<'a> {
+        self.graphics = Some(priority);
+        self
+    }
+
+    /// Requests a queue that can present images to `surface`.
+    ///
+    /// This also enables the `khr_swapchain` extension.
+    pub fn present<W>(mut self, surface: &Surface<W>, priority: f32) -> DeviceBuilder<'a> {
+        let families = self.physical_device
+            .queue_families()
+            .filter_map(|family| match surface.is_supported(family) {
+                            Ok(true) => Some(Ok(family.id())),
+                            Ok(false) => None,
+                            Err(err) => Some(Err(err)),
+                        })
+            .collect();
+
+        self.present = Some((families, priority));
+        self.extensions.khr_swapchain = true;
+        self
+    }
+
+    /// Requests a queue for compute operations that, if possible, runs concurrently with the
+    /// graphics queue.
+    #[inline]
+    pub fn compute(mut self, priority: f32) -> DeviceBuilder<'a> {
+        self.compute = Some(priority);
+        self
+    }
+
+    /// Requests a queue for transfer operations that, if possible, runs concurrently with the
+    /// other queues.
+    #[inline]
+    pub fn transfer(mut self, priority: f32) -> DeviceBuilder<'a> {
+        self.transfer = Some(priority);
+        self
+    }
+
+    /// Chooses the queue families, checks the requirements against the physical device, and
+    /// creates the device.
+    pub fn build(self) -> Result<(Arc<Device>, DeviceQueues), DeviceBuilderError> {
+        let physical = self.physical_device;
+
+        let present = match self.present {
+            Some((Ok(families), priority)) => Some((families, priority)),
+            Some((Err(err), _)) => return Err(err.into()),
+            None => None,
+        };
+
+        let families: SmallVec<[FamilyInfo; 8]> = physical
+            .queue_families()
+            .map(|family| {
+                FamilyInfo {
+                    id: family.id(),
+                    graphics: family.supports_graphics(),
+                    compute: family.supports_compute(),
+                    transfer: family.explicitly_supports_transfers(),
+                    present: present
+                        .as_ref()
+                        .map(|(p, _)| p.contains(&family.id()))
+                        .unwrap_or(false),
+                }
+            })
+            .collect();
+
+        let selection = select_families(&families,
+                                        self.graphics.is_some(),
+                                        present.is_some(),
+                                        self.compute.is_some(),
+                                        self.transfer.is_some());
+
+        let mut missing_roles = Vec::new();
+        if self.graphics.is_some() && selection.graphics.is_none() {
+            missing_roles.push(QueueRole::Graphics);
+        }
+        if present.is_some() && selection.present.is_none() {
+            missing_roles.push(QueueRole::Present);
+        }
+        if self.compute.is_some() && selection.compute.is_none() {
+            missing_roles.push(QueueRole::Compute);
+        }
+        if self.transfer.is_some() && selection.transfer.is_none() {
+            missing_roles.push(QueueRole::Transfer);
+        }
+
+        let missing_extensions = self.extensions
+            .difference(&DeviceExtensions::supported_by_device(physical));
+        let missing_features = self.features.difference(physical.supported_features());
+
+        if !missing_roles.is_empty() || missing_extensions != DeviceExtensions::none() ||
+            missing_features != Features::none()
+        {
+            return Err(DeviceBuilderError::MissingRequirements {
+                           queue_roles: missing_roles,
+                           extensions: missing_extensions,
+                           features: missing_features,
+                       });
+        }
+
+        // One queue per distinct family, with the highest priority of the roles that use it.
+        let roles = [
+            (selection.graphics, self.graphics),
+            (selection.present, present.as_ref().map(|&(_, p)| p)),
+            (selection.compute, self.compute),
+            (selection.transfer, self.transfer),
+        ];
+        let mut queue_families: SmallVec<[(u32, f32); 4]> = SmallVec::new();
+        for &(family, priority) in roles.iter() {
+            let (family, priority) = match (family, priority) {
+                (Some(f), Some(p)) => (f, p),
+                _ => continue,
+            };
+
+            match queue_families.iter_mut().find(|&&mut (f, _)| f == family) {
+                Some(entry) => entry.1 = entry.1.max(priority),
+                None => queue_families.push((family, priority)),
+            }
+        }
+
+        let (device, queues) =
+            Device::new(physical,
+                        &self.features,
+                        &self.extensions,
+                        queue_families
+                            .iter()
+                            .map(|&(f, p)| (physical.queue_family_by_id(f).unwrap(), p)))?;
+        let queues: SmallVec<[Arc<Queue>; 4]> = queues.collect();
+
+        let queue_for = |family: Option<u32>| {
+            family.map(|family| {
+                           let index = queue_families
+                               .iter()
+                               .position(|&(f, _)| f == family)
+                               .unwrap();
+                           queues[index].clone()
+                       })
+        };
+
+        let output = DeviceQueues {
+            graphics: queue_for(self.graphics.and(selection.graphics)),
+            present: queue_for(present.as_ref().and(selection.present)),
+            compute: queue_for(self.compute.and(selection.compute)),
+            transfer: queue_for(self.transfer.and(selection.transfer)),
+        };
+
+        Ok((device, output))
+    }
+}
+
+/// Queues created by a `DeviceBuilder`, one for each requested role.
+///
+/// Roles that share the same queue family share the same queue. Use `Queue::is_same` to find out
+/// whether two roles use the same queue.
+#[derive(Debug, Clone)]
+pub struct DeviceQueues {
+    /// Queue for graphics operations, if requested.
+    pub graphics: Option<Arc<Queue>>,
+    /// Queue that can present to the surface, if requested.
+    pub present: Option<Arc<Queue>>,
+    /// Queue for compute operations, if requested.
+    pub compute: Option<Arc<Queue>>,
+    /// Queue for transfer operations, if requested.
+    pub transfer: Option<Arc<Queue>>,
+}
+
+/// Role of a queue requested from a `DeviceBuilder`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum QueueRole {
+    Graphics,
+    Present,
+    Compute,
+    Transfer,
+}
+
+/// Error that can happen when building a device with a `DeviceBuilder`.
+#[derive(Debug, Clone)]
+pub enum DeviceBuilderError {
+    /// The physical device doesn't meet some of the requirements.
+    MissingRequirements {
+        /// Roles for which no queue family could be found.
+        queue_roles: Vec<QueueRole>,
+        /// Requested extensions that aren't supported.
+        extensions: DeviceExtensions,
+        /// Requested features that aren't supported.
+        features: Features,
+    },
+    /// Checking whether the queue families can present to the surface failed.
+    CapabilitiesError(CapabilitiesError),
+    /// Creating the device failed.
+    DeviceCreationError(DeviceCreationError),
+}
+
+impl error::Error for DeviceBuilderError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            DeviceBuilderError::MissingRequirements { .. } => {
+                "the physical device doesn't meet some of the requirements"
+            },
+            DeviceBuilderError::CapabilitiesError(_) => {
+                "checking whether the queue families can present to the surface failed"
+            },
+            DeviceBuilderError::DeviceCreationError(_) => "creating the device failed",
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&dyn error::Error> {
+        match *self {
+            DeviceBuilderError::CapabilitiesError(ref err) => Some(err),
+            DeviceBuilderError::DeviceCreationError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for DeviceBuilderError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<CapabilitiesError> for DeviceBuilderError {
+    #[inline]
+    fn from(err: CapabilitiesError) -> DeviceBuilderError {
+        DeviceBuilderError::CapabilitiesError(err)
+    }
+}
+
+impl From<DeviceCreationError> for DeviceBuilderError {
+    #[inline]
+    fn from(err: DeviceCreationError) -> DeviceBuilderError {
+        DeviceBuilderError::DeviceCreationError(err)
+    }
+}
+
+// What a queue family supports, as far as the selection is concerned.
+#[derive(Debug, Copy, Clone)]
+struct FamilyInfo {
+    id: u32,
+    graphics: bool,
+    compute: bool,
+    // Explicit support for transfers.
+    transfer: bool,
+    present: bool,
+}
+
+// Families chosen for each role. `None` if the role wasn't requested or if no family is suitable.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Selection {
+    graphics: Option<u32>,
+    present: Option<u32>,
+    compute: Option<u32>,
+    transfer: Option<u32>,
+}
+
+// Implements the policy described in the documentation of `DeviceBuilder`.
+fn select_families(families: &[FamilyInfo], graphics: bool, present: bool, compute: bool,
+                   transfer: bool)
+                   -> Selection {
+    let find = |pred: &dyn Fn(&FamilyInfo) -> bool| families.iter().find(|f| pred(f)).map(|f| f.id);
+
+    // The graphics family is also chosen if only a present queue is requested, so that the
+    // present queue prefers the same family as the graphics queue.
+    let graphics_family = if present {
+        find(&|f| f.graphics && f.present).or_else(|| find(&|f| f.graphics))
+    } else {
+        find(&|f| f.graphics)
+    };
+
+    let present_family = if present {
+        match graphics_family {
+            Some(g) if families.iter().any(|f| f.id == g && f.present) => Some(g),
+            _ => find(&|f| f.present),
+        }
+    } else {
+        None
+    };
+
+    let compute_family = if compute {
+        find(&|f| f.compute && !f.graphics)
+            .or_else(|| find(&|f| f.compute && Some(f.id) != graphics_family))
+            .or_else(|| find(&|f| f.compute))
+    } else {
+        None
+    };
+
+    let transfer_family = if transfer {
+        let used = |f: &FamilyInfo| {
+            (graphics && Some(f.id) == graphics_family) || Some(f.id) == present_family ||
+                Some(f.id) == compute_family
+        };
+        let supports = |f: &FamilyInfo| f.transfer || f.graphics || f.compute;
+
+        find(&|f| f.transfer && !f.graphics && !f.compute)
+            .or_else(|| find(&|f| supports(f) && !used(f)))
+            .or(compute_family)
+            .or_else(|| find(&|f| supports(f)))
+    } else {
+        None
+    };
+
+    Selection {
+        graphics: if graphics { graphics_family } else { None },
+        present: present_family,
+        compute: compute_family,
+        transfer: transfer_family,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FamilyInfo;
+    use super::Selection;
+    use super::select_families;
+    use device::DeviceBuilder;
+    use device::DeviceBuilderError;
+    use device::DeviceExtensions;
+    use device::QueueRole;
+    use features::Features;
+    use instance;
+
+    fn family(id: u32, graphics: bool, compute: bool, transfer: bool, present: bool)
+              -> FamilyInfo {
+        FamilyInfo {
+            id,
+            graphics,
+            compute,
+            transfer,
+            present,
+        }
+    }
+
+    #[test]
+    fn dedicated_families() {
+        let families = [
+            family(0, true, true, true, false),
+            family(1, true, true, true, true),
+            family(2, false, true, true, false),
+            family(3, false, false, true, false),
+        ];
+
+        assert_eq!(select_families(&families, true, true, true, true),
+                   Selection {
+                       graphics: Some(1),
+                       present: Some(1),
+                       compute: Some(2),
+                       transfer: Some(3),
+                   });
+    }
+
+    #[test]
+    fn single_family() {
+        let families = [family(0, true, true, true, true)];
+
+        assert_eq!(select_families(&families, true, true, true, true),
+                   Selection {
+                       graphics: Some(0),
+                       present: Some(0),
+                       compute: Some(0),
+                       transfer: Some(0),
+                   });
+    }
+
+    #[test]
+    fn separate_present_family() {
+        let families = [family(0, true, true, true, false), family(1, false, false, false, true)];
+
+        assert_eq!(select_families(&families, true, true, false, false),
+                   Selection {
+                       graphics: Some(0),
+                       present: Some(1),
+                       compute: None,
+                       transfer: None,
+                   });
+    }
+
+    #[test]
+    fn transfer_falls_back_to_unused_family() {
+        let families = [family(0, true, true, true, false), family(1, false, true, false, false)];
+
+        assert_eq!(select_families(&families, true, false, false, true),
+                   Selection {
+                       graphics: Some(0),
+                       present: None,
+                       compute: None,
+                       transfer: Some(1),
+                   });
+    }
+
+    #[test]
+    fn missing_roles() {
+        let families = [family(0, false, false, true, false)];
+
+        assert_eq!(select_families(&families, true, false, true, true),
+                   Selection {
+                       graphics: None,
+                       present: None,
+                       compute: None,
+                       transfer: Some(0),
+                   });
+    }
+
+    #[test]
+    fn basic_build() {
+        let instance = instance!();
+        let physical = match instance::PhysicalDevice::enumerate(&instance).next() {
+            Some(p) => p,
+            None => return,
+        };
+        if !physical.queue_families().any(|f| f.supports_graphics()) {
+            return;
+        }
+
+        let (_, queues) = DeviceBuilder::new(physical)
+            .graphics(1.0)
+            .transfer(0.5)
+            .build()
+            .unwrap();
+        assert!(queues.graphics.is_some());
+        assert!(queues.transfer.is_some());
+        assert!(queues.compute.is_none());
+        assert!(queues.present.is_none());
+    }
+
+    #[test]
+    fn aggregated_errors() {
+        let instance = instance!();
+        let physical = match instance::PhysicalDevice::enumerate(&instance).next() {
+            Some(p) => p,
+            None => return,
+        };
+
+        let features = Features::all();
+        let extensions = DeviceExtensions::supported_by_device(physical);
+        // In the unlikely situation where the device supports everything, we ignore the test.
+        if physical.supported_features().superset_of(&features) {
+            return;
+        }
+
+        match DeviceBuilder::new(physical)
+            .features(&features)
+            .extensions(&extensions)
+            .graphics(1.0)
+            .build() {
+            Err(DeviceBuilderError::MissingRequirements {
+                    queue_roles,
+                    extensions,
+                    features,
+                }) => {
+                assert!(!queue_roles.contains(&QueueRole::Compute));
+                assert_eq!(extensions, DeviceExtensions::none());
+                assert!(features != Features::none());
+            },
+            _ => panic!(),
+        }
+    }
+}
@@ -16,17 +16,112 @@ use std::str;
 
 use VulkanObject;
 use check_errors;
+use device::DeviceCreationError;
+use instance::InstanceExtensions;
 use instance::PhysicalDevice;
 use extensions::SupportedExtensionsError;
+use version::Version;
 use vk;
 
 macro_rules! device_extensions {
-    ($sname:ident, $rawname:ident, $($ext:ident => $s:expr,)*) => (
+    ($sname:ident, $rawname:ident, $($ext:ident => $s:literal {
+        requires: [$($dep:ident),*],
+        requires_instance: [$($idep:ident),*],
+        promoted_to: $promoted:expr,
+    },)*) => (
         extensions! {
             $sname, $rawname,
             $( $ext => $s,)*
         }
 
+        impl $sname {
+            /// Returns the list of extensions whose functionality is part of the given version of
+            /// Vulkan.
+            pub fn promoted_to_core(api_version: Version) -> $sname {
+                let mut extensions = $sname::none();
+                $(
+                    let promoted: Option<Version> = $promoted;
+                    extensions.$ext = match promoted {
+                        Some(version) => version <= api_version,
+                        None => false,
+                    };
+                )*
+                extensions
+            }
+
+            /// Returns this list with the device extensions that its extensions depend on added,
+            /// except for those that are part of `api_version`.
+            ///
+            /// The instance extensions that are needed are returned by
+            /// `required_instance_extensions()`.
+            pub fn with_dependencies(&self, api_version: Version) -> $sname {
+                let core = $sname::promoted_to_core(api_version);
+                let mut extensions = *self;
+                loop {
+                    let mut next = extensions;
+                    $(
+                        if extensions.$ext {
+                            $(next.$dep |= !core.$dep;)*
+                        }
+                    )*
+                    if next == extensions {
+                        return extensions;
+                    }
+                    extensions = next;
+                }
+            }
+
+            /// Returns the device extensions that the extensions of this list depend on but that
+            /// aren't in this list nor part of `api_version`.
+            #[inline]
+            pub fn missing_dependencies(&self, api_version: Version) -> $sname {
+                self.with_dependencies(api_version).difference(self)
+            }
+
+            /// Returns the instance extensions that must be enabled on the instance in order to
+            /// enable the extensions of this list and their dependencies.
+            ///
+            /// The device extensions that are part of `device_api_version` and the instance
+            /// extensions that are part of `instance_api_version` are left out.
+            pub fn required_instance_extensions(&self, device_api_version: Version,
+                                                instance_api_version: Version)
+                                                -> InstanceExtensions {
+                let all = self.with_dependencies(device_api_version);
+                let mut required = InstanceExtensions::none();
+                $(
+                    if all.$ext {
+                        $(required.$idep = true;)*
+                    }
+                )*
+                required
+                    .with_dependencies(instance_api_version)
+                    .difference(&InstanceExtensions::promoted_to_core(instance_api_version))
+            }
+
+            /// Returns `MissingExtensionDependencies` if some of the extensions of this list
+            /// depend on device extensions that aren't in this list, or on instance extensions
+            /// that weren't enabled on the instance of `physical_device`.
+            ///
+            /// `Device::new` doesn't check the dependencies of the extensions it enables. Call
+            /// this function beforehand to get an error instead of relying on the implementation.
+            pub fn check_dependencies(&self, physical_device: PhysicalDevice)
+                                      -> Result<(), DeviceCreationError> {
+                let instance = physical_device.instance();
+                let device_api_version = physical_device.effective_api_version();
+                let missing = self.missing_dependencies(device_api_version);
+                let missing_instance = self
+                    .required_instance_extensions(device_api_version, instance.api_version())
+                    .difference(&instance.loaded_extensions());
+                if missing != $sname::none() || missing_instance != InstanceExtensions::none() {
+                    return Err(DeviceCreationError::MissingExtensionDependencies {
+                                   extensions: missing,
+                                   instance_extensions: missing_instance,
+                               });
+                }
+                Ok(())
+            }
+        }
+
         impl $rawname {
             /// See the docs of supported_by_device().
             pub fn supported_by_device_raw(physical_device: PhysicalDevice) -> Result<Self, SupportedExtensionsError> {
@@ -62,16 +157,22 @@ macro_rules! device_extensions {
             /// See the docs of supported_by_device().
             pub fn supported_by_device_raw(physical_device: PhysicalDevice) -> Result<Self, SupportedExtensionsError> {
                 let vk = physical_device.instance().pointers();
+                $sname::supported_by_device_handle(vk, physical_device.internal_object())
+            }
 
+            /// Same as `supported_by_device_raw()`, but takes the raw handle of the physical
+            /// device. Used while the `Instance` is still being built.
+            pub(crate) fn supported_by_device_handle(vk: &vk::InstancePointers, physical_device: vk::PhysicalDevice)
+                                                     -> Result<Self, SupportedExtensionsError> {
                 let properties: Vec<vk::ExtensionProperties> = unsafe {
                     let mut num = 0;
                     check_errors(vk.EnumerateDeviceExtensionProperties(
-                        physical_device.internal_object(), ptr::null(), &mut num, ptr::null_mut()
+                        physical_device, ptr::null(), &mut num, ptr::null_mut()
                     ))?;
 
                     let mut properties = Vec::with_capacity(num as usize);
                     check_errors(vk.EnumerateDeviceExtensionProperties(
-                        physical_device.internal_object(), ptr::null(), &mut num, properties.as_mut_ptr()
+                        physical_device, ptr::null(), &mut num, properties.as_mut_ptr()
                     ))?;
                     properties.set_len(num as usize);
                     properties
@@ -106,17 +207,136 @@ macro_rules! device_extensions {
 device_extensions! {
     DeviceExtensions,
     RawDeviceExtensions,
-    khr_swapchain => b"VK_KHR_swapchain",
-    khr_display_swapchain => b"VK_KHR_display_swapchain",
-    khr_sampler_mirror_clamp_to_edge => b"VK_KHR_sampler_mirror_clamp_to_edge",
-    khr_maintenance1 => b"VK_KHR_maintenance1",
-    khr_get_memory_requirements2 => b"VK_KHR_get_memory_requirements2",
-    khr_dedicated_allocation => b"VK_KHR_dedicated_allocation",
-    khr_incremental_present => b"VK_KHR_incremental_present",
-    khr_16bit_storage => b"VK_KHR_16bit_storage",
-    khr_storage_buffer_storage_class => b"VK_KHR_storage_buffer_storage_class",
-    ext_debug_utils => b"VK_EXT_debug_utils",
-    khr_multiview => b"VK_KHR_multiview",
+    khr_swapchain => b"VK_KHR_swapchain" {
+        requires: [],
+        requires_instance: [khr_surface],
+        promoted_to: None,
+    },
+    khr_display_swapchain => b"VK_KHR_display_swapchain" {
+        requires: [khr_swapchain],
+        requires_instance: [khr_display],
+        promoted_to: None,
+    },
+    khr_sampler_mirror_clamp_to_edge => b"VK_KHR_sampler_mirror_clamp_to_edge" {
+        requires: [],
+        requires_instance: [],
+        promoted_to: Some(Version::V1_2),
+    },
+    khr_maintenance1 => b"VK_KHR_maintenance1" {
+        requires: [],
+        requires_instance: [],
+        promoted_to: Some(Version::V1_1),
+    },
+    khr_get_memory_requirements2 => b"VK_KHR_get_memory_requirements2" {
+        requires: [],
+        requires_instance: [],
+        promoted_to: Some(Version::V1_1),
+    },
+    khr_dedicated_allocation => b"VK_KHR_dedicated_allocation" {
+        requires: [khr_get_memory_requirements2],
+        requires_instance: [],
+        promoted_to: Some(Version::V1_1),
+    },
+    khr_incremental_present => b"VK_KHR_incremental_present" {
+        requires: [khr_swapchain],
+        requires_instance: [],
+        promoted_to: None,
+    },
+    khr_16bit_storage => b"VK_KHR_16bit_storage" {
+        requires: [khr_storage_buffer_storage_class],
+        requires_instance: [khr_get_physical_device_properties2],
+        promoted_to: Some(Version::V1_1),
+    },
+    khr_storage_buffer_storage_class => b"VK_KHR_storage_buffer_storage_class" {
+        requires: [],
+        requires_instance: [],
+        promoted_to: Some(Version::V1_1),
+    },
+    ext_debug_utils => b"VK_EXT_debug_utils" {
+        requires: [],
+        requires_instance: [],
+        promoted_to: None,
+    },
+    khr_multiview => b"VK_KHR_multiview" {
+        requires: [],
+        requires_instance: [khr_get_physical_device_properties2],
+        promoted_to: Some(Version::V1_1),
+    },
+    khr_external_memory => b"VK_KHR_external_memory" {
+        requires: [],
+        requires_instance: [khr_external_memory_capabilities],
+        promoted_to: Some(Version::V1_1),
+    },
+    khr_external_memory_fd => b"VK_KHR_external_memory_fd" {
+        requires: [khr_external_memory],
+        requires_instance: [],
+        promoted_to: None,
+    },
+    ext_external_memory_dma_buf => b"VK_EXT_external_memory_dma_buf" {
+        requires: [khr_external_memory_fd],
+        requires_instance: [],
+        promoted_to: None,
+    },
+    khr_external_semaphore => b"VK_KHR_external_semaphore" {
+        requires: [],
+        requires_instance: [khr_external_semaphore_capabilities],
+        promoted_to: Some(Version::V1_1),
+    },
+    khr_external_semaphore_fd => b"VK_KHR_external_semaphore_fd" {
+        requires: [khr_external_semaphore],
+        requires_instance: [],
+        promoted_to: None,
+    },
+    khr_external_fence => b"VK_KHR_external_fence" {
+        requires: [],
+        requires_instance: [khr_external_fence_capabilities],
+        promoted_to: Some(Version::V1_1),
+    },
+    khr_external_fence_fd => b"VK_KHR_external_fence_fd" {
+        requires: [khr_external_fence],
+        requires_instance: [],
+        promoted_to: None,
+    },
+    khr_push_descriptor => b"VK_KHR_push_descriptor" {
+        requires: [],
+        requires_instance: [khr_get_physical_device_properties2],
+        promoted_to: None,
+    },
+    khr_maintenance3 => b"VK_KHR_maintenance3" {
+        requires: [],
+        requires_instance: [khr_get_physical_device_properties2],
+        promoted_to: Some(Version::V1_1),
+    },
+    ext_descriptor_indexing => b"VK_EXT_descriptor_indexing" {
+        requires: [khr_maintenance3],
+        requires_instance: [khr_get_physical_device_properties2],
+        promoted_to: Some(Version::V1_2),
+    },
+    khr_descriptor_update_template => b"VK_KHR_descriptor_update_template" {
+        requires: [],
+        requires_instance: [],
+        promoted_to: Some(Version::V1_1),
+    },
+    khr_bind_memory2 => b"VK_KHR_bind_memory2" {
+        requires: [],
+        requires_instance: [],
+        promoted_to: Some(Version::V1_1),
+    },
+    khr_timeline_semaphore => b"VK_KHR_timeline_semaphore" {
+        requires: [],
+        requires_instance: [khr_get_physical_device_properties2],
+        promoted_to: Some(Version::V1_2),
+    },
+    ext_scalar_block_layout => b"VK_EXT_scalar_block_layout" {
+        requires: [],
+        requires_instance: [khr_get_physical_device_properties2],
+        promoted_to: Some(Version::V1_2),
+    },
+    khr_driver_properties => b"VK_KHR_driver_properties" {
+        requires: [],
+        requires_instance: [khr_get_physical_device_properties2],
+        promoted_to: Some(Version::V1_2),
+    },
 }
 
 /// This helper type can only be instantiated inside this module.
@@ -127,11 +347,100 @@ pub struct Unbuildable(());
 
 #[cfg(test)]
 mod tests {
-    use device::{DeviceExtensions, RawDeviceExtensions};
+    use device::{DeviceCreationError, DeviceExtensions, RawDeviceExtensions};
+    use instance::InstanceExtensions;
+    use version::Version;
 
     #[test]
     fn empty_extensions() {
         let d: RawDeviceExtensions = (&DeviceExtensions::none()).into();
         assert!(d.iter().next().is_none());
     }
+
+    #[test]
+    fn transitive_dependencies() {
+        let extensions = DeviceExtensions {
+            ext_external_memory_dma_buf: true,
+            .. DeviceExtensions::none()
+        };
+
+        assert_eq!(extensions.missing_dependencies(Version::V1_0),
+                   DeviceExtensions {
+                       khr_external_memory_fd: true,
+                       khr_external_memory: true,
+                       .. DeviceExtensions::none()
+                   });
+        assert_eq!(extensions.required_instance_extensions(Version::V1_0, Version::V1_0),
+                   InstanceExtensions {
+                       khr_external_memory_capabilities: true,
+                       khr_get_physical_device_properties2: true,
+                       .. InstanceExtensions::none()
+                   });
+    }
+
+    #[test]
+    fn dependencies_promoted_to_core() {
+        let extensions = DeviceExtensions {
+            ext_external_memory_dma_buf: true,
+            khr_dedicated_allocation: true,
+            .. DeviceExtensions::none()
+        };
+
+        assert_eq!(extensions.missing_dependencies(Version::V1_1),
+                   DeviceExtensions { khr_external_memory_fd: true, .. DeviceExtensions::none() });
+        assert_eq!(extensions.required_instance_extensions(Version::V1_1, Version::V1_1),
+                   InstanceExtensions::none());
+    }
+
+    #[test]
+    fn swapchain_requires_surface() {
+        let extensions = DeviceExtensions { khr_swapchain: true, .. DeviceExtensions::none() };
+        assert_eq!(extensions.missing_dependencies(Version::V1_2), DeviceExtensions::none());
+        assert_eq!(extensions.required_instance_extensions(Version::V1_2, Version::V1_2),
+                   InstanceExtensions { khr_surface: true, .. InstanceExtensions::none() });
+    }
+
+    #[test]
+    fn check_dependencies() {
+        let (device, _) = mock_dev_and_queue!();
+        let physical = device.physical_device();
+
+        let extensions = DeviceExtensions {
+            khr_swapchain: true,
+            khr_dedicated_allocation: true,
+            .. DeviceExtensions::none()
+        };
+        match extensions.check_dependencies(physical) {
+            Err(DeviceCreationError::MissingExtensionDependencies {
+                    extensions,
+                    instance_extensions,
+                }) => {
+                assert_eq!(extensions,
+                           DeviceExtensions {
+                               khr_get_memory_requirements2: true,
+                               .. DeviceExtensions::none()
+                           });
+                assert!(instance_extensions.khr_surface);
+            },
+            _ => panic!(),
+        }
+
+        assert!(DeviceExtensions::none().check_dependencies(physical).is_ok());
+    }
+
+    #[test]
+    fn instance_extensions_use_instance_version() {
+        let extensions = DeviceExtensions {
+            khr_external_memory_fd: true,
+            .. DeviceExtensions::none()
+        };
+
+        // A Vulkan 1.1 instance includes the capabilities extensions, even if the device is 1.0.
+        assert!(!extensions
+                     .required_instance_extensions(Version::V1_0, Version::V1_1)
+                     .khr_external_memory_capabilities);
+        assert!(extensions
+                    .required_instance_extensions(Version::V1_0, Version::V1_0)
+                    .khr_external_memory_capabilities);
+    }
 }
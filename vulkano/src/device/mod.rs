@@ -64,6 +64,13 @@
 //! can query what is supported with respectively `PhysicalDevice::supported_features` and
 //! `DeviceExtensions::supported_by_device`.
 //!
+//! Some extensions depend on other device or instance extensions, unless the API version of the
+//! device already includes them. `DeviceExtensions::with_dependencies` returns a list with these
+//! dependencies added, and `DeviceExtensions::required_instance_extensions` tells which
+//! extensions must be enabled on the instance. `Device::new` doesn't check them, but
+//! `DeviceExtensions::check_dependencies` can be called beforehand to get an error that lists the
+//! missing ones.
+//!
 //! > **Note**: The fact that you need to manually enable features at initialization also means
 //! > that you don't need to worry about a capability not being supported later on in your code.
 //!
@@ -85,6 +92,10 @@
 //!
 //! The `Device::new` function returns the newly-created device, but also the list of queues.
 //!
+//! Alternatively, the `DeviceBuilder` chooses the queue families from the roles that the queues
+//! are going to play (graphics, presenting to a surface, async compute and transfers), and
+//! returns the queues by role.
+//!
 //! # Extended example
 //!
 //! TODO: write
@@ -104,14 +115,18 @@ use std::ptr;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::sync::MutexGuard;
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering;
 use std::sync::Weak;
 use std::ffi::CStr;
 
 use command_buffer::pool::StandardCommandPool;
 use descriptor::descriptor_set::StdDescriptorPool;
 use instance::Instance;
+use instance::InstanceExtensions;
 use instance::PhysicalDevice;
 use instance::QueueFamily;
+use instance::loader;
 use memory::pool::StdMemoryPool;
 
 use Error;
@@ -120,11 +135,17 @@ use SynchronizedVulkanObject;
 use VulkanObject;
 use VulkanHandle;
 use check_errors;
+use version::Version;
 use vk;
 
+pub use self::builder::DeviceBuilder;
+pub use self::builder::DeviceBuilderError;
+pub use self::builder::DeviceQueues;
+pub use self::builder::QueueRole;
 pub use self::extensions::DeviceExtensions;
 pub use self::extensions::RawDeviceExtensions;
 pub use ::features::Features;
+mod builder;
 mod extensions;
 
 /// Represents a Vulkan context.
@@ -137,6 +158,7 @@ pub struct Device {
     standard_descriptor_pool: Mutex<Weak<StdDescriptorPool>>,
     standard_command_pools:
         Mutex<HashMap<u32, Weak<StandardCommandPool>, BuildHasherDefault<FnvHasher>>>,
+    api_version: Version,
     features: Features,
     extensions: DeviceExtensions,
     active_queue_families: SmallVec<[u32; 8]>,
@@ -168,6 +190,10 @@ impl Device {
     ///   queue with a lower value. Note however that no guarantee can be made on the way the
     ///   priority value is handled by the implementation.
     ///
+    /// The dependencies of the extensions aren't checked. Use
+    /// `DeviceExtensions::with_dependencies` to add the missing device extensions, or
+    /// `DeviceExtensions::check_dependencies` to get a `MissingExtensionDependencies` error.
+    ///
     /// # Panic
     ///
     /// - Panics if one of the queue families doesn't belong to the given device.
@@ -187,6 +213,7 @@ impl Device {
         }
 
         let vk_i = phys.instance().pointers();
+        let api_version = phys.effective_api_version();
 
         // this variable will contain the queue family ID and queue ID of each requested queue
         let mut output_queues: SmallVec<[(u32, u32); 8]> = SmallVec::new();
@@ -269,15 +296,22 @@ impl Device {
             //
             //       Note that if we ever remove this, don't forget to adjust the change in
             //       `Device`'s construction below.
+            //
+            //       The features of the device extensions are enabled by chaining their structs to
+            //       `pNext`, which is allowed as soon as the extensions themselves are enabled.
             let features = {
-                let mut features = requested_features.clone().into_vulkan_features();
-                features.robustBufferAccess = vk::TRUE;
-                features
+                let features = Features {
+                    robust_buffer_access: true,
+                    ..requested_features.clone()
+                };
+                let enabled_extensions = DeviceExtensions::from(&extensions);
+                features.into_vulkan_features(api_version, &enabled_extensions)
+                    .map_err(|name| DeviceCreationError::FeatureRequiresExtension { name })?
             };
 
             let infos = vk::DeviceCreateInfo {
                 sType: vk::STRUCTURE_TYPE_DEVICE_CREATE_INFO,
-                pNext: ptr::null(),
+                pNext: features.main.pNext,
                 flags: 0, // reserved
                 queueCreateInfoCount: queues.len() as u32,
                 pQueueCreateInfos: queues.as_ptr(),
@@ -285,7 +319,7 @@ impl Device {
                 ppEnabledLayerNames: layers_ptr.as_ptr(),
                 enabledExtensionCount: extensions_list.len() as u32,
                 ppEnabledExtensionNames: extensions_list.as_ptr(),
-                pEnabledFeatures: &features,
+                pEnabledFeatures: &features.main.features,
             };
 
             let mut output = MaybeUninit::uninit();
@@ -296,11 +330,12 @@ impl Device {
             output.assume_init()
         };
 
-        // loading the function pointers of the newly-created device
+        // loading the function pointers of the newly-created device, using the core version of
+        // the functions when the device supports them
         let vk = vk::DevicePointers::load(|name| unsafe {
-                                              vk_i.GetDeviceProcAddr(device, name.as_ptr()) as
-                                                  *const _
-                                          });
+            let name = loader::core_function_name(name, api_version).unwrap_or(name);
+            vk_i.GetDeviceProcAddr(device, name.as_ptr()) as *const _
+        });
 
         let mut active_queue_families: SmallVec<[u32; 8]> = SmallVec::new();
         for (queue_family, _) in output_queues.iter() {
@@ -318,6 +353,7 @@ impl Device {
                          standard_pool: Mutex::new(Weak::new()),
                          standard_descriptor_pool: Mutex::new(Weak::new()),
                          standard_command_pools: Mutex::new(Default::default()),
+                         api_version,
                          features: Features {
                              // Always enabled ; see above
                              robust_buffer_access: true,
@@ -394,6 +430,16 @@ impl Device {
         &self.features
     }
 
+    /// Returns the version of Vulkan that can be used with this device.
+    ///
+    /// This is the same as `PhysicalDevice::effective_api_version()`. Functionality that was
+    /// promoted to core Vulkan can be used if this version is high enough, even if the
+    /// corresponding extension isn't enabled.
+    #[inline]
+    pub fn api_version(&self) -> Version {
+        self.api_version
+    }
+
     /// Returns the list of extensions that have been loaded.
     #[inline]
     pub fn loaded_extensions(&self) -> &DeviceExtensions {
@@ -481,6 +527,8 @@ impl Device {
 
     /// Assigns a human-readable name to `object` for debugging purposes.
     ///
+    /// Does nothing if the `VK_EXT_debug_utils` extension isn't enabled on the instance.
+    ///
     /// # Panics
     /// * If `object` is not owned by this device.
     pub fn set_object_name<T: VulkanObject + DeviceOwned>(&self, object: &T, name: &CStr) -> Result<(), OomError> {
@@ -493,6 +541,10 @@ impl Device {
     /// # Safety
     /// `object` must be a Vulkan handle owned by this device, and its type must be accurately described by `ty`.
     pub unsafe fn set_object_name_raw(&self, ty: vk::ObjectType, object: u64, name: &CStr) -> Result<(), OomError> {
+        if !self.instance.loaded_extensions().ext_debug_utils {
+            return Ok(());
+        }
+
         let info = vk::DebugUtilsObjectNameInfoEXT {
             sType: vk::STRUCTURE_TYPE_DEBUG_UTILS_OBJECT_NAME_INFO_EXT,
             pNext: ptr::null(),
@@ -615,6 +667,7 @@ impl Iterator for QueuesIter {
                               device: self.device.clone(),
                               family: family,
                               id: id,
+                              label_depth: AtomicU32::new(0),
                           }))
         }
     }
@@ -643,6 +696,24 @@ pub enum DeviceCreationError {
     FeatureNotPresent,
     /// Some of the requested device extensions are not supported by the physical device.
     ExtensionNotPresent,
+    /// Some of the requested features belong to a device extension that wasn't enabled, and that
+    /// isn't part of the API version of the device.
+    FeatureRequiresExtension {
+        /// Name of the extension to enable.
+        name: &'static str,
+    },
+    /// Some of the requested device extensions depend on extensions that weren't enabled, and
+    /// that aren't part of the API version of the device.
+    ///
+    /// Returned by `DeviceExtensions::check_dependencies`. Use
+    /// `DeviceExtensions::with_dependencies` and
+    /// `DeviceExtensions::required_instance_extensions` to find out which extensions to enable.
+    MissingExtensionDependencies {
+        /// Device extensions that must be enabled as well.
+        extensions: DeviceExtensions,
+        /// Instance extensions that must have been enabled when creating the instance.
+        instance_extensions: InstanceExtensions,
+    },
     /// Tried to create too many queues for a given family.
     TooManyQueuesForFamily,
     /// The priority of one of the queues is out of the [0.0; 1.0] range.
@@ -679,6 +750,12 @@ impl error::Error for DeviceCreationError {
             DeviceCreationError::ExtensionNotPresent => {
                 "some of the requested device extensions are not supported by the physical device"
             },
+            DeviceCreationError::FeatureRequiresExtension { .. } => {
+                "some of the requested features belong to a device extension that wasn't enabled"
+            },
+            DeviceCreationError::MissingExtensionDependencies { .. } => {
+                "some of the requested device extensions depend on extensions that weren't enabled"
+            },
             DeviceCreationError::TooManyObjects => {
                 "you have reached the limit to the number of devices that can be created from the
                  same physical device"
@@ -718,6 +795,9 @@ pub struct Queue {
     device: Arc<Device>,
     family: u32,
     id: u32, // id within family
+    // Number of debug labels that have been begun and not yet ended. Only modified while
+    // `queue` is locked.
+    label_depth: AtomicU32,
 }
 
 impl Queue {
@@ -761,6 +841,87 @@ impl Queue {
             Ok(())
         }
     }
+
+    /// Opens a debug label region on this queue, which tools such as debuggers and validation
+    /// layers use to group the work submitted afterwards.
+    ///
+    /// Does nothing if the `VK_EXT_debug_utils` extension isn't enabled on the instance.
+    pub fn begin_label(&self, name: &CStr, color: [f32; 4]) {
+        let queue = self.queue.lock().unwrap();
+        if self.debug_utils_enabled() {
+            unsafe {
+                let info = debug_utils_label(name, color);
+                self.device.pointers().QueueBeginDebugUtilsLabelEXT(*queue, &info);
+            }
+        }
+        self.label_depth.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Closes the debug label region opened by the last call to `begin_label`.
+    pub fn end_label(&self) -> Result<(), DebugLabelError> {
+        let queue = self.queue.lock().unwrap();
+        if self.label_depth.load(Ordering::Relaxed) == 0 {
+            return Err(DebugLabelError::NoLabelToEnd);
+        }
+        if self.debug_utils_enabled() {
+            unsafe {
+                self.device.pointers().QueueEndDebugUtilsLabelEXT(*queue);
+            }
+        }
+        self.label_depth.fetch_sub(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Inserts a single debug label on this queue.
+    ///
+    /// Does nothing if the `VK_EXT_debug_utils` extension isn't enabled on the instance.
+    pub fn insert_label(&self, name: &CStr, color: [f32; 4]) {
+        let queue = self.queue.lock().unwrap();
+        if self.debug_utils_enabled() {
+            unsafe {
+                let info = debug_utils_label(name, color);
+                self.device.pointers().QueueInsertDebugUtilsLabelEXT(*queue, &info);
+            }
+        }
+    }
+
+    #[inline]
+    fn debug_utils_enabled(&self) -> bool {
+        self.device.instance().loaded_extensions().ext_debug_utils
+    }
+}
+
+#[inline]
+fn debug_utils_label(name: &CStr, color: [f32; 4]) -> vk::DebugUtilsLabelEXT {
+    vk::DebugUtilsLabelEXT {
+        sType: vk::STRUCTURE_TYPE_DEBUG_UTILS_LABEL_EXT,
+        pNext: ptr::null(),
+        pLabelName: name.as_ptr(),
+        color,
+    }
+}
+
+/// Error that can happen when ending a debug label on a queue.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DebugLabelError {
+    /// There is no label to end on the queue.
+    NoLabelToEnd,
+}
+
+impl error::Error for DebugLabelError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            DebugLabelError::NoLabelToEnd => "there is no label to end on the queue",
+        }
+    }
+}
+
+impl fmt::Display for DebugLabelError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
 }
 
 impl PartialEq for Queue {
@@ -788,11 +949,17 @@ unsafe impl SynchronizedVulkanObject for Queue {
 
 #[cfg(test)]
 mod tests {
+    use buffer::BufferAccess;
+    use buffer::BufferUsage;
+    use buffer::CpuAccessibleBuffer;
+    use device::DebugLabelError;
     use device::Device;
     use device::DeviceCreationError;
     use device::DeviceExtensions;
     use features::Features;
     use instance;
+    use mock;
+    use std::ffi::CString;
     use std::sync::Arc;
 
     #[test]
@@ -872,4 +1039,63 @@ mod tests {
             _ => panic!(),
         };
     }
+
+    #[test]
+    fn queue_labels() {
+        let (device, queue) = mock_dev_and_queue!(instance_extensions: [ext_debug_utils],
+                                                  extensions: []);
+        let recorder = mock::Recorder::of(device.instance()).unwrap();
+        let name = CString::new("frame").unwrap();
+
+        assert_eq!(queue.end_label(), Err(DebugLabelError::NoLabelToEnd));
+        queue.begin_label(&name, [1.0, 0.0, 0.0, 1.0]);
+        queue.insert_label(&name, [0.0, 1.0, 0.0, 1.0]);
+        assert_eq!(queue.end_label(), Ok(()));
+        assert_eq!(queue.end_label(), Err(DebugLabelError::NoLabelToEnd));
+
+        let labels = recorder.calls()
+            .into_iter()
+            .filter(|call| call.ends_with("DebugUtilsLabelEXT"))
+            .collect::<Vec<_>>();
+        assert_eq!(labels,
+                   vec!["vkQueueBeginDebugUtilsLabelEXT", "vkQueueInsertDebugUtilsLabelEXT",
+                        "vkQueueEndDebugUtilsLabelEXT"]);
+        assert!(recorder.errors().is_empty());
+    }
+
+    #[test]
+    fn queue_labels_without_debug_utils() {
+        let (device, queue) = mock_dev_and_queue!();
+        let recorder = mock::Recorder::of(device.instance()).unwrap();
+        let name = CString::new("frame").unwrap();
+
+        queue.begin_label(&name, [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(queue.end_label(), Ok(()));
+        assert!(!recorder.calls().iter().any(|call| call.ends_with("DebugUtilsLabelEXT")));
+    }
+
+    #[test]
+    fn set_name() {
+        let (device, _) = mock_dev_and_queue!(instance_extensions: [ext_debug_utils],
+                                              extensions: []);
+        let recorder = mock::Recorder::of(device.instance()).unwrap();
+        let buffer = CpuAccessibleBuffer::from_data(device.clone(), BufferUsage::all(), false, 0u32)
+            .unwrap();
+
+        buffer.set_name(&CString::new("buffer").unwrap()).unwrap();
+        assert!(recorder.calls().contains(&"vkSetDebugUtilsObjectNameEXT"));
+        assert!(recorder.errors().is_empty());
+    }
+
+    #[test]
+    fn set_name_without_debug_utils() {
+        let (device, _) = mock_dev_and_queue!();
+        let recorder = mock::Recorder::of(device.instance()).unwrap();
+        let buffer = CpuAccessibleBuffer::from_data(device.clone(), BufferUsage::all(), false, 0u32)
+            .unwrap();
+
+        assert!(!device.instance().loaded_extensions().ext_debug_utils);
+        buffer.set_name(&CString::new("buffer").unwrap()).unwrap();
+        assert!(!recorder.calls().contains(&"vkSetDebugUtilsObjectNameEXT"));
+    }
 }
@@ -7,16 +7,39 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+use std::mem;
+use std::os::raw::c_void;
+use std::ptr;
+
+use device::DeviceExtensions;
+use version::Version;
 use vk;
 
 macro_rules! features {
-    ($($name:ident => $vk:ident,)+) => (
+    {
+        core { $($name:ident => $vk:ident,)+ },
+        extensions {
+            $(
+                $ext:ident [$ext_name:expr, $ext_version:ident] => $ext_ty:ident($ext_stype:ident) {
+                    $($ext_feature:ident => $ext_vk:ident,)+
+                },
+            )*
+        },
+    } => (
         /// Represents all the features that are available on a physical device or enabled on
         /// a logical device.
         ///
         /// Note that the `robust_buffer_access` is guaranteed to be supported by all Vulkan
         /// implementations.
         ///
+        /// Besides the core Vulkan 1.0 features, this struct also contains the features that are
+        /// introduced by device extensions, such as `multiview` or `timeline_semaphore`. These
+        /// can only be reported as supported if the physical device supports the corresponding
+        /// extension (or the Vulkan version that promoted it) and if the instance was created
+        /// with `khr_get_physical_device_properties2` or for Vulkan 1.1. Enabling one of them
+        /// requires enabling the corresponding device extension as well, unless the device's
+        /// API version already includes it.
+        ///
         /// # Example
         ///
         /// ```
@@ -42,11 +65,15 @@ macro_rules! features {
         /// ```
         ///
         #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
         #[allow(missing_docs)]
         pub struct Features {
             $(
                 pub $name: bool,
             )+
+            $($(
+                pub $ext_feature: bool,
+            )+)*
         }
 
         impl Features {
@@ -56,6 +83,9 @@ macro_rules! features {
                     $(
                         $name: false,
                     )+
+                    $($(
+                        $ext_feature: false,
+                    )+)*
                 }
             }
 
@@ -68,6 +98,9 @@ macro_rules! features {
                     $(
                         $name: true,
                     )+
+                    $($(
+                        $ext_feature: true,
+                    )+)*
                 }
             }
 
@@ -77,6 +110,7 @@ macro_rules! features {
             /// in self is true as well.
             pub fn superset_of(&self, other: &Features) -> bool {
                 $((self.$name == true || other.$name == false))&&+
+                $($(&& (self.$ext_feature == true || other.$ext_feature == false))+)*
             }
 
             /// Builds a `Features` that is the intersection of `self` and another `Features`
@@ -88,6 +122,9 @@ macro_rules! features {
                     $(
                         $name: self.$name && other.$name,
                     )+
+                    $($(
+                        $ext_feature: self.$ext_feature && other.$ext_feature,
+                    )+)*
                 }
             }
 
@@ -99,22 +136,79 @@ macro_rules! features {
                     $(
                         $name: self.$name && !other.$name,
                     )+
+                    $($(
+                        $ext_feature: self.$ext_feature && !other.$ext_feature,
+                    )+)*
                 }
             }
 
-            pub(crate) fn from_vulkan_features(features: vk::PhysicalDeviceFeatures) -> Features {
+            pub(crate) fn from_vulkan_features(features: &FeaturesFfi) -> Features {
                 Features {
                     $(
-                        $name: features.$vk != 0,
+                        $name: features.main.features.$vk != 0,
                     )+
+                    $($(
+                        $ext_feature: features.$ext.$ext_vk != 0,
+                    )+)*
                 }
             }
 
-            pub(crate) fn into_vulkan_features(self) -> vk::PhysicalDeviceFeatures {
-                vk::PhysicalDeviceFeatures {
+            /// Builds the chain of structs to pass to `vkCreateDevice`.
+            ///
+            /// Returns the name of the extension to enable if one of the features can't be
+            /// enabled with this API version and list of extensions.
+            pub(crate) fn into_vulkan_features(&self, api_version: Version,
+                                               extensions: &DeviceExtensions)
+                                               -> Result<Box<FeaturesFfi>, &'static str> {
+                let mut output = FeaturesFfi::new(api_version, extensions);
+                $(
+                    output.main.features.$vk = if self.$name { vk::TRUE } else { vk::FALSE };
+                )+
+                $(
+                    if $(self.$ext_feature)||+ {
+                        if !(api_version >= Version::$ext_version || extensions.$ext) {
+                            return Err($ext_name);
+                        }
+                        $(
+                            output.$ext.$ext_vk = if self.$ext_feature { vk::TRUE } else { vk::FALSE };
+                        )+
+                    }
+                )*
+                Ok(output)
+            }
+        }
+
+        /// The `VkPhysicalDeviceFeatures2` struct followed by the feature structs of the device
+        /// extensions.
+        ///
+        /// The structs of the extensions that are neither supported nor part of the API version
+        /// are left out of the `pNext` chain and stay zeroed. Always boxed so that the chain stays
+        /// valid.
+        pub(crate) struct FeaturesFfi {
+            pub(crate) main: vk::PhysicalDeviceFeatures2KHR,
+            $(
+                $ext: vk::$ext_ty,
+            )*
+        }
+
+        impl FeaturesFfi {
+            pub(crate) fn new(api_version: Version, extensions: &DeviceExtensions)
+                              -> Box<FeaturesFfi> {
+                unsafe {
+                    let mut output: Box<FeaturesFfi> = Box::new(mem::zeroed());
+                    output.main.sType = vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_FEATURES_2_KHR;
+
+                    let mut next: *const c_void = ptr::null();
                     $(
-                        $vk: if self.$name { vk::TRUE } else { vk::FALSE },
-                    )+
+                        output.$ext.sType = vk::$ext_stype;
+                        if api_version >= Version::$ext_version || extensions.$ext {
+                            output.$ext.pNext = next as *mut _;
+                            next = &output.$ext as *const _ as *const c_void;
+                        }
+                    )*
+                    output.main.pNext = next;
+
+                    output
                 }
             }
         }
@@ -122,59 +216,150 @@ macro_rules! features {
 }
 
 features!{
-    robust_buffer_access => robustBufferAccess,
-    full_draw_index_uint32 => fullDrawIndexUint32,
-    image_cube_array => imageCubeArray,
-    independent_blend => independentBlend,
-    geometry_shader => geometryShader,
-    tessellation_shader => tessellationShader,
-    sample_rate_shading => sampleRateShading,
-    dual_src_blend => dualSrcBlend,
-    logic_op => logicOp,
-    multi_draw_indirect => multiDrawIndirect,
-    draw_indirect_first_instance => drawIndirectFirstInstance,
-    depth_clamp => depthClamp,
-    depth_bias_clamp => depthBiasClamp,
-    fill_mode_non_solid => fillModeNonSolid,
-    depth_bounds => depthBounds,
-    wide_lines => wideLines,
-    large_points => largePoints,
-    alpha_to_one => alphaToOne,
-    multi_viewport => multiViewport,
-    sampler_anisotropy => samplerAnisotropy,
-    texture_compression_etc2 => textureCompressionETC2,
-    texture_compression_astc_ldr => textureCompressionASTC_LDR,
-    texture_compression_bc => textureCompressionBC,
-    occlusion_query_precise => occlusionQueryPrecise,
-    pipeline_statistics_query => pipelineStatisticsQuery,
-    vertex_pipeline_stores_and_atomics => vertexPipelineStoresAndAtomics,
-    fragment_stores_and_atomics => fragmentStoresAndAtomics,
-    shader_tessellation_and_geometry_point_size => shaderTessellationAndGeometryPointSize,
-    shader_image_gather_extended => shaderImageGatherExtended,
-    shader_storage_image_extended_formats => shaderStorageImageExtendedFormats,
-    shader_storage_image_multisample => shaderStorageImageMultisample,
-    shader_storage_image_read_without_format => shaderStorageImageReadWithoutFormat,
-    shader_storage_image_write_without_format => shaderStorageImageWriteWithoutFormat,
-    shader_uniform_buffer_array_dynamic_indexing => shaderUniformBufferArrayDynamicIndexing,
-    shader_sampled_image_array_dynamic_indexing => shaderSampledImageArrayDynamicIndexing,
-    shader_storage_buffer_array_dynamic_indexing => shaderStorageBufferArrayDynamicIndexing,
-    shader_storage_image_array_dynamic_indexing => shaderStorageImageArrayDynamicIndexing,
-    shader_clip_distance => shaderClipDistance,
-    shader_cull_distance => shaderCullDistance,
-    shader_f3264 => shaderf3264,
-    shader_int64 => shaderInt64,
-    shader_int16 => shaderInt16,
-    shader_resource_residency => shaderResourceResidency,
-    shader_resource_min_lod => shaderResourceMinLod,
-    sparse_binding => sparseBinding,
-    sparse_residency_buffer => sparseResidencyBuffer,
-    sparse_residency_image2d => sparseResidencyImage2D,
-    sparse_residency_image3d => sparseResidencyImage3D,
-    sparse_residency2_samples => sparseResidency2Samples,
-    sparse_residency4_samples => sparseResidency4Samples,
-    sparse_residency8_samples => sparseResidency8Samples,
-    sparse_residency16_samples => sparseResidency16Samples,
-    sparse_residency_aliased => sparseResidencyAliased,
-    variable_multisample_rate => variableMultisampleRate,
-    inherited_queries => inheritedQueries,
+    core {
+        robust_buffer_access => robustBufferAccess,
+        full_draw_index_uint32 => fullDrawIndexUint32,
+        image_cube_array => imageCubeArray,
+        independent_blend => independentBlend,
+        geometry_shader => geometryShader,
+        tessellation_shader => tessellationShader,
+        sample_rate_shading => sampleRateShading,
+        dual_src_blend => dualSrcBlend,
+        logic_op => logicOp,
+        multi_draw_indirect => multiDrawIndirect,
+        draw_indirect_first_instance => drawIndirectFirstInstance,
+        depth_clamp => depthClamp,
+        depth_bias_clamp => depthBiasClamp,
+        fill_mode_non_solid => fillModeNonSolid,
+        depth_bounds => depthBounds,
+        wide_lines => wideLines,
+        large_points => largePoints,
+        alpha_to_one => alphaToOne,
+        multi_viewport => multiViewport,
+        sampler_anisotropy => samplerAnisotropy,
+        texture_compression_etc2 => textureCompressionETC2,
+        texture_compression_astc_ldr => textureCompressionASTC_LDR,
+        texture_compression_bc => textureCompressionBC,
+        occlusion_query_precise => occlusionQueryPrecise,
+        pipeline_statistics_query => pipelineStatisticsQuery,
+        vertex_pipeline_stores_and_atomics => vertexPipelineStoresAndAtomics,
+        fragment_stores_and_atomics => fragmentStoresAndAtomics,
+        shader_tessellation_and_geometry_point_size => shaderTessellationAndGeometryPointSize,
+        shader_image_gather_extended => shaderImageGatherExtended,
+        shader_storage_image_extended_formats => shaderStorageImageExtendedFormats,
+        shader_storage_image_multisample => shaderStorageImageMultisample,
+        shader_storage_image_read_without_format => shaderStorageImageReadWithoutFormat,
+        shader_storage_image_write_without_format => shaderStorageImageWriteWithoutFormat,
+        shader_uniform_buffer_array_dynamic_indexing => shaderUniformBufferArrayDynamicIndexing,
+        shader_sampled_image_array_dynamic_indexing => shaderSampledImageArrayDynamicIndexing,
+        shader_storage_buffer_array_dynamic_indexing => shaderStorageBufferArrayDynamicIndexing,
+        shader_storage_image_array_dynamic_indexing => shaderStorageImageArrayDynamicIndexing,
+        shader_clip_distance => shaderClipDistance,
+        shader_cull_distance => shaderCullDistance,
+        shader_f3264 => shaderf3264,
+        shader_int64 => shaderInt64,
+        shader_int16 => shaderInt16,
+        shader_resource_residency => shaderResourceResidency,
+        shader_resource_min_lod => shaderResourceMinLod,
+        sparse_binding => sparseBinding,
+        sparse_residency_buffer => sparseResidencyBuffer,
+        sparse_residency_image2d => sparseResidencyImage2D,
+        sparse_residency_image3d => sparseResidencyImage3D,
+        sparse_residency2_samples => sparseResidency2Samples,
+        sparse_residency4_samples => sparseResidency4Samples,
+        sparse_residency8_samples => sparseResidency8Samples,
+        sparse_residency16_samples => sparseResidency16Samples,
+        sparse_residency_aliased => sparseResidencyAliased,
+        variable_multisample_rate => variableMultisampleRate,
+        inherited_queries => inheritedQueries,
+    },
+    extensions {
+        khr_16bit_storage ["VK_KHR_16bit_storage", V1_1] => PhysicalDevice16BitStorageFeaturesKHR(STRUCTURE_TYPE_PHYSICAL_DEVICE_16BIT_STORAGE_FEATURES_KHR) {
+            storage_buffer_16bit_access => storageBuffer16BitAccess,
+            uniform_and_storage_buffer_16bit_access => uniformAndStorageBuffer16BitAccess,
+            storage_push_constant16 => storagePushConstant16,
+            storage_input_output16 => storageInputOutput16,
+        },
+        khr_multiview ["VK_KHR_multiview", V1_1] => PhysicalDeviceMultiviewFeaturesKHR(STRUCTURE_TYPE_PHYSICAL_DEVICE_MULTIVIEW_FEATURES_KHR) {
+            multiview => multiview,
+            multiview_geometry_shader => multiviewGeometryShader,
+            multiview_tessellation_shader => multiviewTessellationShader,
+        },
+        ext_descriptor_indexing ["VK_EXT_descriptor_indexing", V1_2] => PhysicalDeviceDescriptorIndexingFeaturesEXT(STRUCTURE_TYPE_PHYSICAL_DEVICE_DESCRIPTOR_INDEXING_FEATURES_EXT) {
+            shader_input_attachment_array_dynamic_indexing => shaderInputAttachmentArrayDynamicIndexing,
+            shader_uniform_texel_buffer_array_dynamic_indexing => shaderUniformTexelBufferArrayDynamicIndexing,
+            shader_storage_texel_buffer_array_dynamic_indexing => shaderStorageTexelBufferArrayDynamicIndexing,
+            shader_uniform_buffer_array_non_uniform_indexing => shaderUniformBufferArrayNonUniformIndexing,
+            shader_sampled_image_array_non_uniform_indexing => shaderSampledImageArrayNonUniformIndexing,
+            shader_storage_buffer_array_non_uniform_indexing => shaderStorageBufferArrayNonUniformIndexing,
+            shader_storage_image_array_non_uniform_indexing => shaderStorageImageArrayNonUniformIndexing,
+            shader_input_attachment_array_non_uniform_indexing => shaderInputAttachmentArrayNonUniformIndexing,
+            shader_uniform_texel_buffer_array_non_uniform_indexing => shaderUniformTexelBufferArrayNonUniformIndexing,
+            shader_storage_texel_buffer_array_non_uniform_indexing => shaderStorageTexelBufferArrayNonUniformIndexing,
+            descriptor_binding_uniform_buffer_update_after_bind => descriptorBindingUniformBufferUpdateAfterBind,
+            descriptor_binding_sampled_image_update_after_bind => descriptorBindingSampledImageUpdateAfterBind,
+            descriptor_binding_storage_image_update_after_bind => descriptorBindingStorageImageUpdateAfterBind,
+            descriptor_binding_storage_buffer_update_after_bind => descriptorBindingStorageBufferUpdateAfterBind,
+            descriptor_binding_uniform_texel_buffer_update_after_bind => descriptorBindingUniformTexelBufferUpdateAfterBind,
+            descriptor_binding_storage_texel_buffer_update_after_bind => descriptorBindingStorageTexelBufferUpdateAfterBind,
+            descriptor_binding_update_unused_while_pending => descriptorBindingUpdateUnusedWhilePending,
+            descriptor_binding_partially_bound => descriptorBindingPartiallyBound,
+            descriptor_binding_variable_descriptor_count => descriptorBindingVariableDescriptorCount,
+            runtime_descriptor_array => runtimeDescriptorArray,
+        },
+        khr_timeline_semaphore ["VK_KHR_timeline_semaphore", V1_2] => PhysicalDeviceTimelineSemaphoreFeaturesKHR(STRUCTURE_TYPE_PHYSICAL_DEVICE_TIMELINE_SEMAPHORE_FEATURES_KHR) {
+            timeline_semaphore => timelineSemaphore,
+        },
+        ext_scalar_block_layout ["VK_EXT_scalar_block_layout", V1_2] => PhysicalDeviceScalarBlockLayoutFeaturesEXT(STRUCTURE_TYPE_PHYSICAL_DEVICE_SCALAR_BLOCK_LAYOUT_FEATURES_EXT) {
+            scalar_block_layout => scalarBlockLayout,
+        },
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use device::DeviceExtensions;
+    use features::Features;
+    use version::Version;
+
+    #[test]
+    fn extension_features_require_extension() {
+        let features = Features {
+            multiview: true,
+            ..Features::none()
+        };
+
+        match features.into_vulkan_features(Version::V1_0, &DeviceExtensions::none()) {
+            Err("VK_KHR_multiview") => (),
+            _ => panic!(),
+        }
+
+        let extensions = DeviceExtensions {
+            khr_multiview: true,
+            ..DeviceExtensions::none()
+        };
+        let ffi = features.into_vulkan_features(Version::V1_0, &extensions).unwrap();
+        assert_eq!(Features::from_vulkan_features(&ffi), features);
+
+        let ffi = features.into_vulkan_features(Version::V1_1, &DeviceExtensions::none()).unwrap();
+        assert_eq!(Features::from_vulkan_features(&ffi), features);
+    }
+
+    #[test]
+    fn all_features_round_trip() {
+        let ffi = Features::all().into_vulkan_features(Version::V1_2, &DeviceExtensions::none())
+            .unwrap();
+        assert_eq!(Features::from_vulkan_features(&ffi), Features::all());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_round_trip() {
+        assert_serde_round_trip!(Features::none());
+        assert_serde_round_trip!(Features {
+            geometry_shader: true,
+            multiview: true,
+            ..Features::none()
+        });
+    }
 }
@@ -177,6 +177,7 @@ macro_rules! formats {
     ($($name:ident => $vk:ident [$bdim:expr] [$sz:expr] [$($f_ty:tt)*] {$($d_ty:tt)*},)+) => (
         /// An enumeration of all the possible formats.
         #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
         #[repr(u32)]
         #[allow(missing_docs)]
         #[allow(non_camel_case_types)]
@@ -968,3 +969,17 @@ macro_rules! impl_clear_values_tuple {
 }
 
 impl_clear_values_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z);
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use format::Format;
+
+    #[test]
+    fn serde_round_trip() {
+        assert_serde_round_trip!(Format::R8G8B8A8Unorm);
+        assert_serde_round_trip!(Format::D24Unorm_S8Uint);
+
+        let format: Format = ::serde_json::from_str("\"B8G8R8A8Srgb\"").unwrap();
+        assert_eq!(format, Format::B8G8R8A8Srgb);
+    }
+}
@@ -421,7 +421,8 @@ impl<'a, R: ?Sized + 'a> Iterator for RenderPassDescDependencies<'a, R>
 }
 
 /// Describes an attachment that will be used in a render pass.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AttachmentDescription {
     /// Format of the image that is going to be bound.
     pub format: Format,
@@ -482,7 +483,8 @@ impl AttachmentDescription {
 ///
 // TODO: add tests for all these restrictions
 // TODO: allow unused attachments (for example attachment 0 and 2 are used, 1 is unused)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PassDescription {
     /// Indices and layouts of attachments to use as color attachments.
     pub color_attachments: Vec<(usize, ImageLayout)>, // TODO: Vec is slow
@@ -546,6 +548,7 @@ pub struct PassDependencyDescription {
 /// Describes what the implementation should do with an attachment after all the subpasses have
 /// completed.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(u32)]
 pub enum StoreOp {
     /// The attachment will be stored. This is what you usually want.
@@ -567,6 +570,7 @@ pub enum StoreOp {
 
 /// Describes what the implementation should do with an attachment at the start of the subpass.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(u32)]
 pub enum LoadOp {
     /// The content of the attachment will be loaded from memory. This is what you want if you want
@@ -593,3 +597,35 @@ pub enum LoadOp {
     /// instead.
     DontCare = vk::ATTACHMENT_LOAD_OP_DONT_CARE,
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use format::Format;
+    use framebuffer::AttachmentDescription;
+    use framebuffer::LoadOp;
+    use framebuffer::PassDescription;
+    use framebuffer::StoreOp;
+    use image::ImageLayout;
+
+    #[test]
+    fn serde_round_trip() {
+        assert_serde_round_trip!(AttachmentDescription {
+            format: Format::D16Unorm,
+            samples: 4,
+            load: LoadOp::Clear,
+            store: StoreOp::DontCare,
+            stencil_load: LoadOp::DontCare,
+            stencil_store: StoreOp::DontCare,
+            initial_layout: ImageLayout::Undefined,
+            final_layout: ImageLayout::DepthStencilAttachmentOptimal,
+        });
+
+        assert_serde_round_trip!(PassDescription {
+            color_attachments: vec![(0, ImageLayout::ColorAttachmentOptimal)],
+            depth_stencil: Some((1, ImageLayout::DepthStencilAttachmentOptimal)),
+            input_attachments: vec![],
+            resolve_attachments: vec![],
+            preserve_attachments: vec![2],
+        });
+    }
+}
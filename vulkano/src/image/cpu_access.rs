@@ -0,0 +1,935 @@
+// Copyright (c) 2020 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Image whose content is accessible to the CPU.
+//!
+//! The `CpuAccessibleImage` uses linear tiling and host-visible memory, which lets the CPU read
+//! and write its texels directly. It is useful for reading back small results or for streaming
+//! small textures that change often, but is usually slower for the GPU to access than an image
+//! with optimal tiling.
+//!
+//! Each array layer is accessed through a `ReadLock` or a `WriteLock`. In linear tiling the rows
+//! of an image can be padded, therefore the locks give access to the content row by row.
+//!
+//! Each access from the CPU or from the GPU locks the whole image for either reading or writing.
+//! You can read the image multiple times simultaneously. Trying to read and write simultaneously,
+//! or write and write simultaneously will return an error.
+
+use smallvec::SmallVec;
+use std::error;
+use std::fmt;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::marker::PhantomData;
+use std::mem;
+use std::slice;
+use std::sync::Arc;
+use std::sync::RwLock;
+use std::sync::RwLockReadGuard;
+use std::sync::RwLockWriteGuard;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+use buffer::BufferAccess;
+use device::Device;
+use device::DeviceOwned;
+use format::AcceptsPixels;
+use format::ClearValue;
+use format::FormatDesc;
+use format::FormatTy;
+use image::Dimensions;
+use image::ImageInner;
+use image::ImageLayout;
+use image::ImageUsage;
+use image::sys::ImageCreationError;
+use image::sys::LinearLayout;
+use image::sys::UnsafeImage;
+use image::sys::UnsafeImageView;
+use image::traits::ImageAccess;
+use image::traits::ImageClearValue;
+use image::traits::ImageContent;
+use image::traits::ImageViewAccess;
+use instance::QueueFamily;
+use memory::CpuAccess as MemCpuAccess;
+use memory::DedicatedAlloc;
+use memory::pool::AllocFromRequirementsFilter;
+use memory::pool::AllocLayout;
+use memory::pool::MappingRequirement;
+use memory::pool::MemoryPool;
+use memory::pool::MemoryPoolAlloc;
+use memory::pool::PotentialDedicatedAllocation;
+use memory::pool::StdMemoryPool;
+use sync::AccessError;
+use sync::Sharing;
+
+/// Image with linear tiling whose content is accessible by the CPU.
+///
+/// The image always has one mipmap level and is always in the `General` layout when the GPU
+/// accesses it. Only one and two-dimensional images, optionally with array layers, and only
+/// color formats are supported.
+///
+/// Setting `host_cached` to `true` when creating the image will make it so that it prefers to
+/// allocate from host-cached memory, which is faster when the CPU reads data written by the GPU.
+#[derive(Debug)]
+pub struct CpuAccessibleImage<F, A = Arc<StdMemoryPool>>
+    where A: MemoryPool
+{
+    // Inner implementation.
+    image: UnsafeImage,
+
+    // We maintain a view of the whole image.
+    view: UnsafeImageView,
+
+    // Memory used to back the image.
+    memory: PotentialDedicatedAllocation<A::Alloc>,
+
+    // Layout of the first array layer in memory.
+    layout: LinearLayout,
+
+    // Dimensions of the image view.
+    dimensions: Dimensions,
+
+    // Format.
+    format: F,
+
+    // Queue families allowed to access this image.
+    queue_families: SmallVec<[u32; 4]>,
+
+    // Access pattern of the image. Works the same way as for `CpuAccessibleBuffer`.
+    access: RwLock<CurrentGpuAccess>,
+
+    // True if the image has been transitioned out of the `Preinitialized` layout.
+    initialized: AtomicBool,
+}
+
+#[derive(Debug)]
+enum CurrentGpuAccess {
+    NonExclusive {
+        // Number of non-exclusive GPU accesses. Can be 0.
+        num: AtomicUsize,
+    },
+    Exclusive {
+        // Number of exclusive locks. Cannot be 0. If 0 is reached, we must jump to `NonExclusive`.
+        num: usize,
+    },
+}
+
+impl<F> CpuAccessibleImage<F> {
+    /// Creates a new image with the given dimensions and format, that can be used as the source
+    /// and destination of transfer operations.
+    #[inline]
+    pub fn new<'a, I>(device: Arc<Device>, dimensions: Dimensions, format: F, host_cached: bool,
+                      queue_families: I)
+                      -> Result<Arc<CpuAccessibleImage<F>>, ImageCreationError>
+        where F: FormatDesc,
+              I: IntoIterator<Item = QueueFamily<'a>>
+    {
+        let usage = ImageUsage {
+            transfer_source: true,
+            transfer_destination: true,
+            ..ImageUsage::none()
+        };
+
+        CpuAccessibleImage::with_usage(device, dimensions, format, usage, host_cached,
+                                       queue_families)
+    }
+
+    /// Same as `new`, but allows specifying the usage.
+    ///
+    /// Note that implementations support fewer usages with linear tiling than with optimal
+    /// tiling. `ImageCreationError::UnsupportedUsage` is returned if the usage isn't supported
+    /// for the format.
+    pub fn with_usage<'a, I>(device: Arc<Device>, dimensions: Dimensions, format: F,
+                             usage: ImageUsage, host_cached: bool, queue_families: I)
+                             -> Result<Arc<CpuAccessibleImage<F>>, ImageCreationError>
+        where F: FormatDesc,
+              I: IntoIterator<Item = QueueFamily<'a>>
+    {
+        match dimensions {
+            Dimensions::Dim1d { .. } |
+            Dimensions::Dim1dArray { .. } |
+            Dimensions::Dim2d { .. } |
+            Dimensions::Dim2dArray { .. } => (),
+            _ => {
+                return Err(ImageCreationError::UnsupportedDimensions {
+                               dimensions: dimensions.to_image_dimensions(),
+                           })
+            },
+        }
+
+        match format.format().ty() {
+            FormatTy::Float | FormatTy::Uint | FormatTy::Sint => (),
+            _ => return Err(ImageCreationError::FormatNotSupported),
+        }
+
+        let queue_families = queue_families
+            .into_iter()
+            .map(|f| f.id())
+            .collect::<SmallVec<[u32; 4]>>();
+
+        let (image, mem_reqs) = unsafe {
+            let sharing = if queue_families.len() >= 2 {
+                Sharing::Concurrent(queue_families.iter().cloned())
+            } else {
+                Sharing::Exclusive
+            };
+
+            UnsafeImage::new(device.clone(),
+                             usage,
+                             format.format(),
+                             dimensions.to_image_dimensions(),
+                             1,
+                             1,
+                             sharing,
+                             true,
+                             true)?
+        };
+
+        let mem = MemoryPool::alloc_from_requirements(&Device::standard_pool(&device),
+                                    &mem_reqs,
+                                    AllocLayout::Linear,
+                                    MappingRequirement::Map,
+                                    DedicatedAlloc::Image(&image),
+                                    |m| if m.is_host_cached() == host_cached {
+                                        AllocFromRequirementsFilter::Preferred
+                                    } else {
+                                        AllocFromRequirementsFilter::Allowed
+                                    })?;
+        debug_assert!((mem.offset() % mem_reqs.alignment) == 0);
+        debug_assert!(mem.mapped_memory().is_some());
+        unsafe {
+            image.bind_memory(mem.memory(), mem.offset())?;
+        }
+
+        // The image has a color format and linear tiling.
+        let layout = unsafe { image.color_linear_layout(0) };
+
+        let view = unsafe {
+            UnsafeImageView::raw(&image,
+                                 dimensions.to_view_type(),
+                                 0 .. image.mipmap_levels(),
+                                 0 .. image.dimensions().array_layers())?
+        };
+
+        Ok(Arc::new(CpuAccessibleImage {
+                        image,
+                        view,
+                        memory: mem,
+                        layout,
+                        dimensions,
+                        format,
+                        queue_families,
+                        access: RwLock::new(CurrentGpuAccess::NonExclusive {
+                                                num: AtomicUsize::new(0),
+                                            }),
+                        initialized: AtomicBool::new(false),
+                    }))
+    }
+}
+
+impl<F, A> CpuAccessibleImage<F, A>
+    where A: MemoryPool
+{
+    /// Returns the dimensions of the image.
+    #[inline]
+    pub fn dimensions(&self) -> Dimensions {
+        self.dimensions
+    }
+
+    /// Returns the queue families this image can be used on.
+    // TODO: use a custom iterator
+    #[inline]
+    pub fn queue_families(&self) -> Vec<QueueFamily> {
+        self.queue_families
+            .iter()
+            .map(|&num| {
+                     self.image
+                         .device()
+                         .physical_device()
+                         .queue_family_by_id(num)
+                         .unwrap()
+                 })
+            .collect()
+    }
+
+    /// Returns the layout in memory of the given array layer.
+    ///
+    /// The `offset` is relative to the start of the memory of the image.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the array layer is out of range.
+    ///
+    #[inline]
+    pub fn layer_layout(&self, array_layer: u32) -> LinearLayout {
+        assert!(array_layer < self.dimensions.array_layers());
+        LinearLayout {
+            offset: self.layout.offset + array_layer as usize * self.layout.array_pitch,
+            ..self.layout
+        }
+    }
+
+    // Builds the description of an array layer for the locks.
+    fn subresource<P>(&self, array_layer: u32) -> Subresource
+        where F: AcceptsPixels<P>
+    {
+        <F as AcceptsPixels<P>>::ensure_accepts(&self.format).unwrap();
+        let layout = self.layer_layout(array_layer);
+        Subresource {
+            offset: layout.offset,
+            row_pitch: layout.row_pitch,
+            width: self.dimensions.width() as usize *
+                <F as AcceptsPixels<P>>::rate(&self.format) as usize,
+            height: self.dimensions.height(),
+        }
+    }
+
+    /// Locks an array layer of the image in order to read its content from the CPU.
+    ///
+    /// If the image is currently used in exclusive mode by the GPU, this function will return
+    /// an error. Similarly if you called `write()` on the image and haven't dropped the lock,
+    /// this function will return an error as well.
+    ///
+    /// After this function successfully locks the image, any attempt to submit a command buffer
+    /// that uses it in exclusive mode will fail. You can still submit this image for
+    /// non-exclusive accesses (ie. reads).
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the array layer is out of range.
+    /// - Panics if `P` isn't a valid pixel type for the format of the image.
+    ///
+    pub fn read<P>(&self, array_layer: u32) -> Result<ReadLock<P>, ReadLockError>
+        where F: AcceptsPixels<P>
+    {
+        let subresource = self.subresource::<P>(array_layer);
+
+        let lock = match self.access.try_read() {
+            Ok(l) => l,
+            Err(_) => return Err(ReadLockError::CpuWriteLocked),
+        };
+
+        if let CurrentGpuAccess::Exclusive { .. } = *lock {
+            return Err(ReadLockError::GpuWriteLocked);
+        }
+
+        let inner = unsafe {
+            self.memory
+                .mapped_memory()
+                .unwrap()
+                .read_write(self.memory.offset() .. self.memory.offset() + self.memory_size())
+        };
+        subresource.check_alignment::<P>(&inner);
+
+        Ok(ReadLock {
+               inner,
+               lock,
+               subresource,
+               marker: PhantomData,
+           })
+    }
+
+    /// Locks an array layer of the image in order to write its content from the CPU.
+    ///
+    /// If the image is currently in use by the GPU, this function will return an error.
+    /// Similarly if you called `read()` on the image and haven't dropped the lock, this
+    /// function will return an error as well.
+    ///
+    /// After this function successfully locks the image, any attempt to submit a command buffer
+    /// that uses it and any attempt to call `read()` will return an error.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the array layer is out of range.
+    /// - Panics if `P` isn't a valid pixel type for the format of the image.
+    ///
+    pub fn write<P>(&self, array_layer: u32) -> Result<WriteLock<P>, WriteLockError>
+        where F: AcceptsPixels<P>
+    {
+        let subresource = self.subresource::<P>(array_layer);
+
+        let lock = match self.access.try_write() {
+            Ok(l) => l,
+            Err(_) => return Err(WriteLockError::CpuLocked),
+        };
+
+        match *lock {
+            CurrentGpuAccess::NonExclusive { ref num } if num.load(Ordering::SeqCst) == 0 => (),
+            _ => return Err(WriteLockError::GpuLocked),
+        }
+
+        let inner = unsafe {
+            self.memory
+                .mapped_memory()
+                .unwrap()
+                .read_write(self.memory.offset() .. self.memory.offset() + self.memory_size())
+        };
+        subresource.check_alignment::<P>(&inner);
+
+        Ok(WriteLock {
+               inner,
+               lock,
+               subresource,
+               marker: PhantomData,
+           })
+    }
+
+    // Number of bytes of memory that contain the image.
+    #[inline]
+    fn memory_size(&self) -> usize {
+        let layers = self.dimensions.array_layers() as usize;
+        self.layout.offset + (layers - 1) * self.layout.array_pitch + self.layout.size
+    }
+}
+
+unsafe impl<F, A> ImageAccess for CpuAccessibleImage<F, A>
+    where F: 'static + Send + Sync,
+          A: MemoryPool
+{
+    #[inline]
+    fn inner(&self) -> ImageInner {
+        ImageInner {
+            image: &self.image,
+            first_layer: 0,
+            num_layers: self.dimensions.array_layers() as usize,
+            first_mipmap_level: 0,
+            num_mipmap_levels: 1,
+        }
+    }
+
+    #[inline]
+    fn initial_layout_requirement(&self) -> ImageLayout {
+        ImageLayout::General
+    }
+
+    #[inline]
+    fn final_layout_requirement(&self) -> ImageLayout {
+        ImageLayout::General
+    }
+
+    #[inline]
+    fn conflicts_buffer(&self, other: &dyn BufferAccess) -> bool {
+        false
+    }
+
+    #[inline]
+    fn conflicts_image(&self, other: &dyn ImageAccess) -> bool {
+        self.conflict_key() == other.conflict_key() // TODO:
+    }
+
+    #[inline]
+    fn conflict_key(&self) -> u64 {
+        self.image.key()
+    }
+
+    #[inline]
+    fn try_gpu_lock(&self, exclusive_access: bool, expected_layout: ImageLayout)
+                    -> Result<(), AccessError> {
+        if expected_layout != ImageLayout::General && expected_layout != ImageLayout::Undefined &&
+            expected_layout != ImageLayout::Preinitialized
+        {
+            return Err(AccessError::UnexpectedImageLayout {
+                           requested: expected_layout,
+                           allowed: ImageLayout::General,
+                       });
+        }
+
+        if exclusive_access {
+            let mut lock = match self.access.try_write() {
+                Ok(lock) => lock,
+                Err(_) => return Err(AccessError::AlreadyInUse),
+            };
+
+            match *lock {
+                CurrentGpuAccess::NonExclusive { ref num } if num.load(Ordering::SeqCst) == 0 => (),
+                _ => return Err(AccessError::AlreadyInUse),
+            };
+
+            *lock = CurrentGpuAccess::Exclusive { num: 1 };
+            Ok(())
+
+        } else {
+            let lock = match self.access.try_read() {
+                Ok(lock) => lock,
+                Err(_) => return Err(AccessError::AlreadyInUse),
+            };
+
+            match *lock {
+                CurrentGpuAccess::Exclusive { .. } => return Err(AccessError::AlreadyInUse),
+                CurrentGpuAccess::NonExclusive { ref num } => {
+                    num.fetch_add(1, Ordering::SeqCst)
+                },
+            };
+
+            Ok(())
+        }
+    }
+
+    #[inline]
+    unsafe fn increase_gpu_lock(&self) {
+        {
+            let read_lock = self.access.read().unwrap();
+            if let CurrentGpuAccess::NonExclusive { ref num } = *read_lock {
+                let prev = num.fetch_add(1, Ordering::SeqCst);
+                debug_assert!(prev >= 1);
+                return;
+            }
+        }
+
+        {
+            let mut write_lock = self.access.write().unwrap();
+            if let CurrentGpuAccess::Exclusive { ref mut num } = *write_lock {
+                *num += 1;
+            } else {
+                unreachable!()
+            }
+        }
+    }
+
+    #[inline]
+    unsafe fn unlock(&self, new_layout: Option<ImageLayout>) {
+        assert!(new_layout.is_none() || new_layout == Some(ImageLayout::General));
+
+        {
+            let read_lock = self.access.read().unwrap();
+            if let CurrentGpuAccess::NonExclusive { ref num } = *read_lock {
+                let prev = num.fetch_sub(1, Ordering::SeqCst);
+                debug_assert!(prev >= 1);
+                return;
+            }
+        }
+
+        {
+            let mut write_lock = self.access.write().unwrap();
+            if let CurrentGpuAccess::Exclusive { ref mut num } = *write_lock {
+                if *num != 1 {
+                    *num -= 1;
+                    return;
+                }
+            } else {
+                panic!()
+            }
+
+            *write_lock = CurrentGpuAccess::NonExclusive { num: AtomicUsize::new(0) };
+        }
+    }
+
+    #[inline]
+    unsafe fn layout_initialized(&self) {
+        self.initialized.store(true, Ordering::SeqCst);
+    }
+
+    #[inline]
+    fn is_layout_initialized(&self) -> bool {
+        self.initialized.load(Ordering::SeqCst)
+    }
+}
+
+unsafe impl<F, A> ImageClearValue<F::ClearValue> for CpuAccessibleImage<F, A>
+    where F: FormatDesc + 'static + Send + Sync,
+          A: MemoryPool
+{
+    #[inline]
+    fn decode(&self, value: F::ClearValue) -> Option<ClearValue> {
+        Some(self.format.decode_clear_value(value))
+    }
+}
+
+unsafe impl<P, F, A> ImageContent<P> for CpuAccessibleImage<F, A>
+    where F: 'static + Send + Sync,
+          A: MemoryPool
+{
+    #[inline]
+    fn matches_format(&self) -> bool {
+        true // FIXME:
+    }
+}
+
+unsafe impl<F, A> ImageViewAccess for CpuAccessibleImage<F, A>
+    where F: 'static + Send + Sync,
+          A: MemoryPool
+{
+    #[inline]
+    fn parent(&self) -> &dyn ImageAccess {
+        self
+    }
+
+    #[inline]
+    fn dimensions(&self) -> Dimensions {
+        self.dimensions
+    }
+
+    #[inline]
+    fn inner(&self) -> &UnsafeImageView {
+        &self.view
+    }
+
+    #[inline]
+    fn descriptor_set_storage_image_layout(&self) -> ImageLayout {
+        ImageLayout::General
+    }
+
+    #[inline]
+    fn descriptor_set_combined_image_sampler_layout(&self) -> ImageLayout {
+        ImageLayout::General
+    }
+
+    #[inline]
+    fn descriptor_set_sampled_image_layout(&self) -> ImageLayout {
+        ImageLayout::General
+    }
+
+    #[inline]
+    fn descriptor_set_input_attachment_layout(&self) -> ImageLayout {
+        ImageLayout::General
+    }
+
+    #[inline]
+    fn identity_swizzle(&self) -> bool {
+        true
+    }
+}
+
+unsafe impl<F, A> DeviceOwned for CpuAccessibleImage<F, A>
+    where A: MemoryPool
+{
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        self.image.device()
+    }
+}
+
+impl<F, A> PartialEq for CpuAccessibleImage<F, A>
+    where F: 'static + Send + Sync,
+          A: MemoryPool
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        ImageAccess::inner(self) == ImageAccess::inner(other)
+    }
+}
+
+impl<F, A> Eq for CpuAccessibleImage<F, A>
+    where F: 'static + Send + Sync,
+          A: MemoryPool
+{}
+
+impl<F, A> Hash for CpuAccessibleImage<F, A>
+    where F: 'static + Send + Sync,
+          A: MemoryPool
+{
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        ImageAccess::inner(self).hash(state);
+    }
+}
+
+// Location of an array layer in the mapped memory, and size of its rows.
+#[derive(Debug, Copy, Clone)]
+struct Subresource {
+    // Offset of the first row, in bytes.
+    offset: usize,
+    // Number of bytes between the start of two rows.
+    row_pitch: usize,
+    // Number of pixel values in a row.
+    width: usize,
+    // Number of rows.
+    height: u32,
+}
+
+impl Subresource {
+    // Checks that the rows can be accessed as slices of `P`.
+    fn check_alignment<P>(&self, memory: &[u8]) {
+        let align = mem::align_of::<P>();
+        assert_eq!((memory.as_ptr() as usize + self.offset) % align, 0);
+        assert_eq!(self.row_pitch % align, 0);
+    }
+
+    #[inline]
+    fn row_range(&self, y: u32) -> (usize, usize) {
+        assert!(y < self.height);
+        (self.offset + y as usize * self.row_pitch, self.width)
+    }
+}
+
+/// Object that can be used to read the content of an array layer of a `CpuAccessibleImage`.
+///
+/// Note that this object holds a rwlock read guard on the image. If another thread tries to
+/// access this image's content or tries to submit a GPU command that uses this image, it will
+/// fail.
+pub struct ReadLock<'a, P: 'a> {
+    inner: MemCpuAccess<'a, [u8]>,
+    lock: RwLockReadGuard<'a, CurrentGpuAccess>,
+    subresource: Subresource,
+    marker: PhantomData<&'a [P]>,
+}
+
+impl<'a, P: 'a> ReadLock<'a, P> {
+    /// Returns the number of pixel values in a row.
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.subresource.width
+    }
+
+    /// Returns the number of rows.
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.subresource.height
+    }
+
+    /// Returns the row at the given position.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `y` is out of range.
+    ///
+    #[inline]
+    pub fn row(&self, y: u32) -> &[P] {
+        let (start, len) = self.subresource.row_range(y);
+        unsafe { slice::from_raw_parts(self.inner.as_ptr().add(start) as *const P, len) }
+    }
+
+    /// Copies the content of the array layer into a `Vec`, without the padding between rows.
+    pub fn to_vec(&self) -> Vec<P>
+        where P: Clone
+    {
+        let mut out = Vec::with_capacity(self.width() * self.height() as usize);
+        for y in 0 .. self.height() {
+            out.extend_from_slice(self.row(y));
+        }
+        out
+    }
+}
+
+/// Error when attempting to CPU-read an image.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReadLockError {
+    /// The image is already locked for write mode by the CPU.
+    CpuWriteLocked,
+    /// The image is already locked for write mode by the GPU.
+    GpuWriteLocked,
+}
+
+impl error::Error for ReadLockError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            ReadLockError::CpuWriteLocked => {
+                "the image is already locked for write mode by the CPU"
+            },
+            ReadLockError::GpuWriteLocked => {
+                "the image is already locked for write mode by the GPU"
+            },
+        }
+    }
+}
+
+impl fmt::Display for ReadLockError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+/// Object that can be used to read or write the content of an array layer of a
+/// `CpuAccessibleImage`.
+///
+/// Note that this object holds a rwlock write guard on the image. If another thread tries to
+/// access this image's content or tries to submit a GPU command that uses this image, it will
+/// fail.
+pub struct WriteLock<'a, P: 'a> {
+    inner: MemCpuAccess<'a, [u8]>,
+    lock: RwLockWriteGuard<'a, CurrentGpuAccess>,
+    subresource: Subresource,
+    marker: PhantomData<&'a mut [P]>,
+}
+
+impl<'a, P: 'a> WriteLock<'a, P> {
+    /// Returns the number of pixel values in a row.
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.subresource.width
+    }
+
+    /// Returns the number of rows.
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.subresource.height
+    }
+
+    /// Returns the row at the given position.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `y` is out of range.
+    ///
+    #[inline]
+    pub fn row(&self, y: u32) -> &[P] {
+        let (start, len) = self.subresource.row_range(y);
+        unsafe { slice::from_raw_parts(self.inner.as_ptr().add(start) as *const P, len) }
+    }
+
+    /// Returns the row at the given position.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `y` is out of range.
+    ///
+    #[inline]
+    pub fn row_mut(&mut self, y: u32) -> &mut [P] {
+        let (start, len) = self.subresource.row_range(y);
+        unsafe { slice::from_raw_parts_mut(self.inner.as_mut_ptr().add(start) as *mut P, len) }
+    }
+
+    /// Copies tightly-packed data into the array layer.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the length of `data` isn't `width() * height()`.
+    ///
+    pub fn copy_from_slice(&mut self, data: &[P])
+        where P: Copy
+    {
+        let width = self.width();
+        assert_eq!(data.len(), width * self.height() as usize);
+        for (y, row) in data.chunks(width).enumerate() {
+            self.row_mut(y as u32).copy_from_slice(row);
+        }
+    }
+}
+
+/// Error when attempting to CPU-write an image.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WriteLockError {
+    /// The image is already locked by the CPU.
+    CpuLocked,
+    /// The image is already locked by the GPU.
+    GpuLocked,
+}
+
+impl error::Error for WriteLockError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            WriteLockError::CpuLocked => {
+                "the image is already locked by the CPU"
+            },
+            WriteLockError::GpuLocked => {
+                "the image is already locked by the GPU"
+            },
+        }
+    }
+}
+
+impl fmt::Display for WriteLockError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CpuAccessibleImage;
+    use super::ReadLockError;
+    use super::WriteLockError;
+    use format::Format;
+    use image::Dimensions;
+    use image::sys::ImageCreationError;
+
+    #[test]
+    fn write_then_read() {
+        let (device, queue) = gfx_dev_and_queue!();
+        let image = CpuAccessibleImage::new(device,
+                                            Dimensions::Dim2d {
+                                                width: 13,
+                                                height: 7,
+                                            },
+                                            Format::R8G8B8A8Unorm,
+                                            false,
+                                            Some(queue.family()))
+            .unwrap();
+
+        let data = (0 .. 13 * 7 * 4).map(|n| n as u8).collect::<Vec<u8>>();
+        {
+            let mut lock = image.write::<u8>(0).unwrap();
+            assert_eq!(lock.width(), 13 * 4);
+            assert_eq!(lock.height(), 7);
+            lock.copy_from_slice(&data);
+        }
+
+        let lock = image.read::<u8>(0).unwrap();
+        assert_eq!(lock.row(1), &data[13 * 4 .. 13 * 4 * 2]);
+        assert_eq!(lock.to_vec(), data);
+    }
+
+    #[test]
+    fn array_layers() {
+        let (device, queue) = gfx_dev_and_queue!();
+        let image = match CpuAccessibleImage::new(device,
+                                                  Dimensions::Dim2dArray {
+                                                      width: 4,
+                                                      height: 4,
+                                                      array_layers: 2,
+                                                  },
+                                                  Format::R32Uint,
+                                                  false,
+                                                  Some(queue.family())) {
+            Ok(i) => i,
+            // Linear images with array layers are optional.
+            Err(ImageCreationError::UnsupportedDimensions { .. }) |
+            Err(ImageCreationError::FormatNotSupported) => return,
+            Err(err) => panic!("{:?}", err),
+        };
+
+        image.write::<u32>(0).unwrap().copy_from_slice(&[1; 16]);
+        image.write::<u32>(1).unwrap().copy_from_slice(&[2; 16]);
+        assert_eq!(image.read::<u32>(0).unwrap().to_vec(), vec![1; 16]);
+        assert_eq!(image.read::<u32>(1).unwrap().to_vec(), vec![2; 16]);
+    }
+
+    #[test]
+    fn lock_conflicts() {
+        let (device, queue) = gfx_dev_and_queue!();
+        let image = CpuAccessibleImage::new(device,
+                                            Dimensions::Dim2d {
+                                                width: 4,
+                                                height: 4,
+                                            },
+                                            Format::R8G8B8A8Unorm,
+                                            false,
+                                            Some(queue.family()))
+            .unwrap();
+
+        {
+            let _read = image.read::<u8>(0).unwrap();
+            let _read2 = image.read::<u8>(0).unwrap();
+            assert_eq!(image.write::<u8>(0).err(), Some(WriteLockError::CpuLocked));
+        }
+
+        let _write = image.write::<u8>(0).unwrap();
+        assert_eq!(image.read::<u8>(0).err(), Some(ReadLockError::CpuWriteLocked));
+    }
+
+    #[test]
+    fn unsupported_dimensions() {
+        let (device, queue) = gfx_dev_and_queue!();
+        let res = CpuAccessibleImage::new(device,
+                                          Dimensions::Cubemap { size: 4 },
+                                          Format::R8G8B8A8Unorm,
+                                          false,
+                                          Some(queue.family()));
+
+        match res {
+            Err(ImageCreationError::UnsupportedDimensions { .. }) => (),
+            _ => panic!(),
+        }
+    }
+}
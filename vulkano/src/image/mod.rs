@@ -40,6 +40,13 @@
 //! - An `AttachmentImage` can be used when you want to draw to an image.
 //! - An `ImmutableImage` stores data which never need be changed after the initial upload,
 //!   like a texture.
+//! - A `CpuAccessibleImage` uses linear tiling and host-visible memory so that the CPU can read
+//!   and write its content directly, for example to read back results or to stream small
+//!   textures.
+//!
+//! Each of these images also acts as a view of the whole image. An `ImageView` can wrap any of
+//! them to view only some of its mipmap levels or array layers, to use a different view type
+//! such as a cubemap, or to reinterpret it with another format.
 //!
 //! # Low-level information
 //!
@@ -48,7 +55,10 @@
 
 use std::cmp;
 
+use vk;
+
 pub use self::attachment::AttachmentImage;
+pub use self::cpu_access::CpuAccessibleImage;
 pub use self::immutable::ImmutableImage;
 pub use self::layout::ImageLayout;
 pub use self::storage::StorageImage;
@@ -58,8 +68,12 @@ pub use self::traits::ImageAccess;
 pub use self::traits::ImageInner;
 pub use self::traits::ImageViewAccess;
 pub use self::usage::ImageUsage;
+pub use self::view::ImageView;
+pub use self::view::ImageViewBuilder;
+pub use self::view::ImageViewCreationError;
 
 pub mod attachment; // TODO: make private
+pub mod cpu_access;
 pub mod immutable; // TODO: make private
 mod layout;
 mod storage;
@@ -67,6 +81,7 @@ pub mod swapchain; // TODO: make private
 pub mod sys;
 pub mod traits;
 mod usage;
+mod view;
 
 /// Specifies how many mipmaps must be allocated.
 ///
@@ -118,21 +133,22 @@ pub struct Swizzle {
 
 /// Describes the value that an individual component must return when being accessed.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u32)]
 pub enum ComponentSwizzle {
     /// Returns the value that this component should normally have.
-    Identity,
+    Identity = vk::COMPONENT_SWIZZLE_IDENTITY,
     /// Always return zero.
-    Zero,
+    Zero = vk::COMPONENT_SWIZZLE_ZERO,
     /// Always return one.
-    One,
+    One = vk::COMPONENT_SWIZZLE_ONE,
     /// Returns the value of the first component.
-    Red,
+    Red = vk::COMPONENT_SWIZZLE_R,
     /// Returns the value of the second component.
-    Green,
+    Green = vk::COMPONENT_SWIZZLE_G,
     /// Returns the value of the third component.
-    Blue,
+    Blue = vk::COMPONENT_SWIZZLE_B,
     /// Returns the value of the fourth component.
-    Alpha,
+    Alpha = vk::COMPONENT_SWIZZLE_A,
 }
 
 impl Default for ComponentSwizzle {
@@ -101,11 +101,34 @@ impl<F> StorageImage<F> {
     }
 
     /// Same as `new`, but allows specifying the usage.
+    #[inline]
     pub fn with_usage<'a, I>(device: Arc<Device>, dimensions: Dimensions, format: F,
                              usage: ImageUsage, queue_families: I)
                              -> Result<Arc<StorageImage<F>>, ImageCreationError>
         where F: FormatDesc,
               I: IntoIterator<Item = QueueFamily<'a>>
+    {
+        StorageImage::new_impl(device, dimensions, format, usage, queue_families, false)
+    }
+
+    /// Same as `with_usage`, but the image is created with a mutable format. An `ImageView` of
+    /// the image can then reinterpret it with another format of the same size, for example view
+    /// an sRGB image as UNORM.
+    #[inline]
+    pub fn with_mutable_format<'a, I>(device: Arc<Device>, dimensions: Dimensions, format: F,
+                                      usage: ImageUsage, queue_families: I)
+                                      -> Result<Arc<StorageImage<F>>, ImageCreationError>
+        where F: FormatDesc,
+              I: IntoIterator<Item = QueueFamily<'a>>
+    {
+        StorageImage::new_impl(device, dimensions, format, usage, queue_families, true)
+    }
+
+    fn new_impl<'a, I>(device: Arc<Device>, dimensions: Dimensions, format: F,
+                       usage: ImageUsage, queue_families: I, mutable_format: bool)
+                       -> Result<Arc<StorageImage<F>>, ImageCreationError>
+        where F: FormatDesc,
+              I: IntoIterator<Item = QueueFamily<'a>>
     {
         let queue_families = queue_families
             .into_iter()
@@ -119,15 +142,27 @@ impl<F> StorageImage<F> {
                 Sharing::Exclusive
             };
 
-            UnsafeImage::new(device.clone(),
-                             usage,
-                             format.format(),
-                             dimensions.to_image_dimensions(),
-                             1,
-                             1,
-                             sharing,
-                             false,
-                             false)?
+            if mutable_format {
+                UnsafeImage::with_mutable_format(device.clone(),
+                                                 usage,
+                                                 format.format(),
+                                                 dimensions.to_image_dimensions(),
+                                                 1,
+                                                 1,
+                                                 sharing,
+                                                 false,
+                                                 false)?
+            } else {
+                UnsafeImage::new(device.clone(),
+                                 usage,
+                                 format.format(),
+                                 dimensions.to_image_dimensions(),
+                                 1,
+                                 1,
+                                 sharing,
+                                 false,
+                                 false)?
+            }
         };
 
         let mem = MemoryPool::alloc_from_requirements(&Device::standard_pool(&device),
@@ -25,21 +25,26 @@ use std::ptr;
 use std::sync::Arc;
 
 use device::Device;
+use device::DeviceOwned;
 use format::Format;
 use format::FormatTy;
 use image::ImageDimensions;
 use image::ImageUsage;
 use image::MipmapsCount;
+use image::Swizzle;
 use image::ViewType;
 use memory::DeviceMemory;
 use memory::DeviceMemoryAllocError;
+use memory::ExternalMemoryHandleType;
 use memory::MemoryRequirements;
+use sync::QueueFamilyOwnership;
 use sync::Sharing;
 
 use Error;
 use OomError;
 use VulkanObject;
 use check_errors;
+use version::Version;
 use vk;
 
 /// A storage for pixels or arbitrary data.
@@ -65,12 +70,20 @@ pub struct UnsafeImage {
     samples: u32,
     mipmaps: u32,
 
+    // True if views of the image can have a different format than the image.
+    mutable_format: bool,
+
     // Features that are supported for this particular format.
     format_features: vk::FormatFeatureFlagBits,
 
     // `vkDestroyImage` is called only if `needs_destruction` is true.
     needs_destruction: bool,
     preinitialized_layout: bool,
+
+    // True if the image was created with `Sharing::Concurrent`.
+    concurrent: bool,
+    // Queue family ownership of the image, for images that aren't concurrent.
+    ownership: QueueFamilyOwnership,
 }
 
 impl UnsafeImage {
@@ -104,14 +117,103 @@ impl UnsafeImage {
                               mipmaps.into(),
                               sharing,
                               linear_tiling,
-                              preinitialized_layout)
+                              preinitialized_layout,
+                              false,
+                              ExternalMemoryHandleType::none())
+    }
+
+    /// Same as `new`, but the image is created with the `MUTABLE_FORMAT` flag. Views of the image
+    /// can then use a different format than the image, as long as it has the same size.
+    ///
+    /// # Panic
+    ///
+    /// - Panics for the same reasons as `new`.
+    ///
+    #[inline]
+    pub unsafe fn with_mutable_format<'a, Mi, I>(device: Arc<Device>, usage: ImageUsage,
+                                                 format: Format, dimensions: ImageDimensions,
+                                                 num_samples: u32, mipmaps: Mi,
+                                                 sharing: Sharing<I>, linear_tiling: bool,
+                                                 preinitialized_layout: bool)
+                                                 -> Result<(UnsafeImage, MemoryRequirements),
+                                                           ImageCreationError>
+        where Mi: Into<MipmapsCount>,
+              I: Iterator<Item = u32>
+    {
+        let sharing = match sharing {
+            Sharing::Exclusive => (vk::SHARING_MODE_EXCLUSIVE, SmallVec::<[u32; 8]>::new()),
+            Sharing::Concurrent(ids) => (vk::SHARING_MODE_CONCURRENT, ids.collect()),
+        };
+
+        UnsafeImage::new_impl(device,
+                              usage,
+                              format,
+                              dimensions,
+                              num_samples,
+                              mipmaps.into(),
+                              sharing,
+                              linear_tiling,
+                              preinitialized_layout,
+                              true,
+                              ExternalMemoryHandleType::none())
+    }
+
+    /// Same as `new`, but the memory bound to the image can be exported or imported with the
+    /// given handle types.
+    ///
+    /// The `VK_KHR_external_memory` extension must be enabled on the device unless it uses Vulkan
+    /// 1.1. The memory must then be allocated with `DeviceMemory::dedicated_alloc_exportable` or
+    /// `DeviceMemory::import_fd` using the same handle types. Use
+    /// `PhysicalDevice::external_image_format_properties` to check beforehand whether the
+    /// configuration is supported.
+    ///
+    /// # Panic
+    ///
+    /// - Panics for the same reasons as `new`.
+    /// - Panics if `handle_types` is empty.
+    ///
+    pub unsafe fn with_external_memory<'a, Mi, I>(device: Arc<Device>, usage: ImageUsage,
+                                                  format: Format, dimensions: ImageDimensions,
+                                                  num_samples: u32, mipmaps: Mi,
+                                                  sharing: Sharing<I>, linear_tiling: bool,
+                                                  preinitialized_layout: bool,
+                                                  handle_types: ExternalMemoryHandleType)
+                                                  -> Result<(UnsafeImage, MemoryRequirements),
+                                                            ImageCreationError>
+        where Mi: Into<MipmapsCount>,
+              I: Iterator<Item = u32>
+    {
+        assert!(!handle_types.is_empty());
+
+        if device.api_version() < Version::V1_1 && !device.loaded_extensions().khr_external_memory {
+            let err = DeviceMemoryAllocError::MissingExtension { name: "VK_KHR_external_memory" };
+            return Err(ImageCreationError::AllocError(err));
+        }
+
+        let sharing = match sharing {
+            Sharing::Exclusive => (vk::SHARING_MODE_EXCLUSIVE, SmallVec::<[u32; 8]>::new()),
+            Sharing::Concurrent(ids) => (vk::SHARING_MODE_CONCURRENT, ids.collect()),
+        };
+
+        UnsafeImage::new_impl(device,
+                              usage,
+                              format,
+                              dimensions,
+                              num_samples,
+                              mipmaps.into(),
+                              sharing,
+                              linear_tiling,
+                              preinitialized_layout,
+                              false,
+                              handle_types)
     }
 
     // Non-templated version to avoid inlining and improve compile times.
     unsafe fn new_impl(device: Arc<Device>, usage: ImageUsage, format: Format,
                        dimensions: ImageDimensions, num_samples: u32, mipmaps: MipmapsCount,
                        (sh_mode, sh_indices): (vk::SharingMode, SmallVec<[u32; 8]>),
-                       linear_tiling: bool, preinitialized_layout: bool)
+                       linear_tiling: bool, preinitialized_layout: bool, mutable_format: bool,
+                       external_handle_types: ExternalMemoryHandleType)
                        -> Result<(UnsafeImage, MemoryRequirements), ImageCreationError> {
         // TODO: doesn't check that the proper features are enabled
 
@@ -156,7 +258,7 @@ impl UnsafeImage {
             {
                 return Err(ImageCreationError::UnsupportedUsage);
             }
-            if device.loaded_extensions().khr_maintenance1 {
+            if device.api_version() >= Version::V1_1 || device.loaded_extensions().khr_maintenance1 {
                 if usage.transfer_source &&
                     (features & vk::FORMAT_FEATURE_TRANSFER_SRC_BIT_KHR == 0)
                 {
@@ -387,6 +489,11 @@ impl UnsafeImage {
                 (vk::IMAGE_TYPE_3D, extent, 1, 0)
             },
         };
+        let flags = if mutable_format {
+            flags | vk::IMAGE_CREATE_MUTABLE_FORMAT_BIT
+        } else {
+            flags
+        };
 
         // Checking the dimensions against the limits.
         if array_layers > device.physical_device().limits().max_image_array_layers() {
@@ -469,9 +576,22 @@ impl UnsafeImage {
 
         // Everything now ok. Creating the image.
         let image = {
+            let external_infos = if !external_handle_types.is_empty() {
+                Some(vk::ExternalMemoryImageCreateInfoKHR {
+                         sType: vk::STRUCTURE_TYPE_EXTERNAL_MEMORY_IMAGE_CREATE_INFO_KHR,
+                         pNext: ptr::null(),
+                         handleTypes: external_handle_types.to_bits(),
+                     })
+            } else {
+                None
+            };
+
             let infos = vk::ImageCreateInfo {
                 sType: vk::STRUCTURE_TYPE_IMAGE_CREATE_INFO,
-                pNext: ptr::null(),
+                pNext: external_infos
+                    .as_ref()
+                    .map(|i| i as *const vk::ExternalMemoryImageCreateInfoKHR)
+                    .unwrap_or(ptr::null()) as *const _,
                 flags: flags,
                 imageType: ty,
                 format: format as u32,
@@ -503,14 +623,18 @@ impl UnsafeImage {
             output.assume_init()
         };
 
-        let mem_reqs = if device.loaded_extensions().khr_get_memory_requirements2 {
+        let mem_reqs = if device.api_version() >= Version::V1_1 ||
+            device.loaded_extensions().khr_get_memory_requirements2
+        {
             let infos = vk::ImageMemoryRequirementsInfo2KHR {
                 sType: vk::STRUCTURE_TYPE_IMAGE_MEMORY_REQUIREMENTS_INFO_2_KHR,
                 pNext: ptr::null_mut(),
                 image: image,
             };
 
-            let mut output2 = if device.loaded_extensions().khr_dedicated_allocation {
+            let mut output2 = if device.api_version() >= Version::V1_1 ||
+                device.loaded_extensions().khr_dedicated_allocation
+            {
                 Some(vk::MemoryDedicatedRequirementsKHR {
                          sType: vk::STRUCTURE_TYPE_MEMORY_DEDICATED_REQUIREMENTS_KHR,
                          pNext: ptr::null(),
@@ -535,8 +659,11 @@ impl UnsafeImage {
 
             let mut out = MemoryRequirements::from_vulkan_reqs(output.memoryRequirements);
             if let Some(output2) = output2 {
-                debug_assert_eq!(output2.requiresDedicatedAllocation, 0);
-                out.prefer_dedicated = output2.prefersDedicatedAllocation != 0;
+                // Only external memory can require a dedicated allocation.
+                debug_assert!(output2.requiresDedicatedAllocation == 0 ||
+                                  !external_handle_types.is_empty());
+                out.prefer_dedicated = output2.prefersDedicatedAllocation != 0 ||
+                    output2.requiresDedicatedAllocation != 0;
             }
             out
 
@@ -556,9 +683,12 @@ impl UnsafeImage {
             dimensions: dimensions,
             samples: num_samples,
             mipmaps: mipmaps,
+            mutable_format,
             format_features: format_features,
             needs_destruction: true,
             preinitialized_layout,
+            concurrent: sh_mode == vk::SHARING_MODE_CONCURRENT,
+            ownership: QueueFamilyOwnership::default(),
         };
 
         Ok((image, mem_reqs))
@@ -586,12 +716,21 @@ impl UnsafeImage {
             dimensions: dimensions,
             samples: samples,
             mipmaps: mipmaps,
+            mutable_format: false,
             format_features: output.assume_init().optimalTilingFeatures,
             needs_destruction: false, // TODO: pass as parameter
             preinitialized_layout: false, // TODO: Maybe this should be passed in?
+            concurrent: false,
+            ownership: QueueFamilyOwnership::default(),
         }
     }
 
+    // Marks an image created with `from_raw` as having a concurrent sharing mode.
+    #[inline]
+    pub(crate) fn set_concurrent_sharing(&mut self) {
+        self.concurrent = true;
+    }
+
     pub unsafe fn bind_memory(&self, memory: &DeviceMemory, offset: usize) -> Result<(), OomError> {
         let vk = self.device.pointers();
 
@@ -630,6 +769,13 @@ impl UnsafeImage {
         self.mipmaps
     }
 
+    /// Returns true if the image has been created with `with_mutable_format`, in which case its
+    /// views can use a different format than the image.
+    #[inline]
+    pub fn mutable_format(&self) -> bool {
+        self.mutable_format
+    }
+
     #[inline]
     pub fn dimensions(&self) -> ImageDimensions {
         self.dimensions
@@ -795,6 +941,18 @@ impl UnsafeImage {
     pub fn preinitialized_layout(&self) -> bool {
         self.preinitialized_layout
     }
+
+    /// Returns true if the image was created with `Sharing::Concurrent`.
+    #[inline]
+    pub fn concurrent_sharing(&self) -> bool {
+        self.concurrent
+    }
+
+    // Returns the queue family ownership of the image.
+    #[inline]
+    pub(crate) fn ownership(&self) -> &QueueFamilyOwnership {
+        &self.ownership
+    }
 }
 
 unsafe impl VulkanObject for UnsafeImage {
@@ -808,6 +966,13 @@ unsafe impl VulkanObject for UnsafeImage {
     }
 }
 
+unsafe impl DeviceOwned for UnsafeImage {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+}
+
 impl fmt::Debug for UnsafeImage {
     #[inline]
     fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
@@ -966,9 +1131,30 @@ pub struct UnsafeImageView {
 
 impl UnsafeImageView {
     /// See the docs of new().
+    #[inline]
     pub unsafe fn raw(image: &UnsafeImage, ty: ViewType, mipmap_levels: Range<u32>,
                       array_layers: Range<u32>)
                       -> Result<UnsafeImageView, OomError> {
+        UnsafeImageView::raw_with(image,
+                                  ty,
+                                  mipmap_levels,
+                                  array_layers,
+                                  image.format,
+                                  Swizzle::default())
+    }
+
+    /// Same as `raw`, but allows choosing the format and the swizzling of the view.
+    ///
+    /// If `format` is different from the format of the image, the image must have been created
+    /// with `UnsafeImage::with_mutable_format` and both formats must have the same size.
+    ///
+    /// # Panic
+    ///
+    /// - Panics for the same reasons as `new`.
+    ///
+    pub unsafe fn raw_with(image: &UnsafeImage, ty: ViewType, mipmap_levels: Range<u32>,
+                           array_layers: Range<u32>, format: Format, swizzle: Swizzle)
+                           -> Result<UnsafeImageView, OomError> {
         let vk = image.device.pointers();
 
         assert!(mipmap_levels.end > mipmap_levels.start);
@@ -976,7 +1162,7 @@ impl UnsafeImageView {
         assert!(array_layers.end > array_layers.start);
         assert!(array_layers.end <= image.dimensions.array_layers());
 
-        let aspect_mask = match image.format.ty() {
+        let aspect_mask = match format.ty() {
             FormatTy::Float | FormatTy::Uint | FormatTy::Sint | FormatTy::Compressed => {
                 vk::IMAGE_ASPECT_COLOR_BIT
             },
@@ -1013,13 +1199,13 @@ impl UnsafeImageView {
                 flags: 0, // reserved
                 image: image.internal_object(),
                 viewType: view_type,
-                format: image.format as u32,
+                format: format as u32,
                 components: vk::ComponentMapping {
-                    r: 0,
-                    g: 0,
-                    b: 0,
-                    a: 0,
-                }, // FIXME:
+                    r: swizzle.r as u32,
+                    g: swizzle.g as u32,
+                    b: swizzle.b as u32,
+                    a: swizzle.a as u32,
+                },
                 subresourceRange: vk::ImageSubresourceRange {
                     aspectMask: aspect_mask,
                     baseMipLevel: mipmap_levels.start,
@@ -1041,8 +1227,8 @@ impl UnsafeImageView {
                view: view,
                device: image.device.clone(),
                usage: image.usage,
-               identity_swizzle: true, // FIXME:
-               format: image.format,
+               identity_swizzle: swizzle == Swizzle::default(),
+               format: format,
            })
     }
 
@@ -1073,6 +1259,12 @@ impl UnsafeImageView {
         self.format
     }
 
+    /// Returns true if the view doesn't use components swizzling.
+    #[inline]
+    pub fn identity_swizzle(&self) -> bool {
+        self.identity_swizzle
+    }
+
     #[inline]
     pub fn usage_transfer_source(&self) -> bool {
         (self.usage & vk::IMAGE_USAGE_TRANSFER_SRC_BIT) != 0
@@ -1492,4 +1684,119 @@ mod tests {
             _ => panic!(),
         };
     }
+
+    #[test]
+    #[cfg(unix)]
+    fn external_memory_shared_between_devices() {
+        use device::Device;
+        use device::DeviceExtensions;
+        use features::Features;
+        use instance::Instance;
+        use instance::InstanceExtensions;
+        use instance::PhysicalDevice;
+        use memory::DedicatedAlloc;
+        use memory::DeviceMemory;
+        use memory::ExternalMemoryHandleType;
+
+        let instance_extensions = InstanceExtensions {
+            khr_get_physical_device_properties2: true,
+            khr_external_memory_capabilities: true,
+            ..InstanceExtensions::none()
+        };
+        let instance = match Instance::new(None, &instance_extensions, None) {
+            Ok(i) => i,
+            Err(_) => return,
+        };
+
+        let physical = match PhysicalDevice::enumerate(&instance).next() {
+            Some(p) => p,
+            None => return,
+        };
+        let queue = physical.queue_families().next().unwrap();
+
+        let extensions = DeviceExtensions {
+            khr_external_memory: true,
+            khr_external_memory_fd: true,
+            khr_get_memory_requirements2: true,
+            khr_dedicated_allocation: true,
+            ..DeviceExtensions::none()
+        };
+        if DeviceExtensions::supported_by_device(physical).intersection(&extensions) != extensions {
+            return;
+        }
+
+        let usage = ImageUsage {
+            sampled: true,
+            transfer_destination: true,
+            ..ImageUsage::none()
+        };
+        let dimensions = ImageDimensions::Dim2d {
+            width: 32,
+            height: 32,
+            array_layers: 1,
+            cubemap_compatible: false,
+        };
+        let handle_type = ExternalMemoryHandleType::posix();
+
+        match physical.external_image_format_properties(Format::R8G8B8A8Unorm, dimensions,
+                                                        usage, false, handle_type) {
+            Some(ref props) if props.exportable && props.importable => (),
+            _ => return,
+        }
+
+        let create_device = || {
+            Device::new(physical, &Features::none(), &extensions, [(queue, 0.5)].iter().cloned())
+                .unwrap()
+                .0
+        };
+        let device1 = create_device();
+        let device2 = create_device();
+
+        let create_image = |device| unsafe {
+            UnsafeImage::with_external_memory(device,
+                                              usage,
+                                              Format::R8G8B8A8Unorm,
+                                              dimensions,
+                                              1,
+                                              1,
+                                              Sharing::Exclusive::<Empty<_>>,
+                                              false,
+                                              false,
+                                              handle_type)
+                .unwrap()
+        };
+        let (image1, reqs1) = create_image(device1.clone());
+        let (image2, reqs2) = create_image(device2.clone());
+
+        let mem_ty1 = physical
+            .memory_types()
+            .find(|t| (reqs1.memory_type_bits & (1 << t.id())) != 0)
+            .unwrap();
+        let memory1 = DeviceMemory::dedicated_alloc_exportable(device1.clone(),
+                                                               mem_ty1,
+                                                               reqs1.size,
+                                                               DedicatedAlloc::Image(&image1),
+                                                               handle_type)
+            .unwrap();
+        unsafe {
+            image1.bind_memory(&memory1, 0).unwrap();
+        }
+
+        let file = memory1.export_fd(handle_type).unwrap();
+
+        let mem_ty2 = physical.memory_type_by_id(mem_ty1.id()).unwrap();
+        assert!((reqs2.memory_type_bits & (1 << mem_ty2.id())) != 0);
+        let memory2 = unsafe {
+            DeviceMemory::import_fd(device2.clone(),
+                                    mem_ty2,
+                                    reqs2.size,
+                                    DedicatedAlloc::Image(&image2),
+                                    handle_type,
+                                    file)
+                .unwrap()
+        };
+        unsafe {
+            image2.bind_memory(&memory2, 0).unwrap();
+        }
+    }
 }
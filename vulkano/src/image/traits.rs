@@ -7,6 +7,7 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+use std::ffi::CStr;
 use std::hash::Hash;
 use std::hash::Hasher;
 
@@ -28,6 +29,7 @@ use image::sys::UnsafeImageView;
 use sampler::Sampler;
 use sync::AccessError;
 
+use OomError;
 use SafeDeref;
 
 /// Trait for types that represent the way a GPU can access an image.
@@ -232,6 +234,15 @@ pub unsafe trait ImageAccess {
     /// - The transitioned layout must not be `Undefined`.
     ///
     unsafe fn unlock(&self, transitioned_layout: Option<ImageLayout>);
+
+    /// Assigns a human-readable name to the underlying image object for debugging purposes.
+    ///
+    /// Does nothing if the `VK_EXT_debug_utils` extension isn't enabled on the instance.
+    #[inline]
+    fn set_name(&self, name: &CStr) -> Result<(), OomError> {
+        let image = self.inner().image;
+        image.device().set_object_name(image, name)
+    }
 }
 
 /// Inner information about an image.
@@ -18,6 +18,7 @@ use vk;
 /// and `input_attachment` can be true as well. The rest must be false or an error will be returned
 /// when creating the image.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ImageUsage {
     /// Can be used as a source for transfers. Includes blits.
     pub transfer_source: bool,
@@ -155,3 +156,18 @@ impl BitOr for ImageUsage {
         }
     }
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use image::ImageUsage;
+
+    #[test]
+    fn serde_round_trip() {
+        assert_serde_round_trip!(ImageUsage::none());
+        assert_serde_round_trip!(ImageUsage {
+            sampled: true,
+            color_attachment: true,
+            .. ImageUsage::none()
+        });
+    }
+}
@@ -0,0 +1,587 @@
+// Copyright (c) 2020 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::error;
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+
+use buffer::BufferAccess;
+use format::Format;
+use format::FormatTy;
+use image::Dimensions;
+use image::ImageDimensions;
+use image::ImageInner;
+use image::ImageLayout;
+use image::Swizzle;
+use image::ViewType;
+use image::sys::UnsafeImageView;
+use image::traits::ImageAccess;
+use image::traits::ImageViewAccess;
+use sync::AccessError;
+
+use OomError;
+
+/// A view of a sub-range of an image, optionally with a different format or swizzling.
+///
+/// The high-level image types each maintain a single view of the whole image. An `ImageView`
+/// wraps one of these images and creates another view of it, for example to access a single
+/// layer of an array image, a single mipmap level, to sample a 2D array image as a cubemap, or
+/// to reinterpret the content of the image with a different format.
+///
+/// Since `ImageView` implements `ImageViewAccess`, it can be used anywhere the original image
+/// could, such as in a descriptor set or a framebuffer. It also implements `ImageAccess` by
+/// forwarding everything to the wrapped image.
+///
+/// # Example
+///
+/// ```
+/// use vulkano::format::Format;
+/// use vulkano::image::Dimensions;
+/// use vulkano::image::ImageView;
+/// use vulkano::image::StorageImage;
+///
+/// # let device: std::sync::Arc<vulkano::device::Device> = return;
+/// # let queue: std::sync::Arc<vulkano::device::Queue> = return;
+/// let image = StorageImage::new(device.clone(),
+///                               Dimensions::Dim2dArray { width: 256, height: 256,
+///                                                        array_layers: 4 },
+///                               Format::R8G8B8A8Unorm, Some(queue.family())).unwrap();
+///
+/// // View of the third layer only.
+/// let layer = ImageView::start(image.clone()).array_layers(2 .. 3).build().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct ImageView<I> {
+    image: I,
+    view: UnsafeImageView,
+    dimensions: Dimensions,
+    mipmap_levels: Range<u32>,
+    array_layers: Range<u32>,
+}
+
+impl<I> ImageView<I>
+    where I: ImageAccess
+{
+    /// Creates a view of the whole image, with the same format as the image and the view type
+    /// that corresponds to its dimensions.
+    #[inline]
+    pub fn new(image: I) -> Result<Arc<ImageView<I>>, ImageViewCreationError> {
+        ImageView::start(image).build()
+    }
+
+    /// Starts building a view of `image`. By default the view covers the whole image, with the
+    /// same format as the image and the view type that corresponds to its dimensions.
+    #[inline]
+    pub fn start(image: I) -> ImageViewBuilder<I> {
+        ImageViewBuilder {
+            image,
+            ty: None,
+            mipmap_levels: None,
+            array_layers: None,
+            format: None,
+            swizzle: Swizzle::default(),
+        }
+    }
+
+    /// Returns the wrapped image.
+    #[inline]
+    pub fn image(&self) -> &I {
+        &self.image
+    }
+
+    /// Returns the range of mipmap levels of the image covered by the view.
+    ///
+    /// The range is relative to the mipmap levels exposed by the wrapped image.
+    #[inline]
+    pub fn mipmap_levels(&self) -> Range<u32> {
+        self.mipmap_levels.clone()
+    }
+
+    /// Returns the range of array layers of the image covered by the view.
+    ///
+    /// The range is relative to the array layers exposed by the wrapped image.
+    #[inline]
+    pub fn array_layers(&self) -> Range<u32> {
+        self.array_layers.clone()
+    }
+}
+
+/// Prototype of an `ImageView`.
+///
+/// Obtained with `ImageView::start()`.
+pub struct ImageViewBuilder<I> {
+    image: I,
+    ty: Option<ViewType>,
+    mipmap_levels: Option<Range<u32>>,
+    array_layers: Option<Range<u32>>,
+    format: Option<Format>,
+    swizzle: Swizzle,
+}
+
+impl<I> ImageViewBuilder<I>
+    where I: ImageAccess
+{
+    /// Sets the type of the view.
+    ///
+    /// The default depends on the dimensions of the image and on the number of array layers
+    /// covered by the view. Cubemap views are never chosen by default.
+    #[inline]
+    pub fn ty(mut self, ty: ViewType) -> ImageViewBuilder<I> {
+        self.ty = Some(ty);
+        self
+    }
+
+    /// Sets the range of mipmap levels covered by the view, relative to the mipmap levels of the
+    /// image. The default is all the mipmap levels.
+    #[inline]
+    pub fn mipmap_levels(mut self, levels: Range<u32>) -> ImageViewBuilder<I> {
+        self.mipmap_levels = Some(levels);
+        self
+    }
+
+    /// Sets the range of array layers covered by the view, relative to the array layers of the
+    /// image. The default is all the array layers.
+    #[inline]
+    pub fn array_layers(mut self, layers: Range<u32>) -> ImageViewBuilder<I> {
+        self.array_layers = Some(layers);
+        self
+    }
+
+    /// Sets the format of the view. The default is the format of the image.
+    ///
+    /// If the format is different from the format of the image, the image must have been
+    /// created with a mutable format and both formats must be color formats of the same size.
+    #[inline]
+    pub fn format(mut self, format: Format) -> ImageViewBuilder<I> {
+        self.format = Some(format);
+        self
+    }
+
+    /// Sets the swizzling of the components of the view. The default is the identity.
+    #[inline]
+    pub fn swizzle(mut self, swizzle: Swizzle) -> ImageViewBuilder<I> {
+        self.swizzle = swizzle;
+        self
+    }
+
+    /// Checks the parameters and builds the view.
+    pub fn build(self) -> Result<Arc<ImageView<I>>, ImageViewCreationError> {
+        let (view, dimensions, mipmap_levels, array_layers) = {
+            let inner = self.image.inner();
+            let num_levels = inner.num_mipmap_levels as u32;
+            let num_layers = inner.num_layers as u32;
+
+            let mipmap_levels = self.mipmap_levels.unwrap_or(0 .. num_levels);
+            if mipmap_levels.start >= mipmap_levels.end || mipmap_levels.end > num_levels {
+                return Err(ImageViewCreationError::MipmapLevelsOutOfRange);
+            }
+
+            let array_layers = self.array_layers.unwrap_or(0 .. num_layers);
+            if array_layers.start >= array_layers.end || array_layers.end > num_layers {
+                return Err(ImageViewCreationError::ArrayLayersOutOfRange);
+            }
+
+            let image_dims = inner.image.dimensions();
+            let layer_count = array_layers.end - array_layers.start;
+            let ty = match self.ty {
+                Some(ty) => ty,
+                None => default_view_type(image_dims, layer_count),
+            };
+            check_view_type(image_dims, ty, layer_count)?;
+
+            let format = self.format.unwrap_or(inner.image.format());
+            check_format(inner.image.format(), format, inner.image.mutable_format())?;
+
+            let first_level = inner.first_mipmap_level as u32 + mipmap_levels.start;
+            let first_layer = inner.first_layer as u32 + array_layers.start;
+            let level_dims = image_dims.mipmap_dimensions(first_level).unwrap();
+            let dimensions = view_dimensions(level_dims, ty, layer_count);
+
+            let view = unsafe {
+                UnsafeImageView::raw_with(inner.image,
+                                          ty,
+                                          first_level .. first_level + mipmap_levels.len() as u32,
+                                          first_layer .. first_layer + layer_count,
+                                          format,
+                                          self.swizzle)?
+            };
+
+            (view, dimensions, mipmap_levels, array_layers)
+        };
+
+        Ok(Arc::new(ImageView {
+                        image: self.image,
+                        view,
+                        dimensions,
+                        mipmap_levels,
+                        array_layers,
+                    }))
+    }
+}
+
+// Returns the view type to use when none has been specified.
+fn default_view_type(dims: ImageDimensions, layer_count: u32) -> ViewType {
+    match dims {
+        ImageDimensions::Dim1d { .. } if layer_count == 1 => ViewType::Dim1d,
+        ImageDimensions::Dim1d { .. } => ViewType::Dim1dArray,
+        ImageDimensions::Dim2d { .. } if layer_count == 1 => ViewType::Dim2d,
+        ImageDimensions::Dim2d { .. } => ViewType::Dim2dArray,
+        ImageDimensions::Dim3d { .. } => ViewType::Dim3d,
+    }
+}
+
+// Checks whether a view of type `ty` covering `layer_count` layers can be created from an image.
+fn check_view_type(dims: ImageDimensions, ty: ViewType, layer_count: u32)
+                   -> Result<(), ImageViewCreationError> {
+    let ok = match (dims, ty) {
+        (ImageDimensions::Dim1d { .. }, ViewType::Dim1d) => layer_count == 1,
+        (ImageDimensions::Dim1d { .. }, ViewType::Dim1dArray) => true,
+        (ImageDimensions::Dim2d { .. }, ViewType::Dim2d) => layer_count == 1,
+        (ImageDimensions::Dim2d { .. }, ViewType::Dim2dArray) => true,
+        (ImageDimensions::Dim2d { cubemap_compatible, .. }, ViewType::Cubemap) => {
+            cubemap_compatible && layer_count == 6
+        },
+        (ImageDimensions::Dim2d { cubemap_compatible, .. }, ViewType::CubemapArray) => {
+            cubemap_compatible && layer_count % 6 == 0
+        },
+        (ImageDimensions::Dim3d { .. }, ViewType::Dim3d) => true,
+        _ => false,
+    };
+
+    if ok {
+        Ok(())
+    } else {
+        Err(ImageViewCreationError::IncompatibleViewType)
+    }
+}
+
+// Checks whether an image of format `image_format` can be viewed with `view_format`.
+fn check_format(image_format: Format, view_format: Format, mutable_format: bool)
+                -> Result<(), ImageViewCreationError> {
+    if image_format == view_format {
+        return Ok(());
+    }
+
+    let is_color = |ty| ty == FormatTy::Float || ty == FormatTy::Uint || ty == FormatTy::Sint;
+
+    if !mutable_format || !is_color(image_format.ty()) || !is_color(view_format.ty()) {
+        return Err(ImageViewCreationError::IncompatibleFormat);
+    }
+
+    match (image_format.size(), view_format.size()) {
+        (Some(a), Some(b)) if a == b => Ok(()),
+        _ => Err(ImageViewCreationError::IncompatibleFormat),
+    }
+}
+
+// Builds the dimensions of a view of type `ty` of the given mipmap level.
+fn view_dimensions(level_dims: ImageDimensions, ty: ViewType, layer_count: u32) -> Dimensions {
+    let width = level_dims.width();
+    let height = level_dims.height();
+
+    match ty {
+        ViewType::Dim1d => Dimensions::Dim1d { width },
+        ViewType::Dim1dArray => Dimensions::Dim1dArray {
+            width,
+            array_layers: layer_count,
+        },
+        ViewType::Dim2d => Dimensions::Dim2d { width, height },
+        ViewType::Dim2dArray => Dimensions::Dim2dArray {
+            width,
+            height,
+            array_layers: layer_count,
+        },
+        ViewType::Dim3d => Dimensions::Dim3d {
+            width,
+            height,
+            depth: level_dims.depth(),
+        },
+        ViewType::Cubemap => Dimensions::Cubemap { size: width },
+        ViewType::CubemapArray => Dimensions::CubemapArray {
+            size: width,
+            array_layers: layer_count / 6,
+        },
+    }
+}
+
+unsafe impl<I> ImageViewAccess for ImageView<I>
+    where I: ImageAccess + ImageViewAccess
+{
+    #[inline]
+    fn parent(&self) -> &dyn ImageAccess {
+        &self.image
+    }
+
+    #[inline]
+    fn dimensions(&self) -> Dimensions {
+        self.dimensions
+    }
+
+    #[inline]
+    fn inner(&self) -> &UnsafeImageView {
+        &self.view
+    }
+
+    #[inline]
+    fn format(&self) -> Format {
+        self.view.format()
+    }
+
+    #[inline]
+    fn descriptor_set_storage_image_layout(&self) -> ImageLayout {
+        ImageViewAccess::descriptor_set_storage_image_layout(&self.image)
+    }
+
+    #[inline]
+    fn descriptor_set_combined_image_sampler_layout(&self) -> ImageLayout {
+        ImageViewAccess::descriptor_set_combined_image_sampler_layout(&self.image)
+    }
+
+    #[inline]
+    fn descriptor_set_sampled_image_layout(&self) -> ImageLayout {
+        ImageViewAccess::descriptor_set_sampled_image_layout(&self.image)
+    }
+
+    #[inline]
+    fn descriptor_set_input_attachment_layout(&self) -> ImageLayout {
+        ImageViewAccess::descriptor_set_input_attachment_layout(&self.image)
+    }
+
+    #[inline]
+    fn identity_swizzle(&self) -> bool {
+        self.view.identity_swizzle()
+    }
+}
+
+unsafe impl<I> ImageAccess for ImageView<I>
+    where I: ImageAccess
+{
+    #[inline]
+    fn inner(&self) -> ImageInner {
+        self.image.inner()
+    }
+
+    #[inline]
+    fn initial_layout_requirement(&self) -> ImageLayout {
+        self.image.initial_layout_requirement()
+    }
+
+    #[inline]
+    fn final_layout_requirement(&self) -> ImageLayout {
+        self.image.final_layout_requirement()
+    }
+
+    #[inline]
+    fn conflicts_buffer(&self, other: &dyn BufferAccess) -> bool {
+        self.image.conflicts_buffer(other)
+    }
+
+    #[inline]
+    fn conflicts_image(&self, other: &dyn ImageAccess) -> bool {
+        self.image.conflicts_image(other)
+    }
+
+    #[inline]
+    fn conflict_key(&self) -> u64 {
+        self.image.conflict_key()
+    }
+
+    #[inline]
+    fn try_gpu_lock(&self, exclusive_access: bool, expected_layout: ImageLayout)
+                    -> Result<(), AccessError> {
+        self.image.try_gpu_lock(exclusive_access, expected_layout)
+    }
+
+    #[inline]
+    unsafe fn increase_gpu_lock(&self) {
+        self.image.increase_gpu_lock()
+    }
+
+    #[inline]
+    unsafe fn unlock(&self, transitioned_layout: Option<ImageLayout>) {
+        self.image.unlock(transitioned_layout)
+    }
+
+    #[inline]
+    unsafe fn layout_initialized(&self) {
+        self.image.layout_initialized()
+    }
+
+    #[inline]
+    fn is_layout_initialized(&self) -> bool {
+        self.image.is_layout_initialized()
+    }
+}
+
+/// Error that can happen when creating an `ImageView`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ImageViewCreationError {
+    /// Not enough memory.
+    OomError(OomError),
+    /// The range of mipmap levels is empty or exceeds the mipmap levels of the image.
+    MipmapLevelsOutOfRange,
+    /// The range of array layers is empty or exceeds the array layers of the image.
+    ArrayLayersOutOfRange,
+    /// The view type is not compatible with the dimensions of the image or with the number of
+    /// array layers.
+    IncompatibleViewType,
+    /// The format of the view is not compatible with the format of the image.
+    IncompatibleFormat,
+}
+
+impl error::Error for ImageViewCreationError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            ImageViewCreationError::OomError(_) => "not enough memory",
+            ImageViewCreationError::MipmapLevelsOutOfRange =>
+                "the range of mipmap levels is empty or exceeds the mipmap levels of the image",
+            ImageViewCreationError::ArrayLayersOutOfRange =>
+                "the range of array layers is empty or exceeds the array layers of the image",
+            ImageViewCreationError::IncompatibleViewType =>
+                "the view type is not compatible with the dimensions of the image or with the \
+                 number of array layers",
+            ImageViewCreationError::IncompatibleFormat =>
+                "the format of the view is not compatible with the format of the image",
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&dyn error::Error> {
+        match *self {
+            ImageViewCreationError::OomError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ImageViewCreationError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<OomError> for ImageViewCreationError {
+    #[inline]
+    fn from(err: OomError) -> ImageViewCreationError {
+        ImageViewCreationError::OomError(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ImageView;
+    use super::ImageViewCreationError;
+    use format::Format;
+    use image::Dimensions;
+    use image::ImageUsage;
+    use image::ImageViewAccess;
+    use image::StorageImage;
+    use image::ViewType;
+
+    #[test]
+    fn single_layer_of_array() {
+        let (device, queue) = gfx_dev_and_queue!();
+        let image = StorageImage::new(device,
+                                      Dimensions::Dim2dArray {
+                                          width: 32,
+                                          height: 32,
+                                          array_layers: 4,
+                                      },
+                                      Format::R8G8B8A8Unorm,
+                                      Some(queue.family()))
+            .unwrap();
+
+        let view = ImageView::start(image).array_layers(2 .. 3).build().unwrap();
+        assert_eq!(view.dimensions(),
+                   Dimensions::Dim2d {
+                       width: 32,
+                       height: 32,
+                   });
+    }
+
+    #[test]
+    fn layers_out_of_range() {
+        let (device, queue) = gfx_dev_and_queue!();
+        let image = StorageImage::new(device,
+                                      Dimensions::Dim2d {
+                                          width: 32,
+                                          height: 32,
+                                      },
+                                      Format::R8G8B8A8Unorm,
+                                      Some(queue.family()))
+            .unwrap();
+
+        match ImageView::start(image).array_layers(0 .. 2).build() {
+            Err(ImageViewCreationError::ArrayLayersOutOfRange) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn cubemap_requires_compatible_image() {
+        let (device, queue) = gfx_dev_and_queue!();
+        let image = StorageImage::new(device,
+                                      Dimensions::Dim2dArray {
+                                          width: 32,
+                                          height: 32,
+                                          array_layers: 6,
+                                      },
+                                      Format::R8G8B8A8Unorm,
+                                      Some(queue.family()))
+            .unwrap();
+
+        match ImageView::start(image).ty(ViewType::Cubemap).build() {
+            Err(ImageViewCreationError::IncompatibleViewType) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn format_requires_mutable_image() {
+        let (device, queue) = gfx_dev_and_queue!();
+        let image = StorageImage::new(device,
+                                      Dimensions::Dim2d {
+                                          width: 32,
+                                          height: 32,
+                                      },
+                                      Format::R8G8B8A8Srgb,
+                                      Some(queue.family()))
+            .unwrap();
+
+        match ImageView::start(image).format(Format::R8G8B8A8Unorm).build() {
+            Err(ImageViewCreationError::IncompatibleFormat) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn reinterpret_mutable_format() {
+        let (device, queue) = gfx_dev_and_queue!();
+        let usage = ImageUsage {
+            sampled: true,
+            transfer_destination: true,
+            ..ImageUsage::none()
+        };
+        let image = StorageImage::with_mutable_format(device,
+                                                      Dimensions::Dim2d {
+                                                          width: 32,
+                                                          height: 32,
+                                                      },
+                                                      Format::R8G8B8A8Srgb,
+                                                      usage,
+                                                      Some(queue.family()))
+            .unwrap();
+
+        let view = ImageView::start(image).format(Format::R8G8B8A8Unorm).build().unwrap();
+        assert_eq!(ImageViewAccess::format(&view), Format::R8G8B8A8Unorm);
+    }
+}
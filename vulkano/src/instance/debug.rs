@@ -36,15 +36,44 @@
 //! be callable. If you don't store the return value of `DebugCallback`'s constructor in a
 //! variable, it will be immediately destroyed and your callback will not work.
 //!
+//! Applications that use the `log` crate can instead create the callback with
+//! `DebugCallback::log`, which forwards every message to it.
+//!
+//! # Strict validation
+//!
+//! In tests, a `StrictValidation` can be used instead of a callback to turn the errors reported
+//! by the validation layers into Rust errors. The layers call the callback from the thread that
+//! made the faulty Vulkan call, therefore errors are recorded per thread.
+//!
+//! ```
+//! # use vulkano::instance::Instance;
+//! # use std::sync::Arc;
+//! # let instance: Arc<Instance> = return;
+//! use vulkano::instance::debug::StrictValidation;
+//!
+//! let _strict = StrictValidation::collecting(&instance).unwrap();
+//! // ... use vulkano ...
+//! StrictValidation::assert_no_errors();
+//! ```
+//!
 
+use std::cell::RefCell;
 use std::error;
 use std::ffi::CStr;
 use std::fmt;
+use std::mem;
 use std::mem::MaybeUninit;
+use std::os::raw::c_char;
 use std::os::raw::c_void;
 use std::panic;
 use std::ptr;
+use std::slice;
 use std::sync::Arc;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::thread;
+
+use log::Level;
 
 use instance::Instance;
 
@@ -92,13 +121,29 @@ impl DebugCallback {
             unsafe {
                 let user_callback = user_data as *mut Box<dyn Fn()> as *const _;
                 let user_callback: &Box<dyn Fn(&Message)> = &*user_callback;
-
-                let layer_prefix = CStr::from_ptr((*callback_data).pMessageIdName)
-                    .to_str()
-                    .expect("debug callback message not utf-8");
-                let description = CStr::from_ptr((*callback_data).pMessage)
-                    .to_str()
-                    .expect("debug callback message not utf-8");
+                let data = &*callback_data;
+
+                let message_id_name = c_str(data.pMessageIdName);
+                let description = c_str(data.pMessage).unwrap_or("");
+
+                let objects = raw_slice(data.pObject, data.objectCount)
+                    .iter()
+                    .map(|object| {
+                             MessageObject {
+                                 ty: object.objectType,
+                                 handle: object.objectHandle,
+                                 name: c_str(object.pObjectName),
+                             }
+                         })
+                    .collect::<Vec<_>>();
+                let queue_labels = raw_slice(data.pQueueLabels, data.queueLabelCount)
+                    .iter()
+                    .map(|label| MessageLabel::from_raw(label))
+                    .collect::<Vec<_>>();
+                let command_buffer_labels = raw_slice(data.pCmdBufLabels, data.cmdBufLabelCount)
+                    .iter()
+                    .map(|label| MessageLabel::from_raw(label))
+                    .collect::<Vec<_>>();
 
                 let message = Message {
                     severity: MessageSeverity {
@@ -112,8 +157,13 @@ impl DebugCallback {
                         validation: (ty & vk::DEBUG_UTILS_MESSAGE_TYPE_VALIDATION_BIT_EXT) != 0,
                         performance: (ty & vk::DEBUG_UTILS_MESSAGE_TYPE_PERFORMANCE_BIT_EXT) != 0,
                     },
-                    layer_prefix,
+                    layer_prefix: message_id_name.unwrap_or(""),
+                    message_id_name,
+                    message_id_number: data.messageIdNumber,
                     description,
+                    objects: &objects,
+                    queue_labels: &queue_labels,
+                    command_buffer_labels: &command_buffer_labels,
                 };
 
                 // Since we box the closure, the type system doesn't detect that the `UnwindSafe`
@@ -195,6 +245,41 @@ impl DebugCallback {
     {
         DebugCallback::new(instance, MessageSeverity::errors_and_warnings(), MessageType::general(), user_callback)
     }
+
+    /// Initializes a debug callback that forwards the messages to the `log` crate.
+    ///
+    /// Shortcut for `new(instance, severity, ty, log_message)`.
+    #[inline]
+    pub fn log(instance: &Arc<Instance>, severity: MessageSeverity, ty: MessageType)
+               -> Result<DebugCallback, DebugCallbackCreationError> {
+        DebugCallback::new(instance, severity, ty, log_message)
+    }
+}
+
+// Turns a nullable C string into a `&str`. Returns `None` if the pointer is null or if the string
+// isn't valid UTF-8.
+unsafe fn c_str<'a>(ptr: *const c_char) -> Option<&'a str> {
+    if ptr.is_null() {
+        None
+    } else {
+        CStr::from_ptr(ptr).to_str().ok()
+    }
+}
+
+// Turns a pointer and a count into a slice. The pointer is allowed to be null if the count is 0.
+unsafe fn raw_slice<'a, T>(ptr: *const T, count: u32) -> &'a [T] {
+    if count == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(ptr, count as usize)
+    }
+}
+
+/// Forwards a message to the `log` crate, with a level chosen by `MessageSeverity::log_level`.
+///
+/// Messages are logged with the `vulkano::instance::debug` target.
+pub fn log_message(message: &Message) {
+    log!(target: "vulkano::instance::debug", message.severity.log_level(), "{}", message);
 }
 
 impl Drop for DebugCallback {
@@ -216,9 +301,133 @@ pub struct Message<'a> {
     /// Type of message,
     pub ty: MessageType,
     /// Prefix of the layer that reported this message.
+    ///
+    /// This is the same as `message_id_name`, or an empty string if there is none.
     pub layer_prefix: &'a str,
+    /// Name that identifies the kind of message, if any.
+    pub message_id_name: Option<&'a str>,
+    /// Number that identifies the kind of message. Its meaning depends on the layer.
+    pub message_id_number: i32,
     /// Description of the message.
     pub description: &'a str,
+    /// Vulkan objects involved in the message. The first one is the most relevant.
+    pub objects: &'a [MessageObject<'a>],
+    /// Labels that were active on the queue, from the innermost to the outermost.
+    pub queue_labels: &'a [MessageLabel<'a>],
+    /// Labels that were active in the command buffer, from the innermost to the outermost.
+    pub command_buffer_labels: &'a [MessageLabel<'a>],
+}
+
+impl<'a> fmt::Display for Message<'a> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self.message_id_name {
+            Some(name) => write!(fmt, "[{} ({:#x})] ", name, self.message_id_number)?,
+            None => write!(fmt, "[{:#x}] ", self.message_id_number)?,
+        }
+        write!(fmt, "{}", self.description)?;
+
+        if !self.objects.is_empty() {
+            write!(fmt, "\n  objects:")?;
+            for object in self.objects {
+                write!(fmt, " {}", object)?;
+            }
+        }
+        if !self.queue_labels.is_empty() {
+            write!(fmt, "\n  queue labels:")?;
+            for label in self.queue_labels {
+                write!(fmt, " \"{}\"", label.name)?;
+            }
+        }
+        if !self.command_buffer_labels.is_empty() {
+            write!(fmt, "\n  command buffer labels:")?;
+            for label in self.command_buffer_labels {
+                write!(fmt, " \"{}\"", label.name)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A Vulkan object involved in a message.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MessageObject<'a> {
+    /// Type of the object, as one of the `vk::OBJECT_TYPE_*` constants.
+    pub ty: vk::ObjectType,
+    /// Raw handle of the object.
+    pub handle: u64,
+    /// Name given to the object with `Device::set_object_name`, if any.
+    pub name: Option<&'a str>,
+}
+
+impl<'a> fmt::Display for MessageObject<'a> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match object_type_name(self.ty) {
+            Some(ty) => write!(fmt, "{}", ty)?,
+            None => write!(fmt, "object type {}", self.ty)?,
+        }
+        write!(fmt, " {:#x}", self.handle)?;
+        if let Some(name) = self.name {
+            write!(fmt, " \"{}\"", name)?;
+        }
+        Ok(())
+    }
+}
+
+// Returns the name of the Vulkan type corresponding to an object type.
+fn object_type_name(ty: vk::ObjectType) -> Option<&'static str> {
+    Some(match ty {
+        vk::OBJECT_TYPE_INSTANCE => "VkInstance",
+        vk::OBJECT_TYPE_PHYSICAL_DEVICE => "VkPhysicalDevice",
+        vk::OBJECT_TYPE_DEVICE => "VkDevice",
+        vk::OBJECT_TYPE_QUEUE => "VkQueue",
+        vk::OBJECT_TYPE_SEMAPHORE => "VkSemaphore",
+        vk::OBJECT_TYPE_COMMAND_BUFFER => "VkCommandBuffer",
+        vk::OBJECT_TYPE_FENCE => "VkFence",
+        vk::OBJECT_TYPE_DEVICE_MEMORY => "VkDeviceMemory",
+        vk::OBJECT_TYPE_BUFFER => "VkBuffer",
+        vk::OBJECT_TYPE_IMAGE => "VkImage",
+        vk::OBJECT_TYPE_EVENT => "VkEvent",
+        vk::OBJECT_TYPE_QUERY_POOL => "VkQueryPool",
+        vk::OBJECT_TYPE_BUFFER_VIEW => "VkBufferView",
+        vk::OBJECT_TYPE_IMAGE_VIEW => "VkImageView",
+        vk::OBJECT_TYPE_SHADER_MODULE => "VkShaderModule",
+        vk::OBJECT_TYPE_PIPELINE_CACHE => "VkPipelineCache",
+        vk::OBJECT_TYPE_PIPELINE_LAYOUT => "VkPipelineLayout",
+        vk::OBJECT_TYPE_RENDER_PASS => "VkRenderPass",
+        vk::OBJECT_TYPE_PIPELINE => "VkPipeline",
+        vk::OBJECT_TYPE_DESCRIPTOR_SET_LAYOUT => "VkDescriptorSetLayout",
+        vk::OBJECT_TYPE_SAMPLER => "VkSampler",
+        vk::OBJECT_TYPE_DESCRIPTOR_POOL => "VkDescriptorPool",
+        vk::OBJECT_TYPE_DESCRIPTOR_SET => "VkDescriptorSet",
+        vk::OBJECT_TYPE_FRAMEBUFFER => "VkFramebuffer",
+        vk::OBJECT_TYPE_COMMAND_POOL => "VkCommandPool",
+        vk::OBJECT_TYPE_SAMPLER_YCBCR_CONVERSION => "VkSamplerYcbcrConversion",
+        vk::OBJECT_TYPE_DESCRIPTOR_UPDATE_TEMPLATE => "VkDescriptorUpdateTemplate",
+        vk::OBJECT_TYPE_SURFACE_KHR => "VkSurfaceKHR",
+        vk::OBJECT_TYPE_SWAPCHAIN_KHR => "VkSwapchainKHR",
+        vk::OBJECT_TYPE_DISPLAY_KHR => "VkDisplayKHR",
+        vk::OBJECT_TYPE_DISPLAY_MODE_KHR => "VkDisplayModeKHR",
+        vk::OBJECT_TYPE_DEBUG_UTILS_MESSENGER_EXT => "VkDebugUtilsMessengerEXT",
+        _ => return None,
+    })
+}
+
+/// A debug label that was active when a message was emitted.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MessageLabel<'a> {
+    /// Name of the label.
+    pub name: &'a str,
+    /// Color of the label, or all zeroes if none was specified.
+    pub color: [f32; 4],
+}
+
+impl<'a> MessageLabel<'a> {
+    unsafe fn from_raw(label: &vk::DebugUtilsLabelEXT) -> MessageLabel<'a> {
+        MessageLabel {
+            name: c_str(label.pLabelName).unwrap_or(""),
+            color: label.color,
+        }
+    }
 }
 
 /// Severity of message.
@@ -265,6 +474,25 @@ impl MessageSeverity {
             verbose: false,
         }
     }
+
+    /// Returns the `log` level corresponding to the most severe field that is set.
+    ///
+    /// `error` maps to `Error`, `warning` to `Warn`, `information` to `Info` and `verbose` to
+    /// `Debug`. Returns `Trace` if no field is set.
+    #[inline]
+    pub fn log_level(&self) -> Level {
+        if self.error {
+            Level::Error
+        } else if self.warning {
+            Level::Warn
+        } else if self.information {
+            Level::Info
+        } else if self.verbose {
+            Level::Debug
+        } else {
+            Level::Trace
+        }
+    }
 }
 
 /// Type of message.
@@ -310,6 +538,163 @@ impl MessageType {
     }
 }
 
+/// Registration of a callback that records the errors reported by the validation layers.
+///
+/// The errors are recorded in a buffer that is local to the thread that made the Vulkan call that
+/// caused them. They can be retrieved with `take_errors()`.
+///
+/// A `StrictValidation` created with `panicking()` additionally makes vulkano panic as soon as it
+/// checks the result of a Vulkan call that caused an error, and when it is dropped while errors
+/// are still recorded. Note that Vulkan functions that don't return a result, such as the ones
+/// that record commands, are only checked at the next function that does.
+#[must_use = "The StrictValidation object must be kept alive for as long as you want errors to \
+              be recorded"]
+pub struct StrictValidation {
+    callback: DebugCallback,
+    panic: bool,
+}
+
+impl StrictValidation {
+    /// Records the errors reported by the validation layers, without panicking.
+    #[inline]
+    pub fn collecting(instance: &Arc<Instance>)
+                      -> Result<StrictValidation, DebugCallbackCreationError> {
+        StrictValidation::new(instance, false)
+    }
+
+    /// Records the errors reported by the validation layers and panics when vulkano notices them.
+    #[inline]
+    pub fn panicking(instance: &Arc<Instance>)
+                     -> Result<StrictValidation, DebugCallbackCreationError> {
+        StrictValidation::new(instance, true)
+    }
+
+    fn new(instance: &Arc<Instance>, panic: bool)
+           -> Result<StrictValidation, DebugCallbackCreationError> {
+        let callback = DebugCallback::new(instance,
+                                          MessageSeverity::errors(),
+                                          MessageType::all(),
+                                          move |msg| record_message(msg, panic))?;
+
+        if panic {
+            PANICKING_VALIDATIONS.fetch_add(1, Ordering::SeqCst);
+        }
+        Ok(StrictValidation { callback, panic })
+    }
+
+    /// Returns the errors recorded on the current thread and clears them.
+    #[inline]
+    pub fn take_errors() -> Vec<ValidationMessage> {
+        RECORDED_MESSAGES.with(|recorded| {
+            mem::take(&mut *recorded.borrow_mut())
+                .into_iter()
+                .map(|(message, _)| message)
+                .collect()
+        })
+    }
+
+    /// Panics if errors have been recorded on the current thread, and clears them.
+    pub fn assert_no_errors() {
+        let errors = StrictValidation::take_errors();
+        if !errors.is_empty() {
+            panic!("{}", format_errors(&errors));
+        }
+    }
+}
+
+impl Drop for StrictValidation {
+    #[inline]
+    fn drop(&mut self) {
+        if self.panic {
+            // Decremented first, so that vulkano stops checking for errors even if we panic below.
+            PANICKING_VALIDATIONS.fetch_sub(1, Ordering::SeqCst);
+            if !thread::panicking() {
+                panic_on_recorded_errors();
+            }
+        }
+    }
+}
+
+/// An error recorded by a `StrictValidation`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationMessage {
+    /// Severity of message.
+    pub severity: MessageSeverity,
+    /// Type of message.
+    pub ty: MessageType,
+    /// Prefix of the layer that reported this message.
+    pub layer_prefix: String,
+    /// Description of the message.
+    pub description: String,
+}
+
+impl fmt::Display for ValidationMessage {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "[{}] {}", self.layer_prefix, self.description)
+    }
+}
+
+// A message recorded by a `StrictValidation`, and whether it must cause a panic.
+type RecordedMessage = (ValidationMessage, bool);
+
+thread_local! {
+    static RECORDED_MESSAGES: RefCell<Vec<RecordedMessage>> = RefCell::new(Vec::new());
+}
+
+// Number of `StrictValidation`s created with `panicking()` that are alive. As long as it is 0,
+// checking the result of a Vulkan call doesn't need to look at `RECORDED_MESSAGES`.
+static PANICKING_VALIDATIONS: AtomicUsize = AtomicUsize::new(0);
+
+// Called from the debug callback of a `StrictValidation`.
+fn record_message(msg: &Message, panic: bool) {
+    let message = ValidationMessage {
+        severity: msg.severity,
+        ty: msg.ty,
+        layer_prefix: msg.layer_prefix.to_owned(),
+        description: msg.description.to_owned(),
+    };
+
+    RECORDED_MESSAGES.with(|recorded| recorded.borrow_mut().push((message, panic)));
+}
+
+fn format_errors(errors: &[ValidationMessage]) -> String {
+    let mut out = format!("{} validation error(s) reported:", errors.len());
+    for error in errors {
+        out.push_str(&format!("\n{}", error));
+    }
+    out
+}
+
+/// Panics if a `StrictValidation` created with `panicking()` recorded errors on the current
+/// thread.
+///
+/// Called after each Vulkan call whose result is checked.
+pub(crate) fn check_strict_validation() {
+    if PANICKING_VALIDATIONS.load(Ordering::Relaxed) == 0 || thread::panicking() {
+        return;
+    }
+
+    panic_on_recorded_errors();
+}
+
+// Panics if errors that should panic have been recorded on the current thread, and clears them.
+fn panic_on_recorded_errors() {
+    let errors = RECORDED_MESSAGES.with(|recorded| {
+        let mut recorded = recorded.borrow_mut();
+        if recorded.iter().any(|&(_, panic)| panic) {
+            Some(mem::take(&mut *recorded))
+        } else {
+            None
+        }
+    });
+
+    if let Some(errors) = errors {
+        let errors = errors.into_iter().map(|(message, _)| message).collect::<Vec<_>>();
+        panic!("{}", format_errors(&errors));
+    }
+}
+
 /// Error that can happen when creating a debug callback.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum DebugCallbackCreationError {
@@ -344,7 +729,177 @@ impl From<Error> for DebugCallbackCreationError {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::panic::AssertUnwindSafe;
+    use std::sync::Mutex;
+    use std::sync::MutexGuard;
     use std::thread;
+
+    use instance::InstanceExtensions;
+    use instance::loader::FunctionPointers;
+    use mock::MockIcd;
+
+    // Serializes the tests that change the number of panicking `StrictValidation`s.
+    fn lock_panicking_validations() -> MutexGuard<'static, ()> {
+        lazy_static! {
+            static ref LOCK: Mutex<()> = Mutex::new(());
+        }
+        LOCK.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn error_message(description: &str) -> Message<'_> {
+        Message {
+            severity: MessageSeverity::errors(),
+            ty: MessageType { validation: true, ..MessageType::none() },
+            layer_prefix: "test",
+            message_id_name: Some("test"),
+            message_id_number: 0,
+            description,
+            objects: &[],
+            queue_labels: &[],
+            command_buffer_labels: &[],
+        }
+    }
+
+    #[test]
+    fn message_display() {
+        let objects = [
+            MessageObject { ty: vk::OBJECT_TYPE_BUFFER, handle: 0x10, name: Some("vertices") },
+            MessageObject { ty: 12345, handle: 0x20, name: None },
+        ];
+        let labels = [MessageLabel { name: "frame", color: [0.0; 4] }];
+        let message = Message {
+            objects: &objects,
+            command_buffer_labels: &labels,
+            message_id_number: 0x1f,
+            ..error_message("bad usage")
+        };
+
+        assert_eq!(message.to_string(),
+                   "[test (0x1f)] bad usage\n  objects: VkBuffer 0x10 \"vertices\" \
+                    object type 12345 0x20\n  command buffer labels: \"frame\"");
+    }
+
+    #[test]
+    fn severity_log_level() {
+        assert_eq!(MessageSeverity::errors_and_warnings().log_level(), Level::Error);
+        assert_eq!(MessageSeverity { warning: true, ..MessageSeverity::none() }.log_level(),
+                   Level::Warn);
+        assert_eq!(MessageSeverity { information: true, ..MessageSeverity::none() }.log_level(),
+                   Level::Info);
+        assert_eq!(MessageSeverity { verbose: true, ..MessageSeverity::none() }.log_level(),
+                   Level::Debug);
+        assert_eq!(MessageSeverity::none().log_level(), Level::Trace);
+    }
+
+    #[test]
+    fn log_adapter() {
+        struct TestLogger;
+
+        thread_local! {
+            static LOGGED: RefCell<Vec<(Level, String, String)>> = RefCell::new(Vec::new());
+        }
+
+        impl log::Log for TestLogger {
+            fn enabled(&self, _: &log::Metadata) -> bool {
+                true
+            }
+
+            fn log(&self, record: &log::Record) {
+                let entry = (record.level(), record.target().to_owned(), record.args().to_string());
+                LOGGED.with(|logged| logged.borrow_mut().push(entry));
+            }
+
+            fn flush(&self) {
+            }
+        }
+
+        static LOGGER: TestLogger = TestLogger;
+        log::set_logger(&LOGGER).unwrap();
+        log::set_max_level(log::LevelFilter::Trace);
+
+        log_message(&error_message("bad call"));
+
+        let logged = LOGGED.with(|logged| logged.borrow().clone());
+        assert_eq!(logged,
+                   vec![(Level::Error,
+                         "vulkano::instance::debug".to_owned(),
+                         "[test (0x0)] bad call".to_owned())]);
+    }
+
+    #[test]
+    fn strict_validation_collect() {
+        record_message(&error_message("first"), false);
+        record_message(&error_message("second"), false);
+
+        // Errors recorded in collecting mode don't panic.
+        check_strict_validation();
+
+        let errors = StrictValidation::take_errors();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].description, "first");
+        assert_eq!(errors[1].to_string(), "[test] second");
+        assert!(StrictValidation::take_errors().is_empty());
+        StrictValidation::assert_no_errors();
+    }
+
+    #[test]
+    fn strict_validation_panic() {
+        let _lock = lock_panicking_validations();
+        // Stands for a `StrictValidation` created with `panicking()`.
+        PANICKING_VALIDATIONS.fetch_add(1, Ordering::SeqCst);
+        record_message(&error_message("bad call"), true);
+        assert_should_panic!("bad call", {
+            check_strict_validation();
+        });
+        PANICKING_VALIDATIONS.fetch_sub(1, Ordering::SeqCst);
+        assert!(StrictValidation::take_errors().is_empty());
+    }
+
+    #[test]
+    fn strict_validation_drop() {
+        let _lock = lock_panicking_validations();
+        let loader = FunctionPointers::new(Box::new(MockIcd) as Box<_>);
+        let extensions = InstanceExtensions { ext_debug_utils: true, ..InstanceExtensions::none() };
+        let instance = Instance::with_loader(loader, None, &extensions, None).unwrap();
+
+        // Dropping panics because of the recorded error, but still stops the checks.
+        let strict = AssertUnwindSafe(StrictValidation::panicking(&instance).unwrap());
+        assert_eq!(PANICKING_VALIDATIONS.load(Ordering::SeqCst), 1);
+        record_message(&error_message("bad call"), true);
+        assert_should_panic!("bad call", {
+            drop(strict);
+        });
+        assert_eq!(PANICKING_VALIDATIONS.load(Ordering::SeqCst), 0);
+
+        // Dropping while already panicking doesn't panic again, which would abort.
+        let strict = AssertUnwindSafe(StrictValidation::panicking(&instance).unwrap());
+        assert_should_panic!("unrelated", {
+            let _strict = strict;
+            record_message(&error_message("bad call"), true);
+            panic!("unrelated");
+        });
+        assert_eq!(PANICKING_VALIDATIONS.load(Ordering::SeqCst), 0);
+        assert_eq!(StrictValidation::take_errors().len(), 1);
+    }
+
+    #[test]
+    fn strict_validation_per_thread() {
+        record_message(&error_message("main thread"), true);
+        thread::spawn(|| {
+            check_strict_validation();
+            assert!(StrictValidation::take_errors().is_empty());
+        }).join().unwrap();
+        assert_eq!(StrictValidation::take_errors().len(), 1);
+    }
+
+    #[test]
+    fn strict_validation_instance() {
+        let instance = instance!();
+        // Fails if `VK_EXT_debug_utils` isn't enabled.
+        assert_eq!(StrictValidation::collecting(&instance).err(),
+                   Some(DebugCallbackCreationError::MissingExtension));
+    }
+
     #[test]
     fn ensure_sendable() {
         // It's useful to be able to initialize a DebugCallback on one thread
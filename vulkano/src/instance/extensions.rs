@@ -18,15 +18,76 @@ use check_errors;
 use instance::loader;
 use instance::loader::LoadingError;
 use extensions::SupportedExtensionsError;
+use instance::InstanceCreationError;
 use vk;
+use version::Version;
 
 macro_rules! instance_extensions {
-    ($sname:ident, $rawname:ident, $($ext:ident => $s:expr,)*) => (
+    ($sname:ident, $rawname:ident, $($ext:ident => $s:literal {
+        requires: [$($dep:ident),*],
+        promoted_to: $promoted:expr,
+    },)*) => (
         extensions! {
             $sname, $rawname,
             $( $ext => $s,)*
         }
 
+        impl $sname {
+            /// Returns the list of extensions whose functionality is part of the given version of
+            /// Vulkan.
+            pub fn promoted_to_core(api_version: Version) -> $sname {
+                let mut extensions = $sname::none();
+                $(
+                    let promoted: Option<Version> = $promoted;
+                    extensions.$ext = match promoted {
+                        Some(version) => version <= api_version,
+                        None => false,
+                    };
+                )*
+                extensions
+            }
+
+            /// Returns this list with the extensions that its extensions depend on added, except
+            /// for those that are part of `api_version`.
+            pub fn with_dependencies(&self, api_version: Version) -> $sname {
+                let core = $sname::promoted_to_core(api_version);
+                let mut extensions = *self;
+                loop {
+                    let mut next = extensions;
+                    $(
+                        if extensions.$ext {
+                            $(next.$dep |= !core.$dep;)*
+                        }
+                    )*
+                    if next == extensions {
+                        return extensions;
+                    }
+                    extensions = next;
+                }
+            }
+
+            /// Returns the extensions that the extensions of this list depend on but that aren't
+            /// in this list nor part of `api_version`.
+            #[inline]
+            pub fn missing_dependencies(&self, api_version: Version) -> $sname {
+                self.with_dependencies(api_version).difference(self)
+            }
+
+            /// Returns `MissingExtensionDependencies` if some of the extensions of this list
+            /// depend on extensions that aren't in this list nor part of `api_version`.
+            ///
+            /// `Instance::new` doesn't check the dependencies of the extensions it enables. Call
+            /// this function beforehand to get an error instead of relying on the implementation.
+            pub fn check_dependencies(&self, api_version: Version)
+                                      -> Result<(), InstanceCreationError> {
+                let missing = self.missing_dependencies(api_version);
+                if missing != $sname::none() {
+                    return Err(InstanceCreationError::MissingExtensionDependencies { missing });
+                }
+                Ok(())
+            }
+        }
+
         impl $rawname {
             /// See the docs of supported_by_core().
             pub fn supported_by_core_raw() -> Result<Self, SupportedExtensionsError> {
@@ -145,20 +206,78 @@ macro_rules! instance_extensions {
 instance_extensions! {
     InstanceExtensions,
     RawInstanceExtensions,
-    khr_surface => b"VK_KHR_surface",
-    khr_display => b"VK_KHR_display",
-    khr_xlib_surface => b"VK_KHR_xlib_surface",
-    khr_xcb_surface => b"VK_KHR_xcb_surface",
-    khr_wayland_surface => b"VK_KHR_wayland_surface",
-    khr_android_surface => b"VK_KHR_android_surface",
-    khr_win32_surface => b"VK_KHR_win32_surface",
-    ext_debug_utils => b"VK_EXT_debug_utils",
-    mvk_ios_surface => b"VK_MVK_ios_surface",
-    mvk_macos_surface => b"VK_MVK_macos_surface",
-    mvk_moltenvk => b"VK_MVK_moltenvk",     // TODO: confirm that it's an instance extension
-    nn_vi_surface => b"VK_NN_vi_surface",
-    ext_swapchain_colorspace => b"VK_EXT_swapchain_colorspace",
-    khr_get_physical_device_properties2 => b"VK_KHR_get_physical_device_properties2",
+    khr_surface => b"VK_KHR_surface" {
+        requires: [],
+        promoted_to: None,
+    },
+    khr_display => b"VK_KHR_display" {
+        requires: [khr_surface],
+        promoted_to: None,
+    },
+    khr_xlib_surface => b"VK_KHR_xlib_surface" {
+        requires: [khr_surface],
+        promoted_to: None,
+    },
+    khr_xcb_surface => b"VK_KHR_xcb_surface" {
+        requires: [khr_surface],
+        promoted_to: None,
+    },
+    khr_wayland_surface => b"VK_KHR_wayland_surface" {
+        requires: [khr_surface],
+        promoted_to: None,
+    },
+    khr_android_surface => b"VK_KHR_android_surface" {
+        requires: [khr_surface],
+        promoted_to: None,
+    },
+    khr_win32_surface => b"VK_KHR_win32_surface" {
+        requires: [khr_surface],
+        promoted_to: None,
+    },
+    ext_debug_utils => b"VK_EXT_debug_utils" {
+        requires: [],
+        promoted_to: None,
+    },
+    mvk_ios_surface => b"VK_MVK_ios_surface" {
+        requires: [khr_surface],
+        promoted_to: None,
+    },
+    mvk_macos_surface => b"VK_MVK_macos_surface" {
+        requires: [khr_surface],
+        promoted_to: None,
+    },
+    mvk_moltenvk => b"VK_MVK_moltenvk" { // TODO: confirm that it's an instance extension
+        requires: [],
+        promoted_to: None,
+    },
+    nn_vi_surface => b"VK_NN_vi_surface" {
+        requires: [khr_surface],
+        promoted_to: None,
+    },
+    ext_headless_surface => b"VK_EXT_headless_surface" {
+        requires: [khr_surface],
+        promoted_to: None,
+    },
+    ext_swapchain_colorspace => b"VK_EXT_swapchain_colorspace" {
+        requires: [khr_surface],
+        promoted_to: None,
+    },
+    khr_get_physical_device_properties2 => b"VK_KHR_get_physical_device_properties2" {
+        requires: [],
+        promoted_to: Some(Version::V1_1),
+    },
+    khr_external_memory_capabilities => b"VK_KHR_external_memory_capabilities" {
+        requires: [khr_get_physical_device_properties2],
+        promoted_to: Some(Version::V1_1),
+    },
+    khr_external_semaphore_capabilities => b"VK_KHR_external_semaphore_capabilities" {
+        requires: [khr_get_physical_device_properties2],
+        promoted_to: Some(Version::V1_1),
+    },
+    khr_external_fence_capabilities => b"VK_KHR_external_fence_capabilities" {
+        requires: [khr_get_physical_device_properties2],
+        promoted_to: Some(Version::V1_1),
+    },
 }
 
 /// This helper type can only be instantiated inside this module.
@@ -170,10 +289,42 @@ pub struct Unbuildable(());
 #[cfg(test)]
 mod tests {
     use instance::{InstanceExtensions, RawInstanceExtensions};
+    use version::Version;
 
     #[test]
     fn empty_extensions() {
         let i: RawInstanceExtensions = (&InstanceExtensions::none()).into();
         assert!(i.iter().next().is_none());
     }
+
+    #[test]
+    fn with_dependencies() {
+        let extensions = InstanceExtensions {
+            khr_xlib_surface: true,
+            .. InstanceExtensions::none()
+        };
+
+        let resolved = extensions.with_dependencies(Version::V1_0);
+        assert!(resolved.khr_surface);
+        assert!(resolved.khr_xlib_surface);
+        assert_eq!(extensions.missing_dependencies(Version::V1_0),
+                   InstanceExtensions { khr_surface: true, .. InstanceExtensions::none() });
+        assert_eq!(resolved.missing_dependencies(Version::V1_0), InstanceExtensions::none());
+    }
+
+    #[test]
+    fn dependencies_promoted_to_core() {
+        let extensions = InstanceExtensions {
+            khr_external_memory_capabilities: true,
+            .. InstanceExtensions::none()
+        };
+
+        assert!(extensions
+                    .missing_dependencies(Version::V1_0)
+                    .khr_get_physical_device_properties2);
+        assert_eq!(extensions.missing_dependencies(Version::V1_1), InstanceExtensions::none());
+        assert!(InstanceExtensions::promoted_to_core(Version::V1_1)
+                    .khr_get_physical_device_properties2);
+        assert_eq!(InstanceExtensions::promoted_to_core(Version::V1_0), InstanceExtensions::none());
+    }
 }
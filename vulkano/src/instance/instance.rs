@@ -9,6 +9,7 @@
 
 use smallvec::SmallVec;
 use std::borrow::Cow;
+use std::cmp;
 use std::error;
 use std::ffi::CStr;
 use std::ffi::CString;
@@ -27,15 +28,28 @@ use OomError;
 use VulkanObject;
 use check_errors;
 use instance::limits::Limits;
+use instance::properties::{DriverProperties, ExtendedProperties, IdProperties};
+use instance::properties::{Maintenance3Properties, PropertiesFfi, SubgroupProperties};
 use instance::loader;
 use instance::loader::FunctionPointers;
 use instance::loader::Loader;
 use instance::loader::LoadingError;
 use vk;
 
+use buffer::BufferUsage;
+use format::Format;
+use image::ImageDimensions;
+use image::ImageUsage;
 use instance::{InstanceExtensions, RawInstanceExtensions};
+use memory::ExternalMemoryHandleType;
+use memory::ExternalMemoryProperties;
+use sync::ExternalFenceHandleType;
+use sync::ExternalFenceProperties;
+use sync::ExternalSemaphoreHandleType;
+use sync::ExternalSemaphoreProperties;
 use version::Version;
-use features::Features;
+use device::DeviceExtensions;
+use features::{Features, FeaturesFfi};
 
 /// An instance of a Vulkan context. This is the main object that should be created by an
 /// application before everything else.
@@ -99,6 +113,7 @@ pub struct Instance {
     vk: vk::InstancePointers,
     extensions: RawInstanceExtensions,
     layers: SmallVec<[CString; 16]>,
+    api_version: Version,
     function_pointers: OwnedOrRef<FunctionPointers<Box<dyn Loader + Send + Sync>>>,
 }
 
@@ -126,6 +141,9 @@ impl Instance {
     /// };
     /// ```
     ///
+    /// The instance uses the highest version of Vulkan that is supported by both the loader and
+    /// vulkano. Use `with_api_version` to request a specific version instead.
+    ///
     /// # Panic
     ///
     /// - Panics if the version numbers passed in `ApplicationInfo` are too large can't be
@@ -145,6 +163,42 @@ impl Instance {
             .collect::<SmallVec<[_; 16]>>();
 
         Instance::new_inner(app_infos,
+                            None,
+                            extensions.into(),
+                            layers,
+                            OwnedOrRef::Ref(loader::auto_loader()?))
+    }
+
+    /// Same as `new`, but requests a specific version of Vulkan.
+    ///
+    /// Returns an error if the loader doesn't support this version. Note that the version that
+    /// is effectively usable with a device is the minimum between this version and the version
+    /// of the physical device. See `Device::api_version()`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use vulkano::instance::Instance;
+    /// use vulkano::instance::InstanceExtensions;
+    /// use vulkano::instance::Version;
+    ///
+    /// let instance = Instance::with_api_version(None, Version::V1_1,
+    ///                                           &InstanceExtensions::none(), None)
+    ///     .expect("Vulkan 1.1 is not supported");
+    /// ```
+    pub fn with_api_version<'a, L, Ext>(app_infos: Option<&ApplicationInfo>,
+                                        api_version: Version, extensions: Ext, layers: L)
+                                        -> Result<Arc<Instance>, InstanceCreationError>
+        where L: IntoIterator<Item = &'a str>,
+              Ext: Into<RawInstanceExtensions>
+    {
+        let layers = layers
+            .into_iter()
+            .map(|layer| CString::new(layer).unwrap())
+            .collect::<SmallVec<[_; 16]>>();
+
+        Instance::new_inner(app_infos,
+                            Some(api_version),
                             extensions.into(),
                             layers,
                             OwnedOrRef::Ref(loader::auto_loader()?))
@@ -163,15 +217,56 @@ impl Instance {
             .collect::<SmallVec<[_; 16]>>();
 
         Instance::new_inner(app_infos,
+                            None,
                             extensions.into(),
                             layers,
                             OwnedOrRef::Owned(loader))
     }
 
-    fn new_inner(app_infos: Option<&ApplicationInfo>, extensions: RawInstanceExtensions,
-                 layers: SmallVec<[CString; 16]>,
+    /// Same as `with_api_version`, but allows specifying a loader where to load Vulkan from.
+    pub fn with_loader_and_api_version<'a, L, Ext>(
+        loader: FunctionPointers<Box<dyn Loader + Send + Sync>>,
+        app_infos: Option<&ApplicationInfo>, api_version: Version, extensions: Ext, layers: L)
+        -> Result<Arc<Instance>, InstanceCreationError>
+        where L: IntoIterator<Item = &'a str>,
+              Ext: Into<RawInstanceExtensions>
+    {
+        let layers = layers
+            .into_iter()
+            .map(|layer| CString::new(layer).unwrap())
+            .collect::<SmallVec<[_; 16]>>();
+
+        Instance::new_inner(app_infos,
+                            Some(api_version),
+                            extensions.into(),
+                            layers,
+                            OwnedOrRef::Owned(loader))
+    }
+
+    fn new_inner(app_infos: Option<&ApplicationInfo>, api_version: Option<Version>,
+                 extensions: RawInstanceExtensions, layers: SmallVec<[CString; 16]>,
                  function_pointers: OwnedOrRef<FunctionPointers<Box<dyn Loader + Send + Sync>>>)
                  -> Result<Arc<Instance>, InstanceCreationError> {
+        // Vulkan 1.0 loaders reject any version other than 1.0, so we have to check what the
+        // loader supports before creating the instance.
+        let supported_version = function_pointers.api_version()?;
+        let api_version = match api_version {
+            Some(requested) => {
+                if requested > supported_version {
+                    return Err(InstanceCreationError::UnsupportedApiVersion {
+                                   requested,
+                                   supported: supported_version,
+                               });
+                }
+                requested
+            },
+            None => {
+                // TODO: bump this when vulkano supports newer versions
+                let max = Version::V1_2;
+                if supported_version > max { max } else { supported_version }
+            },
+        };
+
         // TODO: For now there are still buggy drivers that will segfault if you don't pass any
         //       appinfos. Therefore for now we ensure that it can't be `None`.
         let def = Default::default();
@@ -222,11 +317,7 @@ impl Instance {
                     .engine_version
                     .map(|v| v.into_vulkan_version())
                     .unwrap_or(0),
-                apiVersion: Version {
-                    major: 1,
-                    minor: 1,
-                    patch: 0,
-                }.into_vulkan_version(), // TODO:
+                apiVersion: api_version.into_vulkan_version(),
             })
 
         } else {
@@ -268,8 +359,10 @@ impl Instance {
         };
 
         // Loading the function pointers of the newly-created instance.
+        // If the instance supports a function as part of core Vulkan, we load the core version.
         let vk = {
             vk::InstancePointers::load(|name| unsafe {
+                let name = loader::core_function_name(name, api_version).unwrap_or(name);
                 mem::transmute(function_pointers.get_instance_proc_addr(instance, name.as_ptr()))
             })
         };
@@ -286,14 +379,31 @@ impl Instance {
         };
 
         let vk_khr_get_physical_device_properties2 = CString::new(b"VK_KHR_get_physical_device_properties2".to_vec()).unwrap();
+        let has_khr_get_physical_device_properties2 =
+            extensions.iter().any(|v| *v == vk_khr_get_physical_device_properties2);
 
         // Getting the properties of all physical devices.
-        // If possible, we use VK_KHR_get_physical_device_properties2.
-        let physical_devices = if extensions.iter().any(|v| *v == vk_khr_get_physical_device_properties2) {
-            Instance::init_physical_devices2(&vk, physical_devices, &extensions)
-        } else {
-            Instance::init_physical_devices(&vk, physical_devices)
-        };
+        // If possible, we use VK_KHR_get_physical_device_properties2 or its Vulkan 1.1 equivalent.
+        // The Vulkan 1.1 functions can only be used with a physical device that supports Vulkan
+        // 1.1 as well, so this is chosen for each device.
+        let physical_devices = physical_devices
+            .into_iter()
+            .map(|device| {
+                let properties: vk::PhysicalDeviceProperties = unsafe {
+                    let mut output = MaybeUninit::uninit();
+                    vk.GetPhysicalDeviceProperties(device, output.as_mut_ptr());
+                    output.assume_init()
+                };
+                let device_api_version =
+                    cmp::min(Version::from_vulkan_version(properties.apiVersion), api_version);
+
+                if device_api_version >= Version::V1_1 || has_khr_get_physical_device_properties2 {
+                    Instance::init_physical_device2(&vk, device, &extensions, device_api_version)
+                } else {
+                    Instance::init_physical_device(&vk, device, properties)
+                }
+            })
+            .collect();
 
         Ok(Arc::new(Instance {
                         instance: instance,
@@ -302,127 +412,116 @@ impl Instance {
                         vk: vk,
                         extensions: extensions,
                         layers: layers,
+                        api_version: api_version,
                         function_pointers: function_pointers,
                     }))
     }
 
-    /// Initialize all physical devices
-    fn init_physical_devices(vk: &vk::InstancePointers, physical_devices: Vec<vk::PhysicalDevice>)
-                             -> Vec<PhysicalDeviceInfos> {
-        let mut output = Vec::with_capacity(physical_devices.len());
-
-        for device in physical_devices.into_iter() {
-            let properties: vk::PhysicalDeviceProperties = unsafe {
-                let mut output = MaybeUninit::uninit();
-                vk.GetPhysicalDeviceProperties(device, output.as_mut_ptr());
-                output.assume_init()
-            };
-
-            let queue_families = unsafe {
-                let mut num = 0;
-                vk.GetPhysicalDeviceQueueFamilyProperties(device, &mut num, ptr::null_mut());
+    /// Initialize a physical device
+    fn init_physical_device(vk: &vk::InstancePointers, device: vk::PhysicalDevice,
+                            properties: vk::PhysicalDeviceProperties)
+                            -> PhysicalDeviceInfos {
+        let queue_families = unsafe {
+            let mut num = 0;
+            vk.GetPhysicalDeviceQueueFamilyProperties(device, &mut num, ptr::null_mut());
 
-                let mut families = Vec::with_capacity(num as usize);
-                vk.GetPhysicalDeviceQueueFamilyProperties(device, &mut num, families.as_mut_ptr());
-                families.set_len(num as usize);
-                families
-            };
+            let mut families = Vec::with_capacity(num as usize);
+            vk.GetPhysicalDeviceQueueFamilyProperties(device, &mut num, families.as_mut_ptr());
+            families.set_len(num as usize);
+            families
+        };
 
-            let memory: vk::PhysicalDeviceMemoryProperties = unsafe {
-                let mut output = MaybeUninit::uninit();
-                vk.GetPhysicalDeviceMemoryProperties(device, output.as_mut_ptr());
-                output.assume_init()
-            };
+        let memory: vk::PhysicalDeviceMemoryProperties = unsafe {
+            let mut output = MaybeUninit::uninit();
+            vk.GetPhysicalDeviceMemoryProperties(device, output.as_mut_ptr());
+            output.assume_init()
+        };
 
-            let available_features: vk::PhysicalDeviceFeatures = unsafe {
-                let mut output = MaybeUninit::uninit();
-                vk.GetPhysicalDeviceFeatures(device, output.as_mut_ptr());
-                output.assume_init()
-            };
+        let available_features = unsafe {
+            let mut output = FeaturesFfi::new(Version::V1_0, &DeviceExtensions::none());
+            vk.GetPhysicalDeviceFeatures(device, &mut output.main.features);
+            output
+        };
 
-            output.push(PhysicalDeviceInfos {
-                            device: device,
-                            properties: properties,
-                            memory: memory,
-                            queue_families: queue_families,
-                            available_features: Features::from_vulkan_features(available_features),
-                        });
+        PhysicalDeviceInfos {
+            device: device,
+            properties: properties,
+            memory: memory,
+            queue_families: queue_families,
+            available_features: Features::from_vulkan_features(&available_features),
+            extended_properties: Default::default(),
         }
-        output
-    }
-
-    /// Initialize all physical devices, but use VK_KHR_get_physical_device_properties2
-    /// TODO: Query extension-specific physical device properties, once a new instance extension is supported.
-    fn init_physical_devices2(vk: &vk::InstancePointers,
-                              physical_devices: Vec<vk::PhysicalDevice>,
-                              extensions: &RawInstanceExtensions)
-                              -> Vec<PhysicalDeviceInfos> {
-        let mut output = Vec::with_capacity(physical_devices.len());
-
-        for device in physical_devices.into_iter() {
-            let properties: vk::PhysicalDeviceProperties = unsafe {
-                let mut output = vk::PhysicalDeviceProperties2KHR {
-                    sType: vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_PROPERTIES_2_KHR,
-                    pNext: ptr::null_mut(),
-                    properties: mem::zeroed(),
-                };
+    }
 
-                vk.GetPhysicalDeviceProperties2KHR(device, &mut output);
-                output.properties
-            };
+    /// Initialize a physical device, but use VK_KHR_get_physical_device_properties2
+    ///
+    /// This also queries the features and properties of the device extensions, by chaining their
+    /// structs to the ones of VK_KHR_get_physical_device_properties2. `api_version` is the
+    /// version supported by both the instance and the device, as the structs of the Vulkan
+    /// version that promoted an extension can only be chained if it is supported.
+    fn init_physical_device2(vk: &vk::InstancePointers, device: vk::PhysicalDevice,
+                             extensions: &RawInstanceExtensions, api_version: Version)
+                             -> PhysicalDeviceInfos {
+        let instance_extensions = InstanceExtensions::from(extensions);
+        let device_extensions = DeviceExtensions::supported_by_device_handle(vk, device)
+            .unwrap_or(DeviceExtensions::none());
+
+        let (properties, extended_properties) = unsafe {
+            let mut output = PropertiesFfi::new(api_version,
+                                                &instance_extensions,
+                                                &device_extensions);
+            vk.GetPhysicalDeviceProperties2KHR(device, &mut output.main);
+            let extended_properties = output.extended_properties();
+            (output.main.properties, extended_properties)
+        };
 
-            let queue_families = unsafe {
-                let mut num = 0;
-                vk.GetPhysicalDeviceQueueFamilyProperties2KHR(device, &mut num, ptr::null_mut());
-
-                let mut families = (0 .. num)
-                    .map(|_| {
-                             vk::QueueFamilyProperties2KHR {
-                                 sType: vk::STRUCTURE_TYPE_QUEUE_FAMILY_PROPERTIES_2_KHR,
-                                 pNext: ptr::null_mut(),
-                                 queueFamilyProperties: mem::zeroed(),
-                             }
-                         })
-                    .collect::<Vec<_>>();
-
-                vk.GetPhysicalDeviceQueueFamilyProperties2KHR(device,
-                                                              &mut num,
-                                                              families.as_mut_ptr());
-                families
-                    .into_iter()
-                    .map(|family| family.queueFamilyProperties)
-                    .collect()
-            };
+        let queue_families = unsafe {
+            let mut num = 0;
+            vk.GetPhysicalDeviceQueueFamilyProperties2KHR(device, &mut num, ptr::null_mut());
+
+            let mut families = (0 .. num)
+                .map(|_| {
+                         vk::QueueFamilyProperties2KHR {
+                             sType: vk::STRUCTURE_TYPE_QUEUE_FAMILY_PROPERTIES_2_KHR,
+                             pNext: ptr::null_mut(),
+                             queueFamilyProperties: mem::zeroed(),
+                         }
+                     })
+                .collect::<Vec<_>>();
+
+            vk.GetPhysicalDeviceQueueFamilyProperties2KHR(device,
+                                                          &mut num,
+                                                          families.as_mut_ptr());
+            families
+                .into_iter()
+                .map(|family| family.queueFamilyProperties)
+                .collect()
+        };
 
-            let memory: vk::PhysicalDeviceMemoryProperties = unsafe {
-                let mut output = vk::PhysicalDeviceMemoryProperties2KHR {
-                    sType: vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_MEMORY_PROPERTIES_2_KHR,
-                    pNext: ptr::null_mut(),
-                    memoryProperties: mem::zeroed(),
-                };
-                vk.GetPhysicalDeviceMemoryProperties2KHR(device, &mut output);
-                output.memoryProperties
+        let memory: vk::PhysicalDeviceMemoryProperties = unsafe {
+            let mut output = vk::PhysicalDeviceMemoryProperties2KHR {
+                sType: vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_MEMORY_PROPERTIES_2_KHR,
+                pNext: ptr::null_mut(),
+                memoryProperties: mem::zeroed(),
             };
+            vk.GetPhysicalDeviceMemoryProperties2KHR(device, &mut output);
+            output.memoryProperties
+        };
 
-            let available_features: vk::PhysicalDeviceFeatures = unsafe {
-                let mut output = vk::PhysicalDeviceFeatures2KHR {
-                    sType: vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_FEATURES_2_KHR,
-                    pNext: ptr::null_mut(),
-                    features: mem::zeroed(),
-                };
-                vk.GetPhysicalDeviceFeatures2KHR(device, &mut output);
-                output.features
-            };
+        let available_features = unsafe {
+            let mut output = FeaturesFfi::new(api_version, &device_extensions);
+            vk.GetPhysicalDeviceFeatures2KHR(device, &mut output.main);
+            output
+        };
 
-            output.push(PhysicalDeviceInfos {
-                            device: device,
-                            properties: properties,
-                            memory: memory,
-                            queue_families: queue_families,
-                            available_features: Features::from_vulkan_features(available_features),
-                        });
+        PhysicalDeviceInfos {
+            device: device,
+            properties: properties,
+            memory: memory,
+            queue_families: queue_families,
+            available_features: Features::from_vulkan_features(&available_features),
+            extended_properties: extended_properties,
         }
-        output
     }
 
     /*/// Same as `new`, but provides an allocator that will be used by the Vulkan library whenever
@@ -463,6 +562,15 @@ impl Instance {
         &self.extensions
     }
 
+    /// Returns the version of Vulkan that was passed when creating the instance.
+    ///
+    /// If no version was explicitly requested, this is the highest version supported by both the
+    /// loader and vulkano.
+    #[inline]
+    pub fn api_version(&self) -> Version {
+        self.api_version
+    }
+
     /// Returns the list of layers requested when creating this instance.
     #[doc(hidden)]
     #[inline]
@@ -624,8 +732,23 @@ pub enum InstanceCreationError {
     /// One of the requested extensions is missing.
     ExtensionNotPresent,
     /// The version requested is not supported by the implementation.
-    // TODO: more info about this once the question of the version has been resolved
     IncompatibleDriver,
+    /// The version of Vulkan requested with `with_api_version` is not supported by the loader.
+    UnsupportedApiVersion {
+        /// The version that was requested.
+        requested: Version,
+        /// The highest version supported by the loader.
+        supported: Version,
+    },
+    /// Some of the requested extensions depend on extensions that weren't requested and that
+    /// aren't part of the version of Vulkan being used.
+    ///
+    /// Returned by `InstanceExtensions::check_dependencies`. Use
+    /// `InstanceExtensions::with_dependencies` to add them.
+    MissingExtensionDependencies {
+        /// The extensions that must be enabled as well.
+        missing: InstanceExtensions,
+    },
 }
 
 impl error::Error for InstanceCreationError {
@@ -638,6 +761,12 @@ impl error::Error for InstanceCreationError {
             InstanceCreationError::LayerNotPresent => "layer not present",
             InstanceCreationError::ExtensionNotPresent => "extension not present",
             InstanceCreationError::IncompatibleDriver => "incompatible driver",
+            InstanceCreationError::UnsupportedApiVersion { .. } => {
+                "the requested version of Vulkan is not supported by the loader"
+            },
+            InstanceCreationError::MissingExtensionDependencies { .. } => {
+                "some of the requested extensions depend on extensions that weren't requested"
+            },
         }
     }
 
@@ -693,6 +822,7 @@ struct PhysicalDeviceInfos {
     queue_families: Vec<vk::QueueFamilyProperties>,
     memory: vk::PhysicalDeviceMemoryProperties,
     available_features: Features,
+    extended_properties: ExtendedProperties,
 }
 
 /// Represents one of the available devices on this machine.
@@ -846,6 +976,23 @@ impl<'a> PhysicalDevice<'a> {
         Version::from_vulkan_version(val)
     }
 
+    /// Returns the version of Vulkan that can be used with a device created from this physical
+    /// device.
+    ///
+    /// This is the minimum between the version supported by the physical device and the version
+    /// of the instance. Use this to decide which features are available before creating a device.
+    #[inline]
+    pub fn effective_api_version(&self) -> Version {
+        let device_version = self.api_version();
+        let instance_version = self.instance.api_version();
+
+        if device_version < instance_version {
+            device_version
+        } else {
+            instance_version
+        }
+    }
+
     /// Returns the Vulkan features that are supported by this physical device.
     #[inline]
     pub fn supported_features(&self) -> &'a Features {
@@ -930,6 +1077,42 @@ impl<'a> PhysicalDevice<'a> {
         Limits::from_vk_limits(&self.infos().properties.limits)
     }
 
+    /// Returns the properties of the subgroup operations of this physical device.
+    ///
+    /// Returns `None` if the effective API version of the device is lower than 1.1, or if the
+    /// instance was created for Vulkan 1.0.
+    #[inline]
+    pub fn subgroup_properties(&self) -> Option<&'a SubgroupProperties> {
+        self.infos().extended_properties.subgroup.as_ref()
+    }
+
+    /// Returns information about the driver of this physical device.
+    ///
+    /// Returns `None` if the device supports neither `khr_driver_properties` nor Vulkan 1.2, or if
+    /// the properties couldn't be queried.
+    #[inline]
+    pub fn driver_properties(&self) -> Option<&'a DriverProperties> {
+        self.infos().extended_properties.driver.as_ref()
+    }
+
+    /// Returns the UUIDs and LUID of this physical device and of its driver.
+    ///
+    /// Returns `None` if the instance was created for Vulkan 1.0 without one of the
+    /// `khr_external_*_capabilities` extensions.
+    #[inline]
+    pub fn id_properties(&self) -> Option<&'a IdProperties> {
+        self.infos().extended_properties.id.as_ref()
+    }
+
+    /// Returns the limits introduced by `khr_maintenance3`.
+    ///
+    /// Returns `None` if the device supports neither `khr_maintenance3` nor Vulkan 1.1, or if the
+    /// properties couldn't be queried.
+    #[inline]
+    pub fn maintenance3_properties(&self) -> Option<&'a Maintenance3Properties> {
+        self.infos().extended_properties.maintenance3.as_ref()
+    }
+
     /// Returns an opaque number representing the version of the driver of this device.
     ///
     /// The meaning of this number is implementation-specific. It can be used in bug reports, for
@@ -961,6 +1144,212 @@ impl<'a> PhysicalDevice<'a> {
         &self.infos().properties.pipelineCacheUUID
     }
 
+    /// Queries whether buffers with the given usage can be backed by external memory of the
+    /// given handle type.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the `VK_KHR_external_memory_capabilities` extension wasn't enabled on the
+    ///   instance and the instance uses Vulkan 1.0.
+    /// - Panics if `handle_type` doesn't have exactly one handle type set.
+    ///
+    pub fn external_buffer_properties(&self, usage: BufferUsage,
+                                      handle_type: ExternalMemoryHandleType)
+                                      -> ExternalMemoryProperties {
+        assert!(self.instance.api_version() >= Version::V1_1 ||
+                    self.instance.loaded_extensions().khr_external_memory_capabilities,
+                "the `VK_KHR_external_memory_capabilities` extension must be enabled");
+        assert_eq!(handle_type.to_bits().count_ones(), 1);
+
+        unsafe {
+            let vk_i = self.instance.pointers();
+
+            let infos = vk::PhysicalDeviceExternalBufferInfoKHR {
+                sType: vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_EXTERNAL_BUFFER_INFO_KHR,
+                pNext: ptr::null(),
+                flags: 0,
+                usage: usage.to_vulkan_bits(),
+                handleType: handle_type.to_bits(),
+            };
+
+            let mut output = vk::ExternalBufferPropertiesKHR {
+                sType: vk::STRUCTURE_TYPE_EXTERNAL_BUFFER_PROPERTIES_KHR,
+                pNext: ptr::null_mut(),
+                externalMemoryProperties: mem::zeroed(),
+            };
+
+            vk_i.GetPhysicalDeviceExternalBufferPropertiesKHR(self.internal_object(),
+                                                              &infos,
+                                                              &mut output);
+            ExternalMemoryProperties::from_vulkan(&output.externalMemoryProperties)
+        }
+    }
+
+    /// Queries whether images with the given parameters can be backed by external memory of the
+    /// given handle type.
+    ///
+    /// Returns `None` if the image configuration itself isn't supported.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the `VK_KHR_get_physical_device_properties2` or
+    ///   `VK_KHR_external_memory_capabilities` extensions weren't enabled on the instance and
+    ///   the instance uses Vulkan 1.0.
+    /// - Panics if `handle_type` doesn't have exactly one handle type set.
+    ///
+    pub fn external_image_format_properties(&self, format: Format, dimensions: ImageDimensions,
+                                            usage: ImageUsage, linear_tiling: bool,
+                                            handle_type: ExternalMemoryHandleType)
+                                            -> Option<ExternalMemoryProperties> {
+        assert!(self.instance.api_version() >= Version::V1_1 ||
+                    self.instance.loaded_extensions().khr_get_physical_device_properties2,
+                "the `VK_KHR_get_physical_device_properties2` extension must be enabled");
+        assert!(self.instance.api_version() >= Version::V1_1 ||
+                    self.instance.loaded_extensions().khr_external_memory_capabilities,
+                "the `VK_KHR_external_memory_capabilities` extension must be enabled");
+        assert_eq!(handle_type.to_bits().count_ones(), 1);
+
+        let (ty, flags) = match dimensions {
+            ImageDimensions::Dim1d { .. } => (vk::IMAGE_TYPE_1D, 0),
+            ImageDimensions::Dim2d { cubemap_compatible, .. } => {
+                let flags = if cubemap_compatible {
+                    vk::IMAGE_CREATE_CUBE_COMPATIBLE_BIT
+                } else {
+                    0
+                };
+                (vk::IMAGE_TYPE_2D, flags)
+            },
+            ImageDimensions::Dim3d { .. } => (vk::IMAGE_TYPE_3D, 0),
+        };
+
+        unsafe {
+            let vk_i = self.instance.pointers();
+
+            let external_infos = vk::PhysicalDeviceExternalImageFormatInfoKHR {
+                sType: vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_EXTERNAL_IMAGE_FORMAT_INFO_KHR,
+                pNext: ptr::null(),
+                handleType: handle_type.to_bits(),
+            };
+
+            let infos = vk::PhysicalDeviceImageFormatInfo2KHR {
+                sType: vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_IMAGE_FORMAT_INFO_2_KHR,
+                pNext: &external_infos as *const vk::PhysicalDeviceExternalImageFormatInfoKHR as
+                    *const _,
+                format: format as u32,
+                imageType: ty,
+                tiling: if linear_tiling {
+                    vk::IMAGE_TILING_LINEAR
+                } else {
+                    vk::IMAGE_TILING_OPTIMAL
+                },
+                usage: usage.to_usage_bits(),
+                flags: flags,
+            };
+
+            let mut external_output = vk::ExternalImageFormatPropertiesKHR {
+                sType: vk::STRUCTURE_TYPE_EXTERNAL_IMAGE_FORMAT_PROPERTIES_KHR,
+                pNext: ptr::null_mut(),
+                externalMemoryProperties: mem::zeroed(),
+            };
+
+            let mut output = vk::ImageFormatProperties2KHR {
+                sType: vk::STRUCTURE_TYPE_IMAGE_FORMAT_PROPERTIES_2_KHR,
+                pNext: &mut external_output as *mut vk::ExternalImageFormatPropertiesKHR as
+                    *const _,
+                imageFormatProperties: mem::zeroed(),
+            };
+
+            let r = vk_i.GetPhysicalDeviceImageFormatProperties2KHR(self.internal_object(),
+                                                                   &infos,
+                                                                   &mut output);
+            match check_errors(r) {
+                Ok(_) => (),
+                Err(Error::FormatNotSupported) => return None,
+                Err(err) => panic!("unexpected error: {:?}", err),
+            }
+
+            Some(ExternalMemoryProperties::from_vulkan(&external_output.externalMemoryProperties))
+        }
+    }
+
+    /// Queries whether semaphores can be exported or imported with the given handle type.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the `VK_KHR_external_semaphore_capabilities` extension wasn't enabled on the
+    ///   instance and the instance uses Vulkan 1.0.
+    /// - Panics if `handle_type` doesn't have exactly one handle type set.
+    ///
+    pub fn external_semaphore_properties(&self, handle_type: ExternalSemaphoreHandleType)
+                                         -> ExternalSemaphoreProperties {
+        assert!(self.instance.api_version() >= Version::V1_1 ||
+                    self.instance.loaded_extensions().khr_external_semaphore_capabilities,
+                "the `VK_KHR_external_semaphore_capabilities` extension must be enabled");
+        assert_eq!(handle_type.to_bits().count_ones(), 1);
+
+        unsafe {
+            let vk_i = self.instance.pointers();
+
+            let infos = vk::PhysicalDeviceExternalSemaphoreInfoKHR {
+                sType: vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_EXTERNAL_SEMAPHORE_INFO_KHR,
+                pNext: ptr::null(),
+                handleType: handle_type.to_bits(),
+            };
+
+            let mut output = vk::ExternalSemaphorePropertiesKHR {
+                sType: vk::STRUCTURE_TYPE_EXTERNAL_SEMAPHORE_PROPERTIES_KHR,
+                pNext: ptr::null_mut(),
+                exportFromImportedHandleTypes: 0,
+                compatibleHandleTypes: 0,
+                externalSemaphoreFeatures: 0,
+            };
+
+            vk_i.GetPhysicalDeviceExternalSemaphorePropertiesKHR(self.internal_object(),
+                                                                 &infos,
+                                                                 &mut output);
+            ExternalSemaphoreProperties::from_vulkan(&output)
+        }
+    }
+
+    /// Queries whether fences can be exported or imported with the given handle type.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the `VK_KHR_external_fence_capabilities` extension wasn't enabled on the
+    ///   instance and the instance uses Vulkan 1.0.
+    /// - Panics if `handle_type` doesn't have exactly one handle type set.
+    ///
+    pub fn external_fence_properties(&self, handle_type: ExternalFenceHandleType)
+                                     -> ExternalFenceProperties {
+        assert!(self.instance.api_version() >= Version::V1_1 ||
+                    self.instance.loaded_extensions().khr_external_fence_capabilities,
+                "the `VK_KHR_external_fence_capabilities` extension must be enabled");
+        assert_eq!(handle_type.to_bits().count_ones(), 1);
+
+        unsafe {
+            let vk_i = self.instance.pointers();
+
+            let infos = vk::PhysicalDeviceExternalFenceInfoKHR {
+                sType: vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_EXTERNAL_FENCE_INFO_KHR,
+                pNext: ptr::null(),
+                handleType: handle_type.to_bits(),
+            };
+
+            let mut output = vk::ExternalFencePropertiesKHR {
+                sType: vk::STRUCTURE_TYPE_EXTERNAL_FENCE_PROPERTIES_KHR,
+                pNext: ptr::null_mut(),
+                exportFromImportedHandleTypes: 0,
+                compatibleHandleTypes: 0,
+                externalFenceFeatures: 0,
+            };
+
+            vk_i.GetPhysicalDeviceExternalFencePropertiesKHR(self.internal_object(),
+                                                             &infos,
+                                                             &mut output);
+            ExternalFenceProperties::from_vulkan(&output)
+        }
+    }
+
     // Internal function to make it easier to get the infos of this device.
     #[inline]
     fn infos(&self) -> &'a PhysicalDeviceInfos {
@@ -1350,12 +1739,84 @@ impl<'a> ExactSizeIterator for MemoryHeapsIter<'a> {
 #[cfg(test)]
 mod tests {
     use instance;
+    use instance::Instance;
+    use instance::InstanceCreationError;
+    use instance::InstanceExtensions;
+    use instance::PhysicalDevice;
+    use instance::loader::FunctionPointers;
+    use instance::loader::Loader;
+    use mock::MockIcd;
+    use mock::Recorder;
+    use std::ffi::CStr;
+    use std::mem;
+    use std::os::raw::c_char;
+    use version::Version;
+    use vk;
+
+    // Same as `MockIcd`, but the loader supports Vulkan 1.1. The device of the mock still only
+    // supports Vulkan 1.0.
+    struct MockIcd11;
+
+    unsafe impl Loader for MockIcd11 {
+        unsafe fn get_instance_proc_addr(&self, instance: vk::Instance, name: *const c_char)
+                                         -> extern "system" fn() -> () {
+            extern "system" fn enumerate_instance_version(version: *mut u32) -> vk::Result {
+                unsafe {
+                    *version = Version::V1_1.into_vulkan_version();
+                }
+                vk::SUCCESS
+            }
+
+            if CStr::from_ptr(name).to_bytes() == b"vkEnumerateInstanceVersion" {
+                return mem::transmute(enumerate_instance_version as *const ());
+            }
+
+            MockIcd.get_instance_proc_addr(instance, name)
+        }
+    }
 
     #[test]
     fn create_instance() {
         let _ = instance!();
     }
 
+    #[test]
+    fn missing_extension_dependencies() {
+        let extensions = InstanceExtensions {
+            khr_xlib_surface: true,
+            .. InstanceExtensions::none()
+        };
+
+        match extensions.check_dependencies(Version::V1_0) {
+            Err(InstanceCreationError::MissingExtensionDependencies { missing }) => {
+                assert_eq!(missing,
+                           InstanceExtensions { khr_surface: true, .. InstanceExtensions::none() });
+            },
+            _ => panic!(),
+        }
+        assert!(extensions
+                    .with_dependencies(Version::V1_0)
+                    .check_dependencies(Version::V1_0)
+                    .is_ok());
+    }
+
+    #[test]
+    fn device_older_than_instance() {
+        let loader = FunctionPointers::new(Box::new(MockIcd11) as Box<_>);
+        let instance = Instance::with_loader(loader, None, &InstanceExtensions::none(), None)
+            .unwrap();
+        assert_eq!(instance.api_version(), Version::V1_1);
+
+        // The mock doesn't provide the Vulkan 1.1 queries, which must not be used with a Vulkan
+        // 1.0 device.
+        let physical = PhysicalDevice::enumerate(&instance).next().unwrap();
+        assert_eq!(physical.api_version(), Version::V1_0);
+        assert!(Recorder::of(&instance)
+                    .unwrap()
+                    .calls()
+                    .contains(&"vkGetPhysicalDeviceProperties"));
+    }
+
     #[test]
     fn queue_family_by_id() {
         let instance = instance!();
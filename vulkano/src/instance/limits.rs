@@ -27,6 +27,16 @@ macro_rules! limits_impl {
                 }
             }
 
+            /// Copies all the limits into an owned `LimitsSnapshot`.
+            #[inline]
+            pub fn snapshot(&self) -> LimitsSnapshot {
+                LimitsSnapshot {
+                    $(
+                        $name: self.limits.$target,
+                    )*
+                }
+            }
+
             $(
                 #[inline]
                 pub fn $name(&self) -> $t {
@@ -34,6 +44,20 @@ macro_rules! limits_impl {
                 }
             )*
         }
+
+        /// Owned copy of the limits of a physical device.
+        ///
+        /// Contrary to `Limits`, this struct doesn't borrow the physical device and can be stored,
+        /// compared against the limits of another device or, with the `serde` feature, written to
+        /// and loaded from a file.
+        #[derive(Debug, Clone, PartialEq)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        #[allow(missing_docs)]
+        pub struct LimitsSnapshot {
+            $(
+                pub $name: $t,
+            )*
+        }
     )
 }
 
@@ -145,3 +169,25 @@ limits_impl!{
     optimal_buffer_copy_row_pitch_alignment: u64 => optimalBufferCopyRowPitchAlignment,
     non_coherent_atom_size: u64 => nonCoherentAtomSize,
 }
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn snapshot() {
+        let (device, _) = mock_dev_and_queue!();
+        let limits = device.physical_device().limits();
+        let snapshot = limits.snapshot();
+
+        assert_eq!(snapshot.max_image_dimension_2d, limits.max_image_dimension_2d());
+        assert_eq!(snapshot.point_size_range, limits.point_size_range());
+        assert_eq!(snapshot.non_coherent_atom_size, limits.non_coherent_atom_size());
+        assert_eq!(snapshot, device.physical_device().limits().snapshot());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_round_trip() {
+        let (device, _) = mock_dev_and_queue!();
+        assert_serde_round_trip!(device.physical_device().limits().snapshot());
+    }
+}
@@ -23,6 +23,7 @@
 
 use shared_library;
 use std::error;
+use std::ffi::CStr;
 use std::fmt;
 use std::mem;
 use std::ops::Deref;
@@ -30,7 +31,10 @@ use std::os::raw::c_char;
 use std::os::raw::c_void;
 use std::path::Path;
 
+use OomError;
 use SafeDeref;
+use check_errors;
+use version::Version;
 use vk;
 
 /// Implemented on objects that grant access to a Vulkan implementation.
@@ -38,8 +42,13 @@ pub unsafe trait Loader {
     /// Calls the `vkGetInstanceProcAddr` function. The parameters are the same.
     ///
     /// The returned function must stay valid for as long as `self` is alive.
-    fn get_instance_proc_addr(&self, instance: vk::Instance, name: *const c_char)
-                              -> extern "system" fn() -> ();
+    ///
+    /// # Safety
+    ///
+    /// - `name` must point to a null-terminated string.
+    ///
+    unsafe fn get_instance_proc_addr(&self, instance: vk::Instance, name: *const c_char)
+                                     -> extern "system" fn() -> ();
 }
 
 unsafe impl<T> Loader for T
@@ -47,8 +56,8 @@ unsafe impl<T> Loader for T
           T::Target: Loader
 {
     #[inline]
-    fn get_instance_proc_addr(&self, instance: vk::Instance, name: *const c_char)
-                              -> extern "system" fn() -> () {
+    unsafe fn get_instance_proc_addr(&self, instance: vk::Instance, name: *const c_char)
+                                     -> extern "system" fn() -> () {
         (**self).get_instance_proc_addr(instance, name)
     }
 }
@@ -93,8 +102,8 @@ impl DynamicLibraryLoader {
 
 unsafe impl Loader for DynamicLibraryLoader {
     #[inline]
-    fn get_instance_proc_addr(&self, instance: vk::Instance, name: *const c_char)
-                              -> extern "system" fn() -> () {
+    unsafe fn get_instance_proc_addr(&self, instance: vk::Instance, name: *const c_char)
+                                     -> extern "system" fn() -> () {
         (self.get_proc_addr)(instance, name)
     }
 }
@@ -103,6 +112,8 @@ unsafe impl Loader for DynamicLibraryLoader {
 pub struct FunctionPointers<L> {
     loader: L,
     entry_points: vk::EntryPoints,
+    // Vulkan 1.0 loaders don't provide `vkEnumerateInstanceVersion`.
+    has_enumerate_instance_version: bool,
 }
 
 impl<L> FunctionPointers<L> {
@@ -110,11 +121,20 @@ impl<L> FunctionPointers<L> {
     pub fn new(loader: L) -> FunctionPointers<L>
         where L: Loader
     {
-        let entry_points = vk::EntryPoints::load(|name| unsafe { mem::transmute(loader.get_instance_proc_addr(0, name.as_ptr())) });
+        let mut has_enumerate_instance_version = false;
+
+        let entry_points = vk::EntryPoints::load(|name| unsafe {
+            let ptr: *const c_void = mem::transmute(loader.get_instance_proc_addr(0, name.as_ptr()));
+            if name.to_bytes() == b"vkEnumerateInstanceVersion" {
+                has_enumerate_instance_version = !ptr.is_null();
+            }
+            ptr
+        });
 
         FunctionPointers {
             loader,
             entry_points,
+            has_enumerate_instance_version,
         }
     }
 
@@ -124,16 +144,82 @@ impl<L> FunctionPointers<L> {
         &self.entry_points
     }
 
+    /// Returns the highest version of Vulkan that the loader supports for instances.
+    ///
+    /// This is always 1.0 if the loader doesn't support `vkEnumerateInstanceVersion`.
+    pub fn api_version(&self) -> Result<Version, OomError> {
+        if !self.has_enumerate_instance_version {
+            return Ok(Version::V1_0);
+        }
+
+        unsafe {
+            let mut version = 0;
+            check_errors(self.entry_points.EnumerateInstanceVersion(&mut version))?;
+            Ok(Version::from_vulkan_version(version))
+        }
+    }
+
     /// Calls `get_instance_proc_addr` on the underlying loader.
+    ///
+    /// # Safety
+    ///
+    /// - `name` must point to a null-terminated string.
+    ///
     #[inline]
-    pub fn get_instance_proc_addr(&self, instance: vk::Instance, name: *const c_char)
-                                  -> extern "system" fn() -> ()
+    pub unsafe fn get_instance_proc_addr(&self, instance: vk::Instance, name: *const c_char)
+                                         -> extern "system" fn() -> ()
         where L: Loader
     {
         self.loader.get_instance_proc_addr(instance, name)
     }
 }
 
+// Functions of extensions that have been promoted to core Vulkan, along with the version that
+// promoted them. The names are the core ones, which are the extension ones without the suffix.
+const PROMOTED_FUNCTIONS: &[(&[u8], Version)] = &[
+    (b"vkGetPhysicalDeviceFeatures2\0", Version::V1_1),
+    (b"vkGetPhysicalDeviceProperties2\0", Version::V1_1),
+    (b"vkGetPhysicalDeviceFormatProperties2\0", Version::V1_1),
+    (b"vkGetPhysicalDeviceImageFormatProperties2\0", Version::V1_1),
+    (b"vkGetPhysicalDeviceQueueFamilyProperties2\0", Version::V1_1),
+    (b"vkGetPhysicalDeviceMemoryProperties2\0", Version::V1_1),
+    (b"vkGetPhysicalDeviceSparseImageFormatProperties2\0", Version::V1_1),
+    (b"vkGetPhysicalDeviceExternalBufferProperties\0", Version::V1_1),
+    (b"vkGetPhysicalDeviceExternalSemaphoreProperties\0", Version::V1_1),
+    (b"vkGetPhysicalDeviceExternalFenceProperties\0", Version::V1_1),
+    (b"vkTrimCommandPool\0", Version::V1_1),
+    (b"vkCreateDescriptorUpdateTemplate\0", Version::V1_1),
+    (b"vkDestroyDescriptorUpdateTemplate\0", Version::V1_1),
+    (b"vkUpdateDescriptorSetWithTemplate\0", Version::V1_1),
+    (b"vkGetImageMemoryRequirements2\0", Version::V1_1),
+    (b"vkGetBufferMemoryRequirements2\0", Version::V1_1),
+    (b"vkGetDescriptorSetLayoutSupport\0", Version::V1_1),
+    (b"vkBindBufferMemory2\0", Version::V1_1),
+    (b"vkBindImageMemory2\0", Version::V1_1),
+];
+
+/// If `name` is the name of an extension function that is part of core Vulkan in the version
+/// `api_version`, returns the name of the core function.
+///
+/// This is used when loading function pointers, so that the core functions are used whenever
+/// they are available, even if the extension isn't enabled.
+pub(crate) fn core_function_name(name: &CStr, api_version: Version) -> Option<&'static CStr> {
+    let name = name.to_bytes();
+
+    if !name.ends_with(b"KHR") {
+        return None;
+    }
+
+    let unsuffixed = &name[.. name.len() - 3];
+
+    PROMOTED_FUNCTIONS
+        .iter()
+        .find(|&&(core, version)| {
+            version <= api_version && &core[.. core.len() - 1] == unsuffixed
+        })
+        .map(|&(core, _)| unsafe { CStr::from_bytes_with_nul_unchecked(core) })
+}
+
 /// Expression that returns a loader that assumes that Vulkan is linked to the executable you're
 /// compiling.
 ///
@@ -153,9 +239,9 @@ macro_rules! statically_linked_vulkan_loader {
 
         struct StaticallyLinkedVulkanLoader;
         unsafe impl Loader for StaticallyLinkedVulkanLoader {
-            fn get_instance_proc_addr(&self, instance: vk::Instance, name: *const c_char)
-                                    -> extern "system" fn() -> () {
-                unsafe { vkGetInstanceProcAddr(instance, name) }
+            unsafe fn get_instance_proc_addr(&self, instance: vk::Instance, name: *const c_char)
+                                             -> extern "system" fn() -> () {
+                vkGetInstanceProcAddr(instance, name)
             }
         }
 
@@ -258,6 +344,9 @@ impl fmt::Display for LoadingError {
 mod tests {
     use instance::loader::DynamicLibraryLoader;
     use instance::loader::LoadingError;
+    use instance::loader::core_function_name;
+    use std::ffi::CStr;
+    use version::Version;
 
     #[test]
     fn dl_open_error() {
@@ -268,4 +357,15 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn core_function_names() {
+        let name = CStr::from_bytes_with_nul(b"vkGetPhysicalDeviceFeatures2KHR\0").unwrap();
+        assert_eq!(core_function_name(name, Version::V1_0), None);
+        assert_eq!(core_function_name(name, Version::V1_1).unwrap().to_bytes(),
+                   b"vkGetPhysicalDeviceFeatures2");
+
+        let name = CStr::from_bytes_with_nul(b"vkCreateSwapchainKHR\0").unwrap();
+        assert_eq!(core_function_name(name, Version::V1_2), None);
+    }
 }
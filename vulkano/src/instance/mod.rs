@@ -71,6 +71,12 @@
 //! };
 //! ```
 //!
+//! Some extensions depend on others: `VK_KHR_android_surface` can't be enabled without
+//! `VK_KHR_surface`, for example. `Instance::new()` doesn't check the dependencies.
+//! `InstanceExtensions::with_dependencies` can be used to add them, and
+//! `InstanceExtensions::check_dependencies` returns `MissingExtensionDependencies` if some are
+//! missing.
+//!
 //! # Application info
 //!
 //! When you create an instance, you have the possibility to pass an `ApplicationInfo` struct as
@@ -125,13 +131,23 @@ pub use self::layers::LayersIterator;
 pub use self::layers::LayersListError;
 pub use self::layers::layers_list;
 pub use self::limits::Limits;
+pub use self::limits::LimitsSnapshot;
 pub use self::loader::LoadingError;
+pub use self::properties::ConformanceVersion;
+pub use self::properties::DriverId;
+pub use self::properties::DriverProperties;
+pub use self::properties::IdProperties;
+pub use self::properties::Maintenance3Properties;
+pub use self::properties::SubgroupFeatures;
+pub use self::properties::SubgroupProperties;
 pub use version::Version;
 
 pub mod debug;
 pub mod loader;
+pub mod trace;
 
 mod extensions;
 mod instance;
 mod layers;
 mod limits;
+mod properties;
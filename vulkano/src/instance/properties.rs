@@ -0,0 +1,322 @@
+// Copyright (c) 2020 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Properties of a physical device that are provided by device extensions or by Vulkan versions
+//! more recent than 1.0.
+//!
+//! They are queried by chaining the corresponding structs to `VkPhysicalDeviceProperties2`,
+//! which requires the instance to be created with `khr_get_physical_device_properties2` or for
+//! Vulkan 1.1. The getters on `PhysicalDevice` return `None` when this isn't possible, or when the
+//! physical device doesn't support the extension that provides the properties.
+
+use std::ffi::CStr;
+use std::mem;
+use std::os::raw::c_void;
+use std::ptr;
+
+use descriptor::descriptor::ShaderStages;
+use device::DeviceExtensions;
+use instance::InstanceExtensions;
+use version::Version;
+use vk;
+
+/// Properties of the subgroup operations of a physical device.
+///
+/// Provided by Vulkan 1.1.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SubgroupProperties {
+    /// Number of invocations in each subgroup.
+    pub subgroup_size: u32,
+    /// Shader stages in which subgroup operations are supported.
+    pub supported_stages: ShaderStages,
+    /// Subgroup operations that are supported.
+    pub supported_operations: SubgroupFeatures,
+    /// If true, quad operations are supported in all the supported stages. Otherwise they are
+    /// only supported in fragment and compute shaders.
+    pub quad_operations_in_all_stages: bool,
+}
+
+/// Categories of subgroup operations.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub struct SubgroupFeatures {
+    pub basic: bool,
+    pub vote: bool,
+    pub arithmetic: bool,
+    pub ballot: bool,
+    pub shuffle: bool,
+    pub shuffle_relative: bool,
+    pub clustered: bool,
+    pub quad: bool,
+}
+
+impl SubgroupFeatures {
+    /// Builds a `SubgroupFeatures` with all values to false.
+    #[inline]
+    pub fn none() -> SubgroupFeatures {
+        SubgroupFeatures {
+            basic: false,
+            vote: false,
+            arithmetic: false,
+            ballot: false,
+            shuffle: false,
+            shuffle_relative: false,
+            clustered: false,
+            quad: false,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn from_vulkan_bits(val: vk::SubgroupFeatureFlags) -> SubgroupFeatures {
+        SubgroupFeatures {
+            basic: (val & vk::SUBGROUP_FEATURE_BASIC_BIT) != 0,
+            vote: (val & vk::SUBGROUP_FEATURE_VOTE_BIT) != 0,
+            arithmetic: (val & vk::SUBGROUP_FEATURE_ARITHMETIC_BIT) != 0,
+            ballot: (val & vk::SUBGROUP_FEATURE_BALLOT_BIT) != 0,
+            shuffle: (val & vk::SUBGROUP_FEATURE_SHUFFLE_BIT) != 0,
+            shuffle_relative: (val & vk::SUBGROUP_FEATURE_SHUFFLE_RELATIVE_BIT) != 0,
+            clustered: (val & vk::SUBGROUP_FEATURE_CLUSTERED_BIT) != 0,
+            quad: (val & vk::SUBGROUP_FEATURE_QUAD_BIT) != 0,
+        }
+    }
+}
+
+/// Information about the driver of a physical device.
+///
+/// Provided by `khr_driver_properties` or Vulkan 1.2.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DriverProperties {
+    /// Identifier of the driver.
+    pub driver_id: DriverId,
+    /// Name of the driver.
+    pub driver_name: String,
+    /// Additional information about the driver, such as its version.
+    pub driver_info: String,
+    /// Version of the Vulkan conformance test suite that the driver passed.
+    pub conformance_version: ConformanceVersion,
+}
+
+/// Identifies a Vulkan driver.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub enum DriverId {
+    AmdProprietary,
+    AmdOpenSource,
+    MesaRadv,
+    NvidiaProprietary,
+    IntelProprietaryWindows,
+    IntelOpenSourceMesa,
+    ImaginationProprietary,
+    QualcommProprietary,
+    ArmProprietary,
+    GoogleSwiftshader,
+    GgpProprietary,
+    BroadcomProprietary,
+    /// A driver that isn't known by vulkano.
+    Other(u32),
+}
+
+impl DriverId {
+    #[inline]
+    fn from_vulkan(val: vk::DriverIdKHR) -> DriverId {
+        match val {
+            vk::DRIVER_ID_AMD_PROPRIETARY_KHR => DriverId::AmdProprietary,
+            vk::DRIVER_ID_AMD_OPEN_SOURCE_KHR => DriverId::AmdOpenSource,
+            vk::DRIVER_ID_MESA_RADV_KHR => DriverId::MesaRadv,
+            vk::DRIVER_ID_NVIDIA_PROPRIETARY_KHR => DriverId::NvidiaProprietary,
+            vk::DRIVER_ID_INTEL_PROPRIETARY_WINDOWS_KHR => DriverId::IntelProprietaryWindows,
+            vk::DRIVER_ID_INTEL_OPEN_SOURCE_MESA_KHR => DriverId::IntelOpenSourceMesa,
+            vk::DRIVER_ID_IMAGINATION_PROPRIETARY_KHR => DriverId::ImaginationProprietary,
+            vk::DRIVER_ID_QUALCOMM_PROPRIETARY_KHR => DriverId::QualcommProprietary,
+            vk::DRIVER_ID_ARM_PROPRIETARY_KHR => DriverId::ArmProprietary,
+            vk::DRIVER_ID_GOOGLE_SWIFTSHADER_KHR => DriverId::GoogleSwiftshader,
+            vk::DRIVER_ID_GGP_PROPRIETARY_KHR => DriverId::GgpProprietary,
+            vk::DRIVER_ID_BROADCOM_PROPRIETARY_KHR => DriverId::BroadcomProprietary,
+            other => DriverId::Other(other),
+        }
+    }
+}
+
+/// Version of the Vulkan conformance test suite.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub struct ConformanceVersion {
+    pub major: u8,
+    pub minor: u8,
+    pub subminor: u8,
+    pub patch: u8,
+}
+
+/// Identifiers of a physical device and of its driver, used to match devices across instances,
+/// processes and APIs.
+///
+/// Provided by `khr_external_memory_capabilities`, `khr_external_semaphore_capabilities`,
+/// `khr_external_fence_capabilities` or Vulkan 1.1.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct IdProperties {
+    /// Universally unique identifier of the physical device.
+    pub device_uuid: [u8; 16],
+    /// Universally unique identifier of the driver build in use by the physical device.
+    pub driver_uuid: [u8; 16],
+    /// Locally unique identifier of the physical device, if there is one.
+    pub device_luid: Option<[u8; 8]>,
+    /// Bitfield identifying the node within a linked device adapter that corresponds to the
+    /// physical device. Only meaningful if `device_luid` is `Some`.
+    pub device_node_mask: u32,
+}
+
+/// Limits introduced by `khr_maintenance3`.
+///
+/// Provided by `khr_maintenance3` or Vulkan 1.1.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Maintenance3Properties {
+    /// Maximum number of descriptors in a single descriptor set.
+    pub max_per_set_descriptors: u32,
+    /// Maximum size of a single memory allocation.
+    pub max_memory_allocation_size: u64,
+}
+
+/// The properties that have been queried for a physical device.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ExtendedProperties {
+    pub(crate) subgroup: Option<SubgroupProperties>,
+    pub(crate) driver: Option<DriverProperties>,
+    pub(crate) id: Option<IdProperties>,
+    pub(crate) maintenance3: Option<Maintenance3Properties>,
+}
+
+/// The `VkPhysicalDeviceProperties2` struct followed by the property structs that are available
+/// for a physical device. Always boxed so that the `pNext` chain stays valid.
+pub(crate) struct PropertiesFfi {
+    pub(crate) main: vk::PhysicalDeviceProperties2KHR,
+    subgroup: Option<vk::PhysicalDeviceSubgroupProperties>,
+    driver: Option<vk::PhysicalDeviceDriverPropertiesKHR>,
+    id: Option<vk::PhysicalDeviceIDPropertiesKHR>,
+    maintenance3: Option<vk::PhysicalDeviceMaintenance3PropertiesKHR>,
+}
+
+impl PropertiesFfi {
+    /// Builds the chain of structs to pass to `vkGetPhysicalDeviceProperties2`.
+    ///
+    /// `api_version` must be the effective API version of the physical device.
+    pub(crate) fn new(api_version: Version, instance_extensions: &InstanceExtensions,
+                      device_extensions: &DeviceExtensions)
+                      -> Box<PropertiesFfi> {
+        unsafe {
+            let mut output = Box::new(PropertiesFfi {
+                main: mem::zeroed(),
+                subgroup: None,
+                driver: None,
+                id: None,
+                maintenance3: None,
+            });
+            output.main.sType = vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_PROPERTIES_2_KHR;
+
+            if api_version >= Version::V1_1 {
+                let mut s: vk::PhysicalDeviceSubgroupProperties = mem::zeroed();
+                s.sType = vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_SUBGROUP_PROPERTIES;
+                output.subgroup = Some(s);
+            }
+
+            if api_version >= Version::V1_2 || device_extensions.khr_driver_properties {
+                let mut s: vk::PhysicalDeviceDriverPropertiesKHR = mem::zeroed();
+                s.sType = vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_DRIVER_PROPERTIES_KHR;
+                output.driver = Some(s);
+            }
+
+            if api_version >= Version::V1_1 ||
+                instance_extensions.khr_external_memory_capabilities ||
+                instance_extensions.khr_external_semaphore_capabilities ||
+                instance_extensions.khr_external_fence_capabilities
+            {
+                let mut s: vk::PhysicalDeviceIDPropertiesKHR = mem::zeroed();
+                s.sType = vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_ID_PROPERTIES_KHR;
+                output.id = Some(s);
+            }
+
+            if api_version >= Version::V1_1 || device_extensions.khr_maintenance3 {
+                let mut s: vk::PhysicalDeviceMaintenance3PropertiesKHR = mem::zeroed();
+                s.sType = vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_MAINTENANCE_3_PROPERTIES_KHR;
+                output.maintenance3 = Some(s);
+            }
+
+            // Linking the structs now that they won't move anymore.
+            let mut next: *mut c_void = ptr::null_mut();
+            if let Some(ref mut s) = output.subgroup {
+                s.pNext = next;
+                next = s as *mut _ as *mut c_void;
+            }
+            if let Some(ref mut s) = output.driver {
+                s.pNext = next;
+                next = s as *mut _ as *mut c_void;
+            }
+            if let Some(ref mut s) = output.id {
+                s.pNext = next;
+                next = s as *mut _ as *mut c_void;
+            }
+            if let Some(ref mut s) = output.maintenance3 {
+                s.pNext = next;
+                next = s as *mut _ as *mut c_void;
+            }
+            output.main.pNext = next as *const c_void;
+
+            output
+        }
+    }
+
+    /// Turns the content of the chain into vulkano types. Must only be called after the chain
+    /// has been filled by the implementation.
+    pub(crate) fn extended_properties(&self) -> ExtendedProperties {
+        ExtendedProperties {
+            subgroup: self.subgroup.as_ref().map(|s| {
+                SubgroupProperties {
+                    subgroup_size: s.subgroupSize,
+                    supported_stages: ShaderStages::from_vulkan_bits(s.supportedStages),
+                    supported_operations: SubgroupFeatures::from_vulkan_bits(s.supportedOperations),
+                    quad_operations_in_all_stages: s.quadOperationsInAllStages != 0,
+                }
+            }),
+            driver: self.driver.as_ref().map(|s| unsafe {
+                DriverProperties {
+                    driver_id: DriverId::from_vulkan(s.driverID),
+                    driver_name: CStr::from_ptr(s.driverName.as_ptr())
+                        .to_string_lossy()
+                        .into_owned(),
+                    driver_info: CStr::from_ptr(s.driverInfo.as_ptr())
+                        .to_string_lossy()
+                        .into_owned(),
+                    conformance_version: ConformanceVersion {
+                        major: s.conformanceVersion.major,
+                        minor: s.conformanceVersion.minor,
+                        subminor: s.conformanceVersion.subminor,
+                        patch: s.conformanceVersion.patch,
+                    },
+                }
+            }),
+            id: self.id.as_ref().map(|s| {
+                IdProperties {
+                    device_uuid: s.deviceUUID,
+                    driver_uuid: s.driverUUID,
+                    device_luid: if s.deviceLUIDValid != 0 {
+                        Some(s.deviceLUID)
+                    } else {
+                        None
+                    },
+                    device_node_mask: s.deviceNodeMask,
+                }
+            }),
+            maintenance3: self.maintenance3.as_ref().map(|s| {
+                Maintenance3Properties {
+                    max_per_set_descriptors: s.maxPerSetDescriptors,
+                    max_memory_allocation_size: s.maxMemoryAllocationSize,
+                }
+            }),
+        }
+    }
+}
@@ -0,0 +1,957 @@
+// Copyright (c) 2020 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Tracing of the Vulkan calls made by vulkano.
+//!
+//! A `TracingLoader` wraps around another `Loader`. The function pointers that it returns record
+//! every call they forward to the Vulkan implementation in a `TraceSink`: the name of the
+//! function, its arguments, the handles it created, its return code, when it was made and how
+//! long it took. This makes it possible to find out what vulkano did on a machine without
+//! installing the API dump layer.
+//!
+//! Two sinks are provided: `MemoryTraceSink`, which keeps the calls in memory, and
+//! `FileTraceSink`, which writes one line per call to a file.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use vulkano::instance::Instance;
+//! use vulkano::instance::InstanceExtensions;
+//! use vulkano::instance::loader::DynamicLibraryLoader;
+//! use vulkano::instance::loader::FunctionPointers;
+//! use vulkano::instance::loader::Loader;
+//! use vulkano::instance::trace::FileTraceSink;
+//! use vulkano::instance::trace::TracingLoader;
+//!
+//! let inner = unsafe { DynamicLibraryLoader::new("libvulkan.so.1").unwrap() };
+//! let sink = FileTraceSink::create("vulkan-trace.txt").unwrap();
+//! let loader = TracingLoader::new(inner, sink).unwrap();
+//! let loader = FunctionPointers::new(Box::new(loader) as Box<dyn Loader + Send + Sync>);
+//! let instance = Instance::with_loader(loader, None, &InstanceExtensions::none(), None).unwrap();
+//! ```
+//!
+//! # Limitations
+//!
+//! Since Vulkan function pointers can't carry any state, the state of the tracing is global: only
+//! one `TracingLoader` can be alive at a time, and it assumes that every instance resolves a given
+//! function to the same pointer. This is the case with the official Vulkan loader. Device-level
+//! functions are resolved with `vkGetInstanceProcAddr`, which returns the trampolines of the
+//! loader that dispatch to the right driver, instead of the pointers that `vkGetDeviceProcAddr`
+//! returns for a specific device. The devices can therefore use different drivers, at the cost of
+//! going through the trampolines.
+//!
+//! Only the most common functions are traced. The others are forwarded untouched. Pointer
+//! arguments are recorded as addresses, without the content of the structs they point to.
+
+use std::error;
+use std::ffi::CStr;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::LineWriter;
+use std::io::Write;
+use std::mem;
+use std::os::raw::c_char;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::RwLock;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::thread::ThreadId;
+use std::time::Duration;
+use std::time::Instant;
+
+use instance::loader::Loader;
+use vk;
+
+/// Implementation of `Loader` that records the Vulkan calls made through the functions it returns.
+///
+/// See the documentation of the module.
+pub struct TracingLoader<L> {
+    inner: L,
+}
+
+impl<L> TracingLoader<L> {
+    /// Wraps around `inner`, and sends the calls made through the functions it returns to `sink`.
+    ///
+    /// Returns an error if another `TracingLoader` is alive.
+    pub fn new<S>(inner: L, sink: S) -> Result<TracingLoader<L>, TracingLoaderCreationError>
+        where L: Loader,
+              S: TraceSink + 'static
+    {
+        let mut tracer = TRACER.write().unwrap();
+        if tracer.is_some() {
+            return Err(TracingLoaderCreationError::AlreadyTracing);
+        }
+
+        *tracer = Some(Tracer {
+                           sink: Box::new(sink),
+                           start: Instant::now(),
+                       });
+        Ok(TracingLoader { inner })
+    }
+}
+
+unsafe impl<L> Loader for TracingLoader<L>
+    where L: Loader
+{
+    unsafe fn get_instance_proc_addr(&self, instance: vk::Instance, name: *const c_char)
+                                     -> extern "system" fn() -> () {
+        let ptr = self.inner.get_instance_proc_addr(instance, name);
+        let name = CStr::from_ptr(name);
+
+        if name.to_bytes() == b"vkGetDeviceProcAddr" {
+            // `vkGetDeviceProcAddr` returns pointers that are specific to the driver of the
+            // device, and a second device could overwrite them. The traced functions are
+            // resolved here instead, and `vkGetDeviceProcAddr` returns their wrappers.
+            for traced in wrappers::NAMES {
+                let traced = CStr::from_bytes_with_nul_unchecked(traced);
+                intercept(traced, self.inner.get_instance_proc_addr(instance, traced.as_ptr()));
+            }
+
+            return wrappers::get_device_proc_addr(ptr);
+        }
+
+        intercept(name, ptr)
+    }
+}
+
+impl<L> Drop for TracingLoader<L> {
+    #[inline]
+    fn drop(&mut self) {
+        // Functions that have already been resolved keep forwarding their calls, but they are
+        // no longer recorded.
+        *TRACER.write().unwrap() = None;
+    }
+}
+
+/// Error that can happen when creating a `TracingLoader`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TracingLoaderCreationError {
+    /// Another `TracingLoader` is alive.
+    AlreadyTracing,
+}
+
+impl error::Error for TracingLoaderCreationError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            TracingLoaderCreationError::AlreadyTracing => "another `TracingLoader` is alive",
+        }
+    }
+}
+
+impl fmt::Display for TracingLoaderCreationError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+/// A Vulkan call recorded by a `TracingLoader`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TracedCall {
+    /// Time between the creation of the `TracingLoader` and the start of the call.
+    pub timestamp: Duration,
+    /// How long the call took.
+    pub duration: Duration,
+    /// Thread that made the call.
+    pub thread: ThreadId,
+    /// Name of the function, for example `vkCreateBuffer`.
+    pub name: &'static str,
+    /// Names and values of the arguments. Pointers are recorded as addresses.
+    pub arguments: Vec<(&'static str, String)>,
+    /// Names and values of the handles or values that the function wrote through its output
+    /// parameters.
+    pub outputs: Vec<(&'static str, String)>,
+    /// Return code of the function, for example `VK_SUCCESS`. `None` if the function doesn't
+    /// return anything.
+    pub result: Option<String>,
+}
+
+impl fmt::Display for TracedCall {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt,
+               "[{}.{:06}] {:?} {}(",
+               self.timestamp.as_secs(),
+               self.timestamp.subsec_micros(),
+               self.thread,
+               self.name)?;
+        for (num, &(name, ref value)) in self.arguments.iter().enumerate() {
+            if num != 0 {
+                write!(fmt, ", ")?;
+            }
+            write!(fmt, "{}: {}", name, value)?;
+        }
+        write!(fmt, ")")?;
+        if let Some(ref result) = self.result {
+            write!(fmt, " -> {}", result)?;
+        }
+        for &(name, ref value) in &self.outputs {
+            write!(fmt, " [*{} = {}]", name, value)?;
+        }
+        write!(fmt, " ({}us)", self.duration.as_micros())
+    }
+}
+
+/// Destination of the calls recorded by a `TracingLoader`.
+///
+/// The sink is called from the thread that made the Vulkan call.
+pub trait TraceSink: Send + Sync {
+    /// Records a call.
+    fn record(&self, call: TracedCall);
+}
+
+impl<T> TraceSink for Arc<T>
+    where T: ?Sized + TraceSink
+{
+    #[inline]
+    fn record(&self, call: TracedCall) {
+        (**self).record(call)
+    }
+}
+
+/// Sink that keeps the recorded calls in memory.
+///
+/// Pass an `Arc<MemoryTraceSink>` to the `TracingLoader` and keep a clone of it to retrieve the
+/// calls.
+#[derive(Debug, Default)]
+pub struct MemoryTraceSink {
+    calls: Mutex<Vec<TracedCall>>,
+}
+
+impl MemoryTraceSink {
+    /// Builds an empty sink.
+    #[inline]
+    pub fn new() -> MemoryTraceSink {
+        MemoryTraceSink { calls: Mutex::new(Vec::new()) }
+    }
+
+    /// Returns a copy of the calls recorded so far.
+    #[inline]
+    pub fn calls(&self) -> Vec<TracedCall> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// Returns the calls recorded so far and clears them.
+    #[inline]
+    pub fn take(&self) -> Vec<TracedCall> {
+        mem::take(&mut *self.calls.lock().unwrap())
+    }
+}
+
+impl TraceSink for MemoryTraceSink {
+    #[inline]
+    fn record(&self, call: TracedCall) {
+        self.calls.lock().unwrap().push(call);
+    }
+}
+
+/// Sink that writes one line per recorded call to a file.
+///
+/// Each line is written as soon as the call has returned, so that the trace is complete even if
+/// the program crashes.
+#[derive(Debug)]
+pub struct FileTraceSink {
+    file: Mutex<LineWriter<File>>,
+}
+
+impl FileTraceSink {
+    /// Creates or truncates the file at the given path.
+    #[inline]
+    pub fn create<P>(path: P) -> io::Result<FileTraceSink>
+        where P: AsRef<Path>
+    {
+        Ok(FileTraceSink { file: Mutex::new(LineWriter::new(File::create(path)?)) })
+    }
+}
+
+impl TraceSink for FileTraceSink {
+    #[inline]
+    fn record(&self, call: TracedCall) {
+        // Errors are ignored, as there is nobody to report them to.
+        let _ = writeln!(self.file.lock().unwrap(), "{}", call);
+    }
+}
+
+// State of the tracing, shared by all the wrapper functions.
+struct Tracer {
+    sink: Box<dyn TraceSink>,
+    start: Instant,
+}
+
+lazy_static! {
+    static ref TRACER: RwLock<Option<Tracer>> = RwLock::new(None);
+}
+
+// Returns the pointer that must be returned to the user in place of `ptr`, which is what the
+// inner loader returned for `name`.
+unsafe fn intercept(name: &CStr, ptr: extern "system" fn() -> ()) -> extern "system" fn() -> () {
+    let raw = ptr as usize;
+    if raw == 0 {
+        return ptr;
+    }
+
+    let (slot, wrapper) = match wrappers::lookup(name.to_bytes()) {
+        Some(w) => w,
+        None => return ptr,
+    };
+
+    slot.store(raw, Ordering::Relaxed);
+    mem::transmute(wrapper)
+}
+
+// Called by the wrappers before forwarding a call. Returns `None` if the calls aren't recorded.
+fn begin_call() -> Option<Instant> {
+    if TRACER.read().unwrap().is_some() {
+        Some(Instant::now())
+    } else {
+        None
+    }
+}
+
+// Called by the wrappers after forwarding a call.
+fn end_call(start: Instant, name: &'static str, arguments: Vec<(&'static str, String)>,
+            outputs: Vec<(&'static str, String)>, result: Option<String>) {
+    let duration = start.elapsed();
+    let tracer = TRACER.read().unwrap();
+    let tracer = match *tracer {
+        Some(ref t) => t,
+        None => return,
+    };
+
+    tracer.sink.record(TracedCall {
+                           timestamp: start.duration_since(tracer.start),
+                           duration,
+                           thread: thread::current().id(),
+                           name,
+                           arguments,
+                           outputs,
+                           result,
+                       });
+}
+
+// Formats the value that a function wrote through an output parameter.
+unsafe fn format_output<T>(ptr: *mut T) -> String
+    where T: fmt::Debug
+{
+    if ptr.is_null() {
+        "null".to_owned()
+    } else {
+        format!("{:?}", *ptr)
+    }
+}
+
+// Return type of a traced function.
+trait TraceReturn {
+    fn describe(&self) -> Option<String>;
+}
+
+impl TraceReturn for () {
+    #[inline]
+    fn describe(&self) -> Option<String> {
+        None
+    }
+}
+
+impl TraceReturn for vk::Result {
+    #[inline]
+    fn describe(&self) -> Option<String> {
+        Some(match result_name(*self) {
+                 Some(name) => name.to_owned(),
+                 None => format!("{}", *self as i32),
+             })
+    }
+}
+
+// Returns the name of a return code.
+fn result_name(result: vk::Result) -> Option<&'static str> {
+    Some(match result {
+        vk::SUCCESS => "VK_SUCCESS",
+        vk::NOT_READY => "VK_NOT_READY",
+        vk::TIMEOUT => "VK_TIMEOUT",
+        vk::EVENT_SET => "VK_EVENT_SET",
+        vk::EVENT_RESET => "VK_EVENT_RESET",
+        vk::INCOMPLETE => "VK_INCOMPLETE",
+        vk::ERROR_OUT_OF_HOST_MEMORY => "VK_ERROR_OUT_OF_HOST_MEMORY",
+        vk::ERROR_OUT_OF_DEVICE_MEMORY => "VK_ERROR_OUT_OF_DEVICE_MEMORY",
+        vk::ERROR_INITIALIZATION_FAILED => "VK_ERROR_INITIALIZATION_FAILED",
+        vk::ERROR_DEVICE_LOST => "VK_ERROR_DEVICE_LOST",
+        vk::ERROR_MEMORY_MAP_FAILED => "VK_ERROR_MEMORY_MAP_FAILED",
+        vk::ERROR_LAYER_NOT_PRESENT => "VK_ERROR_LAYER_NOT_PRESENT",
+        vk::ERROR_EXTENSION_NOT_PRESENT => "VK_ERROR_EXTENSION_NOT_PRESENT",
+        vk::ERROR_FEATURE_NOT_PRESENT => "VK_ERROR_FEATURE_NOT_PRESENT",
+        vk::ERROR_INCOMPATIBLE_DRIVER => "VK_ERROR_INCOMPATIBLE_DRIVER",
+        vk::ERROR_TOO_MANY_OBJECTS => "VK_ERROR_TOO_MANY_OBJECTS",
+        vk::ERROR_FORMAT_NOT_SUPPORTED => "VK_ERROR_FORMAT_NOT_SUPPORTED",
+        vk::ERROR_SURFACE_LOST_KHR => "VK_ERROR_SURFACE_LOST_KHR",
+        vk::ERROR_NATIVE_WINDOW_IN_USE_KHR => "VK_ERROR_NATIVE_WINDOW_IN_USE_KHR",
+        vk::SUBOPTIMAL_KHR => "VK_SUBOPTIMAL_KHR",
+        vk::ERROR_OUT_OF_DATE_KHR => "VK_ERROR_OUT_OF_DATE_KHR",
+        vk::ERROR_INCOMPATIBLE_DISPLAY_KHR => "VK_ERROR_INCOMPATIBLE_DISPLAY_KHR",
+        vk::ERROR_VALIDATION_FAILED_EXT => "VK_ERROR_VALIDATION_FAILED_EXT",
+        vk::ERROR_OUT_OF_POOL_MEMORY_KHR => "VK_ERROR_OUT_OF_POOL_MEMORY",
+        _ => return None,
+    })
+}
+
+// Generates, for each function, a slot that holds the real function pointer and a wrapper that
+// forwards to it and records the call. The identifiers between brackets are the output
+// parameters whose content is recorded after the call.
+macro_rules! traced_functions {
+    ($($name:ident($($param:ident: $ty:ty),*) -> $ret:ty [$($out:ident)*];)+) => (
+        mod slots {
+            use std::sync::atomic::AtomicUsize;
+
+            $(
+                #[allow(non_upper_case_globals)]
+                pub static $name: AtomicUsize = AtomicUsize::new(0);
+            )+
+        }
+
+        $(
+            #[allow(non_snake_case)]
+            extern "system" fn $name($($param: $ty),*) -> $ret {
+                unsafe {
+                    let real: extern "system" fn($($ty),*) -> $ret =
+                        mem::transmute(slots::$name.load(Ordering::Relaxed));
+                    let start = super::begin_call();
+                    let ret = real($($param),*);
+                    if let Some(start) = start {
+                        super::end_call(start,
+                                        concat!("vk", stringify!($name)),
+                                        vec![$((stringify!($param), format!("{:?}", $param))),*],
+                                        vec![$((stringify!($out), super::format_output($out))),*],
+                                        super::TraceReturn::describe(&ret));
+                    }
+                    ret
+                }
+            }
+        )+
+
+        // Null-terminated names of the traced functions.
+        pub const NAMES: &[&[u8]] = &[$(concat!("vk", stringify!($name), "\0").as_bytes()),+];
+
+        // Returns the slot and the wrapper of the function with the given name, if it is traced.
+        pub fn lookup(name: &[u8]) -> Option<(&'static AtomicUsize, usize)> {
+            $(
+                if name == concat!("vk", stringify!($name)).as_bytes() {
+                    let wrapper: extern "system" fn($($ty),*) -> $ret = $name;
+                    return Some((&slots::$name, wrapper as usize));
+                }
+            )+
+
+            None
+        }
+    );
+}
+
+mod wrappers {
+    use std::ffi::CStr;
+    use std::mem;
+    use std::os::raw::c_char;
+    use std::os::raw::c_void;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+
+    use vk::*;
+
+    static GET_DEVICE_PROC_ADDR: AtomicUsize = AtomicUsize::new(0);
+
+    // Returns the function to use in place of `real`, which is `vkGetDeviceProcAddr`.
+    pub unsafe fn get_device_proc_addr(real: extern "system" fn() -> ())
+                                       -> extern "system" fn() -> () {
+        if real as usize == 0 {
+            return real;
+        }
+
+        GET_DEVICE_PROC_ADDR.store(real as usize, Ordering::Relaxed);
+        let replacement: extern "system" fn(Device, *const c_char) -> extern "system" fn() -> () =
+            GetDeviceProcAddr;
+        mem::transmute(replacement)
+    }
+
+    // Returns the wrapper of the traced functions that have been resolved with
+    // `vkGetInstanceProcAddr`, and forwards the other functions to `vkGetDeviceProcAddr`.
+    #[allow(non_snake_case)]
+    extern "system" fn GetDeviceProcAddr(device: Device, pName: *const c_char)
+                                         -> extern "system" fn() -> () {
+        unsafe {
+            let real: extern "system" fn(Device, *const c_char) -> extern "system" fn() -> () =
+                mem::transmute(GET_DEVICE_PROC_ADDR.load(Ordering::Relaxed));
+            let ptr = real(device, pName);
+            if ptr as usize == 0 {
+                return ptr;
+            }
+
+            match lookup(CStr::from_ptr(pName).to_bytes()) {
+                Some((slot, wrapper)) if slot.load(Ordering::Relaxed) != 0 => {
+                    mem::transmute(wrapper)
+                },
+                _ => ptr,
+            }
+        }
+    }
+
+    traced_functions! {
+        CreateInstance(pCreateInfo: *const InstanceCreateInfo,
+                       pAllocator: *const AllocationCallbacks,
+                       pInstance: *mut Instance) -> Result [pInstance];
+        EnumerateInstanceExtensionProperties(pLayerName: *const c_char, pPropertyCount: *mut u32,
+                                             pProperties: *mut ExtensionProperties) -> Result [];
+        EnumerateInstanceLayerProperties(pPropertyCount: *mut u32,
+                                         pProperties: *mut LayerProperties) -> Result [];
+        EnumerateInstanceVersion(pApiVersion: *mut u32) -> Result [pApiVersion];
+        DestroyInstance(instance: Instance, pAllocator: *const AllocationCallbacks) -> () [];
+        EnumeratePhysicalDevices(instance: Instance, pPhysicalDeviceCount: *mut u32,
+                                 pPhysicalDevices: *mut PhysicalDevice) -> Result [];
+        EnumerateDeviceExtensionProperties(physicalDevice: PhysicalDevice,
+                                           pLayerName: *const c_char, pPropertyCount: *mut u32,
+                                           pProperties: *mut ExtensionProperties) -> Result [];
+        CreateDevice(physicalDevice: PhysicalDevice, pCreateInfo: *const DeviceCreateInfo,
+                     pAllocator: *const AllocationCallbacks,
+                     pDevice: *mut Device) -> Result [pDevice];
+        DestroyDevice(device: Device, pAllocator: *const AllocationCallbacks) -> () [];
+        GetDeviceQueue(device: Device, queueFamilyIndex: u32, queueIndex: u32,
+                       pQueue: *mut Queue) -> () [pQueue];
+        QueueSubmit(queue: Queue, submitCount: u32, pSubmits: *const SubmitInfo,
+                    fence: Fence) -> Result [];
+        QueueWaitIdle(queue: Queue) -> Result [];
+        DeviceWaitIdle(device: Device) -> Result [];
+        AllocateMemory(device: Device, pAllocateInfo: *const MemoryAllocateInfo,
+                       pAllocator: *const AllocationCallbacks,
+                       pMemory: *mut DeviceMemory) -> Result [pMemory];
+        FreeMemory(device: Device, memory: DeviceMemory,
+                   pAllocator: *const AllocationCallbacks) -> () [];
+        MapMemory(device: Device, memory: DeviceMemory, offset: DeviceSize, size: DeviceSize,
+                  flags: MemoryMapFlags, ppData: *mut *mut c_void) -> Result [];
+        UnmapMemory(device: Device, memory: DeviceMemory) -> () [];
+        BindBufferMemory(device: Device, buffer: Buffer, memory: DeviceMemory,
+                         memoryOffset: DeviceSize) -> Result [];
+        BindImageMemory(device: Device, image: Image, memory: DeviceMemory,
+                        memoryOffset: DeviceSize) -> Result [];
+        CreateFence(device: Device, pCreateInfo: *const FenceCreateInfo,
+                    pAllocator: *const AllocationCallbacks, pFence: *mut Fence) -> Result [pFence];
+        DestroyFence(device: Device, fence: Fence, pAllocator: *const AllocationCallbacks) -> () [];
+        ResetFences(device: Device, fenceCount: u32, pFences: *const Fence) -> Result [];
+        GetFenceStatus(device: Device, fence: Fence) -> Result [];
+        WaitForFences(device: Device, fenceCount: u32, pFences: *const Fence, waitAll: Bool32,
+                      timeout: u64) -> Result [];
+        CreateSemaphore(device: Device, pCreateInfo: *const SemaphoreCreateInfo,
+                        pAllocator: *const AllocationCallbacks,
+                        pSemaphore: *mut Semaphore) -> Result [pSemaphore];
+        DestroySemaphore(device: Device, semaphore: Semaphore,
+                         pAllocator: *const AllocationCallbacks) -> () [];
+        CreateEvent(device: Device, pCreateInfo: *const EventCreateInfo,
+                    pAllocator: *const AllocationCallbacks, pEvent: *mut Event) -> Result [pEvent];
+        DestroyEvent(device: Device, event: Event, pAllocator: *const AllocationCallbacks) -> () [];
+        CreateBuffer(device: Device, pCreateInfo: *const BufferCreateInfo,
+                     pAllocator: *const AllocationCallbacks,
+                     pBuffer: *mut Buffer) -> Result [pBuffer];
+        DestroyBuffer(device: Device, buffer: Buffer,
+                      pAllocator: *const AllocationCallbacks) -> () [];
+        CreateBufferView(device: Device, pCreateInfo: *const BufferViewCreateInfo,
+                         pAllocator: *const AllocationCallbacks,
+                         pView: *mut BufferView) -> Result [pView];
+        DestroyBufferView(device: Device, bufferView: BufferView,
+                          pAllocator: *const AllocationCallbacks) -> () [];
+        CreateImage(device: Device, pCreateInfo: *const ImageCreateInfo,
+                    pAllocator: *const AllocationCallbacks, pImage: *mut Image) -> Result [pImage];
+        DestroyImage(device: Device, image: Image, pAllocator: *const AllocationCallbacks) -> () [];
+        CreateImageView(device: Device, pCreateInfo: *const ImageViewCreateInfo,
+                        pAllocator: *const AllocationCallbacks,
+                        pView: *mut ImageView) -> Result [pView];
+        DestroyImageView(device: Device, imageView: ImageView,
+                         pAllocator: *const AllocationCallbacks) -> () [];
+        CreateShaderModule(device: Device, pCreateInfo: *const ShaderModuleCreateInfo,
+                           pAllocator: *const AllocationCallbacks,
+                           pShaderModule: *mut ShaderModule) -> Result [pShaderModule];
+        DestroyShaderModule(device: Device, shaderModule: ShaderModule,
+                            pAllocator: *const AllocationCallbacks) -> () [];
+        CreateGraphicsPipelines(device: Device, pipelineCache: PipelineCache, createInfoCount: u32,
+                                pCreateInfos: *const GraphicsPipelineCreateInfo,
+                                pAllocator: *const AllocationCallbacks,
+                                pPipelines: *mut Pipeline) -> Result [];
+        CreateComputePipelines(device: Device, pipelineCache: PipelineCache, createInfoCount: u32,
+                               pCreateInfos: *const ComputePipelineCreateInfo,
+                               pAllocator: *const AllocationCallbacks,
+                               pPipelines: *mut Pipeline) -> Result [];
+        DestroyPipeline(device: Device, pipeline: Pipeline,
+                        pAllocator: *const AllocationCallbacks) -> () [];
+        CreatePipelineLayout(device: Device, pCreateInfo: *const PipelineLayoutCreateInfo,
+                             pAllocator: *const AllocationCallbacks,
+                             pPipelineLayout: *mut PipelineLayout) -> Result [pPipelineLayout];
+        DestroyPipelineLayout(device: Device, pipelineLayout: PipelineLayout,
+                              pAllocator: *const AllocationCallbacks) -> () [];
+        CreateSampler(device: Device, pCreateInfo: *const SamplerCreateInfo,
+                      pAllocator: *const AllocationCallbacks,
+                      pSampler: *mut Sampler) -> Result [pSampler];
+        DestroySampler(device: Device, sampler: Sampler,
+                       pAllocator: *const AllocationCallbacks) -> () [];
+        CreateDescriptorSetLayout(device: Device,
+                                  pCreateInfo: *const DescriptorSetLayoutCreateInfo,
+                                  pAllocator: *const AllocationCallbacks,
+                                  pSetLayout: *mut DescriptorSetLayout) -> Result [pSetLayout];
+        DestroyDescriptorSetLayout(device: Device, descriptorSetLayout: DescriptorSetLayout,
+                                   pAllocator: *const AllocationCallbacks) -> () [];
+        CreateDescriptorPool(device: Device, pCreateInfo: *const DescriptorPoolCreateInfo,
+                             pAllocator: *const AllocationCallbacks,
+                             pDescriptorPool: *mut DescriptorPool) -> Result [pDescriptorPool];
+        DestroyDescriptorPool(device: Device, descriptorPool: DescriptorPool,
+                              pAllocator: *const AllocationCallbacks) -> () [];
+        ResetDescriptorPool(device: Device, descriptorPool: DescriptorPool,
+                            flags: DescriptorPoolResetFlags) -> Result [];
+        AllocateDescriptorSets(device: Device, pAllocateInfo: *const DescriptorSetAllocateInfo,
+                               pDescriptorSets: *mut DescriptorSet) -> Result [];
+        FreeDescriptorSets(device: Device, descriptorPool: DescriptorPool, descriptorSetCount: u32,
+                           pDescriptorSets: *const DescriptorSet) -> Result [];
+        UpdateDescriptorSets(device: Device, descriptorWriteCount: u32,
+                             pDescriptorWrites: *const WriteDescriptorSet,
+                             descriptorCopyCount: u32,
+                             pDescriptorCopies: *const CopyDescriptorSet) -> () [];
+        CreateFramebuffer(device: Device, pCreateInfo: *const FramebufferCreateInfo,
+                          pAllocator: *const AllocationCallbacks,
+                          pFramebuffer: *mut Framebuffer) -> Result [pFramebuffer];
+        DestroyFramebuffer(device: Device, framebuffer: Framebuffer,
+                           pAllocator: *const AllocationCallbacks) -> () [];
+        CreateRenderPass(device: Device, pCreateInfo: *const RenderPassCreateInfo,
+                         pAllocator: *const AllocationCallbacks,
+                         pRenderPass: *mut RenderPass) -> Result [pRenderPass];
+        DestroyRenderPass(device: Device, renderPass: RenderPass,
+                          pAllocator: *const AllocationCallbacks) -> () [];
+        CreateCommandPool(device: Device, pCreateInfo: *const CommandPoolCreateInfo,
+                          pAllocator: *const AllocationCallbacks,
+                          pCommandPool: *mut CommandPool) -> Result [pCommandPool];
+        DestroyCommandPool(device: Device, commandPool: CommandPool,
+                           pAllocator: *const AllocationCallbacks) -> () [];
+        ResetCommandPool(device: Device, commandPool: CommandPool,
+                         flags: CommandPoolResetFlags) -> Result [];
+        AllocateCommandBuffers(device: Device, pAllocateInfo: *const CommandBufferAllocateInfo,
+                               pCommandBuffers: *mut CommandBuffer) -> Result [];
+        FreeCommandBuffers(device: Device, commandPool: CommandPool, commandBufferCount: u32,
+                           pCommandBuffers: *const CommandBuffer) -> () [];
+        BeginCommandBuffer(commandBuffer: CommandBuffer,
+                           pBeginInfo: *const CommandBufferBeginInfo) -> Result [];
+        EndCommandBuffer(commandBuffer: CommandBuffer) -> Result [];
+        ResetCommandBuffer(commandBuffer: CommandBuffer,
+                           flags: CommandBufferResetFlags) -> Result [];
+        CmdBindPipeline(commandBuffer: CommandBuffer, pipelineBindPoint: PipelineBindPoint,
+                        pipeline: Pipeline) -> () [];
+        CmdSetViewport(commandBuffer: CommandBuffer, firstViewport: u32, viewportCount: u32,
+                       pViewports: *const Viewport) -> () [];
+        CmdSetScissor(commandBuffer: CommandBuffer, firstScissor: u32, scissorCount: u32,
+                      pScissors: *const Rect2D) -> () [];
+        CmdBindDescriptorSets(commandBuffer: CommandBuffer, pipelineBindPoint: PipelineBindPoint,
+                              layout: PipelineLayout, firstSet: u32, descriptorSetCount: u32,
+                              pDescriptorSets: *const DescriptorSet, dynamicOffsetCount: u32,
+                              pDynamicOffsets: *const u32) -> () [];
+        CmdBindIndexBuffer(commandBuffer: CommandBuffer, buffer: Buffer, offset: DeviceSize,
+                           indexType: IndexType) -> () [];
+        CmdBindVertexBuffers(commandBuffer: CommandBuffer, firstBinding: u32, bindingCount: u32,
+                             pBuffers: *const Buffer, pOffsets: *const DeviceSize) -> () [];
+        CmdDraw(commandBuffer: CommandBuffer, vertexCount: u32, instanceCount: u32,
+                firstVertex: u32, firstInstance: u32) -> () [];
+        CmdDrawIndexed(commandBuffer: CommandBuffer, indexCount: u32, instanceCount: u32,
+                       firstIndex: u32, vertexOffset: i32, firstInstance: u32) -> () [];
+        CmdDrawIndirect(commandBuffer: CommandBuffer, buffer: Buffer, offset: DeviceSize,
+                        drawCount: u32, stride: u32) -> () [];
+        CmdDrawIndexedIndirect(commandBuffer: CommandBuffer, buffer: Buffer, offset: DeviceSize,
+                               drawCount: u32, stride: u32) -> () [];
+        CmdDispatch(commandBuffer: CommandBuffer, x: u32, y: u32, z: u32) -> () [];
+        CmdDispatchIndirect(commandBuffer: CommandBuffer, buffer: Buffer,
+                            offset: DeviceSize) -> () [];
+        CmdCopyBuffer(commandBuffer: CommandBuffer, srcBuffer: Buffer, dstBuffer: Buffer,
+                      regionCount: u32, pRegions: *const BufferCopy) -> () [];
+        CmdCopyImage(commandBuffer: CommandBuffer, srcImage: Image, srcImageLayout: ImageLayout,
+                     dstImage: Image, dstImageLayout: ImageLayout, regionCount: u32,
+                     pRegions: *const ImageCopy) -> () [];
+        CmdBlitImage(commandBuffer: CommandBuffer, srcImage: Image, srcImageLayout: ImageLayout,
+                     dstImage: Image, dstImageLayout: ImageLayout, regionCount: u32,
+                     pRegions: *const ImageBlit, filter: Filter) -> () [];
+        CmdCopyBufferToImage(commandBuffer: CommandBuffer, srcBuffer: Buffer, dstImage: Image,
+                             dstImageLayout: ImageLayout, regionCount: u32,
+                             pRegions: *const BufferImageCopy) -> () [];
+        CmdCopyImageToBuffer(commandBuffer: CommandBuffer, srcImage: Image,
+                             srcImageLayout: ImageLayout, dstBuffer: Buffer, regionCount: u32,
+                             pRegions: *const BufferImageCopy) -> () [];
+        CmdUpdateBuffer(commandBuffer: CommandBuffer, dstBuffer: Buffer, dstOffset: DeviceSize,
+                        dataSize: DeviceSize, pData: *const u32) -> () [];
+        CmdFillBuffer(commandBuffer: CommandBuffer, dstBuffer: Buffer, dstOffset: DeviceSize,
+                      size: DeviceSize, data: u32) -> () [];
+        CmdClearColorImage(commandBuffer: CommandBuffer, image: Image, imageLayout: ImageLayout,
+                           pColor: *const ClearColorValue, rangeCount: u32,
+                           pRanges: *const ImageSubresourceRange) -> () [];
+        CmdPipelineBarrier(commandBuffer: CommandBuffer, srcStageMask: PipelineStageFlags,
+                           dstStageMask: PipelineStageFlags, dependencyFlags: DependencyFlags,
+                           memoryBarrierCount: u32, pMemoryBarriers: *const MemoryBarrier,
+                           bufferMemoryBarrierCount: u32,
+                           pBufferMemoryBarriers: *const BufferMemoryBarrier,
+                           imageMemoryBarrierCount: u32,
+                           pImageMemoryBarriers: *const ImageMemoryBarrier) -> () [];
+        CmdPushConstants(commandBuffer: CommandBuffer, layout: PipelineLayout,
+                         stageFlags: ShaderStageFlags, offset: u32, size: u32,
+                         pValues: *const c_void) -> () [];
+        CmdBeginRenderPass(commandBuffer: CommandBuffer,
+                           pRenderPassBegin: *const RenderPassBeginInfo,
+                           contents: SubpassContents) -> () [];
+        CmdNextSubpass(commandBuffer: CommandBuffer, contents: SubpassContents) -> () [];
+        CmdEndRenderPass(commandBuffer: CommandBuffer) -> () [];
+        CmdExecuteCommands(commandBuffer: CommandBuffer, commandBufferCount: u32,
+                           pCommandBuffers: *const CommandBuffer) -> () [];
+        CreateSwapchainKHR(device: Device, pCreateInfo: *const SwapchainCreateInfoKHR,
+                           pAllocator: *const AllocationCallbacks,
+                           pSwapchain: *mut SwapchainKHR) -> Result [pSwapchain];
+        DestroySwapchainKHR(device: Device, swapchain: SwapchainKHR,
+                            pAllocator: *const AllocationCallbacks) -> () [];
+        GetSwapchainImagesKHR(device: Device, swapchain: SwapchainKHR,
+                              pSwapchainImageCount: *mut u32,
+                              pSwapchainImages: *mut Image) -> Result [];
+        AcquireNextImageKHR(device: Device, swapchain: SwapchainKHR, timeout: u64,
+                            semaphore: Semaphore, fence: Fence,
+                            pImageIndex: *mut u32) -> Result [pImageIndex];
+        QueuePresentKHR(queue: Queue, pPresentInfo: *const PresentInfoKHR) -> Result [];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use instance::loader::FunctionPointers;
+    use std::env;
+    use std::fs;
+    use std::ptr;
+    use version::Version;
+
+    // The tracing state is global, so the tests must not run concurrently.
+    lazy_static! {
+        static ref TEST_LOCK: Mutex<()> = Mutex::new(());
+    }
+
+    fn lock() -> ::std::sync::MutexGuard<'static, ()> {
+        TEST_LOCK.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    extern "system" fn enumerate_instance_version(version: *mut u32) -> vk::Result {
+        unsafe {
+            *version = Version::V1_1.into_vulkan_version();
+        }
+        vk::SUCCESS
+    }
+
+    extern "system" fn create_buffer(_: vk::Device, _: *const vk::BufferCreateInfo,
+                                     _: *const vk::AllocationCallbacks, buffer: *mut vk::Buffer)
+                                     -> vk::Result {
+        unsafe {
+            *buffer = 42;
+        }
+        vk::ERROR_OUT_OF_DEVICE_MEMORY
+    }
+
+    // Function of a driver, that `vkGetDeviceProcAddr` returns for devices other than 5.
+    extern "system" fn driver_create_buffer(_: vk::Device, _: *const vk::BufferCreateInfo,
+                                            _: *const vk::AllocationCallbacks,
+                                            _: *mut vk::Buffer)
+                                            -> vk::Result {
+        vk::ERROR_INITIALIZATION_FAILED
+    }
+
+    extern "system" fn get_physical_device_features(_: vk::PhysicalDevice,
+                                                    _: *mut vk::PhysicalDeviceFeatures) {
+    }
+
+    extern "system" fn cmd_set_line_width(_: vk::CommandBuffer, _: f32) {
+    }
+
+    extern "system" fn get_device_proc_addr(device: vk::Device, name: *const c_char)
+                                            -> extern "system" fn() -> () {
+        unsafe {
+            let ptr = match CStr::from_ptr(name).to_bytes() {
+                b"vkCreateBuffer" if device == 5 => create_buffer as *const () as usize,
+                b"vkCreateBuffer" => driver_create_buffer as *const () as usize,
+                b"vkCmdSetLineWidth" => cmd_set_line_width as *const () as usize,
+                _ => 0,
+            };
+            mem::transmute(ptr)
+        }
+    }
+
+    // Loader that provides a few fake functions. `vkCreateBuffer` is only available from an
+    // instance, like the trampolines of the Vulkan loader.
+    struct FakeLoader;
+
+    unsafe impl Loader for FakeLoader {
+        unsafe fn get_instance_proc_addr(&self, instance: vk::Instance, name: *const c_char)
+                                         -> extern "system" fn() -> () {
+            let ptr: *const () = match CStr::from_ptr(name).to_bytes() {
+                b"vkEnumerateInstanceVersion" => enumerate_instance_version as *const (),
+                b"vkGetDeviceProcAddr" => get_device_proc_addr as *const (),
+                b"vkGetPhysicalDeviceFeatures" => get_physical_device_features as *const (),
+                b"vkCreateBuffer" if instance != 0 => create_buffer as *const (),
+                _ => ptr::null(),
+            };
+            mem::transmute(ptr)
+        }
+    }
+
+    fn load<L: Loader>(loader: &L, name: &[u8]) -> usize {
+        let name = CStr::from_bytes_with_nul(name).unwrap();
+        unsafe { loader.get_instance_proc_addr(1, name.as_ptr()) as usize }
+    }
+
+    #[test]
+    fn traces_entry_points() {
+        let _lock = lock();
+        let sink = Arc::new(MemoryTraceSink::new());
+        let loader = TracingLoader::new(FakeLoader, sink.clone()).unwrap();
+        let function_pointers = FunctionPointers::new(loader);
+
+        assert_eq!(function_pointers.api_version().unwrap(), Version::V1_1);
+
+        let calls = sink.take();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].name, "vkEnumerateInstanceVersion");
+        assert_eq!(calls[0].arguments[0].0, "pApiVersion");
+        assert_eq!(calls[0].outputs,
+                   vec![("pApiVersion", Version::V1_1.into_vulkan_version().to_string())]);
+        assert_eq!(calls[0].result, Some("VK_SUCCESS".to_owned()));
+        assert_eq!(calls[0].thread, thread::current().id());
+    }
+
+    #[test]
+    fn traces_device_functions() {
+        let _lock = lock();
+        let sink = Arc::new(MemoryTraceSink::new());
+        let loader = TracingLoader::new(FakeLoader, sink.clone()).unwrap();
+
+        unsafe {
+            let get_device_proc_addr: extern "system" fn(vk::Device, *const c_char)
+                                                         -> extern "system" fn() -> () =
+                mem::transmute(load(&loader, b"vkGetDeviceProcAddr\0"));
+            let create_buffer: extern "system" fn(vk::Device, *const vk::BufferCreateInfo,
+                                                  *const vk::AllocationCallbacks,
+                                                  *mut vk::Buffer) -> vk::Result =
+                mem::transmute(get_device_proc_addr(5, b"vkCreateBuffer\0".as_ptr() as *const _));
+
+            let mut buffer = 0;
+            assert_eq!(create_buffer(5, ptr::null(), ptr::null(), &mut buffer),
+                       vk::ERROR_OUT_OF_DEVICE_MEMORY);
+            assert_eq!(buffer, 42);
+        }
+
+        let calls = sink.take();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].name, "vkCreateBuffer");
+        assert_eq!(calls[0].arguments[0], ("device", "5".to_owned()));
+        assert_eq!(calls[0].arguments[1], ("pCreateInfo", "0x0".to_owned()));
+        assert_eq!(calls[0].outputs, vec![("pBuffer", "42".to_owned())]);
+        assert_eq!(calls[0].result, Some("VK_ERROR_OUT_OF_DEVICE_MEMORY".to_owned()));
+    }
+
+    #[test]
+    fn devices_with_different_drivers() {
+        let _lock = lock();
+        let sink = Arc::new(MemoryTraceSink::new());
+        let loader = TracingLoader::new(FakeLoader, sink.clone()).unwrap();
+
+        unsafe {
+            let get_device_proc_addr: extern "system" fn(vk::Device, *const c_char)
+                                                         -> extern "system" fn() -> () =
+                mem::transmute(load(&loader, b"vkGetDeviceProcAddr\0"));
+            let name = b"vkCreateBuffer\0".as_ptr() as *const _;
+            let first = get_device_proc_addr(5, name) as usize;
+            let second = get_device_proc_addr(6, name) as usize;
+            assert_eq!(first, second);
+
+            // The calls go through the function returned by `vkGetInstanceProcAddr`, which
+            // dispatches to the right driver.
+            let create_buffer: extern "system" fn(vk::Device, *const vk::BufferCreateInfo,
+                                                  *const vk::AllocationCallbacks,
+                                                  *mut vk::Buffer) -> vk::Result =
+                mem::transmute(second);
+            let mut buffer = 0;
+            assert_eq!(create_buffer(6, ptr::null(), ptr::null(), &mut buffer),
+                       vk::ERROR_OUT_OF_DEVICE_MEMORY);
+
+            // Functions that aren't traced come from `vkGetDeviceProcAddr`.
+            assert_eq!(get_device_proc_addr(6, b"vkCmdSetLineWidth\0".as_ptr() as *const _)
+                           as usize,
+                       cmd_set_line_width as *const () as usize);
+            assert_eq!(get_device_proc_addr(6, b"vkCreateImage\0".as_ptr() as *const _)
+                           as usize,
+                       0);
+        }
+
+        let calls = sink.take();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].name, "vkCreateBuffer");
+        assert_eq!(calls[0].arguments[0], ("device", "6".to_owned()));
+    }
+
+    #[test]
+    fn untraced_functions() {
+        let _lock = lock();
+        let loader = TracingLoader::new(FakeLoader, MemoryTraceSink::new()).unwrap();
+
+        // Missing functions stay missing.
+        assert_eq!(load(&loader, b"vkCreateImage\0"), 0);
+        // Functions that aren't traced are returned untouched.
+        assert_eq!(load(&loader, b"vkGetPhysicalDeviceFeatures\0"),
+                   get_physical_device_features as *const () as usize);
+        // The others are wrapped.
+        assert!(load(&loader, b"vkEnumerateInstanceVersion\0") !=
+                    enumerate_instance_version as *const () as usize);
+    }
+
+    #[test]
+    fn stops_after_drop() {
+        let _lock = lock();
+        let sink = Arc::new(MemoryTraceSink::new());
+        let loader = TracingLoader::new(FakeLoader, sink.clone()).unwrap();
+        let function_pointers = FunctionPointers::new(loader);
+
+        assert_eq!(TracingLoader::new(FakeLoader, MemoryTraceSink::new()).err(),
+                   Some(TracingLoaderCreationError::AlreadyTracing));
+
+        unsafe {
+            let enumerate_instance_version: extern "system" fn(*mut u32) -> vk::Result =
+                mem::transmute(function_pointers
+                                   .get_instance_proc_addr(0,
+                                                           b"vkEnumerateInstanceVersion\0"
+                                                               .as_ptr() as *const _));
+            drop(function_pointers);
+
+            let mut version = 0;
+            assert_eq!(enumerate_instance_version(&mut version), vk::SUCCESS);
+            assert_eq!(version, Version::V1_1.into_vulkan_version());
+        }
+
+        assert!(sink.take().is_empty());
+        let _loader = TracingLoader::new(FakeLoader, MemoryTraceSink::new()).unwrap();
+    }
+
+    #[test]
+    fn file_sink() {
+        let _lock = lock();
+        let path = env::temp_dir().join(format!("vulkano-trace-test-{}.txt", ::std::process::id()));
+        let sink = FileTraceSink::create(&path).unwrap();
+        let function_pointers = FunctionPointers::new(TracingLoader::new(FakeLoader, sink)
+                                                          .unwrap());
+        function_pointers.api_version().unwrap();
+        drop(function_pointers);
+
+        let content = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let lines = content.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].contains("vkEnumerateInstanceVersion(pApiVersion: 0x"));
+        assert!(lines[0].contains(&format!(") -> VK_SUCCESS [*pApiVersion = {}]",
+                                           Version::V1_1.into_vulkan_version())));
+    }
+}
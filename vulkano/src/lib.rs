@@ -57,6 +57,14 @@
 //!   `GpuFuture`s allow you to chain multiple submissions together and are essential to performing
 //!   multiple operations on multiple different GPU queues.
 //!
+//! # Cargo features
+//!
+//! - `serde`: implements `Serialize` and `Deserialize` for the types that describe a
+//!   configuration rather than a Vulkan object, such as `Features`, `Format`, the sampler and
+//!   pipeline state descriptions, attachment and pass descriptions, usage flags and
+//!   [`LimitsSnapshot`](instance/struct.LimitsSnapshot.html). This allows loading them from data
+//!   files.
+//!
 
 //#![warn(missing_docs)]        // TODO: activate
 #![allow(dead_code)] // TODO: remove
@@ -66,6 +74,13 @@ extern crate crossbeam;
 extern crate fnv;
 #[macro_use]
 extern crate lazy_static;
+#[macro_use]
+extern crate log;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
 extern crate shared_library;
 extern crate smallvec;
 extern crate vk_sys as vk;
@@ -77,6 +92,7 @@ mod tests;
 #[macro_use]
 mod extensions;
 mod features;
+mod mock;
 mod version;
 
 pub mod buffer;
@@ -220,10 +236,13 @@ pub(crate) enum Error {
     IncompatibleDisplay = vk::ERROR_INCOMPATIBLE_DISPLAY_KHR,
     ValidationFailed = vk::ERROR_VALIDATION_FAILED_EXT,
     OutOfPoolMemory = vk::ERROR_OUT_OF_POOL_MEMORY_KHR,
+    InvalidExternalHandle = vk::ERROR_INVALID_EXTERNAL_HANDLE_KHR,
 }
 
 /// Checks whether the result returned correctly.
 fn check_errors(result: vk::Result) -> Result<Success, Error> {
+    instance::debug::check_strict_validation();
+
     match result {
         vk::SUCCESS => Ok(Success::Success),
         vk::NOT_READY => Ok(Success::NotReady),
@@ -249,6 +268,7 @@ fn check_errors(result: vk::Result) -> Result<Success, Error> {
         vk::ERROR_INCOMPATIBLE_DISPLAY_KHR => Err(Error::IncompatibleDisplay),
         vk::ERROR_VALIDATION_FAILED_EXT => Err(Error::ValidationFailed),
         vk::ERROR_OUT_OF_POOL_MEMORY_KHR => Err(Error::OutOfPoolMemory),
+        vk::ERROR_INVALID_EXTERNAL_HANDLE_KHR => Err(Error::InvalidExternalHandle),
         vk::ERROR_INVALID_SHADER_NV => panic!("Vulkan function returned \
                                                VK_ERROR_INVALID_SHADER_NV"),
         c => unreachable!("Unexpected error code returned by Vulkan: {}", c),
@@ -9,11 +9,16 @@
 
 use std::error;
 use std::fmt;
+#[cfg(unix)]
+use std::fs::File;
 use std::mem::MaybeUninit;
 use std::ops::Deref;
 use std::ops::DerefMut;
 use std::ops::Range;
+use std::os::raw::c_int;
 use std::os::raw::c_void;
+#[cfg(unix)]
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd};
 use std::ptr;
 use std::sync::Arc;
 
@@ -26,6 +31,8 @@ use device::DeviceOwned;
 use instance::MemoryType;
 use memory::Content;
 use memory::DedicatedAlloc;
+use memory::ExternalMemoryHandleType;
+use version::Version;
 use vk;
 
 /// Represents memory that has been allocated.
@@ -48,6 +55,7 @@ pub struct DeviceMemory {
     device: Arc<Device>,
     size: usize,
     memory_type_index: u32,
+    export_handle_types: ExternalMemoryHandleType,
 }
 
 impl DeviceMemory {
@@ -78,6 +86,112 @@ impl DeviceMemory {
     pub fn dedicated_alloc(device: Arc<Device>, memory_type: MemoryType, size: usize,
                            resource: DedicatedAlloc)
                            -> Result<DeviceMemory, DeviceMemoryAllocError> {
+        DeviceMemory::alloc_impl(device,
+                                 memory_type,
+                                 size,
+                                 resource,
+                                 ExternalMemoryHandleType::none(),
+                                 None)
+    }
+
+    /// Same as `alloc`, but the memory can later be exported with the given handle types.
+    ///
+    /// The `VK_KHR_external_memory` extension must be enabled on the device. Exporting as a file
+    /// descriptor additionally requires `VK_KHR_external_memory_fd`, and exporting as a dma-buf
+    /// requires `VK_EXT_external_memory_dma_buf`.
+    ///
+    /// If the memory is going to be bound to a buffer or an image, that resource must have been
+    /// created with the same handle types. See `UnsafeBuffer::with_external_memory` and
+    /// `UnsafeImage::with_external_memory`.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `handle_types` is empty.
+    /// - Panics if `size` is 0.
+    /// - Panics if `memory_type` doesn't belong to the same physical device as `device`.
+    ///
+    #[inline]
+    pub fn alloc_exportable(device: Arc<Device>, memory_type: MemoryType, size: usize,
+                            handle_types: ExternalMemoryHandleType)
+                            -> Result<DeviceMemory, DeviceMemoryAllocError> {
+        DeviceMemory::dedicated_alloc_exportable(device,
+                                                 memory_type,
+                                                 size,
+                                                 DedicatedAlloc::None,
+                                                 handle_types)
+    }
+
+    /// Equivalent of `dedicated_alloc` for `alloc_exportable`.
+    ///
+    /// Some implementations require exportable memory to be a dedicated allocation. This can be
+    /// checked with `PhysicalDevice::external_buffer_properties` or
+    /// `PhysicalDevice::external_image_format_properties`.
+    pub fn dedicated_alloc_exportable(device: Arc<Device>, memory_type: MemoryType, size: usize,
+                                      resource: DedicatedAlloc,
+                                      handle_types: ExternalMemoryHandleType)
+                                      -> Result<DeviceMemory, DeviceMemoryAllocError> {
+        assert!(!handle_types.is_empty());
+        check_external_memory_extensions(&device, &handle_types)?;
+        DeviceMemory::alloc_impl(device, memory_type, size, resource, handle_types, None)
+    }
+
+    /// Imports memory that was exported as a POSIX file descriptor, by this process or by
+    /// another one.
+    ///
+    /// `handle_type` must be either `opaque_fd` or `dma_buf`, and must be the only handle type
+    /// that is enabled. On success, the file descriptor is owned by the Vulkan implementation and
+    /// is closed when the returned `DeviceMemory` is freed. On error, the file is closed.
+    ///
+    /// # Safety
+    ///
+    /// - For `opaque_fd`, the file descriptor must have been exported by a device with the same
+    ///   device and driver UUIDs as `device`, and `size` and `memory_type` must match the
+    ///   allocation it was exported from.
+    /// - If the exported memory was a dedicated allocation, then `resource` must be a resource
+    ///   that was created with the same parameters as the original one.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `handle_type` doesn't have exactly one of `opaque_fd` or `dma_buf` set.
+    /// - Panics if `size` is 0.
+    /// - Panics if `memory_type` doesn't belong to the same physical device as `device`.
+    ///
+    #[cfg(unix)]
+    pub unsafe fn import_fd(device: Arc<Device>, memory_type: MemoryType, size: usize,
+                            resource: DedicatedAlloc, handle_type: ExternalMemoryHandleType,
+                            file: File)
+                            -> Result<DeviceMemory, DeviceMemoryAllocError> {
+        assert!(handle_type == ExternalMemoryHandleType::posix() ||
+                    handle_type == ExternalMemoryHandleType {
+                        dma_buf: true,
+                        ..ExternalMemoryHandleType::none()
+                    },
+                "only `opaque_fd` or `dma_buf` can be imported from a file descriptor");
+
+        if !device.loaded_extensions().khr_external_memory_fd {
+            return Err(DeviceMemoryAllocError::MissingExtension {
+                           name: "VK_KHR_external_memory_fd",
+                       });
+        }
+        check_external_memory_extensions(&device, &handle_type)?;
+
+        let fd = file.as_raw_fd();
+        let mem = DeviceMemory::alloc_impl(device,
+                                           memory_type,
+                                           size,
+                                           resource,
+                                           ExternalMemoryHandleType::none(),
+                                           Some((handle_type, fd)))?;
+
+        // The implementation now owns the file descriptor.
+        let _ = file.into_raw_fd();
+        Ok(mem)
+    }
+
+    fn alloc_impl(device: Arc<Device>, memory_type: MemoryType, size: usize,
+                  resource: DedicatedAlloc, export_handle_types: ExternalMemoryHandleType,
+                  import: Option<(ExternalMemoryHandleType, c_int)>)
+                  -> Result<DeviceMemory, DeviceMemoryAllocError> {
         assert!(size >= 1);
         assert_eq!(device.physical_device().internal_object(),
                    memory_type.physical_device().internal_object());
@@ -98,7 +212,9 @@ impl DeviceMemory {
             let vk = device.pointers();
 
             // Decide whether we are going to pass a `vkMemoryDedicatedAllocateInfoKHR`.
-            let dedicated_alloc_info = if device.loaded_extensions().khr_dedicated_allocation {
+            let dedicated_alloc_info = if device.api_version() >= Version::V1_1 ||
+                device.loaded_extensions().khr_dedicated_allocation
+            {
                 match resource {
                     DedicatedAlloc::Buffer(buffer) => {
                         Some(vk::MemoryDedicatedAllocateInfoKHR {
@@ -124,12 +240,39 @@ impl DeviceMemory {
                 None
             };
 
+            let mut p_next = dedicated_alloc_info
+                .as_ref()
+                .map(|i| i as *const vk::MemoryDedicatedAllocateInfoKHR)
+                .unwrap_or(ptr::null()) as *const c_void;
+
+            let export_info = if !export_handle_types.is_empty() {
+                Some(vk::ExportMemoryAllocateInfoKHR {
+                         sType: vk::STRUCTURE_TYPE_EXPORT_MEMORY_ALLOCATE_INFO_KHR,
+                         pNext: p_next,
+                         handleTypes: export_handle_types.to_bits(),
+                     })
+            } else {
+                None
+            };
+            if let Some(ref export_info) = export_info {
+                p_next = export_info as *const vk::ExportMemoryAllocateInfoKHR as *const _;
+            }
+
+            let import_info = import.map(|(handle_type, fd)| {
+                vk::ImportMemoryFdInfoKHR {
+                    sType: vk::STRUCTURE_TYPE_IMPORT_MEMORY_FD_INFO_KHR,
+                    pNext: p_next,
+                    handleType: handle_type.to_bits(),
+                    fd: fd,
+                }
+            });
+            if let Some(ref import_info) = import_info {
+                p_next = import_info as *const vk::ImportMemoryFdInfoKHR as *const _;
+            }
+
             let infos = vk::MemoryAllocateInfo {
                 sType: vk::STRUCTURE_TYPE_MEMORY_ALLOCATE_INFO,
-                pNext: dedicated_alloc_info
-                    .as_ref()
-                    .map(|i| i as *const vk::MemoryDedicatedAllocateInfoKHR)
-                    .unwrap_or(ptr::null()) as *const _,
+                pNext: p_next,
                 allocationSize: size as u64,
                 memoryTypeIndex: memory_type.id(),
             };
@@ -148,6 +291,7 @@ impl DeviceMemory {
                device: device,
                size: size,
                memory_type_index: memory_type.id(),
+               export_handle_types: export_handle_types,
            })
     }
 
@@ -207,6 +351,87 @@ impl DeviceMemory {
     pub fn size(&self) -> usize {
         self.size
     }
+
+    /// Returns the handle types this memory can be exported as.
+    ///
+    /// This is empty unless the memory was allocated with `alloc_exportable` or
+    /// `dedicated_alloc_exportable`.
+    #[inline]
+    pub fn export_handle_types(&self) -> ExternalMemoryHandleType {
+        self.export_handle_types
+    }
+
+    /// Exports the memory as a POSIX file descriptor.
+    ///
+    /// `handle_type` must be either `opaque_fd` or `dma_buf`, and the memory must have been
+    /// allocated as exportable with that handle type. Each call returns a new file descriptor
+    /// that refers to the same memory and that is owned by the caller.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `handle_type` doesn't have exactly one of `opaque_fd` or `dma_buf` set.
+    ///
+    #[cfg(unix)]
+    pub fn export_fd(&self, handle_type: ExternalMemoryHandleType)
+                     -> Result<File, DeviceMemoryExportError> {
+        assert!(handle_type == ExternalMemoryHandleType::posix() ||
+                    handle_type == ExternalMemoryHandleType {
+                        dma_buf: true,
+                        ..ExternalMemoryHandleType::none()
+                    },
+                "only `opaque_fd` or `dma_buf` can be exported as a file descriptor");
+
+        if !self.device.loaded_extensions().khr_external_memory_fd {
+            return Err(DeviceMemoryExportError::MissingExtension {
+                           name: "VK_KHR_external_memory_fd",
+                       });
+        }
+
+        if !self.export_handle_types.superset_of(&handle_type) {
+            return Err(DeviceMemoryExportError::HandleTypeNotEnabled);
+        }
+
+        let fd = unsafe {
+            let vk = self.device.pointers();
+
+            let infos = vk::MemoryGetFdInfoKHR {
+                sType: vk::STRUCTURE_TYPE_MEMORY_GET_FD_INFO_KHR,
+                pNext: ptr::null(),
+                memory: self.memory,
+                handleType: handle_type.to_bits(),
+            };
+
+            let mut output = MaybeUninit::uninit();
+            check_errors(vk.GetMemoryFdKHR(self.device.internal_object(),
+                                           &infos,
+                                           output.as_mut_ptr()))?;
+            output.assume_init()
+        };
+
+        Ok(unsafe { File::from_raw_fd(fd) })
+    }
+}
+
+// Checks that the device extensions required by `handle_types` are enabled.
+fn check_external_memory_extensions(device: &Device, handle_types: &ExternalMemoryHandleType)
+                                    -> Result<(), DeviceMemoryAllocError> {
+    let extensions = device.loaded_extensions();
+
+    if !extensions.khr_external_memory {
+        return Err(DeviceMemoryAllocError::MissingExtension { name: "VK_KHR_external_memory" });
+    }
+    if handle_types.opaque_fd && !extensions.khr_external_memory_fd {
+        return Err(DeviceMemoryAllocError::MissingExtension {
+                       name: "VK_KHR_external_memory_fd",
+                   });
+    }
+    if handle_types.dma_buf && !extensions.ext_external_memory_dma_buf {
+        return Err(DeviceMemoryAllocError::MissingExtension {
+                       name: "VK_EXT_external_memory_dma_buf",
+                   });
+    }
+
+    Ok(())
 }
 
 unsafe impl DeviceOwned for DeviceMemory {
@@ -468,6 +693,10 @@ pub enum DeviceMemoryAllocError {
     TooManyObjects,
     /// Memory map failed.
     MemoryMapFailed,
+    /// A device extension required for this operation is not enabled.
+    MissingExtension { name: &'static str },
+    /// The handle that was passed for importing is not valid.
+    InvalidExternalHandle,
 }
 
 impl error::Error for DeviceMemoryAllocError {
@@ -478,6 +707,10 @@ impl error::Error for DeviceMemoryAllocError {
             DeviceMemoryAllocError::TooManyObjects =>
                 "the maximum number of allocations has been exceeded",
             DeviceMemoryAllocError::MemoryMapFailed => "memory map failed",
+            DeviceMemoryAllocError::MissingExtension { .. } =>
+                "a device extension required for this operation is not enabled",
+            DeviceMemoryAllocError::InvalidExternalHandle =>
+                "the handle that was passed for importing is not valid",
         }
     }
 
@@ -505,6 +738,7 @@ impl From<Error> for DeviceMemoryAllocError {
             e @ Error::OutOfDeviceMemory => DeviceMemoryAllocError::OomError(e.into()),
             Error::TooManyObjects => DeviceMemoryAllocError::TooManyObjects,
             Error::MemoryMapFailed => DeviceMemoryAllocError::MemoryMapFailed,
+            Error::InvalidExternalHandle => DeviceMemoryAllocError::InvalidExternalHandle,
             _ => panic!("unexpected error: {:?}", err),
         }
     }
@@ -517,11 +751,72 @@ impl From<OomError> for DeviceMemoryAllocError {
     }
 }
 
+/// Error type returned by `DeviceMemory::export_fd`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DeviceMemoryExportError {
+    /// Not enough memory available.
+    OomError(OomError),
+    /// The maximum number of file descriptors or handles has been exceeded.
+    TooManyObjects,
+    /// A device extension required for this operation is not enabled.
+    MissingExtension { name: &'static str },
+    /// The memory wasn't allocated as exportable with the requested handle type.
+    HandleTypeNotEnabled,
+}
+
+impl error::Error for DeviceMemoryExportError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            DeviceMemoryExportError::OomError(_) => "not enough memory available",
+            DeviceMemoryExportError::TooManyObjects =>
+                "the maximum number of file descriptors or handles has been exceeded",
+            DeviceMemoryExportError::MissingExtension { .. } =>
+                "a device extension required for this operation is not enabled",
+            DeviceMemoryExportError::HandleTypeNotEnabled =>
+                "the memory wasn't allocated as exportable with the requested handle type",
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&dyn error::Error> {
+        match *self {
+            DeviceMemoryExportError::OomError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for DeviceMemoryExportError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<Error> for DeviceMemoryExportError {
+    #[inline]
+    fn from(err: Error) -> DeviceMemoryExportError {
+        match err {
+            e @ Error::OutOfHostMemory |
+            e @ Error::OutOfDeviceMemory => DeviceMemoryExportError::OomError(e.into()),
+            Error::TooManyObjects => DeviceMemoryExportError::TooManyObjects,
+            _ => panic!("unexpected error: {:?}", err),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    #[cfg(unix)]
+    use std::fs::File;
+
     use OomError;
+    use memory::DedicatedAlloc;
     use memory::DeviceMemory;
     use memory::DeviceMemoryAllocError;
+    use memory::DeviceMemoryExportError;
+    use memory::ExternalMemoryHandleType;
 
     #[test]
     fn create() {
@@ -594,4 +889,77 @@ mod tests {
         }
         assert_eq!(*device.allocation_count().lock().unwrap(), 1);
     }
+
+    #[test]
+    fn export_requires_extension() {
+        let (device, _) = mock_dev_and_queue!();
+        let mem_ty = device.physical_device().memory_types().next().unwrap();
+        match DeviceMemory::alloc_exportable(device.clone(), mem_ty, 256,
+                                             ExternalMemoryHandleType::posix()) {
+            Err(DeviceMemoryAllocError::MissingExtension { .. }) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn export_fd_requires_extension() {
+        let (device, _) = mock_dev_and_queue!();
+        let mem_ty = device.physical_device().memory_types().next().unwrap();
+        let mem = DeviceMemory::alloc(device.clone(), mem_ty, 256).unwrap();
+        match mem.export_fd(ExternalMemoryHandleType::posix()) {
+            Err(DeviceMemoryExportError::MissingExtension { .. }) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn export_not_enabled_handle_type() {
+        let (device, _) = mock_dev_and_queue!(extensions: [khr_external_memory,
+                                                           khr_external_memory_fd]);
+        let mem_ty = device.physical_device().memory_types().next().unwrap();
+        let mem = DeviceMemory::alloc(device.clone(), mem_ty, 256).unwrap();
+        match mem.export_fd(ExternalMemoryHandleType::posix()) {
+            Err(DeviceMemoryExportError::HandleTypeNotEnabled) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn export_and_import_fd() {
+        let (device, _) = mock_dev_and_queue!(extensions: [khr_external_memory,
+                                                           khr_external_memory_fd]);
+        let mem_ty = device.physical_device().memory_types().next().unwrap();
+        let mem = DeviceMemory::alloc_exportable(device.clone(), mem_ty, 256,
+                                                 ExternalMemoryHandleType::posix())
+            .unwrap();
+        let file = mem.export_fd(ExternalMemoryHandleType::posix()).unwrap();
+
+        let imported = unsafe {
+            DeviceMemory::import_fd(device.clone(), mem_ty, 256, DedicatedAlloc::None,
+                                    ExternalMemoryHandleType::posix(), file)
+                .unwrap()
+        };
+        assert_eq!(imported.size(), 256);
+        assert!(imported.export_handle_types().is_empty());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn import_invalid_fd() {
+        let (device, _) = mock_dev_and_queue!(extensions: [khr_external_memory,
+                                                           khr_external_memory_fd]);
+        let mem_ty = device.physical_device().memory_types().next().unwrap();
+        let file = File::open("/dev/null").unwrap();
+
+        match unsafe {
+            DeviceMemory::import_fd(device.clone(), mem_ty, 256, DedicatedAlloc::None,
+                                    ExternalMemoryHandleType::posix(), file)
+        } {
+            Err(DeviceMemoryAllocError::InvalidExternalHandle) => (),
+            _ => panic!(),
+        }
+    }
 }
@@ -0,0 +1,181 @@
+// Copyright (c) 2020 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::ops::BitOr;
+use vk;
+
+/// Describes the handle types that can be used to share a memory allocation with an external
+/// API or another process.
+///
+/// Only `opaque_fd` and `dma_buf` can currently be exported or imported by vulkano. The other
+/// handle types are listed so that capability queries can report them.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ExternalMemoryHandleType {
+    pub opaque_fd: bool,
+    pub opaque_win32: bool,
+    pub opaque_win32_kmt: bool,
+    pub d3d11_texture: bool,
+    pub d3d11_texture_kmt: bool,
+    pub d3d12_heap: bool,
+    pub d3d12_resource: bool,
+    pub dma_buf: bool,
+}
+
+impl ExternalMemoryHandleType {
+    /// Builds an `ExternalMemoryHandleType` with all values set to false.
+    #[inline]
+    pub fn none() -> ExternalMemoryHandleType {
+        ExternalMemoryHandleType {
+            opaque_fd: false,
+            opaque_win32: false,
+            opaque_win32_kmt: false,
+            d3d11_texture: false,
+            d3d11_texture_kmt: false,
+            d3d12_heap: false,
+            d3d12_resource: false,
+            dma_buf: false,
+        }
+    }
+
+    /// Builds an `ExternalMemoryHandleType` with `opaque_fd` set to true and the rest to false.
+    ///
+    /// This is the handle type to use to share memory between two Vulkan devices or processes on
+    /// a POSIX system.
+    #[inline]
+    pub fn posix() -> ExternalMemoryHandleType {
+        ExternalMemoryHandleType {
+            opaque_fd: true,
+            ..ExternalMemoryHandleType::none()
+        }
+    }
+
+    /// Returns true if no handle type is enabled.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        *self == ExternalMemoryHandleType::none()
+    }
+
+    /// Returns true if all the handle types of `other` are also enabled in `self`.
+    #[inline]
+    pub fn superset_of(&self, other: &ExternalMemoryHandleType) -> bool {
+        (self.to_bits() & other.to_bits()) == other.to_bits()
+    }
+
+    #[inline]
+    pub(crate) fn to_bits(&self) -> vk::ExternalMemoryHandleTypeFlagsKHR {
+        let mut result = 0;
+        if self.opaque_fd {
+            result |= vk::EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_FD_BIT_KHR;
+        }
+        if self.opaque_win32 {
+            result |= vk::EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_WIN32_BIT_KHR;
+        }
+        if self.opaque_win32_kmt {
+            result |= vk::EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_WIN32_KMT_BIT_KHR;
+        }
+        if self.d3d11_texture {
+            result |= vk::EXTERNAL_MEMORY_HANDLE_TYPE_D3D11_TEXTURE_BIT_KHR;
+        }
+        if self.d3d11_texture_kmt {
+            result |= vk::EXTERNAL_MEMORY_HANDLE_TYPE_D3D11_TEXTURE_KMT_BIT_KHR;
+        }
+        if self.d3d12_heap {
+            result |= vk::EXTERNAL_MEMORY_HANDLE_TYPE_D3D12_HEAP_BIT_KHR;
+        }
+        if self.d3d12_resource {
+            result |= vk::EXTERNAL_MEMORY_HANDLE_TYPE_D3D12_RESOURCE_BIT_KHR;
+        }
+        if self.dma_buf {
+            result |= vk::EXTERNAL_MEMORY_HANDLE_TYPE_DMA_BUF_BIT_EXT;
+        }
+        result
+    }
+
+    #[inline]
+    pub(crate) fn from_bits(val: vk::ExternalMemoryHandleTypeFlagsKHR) -> ExternalMemoryHandleType {
+        ExternalMemoryHandleType {
+            opaque_fd: (val & vk::EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_FD_BIT_KHR) != 0,
+            opaque_win32: (val & vk::EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_WIN32_BIT_KHR) != 0,
+            opaque_win32_kmt: (val & vk::EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_WIN32_KMT_BIT_KHR) !=
+                0,
+            d3d11_texture: (val & vk::EXTERNAL_MEMORY_HANDLE_TYPE_D3D11_TEXTURE_BIT_KHR) != 0,
+            d3d11_texture_kmt: (val & vk::EXTERNAL_MEMORY_HANDLE_TYPE_D3D11_TEXTURE_KMT_BIT_KHR) !=
+                0,
+            d3d12_heap: (val & vk::EXTERNAL_MEMORY_HANDLE_TYPE_D3D12_HEAP_BIT_KHR) != 0,
+            d3d12_resource: (val & vk::EXTERNAL_MEMORY_HANDLE_TYPE_D3D12_RESOURCE_BIT_KHR) != 0,
+            dma_buf: (val & vk::EXTERNAL_MEMORY_HANDLE_TYPE_DMA_BUF_BIT_EXT) != 0,
+        }
+    }
+}
+
+impl BitOr for ExternalMemoryHandleType {
+    type Output = Self;
+
+    #[inline]
+    fn bitor(self, rhs: Self) -> Self {
+        ExternalMemoryHandleType::from_bits(self.to_bits() | rhs.to_bits())
+    }
+}
+
+/// Capabilities of the implementation regarding one external memory handle type, for a given
+/// buffer or image configuration.
+///
+/// Can be obtained with `PhysicalDevice::external_buffer_properties` or
+/// `PhysicalDevice::external_image_format_properties`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ExternalMemoryProperties {
+    /// True if the memory must be a dedicated allocation in order to be exported or imported.
+    pub dedicated_only: bool,
+    /// True if memory of this handle type can be exported.
+    pub exportable: bool,
+    /// True if memory of this handle type can be imported.
+    pub importable: bool,
+    /// Handle types that can be exported from memory that was imported with this handle type.
+    pub export_from_imported_handle_types: ExternalMemoryHandleType,
+    /// Handle types that can be specified at the same time as this one when creating the
+    /// resource or allocating its memory.
+    pub compatible_handle_types: ExternalMemoryHandleType,
+}
+
+impl ExternalMemoryProperties {
+    #[inline]
+    pub(crate) fn from_vulkan(props: &vk::ExternalMemoryPropertiesKHR)
+                              -> ExternalMemoryProperties {
+        let features = props.externalMemoryFeatures;
+
+        ExternalMemoryProperties {
+            dedicated_only: (features & vk::EXTERNAL_MEMORY_FEATURE_DEDICATED_ONLY_BIT_KHR) != 0,
+            exportable: (features & vk::EXTERNAL_MEMORY_FEATURE_EXPORTABLE_BIT_KHR) != 0,
+            importable: (features & vk::EXTERNAL_MEMORY_FEATURE_IMPORTABLE_BIT_KHR) != 0,
+            export_from_imported_handle_types:
+                ExternalMemoryHandleType::from_bits(props.exportFromImportedHandleTypes),
+            compatible_handle_types:
+                ExternalMemoryHandleType::from_bits(props.compatibleHandleTypes),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use memory::ExternalMemoryHandleType;
+
+    #[test]
+    fn bits_round_trip() {
+        let ty = ExternalMemoryHandleType {
+            opaque_fd: true,
+            dma_buf: true,
+            ..ExternalMemoryHandleType::none()
+        };
+
+        assert_eq!(ExternalMemoryHandleType::from_bits(ty.to_bits()), ty);
+        assert!(ty.superset_of(&ExternalMemoryHandleType::posix()));
+        assert!(!ExternalMemoryHandleType::posix().superset_of(&ty));
+        assert!(ExternalMemoryHandleType::none().is_empty());
+    }
+}
@@ -14,6 +14,7 @@ use memory::DeviceMemory;
 use memory::DeviceMemoryAllocError;
 use memory::MappedDeviceMemory;
 use memory::MemoryRequirements;
+use version::Version;
 
 pub use self::host_visible::StdHostVisibleMemoryTypePool;
 pub use self::host_visible::StdHostVisibleMemoryTypePoolAlloc;
@@ -118,7 +119,8 @@ pub unsafe trait MemoryPool: DeviceOwned {
 
         // Redirect to `self.alloc_generic` if we don't perform a dedicated allocation.
         if !requirements.prefer_dedicated ||
-            !self.device().loaded_extensions().khr_dedicated_allocation
+            (self.device().api_version() < Version::V1_1 &&
+                 !self.device().loaded_extensions().khr_dedicated_allocation)
         {
             let alloc = self.alloc_generic(mem_ty,
                                            requirements.size,
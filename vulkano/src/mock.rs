@@ -0,0 +1,2661 @@
+// Copyright (c) 2020 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! In-process mock of a Vulkan implementation, so that tests can create a device without a GPU.
+//!
+//! `MockIcd` is a `Loader` whose functions don't talk to any driver. Each instance created with
+//! it gets its own `Recorder`, which keeps track of what vulkano does with it: the functions that
+//! are called, the objects that are alive, the memory bound to buffers and images, the commands
+//! recorded in each command buffer and the submissions made to the queues.
+//!
+//! Calls that would be invalid on a real implementation, such as using a destroyed object or
+//! recording a command outside of `vkBeginCommandBuffer`, don't crash. They are reported as
+//! `MockError`s, along with the objects that are still alive when their device or instance is
+//! destroyed.
+//!
+//! The mock exposes a single Vulkan 1.0 physical device with every core feature, a queue family
+//! with graphics, compute and transfer support followed by a queue family with transfer support
+//! only, and one memory type that is both device local and host visible. Submissions are executed
+//! immediately.
+//!
+//! The only device extensions are the external memory, fence and semaphore ones, so that the
+//! error paths of importing can be tested, `VK_KHR_push_descriptor` and
+//! `VK_EXT_descriptor_indexing`, which only change the creation of layouts and the allocation of
+//! sets, `VK_KHR_descriptor_update_template`, whose templates don't look at the data they are
+//! given, and `VK_KHR_swapchain`. The file descriptors exported by the mock are opened on
+//! `/dev/null` and carry no payload. They are the only ones that can be imported. Importing one
+//! doesn't change the state of a fence or a semaphore, and gives new, empty memory. Other file
+//! descriptors are rejected with `VK_ERROR_INVALID_EXTERNAL_HANDLE`.
+//!
+//! The instance extensions are `VK_KHR_surface`, `VK_EXT_headless_surface` and
+//! `VK_EXT_debug_utils`. Headless surfaces can be presented to from the first queue family, and
+//! their swapchains hand out their images in order. `Recorder::make_swapchains_out_of_date`
+//! simulates a resize of the surfaces. Debug messengers can be created, but are never called.
+//! Debug labels and object names are accepted and only recorded.
+//!
+//! Functions that the mock doesn't implement are reported as missing to the loader.
+
+#![cfg(test)]
+
+use std::collections::HashMap;
+use std::ffi::CStr;
+#[cfg(unix)]
+use std::fs::File;
+use std::mem;
+use std::os::raw::c_char;
+use std::os::raw::c_int;
+#[cfg(unix)]
+use std::os::unix::io::{FromRawFd, IntoRawFd};
+use std::ptr;
+use std::slice;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+use instance::Instance;
+use instance::loader::Loader;
+use version::Version;
+
+use VulkanObject;
+use vk;
+
+lazy_static! {
+    // Recorder of each dispatchable handle created by the mock. This is how the functions find
+    // the state they work on.
+    static ref DISPATCHABLE: Mutex<HashMap<usize, Arc<Recorder>>> = Mutex::new(HashMap::new());
+}
+
+// Handles are unique across all the instances, so that dispatchable and non-dispatchable handles
+// can share the same map.
+static NEXT_HANDLE: AtomicUsize = AtomicUsize::new(1);
+
+// Number of queues of the first and of the second queue family.
+const QUEUE_COUNTS: [u32; 2] = [4, 1];
+const INSTANCE_EXTENSIONS: &[&str] = &["VK_KHR_surface", "VK_EXT_headless_surface",
+                                       "VK_EXT_debug_utils"];
+const DEVICE_EXTENSIONS: &[&str] = &["VK_KHR_external_memory", "VK_KHR_external_memory_fd",
+                                     "VK_KHR_external_fence", "VK_KHR_external_fence_fd",
+                                     "VK_KHR_external_semaphore", "VK_KHR_external_semaphore_fd",
+                                     "VK_KHR_push_descriptor", "VK_EXT_descriptor_indexing",
+                                     "VK_KHR_descriptor_update_template", "VK_KHR_swapchain"];
+// Minimum number of images of the swapchains, which is also the number of images they have.
+const MIN_IMAGE_COUNT: u32 = 2;
+const MEMORY_ALIGNMENT: u64 = 256;
+
+/// Implementation of `Loader` that returns the functions of the mock.
+pub struct MockIcd;
+
+unsafe impl Loader for MockIcd {
+    unsafe fn get_instance_proc_addr(&self, _: vk::Instance, name: *const c_char)
+                                     -> extern "system" fn() -> () {
+        mem::transmute(lookup(CStr::from_ptr(name).to_bytes()))
+    }
+}
+
+/// State of the mock for one instance and everything created from it.
+#[derive(Debug, Default)]
+pub struct Recorder {
+    state: Mutex<State>,
+}
+
+impl Recorder {
+    /// Returns the recorder of an instance created with `MockIcd`.
+    ///
+    /// Returns `None` if the instance wasn't created with the mock.
+    pub fn of(instance: &Instance) -> Option<Arc<Recorder>> {
+        DISPATCHABLE.lock().unwrap().get(&instance.internal_object()).cloned()
+    }
+
+    /// Returns the names of the Vulkan functions that have been called so far, in order.
+    pub fn calls(&self) -> Vec<&'static str> {
+        self.state.lock().unwrap().calls.clone()
+    }
+
+    /// Same as `calls`, but also forgets them.
+    pub fn take_calls(&self) -> Vec<&'static str> {
+        mem::take(&mut self.state.lock().unwrap().calls)
+    }
+
+    /// Returns the commands recorded in a command buffer since it was last begun.
+    ///
+    /// Returns `None` if the command buffer doesn't exist.
+    pub fn commands(&self, command_buffer: vk::CommandBuffer) -> Option<Vec<&'static str>> {
+        match self.state.lock().unwrap().objects.get(&(command_buffer as u64)) {
+            Some(&Object { data: ObjectData::CommandBuffer { ref commands, .. }, .. }) => {
+                Some(commands.clone())
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns the state of a command buffer, or `None` if it doesn't exist.
+    pub fn command_buffer_state(&self, command_buffer: vk::CommandBuffer)
+                                -> Option<CommandBufferState> {
+        match self.state.lock().unwrap().objects.get(&(command_buffer as u64)) {
+            Some(&Object { data: ObjectData::CommandBuffer { state, .. }, .. }) => Some(state),
+            _ => None,
+        }
+    }
+
+    /// Returns the submissions made to all the queues so far, in order.
+    pub fn submissions(&self) -> Vec<Submission> {
+        self.state.lock().unwrap().submissions.clone()
+    }
+
+    /// Returns the type and handle of all the objects that are alive, sorted by handle.
+    pub fn live_objects(&self) -> Vec<(vk::ObjectType, u64)> {
+        let state = self.state.lock().unwrap();
+        let mut objects = state
+            .objects
+            .iter()
+            .map(|(&handle, object)| (object.ty, handle))
+            .collect::<Vec<_>>();
+        objects.sort_by_key(|&(_, handle)| handle);
+        objects
+    }
+
+    /// Returns the number of objects of the given type that are alive.
+    pub fn live_count(&self, ty: vk::ObjectType) -> usize {
+        let state = self.state.lock().unwrap();
+        state.objects.values().filter(|object| object.ty == ty).count()
+    }
+
+    /// Returns the misuses of the API that have been detected so far, in order.
+    pub fn errors(&self) -> Vec<MockError> {
+        self.state.lock().unwrap().errors.clone()
+    }
+
+    /// Makes the swapchains that are alive out of date, as if their surface had been resized.
+    /// Acquiring or presenting their images then fails with `VK_ERROR_OUT_OF_DATE_KHR`. Swapchains
+    /// created afterwards aren't affected.
+    pub fn make_swapchains_out_of_date(&self) {
+        let mut state = self.state.lock().unwrap();
+        for object in state.objects.values_mut() {
+            if let ObjectData::Swapchain { ref mut out_of_date, .. } = object.data {
+                *out_of_date = true;
+            }
+        }
+    }
+}
+
+/// State of a command buffer, as defined by the Vulkan specs.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CommandBufferState {
+    Initial,
+    Recording,
+    Executable,
+    /// The command buffer was recorded with the one-time-submit flag and has been submitted.
+    Invalid,
+}
+
+/// A call to `vkQueueSubmit`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Submission {
+    pub queue: vk::Queue,
+    pub wait_semaphores: Vec<vk::Semaphore>,
+    pub command_buffers: Vec<vk::CommandBuffer>,
+    /// Commands of the command buffers, in the order in which they are executed.
+    pub commands: Vec<&'static str>,
+    pub signal_semaphores: Vec<vk::Semaphore>,
+    pub fence: vk::Fence,
+}
+
+/// Misuse of the Vulkan API detected by the mock.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MockError {
+    /// A handle that doesn't exist, that has been destroyed or that has the wrong type was passed
+    /// to a function.
+    UnknownHandle {
+        function: &'static str,
+        ty: vk::ObjectType,
+        handle: u64,
+    },
+
+    /// A function was called on a command buffer that isn't in the right state for it.
+    InvalidCommandBufferState {
+        function: &'static str,
+        command_buffer: vk::CommandBuffer,
+        state: CommandBufferState,
+    },
+
+    /// Memory was bound to a buffer or an image that already has memory bound.
+    AlreadyBound {
+        function: &'static str,
+        handle: u64,
+    },
+
+    /// The memory bound to a buffer or an image is too small for it.
+    BindingOutOfRange {
+        function: &'static str,
+        handle: u64,
+        memory: vk::DeviceMemory,
+    },
+
+    /// A buffer or an image without memory was used by a command.
+    NotBound {
+        function: &'static str,
+        handle: u64,
+    },
+
+    /// Memory that is already mapped was mapped again.
+    AlreadyMapped { memory: vk::DeviceMemory },
+
+    /// Memory that isn't mapped was unmapped.
+    NotMapped { memory: vk::DeviceMemory },
+
+    /// An object was still alive when the device or the instance it belongs to was destroyed.
+    Leak { ty: vk::ObjectType, handle: u64 },
+}
+
+#[derive(Debug, Default)]
+struct State {
+    instance: usize,
+    physical_device: usize,
+    calls: Vec<&'static str>,
+    objects: HashMap<u64, Object>,
+    submissions: Vec<Submission>,
+    errors: Vec<MockError>,
+    // Dispatchable handles to unregister when the instance is destroyed.
+    dispatchable: Vec<usize>,
+    // File descriptors returned by the `vkGet*FdKHR` functions and not imported yet, with the
+    // type of the object they were exported from.
+    exported_fds: HashMap<c_int, vk::ObjectType>,
+}
+
+#[derive(Debug)]
+struct Object {
+    ty: vk::ObjectType,
+    // Object that destroys this one when it is destroyed: the instance for devices, the device
+    // for device-level objects, and the pool for command buffers and descriptor sets.
+    parent: u64,
+    data: ObjectData,
+}
+
+#[derive(Debug)]
+enum ObjectData {
+    None,
+    Queue {
+        family: u32,
+        index: u32,
+    },
+    // Fences and events.
+    Signal(bool),
+    // Buffers and images, with the memory and offset they are bound to.
+    Resource {
+        size: u64,
+        binding: Option<(vk::DeviceMemory, u64)>,
+    },
+    Memory {
+        size: u64,
+        // Allocated the first time the memory is mapped.
+        contents: Vec<u8>,
+        mapped: bool,
+    },
+    CommandBuffer {
+        state: CommandBufferState,
+        one_time: bool,
+        commands: Vec<&'static str>,
+    },
+    Swapchain {
+        images: Vec<vk::Image>,
+        // Index of the image returned by the next acquire.
+        next: u32,
+        out_of_date: bool,
+    },
+}
+
+impl State {
+    fn create(&mut self, ty: vk::ObjectType, parent: u64, data: ObjectData) -> u64 {
+        let handle = NEXT_HANDLE.fetch_add(1, Ordering::Relaxed) as u64;
+        self.objects.insert(handle,
+                            Object {
+                                ty,
+                                parent,
+                                data,
+                            });
+        handle
+    }
+
+    // Checks that `handle` is an object of type `ty`, and reports an error otherwise.
+    fn check(&mut self, function: &'static str, ty: vk::ObjectType, handle: u64) -> bool {
+        match self.objects.get(&handle) {
+            Some(object) if object.ty == ty => true,
+            _ => {
+                self.errors.push(MockError::UnknownHandle { function, ty, handle });
+                false
+            },
+        }
+    }
+
+    // Returns a new file descriptor exported from an object of type `ty`.
+    fn export_fd(&mut self, ty: vk::ObjectType) -> c_int {
+        let fd = open_fd();
+        self.exported_fds.insert(fd, ty);
+        fd
+    }
+
+    // Takes ownership of `fd` if it was exported from an object of type `ty`. Returns false if
+    // it wasn't, in which case the caller keeps the ownership.
+    fn import_fd(&mut self, ty: vk::ObjectType, fd: c_int) -> bool {
+        if self.exported_fds.get(&fd) != Some(&ty) {
+            return false;
+        }
+
+        self.exported_fds.remove(&fd);
+        close_fd(fd);
+        true
+    }
+
+    fn data(&mut self, handle: u64) -> &mut ObjectData {
+        &mut self.objects.get_mut(&handle).unwrap().data
+    }
+
+    fn destroy(&mut self, function: &'static str, ty: vk::ObjectType, handle: u64) {
+        // Destroying a null handle is a no-op.
+        if handle == 0 || !self.check(function, ty, handle) {
+            return;
+        }
+
+        self.destroy_children(handle, false);
+        self.objects.remove(&handle);
+    }
+
+    // Destroys the objects owned by `parent`. If `report_leaks` is true, the ones that should
+    // have been destroyed explicitly are reported as leaks.
+    fn destroy_children(&mut self, parent: u64, report_leaks: bool) {
+        let mut children = self
+            .objects
+            .iter()
+            .filter(|&(_, object)| object.parent == parent)
+            .map(|(&handle, object)| (object.ty, handle))
+            .collect::<Vec<_>>();
+        children.sort_by_key(|&(_, handle)| handle);
+
+        for (ty, handle) in children {
+            if report_leaks && ty != vk::OBJECT_TYPE_PHYSICAL_DEVICE &&
+                ty != vk::OBJECT_TYPE_QUEUE
+            {
+                self.errors.push(MockError::Leak { ty, handle });
+            }
+
+            self.destroy_children(handle, false);
+            self.objects.remove(&handle);
+        }
+    }
+
+    fn dispatchable(&mut self, recorder: &Arc<Recorder>) -> usize {
+        let handle = NEXT_HANDLE.fetch_add(1, Ordering::Relaxed);
+        DISPATCHABLE.lock().unwrap().insert(handle, recorder.clone());
+        self.dispatchable.push(handle);
+        handle
+    }
+
+    fn bind_memory(&mut self, function: &'static str, ty: vk::ObjectType, handle: u64,
+                   memory: vk::DeviceMemory, offset: u64)
+                   -> vk::Result {
+        if !self.check(function, ty, handle) ||
+            !self.check(function, vk::OBJECT_TYPE_DEVICE_MEMORY, memory)
+        {
+            return vk::ERROR_OUT_OF_DEVICE_MEMORY;
+        }
+
+        let memory_size = match *self.data(memory) {
+            ObjectData::Memory { size, .. } => size,
+            _ => unreachable!(),
+        };
+
+        let error = match *self.data(handle) {
+            ObjectData::Resource { binding: Some(_), .. } => {
+                Some(MockError::AlreadyBound { function, handle })
+            },
+            ObjectData::Resource { size, ref mut binding } => {
+                if offset + size > memory_size {
+                    Some(MockError::BindingOutOfRange {
+                             function,
+                             handle,
+                             memory,
+                         })
+                } else {
+                    *binding = Some((memory, offset));
+                    None
+                }
+            },
+            _ => unreachable!(),
+        };
+
+        if let Some(error) = error {
+            self.errors.push(error);
+        }
+
+        vk::SUCCESS
+    }
+
+    // Checks that a command buffer is in one of the given states, and reports an error otherwise.
+    fn check_command_buffer(&mut self, function: &'static str, command_buffer: vk::CommandBuffer,
+                            expected: &[CommandBufferState])
+                            -> bool {
+        if !self.check(function, vk::OBJECT_TYPE_COMMAND_BUFFER, command_buffer as u64) {
+            return false;
+        }
+
+        let state = match *self.data(command_buffer as u64) {
+            ObjectData::CommandBuffer { state, .. } => state,
+            _ => unreachable!(),
+        };
+
+        if !expected.contains(&state) {
+            self.errors.push(MockError::InvalidCommandBufferState {
+                                 function,
+                                 command_buffer,
+                                 state,
+                             });
+            return false;
+        }
+
+        true
+    }
+
+    fn record_command(&mut self, function: &'static str, command_buffer: vk::CommandBuffer,
+                      objects: &[(vk::ObjectType, u64)]) {
+        if !self.check_command_buffer(function, command_buffer, &[CommandBufferState::Recording]) {
+            return;
+        }
+
+        for &(ty, handle) in objects {
+            if !self.check(function, ty, handle) {
+                continue;
+            }
+
+            if let ObjectData::Resource { binding: None, .. } = *self.data(handle) {
+                self.errors.push(MockError::NotBound { function, handle });
+            }
+        }
+
+        if let ObjectData::CommandBuffer { ref mut commands, .. } =
+            *self.data(command_buffer as u64)
+        {
+            commands.push(function);
+        }
+    }
+}
+
+fn recorder(handle: usize) -> Option<Arc<Recorder>> {
+    DISPATCHABLE.lock().unwrap().get(&handle).cloned()
+}
+
+// Runs `f` with the state of the recorder that `handle` belongs to, after recording the call.
+fn with_state<R, F>(handle: usize, function: &'static str, f: F) -> Option<R>
+    where F: FnOnce(&mut State, &Arc<Recorder>) -> R
+{
+    let recorder = recorder(handle)?;
+    let mut state = recorder.state.lock().unwrap();
+    state.calls.push(function);
+    Some(f(&mut state, &recorder))
+}
+
+// Same as `with_state`, but also checks that `device` is a live device.
+fn with_device<F>(device: vk::Device, function: &'static str, f: F) -> vk::Result
+    where F: FnOnce(&mut State) -> vk::Result
+{
+    with_state(device, function, |state, _| {
+        if !state.check(function, vk::OBJECT_TYPE_DEVICE, device as u64) {
+            return vk::ERROR_DEVICE_LOST;
+        }
+
+        f(state)
+    }).unwrap_or(vk::ERROR_DEVICE_LOST)
+}
+
+fn create_object(device: vk::Device, function: &'static str, ty: vk::ObjectType,
+                 data: ObjectData, output: *mut u64)
+                 -> vk::Result {
+    with_device(device, function, |state| {
+        let handle = state.create(ty, device as u64, data);
+        unsafe {
+            *output = handle;
+        }
+        vk::SUCCESS
+    })
+}
+
+fn destroy_object(device: vk::Device, function: &'static str, ty: vk::ObjectType, handle: u64) {
+    with_device(device, function, |state| {
+        state.destroy(function, ty, handle);
+        vk::SUCCESS
+    });
+}
+
+// Implements the usual two-call idiom of the enumeration functions.
+unsafe fn enumerate<T>(values: &[T], count: *mut u32, output: *mut T) -> vk::Result {
+    if output.is_null() {
+        *count = values.len() as u32;
+        return vk::SUCCESS;
+    }
+
+    let written = (*count as usize).min(values.len());
+    ptr::copy_nonoverlapping(values.as_ptr(), output, written);
+    *count = written as u32;
+
+    if written < values.len() {
+        vk::INCOMPLETE
+    } else {
+        vk::SUCCESS
+    }
+}
+
+unsafe fn raw_slice<'a, T>(data: *const T, len: u32) -> &'a [T] {
+    if len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(data, len as usize)
+    }
+}
+
+fn physical_device_properties() -> vk::PhysicalDeviceProperties {
+    let mut properties: vk::PhysicalDeviceProperties = unsafe { mem::zeroed() };
+    properties.apiVersion = Version::V1_0.into_vulkan_version();
+    properties.deviceType = vk::PHYSICAL_DEVICE_TYPE_CPU;
+    for (dest, &byte) in properties
+        .deviceName
+        .iter_mut()
+        .zip(b"vulkano mock device".iter())
+    {
+        *dest = byte as c_char;
+    }
+
+    let all_samples = vk::SAMPLE_COUNT_1_BIT | vk::SAMPLE_COUNT_2_BIT | vk::SAMPLE_COUNT_4_BIT |
+        vk::SAMPLE_COUNT_8_BIT;
+
+    let limits = &mut properties.limits;
+    limits.maxImageDimension1D = 16384;
+    limits.maxImageDimension2D = 16384;
+    limits.maxImageDimension3D = 2048;
+    limits.maxImageDimensionCube = 16384;
+    limits.maxImageArrayLayers = 2048;
+    limits.maxTexelBufferElements = 1 << 27;
+    limits.maxUniformBufferRange = 1 << 16;
+    limits.maxStorageBufferRange = 1 << 30;
+    limits.maxPushConstantsSize = 256;
+    limits.maxMemoryAllocationCount = 4096;
+    limits.maxSamplerAllocationCount = 4000;
+    limits.bufferImageGranularity = 1024;
+    limits.sparseAddressSpaceSize = 1 << 40;
+    limits.maxBoundDescriptorSets = 8;
+    limits.maxPerStageDescriptorSamplers = 1 << 20;
+    limits.maxPerStageDescriptorUniformBuffers = 1 << 20;
+    limits.maxPerStageDescriptorStorageBuffers = 1 << 20;
+    limits.maxPerStageDescriptorSampledImages = 1 << 20;
+    limits.maxPerStageDescriptorStorageImages = 1 << 20;
+    limits.maxPerStageDescriptorInputAttachments = 1 << 20;
+    limits.maxPerStageResources = 1 << 20;
+    limits.maxDescriptorSetSamplers = 1 << 20;
+    limits.maxDescriptorSetUniformBuffers = 1 << 20;
+    limits.maxDescriptorSetUniformBuffersDynamic = 16;
+    limits.maxDescriptorSetStorageBuffers = 1 << 20;
+    limits.maxDescriptorSetStorageBuffersDynamic = 16;
+    limits.maxDescriptorSetSampledImages = 1 << 20;
+    limits.maxDescriptorSetStorageImages = 1 << 20;
+    limits.maxDescriptorSetInputAttachments = 1 << 20;
+    limits.maxVertexInputAttributes = 32;
+    limits.maxVertexInputBindings = 32;
+    limits.maxVertexInputAttributeOffset = 2047;
+    limits.maxVertexInputBindingStride = 2048;
+    limits.maxVertexOutputComponents = 128;
+    limits.maxTessellationGenerationLevel = 64;
+    limits.maxTessellationPatchSize = 32;
+    limits.maxTessellationControlPerVertexInputComponents = 128;
+    limits.maxTessellationControlPerVertexOutputComponents = 128;
+    limits.maxTessellationControlPerPatchOutputComponents = 120;
+    limits.maxTessellationControlTotalOutputComponents = 4096;
+    limits.maxTessellationEvaluationInputComponents = 128;
+    limits.maxTessellationEvaluationOutputComponents = 128;
+    limits.maxGeometryShaderInvocations = 32;
+    limits.maxGeometryInputComponents = 128;
+    limits.maxGeometryOutputComponents = 128;
+    limits.maxGeometryOutputVertices = 256;
+    limits.maxGeometryTotalOutputComponents = 1024;
+    limits.maxFragmentInputComponents = 128;
+    limits.maxFragmentOutputAttachments = 8;
+    limits.maxFragmentDualSrcAttachments = 1;
+    limits.maxFragmentCombinedOutputResources = 1 << 20;
+    limits.maxComputeSharedMemorySize = 1 << 15;
+    limits.maxComputeWorkGroupCount = [65535; 3];
+    limits.maxComputeWorkGroupInvocations = 1024;
+    limits.maxComputeWorkGroupSize = [1024, 1024, 64];
+    limits.subPixelPrecisionBits = 8;
+    limits.subTexelPrecisionBits = 8;
+    limits.mipmapPrecisionBits = 8;
+    limits.maxDrawIndexedIndexValue = u32::MAX;
+    limits.maxDrawIndirectCount = u32::MAX;
+    limits.maxSamplerLodBias = 16.0;
+    limits.maxSamplerAnisotropy = 16.0;
+    limits.maxViewports = 16;
+    limits.maxViewportDimensions = [16384; 2];
+    limits.viewportBoundsRange = [-32768.0, 32767.0];
+    limits.viewportSubPixelBits = 8;
+    limits.minMemoryMapAlignment = 64;
+    limits.minTexelBufferOffsetAlignment = 16;
+    limits.minUniformBufferOffsetAlignment = 256;
+    limits.minStorageBufferOffsetAlignment = 256;
+    limits.minTexelOffset = -8;
+    limits.maxTexelOffset = 7;
+    limits.minTexelGatherOffset = -32;
+    limits.maxTexelGatherOffset = 31;
+    limits.minInterpolationOffset = -0.5;
+    limits.maxInterpolationOffset = 0.4375;
+    limits.subPixelInterpolationOffsetBits = 4;
+    limits.maxFramebufferWidth = 16384;
+    limits.maxFramebufferHeight = 16384;
+    limits.maxFramebufferLayers = 2048;
+    limits.framebufferColorSampleCounts = all_samples;
+    limits.framebufferDepthSampleCounts = all_samples;
+    limits.framebufferStencilSampleCounts = all_samples;
+    limits.framebufferNoAttachmentsSampleCounts = all_samples;
+    limits.maxColorAttachments = 8;
+    limits.sampledImageColorSampleCounts = all_samples;
+    limits.sampledImageIntegerSampleCounts = all_samples;
+    limits.sampledImageDepthSampleCounts = all_samples;
+    limits.sampledImageStencilSampleCounts = all_samples;
+    limits.storageImageSampleCounts = all_samples;
+    limits.maxSampleMaskWords = 1;
+    limits.timestampComputeAndGraphics = vk::TRUE;
+    limits.timestampPeriod = 1.0;
+    limits.maxClipDistances = 8;
+    limits.maxCullDistances = 8;
+    limits.maxCombinedClipAndCullDistances = 8;
+    limits.discreteQueuePriorities = 2;
+    limits.pointSizeRange = [1.0, 64.0];
+    limits.lineWidthRange = [1.0, 8.0];
+    limits.pointSizeGranularity = 1.0;
+    limits.lineWidthGranularity = 1.0;
+    limits.strictLines = vk::TRUE;
+    limits.standardSampleLocations = vk::TRUE;
+    limits.optimalBufferCopyOffsetAlignment = 1;
+    limits.optimalBufferCopyRowPitchAlignment = 1;
+    limits.nonCoherentAtomSize = 64;
+
+    properties
+}
+
+// Opens the file descriptors exported by the mock. Vulkano only exports file descriptors on unix.
+#[cfg(unix)]
+fn open_fd() -> c_int {
+    File::open("/dev/null").unwrap().into_raw_fd()
+}
+
+#[cfg(not(unix))]
+fn open_fd() -> c_int {
+    unreachable!()
+}
+
+#[cfg(unix)]
+fn close_fd(fd: c_int) {
+    unsafe {
+        drop(File::from_raw_fd(fd));
+    }
+}
+
+#[cfg(not(unix))]
+fn close_fd(_: c_int) {
+    unreachable!()
+}
+
+fn align(size: u64) -> u64 {
+    size.div_ceil(MEMORY_ALIGNMENT) * MEMORY_ALIGNMENT
+}
+
+macro_rules! entry_points {
+    ($($name:ident,)+) => (
+        // Returns the address of the function with the given name, or 0 if it isn't implemented.
+        fn lookup(name: &[u8]) -> usize {
+            $(
+                if name == concat!("vk", stringify!($name)).as_bytes() {
+                    return functions::$name as *const () as usize;
+                }
+            )+
+
+            commands::lookup(name)
+        }
+    );
+}
+
+entry_points! {
+    CreateInstance,
+    EnumerateInstanceExtensionProperties,
+    EnumerateInstanceLayerProperties,
+    DestroyInstance,
+    EnumeratePhysicalDevices,
+    EnumerateDeviceExtensionProperties,
+    EnumerateDeviceLayerProperties,
+    GetPhysicalDeviceFeatures,
+    GetPhysicalDeviceFormatProperties,
+    GetPhysicalDeviceImageFormatProperties,
+    GetPhysicalDeviceProperties,
+    GetPhysicalDeviceQueueFamilyProperties,
+    GetPhysicalDeviceMemoryProperties,
+    CreateDevice,
+    GetDeviceProcAddr,
+    DestroyDevice,
+    GetDeviceQueue,
+    QueueSubmit,
+    QueueWaitIdle,
+    QueueBeginDebugUtilsLabelEXT,
+    QueueEndDebugUtilsLabelEXT,
+    QueueInsertDebugUtilsLabelEXT,
+    DeviceWaitIdle,
+    SetDebugUtilsObjectNameEXT,
+    AllocateMemory,
+    FreeMemory,
+    GetMemoryFdKHR,
+    MapMemory,
+    UnmapMemory,
+    FlushMappedMemoryRanges,
+    InvalidateMappedMemoryRanges,
+    BindBufferMemory,
+    BindImageMemory,
+    GetBufferMemoryRequirements,
+    GetImageMemoryRequirements,
+    CreateFence,
+    DestroyFence,
+    ResetFences,
+    GetFenceStatus,
+    WaitForFences,
+    GetFenceFdKHR,
+    ImportFenceFdKHR,
+    CreateSemaphore,
+    DestroySemaphore,
+    GetSemaphoreFdKHR,
+    ImportSemaphoreFdKHR,
+    CreateEvent,
+    DestroyEvent,
+    GetEventStatus,
+    SetEvent,
+    ResetEvent,
+    CreateQueryPool,
+    DestroyQueryPool,
+    CreateBuffer,
+    DestroyBuffer,
+    CreateBufferView,
+    DestroyBufferView,
+    CreateImage,
+    DestroyImage,
+    CreateImageView,
+    DestroyImageView,
+    CreateShaderModule,
+    DestroyShaderModule,
+    CreatePipelineCache,
+    DestroyPipelineCache,
+    CreateGraphicsPipelines,
+    CreateComputePipelines,
+    DestroyPipeline,
+    CreatePipelineLayout,
+    DestroyPipelineLayout,
+    CreateSampler,
+    DestroySampler,
+    CreateDescriptorSetLayout,
+    DestroyDescriptorSetLayout,
+    CreateDescriptorPool,
+    DestroyDescriptorPool,
+    ResetDescriptorPool,
+    AllocateDescriptorSets,
+    FreeDescriptorSets,
+    UpdateDescriptorSets,
+    CreateDescriptorUpdateTemplateKHR,
+    DestroyDescriptorUpdateTemplateKHR,
+    UpdateDescriptorSetWithTemplateKHR,
+    CreateFramebuffer,
+    DestroyFramebuffer,
+    CreateRenderPass,
+    DestroyRenderPass,
+    CreateCommandPool,
+    DestroyCommandPool,
+    ResetCommandPool,
+    AllocateCommandBuffers,
+    FreeCommandBuffers,
+    BeginCommandBuffer,
+    EndCommandBuffer,
+    ResetCommandBuffer,
+    CreateHeadlessSurfaceEXT,
+    DestroySurfaceKHR,
+    GetPhysicalDeviceSurfaceSupportKHR,
+    GetPhysicalDeviceSurfaceCapabilitiesKHR,
+    GetPhysicalDeviceSurfaceFormatsKHR,
+    GetPhysicalDeviceSurfacePresentModesKHR,
+    CreateSwapchainKHR,
+    DestroySwapchainKHR,
+    GetSwapchainImagesKHR,
+    AcquireNextImageKHR,
+    QueuePresentKHR,
+    CreateDebugUtilsMessengerEXT,
+    DestroyDebugUtilsMessengerEXT,
+}
+
+// Objects that don't have any state besides being alive.
+macro_rules! simple_objects {
+    ($($create:ident, $destroy:ident: $info:ident => $ty:ident;)+) => (
+        $(
+            pub extern "system" fn $create(device: Device, _: *const $info,
+                                           _: *const AllocationCallbacks, pObject: *mut u64)
+                                           -> Result {
+                create_object(device, concat!("vk", stringify!($create)), $ty, ObjectData::None,
+                              pObject)
+            }
+
+            pub extern "system" fn $destroy(device: Device, object: u64,
+                                            _: *const AllocationCallbacks) {
+                destroy_object(device, concat!("vk", stringify!($destroy)), $ty, object)
+            }
+        )+
+    );
+}
+
+#[allow(non_snake_case)]
+mod functions {
+    use std::ffi::CStr;
+    use std::mem;
+    use std::os::raw::c_char;
+    use std::os::raw::c_int;
+    use std::os::raw::c_void;
+    use std::slice;
+    use std::sync::Arc;
+
+    use vk::*;
+
+    use super::CommandBufferState;
+    use super::DEVICE_EXTENSIONS;
+    use super::INSTANCE_EXTENSIONS;
+    use super::MIN_IMAGE_COUNT;
+    use super::MockError;
+    use super::ObjectData;
+    use super::Recorder;
+    use super::State;
+    use super::Submission;
+    use super::DISPATCHABLE;
+    use super::MEMORY_ALIGNMENT;
+    use super::QUEUE_COUNTS;
+    use super::align;
+    use super::create_object;
+    use super::destroy_object;
+    use super::enumerate;
+    use super::physical_device_properties;
+    use super::raw_slice;
+    use super::with_device;
+    use super::with_state;
+
+    pub extern "system" fn CreateInstance(pCreateInfo: *const InstanceCreateInfo,
+                                          _: *const AllocationCallbacks,
+                                          pInstance: *mut Instance)
+                                          -> Result {
+        unsafe {
+            let infos = &*pCreateInfo;
+            if infos.enabledLayerCount != 0 {
+                return ERROR_LAYER_NOT_PRESENT;
+            }
+            for &name in raw_slice(infos.ppEnabledExtensionNames, infos.enabledExtensionCount) {
+                let name = CStr::from_ptr(name).to_bytes();
+                if !INSTANCE_EXTENSIONS.iter().any(|ext| ext.as_bytes() == name) {
+                    return ERROR_EXTENSION_NOT_PRESENT;
+                }
+            }
+        }
+
+        let recorder = Arc::new(Recorder::default());
+        let mut state = recorder.state.lock().unwrap();
+        state.calls.push("vkCreateInstance");
+
+        let instance = state.dispatchable(&recorder);
+        let physical_device = state.dispatchable(&recorder);
+        state.objects.insert(instance as u64,
+                             super::Object {
+                                 ty: OBJECT_TYPE_INSTANCE,
+                                 parent: 0,
+                                 data: ObjectData::None,
+                             });
+        state.objects.insert(physical_device as u64,
+                             super::Object {
+                                 ty: OBJECT_TYPE_PHYSICAL_DEVICE,
+                                 parent: instance as u64,
+                                 data: ObjectData::None,
+                             });
+        state.instance = instance;
+        state.physical_device = physical_device;
+
+        unsafe {
+            *pInstance = instance;
+        }
+        SUCCESS
+    }
+
+    pub extern "system" fn EnumerateInstanceExtensionProperties(
+        _: *const c_char, pPropertyCount: *mut u32, pProperties: *mut ExtensionProperties)
+        -> Result {
+        let properties = extension_properties(INSTANCE_EXTENSIONS);
+        unsafe { enumerate(&properties, pPropertyCount, pProperties) }
+    }
+
+    pub extern "system" fn EnumerateInstanceLayerProperties(pPropertyCount: *mut u32,
+                                                            pProperties: *mut LayerProperties)
+                                                            -> Result {
+        unsafe { enumerate(&[], pPropertyCount, pProperties) }
+    }
+
+    pub extern "system" fn DestroyInstance(instance: Instance, _: *const AllocationCallbacks) {
+        let dispatchable = with_state(instance, "vkDestroyInstance", |state, _| {
+            if !state.check("vkDestroyInstance", OBJECT_TYPE_INSTANCE, instance as u64) {
+                return Vec::new();
+            }
+
+            state.destroy_children(instance as u64, true);
+            state.objects.remove(&(instance as u64));
+            mem::take(&mut state.dispatchable)
+        });
+
+        let mut registry = DISPATCHABLE.lock().unwrap();
+        for handle in dispatchable.unwrap_or_default() {
+            registry.remove(&handle);
+        }
+    }
+
+    pub extern "system" fn EnumeratePhysicalDevices(instance: Instance,
+                                                    pPhysicalDeviceCount: *mut u32,
+                                                    pPhysicalDevices: *mut PhysicalDevice)
+                                                    -> Result {
+        with_state(instance, "vkEnumeratePhysicalDevices", |state, _| unsafe {
+            if !state.check("vkEnumeratePhysicalDevices", OBJECT_TYPE_INSTANCE, instance as u64) {
+                return ERROR_INITIALIZATION_FAILED;
+            }
+
+            enumerate(&[state.physical_device], pPhysicalDeviceCount, pPhysicalDevices)
+        }).unwrap_or(ERROR_INITIALIZATION_FAILED)
+    }
+
+    pub extern "system" fn EnumerateDeviceExtensionProperties(
+        physicalDevice: PhysicalDevice, _: *const c_char, pPropertyCount: *mut u32,
+        pProperties: *mut ExtensionProperties)
+        -> Result {
+        with_state(physicalDevice, "vkEnumerateDeviceExtensionProperties", |_, _| unsafe {
+            enumerate(&extension_properties(DEVICE_EXTENSIONS), pPropertyCount, pProperties)
+        }).unwrap_or(ERROR_INITIALIZATION_FAILED)
+    }
+
+    fn extension_properties(names: &[&str]) -> Vec<ExtensionProperties> {
+        names
+            .iter()
+            .map(|name| {
+                let mut properties: ExtensionProperties = unsafe { mem::zeroed() };
+                for (dest, &byte) in properties.extensionName.iter_mut().zip(name.as_bytes()) {
+                    *dest = byte as c_char;
+                }
+                properties.specVersion = 1;
+                properties
+            })
+            .collect()
+    }
+
+    pub extern "system" fn EnumerateDeviceLayerProperties(physicalDevice: PhysicalDevice,
+                                                          pPropertyCount: *mut u32,
+                                                          pProperties: *mut LayerProperties)
+                                                          -> Result {
+        with_state(physicalDevice, "vkEnumerateDeviceLayerProperties", |_, _| unsafe {
+            enumerate(&[], pPropertyCount, pProperties)
+        }).unwrap_or(ERROR_INITIALIZATION_FAILED)
+    }
+
+    pub extern "system" fn GetPhysicalDeviceFeatures(physicalDevice: PhysicalDevice,
+                                                     pFeatures: *mut PhysicalDeviceFeatures) {
+        with_state(physicalDevice, "vkGetPhysicalDeviceFeatures", |_, _| unsafe {
+            // `PhysicalDeviceFeatures` only contains `Bool32`s.
+            let len = mem::size_of::<PhysicalDeviceFeatures>() / mem::size_of::<Bool32>();
+            for feature in slice::from_raw_parts_mut(pFeatures as *mut Bool32, len) {
+                *feature = TRUE;
+            }
+        });
+    }
+
+    pub extern "system" fn GetPhysicalDeviceFormatProperties(
+        physicalDevice: PhysicalDevice, _: Format, pFormatProperties: *mut FormatProperties) {
+        with_state(physicalDevice, "vkGetPhysicalDeviceFormatProperties", |_, _| unsafe {
+            let image_features = FORMAT_FEATURE_SAMPLED_IMAGE_BIT |
+                FORMAT_FEATURE_STORAGE_IMAGE_BIT |
+                FORMAT_FEATURE_STORAGE_IMAGE_ATOMIC_BIT |
+                FORMAT_FEATURE_COLOR_ATTACHMENT_BIT |
+                FORMAT_FEATURE_COLOR_ATTACHMENT_BLEND_BIT |
+                FORMAT_FEATURE_DEPTH_STENCIL_ATTACHMENT_BIT |
+                FORMAT_FEATURE_BLIT_SRC_BIT |
+                FORMAT_FEATURE_BLIT_DST_BIT |
+                FORMAT_FEATURE_SAMPLED_IMAGE_FILTER_LINEAR_BIT;
+            let buffer_features = FORMAT_FEATURE_UNIFORM_TEXEL_BUFFER_BIT |
+                FORMAT_FEATURE_STORAGE_TEXEL_BUFFER_BIT |
+                FORMAT_FEATURE_STORAGE_TEXEL_BUFFER_ATOMIC_BIT |
+                FORMAT_FEATURE_VERTEX_BUFFER_BIT;
+
+            *pFormatProperties = FormatProperties {
+                linearTilingFeatures: image_features,
+                optimalTilingFeatures: image_features,
+                bufferFeatures: buffer_features,
+            };
+        });
+    }
+
+    pub extern "system" fn GetPhysicalDeviceImageFormatProperties(
+        physicalDevice: PhysicalDevice, _: Format, _: ImageType, _: ImageTiling,
+        _: ImageUsageFlags, _: ImageCreateFlags,
+        pImageFormatProperties: *mut ImageFormatProperties)
+        -> Result {
+        with_state(physicalDevice, "vkGetPhysicalDeviceImageFormatProperties", |_, _| unsafe {
+            *pImageFormatProperties = ImageFormatProperties {
+                maxExtent: Extent3D {
+                    width: 16384,
+                    height: 16384,
+                    depth: 2048,
+                },
+                maxMipLevels: 15,
+                maxArrayLayers: 2048,
+                sampleCounts: SAMPLE_COUNT_1_BIT | SAMPLE_COUNT_2_BIT | SAMPLE_COUNT_4_BIT |
+                    SAMPLE_COUNT_8_BIT,
+                maxResourceSize: 1 << 31,
+            };
+            SUCCESS
+        }).unwrap_or(ERROR_INITIALIZATION_FAILED)
+    }
+
+    pub extern "system" fn GetPhysicalDeviceProperties(physicalDevice: PhysicalDevice,
+                                                       pProperties: *mut PhysicalDeviceProperties) {
+        with_state(physicalDevice, "vkGetPhysicalDeviceProperties", |_, _| unsafe {
+            *pProperties = physical_device_properties();
+        });
+    }
+
+    pub extern "system" fn GetPhysicalDeviceQueueFamilyProperties(
+        physicalDevice: PhysicalDevice, pQueueFamilyPropertyCount: *mut u32,
+        pQueueFamilyProperties: *mut QueueFamilyProperties) {
+        with_state(physicalDevice, "vkGetPhysicalDeviceQueueFamilyProperties", |_, _| unsafe {
+            let family = |queueFlags, queueCount| {
+                QueueFamilyProperties {
+                    queueFlags,
+                    queueCount,
+                    timestampValidBits: 64,
+                    minImageTransferGranularity: Extent3D {
+                        width: 1,
+                        height: 1,
+                        depth: 1,
+                    },
+                }
+            };
+            let families = [
+                family(QUEUE_GRAPHICS_BIT | QUEUE_COMPUTE_BIT | QUEUE_TRANSFER_BIT,
+                       QUEUE_COUNTS[0]),
+                family(QUEUE_TRANSFER_BIT, QUEUE_COUNTS[1]),
+            ];
+            enumerate(&families, pQueueFamilyPropertyCount, pQueueFamilyProperties);
+        });
+    }
+
+    pub extern "system" fn GetPhysicalDeviceMemoryProperties(
+        physicalDevice: PhysicalDevice, pMemoryProperties: *mut PhysicalDeviceMemoryProperties) {
+        with_state(physicalDevice, "vkGetPhysicalDeviceMemoryProperties", |_, _| unsafe {
+            let mut properties: PhysicalDeviceMemoryProperties = mem::zeroed();
+            properties.memoryTypeCount = 1;
+            properties.memoryTypes[0] = MemoryType {
+                propertyFlags: MEMORY_PROPERTY_DEVICE_LOCAL_BIT |
+                    MEMORY_PROPERTY_HOST_VISIBLE_BIT |
+                    MEMORY_PROPERTY_HOST_COHERENT_BIT,
+                heapIndex: 0,
+            };
+            properties.memoryHeapCount = 1;
+            properties.memoryHeaps[0] = MemoryHeap {
+                size: 1 << 32,
+                flags: MEMORY_HEAP_DEVICE_LOCAL_BIT,
+            };
+            *pMemoryProperties = properties;
+        });
+    }
+
+    pub extern "system" fn CreateDevice(physicalDevice: PhysicalDevice,
+                                        pCreateInfo: *const DeviceCreateInfo,
+                                        _: *const AllocationCallbacks, pDevice: *mut Device)
+                                        -> Result {
+        with_state(physicalDevice, "vkCreateDevice", |state, recorder| unsafe {
+            let infos = &*pCreateInfo;
+            for &name in raw_slice(infos.ppEnabledExtensionNames, infos.enabledExtensionCount) {
+                let name = CStr::from_ptr(name).to_bytes();
+                if !DEVICE_EXTENSIONS.iter().any(|ext| ext.as_bytes() == name) {
+                    return ERROR_EXTENSION_NOT_PRESENT;
+                }
+            }
+
+            let queue_infos = raw_slice(infos.pQueueCreateInfos, infos.queueCreateInfoCount);
+            if queue_infos
+                .iter()
+                .any(|q| {
+                         QUEUE_COUNTS
+                             .get(q.queueFamilyIndex as usize)
+                             .map(|&count| q.queueCount > count)
+                             .unwrap_or(true)
+                     })
+            {
+                return ERROR_INITIALIZATION_FAILED;
+            }
+
+            let device = state.dispatchable(recorder);
+            state.objects.insert(device as u64,
+                                 super::Object {
+                                     ty: OBJECT_TYPE_DEVICE,
+                                     parent: state.instance as u64,
+                                     data: ObjectData::None,
+                                 });
+
+            for queue_info in queue_infos {
+                for index in 0 .. queue_info.queueCount {
+                    let queue = state.dispatchable(recorder);
+                    state.objects.insert(queue as u64,
+                                         super::Object {
+                                             ty: OBJECT_TYPE_QUEUE,
+                                             parent: device as u64,
+                                             data: ObjectData::Queue {
+                                                 family: queue_info.queueFamilyIndex,
+                                                 index,
+                                             },
+                                         });
+                }
+            }
+
+            *pDevice = device;
+            SUCCESS
+        }).unwrap_or(ERROR_INITIALIZATION_FAILED)
+    }
+
+    pub extern "system" fn GetDeviceProcAddr(_: Device, pName: *const c_char)
+                                             -> extern "system" fn() -> () {
+        unsafe { mem::transmute(super::lookup(::std::ffi::CStr::from_ptr(pName).to_bytes())) }
+    }
+
+    pub extern "system" fn DestroyDevice(device: Device, _: *const AllocationCallbacks) {
+        with_state(device, "vkDestroyDevice", |state, _| {
+            if device == 0 || !state.check("vkDestroyDevice", OBJECT_TYPE_DEVICE, device as u64) {
+                return;
+            }
+
+            state.destroy_children(device as u64, true);
+            state.objects.remove(&(device as u64));
+        });
+    }
+
+    pub extern "system" fn GetDeviceQueue(device: Device, queueFamilyIndex: u32, queueIndex: u32,
+                                          pQueue: *mut Queue) {
+        with_device(device, "vkGetDeviceQueue", |state| {
+            let queue = state.objects.iter().find(|&(_, object)| {
+                match object.data {
+                    ObjectData::Queue { family, index } => {
+                        object.parent == device as u64 && family == queueFamilyIndex &&
+                            index == queueIndex
+                    },
+                    _ => false,
+                }
+            });
+
+            unsafe {
+                *pQueue = queue.map(|(&handle, _)| handle as Queue).unwrap_or(0);
+            }
+            SUCCESS
+        });
+    }
+
+    pub extern "system" fn QueueSubmit(queue: Queue, submitCount: u32,
+                                       pSubmits: *const SubmitInfo, fence: Fence)
+                                       -> Result {
+        const NAME: &str = "vkQueueSubmit";
+
+        with_state(queue, NAME, |state, _| unsafe {
+            if !state.check(NAME, OBJECT_TYPE_QUEUE, queue as u64) {
+                return ERROR_DEVICE_LOST;
+            }
+
+            let mut submission = Submission {
+                queue,
+                wait_semaphores: Vec::new(),
+                command_buffers: Vec::new(),
+                commands: Vec::new(),
+                signal_semaphores: Vec::new(),
+                fence,
+            };
+
+            for submit in raw_slice(pSubmits, submitCount) {
+                let wait_semaphores = raw_slice(submit.pWaitSemaphores,
+                                                submit.waitSemaphoreCount);
+                let command_buffers = raw_slice(submit.pCommandBuffers,
+                                                submit.commandBufferCount);
+                let signal_semaphores = raw_slice(submit.pSignalSemaphores,
+                                                  submit.signalSemaphoreCount);
+
+                for &semaphore in wait_semaphores.iter().chain(signal_semaphores) {
+                    state.check(NAME, OBJECT_TYPE_SEMAPHORE, semaphore);
+                }
+
+                for &command_buffer in command_buffers {
+                    if !state.check_command_buffer(NAME,
+                                                   command_buffer,
+                                                   &[CommandBufferState::Executable])
+                    {
+                        continue;
+                    }
+
+                    if let ObjectData::CommandBuffer {
+                        ref mut state,
+                        one_time,
+                        ref commands,
+                    } = *state.data(command_buffer as u64)
+                    {
+                        submission.commands.extend(commands.iter().cloned());
+                        if one_time {
+                            *state = CommandBufferState::Invalid;
+                        }
+                    }
+                }
+
+                submission.wait_semaphores.extend(wait_semaphores.iter().cloned());
+                submission.command_buffers.extend(command_buffers.iter().cloned());
+                submission.signal_semaphores.extend(signal_semaphores.iter().cloned());
+            }
+
+            // The work is done immediately, so the fence can be signaled right away.
+            if fence != 0 && state.check(NAME, OBJECT_TYPE_FENCE, fence) {
+                *state.data(fence) = ObjectData::Signal(true);
+            }
+
+            state.submissions.push(submission);
+            SUCCESS
+        }).unwrap_or(ERROR_DEVICE_LOST)
+    }
+
+    pub extern "system" fn QueueWaitIdle(queue: Queue) -> Result {
+        with_state(queue, "vkQueueWaitIdle", |state, _| {
+            if !state.check("vkQueueWaitIdle", OBJECT_TYPE_QUEUE, queue as u64) {
+                return ERROR_DEVICE_LOST;
+            }
+            SUCCESS
+        }).unwrap_or(ERROR_DEVICE_LOST)
+    }
+
+    fn queue_label(queue: Queue, function: &'static str) {
+        with_state(queue, function, |state, _| {
+            state.check(function, OBJECT_TYPE_QUEUE, queue as u64);
+        });
+    }
+
+    pub extern "system" fn QueueBeginDebugUtilsLabelEXT(queue: Queue,
+                                                        _: *const DebugUtilsLabelEXT) {
+        queue_label(queue, "vkQueueBeginDebugUtilsLabelEXT")
+    }
+
+    pub extern "system" fn QueueEndDebugUtilsLabelEXT(queue: Queue) {
+        queue_label(queue, "vkQueueEndDebugUtilsLabelEXT")
+    }
+
+    pub extern "system" fn QueueInsertDebugUtilsLabelEXT(queue: Queue,
+                                                         _: *const DebugUtilsLabelEXT) {
+        queue_label(queue, "vkQueueInsertDebugUtilsLabelEXT")
+    }
+
+    pub extern "system" fn DeviceWaitIdle(device: Device) -> Result {
+        with_device(device, "vkDeviceWaitIdle", |_| SUCCESS)
+    }
+
+    pub extern "system" fn SetDebugUtilsObjectNameEXT(device: Device,
+                                                      pNameInfo: *const DebugUtilsObjectNameInfoEXT)
+                                                      -> Result {
+        with_device(device, "vkSetDebugUtilsObjectNameEXT", |state| unsafe {
+            let info = &*pNameInfo;
+            state.check("vkSetDebugUtilsObjectNameEXT", info.objectType, info.objectHandle);
+            SUCCESS
+        })
+    }
+
+    pub extern "system" fn AllocateMemory(device: Device,
+                                          pAllocateInfo: *const MemoryAllocateInfo,
+                                          _: *const AllocationCallbacks,
+                                          pMemory: *mut DeviceMemory)
+                                          -> Result {
+        let infos = unsafe { &*pAllocateInfo };
+        if infos.memoryTypeIndex != 0 {
+            return ERROR_OUT_OF_DEVICE_MEMORY;
+        }
+
+        let import = unsafe { import_memory_fd(infos.pNext) };
+        with_device(device, "vkAllocateMemory", |state| unsafe {
+            if let Some(fd) = import {
+                if !state.import_fd(OBJECT_TYPE_DEVICE_MEMORY, fd) {
+                    return ERROR_INVALID_EXTERNAL_HANDLE_KHR;
+                }
+            }
+
+            let data = ObjectData::Memory {
+                size: infos.allocationSize,
+                contents: Vec::new(),
+                mapped: false,
+            };
+            *pMemory = state.create(OBJECT_TYPE_DEVICE_MEMORY, device as u64, data);
+            SUCCESS
+        })
+    }
+
+    // Returns the file descriptor of the `VkImportMemoryFdInfoKHR` of a `pNext` chain, if any.
+    unsafe fn import_memory_fd(mut next: *const c_void) -> Option<c_int> {
+        while !next.is_null() {
+            // All the structs of the chain start with `sType` and `pNext`.
+            let infos = &*(next as *const ImportMemoryFdInfoKHR);
+            if infos.sType == STRUCTURE_TYPE_IMPORT_MEMORY_FD_INFO_KHR {
+                return Some(infos.fd);
+            }
+            next = infos.pNext;
+        }
+
+        None
+    }
+
+    pub extern "system" fn GetMemoryFdKHR(device: Device, pGetFdInfo: *const MemoryGetFdInfoKHR,
+                                          pFd: *mut c_int)
+                                          -> Result {
+        with_device(device, "vkGetMemoryFdKHR", |state| unsafe {
+            let memory = (*pGetFdInfo).memory;
+            if !state.check("vkGetMemoryFdKHR", OBJECT_TYPE_DEVICE_MEMORY, memory) {
+                return ERROR_DEVICE_LOST;
+            }
+            *pFd = state.export_fd(OBJECT_TYPE_DEVICE_MEMORY);
+            SUCCESS
+        })
+    }
+
+    pub extern "system" fn FreeMemory(device: Device, memory: DeviceMemory,
+                                      _: *const AllocationCallbacks) {
+        destroy_object(device, "vkFreeMemory", OBJECT_TYPE_DEVICE_MEMORY, memory)
+    }
+
+    pub extern "system" fn MapMemory(device: Device, memory: DeviceMemory, offset: DeviceSize,
+                                     _: DeviceSize, _: MemoryMapFlags, ppData: *mut *mut c_void)
+                                     -> Result {
+        with_device(device, "vkMapMemory", |state| {
+            if !state.check("vkMapMemory", OBJECT_TYPE_DEVICE_MEMORY, memory) {
+                return ERROR_MEMORY_MAP_FAILED;
+            }
+
+            let ptr = match *state.data(memory) {
+                ObjectData::Memory { mapped: true, .. } => None,
+                ObjectData::Memory {
+                    size,
+                    ref mut contents,
+                    ref mut mapped,
+                } => {
+                    if contents.is_empty() {
+                        *contents = vec![0; size as usize];
+                    }
+                    *mapped = true;
+                    Some(contents[offset as usize ..].as_mut_ptr())
+                },
+                _ => unreachable!(),
+            };
+
+            match ptr {
+                Some(ptr) => {
+                    unsafe {
+                        *ppData = ptr as *mut c_void;
+                    }
+                    SUCCESS
+                },
+                None => {
+                    state.errors.push(MockError::AlreadyMapped { memory });
+                    ERROR_MEMORY_MAP_FAILED
+                },
+            }
+        })
+    }
+
+    pub extern "system" fn UnmapMemory(device: Device, memory: DeviceMemory) {
+        with_device(device, "vkUnmapMemory", |state| {
+            if !state.check("vkUnmapMemory", OBJECT_TYPE_DEVICE_MEMORY, memory) {
+                return SUCCESS;
+            }
+
+            let was_mapped = match *state.data(memory) {
+                ObjectData::Memory { ref mut mapped, .. } => mem::replace(mapped, false),
+                _ => unreachable!(),
+            };
+
+            if !was_mapped {
+                state.errors.push(MockError::NotMapped { memory });
+            }
+            SUCCESS
+        });
+    }
+
+    fn check_ranges(state: &mut State, function: &'static str, ranges: &[MappedMemoryRange]) {
+        for range in ranges {
+            if !state.check(function, OBJECT_TYPE_DEVICE_MEMORY, range.memory) {
+                continue;
+            }
+
+            if let ObjectData::Memory { mapped: false, .. } = *state.data(range.memory) {
+                state.errors.push(MockError::NotMapped { memory: range.memory });
+            }
+        }
+    }
+
+    pub extern "system" fn FlushMappedMemoryRanges(device: Device, memoryRangeCount: u32,
+                                                   pMemoryRanges: *const MappedMemoryRange)
+                                                   -> Result {
+        with_device(device, "vkFlushMappedMemoryRanges", |state| unsafe {
+            check_ranges(state,
+                         "vkFlushMappedMemoryRanges",
+                         raw_slice(pMemoryRanges, memoryRangeCount));
+            SUCCESS
+        })
+    }
+
+    pub extern "system" fn InvalidateMappedMemoryRanges(device: Device, memoryRangeCount: u32,
+                                                        pMemoryRanges: *const MappedMemoryRange)
+                                                        -> Result {
+        with_device(device, "vkInvalidateMappedMemoryRanges", |state| unsafe {
+            check_ranges(state,
+                         "vkInvalidateMappedMemoryRanges",
+                         raw_slice(pMemoryRanges, memoryRangeCount));
+            SUCCESS
+        })
+    }
+
+    pub extern "system" fn BindBufferMemory(device: Device, buffer: Buffer, memory: DeviceMemory,
+                                            memoryOffset: DeviceSize)
+                                            -> Result {
+        with_device(device, "vkBindBufferMemory", |state| {
+            state.bind_memory("vkBindBufferMemory",
+                              OBJECT_TYPE_BUFFER,
+                              buffer,
+                              memory,
+                              memoryOffset)
+        })
+    }
+
+    pub extern "system" fn BindImageMemory(device: Device, image: Image, memory: DeviceMemory,
+                                           memoryOffset: DeviceSize)
+                                           -> Result {
+        with_device(device, "vkBindImageMemory", |state| {
+            state.bind_memory("vkBindImageMemory", OBJECT_TYPE_IMAGE, image, memory, memoryOffset)
+        })
+    }
+
+    fn memory_requirements(device: Device, function: &'static str, ty: ObjectType, handle: u64,
+                           output: *mut MemoryRequirements) {
+        with_device(device, function, |state| {
+            let size = if state.check(function, ty, handle) {
+                match *state.data(handle) {
+                    ObjectData::Resource { size, .. } => size,
+                    _ => unreachable!(),
+                }
+            } else {
+                0
+            };
+
+            unsafe {
+                *output = MemoryRequirements {
+                    size: align(size),
+                    alignment: MEMORY_ALIGNMENT,
+                    memoryTypeBits: 1,
+                };
+            }
+            SUCCESS
+        });
+    }
+
+    pub extern "system" fn GetBufferMemoryRequirements(
+        device: Device, buffer: Buffer, pMemoryRequirements: *mut MemoryRequirements) {
+        memory_requirements(device,
+                            "vkGetBufferMemoryRequirements",
+                            OBJECT_TYPE_BUFFER,
+                            buffer,
+                            pMemoryRequirements)
+    }
+
+    pub extern "system" fn GetImageMemoryRequirements(
+        device: Device, image: Image, pMemoryRequirements: *mut MemoryRequirements) {
+        memory_requirements(device,
+                            "vkGetImageMemoryRequirements",
+                            OBJECT_TYPE_IMAGE,
+                            image,
+                            pMemoryRequirements)
+    }
+
+    pub extern "system" fn CreateFence(device: Device, pCreateInfo: *const FenceCreateInfo,
+                                       _: *const AllocationCallbacks, pFence: *mut Fence)
+                                       -> Result {
+        let signaled = unsafe { (*pCreateInfo).flags & FENCE_CREATE_SIGNALED_BIT != 0 };
+        create_object(device,
+                      "vkCreateFence",
+                      OBJECT_TYPE_FENCE,
+                      ObjectData::Signal(signaled),
+                      pFence)
+    }
+
+    pub extern "system" fn DestroyFence(device: Device, fence: Fence,
+                                        _: *const AllocationCallbacks) {
+        destroy_object(device, "vkDestroyFence", OBJECT_TYPE_FENCE, fence)
+    }
+
+    // Returns whether a fence or an event is signaled, or `None` if it doesn't exist.
+    fn signaled(state: &mut State, function: &'static str, ty: ObjectType, handle: u64)
+                -> Option<bool> {
+        if !state.check(function, ty, handle) {
+            return None;
+        }
+
+        match *state.data(handle) {
+            ObjectData::Signal(signaled) => Some(signaled),
+            _ => unreachable!(),
+        }
+    }
+
+    pub extern "system" fn ResetFences(device: Device, fenceCount: u32, pFences: *const Fence)
+                                       -> Result {
+        with_device(device, "vkResetFences", |state| unsafe {
+            for &fence in raw_slice(pFences, fenceCount) {
+                if state.check("vkResetFences", OBJECT_TYPE_FENCE, fence) {
+                    *state.data(fence) = ObjectData::Signal(false);
+                }
+            }
+            SUCCESS
+        })
+    }
+
+    pub extern "system" fn GetFenceStatus(device: Device, fence: Fence) -> Result {
+        with_device(device, "vkGetFenceStatus", |state| {
+            match signaled(state, "vkGetFenceStatus", OBJECT_TYPE_FENCE, fence) {
+                Some(true) => SUCCESS,
+                Some(false) => NOT_READY,
+                None => ERROR_DEVICE_LOST,
+            }
+        })
+    }
+
+    pub extern "system" fn WaitForFences(device: Device, fenceCount: u32, pFences: *const Fence,
+                                         waitAll: Bool32, _: u64)
+                                         -> Result {
+        with_device(device, "vkWaitForFences", |state| unsafe {
+            let mut statuses = Vec::new();
+            for &fence in raw_slice(pFences, fenceCount) {
+                match signaled(state, "vkWaitForFences", OBJECT_TYPE_FENCE, fence) {
+                    Some(signaled) => statuses.push(signaled),
+                    None => return ERROR_DEVICE_LOST,
+                }
+            }
+
+            // Nothing is ever pending, so waiting on an unsignaled fence can only time out.
+            let done = if waitAll != 0 {
+                statuses.iter().all(|&s| s)
+            } else {
+                statuses.iter().any(|&s| s)
+            };
+
+            if done { SUCCESS } else { TIMEOUT }
+        })
+    }
+
+    pub extern "system" fn GetFenceFdKHR(device: Device, pGetFdInfo: *const FenceGetFdInfoKHR,
+                                         pFd: *mut c_int)
+                                         -> Result {
+        with_device(device, "vkGetFenceFdKHR", |state| unsafe {
+            if !state.check("vkGetFenceFdKHR", OBJECT_TYPE_FENCE, (*pGetFdInfo).fence) {
+                return ERROR_DEVICE_LOST;
+            }
+            *pFd = state.export_fd(OBJECT_TYPE_FENCE);
+            SUCCESS
+        })
+    }
+
+    pub extern "system" fn ImportFenceFdKHR(device: Device,
+                                            pImportFenceFdInfo: *const ImportFenceFdInfoKHR)
+                                            -> Result {
+        with_device(device, "vkImportFenceFdKHR", |state| unsafe {
+            let infos = &*pImportFenceFdInfo;
+            if !state.check("vkImportFenceFdKHR", OBJECT_TYPE_FENCE, infos.fence) {
+                return ERROR_DEVICE_LOST;
+            }
+            if !state.import_fd(OBJECT_TYPE_FENCE, infos.fd) {
+                return ERROR_INVALID_EXTERNAL_HANDLE_KHR;
+            }
+            SUCCESS
+        })
+    }
+
+    pub extern "system" fn CreateEvent(device: Device, _: *const EventCreateInfo,
+                                       _: *const AllocationCallbacks, pEvent: *mut Event)
+                                       -> Result {
+        create_object(device,
+                      "vkCreateEvent",
+                      OBJECT_TYPE_EVENT,
+                      ObjectData::Signal(false),
+                      pEvent)
+    }
+
+    pub extern "system" fn DestroyEvent(device: Device, event: Event,
+                                        _: *const AllocationCallbacks) {
+        destroy_object(device, "vkDestroyEvent", OBJECT_TYPE_EVENT, event)
+    }
+
+    pub extern "system" fn GetEventStatus(device: Device, event: Event) -> Result {
+        with_device(device, "vkGetEventStatus", |state| {
+            match signaled(state, "vkGetEventStatus", OBJECT_TYPE_EVENT, event) {
+                Some(true) => EVENT_SET,
+                Some(false) => EVENT_RESET,
+                None => ERROR_DEVICE_LOST,
+            }
+        })
+    }
+
+    fn set_event(device: Device, function: &'static str, event: Event, value: bool) -> Result {
+        with_device(device, function, |state| {
+            if state.check(function, OBJECT_TYPE_EVENT, event) {
+                *state.data(event) = ObjectData::Signal(value);
+            }
+            SUCCESS
+        })
+    }
+
+    pub extern "system" fn SetEvent(device: Device, event: Event) -> Result {
+        set_event(device, "vkSetEvent", event, true)
+    }
+
+    pub extern "system" fn ResetEvent(device: Device, event: Event) -> Result {
+        set_event(device, "vkResetEvent", event, false)
+    }
+
+    pub extern "system" fn CreateBuffer(device: Device, pCreateInfo: *const BufferCreateInfo,
+                                        _: *const AllocationCallbacks, pBuffer: *mut Buffer)
+                                        -> Result {
+        let data = ObjectData::Resource {
+            size: unsafe { (*pCreateInfo).size },
+            binding: None,
+        };
+        create_object(device, "vkCreateBuffer", OBJECT_TYPE_BUFFER, data, pBuffer)
+    }
+
+    pub extern "system" fn DestroyBuffer(device: Device, buffer: Buffer,
+                                         _: *const AllocationCallbacks) {
+        destroy_object(device, "vkDestroyBuffer", OBJECT_TYPE_BUFFER, buffer)
+    }
+
+    pub extern "system" fn CreateImage(device: Device, pCreateInfo: *const ImageCreateInfo,
+                                       _: *const AllocationCallbacks, pImage: *mut Image)
+                                       -> Result {
+        let infos = unsafe { &*pCreateInfo };
+        // Enough for every mipmap of the largest formats.
+        let texels = infos.extent.width as u64 * infos.extent.height as u64 *
+            infos.extent.depth as u64 * infos.arrayLayers as u64;
+        let data = ObjectData::Resource {
+            size: texels * 32 * infos.samples as u64,
+            binding: None,
+        };
+        create_object(device, "vkCreateImage", OBJECT_TYPE_IMAGE, data, pImage)
+    }
+
+    pub extern "system" fn DestroyImage(device: Device, image: Image,
+                                        _: *const AllocationCallbacks) {
+        destroy_object(device, "vkDestroyImage", OBJECT_TYPE_IMAGE, image)
+    }
+
+    pub extern "system" fn GetSemaphoreFdKHR(device: Device,
+                                             pGetFdInfo: *const SemaphoreGetFdInfoKHR,
+                                             pFd: *mut c_int)
+                                             -> Result {
+        with_device(device, "vkGetSemaphoreFdKHR", |state| unsafe {
+            let semaphore = (*pGetFdInfo).semaphore;
+            if !state.check("vkGetSemaphoreFdKHR", OBJECT_TYPE_SEMAPHORE, semaphore) {
+                return ERROR_DEVICE_LOST;
+            }
+            *pFd = state.export_fd(OBJECT_TYPE_SEMAPHORE);
+            SUCCESS
+        })
+    }
+
+    pub extern "system" fn ImportSemaphoreFdKHR(
+        device: Device, pImportSemaphoreFdInfo: *const ImportSemaphoreFdInfoKHR)
+        -> Result {
+        const NAME: &str = "vkImportSemaphoreFdKHR";
+
+        with_device(device, NAME, |state| unsafe {
+            let infos = &*pImportSemaphoreFdInfo;
+            if !state.check(NAME, OBJECT_TYPE_SEMAPHORE, infos.semaphore) {
+                return ERROR_DEVICE_LOST;
+            }
+            if !state.import_fd(OBJECT_TYPE_SEMAPHORE, infos.fd) {
+                return ERROR_INVALID_EXTERNAL_HANDLE_KHR;
+            }
+            SUCCESS
+        })
+    }
+
+    simple_objects! {
+        CreateSemaphore, DestroySemaphore: SemaphoreCreateInfo => OBJECT_TYPE_SEMAPHORE;
+        CreateQueryPool, DestroyQueryPool: QueryPoolCreateInfo => OBJECT_TYPE_QUERY_POOL;
+        CreateBufferView, DestroyBufferView: BufferViewCreateInfo => OBJECT_TYPE_BUFFER_VIEW;
+        CreateImageView, DestroyImageView: ImageViewCreateInfo => OBJECT_TYPE_IMAGE_VIEW;
+        CreateShaderModule, DestroyShaderModule: ShaderModuleCreateInfo =>
+            OBJECT_TYPE_SHADER_MODULE;
+        CreatePipelineCache, DestroyPipelineCache: PipelineCacheCreateInfo =>
+            OBJECT_TYPE_PIPELINE_CACHE;
+        CreatePipelineLayout, DestroyPipelineLayout: PipelineLayoutCreateInfo =>
+            OBJECT_TYPE_PIPELINE_LAYOUT;
+        CreateSampler, DestroySampler: SamplerCreateInfo => OBJECT_TYPE_SAMPLER;
+        CreateDescriptorSetLayout, DestroyDescriptorSetLayout: DescriptorSetLayoutCreateInfo =>
+            OBJECT_TYPE_DESCRIPTOR_SET_LAYOUT;
+        CreateDescriptorPool, DestroyDescriptorPool: DescriptorPoolCreateInfo =>
+            OBJECT_TYPE_DESCRIPTOR_POOL;
+        CreateDescriptorUpdateTemplateKHR, DestroyDescriptorUpdateTemplateKHR:
+            DescriptorUpdateTemplateCreateInfoKHR => OBJECT_TYPE_DESCRIPTOR_UPDATE_TEMPLATE_KHR;
+        CreateFramebuffer, DestroyFramebuffer: FramebufferCreateInfo => OBJECT_TYPE_FRAMEBUFFER;
+        CreateRenderPass, DestroyRenderPass: RenderPassCreateInfo => OBJECT_TYPE_RENDER_PASS;
+        CreateCommandPool, DestroyCommandPool: CommandPoolCreateInfo => OBJECT_TYPE_COMMAND_POOL;
+    }
+
+    fn create_pipelines(device: Device, function: &'static str, count: u32,
+                        pPipelines: *mut Pipeline)
+                        -> Result {
+        with_device(device, function, |state| {
+            for num in 0 .. count as usize {
+                let pipeline = state.create(OBJECT_TYPE_PIPELINE, device as u64, ObjectData::None);
+                unsafe {
+                    *pPipelines.add(num) = pipeline;
+                }
+            }
+            SUCCESS
+        })
+    }
+
+    pub extern "system" fn CreateGraphicsPipelines(device: Device, _: PipelineCache,
+                                                   createInfoCount: u32,
+                                                   _: *const GraphicsPipelineCreateInfo,
+                                                   _: *const AllocationCallbacks,
+                                                   pPipelines: *mut Pipeline)
+                                                   -> Result {
+        create_pipelines(device, "vkCreateGraphicsPipelines", createInfoCount, pPipelines)
+    }
+
+    pub extern "system" fn CreateComputePipelines(device: Device, _: PipelineCache,
+                                                  createInfoCount: u32,
+                                                  _: *const ComputePipelineCreateInfo,
+                                                  _: *const AllocationCallbacks,
+                                                  pPipelines: *mut Pipeline)
+                                                  -> Result {
+        create_pipelines(device, "vkCreateComputePipelines", createInfoCount, pPipelines)
+    }
+
+    pub extern "system" fn DestroyPipeline(device: Device, pipeline: Pipeline,
+                                           _: *const AllocationCallbacks) {
+        destroy_object(device, "vkDestroyPipeline", OBJECT_TYPE_PIPELINE, pipeline)
+    }
+
+    pub extern "system" fn ResetDescriptorPool(device: Device, descriptorPool: DescriptorPool,
+                                               _: DescriptorPoolResetFlags)
+                                               -> Result {
+        with_device(device, "vkResetDescriptorPool", |state| {
+            if state.check("vkResetDescriptorPool", OBJECT_TYPE_DESCRIPTOR_POOL, descriptorPool) {
+                state.destroy_children(descriptorPool, false);
+            }
+            SUCCESS
+        })
+    }
+
+    pub extern "system" fn AllocateDescriptorSets(device: Device,
+                                                  pAllocateInfo: *const DescriptorSetAllocateInfo,
+                                                  pDescriptorSets: *mut DescriptorSet)
+                                                  -> Result {
+        const NAME: &str = "vkAllocateDescriptorSets";
+
+        with_device(device, NAME, |state| unsafe {
+            let infos = &*pAllocateInfo;
+            if !state.check(NAME, OBJECT_TYPE_DESCRIPTOR_POOL, infos.descriptorPool) {
+                return ERROR_OUT_OF_DEVICE_MEMORY;
+            }
+
+            let layouts = raw_slice(infos.pSetLayouts, infos.descriptorSetCount);
+            for (num, &layout) in layouts.iter().enumerate() {
+                state.check(NAME, OBJECT_TYPE_DESCRIPTOR_SET_LAYOUT, layout);
+                let set = state.create(OBJECT_TYPE_DESCRIPTOR_SET,
+                                       infos.descriptorPool,
+                                       ObjectData::None);
+                *pDescriptorSets.add(num) = set;
+            }
+            SUCCESS
+        })
+    }
+
+    pub extern "system" fn FreeDescriptorSets(device: Device, descriptorPool: DescriptorPool,
+                                              descriptorSetCount: u32,
+                                              pDescriptorSets: *const DescriptorSet)
+                                              -> Result {
+        with_device(device, "vkFreeDescriptorSets", |state| unsafe {
+            state.check("vkFreeDescriptorSets", OBJECT_TYPE_DESCRIPTOR_POOL, descriptorPool);
+            for &set in raw_slice(pDescriptorSets, descriptorSetCount) {
+                state.destroy("vkFreeDescriptorSets", OBJECT_TYPE_DESCRIPTOR_SET, set);
+            }
+            SUCCESS
+        })
+    }
+
+    pub extern "system" fn UpdateDescriptorSets(device: Device, descriptorWriteCount: u32,
+                                                pDescriptorWrites: *const WriteDescriptorSet,
+                                                descriptorCopyCount: u32,
+                                                pDescriptorCopies: *const CopyDescriptorSet) {
+        const NAME: &str = "vkUpdateDescriptorSets";
+
+        with_device(device, NAME, |state| unsafe {
+            for write in raw_slice(pDescriptorWrites, descriptorWriteCount) {
+                state.check(NAME, OBJECT_TYPE_DESCRIPTOR_SET, write.dstSet);
+            }
+            for copy in raw_slice(pDescriptorCopies, descriptorCopyCount) {
+                state.check(NAME, OBJECT_TYPE_DESCRIPTOR_SET, copy.srcSet);
+                state.check(NAME, OBJECT_TYPE_DESCRIPTOR_SET, copy.dstSet);
+            }
+            SUCCESS
+        });
+    }
+
+    pub extern "system" fn UpdateDescriptorSetWithTemplateKHR(
+        device: Device, descriptorSet: DescriptorSet,
+        descriptorUpdateTemplate: DescriptorUpdateTemplateKHR, _: *const c_void) {
+        const NAME: &str = "vkUpdateDescriptorSetWithTemplateKHR";
+
+        with_device(device, NAME, |state| {
+            state.check(NAME, OBJECT_TYPE_DESCRIPTOR_SET, descriptorSet);
+            state.check(NAME, OBJECT_TYPE_DESCRIPTOR_UPDATE_TEMPLATE_KHR, descriptorUpdateTemplate);
+            SUCCESS
+        });
+    }
+
+    pub extern "system" fn ResetCommandPool(device: Device, commandPool: CommandPool,
+                                            _: CommandPoolResetFlags)
+                                            -> Result {
+        with_device(device, "vkResetCommandPool", |state| {
+            if !state.check("vkResetCommandPool", OBJECT_TYPE_COMMAND_POOL, commandPool) {
+                return SUCCESS;
+            }
+
+            for object in state.objects.values_mut() {
+                if object.parent != commandPool {
+                    continue;
+                }
+
+                if let ObjectData::CommandBuffer {
+                    ref mut state,
+                    ref mut commands,
+                    ..
+                } = object.data
+                {
+                    *state = CommandBufferState::Initial;
+                    commands.clear();
+                }
+            }
+            SUCCESS
+        })
+    }
+
+    pub extern "system" fn AllocateCommandBuffers(device: Device,
+                                                  pAllocateInfo: *const CommandBufferAllocateInfo,
+                                                  pCommandBuffers: *mut CommandBuffer)
+                                                  -> Result {
+        with_state(device, "vkAllocateCommandBuffers", |state, recorder| unsafe {
+            let infos = &*pAllocateInfo;
+            if !state.check("vkAllocateCommandBuffers", OBJECT_TYPE_DEVICE, device as u64) ||
+                !state.check("vkAllocateCommandBuffers",
+                             OBJECT_TYPE_COMMAND_POOL,
+                             infos.commandPool)
+            {
+                return ERROR_OUT_OF_DEVICE_MEMORY;
+            }
+
+            for num in 0 .. infos.commandBufferCount as usize {
+                let command_buffer = state.dispatchable(recorder);
+                state.objects.insert(command_buffer as u64,
+                                     super::Object {
+                                         ty: OBJECT_TYPE_COMMAND_BUFFER,
+                                         parent: infos.commandPool,
+                                         data: ObjectData::CommandBuffer {
+                                             state: CommandBufferState::Initial,
+                                             one_time: false,
+                                             commands: Vec::new(),
+                                         },
+                                     });
+                *pCommandBuffers.add(num) = command_buffer;
+            }
+            SUCCESS
+        }).unwrap_or(ERROR_DEVICE_LOST)
+    }
+
+    pub extern "system" fn FreeCommandBuffers(device: Device, commandPool: CommandPool,
+                                              commandBufferCount: u32,
+                                              pCommandBuffers: *const CommandBuffer) {
+        with_device(device, "vkFreeCommandBuffers", |state| unsafe {
+            state.check("vkFreeCommandBuffers", OBJECT_TYPE_COMMAND_POOL, commandPool);
+            for &command_buffer in raw_slice(pCommandBuffers, commandBufferCount) {
+                state.destroy("vkFreeCommandBuffers",
+                              OBJECT_TYPE_COMMAND_BUFFER,
+                              command_buffer as u64);
+            }
+            SUCCESS
+        });
+    }
+
+    pub extern "system" fn BeginCommandBuffer(commandBuffer: CommandBuffer,
+                                              pBeginInfo: *const CommandBufferBeginInfo)
+                                              -> Result {
+        with_state(commandBuffer, "vkBeginCommandBuffer", |state, _| {
+            if !state.check_command_buffer("vkBeginCommandBuffer",
+                                           commandBuffer,
+                                           &[CommandBufferState::Initial,
+                                             CommandBufferState::Executable,
+                                             CommandBufferState::Invalid])
+            {
+                return SUCCESS;
+            }
+
+            let flags = unsafe { (*pBeginInfo).flags };
+            *state.data(commandBuffer as u64) = ObjectData::CommandBuffer {
+                state: CommandBufferState::Recording,
+                one_time: flags & COMMAND_BUFFER_USAGE_ONE_TIME_SUBMIT_BIT != 0,
+                commands: Vec::new(),
+            };
+            SUCCESS
+        }).unwrap_or(ERROR_DEVICE_LOST)
+    }
+
+    pub extern "system" fn EndCommandBuffer(commandBuffer: CommandBuffer) -> Result {
+        with_state(commandBuffer, "vkEndCommandBuffer", |state, _| {
+            if state.check_command_buffer("vkEndCommandBuffer",
+                                          commandBuffer,
+                                          &[CommandBufferState::Recording])
+            {
+                if let ObjectData::CommandBuffer { ref mut state, .. } =
+                    *state.data(commandBuffer as u64)
+                {
+                    *state = CommandBufferState::Executable;
+                }
+            }
+            SUCCESS
+        }).unwrap_or(ERROR_DEVICE_LOST)
+    }
+
+    pub extern "system" fn ResetCommandBuffer(commandBuffer: CommandBuffer,
+                                              _: CommandBufferResetFlags)
+                                              -> Result {
+        with_state(commandBuffer, "vkResetCommandBuffer", |state, _| {
+            if state.check("vkResetCommandBuffer",
+                           OBJECT_TYPE_COMMAND_BUFFER,
+                           commandBuffer as u64)
+            {
+                if let ObjectData::CommandBuffer {
+                    ref mut state,
+                    ref mut commands,
+                    ..
+                } = *state.data(commandBuffer as u64)
+                {
+                    *state = CommandBufferState::Initial;
+                    commands.clear();
+                }
+            }
+            SUCCESS
+        }).unwrap_or(ERROR_DEVICE_LOST)
+    }
+
+    pub extern "system" fn CreateHeadlessSurfaceEXT(instance: Instance,
+                                                    _: *const HeadlessSurfaceCreateInfoEXT,
+                                                    _: *const AllocationCallbacks,
+                                                    pSurface: *mut SurfaceKHR)
+                                                    -> Result {
+        const NAME: &str = "vkCreateHeadlessSurfaceEXT";
+
+        with_state(instance, NAME, |state, _| unsafe {
+            if !state.check(NAME, OBJECT_TYPE_INSTANCE, instance as u64) {
+                return ERROR_INITIALIZATION_FAILED;
+            }
+
+            *pSurface = state.create(OBJECT_TYPE_SURFACE_KHR, instance as u64, ObjectData::None);
+            SUCCESS
+        }).unwrap_or(ERROR_INITIALIZATION_FAILED)
+    }
+
+    pub extern "system" fn DestroySurfaceKHR(instance: Instance, surface: SurfaceKHR,
+                                             _: *const AllocationCallbacks) {
+        with_state(instance, "vkDestroySurfaceKHR", |state, _| {
+            state.destroy("vkDestroySurfaceKHR", OBJECT_TYPE_SURFACE_KHR, surface);
+        });
+    }
+
+    pub extern "system" fn GetPhysicalDeviceSurfaceSupportKHR(physicalDevice: PhysicalDevice,
+                                                              queueFamilyIndex: u32,
+                                                              surface: SurfaceKHR,
+                                                              pSupported: *mut Bool32)
+                                                              -> Result {
+        const NAME: &str = "vkGetPhysicalDeviceSurfaceSupportKHR";
+
+        with_state(physicalDevice, NAME, |state, _| unsafe {
+            if !state.check(NAME, OBJECT_TYPE_SURFACE_KHR, surface) {
+                return ERROR_SURFACE_LOST_KHR;
+            }
+
+            // Only the queue family with graphics support can present.
+            *pSupported = if queueFamilyIndex == 0 { TRUE } else { FALSE };
+            SUCCESS
+        }).unwrap_or(ERROR_INITIALIZATION_FAILED)
+    }
+
+    pub extern "system" fn GetPhysicalDeviceSurfaceCapabilitiesKHR(
+        physicalDevice: PhysicalDevice, surface: SurfaceKHR,
+        pSurfaceCapabilities: *mut SurfaceCapabilitiesKHR)
+        -> Result {
+        const NAME: &str = "vkGetPhysicalDeviceSurfaceCapabilitiesKHR";
+
+        with_state(physicalDevice, NAME, |state, _| unsafe {
+            if !state.check(NAME, OBJECT_TYPE_SURFACE_KHR, surface) {
+                return ERROR_SURFACE_LOST_KHR;
+            }
+
+            *pSurfaceCapabilities = SurfaceCapabilitiesKHR {
+                minImageCount: MIN_IMAGE_COUNT,
+                maxImageCount: 8,
+                // Headless surfaces don't have a current extent.
+                currentExtent: Extent2D {
+                    width: 0xffffffff,
+                    height: 0xffffffff,
+                },
+                minImageExtent: Extent2D {
+                    width: 1,
+                    height: 1,
+                },
+                maxImageExtent: Extent2D {
+                    width: 16384,
+                    height: 16384,
+                },
+                maxImageArrayLayers: 1,
+                supportedTransforms: SURFACE_TRANSFORM_IDENTITY_BIT_KHR,
+                currentTransform: SURFACE_TRANSFORM_IDENTITY_BIT_KHR,
+                supportedCompositeAlpha: COMPOSITE_ALPHA_OPAQUE_BIT_KHR,
+                supportedUsageFlags: IMAGE_USAGE_TRANSFER_SRC_BIT | IMAGE_USAGE_TRANSFER_DST_BIT |
+                    IMAGE_USAGE_SAMPLED_BIT |
+                    IMAGE_USAGE_STORAGE_BIT |
+                    IMAGE_USAGE_COLOR_ATTACHMENT_BIT,
+            };
+            SUCCESS
+        }).unwrap_or(ERROR_INITIALIZATION_FAILED)
+    }
+
+    pub extern "system" fn GetPhysicalDeviceSurfaceFormatsKHR(
+        physicalDevice: PhysicalDevice, surface: SurfaceKHR, pSurfaceFormatCount: *mut u32,
+        pSurfaceFormats: *mut SurfaceFormatKHR)
+        -> Result {
+        const NAME: &str = "vkGetPhysicalDeviceSurfaceFormatsKHR";
+
+        with_state(physicalDevice, NAME, |state, _| unsafe {
+            if !state.check(NAME, OBJECT_TYPE_SURFACE_KHR, surface) {
+                return ERROR_SURFACE_LOST_KHR;
+            }
+
+            let formats = [
+                SurfaceFormatKHR {
+                    format: FORMAT_B8G8R8A8_UNORM,
+                    colorSpace: COLOR_SPACE_SRGB_NONLINEAR_KHR,
+                },
+                SurfaceFormatKHR {
+                    format: FORMAT_B8G8R8A8_SRGB,
+                    colorSpace: COLOR_SPACE_SRGB_NONLINEAR_KHR,
+                },
+            ];
+            enumerate(&formats, pSurfaceFormatCount, pSurfaceFormats)
+        }).unwrap_or(ERROR_INITIALIZATION_FAILED)
+    }
+
+    pub extern "system" fn GetPhysicalDeviceSurfacePresentModesKHR(
+        physicalDevice: PhysicalDevice, surface: SurfaceKHR, pPresentModeCount: *mut u32,
+        pPresentModes: *mut PresentModeKHR)
+        -> Result {
+        const NAME: &str = "vkGetPhysicalDeviceSurfacePresentModesKHR";
+
+        with_state(physicalDevice, NAME, |state, _| unsafe {
+            if !state.check(NAME, OBJECT_TYPE_SURFACE_KHR, surface) {
+                return ERROR_SURFACE_LOST_KHR;
+            }
+
+            let modes = [
+                PRESENT_MODE_FIFO_KHR,
+                PRESENT_MODE_MAILBOX_KHR,
+                PRESENT_MODE_IMMEDIATE_KHR,
+            ];
+            enumerate(&modes, pPresentModeCount, pPresentModes)
+        }).unwrap_or(ERROR_INITIALIZATION_FAILED)
+    }
+
+    pub extern "system" fn CreateSwapchainKHR(device: Device,
+                                              pCreateInfo: *const SwapchainCreateInfoKHR,
+                                              _: *const AllocationCallbacks,
+                                              pSwapchain: *mut SwapchainKHR)
+                                              -> Result {
+        const NAME: &str = "vkCreateSwapchainKHR";
+
+        with_device(device, NAME, |state| unsafe {
+            let infos = &*pCreateInfo;
+            if !state.check(NAME, OBJECT_TYPE_SURFACE_KHR, infos.surface) {
+                return ERROR_SURFACE_LOST_KHR;
+            }
+            if infos.oldSwapchain != 0 {
+                state.check(NAME, OBJECT_TYPE_SWAPCHAIN_KHR, infos.oldSwapchain);
+            }
+
+            let swapchain =
+                state.create(OBJECT_TYPE_SWAPCHAIN_KHR, device as u64, ObjectData::None);
+            let images = (0 .. infos.minImageCount)
+                .map(|_| state.create(OBJECT_TYPE_IMAGE, swapchain, ObjectData::None))
+                .collect();
+            *state.data(swapchain) = ObjectData::Swapchain {
+                images,
+                next: 0,
+                out_of_date: false,
+            };
+
+            *pSwapchain = swapchain;
+            SUCCESS
+        })
+    }
+
+    pub extern "system" fn DestroySwapchainKHR(device: Device, swapchain: SwapchainKHR,
+                                               _: *const AllocationCallbacks) {
+        destroy_object(device, "vkDestroySwapchainKHR", OBJECT_TYPE_SWAPCHAIN_KHR, swapchain)
+    }
+
+    pub extern "system" fn GetSwapchainImagesKHR(device: Device, swapchain: SwapchainKHR,
+                                                 pSwapchainImageCount: *mut u32,
+                                                 pSwapchainImages: *mut Image)
+                                                 -> Result {
+        const NAME: &str = "vkGetSwapchainImagesKHR";
+
+        with_device(device, NAME, |state| unsafe {
+            if !state.check(NAME, OBJECT_TYPE_SWAPCHAIN_KHR, swapchain) {
+                return ERROR_DEVICE_LOST;
+            }
+
+            let images = match *state.data(swapchain) {
+                ObjectData::Swapchain { ref images, .. } => images.clone(),
+                _ => unreachable!(),
+            };
+            enumerate(&images, pSwapchainImageCount, pSwapchainImages)
+        })
+    }
+
+    pub extern "system" fn AcquireNextImageKHR(device: Device, swapchain: SwapchainKHR, _: u64,
+                                               semaphore: Semaphore, fence: Fence,
+                                               pImageIndex: *mut u32)
+                                               -> Result {
+        const NAME: &str = "vkAcquireNextImageKHR";
+
+        with_device(device, NAME, |state| {
+            if !state.check(NAME, OBJECT_TYPE_SWAPCHAIN_KHR, swapchain) {
+                return ERROR_DEVICE_LOST;
+            }
+            if semaphore != 0 {
+                state.check(NAME, OBJECT_TYPE_SEMAPHORE, semaphore);
+            }
+
+            let index = match *state.data(swapchain) {
+                ObjectData::Swapchain { out_of_date: true, .. } => return ERROR_OUT_OF_DATE_KHR,
+                ObjectData::Swapchain {
+                    ref images,
+                    ref mut next,
+                    ..
+                } => {
+                    let index = *next;
+                    *next = (index + 1) % images.len() as u32;
+                    index
+                },
+                _ => unreachable!(),
+            };
+
+            // The image is available right away.
+            if fence != 0 && state.check(NAME, OBJECT_TYPE_FENCE, fence) {
+                *state.data(fence) = ObjectData::Signal(true);
+            }
+
+            unsafe {
+                *pImageIndex = index;
+            }
+            SUCCESS
+        })
+    }
+
+    pub extern "system" fn QueuePresentKHR(queue: Queue, pPresentInfo: *const PresentInfoKHR)
+                                           -> Result {
+        const NAME: &str = "vkQueuePresentKHR";
+
+        with_state(queue, NAME, |state, _| unsafe {
+            if !state.check(NAME, OBJECT_TYPE_QUEUE, queue as u64) {
+                return ERROR_DEVICE_LOST;
+            }
+
+            let infos = &*pPresentInfo;
+            for &semaphore in raw_slice(infos.pWaitSemaphores, infos.waitSemaphoreCount) {
+                state.check(NAME, OBJECT_TYPE_SEMAPHORE, semaphore);
+            }
+
+            let mut result = SUCCESS;
+            let swapchains = raw_slice(infos.pSwapchains, infos.swapchainCount);
+            for (n, &swapchain) in swapchains.iter().enumerate() {
+                let valid = state.check(NAME, OBJECT_TYPE_SWAPCHAIN_KHR, swapchain);
+                let swapchain_result = if !valid {
+                    ERROR_DEVICE_LOST
+                } else {
+                    match *state.data(swapchain) {
+                        ObjectData::Swapchain { out_of_date: true, .. } => ERROR_OUT_OF_DATE_KHR,
+                        _ => SUCCESS,
+                    }
+                };
+
+                if !infos.pResults.is_null() {
+                    *infos.pResults.add(n) = swapchain_result;
+                }
+                if swapchain_result != SUCCESS {
+                    result = swapchain_result;
+                }
+            }
+
+            result
+        }).unwrap_or(ERROR_DEVICE_LOST)
+    }
+
+    pub extern "system" fn CreateDebugUtilsMessengerEXT(
+        instance: Instance, _: *const DebugUtilsMessengerCreateInfoEXT,
+        _: *const AllocationCallbacks, pMessenger: *const DebugUtilsMessengerEXT)
+        -> Result {
+        const NAME: &str = "vkCreateDebugUtilsMessengerEXT";
+
+        with_state(instance, NAME, |state, _| unsafe {
+            if !state.check(NAME, OBJECT_TYPE_INSTANCE, instance as u64) {
+                return ERROR_INITIALIZATION_FAILED;
+            }
+
+            let messenger = state.create(OBJECT_TYPE_DEBUG_UTILS_MESSENGER_EXT,
+                                         instance as u64,
+                                         ObjectData::None);
+            // The output pointer is `*const` in vk-sys.
+            *(pMessenger as *mut DebugUtilsMessengerEXT) = messenger;
+            SUCCESS
+        }).unwrap_or(ERROR_INITIALIZATION_FAILED)
+    }
+
+    pub extern "system" fn DestroyDebugUtilsMessengerEXT(instance: Instance,
+                                                         messenger: DebugUtilsMessengerEXT,
+                                                         _: *const AllocationCallbacks)
+                                                         -> Result {
+        const NAME: &str = "vkDestroyDebugUtilsMessengerEXT";
+
+        with_state(instance, NAME, |state, _| {
+            state.destroy(NAME, OBJECT_TYPE_DEBUG_UTILS_MESSENGER_EXT, messenger);
+        });
+        SUCCESS
+    }
+}
+
+// The `vkCmd*` functions, which record their name in the command buffer after checking that the
+// objects they use are alive.
+macro_rules! commands {
+    ($($name:ident(commandBuffer $(, $param:ident: $ty:ty)*)
+       [$($check_ty:ident $check:ident),*];)+) => (
+        #[allow(non_snake_case)]
+        #[allow(unused_variables)]
+        // Matches the signature of `vkCmdSetBlendConstants` in vk-sys.
+        #[allow(improper_ctypes_definitions)]
+        mod commands {
+            use std::os::raw::c_void;
+
+            use vk::*;
+
+            use super::with_state;
+
+            const BUFFER: ObjectType = OBJECT_TYPE_BUFFER;
+            const EVENT: ObjectType = OBJECT_TYPE_EVENT;
+            const IMAGE: ObjectType = OBJECT_TYPE_IMAGE;
+            const PIPELINE: ObjectType = OBJECT_TYPE_PIPELINE;
+            const PIPELINE_LAYOUT: ObjectType = OBJECT_TYPE_PIPELINE_LAYOUT;
+            const QUERY_POOL: ObjectType = OBJECT_TYPE_QUERY_POOL;
+
+            $(
+                extern "system" fn $name(commandBuffer: CommandBuffer $(, $param: $ty)*) {
+                    let function = concat!("vk", stringify!($name));
+                    with_state(commandBuffer, function, |state, _| {
+                        state.record_command(function, commandBuffer, &[$(($check_ty, $check)),*]);
+                    });
+                }
+            )+
+
+            pub fn lookup(name: &[u8]) -> usize {
+                $(
+                    if name == concat!("vk", stringify!($name)).as_bytes() {
+                        return $name as *const () as usize;
+                    }
+                )+
+
+                0
+            }
+        }
+    );
+}
+
+commands! {
+    CmdBindPipeline(commandBuffer, pipelineBindPoint: PipelineBindPoint,
+                    pipeline: Pipeline) [PIPELINE pipeline];
+    CmdSetViewport(commandBuffer, firstViewport: u32, viewportCount: u32,
+                   pViewports: *const Viewport) [];
+    CmdSetScissor(commandBuffer, firstScissor: u32, scissorCount: u32,
+                  pScissors: *const Rect2D) [];
+    CmdSetLineWidth(commandBuffer, lineWidth: f32) [];
+    CmdSetDepthBias(commandBuffer, depthBiasConstantFactor: f32, depthBiasClamp: f32,
+                    depthBiasSlopeFactor: f32) [];
+    CmdSetBlendConstants(commandBuffer, blendConstants: [f32; 4]) [];
+    CmdSetDepthBounds(commandBuffer, minDepthBounds: f32, maxDepthBounds: f32) [];
+    CmdSetStencilCompareMask(commandBuffer, faceMask: StencilFaceFlags, compareMask: u32) [];
+    CmdSetStencilWriteMask(commandBuffer, faceMask: StencilFaceFlags, writeMask: u32) [];
+    CmdSetStencilReference(commandBuffer, faceMask: StencilFaceFlags, reference: u32) [];
+    CmdBindDescriptorSets(commandBuffer, pipelineBindPoint: PipelineBindPoint,
+                          layout: PipelineLayout, firstSet: u32, descriptorSetCount: u32,
+                          pDescriptorSets: *const DescriptorSet, dynamicOffsetCount: u32,
+                          pDynamicOffsets: *const u32) [PIPELINE_LAYOUT layout];
+    CmdBindIndexBuffer(commandBuffer, buffer: Buffer, offset: DeviceSize,
+                       indexType: IndexType) [BUFFER buffer];
+    CmdBindVertexBuffers(commandBuffer, firstBinding: u32, bindingCount: u32,
+                         pBuffers: *const Buffer, pOffsets: *const DeviceSize) [];
+    CmdDraw(commandBuffer, vertexCount: u32, instanceCount: u32, firstVertex: u32,
+            firstInstance: u32) [];
+    CmdDrawIndexed(commandBuffer, indexCount: u32, instanceCount: u32, firstIndex: u32,
+                   vertexOffset: i32, firstInstance: u32) [];
+    CmdDrawIndirect(commandBuffer, buffer: Buffer, offset: DeviceSize, drawCount: u32,
+                    stride: u32) [BUFFER buffer];
+    CmdDrawIndexedIndirect(commandBuffer, buffer: Buffer, offset: DeviceSize, drawCount: u32,
+                           stride: u32) [BUFFER buffer];
+    CmdDispatch(commandBuffer, x: u32, y: u32, z: u32) [];
+    CmdDispatchIndirect(commandBuffer, buffer: Buffer,
+                        offset: DeviceSize) [BUFFER buffer];
+    CmdCopyBuffer(commandBuffer, srcBuffer: Buffer, dstBuffer: Buffer, regionCount: u32,
+                  pRegions: *const BufferCopy) [BUFFER srcBuffer, BUFFER dstBuffer];
+    CmdCopyImage(commandBuffer, srcImage: Image, srcImageLayout: ImageLayout, dstImage: Image,
+                 dstImageLayout: ImageLayout, regionCount: u32,
+                 pRegions: *const ImageCopy) [IMAGE srcImage, IMAGE dstImage];
+    CmdBlitImage(commandBuffer, srcImage: Image, srcImageLayout: ImageLayout, dstImage: Image,
+                 dstImageLayout: ImageLayout, regionCount: u32, pRegions: *const ImageBlit,
+                 filter: Filter) [IMAGE srcImage, IMAGE dstImage];
+    CmdCopyBufferToImage(commandBuffer, srcBuffer: Buffer, dstImage: Image,
+                         dstImageLayout: ImageLayout, regionCount: u32,
+                         pRegions: *const BufferImageCopy) [BUFFER srcBuffer, IMAGE dstImage];
+    CmdCopyImageToBuffer(commandBuffer, srcImage: Image, srcImageLayout: ImageLayout,
+                         dstBuffer: Buffer, regionCount: u32,
+                         pRegions: *const BufferImageCopy) [IMAGE srcImage, BUFFER dstBuffer];
+    CmdUpdateBuffer(commandBuffer, dstBuffer: Buffer, dstOffset: DeviceSize,
+                    dataSize: DeviceSize, pData: *const u32) [BUFFER dstBuffer];
+    CmdFillBuffer(commandBuffer, dstBuffer: Buffer, dstOffset: DeviceSize, size: DeviceSize,
+                  data: u32) [BUFFER dstBuffer];
+    CmdClearColorImage(commandBuffer, image: Image, imageLayout: ImageLayout,
+                       pColor: *const ClearColorValue, rangeCount: u32,
+                       pRanges: *const ImageSubresourceRange) [IMAGE image];
+    CmdClearDepthStencilImage(commandBuffer, image: Image, imageLayout: ImageLayout,
+                              pDepthStencil: *const ClearDepthStencilValue, rangeCount: u32,
+                              pRanges: *const ImageSubresourceRange) [IMAGE image];
+    CmdClearAttachments(commandBuffer, attachmentCount: u32,
+                        pAttachments: *const ClearAttachment, rectCount: u32,
+                        pRects: *const ClearRect) [];
+    CmdResolveImage(commandBuffer, srcImage: Image, srcImageLayout: ImageLayout,
+                    dstImage: Image, dstImageLayout: ImageLayout, regionCount: u32,
+                    pRegions: *const ImageResolve) [IMAGE srcImage, IMAGE dstImage];
+    CmdSetEvent(commandBuffer, event: Event,
+                stageMask: PipelineStageFlags) [EVENT event];
+    CmdResetEvent(commandBuffer, event: Event,
+                  stageMask: PipelineStageFlags) [EVENT event];
+    CmdWaitEvents(commandBuffer, eventCount: u32, pEvents: *const Event,
+                  srcStageMask: PipelineStageFlags, dstStageMask: PipelineStageFlags,
+                  memoryBarrierCount: u32, pMemoryBarriers: *const MemoryBarrier,
+                  bufferMemoryBarrierCount: u32,
+                  pBufferMemoryBarriers: *const BufferMemoryBarrier,
+                  imageMemoryBarrierCount: u32,
+                  pImageMemoryBarriers: *const ImageMemoryBarrier) [];
+    CmdPipelineBarrier(commandBuffer, srcStageMask: PipelineStageFlags,
+                       dstStageMask: PipelineStageFlags, dependencyFlags: DependencyFlags,
+                       memoryBarrierCount: u32, pMemoryBarriers: *const MemoryBarrier,
+                       bufferMemoryBarrierCount: u32,
+                       pBufferMemoryBarriers: *const BufferMemoryBarrier,
+                       imageMemoryBarrierCount: u32,
+                       pImageMemoryBarriers: *const ImageMemoryBarrier) [];
+    CmdBeginQuery(commandBuffer, queryPool: QueryPool, query: u32,
+                  flags: QueryControlFlags) [QUERY_POOL queryPool];
+    CmdEndQuery(commandBuffer, queryPool: QueryPool,
+                query: u32) [QUERY_POOL queryPool];
+    CmdResetQueryPool(commandBuffer, queryPool: QueryPool, firstQuery: u32,
+                      queryCount: u32) [QUERY_POOL queryPool];
+    CmdWriteTimestamp(commandBuffer, pipelineStage: PipelineStageFlagBits, queryPool: QueryPool,
+                      query: u32) [QUERY_POOL queryPool];
+    CmdCopyQueryPoolResults(commandBuffer, queryPool: QueryPool, firstQuery: u32,
+                            queryCount: u32, dstBuffer: Buffer, dstOffset: DeviceSize,
+                            stride: DeviceSize,
+                            flags: QueryResultFlags) [QUERY_POOL queryPool, BUFFER dstBuffer];
+    CmdPushConstants(commandBuffer, layout: PipelineLayout, stageFlags: ShaderStageFlags,
+                     offset: u32, size: u32,
+                     pValues: *const c_void) [PIPELINE_LAYOUT layout];
+    CmdPushDescriptorSetKHR(commandBuffer, pipelineBindPoint: PipelineBindPoint,
+                            layout: PipelineLayout, set: u32, descriptorWriteCount: u32,
+                            pDescriptorWrites: *const WriteDescriptorSet) [PIPELINE_LAYOUT layout];
+    CmdBeginDebugUtilsLabelEXT(commandBuffer, pLabelInfo: *const DebugUtilsLabelEXT) [];
+    CmdEndDebugUtilsLabelEXT(commandBuffer) [];
+    CmdInsertDebugUtilsLabelEXT(commandBuffer, pLabelInfo: *const DebugUtilsLabelEXT) [];
+    CmdBeginRenderPass(commandBuffer, pRenderPassBegin: *const RenderPassBeginInfo,
+                       contents: SubpassContents) [];
+    CmdNextSubpass(commandBuffer, contents: SubpassContents) [];
+    CmdEndRenderPass(commandBuffer) [];
+    CmdExecuteCommands(commandBuffer, commandBufferCount: u32,
+                       pCommandBuffers: *const CommandBuffer) [];
+}
+
+mod tests {
+    use std::ptr;
+
+    use buffer::BufferUsage;
+    use buffer::CpuAccessibleBuffer;
+    use command_buffer::AutoCommandBufferBuilder;
+    use command_buffer::CommandBuffer;
+    use command_buffer::pool::UnsafeCommandPool;
+    use mock::CommandBufferState;
+    use mock::MockError;
+    use mock::Recorder;
+    use sync::GpuFuture;
+
+    use SynchronizedVulkanObject;
+    use VulkanObject;
+    use vk;
+
+    #[test]
+    fn records_device_creation() {
+        let (device, _) = mock_dev_and_queue!();
+        let recorder = Recorder::of(device.instance()).unwrap();
+
+        let calls = recorder.calls();
+        assert_eq!(calls[0], "vkCreateInstance");
+        assert!(calls.contains(&"vkCreateDevice"));
+        assert_eq!(recorder.live_count(vk::OBJECT_TYPE_DEVICE), 1);
+        assert_eq!(recorder.live_count(vk::OBJECT_TYPE_QUEUE), 1);
+    }
+
+    #[test]
+    fn clean_destruction() {
+        let (device, queue) = mock_dev_and_queue!();
+        let recorder = Recorder::of(device.instance()).unwrap();
+
+        let _ = CpuAccessibleBuffer::from_data(device.clone(), BufferUsage::all(), false, 0u32)
+            .unwrap();
+        drop(queue);
+        drop(device);
+
+        assert_eq!(recorder.calls().last(), Some(&"vkDestroyInstance"));
+        assert!(recorder.live_objects().is_empty());
+        assert_eq!(recorder.errors(), vec![]);
+    }
+
+    #[test]
+    fn reports_leaks() {
+        let (device, queue) = mock_dev_and_queue!();
+        let recorder = Recorder::of(device.instance()).unwrap();
+
+        let fence = unsafe {
+            let infos = vk::FenceCreateInfo {
+                sType: vk::STRUCTURE_TYPE_FENCE_CREATE_INFO,
+                pNext: ptr::null(),
+                flags: 0,
+            };
+
+            let mut fence = 0;
+            device
+                .pointers()
+                .CreateFence(device.internal_object(), &infos, ptr::null(), &mut fence);
+            fence
+        };
+
+        drop(queue);
+        drop(device);
+
+        assert_eq!(recorder.errors(),
+                   vec![
+                       MockError::Leak {
+                           ty: vk::OBJECT_TYPE_FENCE,
+                           handle: fence,
+                       },
+                   ]);
+    }
+
+    #[test]
+    fn reports_memory_misuse() {
+        let (device, _) = mock_dev_and_queue!();
+        let recorder = Recorder::of(device.instance()).unwrap();
+        let vk = device.pointers();
+        let raw_device = device.internal_object();
+
+        let (buffer, memory) = unsafe {
+            let infos = vk::BufferCreateInfo {
+                sType: vk::STRUCTURE_TYPE_BUFFER_CREATE_INFO,
+                pNext: ptr::null(),
+                flags: 0,
+                size: 1024,
+                usage: vk::BUFFER_USAGE_TRANSFER_DST_BIT,
+                sharingMode: vk::SHARING_MODE_EXCLUSIVE,
+                queueFamilyIndexCount: 0,
+                pQueueFamilyIndices: ptr::null(),
+            };
+            let mut buffer = 0;
+            vk.CreateBuffer(raw_device, &infos, ptr::null(), &mut buffer);
+
+            let infos = vk::MemoryAllocateInfo {
+                sType: vk::STRUCTURE_TYPE_MEMORY_ALLOCATE_INFO,
+                pNext: ptr::null(),
+                allocationSize: 512,
+                memoryTypeIndex: 0,
+            };
+            let mut memory = 0;
+            vk.AllocateMemory(raw_device, &infos, ptr::null(), &mut memory);
+
+            vk.BindBufferMemory(raw_device, buffer, memory, 0);
+            vk.UnmapMemory(raw_device, memory);
+            vk.DestroyBuffer(raw_device, buffer, ptr::null());
+            vk.DestroyBuffer(raw_device, buffer, ptr::null());
+            vk.FreeMemory(raw_device, memory, ptr::null());
+            (buffer, memory)
+        };
+
+        assert_eq!(recorder.errors(),
+                   vec![
+                       MockError::BindingOutOfRange {
+                           function: "vkBindBufferMemory",
+                           handle: buffer,
+                           memory,
+                       },
+                       MockError::NotMapped { memory },
+                       MockError::UnknownHandle {
+                           function: "vkDestroyBuffer",
+                           ty: vk::OBJECT_TYPE_BUFFER,
+                           handle: buffer,
+                       },
+                   ]);
+    }
+
+    #[test]
+    fn reports_commands_outside_recording() {
+        let (device, queue) = mock_dev_and_queue!();
+        let recorder = Recorder::of(device.instance()).unwrap();
+
+        let pool = UnsafeCommandPool::new(device.clone(), queue.family(), false, false).unwrap();
+        let command_buffer = pool.alloc_command_buffers(false, 1)
+            .unwrap()
+            .next()
+            .unwrap()
+            .internal_object();
+
+        unsafe {
+            device.pointers().CmdDispatch(command_buffer, 1, 1, 1);
+            device.pointers().EndCommandBuffer(command_buffer);
+        }
+
+        assert_eq!(recorder.commands(command_buffer), Some(vec![]));
+        assert_eq!(recorder.errors(),
+                   vec![
+                       MockError::InvalidCommandBufferState {
+                           function: "vkCmdDispatch",
+                           command_buffer,
+                           state: CommandBufferState::Initial,
+                       },
+                       MockError::InvalidCommandBufferState {
+                           function: "vkEndCommandBuffer",
+                           command_buffer,
+                           state: CommandBufferState::Initial,
+                       },
+                   ]);
+    }
+
+    #[test]
+    fn records_commands_and_submissions() {
+        let (device, queue) = mock_dev_and_queue!();
+        let recorder = Recorder::of(device.instance()).unwrap();
+
+        let buffer = CpuAccessibleBuffer::from_data(device.clone(), BufferUsage::all(), false,
+                                                    0u32)
+            .unwrap();
+        let command_buffer = AutoCommandBufferBuilder::new(device.clone(), queue.family())
+            .unwrap()
+            .fill_buffer(buffer, 12)
+            .unwrap()
+            .build()
+            .unwrap();
+        let raw = command_buffer.inner().internal_object();
+
+        assert_eq!(recorder.commands(raw), Some(vec!["vkCmdFillBuffer"]));
+        assert_eq!(recorder.command_buffer_state(raw), Some(CommandBufferState::Executable));
+
+        command_buffer
+            .execute(queue.clone())
+            .unwrap()
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+
+        let submissions = recorder.submissions();
+        assert_eq!(submissions.len(), 1);
+        assert_eq!(submissions[0].queue, *queue.internal_object_guard());
+        assert_eq!(submissions[0].command_buffers, vec![raw]);
+        assert_eq!(submissions[0].commands, vec!["vkCmdFillBuffer"]);
+        assert_ne!(submissions[0].fence, 0);
+        assert_eq!(recorder.errors(), vec![]);
+    }
+}
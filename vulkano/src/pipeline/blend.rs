@@ -27,6 +27,7 @@ use vk;
 /// Describes how the color output of the fragment shader is written to the attachment. See the
 /// documentation of the `blend` module for more info.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Blend {
     pub logic_op: Option<LogicOp>,
 
@@ -63,6 +64,7 @@ impl Blend {
 
 /// Describes how the blending system should behave.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum AttachmentsBlend {
     /// All the framebuffer attachments will use the same blending.
     Collective(AttachmentBlend),
@@ -74,6 +76,7 @@ pub enum AttachmentsBlend {
 
 /// Describes how the blending system should behave for an individual attachment.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AttachmentBlend {
     // TODO: could be automatically determined from the other params
     /// If false, blending is ignored and the output is directly written to the attachment.
@@ -188,6 +191,7 @@ impl AttachmentBlend {
 ///
 /// Also note that some implementations don't support logic operations.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(u32)]
 pub enum LogicOp {
     /// Returns `0`.
@@ -232,6 +236,7 @@ impl Default for LogicOp {
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(u32)]
 pub enum BlendOp {
     Add = vk::BLEND_OP_ADD,
@@ -242,6 +247,7 @@ pub enum BlendOp {
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(u32)]
 pub enum BlendFactor {
     Zero = vk::BLEND_FACTOR_ZERO,
@@ -264,3 +270,25 @@ pub enum BlendFactor {
     Src1Alpha = vk::BLEND_FACTOR_SRC1_ALPHA,
     OneMinusSrc1Alpha = vk::BLEND_FACTOR_ONE_MINUS_SRC1_ALPHA,
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use pipeline::blend::AttachmentBlend;
+    use pipeline::blend::AttachmentsBlend;
+    use pipeline::blend::Blend;
+    use pipeline::blend::LogicOp;
+
+    #[test]
+    fn serde_round_trip() {
+        assert_serde_round_trip!(AttachmentBlend::alpha_blending());
+        assert_serde_round_trip!(Blend::pass_through());
+        assert_serde_round_trip!(Blend {
+            logic_op: Some(LogicOp::Xor),
+            attachments: AttachmentsBlend::Individual(vec![
+                AttachmentBlend::pass_through(),
+                AttachmentBlend::alpha_blending(),
+            ]),
+            blend_constants: None,
+        });
+    }
+}
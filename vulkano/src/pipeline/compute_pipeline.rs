@@ -8,6 +8,7 @@
 // according to those terms.
 
 use std::error;
+use std::ffi::CStr;
 use std::fmt;
 use std::marker::PhantomData;
 use std::mem;
@@ -173,6 +174,14 @@ impl<Pl> ComputePipeline<Pl> {
         &self.inner.device
     }
 
+    /// Assigns a human-readable name to this pipeline for debugging purposes.
+    ///
+    /// Does nothing if the `VK_EXT_debug_utils` extension isn't enabled on the instance.
+    #[inline]
+    pub fn set_name(&self, name: &CStr) -> Result<(), OomError> {
+        self.inner.device.set_object_name(self, name)
+    }
+
     /// Returns the pipeline layout used in this compute pipeline.
     #[inline]
     pub fn layout(&self) -> &Pl {
@@ -253,6 +262,16 @@ unsafe impl<Pl> PipelineLayoutDesc for ComputePipeline<Pl>
         self.pipeline_layout.descriptor(set, binding)
     }
 
+    #[inline]
+    fn provided_set_layout(&self, set: usize) -> Option<Arc<UnsafeDescriptorSetLayout>> {
+        self.pipeline_layout.provided_set_layout(set)
+    }
+
+    #[inline]
+    fn is_runtime_array(&self, set: usize, binding: usize) -> bool {
+        self.pipeline_layout.is_runtime_array(set, binding)
+    }
+
     #[inline]
     fn num_push_constants_ranges(&self) -> usize {
         self.pipeline_layout.num_push_constants_ranges()
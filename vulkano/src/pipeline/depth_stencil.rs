@@ -25,7 +25,8 @@ use std::u32;
 use vk;
 
 /// Configuration of the depth and stencil tests.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct DepthStencil {
     /// Comparison to use between the depth value of each fragment and the depth value currently
     /// in the depth buffer.
@@ -81,7 +82,8 @@ impl Default for DepthStencil {
 }
 
 /// Configuration of a stencil test.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Stencil {
     /// The comparison to perform between the existing stencil value in the stencil buffer, and
     /// the reference value (given by `reference`).
@@ -160,6 +162,7 @@ impl Default for Stencil {
 
 /// Operation to perform after the depth and stencil tests.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(u32)]
 pub enum StencilOp {
     Keep = vk::STENCIL_OP_KEEP,
@@ -190,6 +193,7 @@ pub struct DynamicStencilValue {
 
 /// Allows you to ask the GPU to exclude fragments that are outside of a certain range.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum DepthBounds {
     /// The test is disabled. All fragments pass the depth bounds test.
     Disabled,
@@ -218,6 +222,7 @@ impl DepthBounds {
 ///
 /// Used for both depth testing and stencil testing.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(u32)]
 pub enum Compare {
     /// The test never passes.
@@ -237,3 +242,30 @@ pub enum Compare {
     /// The test always passes.
     Always = vk::COMPARE_OP_ALWAYS,
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use pipeline::depth_stencil::Compare;
+    use pipeline::depth_stencil::DepthBounds;
+    use pipeline::depth_stencil::DepthStencil;
+    use pipeline::depth_stencil::Stencil;
+    use pipeline::depth_stencil::StencilOp;
+
+    #[test]
+    fn serde_round_trip() {
+        assert_serde_round_trip!(DepthStencil::simple_depth_test());
+        assert_serde_round_trip!(DepthStencil {
+            depth_compare: Compare::GreaterOrEqual,
+            depth_write: false,
+            depth_bounds_test: DepthBounds::Fixed(0.25 .. 0.75),
+            stencil_front: Stencil {
+                compare: Compare::Equal,
+                pass_op: StencilOp::IncrementAndWrap,
+                compare_mask: None,
+                reference: Some(1),
+                .. Default::default()
+            },
+            stencil_back: Default::default(),
+        });
+    }
+}
@@ -7,6 +7,7 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+use std::ffi::CStr;
 use std::fmt;
 use std::hash::Hash;
 use std::hash::Hasher;
@@ -15,6 +16,7 @@ use std::ptr;
 use std::sync::Arc;
 use std::u32;
 
+use OomError;
 use SafeDeref;
 use VulkanObject;
 use buffer::BufferAccess;
@@ -115,6 +117,14 @@ impl<Mv, L, Rp> GraphicsPipeline<Mv, L, Rp> {
     pub fn device(&self) -> &Arc<Device> {
         &self.inner.device
     }
+
+    /// Assigns a human-readable name to this pipeline for debugging purposes.
+    ///
+    /// Does nothing if the `VK_EXT_debug_utils` extension isn't enabled on the instance.
+    #[inline]
+    pub fn set_name(&self, name: &CStr) -> Result<(), OomError> {
+        self.inner.device.set_object_name(self, name)
+    }
 }
 
 impl<Mv, L, Rp> GraphicsPipeline<Mv, L, Rp>
@@ -225,6 +235,16 @@ unsafe impl<Mv, L, Rp> PipelineLayoutDesc for GraphicsPipeline<Mv, L, Rp>
         self.layout.descriptor(set, binding)
     }
 
+    #[inline]
+    fn provided_set_layout(&self, set: usize) -> Option<Arc<UnsafeDescriptorSetLayout>> {
+        self.layout.provided_set_layout(set)
+    }
+
+    #[inline]
+    fn is_runtime_array(&self, set: usize, binding: usize) -> bool {
+        self.layout.is_runtime_array(set, binding)
+    }
+
     #[inline]
     fn num_push_constants_ranges(&self) -> usize {
         self.layout.num_push_constants_ranges()
@@ -16,7 +16,8 @@
 use vk;
 
 /// State of the rasterizer.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Rasterization {
     /// If true, then the depth value of the vertices will be clamped to [0.0 ; 1.0]. If false,
     /// fragments whose depth is outside of this range will be discarded.
@@ -60,7 +61,8 @@ impl Default for Rasterization {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum DepthBiasControl {
     Disabled,
     Dynamic,
@@ -77,7 +79,8 @@ impl DepthBiasControl {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct DepthBias {
     pub constant_factor: f32,
     /// Requires the `depth_bias_clamp` feature to be enabled.
@@ -91,7 +94,8 @@ pub struct DepthBias {
 /// clockwise or counter-clockwise correspond to the front and the back of each triangle. Then
 /// `cull_mode` lets you specify whether front faces should be discarded, back faces should be
 /// discarded, or none, or both.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(u32)]
 pub enum CullMode {
     /// No culling.
@@ -112,7 +116,8 @@ impl Default for CullMode {
 }
 
 /// Specifies which triangle orientation corresponds to the front or the triangle.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(u32)]
 pub enum FrontFace {
     /// Triangles whose vertices are oriented counter-clockwise on the screen will be considered
@@ -132,6 +137,7 @@ impl Default for FrontFace {
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(u32)]
 pub enum PolygonMode {
     Fill = vk::POLYGON_MODE_FILL,
@@ -145,3 +151,30 @@ impl Default for PolygonMode {
         PolygonMode::Fill
     }
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use pipeline::raster::CullMode;
+    use pipeline::raster::DepthBias;
+    use pipeline::raster::DepthBiasControl;
+    use pipeline::raster::FrontFace;
+    use pipeline::raster::PolygonMode;
+    use pipeline::raster::Rasterization;
+
+    #[test]
+    fn serde_round_trip() {
+        assert_serde_round_trip!(Rasterization::default());
+        assert_serde_round_trip!(Rasterization {
+            polygon_mode: PolygonMode::Line,
+            cull_mode: CullMode::Back,
+            front_face: FrontFace::Clockwise,
+            line_width: None,
+            depth_bias: DepthBiasControl::Static(DepthBias {
+                constant_factor: 1.5,
+                clamp: 0.0,
+                slope_factor: 2.0,
+            }),
+            .. Rasterization::default()
+        });
+    }
+}
@@ -509,6 +509,7 @@ impl Drop for Sampler {
 
 /// Describes how the color of each pixel should be determined.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(u32)]
 pub enum Filter {
     /// The four pixels whose center surround the requested coordinates are taken, then their
@@ -522,6 +523,7 @@ pub enum Filter {
 
 /// Describes which mipmap from the source to use.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(u32)]
 pub enum MipmapMode {
     /// Use the mipmap whose dimensions are the nearest to the dimensions of the destination.
@@ -535,6 +537,7 @@ pub enum MipmapMode {
 /// How the sampler should behave when it needs to access a pixel that is out of range of the
 /// texture.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum SamplerAddressMode {
     /// Repeat the texture. In other words, the pixel at coordinate `x + 1.0` is the same as the
     /// one at coordinate `x`.
@@ -584,6 +587,7 @@ impl SamplerAddressMode {
 /// How the sampler should behave when it needs to access a pixel that is out of range of the
 /// texture.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(u32)]
 pub enum UnnormalizedSamplerAddressMode {
     /// The coordinates are clamped to the valid range. Coordinates below 0 have the same value
@@ -626,6 +630,7 @@ impl UnnormalizedSamplerAddressMode {
 /// Using a border color restricts the sampler to either floating-point images or integer images.
 /// See the documentation of the `sampler` module for more info.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(u32)]
 pub enum BorderColor {
     /// The value `(0.0, 0.0, 0.0, 0.0)`. Can only be used with floating-point images.
@@ -953,4 +958,15 @@ mod tests {
             _ => panic!(),
         }
     }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_round_trip() {
+        assert_serde_round_trip!(sampler::Filter::Nearest);
+        assert_serde_round_trip!(sampler::MipmapMode::Linear);
+        assert_serde_round_trip!(sampler::SamplerAddressMode::Repeat);
+        assert_serde_round_trip!(sampler::SamplerAddressMode::ClampToBorder(
+            sampler::BorderColor::FloatOpaqueWhite));
+        assert_serde_round_trip!(sampler::UnnormalizedSamplerAddressMode::ClampToEdge);
+    }
 }
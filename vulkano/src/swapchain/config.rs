@@ -0,0 +1,422 @@
+// Copyright (c) 2020 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::error;
+use std::fmt;
+use std::sync::Arc;
+
+use device::Device;
+use format::Format;
+use image::ImageUsage;
+use image::swapchain::SwapchainImage;
+use instance::PhysicalDevice;
+use swapchain::Capabilities;
+use swapchain::CapabilitiesError;
+use swapchain::ColorSpace;
+use swapchain::CompositeAlpha;
+use swapchain::PresentMode;
+use swapchain::Surface;
+use swapchain::SurfaceTransform;
+use swapchain::Swapchain;
+use swapchain::SwapchainCreationError;
+use sync::SharingMode;
+
+/// Preferences used to choose the parameters of a swapchain.
+///
+/// Each list is ordered from the most preferred to the least preferred value. Call `resolve` with
+/// the capabilities of the surface in order to obtain parameters that are valid for
+/// `Swapchain::new`.
+///
+/// The choices are made as follows:
+///
+/// - The format and color space are the first entry of `formats` that the surface supports. If
+///   none is supported, the first format supported by the surface is used.
+/// - The present mode is the first entry of `present_modes` that the surface supports. If none
+///   is supported, `Fifo` is used as it is always supported.
+/// - The composite alpha is the first entry of `composite_alpha` that the surface supports. If
+///   none is supported, the first composite alpha supported by the surface is used.
+/// - The number of images is `latency + 1`, clamped to the limits of the surface.
+/// - The dimensions are the current extent of the surface. If the surface doesn't have one, the
+///   dimensions passed to `resolve` are used, clamped to the limits of the surface.
+/// - The transform is the current transform of the surface.
+///
+/// # Example
+///
+/// ```
+/// use vulkano::swapchain::SwapchainConfig;
+///
+/// # let capabilities: vulkano::swapchain::Capabilities = return;
+/// let resolved = SwapchainConfig::default().resolve(&capabilities, [1024, 768]).unwrap();
+/// if resolved.present_mode_preference.is_none() {
+///     println!("falling back to {:?}", resolved.present_mode);
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwapchainConfig {
+    /// Desired formats and color spaces.
+    pub formats: Vec<(Format, ColorSpace)>,
+
+    /// Desired present modes.
+    pub present_modes: Vec<PresentMode>,
+
+    /// Desired composite alpha modes.
+    pub composite_alpha: Vec<CompositeAlpha>,
+
+    /// Desired number of images that can be waiting to be presented while the application draws
+    /// on another one. A higher value gives smoother frame pacing at the cost of latency.
+    pub latency: u32,
+
+    /// How the images of the swapchain are going to be used. Must be supported by the surface.
+    pub usage: ImageUsage,
+}
+
+impl Default for SwapchainConfig {
+    /// Returns a configuration that prefers sRGB formats, `Mailbox` over `Fifo`, an opaque
+    /// surface, a latency of one image, and images used as color attachments.
+    #[inline]
+    fn default() -> SwapchainConfig {
+        SwapchainConfig {
+            formats: vec![
+                (Format::B8G8R8A8Srgb, ColorSpace::SrgbNonLinear),
+                (Format::R8G8B8A8Srgb, ColorSpace::SrgbNonLinear),
+            ],
+            present_modes: vec![PresentMode::Mailbox, PresentMode::Fifo],
+            composite_alpha: vec![CompositeAlpha::Opaque, CompositeAlpha::Inherit],
+            latency: 1,
+            usage: ImageUsage {
+                color_attachment: true,
+                ..ImageUsage::none()
+            },
+        }
+    }
+}
+
+impl SwapchainConfig {
+    /// Chooses the parameters of a swapchain from the given capabilities of a surface.
+    ///
+    /// `dimensions` are the dimensions of the window. They are only used if the surface doesn't
+    /// have a current extent.
+    pub fn resolve(&self, capabilities: &Capabilities, dimensions: [u32; 2])
+                   -> Result<ResolvedSwapchainConfig, SwapchainConfigError> {
+        let (format_preference, (format, color_space)) = match self.formats
+            .iter()
+            .position(|f| capabilities.supported_formats.contains(f))
+        {
+            Some(n) => (Some(n), self.formats[n]),
+            None => match capabilities.supported_formats.first() {
+                Some(&f) => (None, f),
+                None => return Err(SwapchainConfigError::NoSupportedFormat),
+            },
+        };
+
+        let (present_mode_preference, present_mode) = match self.present_modes
+            .iter()
+            .position(|&m| capabilities.present_modes.supports(m))
+        {
+            Some(n) => (Some(n), self.present_modes[n]),
+            None => (None, PresentMode::Fifo),
+        };
+
+        let (composite_alpha_preference, composite_alpha) = match self.composite_alpha
+            .iter()
+            .position(|&a| capabilities.supported_composite_alpha.supports(a))
+        {
+            Some(n) => (Some(n), self.composite_alpha[n]),
+            None => match capabilities.supported_composite_alpha.iter().next() {
+                Some(a) => (None, a),
+                None => return Err(SwapchainConfigError::NoSupportedCompositeAlpha),
+            },
+        };
+
+        let usage_bits = self.usage.to_usage_bits();
+        if usage_bits & capabilities.supported_usage_flags.to_usage_bits() != usage_bits {
+            return Err(SwapchainConfigError::UnsupportedUsage);
+        }
+
+        let mut num_images = self.latency.saturating_add(1).max(capabilities.min_image_count);
+        if let Some(max) = capabilities.max_image_count {
+            num_images = num_images.min(max);
+        }
+
+        let dimensions = capabilities.current_extent.unwrap_or_else(|| {
+            [
+                dimensions[0]
+                    .max(capabilities.min_image_extent[0])
+                    .min(capabilities.max_image_extent[0]),
+                dimensions[1]
+                    .max(capabilities.min_image_extent[1])
+                    .min(capabilities.max_image_extent[1]),
+            ]
+        });
+
+        Ok(ResolvedSwapchainConfig {
+               format,
+               color_space,
+               present_mode,
+               composite_alpha,
+               num_images,
+               dimensions,
+               transform: capabilities.current_transform,
+               usage: self.usage,
+               format_preference,
+               present_mode_preference,
+               composite_alpha_preference,
+           })
+    }
+
+    /// Queries the capabilities of `surface` when used by `device`, then calls `resolve`.
+    #[inline]
+    pub fn resolve_for_surface<W>(&self, surface: &Surface<W>, device: PhysicalDevice,
+                                  dimensions: [u32; 2])
+                                  -> Result<ResolvedSwapchainConfig, SwapchainConfigError> {
+        let capabilities = surface.capabilities(device)?;
+        self.resolve(&capabilities, dimensions)
+    }
+}
+
+/// Parameters of a swapchain chosen by `SwapchainConfig::resolve`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ResolvedSwapchainConfig {
+    /// Format of the images.
+    pub format: Format,
+    /// Color space of the images.
+    pub color_space: ColorSpace,
+    /// How to present images.
+    pub present_mode: PresentMode,
+    /// How to handle the alpha channel.
+    pub composite_alpha: CompositeAlpha,
+    /// Number of images of the swapchain.
+    pub num_images: u32,
+    /// Dimensions of the images.
+    pub dimensions: [u32; 2],
+    /// Transformation to use with the surface.
+    pub transform: SurfaceTransform,
+    /// How the images are going to be used.
+    pub usage: ImageUsage,
+
+    /// Index within `SwapchainConfig::formats` of the chosen format, or `None` if none of them
+    /// is supported and the first format of the surface has been chosen instead.
+    pub format_preference: Option<usize>,
+    /// Index within `SwapchainConfig::present_modes` of the chosen present mode, or `None` if
+    /// none of them is supported and `Fifo` has been chosen instead.
+    pub present_mode_preference: Option<usize>,
+    /// Index within `SwapchainConfig::composite_alpha` of the chosen composite alpha, or `None`
+    /// if none of them is supported and the first composite alpha of the surface has been chosen
+    /// instead.
+    pub composite_alpha_preference: Option<usize>,
+}
+
+impl ResolvedSwapchainConfig {
+    /// Creates a swapchain with these parameters.
+    ///
+    /// The images have one layer, and the parts of the images that aren't visible are clipped.
+    #[inline]
+    pub fn build_swapchain<W, S>(
+        &self, device: Arc<Device>, surface: Arc<Surface<W>>, sharing: S)
+        -> Result<(Arc<Swapchain<W>>, Vec<Arc<SwapchainImage<W>>>), SwapchainCreationError>
+        where S: Into<SharingMode>
+    {
+        Swapchain::new(device,
+                       surface,
+                       self.num_images,
+                       self.format,
+                       self.dimensions,
+                       1,
+                       self.usage,
+                       sharing,
+                       self.transform,
+                       self.composite_alpha,
+                       self.present_mode,
+                       true,
+                       self.color_space)
+    }
+}
+
+/// Error that can happen when resolving a `SwapchainConfig`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SwapchainConfigError {
+    /// Querying the capabilities of the surface failed.
+    CapabilitiesError(CapabilitiesError),
+    /// The surface doesn't support any format.
+    NoSupportedFormat,
+    /// The surface doesn't support any composite alpha mode.
+    NoSupportedCompositeAlpha,
+    /// The requested usage isn't supported by the surface.
+    UnsupportedUsage,
+}
+
+impl error::Error for SwapchainConfigError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            SwapchainConfigError::CapabilitiesError(_) => {
+                "querying the capabilities of the surface failed"
+            },
+            SwapchainConfigError::NoSupportedFormat => "the surface doesn't support any format",
+            SwapchainConfigError::NoSupportedCompositeAlpha => {
+                "the surface doesn't support any composite alpha mode"
+            },
+            SwapchainConfigError::UnsupportedUsage => {
+                "the requested usage isn't supported by the surface"
+            },
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&dyn error::Error> {
+        match *self {
+            SwapchainConfigError::CapabilitiesError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for SwapchainConfigError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<CapabilitiesError> for SwapchainConfigError {
+    #[inline]
+    fn from(err: CapabilitiesError) -> SwapchainConfigError {
+        SwapchainConfigError::CapabilitiesError(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use format::Format;
+    use image::ImageUsage;
+    use swapchain::Capabilities;
+    use swapchain::ColorSpace;
+    use swapchain::CompositeAlpha;
+    use swapchain::PresentMode;
+    use swapchain::SupportedCompositeAlpha;
+    use swapchain::SupportedPresentModes;
+    use swapchain::SupportedSurfaceTransforms;
+    use swapchain::SurfaceTransform;
+    use swapchain::SwapchainConfig;
+    use swapchain::SwapchainConfigError;
+
+    fn capabilities() -> Capabilities {
+        Capabilities {
+            min_image_count: 2,
+            max_image_count: Some(3),
+            current_extent: None,
+            min_image_extent: [1, 1],
+            max_image_extent: [4096, 4096],
+            max_image_array_layers: 1,
+            supported_transforms: SupportedSurfaceTransforms {
+                identity: true,
+                ..SupportedSurfaceTransforms::none()
+            },
+            current_transform: SurfaceTransform::Identity,
+            supported_composite_alpha: SupportedCompositeAlpha {
+                opaque: true,
+                ..SupportedCompositeAlpha::none()
+            },
+            supported_usage_flags: ImageUsage {
+                color_attachment: true,
+                transfer_destination: true,
+                ..ImageUsage::none()
+            },
+            supported_formats: vec![
+                (Format::B8G8R8A8Unorm, ColorSpace::SrgbNonLinear),
+                (Format::B8G8R8A8Srgb, ColorSpace::SrgbNonLinear),
+            ],
+            present_modes: SupportedPresentModes {
+                fifo: true,
+                mailbox: true,
+                ..SupportedPresentModes::none()
+            },
+        }
+    }
+
+    #[test]
+    fn preferred_values() {
+        let resolved = SwapchainConfig::default().resolve(&capabilities(), [800, 600]).unwrap();
+        assert_eq!(resolved.format, Format::B8G8R8A8Srgb);
+        assert_eq!(resolved.color_space, ColorSpace::SrgbNonLinear);
+        assert_eq!(resolved.format_preference, Some(0));
+        assert_eq!(resolved.present_mode, PresentMode::Mailbox);
+        assert_eq!(resolved.present_mode_preference, Some(0));
+        assert_eq!(resolved.composite_alpha, CompositeAlpha::Opaque);
+        assert_eq!(resolved.composite_alpha_preference, Some(0));
+        assert_eq!(resolved.num_images, 2);
+        assert_eq!(resolved.dimensions, [800, 600]);
+    }
+
+    #[test]
+    fn fallbacks() {
+        let mut caps = capabilities();
+        caps.supported_formats = vec![(Format::R16G16B16A16Sfloat, ColorSpace::ExtendedSrgbLinear)];
+        caps.present_modes = SupportedPresentModes {
+            fifo: true,
+            ..SupportedPresentModes::none()
+        };
+        caps.supported_composite_alpha = SupportedCompositeAlpha {
+            pre_multiplied: true,
+            ..SupportedCompositeAlpha::none()
+        };
+
+        let config = SwapchainConfig {
+            present_modes: vec![PresentMode::Immediate],
+            ..SwapchainConfig::default()
+        };
+        let resolved = config.resolve(&caps, [800, 600]).unwrap();
+        assert_eq!(resolved.format, Format::R16G16B16A16Sfloat);
+        assert_eq!(resolved.format_preference, None);
+        assert_eq!(resolved.present_mode, PresentMode::Fifo);
+        assert_eq!(resolved.present_mode_preference, None);
+        assert_eq!(resolved.composite_alpha, CompositeAlpha::PreMultiplied);
+        assert_eq!(resolved.composite_alpha_preference, None);
+    }
+
+    #[test]
+    fn clamped_image_count_and_dimensions() {
+        let mut caps = capabilities();
+        caps.max_image_extent = [640, 480];
+
+        let config = SwapchainConfig {
+            latency: 5,
+            ..SwapchainConfig::default()
+        };
+        let resolved = config.resolve(&caps, [800, 600]).unwrap();
+        assert_eq!(resolved.num_images, 3);
+        assert_eq!(resolved.dimensions, [640, 480]);
+
+        caps.current_extent = Some([320, 240]);
+        let resolved = config.resolve(&caps, [800, 600]).unwrap();
+        assert_eq!(resolved.dimensions, [320, 240]);
+    }
+
+    #[test]
+    fn errors() {
+        let mut caps = capabilities();
+        caps.supported_formats.clear();
+        match SwapchainConfig::default().resolve(&caps, [800, 600]) {
+            Err(SwapchainConfigError::NoSupportedFormat) => (),
+            _ => panic!(),
+        }
+
+        let config = SwapchainConfig {
+            usage: ImageUsage {
+                storage: true,
+                ..ImageUsage::none()
+            },
+            ..SwapchainConfig::default()
+        };
+        match config.resolve(&capabilities(), [800, 600]) {
+            Err(SwapchainConfigError::UnsupportedUsage) => (),
+            _ => panic!(),
+        }
+    }
+}
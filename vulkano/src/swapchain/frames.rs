@@ -0,0 +1,591 @@
+// Copyright (c) 2020 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::error;
+use std::fmt;
+use std::sync::Arc;
+
+use device::DeviceOwned;
+use device::Queue;
+use image::swapchain::SwapchainImage;
+use swapchain::AcquireError;
+use swapchain::CapabilitiesError;
+use swapchain::Swapchain;
+use swapchain::SwapchainAcquireFuture;
+use swapchain::SwapchainCreationError;
+use swapchain::PresentFuture;
+use swapchain::acquire_next_image;
+use sync::FenceSignalFuture;
+use sync::FlushError;
+use sync::GpuFuture;
+
+/// Manages a rendering loop with several frames in flight on a swapchain.
+///
+/// Each frame in flight has a slot that holds the fence signaled at the end of the frame, and
+/// user-defined resources of type `R`, for example a `CpuBufferPool` for the uniform buffers of
+/// that frame. Before a slot is reused, `begin_frame` waits for the frame that previously used it
+/// to be finished, which bounds the number of frames the CPU can be ahead of the GPU.
+///
+/// The swapchain is recreated automatically when it is out of date or suboptimal, or after a call
+/// to `set_dimensions`.
+///
+/// Note that the futures of the different frames are not joined together. Resources that are
+/// written by the GPU should therefore belong to the resources of a frame instead of being
+/// shared between frames.
+///
+/// # Example
+///
+/// ```no_run
+/// use vulkano::buffer::CpuBufferPool;
+/// use vulkano::command_buffer::AutoCommandBufferBuilder;
+/// use vulkano::swapchain::FrameError;
+/// use vulkano::swapchain::FramesInFlight;
+/// use vulkano::swapchain::SwapchainCreationError;
+/// use vulkano::sync::GpuFuture;
+///
+/// # let device: std::sync::Arc<vulkano::device::Device> = return;
+/// # let queue: std::sync::Arc<vulkano::device::Queue> = return;
+/// # let swapchain: std::sync::Arc<vulkano::swapchain::Swapchain<()>> = return;
+/// # let images = return;
+/// let mut frames = FramesInFlight::new(swapchain, images, queue.clone(), 2, |_| {
+///     CpuBufferPool::<[f32; 4]>::uniform_buffer(device.clone())
+/// }).unwrap();
+///
+/// loop {
+///     let frame = match frames.begin_frame() {
+///         Ok(frame) => frame,
+///         // The window is being resized.
+///         Err(FrameError::SwapchainCreationError(SwapchainCreationError::UnsupportedDimensions)) => {
+///             continue;
+///         },
+///         Err(err) => panic!("{:?}", err),
+///     };
+///
+///     if frame.swapchain_recreated() {
+///         // Recreate the framebuffers from `frames.images()`.
+///     }
+///
+///     let _uniforms = frames.resources().next([0.0; 4]).unwrap();
+///     let command_buffer = AutoCommandBufferBuilder::primary_one_time_submit(device.clone(),
+///                                                                            queue.family())
+///         .unwrap()
+///         // Draw to `frame.image()`.
+///         .build()
+///         .unwrap();
+///
+///     let future = frame.into_future().then_execute(queue.clone(), command_buffer).unwrap();
+///     frames.end_frame(future).unwrap();
+/// }
+/// ```
+pub struct FramesInFlight<W, R = ()> {
+    swapchain: Arc<Swapchain<W>>,
+    images: Vec<Arc<SwapchainImage<W>>>,
+
+    // Queue used to present the images.
+    queue: Arc<Queue>,
+
+    // One slot per frame in flight.
+    slots: Vec<FrameSlot<W, R>>,
+
+    // Index within `slots` of the current frame.
+    current: usize,
+
+    // Index of the image acquired by `begin_frame`, until `end_frame` is called.
+    acquired: Option<usize>,
+
+    // True if the swapchain must be recreated before acquiring the next image.
+    recreate: bool,
+
+    // True if the swapchain has been recreated since the last frame was returned to the user.
+    recreated: bool,
+
+    // Dimensions to pass when recreating the swapchain, if set by the user.
+    dimensions: Option<[u32; 2]>,
+}
+
+struct FrameSlot<W, R> {
+    // Future of the last frame that used this slot. `None` if the slot hasn't been used yet or
+    // if presenting the frame failed.
+    future: Option<FenceSignalFuture<PresentFuture<Box<dyn GpuFuture>, W>>>,
+
+    // Resources of the user.
+    resources: R,
+}
+
+impl<W, R> FramesInFlight<W, R> {
+    /// Builds a new `FramesInFlight` that renders to `swapchain` and presents on `queue`.
+    ///
+    /// `resources` is called once for each of the `frames_in_flight` slots in order to build the
+    /// per-frame resources.
+    ///
+    /// The application can only hold `images.len() - min_image_count + 1` images of the swapchain
+    /// at the same time, where `min_image_count` comes from the capabilities of the surface.
+    /// Having more frames in flight than that would only make `begin_frame` block while acquiring
+    /// the next image.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `frames_in_flight` is 0.
+    /// - Panics if `frames_in_flight` is greater than `images.len() - min_image_count + 1`.
+    ///
+    pub fn new<F>(swapchain: Arc<Swapchain<W>>, images: Vec<Arc<SwapchainImage<W>>>,
+                  queue: Arc<Queue>, frames_in_flight: usize, mut resources: F)
+                  -> Result<FramesInFlight<W, R>, CapabilitiesError>
+        where F: FnMut(usize) -> R
+    {
+        assert!(frames_in_flight >= 1);
+
+        let capabilities = swapchain
+            .surface()
+            .capabilities(swapchain.device().physical_device())?;
+        let max_frames_in_flight = (images.len() + 1)
+            .saturating_sub(capabilities.min_image_count as usize);
+        assert!(frames_in_flight <= max_frames_in_flight,
+                "frames_in_flight ({}) must not be greater than the number of images ({}) minus \
+                 the minimum number of images of the surface ({}) plus one",
+                frames_in_flight,
+                images.len(),
+                capabilities.min_image_count);
+
+        let slots = (0 .. frames_in_flight)
+            .map(|n| {
+                     FrameSlot {
+                         future: None,
+                         resources: resources(n),
+                     }
+                 })
+            .collect();
+
+        Ok(FramesInFlight {
+               swapchain,
+               images,
+               queue,
+               slots,
+               current: 0,
+               acquired: None,
+               recreate: false,
+               recreated: false,
+               dimensions: None,
+           })
+    }
+
+    /// Returns the current swapchain.
+    #[inline]
+    pub fn swapchain(&self) -> &Arc<Swapchain<W>> {
+        &self.swapchain
+    }
+
+    /// Returns the images of the current swapchain.
+    #[inline]
+    pub fn images(&self) -> &[Arc<SwapchainImage<W>>] {
+        &self.images
+    }
+
+    /// Returns the queue used to present the images.
+    #[inline]
+    pub fn queue(&self) -> &Arc<Queue> {
+        &self.queue
+    }
+
+    /// Returns the number of frames in flight.
+    #[inline]
+    pub fn frames_in_flight(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Returns the index of the slot of the current frame, between 0 and `frames_in_flight()`.
+    #[inline]
+    pub fn current_slot(&self) -> usize {
+        self.current
+    }
+
+    /// Returns the resources of the current frame.
+    #[inline]
+    pub fn resources(&self) -> &R {
+        &self.slots[self.current].resources
+    }
+
+    /// Returns the resources of the current frame.
+    #[inline]
+    pub fn resources_mut(&mut self) -> &mut R {
+        &mut self.slots[self.current].resources
+    }
+
+    /// Requests the swapchain to be recreated with the given dimensions before the next frame.
+    ///
+    /// Call this when the window is resized. Without a call to this method, the swapchain is
+    /// recreated with the current dimensions of the surface.
+    #[inline]
+    pub fn set_dimensions(&mut self, dimensions: [u32; 2]) {
+        self.dimensions = Some(dimensions);
+        self.recreate = true;
+    }
+
+    /// Requests the swapchain to be recreated before the next frame.
+    #[inline]
+    pub fn recreate_swapchain(&mut self) {
+        self.recreate = true;
+    }
+
+    /// Starts a new frame.
+    ///
+    /// Waits for the frame that previously used the slot of the new frame to be finished,
+    /// recreates the swapchain if necessary, and acquires the next image of the swapchain.
+    ///
+    /// If recreating the swapchain fails, for example with `UnsupportedDimensions` while the
+    /// window is being resized, it is attempted again at the next call.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the previous frame hasn't been ended with `end_frame`.
+    ///
+    pub fn begin_frame(&mut self) -> Result<Frame<W>, FrameError> {
+        assert!(self.acquired.is_none(), "begin_frame called twice without calling end_frame");
+
+        if let Some(future) = self.slots[self.current].future.take() {
+            future.wait(None)?;
+        }
+
+        for slot in self.slots.iter_mut() {
+            if let Some(ref mut future) = slot.future {
+                future.cleanup_finished();
+            }
+        }
+
+        loop {
+            if self.recreate {
+                let (swapchain, images) = match self.dimensions {
+                    Some(dimensions) => self.swapchain.recreate_with_dimensions(dimensions)?,
+                    None => self.swapchain.recreate()?,
+                };
+
+                self.swapchain = swapchain;
+                self.images = images;
+                self.recreate = false;
+                self.recreated = true;
+            }
+
+            match acquire_next_image(self.swapchain.clone(), None) {
+                Ok((image_num, suboptimal, future)) => {
+                    // The image is still usable, but the swapchain will be recreated for the
+                    // next frame.
+                    if suboptimal {
+                        self.recreate = true;
+                    }
+
+                    self.acquired = Some(image_num);
+
+                    let swapchain_recreated = self.recreated;
+                    self.recreated = false;
+
+                    return Ok(Frame {
+                                  image_num,
+                                  image: self.images[image_num].clone(),
+                                  future,
+                                  swapchain_recreated,
+                              });
+                },
+                Err(AcquireError::OutOfDate) => {
+                    self.recreate = true;
+                },
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    /// Ends the current frame by presenting its image after `future`, and signals the fence of
+    /// the slot of the frame.
+    ///
+    /// `future` must have been built from the future returned by `Frame::into_future`. If the
+    /// swapchain turns out to be out of date, it is recreated at the next frame and no error is
+    /// returned.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if no frame has been started with `begin_frame`.
+    ///
+    pub fn end_frame<F>(&mut self, future: F) -> Result<(), FlushError>
+        where F: GpuFuture + 'static
+    {
+        let image_num = self.acquired
+            .take()
+            .expect("end_frame called without calling begin_frame");
+
+        let future = (Box::new(future) as Box<dyn GpuFuture>)
+            .then_swapchain_present(self.queue.clone(), self.swapchain.clone(), image_num)
+            .then_signal_fence_and_flush();
+
+        let current = self.current;
+        self.current = (self.current + 1) % self.slots.len();
+
+        match future {
+            Ok(future) => {
+                self.slots[current].future = Some(future);
+                Ok(())
+            },
+            Err(FlushError::OutOfDate) => {
+                self.recreate = true;
+                Ok(())
+            },
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// A frame started with `FramesInFlight::begin_frame`.
+pub struct Frame<W> {
+    image_num: usize,
+    image: Arc<SwapchainImage<W>>,
+    future: SwapchainAcquireFuture<W>,
+    swapchain_recreated: bool,
+}
+
+impl<W> Frame<W> {
+    /// Returns the index of the acquired image within `FramesInFlight::images()`.
+    #[inline]
+    pub fn image_num(&self) -> usize {
+        self.image_num
+    }
+
+    /// Returns the acquired image.
+    #[inline]
+    pub fn image(&self) -> &Arc<SwapchainImage<W>> {
+        &self.image
+    }
+
+    /// Returns true if the swapchain has been recreated since the previous frame. If that's the
+    /// case, everything that depends on the swapchain images, such as framebuffers, must be
+    /// recreated.
+    #[inline]
+    pub fn swapchain_recreated(&self) -> bool {
+        self.swapchain_recreated
+    }
+
+    /// Returns the future that represents the moment when the image is acquired. Build the
+    /// operations of the frame after this future, then pass them to `FramesInFlight::end_frame`.
+    #[inline]
+    pub fn into_future(self) -> SwapchainAcquireFuture<W> {
+        self.future
+    }
+}
+
+/// Error that can happen when starting a frame.
+#[derive(Debug)]
+pub enum FrameError {
+    /// Acquiring the next image failed.
+    AcquireError(AcquireError),
+    /// Recreating the swapchain failed.
+    SwapchainCreationError(SwapchainCreationError),
+    /// Waiting for a previous frame failed.
+    FlushError(FlushError),
+}
+
+impl error::Error for FrameError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            FrameError::AcquireError(_) => "acquiring the next image failed",
+            FrameError::SwapchainCreationError(_) => "recreating the swapchain failed",
+            FrameError::FlushError(_) => "waiting for a previous frame failed",
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&dyn error::Error> {
+        match *self {
+            FrameError::AcquireError(ref err) => Some(err),
+            FrameError::SwapchainCreationError(ref err) => Some(err),
+            FrameError::FlushError(ref err) => Some(err),
+        }
+    }
+}
+
+impl fmt::Display for FrameError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<AcquireError> for FrameError {
+    #[inline]
+    fn from(err: AcquireError) -> FrameError {
+        FrameError::AcquireError(err)
+    }
+}
+
+impl From<SwapchainCreationError> for FrameError {
+    #[inline]
+    fn from(err: SwapchainCreationError) -> FrameError {
+        FrameError::SwapchainCreationError(err)
+    }
+}
+
+impl From<FlushError> for FrameError {
+    #[inline]
+    fn from(err: FlushError) -> FrameError {
+        FrameError::FlushError(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use device::Device;
+    use device::DeviceExtensions;
+    use features::Features;
+    use format::Format;
+    use image::ImageUsage;
+    use instance::Instance;
+    use instance::InstanceExtensions;
+    use instance::PhysicalDevice;
+    use instance::loader::FunctionPointers;
+    use mock::MockIcd;
+    use mock::Recorder;
+    use swapchain::ColorSpace;
+    use swapchain::CompositeAlpha;
+    use swapchain::FramesInFlight;
+    use swapchain::PresentMode;
+    use swapchain::Surface;
+    use swapchain::SurfaceTransform;
+    use swapchain::Swapchain;
+
+    // Builds a `FramesInFlight` on a headless surface of the mock, whose surfaces require at least
+    // two images. The resources of each slot are the index of the slot.
+    fn frames(num_images: u32, frames_in_flight: usize)
+              -> (FramesInFlight<(), usize>, Arc<Recorder>) {
+        let loader = FunctionPointers::new(Box::new(MockIcd) as Box<_>);
+        let extensions = InstanceExtensions {
+            khr_surface: true,
+            ext_headless_surface: true,
+            ..InstanceExtensions::none()
+        };
+        let instance = Instance::with_loader(loader, None, &extensions, None).unwrap();
+        let recorder = Recorder::of(&instance).unwrap();
+        let surface = Surface::headless(instance.clone(), ()).unwrap();
+
+        let physical = PhysicalDevice::enumerate(&instance).next().unwrap();
+        let queue_family = physical.queue_families().next().unwrap();
+        let extensions = DeviceExtensions {
+            khr_swapchain: true,
+            ..DeviceExtensions::none()
+        };
+        let (device, mut queues) = Device::new(physical,
+                                               &Features::none(),
+                                               &extensions,
+                                               [(queue_family, 0.5)].iter().cloned())
+            .unwrap();
+        let queue = queues.next().unwrap();
+
+        let (swapchain, images) = Swapchain::new(device,
+                                                 surface,
+                                                 num_images,
+                                                 Format::B8G8R8A8Unorm,
+                                                 [64, 64],
+                                                 1,
+                                                 ImageUsage {
+                                                     color_attachment: true,
+                                                     ..ImageUsage::none()
+                                                 },
+                                                 &queue,
+                                                 SurfaceTransform::Identity,
+                                                 CompositeAlpha::Opaque,
+                                                 PresentMode::Fifo,
+                                                 true,
+                                                 ColorSpace::SrgbNonLinear)
+            .unwrap();
+
+        let frames = FramesInFlight::new(swapchain, images, queue, frames_in_flight, |n| n)
+            .unwrap();
+        (frames, recorder)
+    }
+
+    #[test]
+    fn begin_end() {
+        let (mut frames, recorder) = frames(3, 2);
+        assert_eq!(frames.frames_in_flight(), 2);
+
+        for n in 0 .. 4 {
+            let frame = frames.begin_frame().unwrap();
+            assert_eq!(frame.image_num(), n % 3);
+            assert!(!frame.swapchain_recreated());
+            assert_eq!(frames.current_slot(), n % 2);
+            assert_eq!(*frames.resources(), n % 2);
+
+            let future = frame.into_future();
+            frames.end_frame(future).unwrap();
+        }
+
+        let calls = recorder.calls();
+        assert_eq!(calls.iter().filter(|&&c| c == "vkAcquireNextImageKHR").count(), 4);
+        assert_eq!(calls.iter().filter(|&&c| c == "vkQueuePresentKHR").count(), 4);
+        assert_eq!(recorder.errors(), vec![]);
+    }
+
+    #[test]
+    fn out_of_date_on_acquire() {
+        let (mut frames, recorder) = frames(2, 1);
+        let swapchain = frames.swapchain().clone();
+
+        let frame = frames.begin_frame().unwrap();
+        frames.end_frame(frame.into_future()).unwrap();
+
+        recorder.make_swapchains_out_of_date();
+
+        let frame = frames.begin_frame().unwrap();
+        assert!(frame.swapchain_recreated());
+        assert!(!Arc::ptr_eq(&swapchain, frames.swapchain()));
+        assert_eq!(frames.swapchain().dimensions(), [64, 64]);
+        frames.end_frame(frame.into_future()).unwrap();
+
+        let frame = frames.begin_frame().unwrap();
+        assert!(!frame.swapchain_recreated());
+        frames.end_frame(frame.into_future()).unwrap();
+    }
+
+    #[test]
+    fn out_of_date_on_present() {
+        let (mut frames, recorder) = frames(2, 1);
+        let swapchain = frames.swapchain().clone();
+
+        let frame = frames.begin_frame().unwrap();
+        recorder.make_swapchains_out_of_date();
+        // The error is swallowed and the swapchain is recreated at the next frame.
+        frames.end_frame(frame.into_future()).unwrap();
+
+        let frame = frames.begin_frame().unwrap();
+        assert!(frame.swapchain_recreated());
+        assert!(!Arc::ptr_eq(&swapchain, frames.swapchain()));
+        frames.end_frame(frame.into_future()).unwrap();
+    }
+
+    #[test]
+    fn set_dimensions() {
+        let (mut frames, _) = frames(3, 2);
+
+        let frame = frames.begin_frame().unwrap();
+        frames.end_frame(frame.into_future()).unwrap();
+
+        frames.set_dimensions([32, 16]);
+
+        let frame = frames.begin_frame().unwrap();
+        assert!(frame.swapchain_recreated());
+        assert_eq!(frames.swapchain().dimensions(), [32, 16]);
+        assert_eq!(frames.images().len(), 3);
+        frames.end_frame(frame.into_future()).unwrap();
+    }
+
+    #[test]
+    fn too_many_frames_in_flight() {
+        assert_should_panic!("frames_in_flight (3) must not be greater than", {
+            frames(3, 3);
+        });
+    }
+}
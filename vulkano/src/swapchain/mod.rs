@@ -216,6 +216,10 @@
 //! Creating a swapchain not only returns the swapchain object, but also all the images that belong
 //! to it.
 //!
+//! Instead of choosing each parameter by hand, you can describe your preferences with a
+//! `SwapchainConfig` and resolve it against the capabilities of the surface. The resulting
+//! `ResolvedSwapchainConfig` can then create the swapchain with `build_swapchain`.
+//!
 //! ## Acquiring and presenting images
 //!
 //! Once you created a swapchain and retrieved all the images that belong to it (see previous
@@ -311,6 +315,12 @@ pub use self::capabilities::SupportedPresentModesIter;
 pub use self::capabilities::SupportedSurfaceTransforms;
 pub use self::capabilities::SupportedSurfaceTransformsIter;
 pub use self::capabilities::SurfaceTransform;
+pub use self::config::ResolvedSwapchainConfig;
+pub use self::config::SwapchainConfig;
+pub use self::config::SwapchainConfigError;
+pub use self::frames::Frame;
+pub use self::frames::FrameError;
+pub use self::frames::FramesInFlight;
 pub use self::present_region::PresentRegion;
 pub use self::present_region::RectangleLayer;
 pub use self::surface::CapabilitiesError;
@@ -328,7 +338,9 @@ pub use self::swapchain::present;
 pub use self::swapchain::present_incremental;
 
 mod capabilities;
+mod config;
 pub mod display;
+mod frames;
 mod present_region;
 mod surface;
 mod swapchain;
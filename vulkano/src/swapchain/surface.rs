@@ -449,6 +449,44 @@ impl<W> Surface<W> {
                     }))
     }
 
+    /// Creates a headless `Surface`, which isn't associated to any window system.
+    ///
+    /// Images presented to a headless surface are discarded. This makes it possible to exercise
+    /// the swapchain code paths, for example in tests, on machines that don't have a display.
+    ///
+    /// The current extent of a headless surface is undefined, so the dimensions must always be
+    /// passed explicitly when creating a swapchain for it.
+    pub fn headless(instance: Arc<Instance>, win: W)
+                    -> Result<Arc<Surface<W>>, SurfaceCreationError> {
+        let vk = instance.pointers();
+
+        if !instance.loaded_extensions().ext_headless_surface {
+            return Err(SurfaceCreationError::MissingExtension { name: "VK_EXT_headless_surface" });
+        }
+
+        let surface = unsafe {
+            let infos = vk::HeadlessSurfaceCreateInfoEXT {
+                sType: vk::STRUCTURE_TYPE_HEADLESS_SURFACE_CREATE_INFO_EXT,
+                pNext: ptr::null(),
+                flags: 0, // reserved
+            };
+
+            let mut output = MaybeUninit::uninit();
+            check_errors(vk.CreateHeadlessSurfaceEXT(instance.internal_object(),
+                                                     &infos,
+                                                     ptr::null(),
+                                                     output.as_mut_ptr()))?;
+            output.assume_init()
+        };
+
+        Ok(Arc::new(Surface {
+                        window: win,
+                        instance: instance.clone(),
+                        surface: surface,
+                        has_swapchain: AtomicBool::new(false),
+                    }))
+    }
+
     /// Returns true if the given queue family can draw on this surface.
     // FIXME: vulkano doesn't check this for the moment!
     pub fn is_supported(&self, queue: QueueFamily) -> Result<bool, CapabilitiesError> {
@@ -614,6 +652,10 @@ pub enum SurfaceCreationError {
         /// Name of the missing extension.
         name: &'static str,
     },
+
+    /// The window handle is for a platform that vulkano can't create surfaces for, such as the
+    /// web.
+    UnsupportedWindowHandle,
 }
 
 impl error::Error for SurfaceCreationError {
@@ -623,6 +665,8 @@ impl error::Error for SurfaceCreationError {
             SurfaceCreationError::OomError(_) => "not enough memory available",
             SurfaceCreationError::MissingExtension { .. } =>
                 "the extension required for this function was not enabled",
+            SurfaceCreationError::UnsupportedWindowHandle =>
+                "surfaces can't be created for this kind of window handle",
         }
     }
 
@@ -759,6 +803,15 @@ mod tests {
         }
     }
 
+    #[test]
+    fn ext_headless_surface_ext_missing() {
+        let instance = instance!();
+        match Surface::headless(instance, ()) {
+            Err(SurfaceCreationError::MissingExtension { .. }) => (),
+            _ => panic!(),
+        }
+    }
+
     #[test]
     fn khr_android_surface_ext_missing() {
         let instance = instance!();
@@ -276,6 +276,9 @@ impl <W> Swapchain<W> {
     }
 
     /// Recreates the swapchain with current dimensions of corresponding surface.
+    ///
+    /// If the surface doesn't have a current extent, as is the case for headless surfaces, the
+    /// dimensions of this swapchain are kept.
     pub fn recreate(&self)
         -> Result<(Arc<Swapchain<W>>, Vec<Arc<SwapchainImage<W>>>), SwapchainCreationError> {
         Swapchain::new_inner(self.device.clone(),
@@ -354,7 +357,13 @@ impl <W> Swapchain<W> {
             }
             dimensions
         } else {
-            capabilities.current_extent.unwrap()
+            // Surfaces such as headless surfaces don't have a current extent, in which case the
+            // dimensions of the old swapchain are kept.
+            match (capabilities.current_extent, old_swapchain) {
+                (Some(extent), _) => extent,
+                (None, Some(old_swapchain)) => old_swapchain.dimensions,
+                (None, None) => unreachable!(),
+            }
         };
         if layers < 1 || layers > capabilities.max_image_array_layers {
             return Err(SwapchainCreationError::UnsupportedArrayLayers);
@@ -486,13 +495,16 @@ impl <W> Swapchain<W> {
                     cubemap_compatible: false,
                 };
 
-                let img = UnsafeImage::from_raw(device.clone(),
-                                                image,
-                                                usage.to_usage_bits(),
-                                                format,
-                                                dims,
-                                                1,
-                                                1);
+                let mut img = UnsafeImage::from_raw(device.clone(),
+                                                    image,
+                                                    usage.to_usage_bits(),
+                                                    format,
+                                                    dims,
+                                                    1,
+                                                    1);
+                if let SharingMode::Concurrent(_) = sharing {
+                    img.set_concurrent_sharing();
+                }
 
                 ImageEntry {
                     image: img,
@@ -1252,3 +1264,103 @@ pub unsafe fn acquire_next_image_raw<W>(swapchain: &Swapchain<W>, timeout: Optio
 
     Ok(AcquiredImage { id, suboptimal })
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use device::Device;
+    use device::DeviceExtensions;
+    use features::Features;
+    use format::Format;
+    use image::ImageUsage;
+    use instance::Instance;
+    use instance::InstanceExtensions;
+    use instance::PhysicalDevice;
+    use swapchain::ColorSpace;
+    use swapchain::PresentMode;
+    use swapchain::Surface;
+    use swapchain::Swapchain;
+    use swapchain::acquire_next_image;
+    use sync::GpuFuture;
+
+    #[test]
+    fn headless_acquire_present_recreate() {
+        let extensions = InstanceExtensions {
+            khr_surface: true,
+            ext_headless_surface: true,
+            .. InstanceExtensions::none()
+        };
+        let instance = match Instance::new(None, &extensions, None) {
+            Ok(i) => i,
+            Err(_) => return,
+        };
+        let surface = Surface::headless(instance.clone(), ()).unwrap();
+
+        let physical = match PhysicalDevice::enumerate(&instance).next() {
+            Some(p) => p,
+            None => return,
+        };
+        let queue_family = match physical
+            .queue_families()
+            .find(|q| q.supports_graphics() && surface.is_supported(*q).unwrap_or(false))
+        {
+            Some(q) => q,
+            None => return,
+        };
+        let extensions = DeviceExtensions {
+            khr_swapchain: true,
+            .. DeviceExtensions::none()
+        };
+        let (device, mut queues) = match Device::new(physical,
+                                                     &Features::none(),
+                                                     &extensions,
+                                                     [(queue_family, 0.5)].iter().cloned()) {
+            Ok(r) => r,
+            Err(_) => return,
+        };
+        let queue = queues.next().unwrap();
+
+        let caps = surface.capabilities(physical).unwrap();
+        let (format, color_space) = caps
+            .supported_formats
+            .iter()
+            .cloned()
+            .find(|&(_, c)| c == ColorSpace::SrgbNonLinear)
+            .unwrap_or((Format::B8G8R8A8Unorm, ColorSpace::SrgbNonLinear));
+        let dimensions = [caps.min_image_extent[0].max(64), caps.min_image_extent[1].max(64)];
+
+        let (swapchain, images) = Swapchain::new(device.clone(),
+                                                 surface.clone(),
+                                                 caps.min_image_count,
+                                                 format,
+                                                 dimensions,
+                                                 1,
+                                                 ImageUsage {
+                                                     color_attachment: true,
+                                                     .. ImageUsage::none()
+                                                 },
+                                                 &queue,
+                                                 caps.current_transform,
+                                                 caps.supported_composite_alpha.iter().next().unwrap(),
+                                                 PresentMode::Fifo,
+                                                 true,
+                                                 color_space)
+            .unwrap();
+        assert_eq!(images.len(), swapchain.num_images() as usize);
+
+        let (image_num, _, acquire_future) =
+            acquire_next_image(swapchain.clone(), Some(Duration::from_secs(5))).unwrap();
+        assert!(image_num < images.len());
+
+        acquire_future
+            .then_swapchain_present(queue.clone(), swapchain.clone(), image_num)
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+
+        let (recreated, _) = swapchain.recreate().unwrap();
+        assert_eq!(recreated.dimensions(), dimensions);
+    }
+}
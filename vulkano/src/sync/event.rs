@@ -194,14 +194,14 @@ mod tests {
 
     #[test]
     fn event_create() {
-        let (device, _) = gfx_dev_and_queue!();
+        let (device, _) = mock_dev_and_queue!();
         let event = Event::alloc(device).unwrap();
         assert!(!event.signaled().unwrap());
     }
 
     #[test]
     fn event_set() {
-        let (device, _) = gfx_dev_and_queue!();
+        let (device, _) = mock_dev_and_queue!();
         let mut event = Event::alloc(device).unwrap();
         assert!(!event.signaled().unwrap());
 
@@ -211,7 +211,7 @@ mod tests {
 
     #[test]
     fn event_reset() {
-        let (device, _) = gfx_dev_and_queue!();
+        let (device, _) = mock_dev_and_queue!();
 
         let mut event = Event::alloc(device).unwrap();
         event.set();
@@ -223,7 +223,7 @@ mod tests {
 
     #[test]
     fn event_pool() {
-        let (device, _) = gfx_dev_and_queue!();
+        let (device, _) = mock_dev_and_queue!();
 
         assert_eq!(device.event_pool().lock().unwrap().len(), 0);
         let event1_internal_obj = {
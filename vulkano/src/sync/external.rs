@@ -0,0 +1,319 @@
+// Copyright (c) 2020 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Handle types and errors used to share semaphores and fences with external APIs or processes.
+
+use std::error;
+use std::fmt;
+
+use Error;
+use OomError;
+use vk;
+
+/// Describes the handle types that can be used to share a semaphore.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ExternalSemaphoreHandleType {
+    pub opaque_fd: bool,
+    pub opaque_win32: bool,
+    pub opaque_win32_kmt: bool,
+    pub d3d12_fence: bool,
+    pub sync_fd: bool,
+}
+
+impl ExternalSemaphoreHandleType {
+    /// Builds an `ExternalSemaphoreHandleType` with all values set to false.
+    #[inline]
+    pub fn none() -> ExternalSemaphoreHandleType {
+        ExternalSemaphoreHandleType {
+            opaque_fd: false,
+            opaque_win32: false,
+            opaque_win32_kmt: false,
+            d3d12_fence: false,
+            sync_fd: false,
+        }
+    }
+
+    /// Builds an `ExternalSemaphoreHandleType` with `opaque_fd` set to true and the rest to false.
+    #[inline]
+    pub fn posix() -> ExternalSemaphoreHandleType {
+        ExternalSemaphoreHandleType {
+            opaque_fd: true,
+            ..ExternalSemaphoreHandleType::none()
+        }
+    }
+
+    /// Returns true if no handle type is enabled.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        *self == ExternalSemaphoreHandleType::none()
+    }
+
+    /// Returns true if all the handle types of `other` are also enabled in `self`.
+    #[inline]
+    pub fn superset_of(&self, other: &ExternalSemaphoreHandleType) -> bool {
+        (self.to_bits() & other.to_bits()) == other.to_bits()
+    }
+
+    #[inline]
+    pub(crate) fn to_bits(&self) -> vk::ExternalSemaphoreHandleTypeFlagsKHR {
+        let mut result = 0;
+        if self.opaque_fd {
+            result |= vk::EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_FD_BIT_KHR;
+        }
+        if self.opaque_win32 {
+            result |= vk::EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_WIN32_BIT_KHR;
+        }
+        if self.opaque_win32_kmt {
+            result |= vk::EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_WIN32_KMT_BIT_KHR;
+        }
+        if self.d3d12_fence {
+            result |= vk::EXTERNAL_SEMAPHORE_HANDLE_TYPE_D3D12_FENCE_BIT_KHR;
+        }
+        if self.sync_fd {
+            result |= vk::EXTERNAL_SEMAPHORE_HANDLE_TYPE_SYNC_FD_BIT_KHR;
+        }
+        result
+    }
+
+    #[inline]
+    pub(crate) fn from_bits(val: vk::ExternalSemaphoreHandleTypeFlagsKHR)
+                            -> ExternalSemaphoreHandleType {
+        ExternalSemaphoreHandleType {
+            opaque_fd: (val & vk::EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_FD_BIT_KHR) != 0,
+            opaque_win32: (val & vk::EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_WIN32_BIT_KHR) != 0,
+            opaque_win32_kmt:
+                (val & vk::EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_WIN32_KMT_BIT_KHR) != 0,
+            d3d12_fence: (val & vk::EXTERNAL_SEMAPHORE_HANDLE_TYPE_D3D12_FENCE_BIT_KHR) != 0,
+            sync_fd: (val & vk::EXTERNAL_SEMAPHORE_HANDLE_TYPE_SYNC_FD_BIT_KHR) != 0,
+        }
+    }
+
+    // Returns true if exactly one handle type that is backed by a file descriptor is enabled.
+    #[inline]
+    pub(crate) fn is_single_fd(&self) -> bool {
+        *self == ExternalSemaphoreHandleType::posix() ||
+            *self == ExternalSemaphoreHandleType {
+                sync_fd: true,
+                ..ExternalSemaphoreHandleType::none()
+            }
+    }
+}
+
+/// Capabilities of the implementation regarding one external semaphore handle type.
+///
+/// Can be obtained with `PhysicalDevice::external_semaphore_properties`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ExternalSemaphoreProperties {
+    /// True if semaphores can be exported with this handle type.
+    pub exportable: bool,
+    /// True if semaphores can be imported with this handle type.
+    pub importable: bool,
+    /// Handle types that can be exported from a semaphore that was imported with this handle
+    /// type.
+    pub export_from_imported_handle_types: ExternalSemaphoreHandleType,
+    /// Handle types that can be specified at the same time as this one when creating a
+    /// semaphore.
+    pub compatible_handle_types: ExternalSemaphoreHandleType,
+}
+
+impl ExternalSemaphoreProperties {
+    #[inline]
+    pub(crate) fn from_vulkan(props: &vk::ExternalSemaphorePropertiesKHR)
+                              -> ExternalSemaphoreProperties {
+        let features = props.externalSemaphoreFeatures;
+
+        ExternalSemaphoreProperties {
+            exportable: (features & vk::EXTERNAL_SEMAPHORE_FEATURE_EXPORTABLE_BIT_KHR) != 0,
+            importable: (features & vk::EXTERNAL_SEMAPHORE_FEATURE_IMPORTABLE_BIT_KHR) != 0,
+            export_from_imported_handle_types:
+                ExternalSemaphoreHandleType::from_bits(props.exportFromImportedHandleTypes),
+            compatible_handle_types:
+                ExternalSemaphoreHandleType::from_bits(props.compatibleHandleTypes),
+        }
+    }
+}
+
+/// Describes the handle types that can be used to share a fence.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ExternalFenceHandleType {
+    pub opaque_fd: bool,
+    pub opaque_win32: bool,
+    pub opaque_win32_kmt: bool,
+    pub sync_fd: bool,
+}
+
+impl ExternalFenceHandleType {
+    /// Builds an `ExternalFenceHandleType` with all values set to false.
+    #[inline]
+    pub fn none() -> ExternalFenceHandleType {
+        ExternalFenceHandleType {
+            opaque_fd: false,
+            opaque_win32: false,
+            opaque_win32_kmt: false,
+            sync_fd: false,
+        }
+    }
+
+    /// Builds an `ExternalFenceHandleType` with `opaque_fd` set to true and the rest to false.
+    #[inline]
+    pub fn posix() -> ExternalFenceHandleType {
+        ExternalFenceHandleType {
+            opaque_fd: true,
+            ..ExternalFenceHandleType::none()
+        }
+    }
+
+    /// Returns true if no handle type is enabled.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        *self == ExternalFenceHandleType::none()
+    }
+
+    /// Returns true if all the handle types of `other` are also enabled in `self`.
+    #[inline]
+    pub fn superset_of(&self, other: &ExternalFenceHandleType) -> bool {
+        (self.to_bits() & other.to_bits()) == other.to_bits()
+    }
+
+    #[inline]
+    pub(crate) fn to_bits(&self) -> vk::ExternalFenceHandleTypeFlagsKHR {
+        let mut result = 0;
+        if self.opaque_fd {
+            result |= vk::EXTERNAL_FENCE_HANDLE_TYPE_OPAQUE_FD_BIT_KHR;
+        }
+        if self.opaque_win32 {
+            result |= vk::EXTERNAL_FENCE_HANDLE_TYPE_OPAQUE_WIN32_BIT_KHR;
+        }
+        if self.opaque_win32_kmt {
+            result |= vk::EXTERNAL_FENCE_HANDLE_TYPE_OPAQUE_WIN32_KMT_BIT_KHR;
+        }
+        if self.sync_fd {
+            result |= vk::EXTERNAL_FENCE_HANDLE_TYPE_SYNC_FD_BIT_KHR;
+        }
+        result
+    }
+
+    #[inline]
+    pub(crate) fn from_bits(val: vk::ExternalFenceHandleTypeFlagsKHR) -> ExternalFenceHandleType {
+        ExternalFenceHandleType {
+            opaque_fd: (val & vk::EXTERNAL_FENCE_HANDLE_TYPE_OPAQUE_FD_BIT_KHR) != 0,
+            opaque_win32: (val & vk::EXTERNAL_FENCE_HANDLE_TYPE_OPAQUE_WIN32_BIT_KHR) != 0,
+            opaque_win32_kmt: (val & vk::EXTERNAL_FENCE_HANDLE_TYPE_OPAQUE_WIN32_KMT_BIT_KHR) != 0,
+            sync_fd: (val & vk::EXTERNAL_FENCE_HANDLE_TYPE_SYNC_FD_BIT_KHR) != 0,
+        }
+    }
+
+    // Returns true if exactly one handle type that is backed by a file descriptor is enabled.
+    #[inline]
+    pub(crate) fn is_single_fd(&self) -> bool {
+        *self == ExternalFenceHandleType::posix() ||
+            *self == ExternalFenceHandleType {
+                sync_fd: true,
+                ..ExternalFenceHandleType::none()
+            }
+    }
+}
+
+/// Capabilities of the implementation regarding one external fence handle type.
+///
+/// Can be obtained with `PhysicalDevice::external_fence_properties`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ExternalFenceProperties {
+    /// True if fences can be exported with this handle type.
+    pub exportable: bool,
+    /// True if fences can be imported with this handle type.
+    pub importable: bool,
+    /// Handle types that can be exported from a fence that was imported with this handle type.
+    pub export_from_imported_handle_types: ExternalFenceHandleType,
+    /// Handle types that can be specified at the same time as this one when creating a fence.
+    pub compatible_handle_types: ExternalFenceHandleType,
+}
+
+impl ExternalFenceProperties {
+    #[inline]
+    pub(crate) fn from_vulkan(props: &vk::ExternalFencePropertiesKHR) -> ExternalFenceProperties {
+        let features = props.externalFenceFeatures;
+
+        ExternalFenceProperties {
+            exportable: (features & vk::EXTERNAL_FENCE_FEATURE_EXPORTABLE_BIT_KHR) != 0,
+            importable: (features & vk::EXTERNAL_FENCE_FEATURE_IMPORTABLE_BIT_KHR) != 0,
+            export_from_imported_handle_types:
+                ExternalFenceHandleType::from_bits(props.exportFromImportedHandleTypes),
+            compatible_handle_types:
+                ExternalFenceHandleType::from_bits(props.compatibleHandleTypes),
+        }
+    }
+}
+
+/// Error that can happen when creating, exporting or importing an external semaphore or fence.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ExternalSyncError {
+    /// Not enough memory available.
+    OomError(OomError),
+    /// The maximum number of file descriptors or handles has been exceeded.
+    TooManyObjects,
+    /// A device extension required for this operation is not enabled.
+    MissingExtension { name: &'static str },
+    /// The object wasn't created as exportable with the requested handle type.
+    HandleTypeNotEnabled,
+    /// The handle that was passed for importing is not valid.
+    InvalidExternalHandle,
+}
+
+impl error::Error for ExternalSyncError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            ExternalSyncError::OomError(_) => "not enough memory available",
+            ExternalSyncError::TooManyObjects =>
+                "the maximum number of file descriptors or handles has been exceeded",
+            ExternalSyncError::MissingExtension { .. } =>
+                "a device extension required for this operation is not enabled",
+            ExternalSyncError::HandleTypeNotEnabled =>
+                "the object wasn't created as exportable with the requested handle type",
+            ExternalSyncError::InvalidExternalHandle =>
+                "the handle that was passed for importing is not valid",
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&dyn error::Error> {
+        match *self {
+            ExternalSyncError::OomError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ExternalSyncError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<OomError> for ExternalSyncError {
+    #[inline]
+    fn from(err: OomError) -> ExternalSyncError {
+        ExternalSyncError::OomError(err)
+    }
+}
+
+impl From<Error> for ExternalSyncError {
+    #[inline]
+    fn from(err: Error) -> ExternalSyncError {
+        match err {
+            e @ Error::OutOfHostMemory |
+            e @ Error::OutOfDeviceMemory => ExternalSyncError::OomError(e.into()),
+            Error::TooManyObjects => ExternalSyncError::TooManyObjects,
+            Error::InvalidExternalHandle => ExternalSyncError::InvalidExternalHandle,
+            _ => panic!("unexpected error: {:?}", err),
+        }
+    }
+}
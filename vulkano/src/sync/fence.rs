@@ -10,7 +10,11 @@
 use smallvec::SmallVec;
 use std::error;
 use std::fmt;
+#[cfg(unix)]
+use std::fs::File;
 use std::mem::MaybeUninit;
+#[cfg(unix)]
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd};
 use std::ptr;
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
@@ -25,6 +29,9 @@ use VulkanObject;
 use check_errors;
 use device::Device;
 use device::DeviceOwned;
+use sync::ExternalFenceHandleType;
+use sync::ExternalSyncError;
+use version::Version;
 use vk;
 
 /// A fence is used to know when a command buffer submission has finished its execution.
@@ -48,6 +55,9 @@ pub struct Fence<D = Arc<Device>>
     // Indicates whether this fence was taken from the fence pool.
     // If true, will be put back into fence pool on drop.
     must_put_in_pool: bool,
+
+    // Handle types this fence can be exported as.
+    export_handle_types: ExternalFenceHandleType,
 }
 
 impl<D> Fence<D>
@@ -73,11 +83,12 @@ impl<D> Fence<D>
                        device: device,
                        signaled: AtomicBool::new(false),
                        must_put_in_pool: true,
+                       export_handle_types: ExternalFenceHandleType::none(),
                    })
             },
             None => {
                 // Pool is empty, alloc new fence
-                Fence::alloc_impl(device, false, true)
+                Ok(Fence::alloc_impl(device, false, true, ExternalFenceHandleType::none())?)
             },
         }
     }
@@ -85,20 +96,60 @@ impl<D> Fence<D>
     /// Builds a new fence.
     #[inline]
     pub fn alloc(device: D) -> Result<Fence<D>, OomError> {
-        Fence::alloc_impl(device, false, false)
+        Ok(Fence::alloc_impl(device, false, false, ExternalFenceHandleType::none())?)
     }
 
     /// Builds a new fence in signaled state.
     #[inline]
     pub fn alloc_signaled(device: D) -> Result<Fence<D>, OomError> {
-        Fence::alloc_impl(device, true, false)
+        Ok(Fence::alloc_impl(device, true, false, ExternalFenceHandleType::none())?)
     }
 
-    fn alloc_impl(device: D, signaled: bool, must_put_in_pool: bool) -> Result<Fence<D>, OomError> {
+    /// Builds a new fence that can later be exported with the given handle types.
+    ///
+    /// The `VK_KHR_external_fence` extension must be enabled on the device unless it uses Vulkan
+    /// 1.1, plus `VK_KHR_external_fence_fd` for handle types that are backed by a file descriptor.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `handle_types` is empty.
+    ///
+    pub fn alloc_exportable(device: D, signaled: bool, handle_types: ExternalFenceHandleType)
+                            -> Result<Fence<D>, ExternalSyncError> {
+        assert!(!handle_types.is_empty());
+
+        if device.api_version() < Version::V1_1 && !device.loaded_extensions().khr_external_fence {
+            return Err(ExternalSyncError::MissingExtension { name: "VK_KHR_external_fence" });
+        }
+        if (handle_types.opaque_fd || handle_types.sync_fd) &&
+            !device.loaded_extensions().khr_external_fence_fd
+        {
+            return Err(ExternalSyncError::MissingExtension { name: "VK_KHR_external_fence_fd" });
+        }
+
+        Ok(Fence::alloc_impl(device, signaled, false, handle_types)?)
+    }
+
+    fn alloc_impl(device: D, signaled: bool, must_put_in_pool: bool,
+                  export_handle_types: ExternalFenceHandleType)
+                  -> Result<Fence<D>, Error> {
         let fence = unsafe {
+            let export_infos = if !export_handle_types.is_empty() {
+                Some(vk::ExportFenceCreateInfoKHR {
+                         sType: vk::STRUCTURE_TYPE_EXPORT_FENCE_CREATE_INFO_KHR,
+                         pNext: ptr::null(),
+                         handleTypes: export_handle_types.to_bits(),
+                     })
+            } else {
+                None
+            };
+
             let infos = vk::FenceCreateInfo {
                 sType: vk::STRUCTURE_TYPE_FENCE_CREATE_INFO,
-                pNext: ptr::null(),
+                pNext: export_infos
+                    .as_ref()
+                    .map(|i| i as *const vk::ExportFenceCreateInfoKHR)
+                    .unwrap_or(ptr::null()) as *const _,
                 flags: if signaled {
                     vk::FENCE_CREATE_SIGNALED_BIT
                 } else {
@@ -120,9 +171,121 @@ impl<D> Fence<D>
                device: device,
                signaled: AtomicBool::new(signaled),
                must_put_in_pool: must_put_in_pool,
+               export_handle_types: export_handle_types,
            })
     }
 
+    /// Returns the handle types this fence can be exported as.
+    #[inline]
+    pub fn export_handle_types(&self) -> ExternalFenceHandleType {
+        self.export_handle_types
+    }
+
+    /// Exports the fence as a POSIX file descriptor.
+    ///
+    /// `handle_type` must be either `opaque_fd` or `sync_fd`, and the fence must have been
+    /// created as exportable with that handle type. The returned file is owned by the caller.
+    ///
+    /// > **Note**: Exporting a `sync_fd` resets the fence once the payload has been exported.
+    /// > The fence must be signaled, or have a signal operation pending, when doing so.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `handle_type` doesn't have exactly one of `opaque_fd` or `sync_fd` set.
+    ///
+    #[cfg(unix)]
+    pub fn export_fd(&self, handle_type: ExternalFenceHandleType)
+                     -> Result<File, ExternalSyncError> {
+        assert!(handle_type.is_single_fd(),
+                "only `opaque_fd` or `sync_fd` can be exported as a file descriptor");
+
+        if !self.device.loaded_extensions().khr_external_fence_fd {
+            return Err(ExternalSyncError::MissingExtension { name: "VK_KHR_external_fence_fd" });
+        }
+        if !self.export_handle_types.superset_of(&handle_type) {
+            return Err(ExternalSyncError::HandleTypeNotEnabled);
+        }
+
+        unsafe {
+            let vk = self.device.pointers();
+
+            let infos = vk::FenceGetFdInfoKHR {
+                sType: vk::STRUCTURE_TYPE_FENCE_GET_FD_INFO_KHR,
+                pNext: ptr::null(),
+                fence: self.fence,
+                handleType: handle_type.to_bits(),
+            };
+
+            let mut output = MaybeUninit::uninit();
+            check_errors(vk.GetFenceFdKHR(self.device.internal_object(),
+                                          &infos,
+                                          output.as_mut_ptr()))?;
+
+            if handle_type.sync_fd {
+                self.signaled.store(false, Ordering::Relaxed);
+            }
+
+            Ok(File::from_raw_fd(output.assume_init()))
+        }
+    }
+
+    /// Imports a payload that was exported as a POSIX file descriptor into this fence.
+    ///
+    /// If `temporary` is true, the imported payload only replaces the current one until the
+    /// fence is reset. A `sync_fd` can only be imported temporarily.
+    ///
+    /// On success, the file descriptor is owned by the Vulkan implementation. The fence is then
+    /// destroyed when dropped, instead of being put back in the pool it may come from.
+    ///
+    /// # Safety
+    ///
+    /// - The fence must not be in use by a pending queue operation.
+    /// - For `opaque_fd`, the payload must come from a device with the same device and driver
+    ///   UUIDs as this one.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `handle_type` doesn't have exactly one of `opaque_fd` or `sync_fd` set.
+    /// - Panics if `handle_type` is `sync_fd` and `temporary` is false.
+    ///
+    #[cfg(unix)]
+    pub unsafe fn import_fd(&mut self, handle_type: ExternalFenceHandleType, temporary: bool,
+                            file: File)
+                            -> Result<(), ExternalSyncError> {
+        assert!(handle_type.is_single_fd(),
+                "only `opaque_fd` or `sync_fd` can be imported from a file descriptor");
+        assert!(temporary || !handle_type.sync_fd,
+                "a `sync_fd` can only be imported temporarily");
+
+        if !self.device.loaded_extensions().khr_external_fence_fd {
+            return Err(ExternalSyncError::MissingExtension { name: "VK_KHR_external_fence_fd" });
+        }
+
+        let vk = self.device.pointers();
+
+        let infos = vk::ImportFenceFdInfoKHR {
+            sType: vk::STRUCTURE_TYPE_IMPORT_FENCE_FD_INFO_KHR,
+            pNext: ptr::null(),
+            fence: self.fence,
+            flags: if temporary {
+                vk::FENCE_IMPORT_TEMPORARY_BIT_KHR
+            } else {
+                0
+            },
+            handleType: handle_type.to_bits(),
+            fd: file.as_raw_fd(),
+        };
+
+        check_errors(vk.ImportFenceFdKHR(self.device.internal_object(), &infos))?;
+        // The implementation now owns the file descriptor.
+        let _ = file.into_raw_fd();
+        // The payload of the fence must not be shared with the next user of the pool.
+        self.must_put_in_pool = false;
+        // The imported payload may or may not be signaled.
+        self.signaled.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+
     /// Returns true if the fence is signaled.
     #[inline]
     pub fn ready(&self) -> Result<bool, OomError> {
@@ -185,6 +348,12 @@ impl<D> Fence<D>
         }
     }
 
+    // Returns the device of the fence, whatever `D` is.
+    #[inline]
+    pub(crate) fn raw_device(&self) -> &Device {
+        &self.device
+    }
+
     /// Waits for multiple fences at once.
     ///
     /// # Panic
@@ -386,12 +555,15 @@ impl From<Error> for FenceWaitError {
 #[cfg(test)]
 mod tests {
     use VulkanObject;
+    use mock::Recorder;
     use std::time::Duration;
+    use sync::ExternalFenceHandleType;
+    use sync::ExternalSyncError;
     use sync::Fence;
 
     #[test]
     fn fence_create() {
-        let (device, _) = gfx_dev_and_queue!();
+        let (device, _) = mock_dev_and_queue!();
 
         let fence = Fence::alloc(device.clone()).unwrap();
         assert!(!fence.ready().unwrap());
@@ -399,7 +571,7 @@ mod tests {
 
     #[test]
     fn fence_create_signaled() {
-        let (device, _) = gfx_dev_and_queue!();
+        let (device, _) = mock_dev_and_queue!();
 
         let fence = Fence::alloc_signaled(device.clone()).unwrap();
         assert!(fence.ready().unwrap());
@@ -407,7 +579,7 @@ mod tests {
 
     #[test]
     fn fence_signaled_wait() {
-        let (device, _) = gfx_dev_and_queue!();
+        let (device, _) = mock_dev_and_queue!();
 
         let fence = Fence::alloc_signaled(device.clone()).unwrap();
         fence.wait(Some(Duration::new(0, 10))).unwrap();
@@ -415,7 +587,7 @@ mod tests {
 
     #[test]
     fn fence_reset() {
-        let (device, _) = gfx_dev_and_queue!();
+        let (device, _) = mock_dev_and_queue!();
 
         let mut fence = Fence::alloc_signaled(device.clone()).unwrap();
         fence.reset().unwrap();
@@ -424,8 +596,8 @@ mod tests {
 
     #[test]
     fn multiwait_different_devices() {
-        let (device1, _) = gfx_dev_and_queue!();
-        let (device2, _) = gfx_dev_and_queue!();
+        let (device1, _) = mock_dev_and_queue!();
+        let (device2, _) = mock_dev_and_queue!();
 
         assert_should_panic!("Tried to wait for multiple fences that didn't belong \
                               to the same device",
@@ -442,8 +614,8 @@ mod tests {
     fn multireset_different_devices() {
         use std::iter::once;
 
-        let (device1, _) = gfx_dev_and_queue!();
-        let (device2, _) = gfx_dev_and_queue!();
+        let (device1, _) = mock_dev_and_queue!();
+        let (device2, _) = mock_dev_and_queue!();
 
         assert_should_panic!("Tried to reset multiple fences that didn't belong \
                               to the same device",
@@ -458,7 +630,7 @@ mod tests {
 
     #[test]
     fn fence_pool() {
-        let (device, _) = gfx_dev_and_queue!();
+        let (device, _) = mock_dev_and_queue!();
 
         assert_eq!(device.fence_pool().lock().unwrap().len(), 0);
         let fence1_internal_obj = {
@@ -471,5 +643,63 @@ mod tests {
         let fence2 = Fence::from_pool(device.clone()).unwrap();
         assert_eq!(device.fence_pool().lock().unwrap().len(), 0);
         assert_eq!(fence2.internal_object(), fence1_internal_obj);
+
+        let calls = Recorder::of(device.instance()).unwrap().calls();
+        assert_eq!(calls.iter().filter(|&&c| c == "vkCreateFence").count(), 1);
+        assert!(!calls.contains(&"vkDestroyFence"));
+    }
+
+    #[test]
+    fn fence_exportable_requires_extension() {
+        let (device, _) = mock_dev_and_queue!();
+
+        match Fence::alloc_exportable(device.clone(), false, ExternalFenceHandleType::posix()) {
+            Err(ExternalSyncError::MissingExtension { .. }) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn imported_fence_not_put_in_pool() {
+        let (device, _) = mock_dev_and_queue!(extensions: [khr_external_fence,
+                                                           khr_external_fence_fd]);
+        let handle_type = ExternalFenceHandleType::posix();
+        let exported = Fence::alloc_exportable(device.clone(), false, handle_type).unwrap();
+        let file = exported.export_fd(handle_type).unwrap();
+
+        let mut fence = Fence::from_pool(device.clone()).unwrap();
+        unsafe {
+            fence.import_fd(handle_type, false, file).unwrap();
+        }
+        drop(fence);
+
+        assert_eq!(device.fence_pool().lock().unwrap().len(), 0);
+        let calls = Recorder::of(device.instance()).unwrap().calls();
+        assert!(calls.contains(&"vkDestroyFence"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn failed_import_closes_fd() {
+        use std::fs::File;
+        use std::io::Read;
+        use std::os::unix::io::{FromRawFd, IntoRawFd};
+        use std::os::unix::net::UnixStream;
+
+        let (device, _) = mock_dev_and_queue!(extensions: [khr_external_fence,
+                                                           khr_external_fence_fd]);
+        let mut fence = Fence::alloc(device.clone()).unwrap();
+
+        // The other end of the socket reaches the end of the stream once the fd is closed.
+        let (ours, mut theirs) = UnixStream::pair().unwrap();
+        let file = unsafe { File::from_raw_fd(ours.into_raw_fd()) };
+        match unsafe { fence.import_fd(ExternalFenceHandleType::posix(), false, file) } {
+            Err(ExternalSyncError::InvalidExternalHandle) => (),
+            _ => panic!(),
+        }
+
+        theirs.set_nonblocking(true).unwrap();
+        assert_eq!(theirs.read(&mut [0; 1]).unwrap(), 0);
     }
 }
@@ -0,0 +1,430 @@
+// Copyright (c) 2020 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Condvar;
+use std::sync::Mutex;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::task::Context;
+use std::task::Poll;
+use std::task::Waker;
+use std::thread;
+use std::time::Duration;
+
+use smallvec::SmallVec;
+
+use SafeDeref;
+use VulkanObject;
+use device::Device;
+use device::DeviceOwned;
+use sync::Fence;
+use sync::FenceSignalFuture;
+use sync::FenceWaitError;
+use sync::FlushError;
+use sync::GpuFuture;
+use vk;
+
+/// Adapter that makes it possible to `.await` the signalling of a fence from an async runtime.
+///
+/// Obtained with `FenceSignalFuture::into_async_wait()` or `Fence::into_async_wait()`.
+///
+/// The fence is waited upon by a background thread shared by all the `FenceAsyncWait` objects,
+/// which wakes up the task once the fence is signaled. That thread takes ownership of the fence
+/// or of the `FenceSignalFuture`, which keeps alive the resources used by the submission until
+/// the GPU has finished processing it. Dropping a `FenceAsyncWait` before completion is therefore
+/// safe and never blocks. A fence that was passed with `Fence::into_async_wait()` is released as
+/// soon as its `FenceAsyncWait` is dropped, as it may never be signaled.
+///
+/// ```
+/// use vulkano::sync::GpuFuture;
+///
+/// # let future: vulkano::sync::NowFuture = return;
+/// let wait = future.then_signal_fence_and_flush().unwrap().into_async_wait();
+/// // `wait` can now be awaited from an async function.
+/// ```
+#[must_use = "futures do nothing unless polled"]
+pub struct FenceAsyncWait {
+    shared: Arc<Shared>,
+}
+
+impl Future for FenceAsyncWait {
+    type Output = Result<(), FlushError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), FlushError>> {
+        let mut state = self.shared.state.lock().unwrap();
+
+        if let Some(ref result) = state.result {
+            return Poll::Ready(result.clone());
+        }
+
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl Drop for FenceAsyncWait {
+    #[inline]
+    fn drop(&mut self) {
+        self.shared.dropped.store(true, Ordering::Release);
+    }
+}
+
+impl<F> FenceSignalFuture<F>
+    where F: GpuFuture + Send + 'static
+{
+    /// Turns this future into a `std::future::Future` that is ready once the fence is signaled.
+    ///
+    /// The submission is flushed if that wasn't done yet. See `FenceAsyncWait` for more
+    /// information.
+    #[inline]
+    pub fn into_async_wait(self) -> FenceAsyncWait {
+        spawn(Box::new(self))
+    }
+}
+
+impl<D> Fence<D>
+    where D: SafeDeref<Target = Device> + Send + 'static
+{
+    /// Turns this fence into a `std::future::Future` that is ready once the fence is signaled.
+    ///
+    /// See `FenceAsyncWait` for more information.
+    #[inline]
+    pub fn into_async_wait(self) -> FenceAsyncWait {
+        spawn(Box::new(self))
+    }
+}
+
+// Something whose completion can be waited upon by the background thread.
+trait WaitTarget: Send {
+    // Blocks until the operation is finished or the timeout expires. Returns `Ok(false)` if the
+    // timeout expired. The operation must not be dropped or abandoned in case of a timeout.
+    fn wait_timeout(&self, timeout: Duration) -> Result<bool, FlushError>;
+
+    // Returns the device and the fence to block on while the operation is pending. Only called
+    // after `wait_timeout` returned `Ok(false)`.
+    fn raw_fence(&self) -> Option<(&Device, vk::Fence)>;
+
+    // Returns true if the operation has been submitted by vulkano, in which case it must be kept
+    // alive until it is finished even if nobody waits for it anymore.
+    fn is_submitted(&self) -> bool;
+}
+
+impl<F> WaitTarget for FenceSignalFuture<F>
+    where F: GpuFuture + Send
+{
+    #[inline]
+    fn wait_timeout(&self, timeout: Duration) -> Result<bool, FlushError> {
+        FenceSignalFuture::wait_timeout(self, timeout)
+    }
+
+    #[inline]
+    fn raw_fence(&self) -> Option<(&Device, vk::Fence)> {
+        FenceSignalFuture::raw_fence(self).map(|fence| (&**self.device(), fence))
+    }
+
+    #[inline]
+    fn is_submitted(&self) -> bool {
+        FenceSignalFuture::raw_fence(self).is_some()
+    }
+}
+
+impl<D> WaitTarget for Fence<D>
+    where D: SafeDeref<Target = Device> + Send
+{
+    #[inline]
+    fn wait_timeout(&self, timeout: Duration) -> Result<bool, FlushError> {
+        match self.wait(Some(timeout)) {
+            Ok(()) => Ok(true),
+            Err(FenceWaitError::Timeout) => Ok(false),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    #[inline]
+    fn raw_fence(&self) -> Option<(&Device, vk::Fence)> {
+        Some((self.raw_device(), self.internal_object()))
+    }
+
+    #[inline]
+    fn is_submitted(&self) -> bool {
+        false
+    }
+}
+
+// Hands over `target` to the background thread and returns the corresponding future.
+fn spawn(target: Box<dyn WaitTarget>) -> FenceAsyncWait {
+    let shared = Arc::new(Shared {
+                              state: Mutex::new(SharedState {
+                                                    result: None,
+                                                    waker: None,
+                                                }),
+                              dropped: AtomicBool::new(false),
+                          });
+
+    WAITER.new_entries.lock().unwrap().push(Entry {
+                                                target,
+                                                shared: shared.clone(),
+                                            });
+    WAITER.condvar.notify_one();
+
+    FenceAsyncWait { shared }
+}
+
+// State shared between a `FenceAsyncWait` and the background thread.
+struct Shared {
+    state: Mutex<SharedState>,
+    // True once the `FenceAsyncWait` has been dropped.
+    dropped: AtomicBool,
+}
+
+struct SharedState {
+    // Result of the wait, once finished.
+    result: Option<Result<(), FlushError>>,
+    // Waker of the task that last polled the future.
+    waker: Option<Waker>,
+}
+
+struct Entry {
+    target: Box<dyn WaitTarget>,
+    shared: Arc<Shared>,
+}
+
+struct Waiter {
+    // Entries that haven't been picked up by the background thread yet.
+    new_entries: Mutex<Vec<Entry>>,
+    // Notified whenever an entry is added to `new_entries`.
+    condvar: Condvar,
+}
+
+lazy_static! {
+    static ref WAITER: Arc<Waiter> = {
+        let waiter = Arc::new(Waiter {
+                                  new_entries: Mutex::new(Vec::new()),
+                                  condvar: Condvar::new(),
+                              });
+
+        let thread_waiter = waiter.clone();
+        thread::Builder::new()
+            .name("vulkano-fence-waiter".to_owned())
+            .spawn(move || run(&thread_waiter))
+            .expect("failed to spawn the fence waiter thread");
+
+        waiter
+    };
+}
+
+// How long the background thread blocks on the pending fences before picking up the entries that
+// were added in the meantime.
+const WAIT_TIMEOUT: Duration = Duration::from_millis(10);
+
+// Main loop of the background thread.
+fn run(waiter: &Waiter) {
+    let mut entries: Vec<Entry> = Vec::new();
+
+    loop {
+        {
+            let mut new_entries = waiter.new_entries.lock().unwrap();
+            while entries.is_empty() && new_entries.is_empty() {
+                new_entries = waiter.condvar.wait(new_entries).unwrap();
+            }
+            entries.extend(new_entries.drain(..));
+        }
+
+        // Checking the entries without blocking also flushes the ones that haven't been flushed
+        // yet. Dropping the finished entries destroys the resources that were kept alive. The
+        // entries that nobody waits for are only kept if a submission still uses them.
+        entries.retain(|entry| {
+            let result = match entry.target.wait_timeout(Duration::from_secs(0)) {
+                Ok(false) => {
+                    return !entry.shared.dropped.load(Ordering::Acquire) ||
+                        entry.target.is_submitted();
+                },
+                Ok(true) => Ok(()),
+                Err(err) => Err(err),
+            };
+
+            let mut state = entry.shared.state.lock().unwrap();
+            state.result = Some(result);
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+            false
+        });
+
+        wait_any(&entries);
+    }
+}
+
+// Blocks until any of the fences of `entries` is signaled or until `WAIT_TIMEOUT` has elapsed.
+fn wait_any(entries: &[Entry]) {
+    // `vkWaitForFences` only accepts fences of a single device, so the timeout is split between
+    // the devices.
+    let mut groups: SmallVec<[(&Device, SmallVec<[vk::Fence; 8]>); 1]> = SmallVec::new();
+    for entry in entries {
+        if let Some((device, fence)) = entry.target.raw_fence() {
+            match groups.iter_mut().find(|group| group.0 as *const Device == device) {
+                Some(group) => group.1.push(fence),
+                None => groups.push((device, Some(fence).into_iter().collect())),
+            }
+        }
+    }
+
+    if groups.is_empty() {
+        return;
+    }
+
+    let timeout = WAIT_TIMEOUT / groups.len() as u32;
+    let timeout_ns = timeout
+        .as_secs()
+        .saturating_mul(1_000_000_000)
+        .saturating_add(timeout.subsec_nanos() as u64);
+
+    for (device, fences) in groups {
+        unsafe {
+            let vk = device.pointers();
+            // Errors such as a lost device are reported by `wait_timeout` on the next iteration.
+            vk.WaitForFences(device.internal_object(),
+                             fences.len() as u32,
+                             fences.as_ptr(),
+                             vk::FALSE,
+                             timeout_ns);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::task::Context;
+    use std::task::Poll;
+    use std::task::Wake;
+    use std::thread;
+    use std::thread::Thread;
+
+    use std::time::Duration;
+    use std::time::Instant;
+
+    use command_buffer::AutoCommandBufferBuilder;
+    use mock::Recorder;
+    use sync::Fence;
+    use sync::GpuFuture;
+    use sync::now;
+
+    // Minimal local executor that parks the current thread until the future is woken up.
+    fn block_on<F: Future>(mut future: F) -> F::Output {
+        struct ThreadWaker(Thread);
+        impl Wake for ThreadWaker {
+            fn wake(self: Arc<Self>) {
+                self.0.unpark();
+            }
+        }
+
+        let waker = Arc::new(ThreadWaker(thread::current())).into();
+        let mut cx = Context::from_waker(&waker);
+        let mut future = unsafe { Pin::new_unchecked(&mut future) };
+
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    #[test]
+    fn signaled_fence() {
+        let (device, _) = mock_dev_and_queue!();
+        let fence = Fence::alloc_signaled(device).unwrap();
+        block_on(fence.into_async_wait()).unwrap();
+    }
+
+    #[test]
+    fn fence_signal_future() {
+        let (device, queue) = mock_dev_and_queue!();
+        let cb = AutoCommandBufferBuilder::new(device.clone(), queue.family())
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let future = now(device)
+            .then_execute(queue, cb)
+            .unwrap()
+            .then_signal_fence_and_flush()
+            .unwrap();
+        block_on(future.into_async_wait()).unwrap();
+    }
+
+    #[test]
+    fn drop_before_completion() {
+        let (device, queue) = mock_dev_and_queue!();
+        let cb = AutoCommandBufferBuilder::new(device.clone(), queue.family())
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let future = now(device)
+            .then_execute(queue, cb)
+            .unwrap()
+            .then_signal_fence_and_flush()
+            .unwrap();
+        drop(future.into_async_wait());
+    }
+
+    #[test]
+    fn drop_releases_pending_fence() {
+        let (device, _) = mock_dev_and_queue!();
+        let recorder = Recorder::of(device.instance()).unwrap();
+
+        // The mock never signals this fence, so it would stay on the background thread if it
+        // wasn't released when the wait is dropped.
+        drop(Fence::alloc(device.clone()).unwrap().into_async_wait());
+
+        let start = Instant::now();
+        while !recorder.calls().contains(&"vkDestroyFence") {
+            assert!(start.elapsed() < Duration::from_secs(10));
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn pending_fence_doesnt_block_others() {
+        let (device, queue) = mock_dev_and_queue!();
+
+        // The mock only signals the fences that are passed to a submission, so this one stays
+        // pending forever.
+        let pending = Fence::alloc(device.clone()).unwrap().into_async_wait();
+
+        let waits = (0 .. 3)
+            .map(|_| {
+                let cb = AutoCommandBufferBuilder::new(device.clone(), queue.family())
+                    .unwrap()
+                    .build()
+                    .unwrap();
+
+                now(device.clone())
+                    .then_execute(queue.clone(), cb)
+                    .unwrap()
+                    .then_signal_fence_and_flush()
+                    .unwrap()
+                    .into_async_wait()
+            })
+            .collect::<Vec<_>>();
+
+        for wait in waits {
+            block_on(wait).unwrap();
+        }
+
+        drop(pending);
+    }
+}
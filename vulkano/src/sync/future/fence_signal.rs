@@ -24,9 +24,12 @@ use image::ImageLayout;
 use sync::AccessCheckError;
 use sync::AccessFlagBits;
 use sync::Fence;
+use sync::FenceWaitError;
 use sync::FlushError;
 use sync::GpuFuture;
 use sync::PipelineStages;
+use vk;
+use VulkanObject;
 
 /// Builds a new fence signal future.
 #[inline]
@@ -156,6 +159,41 @@ impl<F> FenceSignalFuture<F>
 impl<F> FenceSignalFuture<F>
     where F: GpuFuture
 {
+    // Blocks until the fence is signaled or the timeout expires, flushing if necessary. Returns
+    // `Ok(false)` if the timeout expired. Contrary to `wait`, the previous future is kept alive in
+    // case of a timeout.
+    pub(super) fn wait_timeout(&self, timeout: Duration) -> Result<bool, FlushError> {
+        let mut state = self.state.lock().unwrap();
+
+        self.flush_impl(&mut state)?;
+
+        match *state {
+            FenceSignalFutureState::Flushed(ref previous, ref fence) => {
+                match fence.wait(Some(timeout)) {
+                    Ok(()) => unsafe {
+                        previous.signal_finished();
+                    },
+                    Err(FenceWaitError::Timeout) => return Ok(false),
+                    Err(err) => return Err(err.into()),
+                }
+            },
+            FenceSignalFutureState::Cleaned => return Ok(true),
+            _ => unreachable!(),
+        }
+
+        *state = FenceSignalFutureState::Cleaned;
+        Ok(true)
+    }
+
+    // Returns the fence that is going to be signaled, if the submission has been flushed and the
+    // fence hasn't been waited upon yet.
+    pub(super) fn raw_fence(&self) -> Option<vk::Fence> {
+        match *self.state.lock().unwrap() {
+            FenceSignalFutureState::Flushed(_, ref fence) => Some(fence.internal_object()),
+            _ => None,
+        }
+    }
+
     // Implementation of `cleanup_finished`, but takes a `&self` instead of a `&mut self`.
     // This is an external function so that we can also call it from an `Arc<FenceSignalFuture>`.
     #[inline]
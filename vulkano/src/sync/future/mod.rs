@@ -32,11 +32,13 @@ use sync::AccessFlagBits;
 use sync::FenceWaitError;
 use sync::PipelineStages;
 
+pub use self::async_wait::FenceAsyncWait;
 pub use self::fence_signal::{FenceSignalFuture, FenceSignalFutureBehavior};
 pub use self::join::JoinFuture;
 pub use self::now::{NowFuture, now};
 pub use self::semaphore_signal::SemaphoreSignalFuture;
 
+mod async_wait;
 mod now;
 mod fence_signal;
 mod join;
@@ -331,6 +333,29 @@ pub enum AccessError {
 
     /// Trying to use a swapchain image without depending on a corresponding acquire image future.
     SwapchainImageAcquireOnly,
+
+    /// The queue family ownership of the resource has been released by a previous submission.
+    /// The resource must be acquired by a command buffer submitted on the `destination` queue
+    /// family before it can be used again.
+    QueueFamilyOwnershipReleased {
+        /// Queue family that released the ownership.
+        source: u32,
+        /// Queue family that the ownership has been released to.
+        destination: u32,
+    },
+
+    /// The resource has been acquired by another queue family. Its ownership must be released
+    /// and acquired again before it can be used on this queue family.
+    WrongQueueFamilyOwner {
+        /// Queue family that currently owns the resource.
+        owner: u32,
+        /// Queue family that tried to use the resource.
+        requested: u32,
+    },
+
+    /// Trying to acquire the queue family ownership of a resource whose ownership hasn't been
+    /// released to this queue family by a previous submission.
+    QueueFamilyOwnershipNotReleased,
 }
 
 impl error::Error for AccessError {
@@ -357,6 +382,16 @@ impl error::Error for AccessError {
                 "trying to use a swapchain image without depending on a corresponding acquire \
                  image future"
             },
+            AccessError::QueueFamilyOwnershipReleased { .. } => {
+                "the queue family ownership of the resource has been released and must be \
+                 acquired first"
+            },
+            AccessError::WrongQueueFamilyOwner { .. } => {
+                "the resource is owned by another queue family"
+            },
+            AccessError::QueueFamilyOwnershipNotReleased => {
+                "trying to acquire the queue family ownership of a resource that wasn't released"
+            },
         }
     }
 }
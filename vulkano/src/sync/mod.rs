@@ -107,11 +107,17 @@ use device::Queue;
 use std::sync::Arc;
 
 pub use self::event::Event;
+pub use self::external::ExternalFenceHandleType;
+pub use self::external::ExternalFenceProperties;
+pub use self::external::ExternalSemaphoreHandleType;
+pub use self::external::ExternalSemaphoreProperties;
+pub use self::external::ExternalSyncError;
 pub use self::fence::Fence;
 pub use self::fence::FenceWaitError;
 pub use self::future::now;
 pub use self::future::AccessCheckError;
 pub use self::future::AccessError;
+pub use self::future::FenceAsyncWait;
 pub use self::future::FenceSignalFuture;
 pub use self::future::FlushError;
 pub use self::future::GpuFuture;
@@ -122,9 +128,13 @@ pub use self::pipeline::AccessFlagBits;
 pub use self::pipeline::PipelineStages;
 pub use self::semaphore::Semaphore;
 
+pub(crate) use self::ownership::QueueFamilyOwnership;
+
 mod event;
+mod external;
 mod fence;
 mod future;
+mod ownership;
 mod pipeline;
 mod semaphore;
 
@@ -0,0 +1,130 @@
+// Copyright (c) 2020 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+
+use sync::AccessError;
+
+// Queue family ownership of a buffer or an image created with `Sharing::Exclusive`.
+//
+// It is stored in the `UnsafeBuffer` or `UnsafeImage` and updated when a command buffer that
+// transfers the ownership is submitted, so that it outlives the futures of the submissions. Only
+// the transfers recorded with the ownership commands of `AutoCommandBufferBuilder` are known; a
+// resource that has never been transferred can be used by any queue family.
+#[derive(Debug, Default)]
+pub(crate) struct QueueFamilyOwnership {
+    state: Mutex<OwnershipState>,
+}
+
+#[derive(Debug, Default)]
+struct OwnershipState {
+    // Queue family that acquired the ownership of the resource, if any.
+    owner: Option<u32>,
+    // Source and destination queue families of a release that hasn't been acquired yet.
+    release: Option<(u32, u32)>,
+}
+
+impl QueueFamilyOwnership {
+    // Locks the ownership, so that a submission can check it and record its transfers without
+    // another submission doing the same in between.
+    pub fn lock(&self) -> QueueFamilyOwnershipLock {
+        QueueFamilyOwnershipLock { state: self.state.lock().unwrap() }
+    }
+
+    // Same as `QueueFamilyOwnershipLock::check`.
+    pub fn check(&self, family: u32, acquire: Option<(u32, u32)>) -> Result<(), AccessError> {
+        self.lock().check(family, acquire)
+    }
+}
+
+// Lock on the queue family ownership of a resource, returned by `QueueFamilyOwnership::lock`.
+pub(crate) struct QueueFamilyOwnershipLock<'a> {
+    state: MutexGuard<'a, OwnershipState>,
+}
+
+impl<'a> QueueFamilyOwnershipLock<'a> {
+    // Checks whether a submission on a queue of `family` can use the resource. `acquire` contains
+    // the source and destination queue families if the submission acquires the ownership.
+    pub fn check(&self, family: u32, acquire: Option<(u32, u32)>) -> Result<(), AccessError> {
+        match (self.state.release, acquire) {
+            (Some(release), Some(acquire)) if release == acquire => Ok(()),
+            (_, Some(_)) => Err(AccessError::QueueFamilyOwnershipNotReleased),
+            (Some((source, destination)), None) => {
+                Err(AccessError::QueueFamilyOwnershipReleased {
+                        source,
+                        destination,
+                    })
+            },
+            (None, None) => {
+                match self.state.owner {
+                    Some(owner) if owner != family => {
+                        Err(AccessError::WrongQueueFamilyOwner {
+                                owner,
+                                requested: family,
+                            })
+                    },
+                    _ => Ok(()),
+                }
+            },
+        }
+    }
+
+    // Records the transfers of a submission that has been checked with `check` while the lock
+    // was held.
+    pub fn transfer(&mut self, acquire: Option<(u32, u32)>, release: Option<(u32, u32)>) {
+        if let Some((_, destination)) = acquire {
+            self.state.owner = Some(destination);
+            self.state.release = None;
+        }
+
+        if let Some(release) = release {
+            self.state.owner = None;
+            self.state.release = Some(release);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::QueueFamilyOwnership;
+    use sync::AccessError;
+
+    #[test]
+    fn never_transferred() {
+        let ownership = QueueFamilyOwnership::default();
+        assert!(ownership.check(0, None).is_ok());
+        assert!(ownership.check(1, None).is_ok());
+        assert_eq!(ownership.check(1, Some((0, 1))),
+                   Err(AccessError::QueueFamilyOwnershipNotReleased));
+    }
+
+    #[test]
+    fn release_then_acquire() {
+        let ownership = QueueFamilyOwnership::default();
+        ownership.lock().transfer(None, Some((0, 1)));
+
+        assert_eq!(ownership.check(0, None),
+                   Err(AccessError::QueueFamilyOwnershipReleased {
+                           source: 0,
+                           destination: 1,
+                       }));
+        assert_eq!(ownership.check(2, Some((0, 2))),
+                   Err(AccessError::QueueFamilyOwnershipNotReleased));
+        assert!(ownership.check(1, Some((0, 1))).is_ok());
+
+        ownership.lock().transfer(Some((0, 1)), None);
+        assert!(ownership.check(1, None).is_ok());
+        assert_eq!(ownership.check(0, None),
+                   Err(AccessError::WrongQueueFamilyOwner {
+                           owner: 1,
+                           requested: 0,
+                       }));
+    }
+}
@@ -7,16 +7,24 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+#[cfg(unix)]
+use std::fs::File;
 use std::mem::MaybeUninit;
+#[cfg(unix)]
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd};
 use std::ptr;
 use std::sync::Arc;
 
+use Error;
 use OomError;
 use SafeDeref;
 use VulkanObject;
 use check_errors;
 use device::Device;
 use device::DeviceOwned;
+use sync::ExternalSemaphoreHandleType;
+use sync::ExternalSyncError;
+use version::Version;
 use vk;
 
 /// Used to provide synchronization between command buffers during their execution.
@@ -30,6 +38,7 @@ pub struct Semaphore<D = Arc<Device>>
     semaphore: vk::Semaphore,
     device: D,
     must_put_in_pool: bool,
+    export_handle_types: ExternalSemaphoreHandleType,
 }
 
 impl<D> Semaphore<D>
@@ -49,11 +58,12 @@ impl<D> Semaphore<D>
                        device: device,
                        semaphore: raw_sem,
                        must_put_in_pool: true,
+                       export_handle_types: ExternalSemaphoreHandleType::none(),
                    })
             },
             None => {
                 // Pool is empty, alloc new semaphore
-                Semaphore::alloc_impl(device, true)
+                Ok(Semaphore::alloc_impl(device, true, ExternalSemaphoreHandleType::none())?)
             },
         }
     }
@@ -61,22 +71,66 @@ impl<D> Semaphore<D>
     /// Builds a new semaphore.
     #[inline]
     pub fn alloc(device: D) -> Result<Semaphore<D>, OomError> {
-        Semaphore::alloc_impl(device, false)
+        Ok(Semaphore::alloc_impl(device, false, ExternalSemaphoreHandleType::none())?)
     }
 
-    fn alloc_impl(device: D, must_put_in_pool: bool) -> Result<Semaphore<D>, OomError> {
+    /// Builds a new semaphore that can later be exported with the given handle types.
+    ///
+    /// The `VK_KHR_external_semaphore` extension must be enabled on the device unless it uses
+    /// Vulkan 1.1, plus `VK_KHR_external_semaphore_fd` for handle types that are backed by a file
+    /// descriptor.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `handle_types` is empty.
+    ///
+    pub fn alloc_exportable(device: D, handle_types: ExternalSemaphoreHandleType)
+                            -> Result<Semaphore<D>, ExternalSyncError> {
+        assert!(!handle_types.is_empty());
+
+        if device.api_version() < Version::V1_1 &&
+            !device.loaded_extensions().khr_external_semaphore
+        {
+            return Err(ExternalSyncError::MissingExtension { name: "VK_KHR_external_semaphore" });
+        }
+        if (handle_types.opaque_fd || handle_types.sync_fd) &&
+            !device.loaded_extensions().khr_external_semaphore_fd
+        {
+            return Err(ExternalSyncError::MissingExtension {
+                           name: "VK_KHR_external_semaphore_fd",
+                       });
+        }
+
+        Ok(Semaphore::alloc_impl(device, false, handle_types)?)
+    }
+
+    fn alloc_impl(device: D, must_put_in_pool: bool,
+                  export_handle_types: ExternalSemaphoreHandleType)
+                  -> Result<Semaphore<D>, Error> {
         let semaphore = unsafe {
-            // since the creation is constant, we use a `static` instead of a struct on the stack
-            static mut INFOS: vk::SemaphoreCreateInfo = vk::SemaphoreCreateInfo {
+            let export_infos = if !export_handle_types.is_empty() {
+                Some(vk::ExportSemaphoreCreateInfoKHR {
+                         sType: vk::STRUCTURE_TYPE_EXPORT_SEMAPHORE_CREATE_INFO_KHR,
+                         pNext: ptr::null(),
+                         handleTypes: export_handle_types.to_bits(),
+                     })
+            } else {
+                None
+            };
+
+            let infos = vk::SemaphoreCreateInfo {
                 sType: vk::STRUCTURE_TYPE_SEMAPHORE_CREATE_INFO,
-                pNext: 0 as *const _, // ptr::null()
+                pNext: export_infos
+                    .as_ref()
+                    .map(|i| i as *const vk::ExportSemaphoreCreateInfoKHR)
+                    .unwrap_or(ptr::null()) as *const _,
                 flags: 0, // reserved
             };
 
             let vk = device.pointers();
             let mut output = MaybeUninit::uninit();
             check_errors(vk.CreateSemaphore(device.internal_object(),
-                                            &INFOS,
+                                            &infos,
                                             ptr::null(),
                                             output.as_mut_ptr()))?;
             output.assume_init()
@@ -86,8 +140,117 @@ impl<D> Semaphore<D>
                device: device,
                semaphore: semaphore,
                must_put_in_pool: must_put_in_pool,
+               export_handle_types: export_handle_types,
            })
     }
+
+    /// Returns the handle types this semaphore can be exported as.
+    #[inline]
+    pub fn export_handle_types(&self) -> ExternalSemaphoreHandleType {
+        self.export_handle_types
+    }
+
+    /// Exports the semaphore as a POSIX file descriptor.
+    ///
+    /// `handle_type` must be either `opaque_fd` or `sync_fd`, and the semaphore must have been
+    /// created as exportable with that handle type. The returned file is owned by the caller.
+    ///
+    /// > **Note**: Exporting a `sync_fd` has the same effect as waiting on the semaphore. The
+    /// > semaphore must be signaled, or have a signal operation pending, when doing so.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `handle_type` doesn't have exactly one of `opaque_fd` or `sync_fd` set.
+    ///
+    #[cfg(unix)]
+    pub fn export_fd(&self, handle_type: ExternalSemaphoreHandleType)
+                     -> Result<File, ExternalSyncError> {
+        assert!(handle_type.is_single_fd(),
+                "only `opaque_fd` or `sync_fd` can be exported as a file descriptor");
+
+        if !self.device.loaded_extensions().khr_external_semaphore_fd {
+            return Err(ExternalSyncError::MissingExtension {
+                           name: "VK_KHR_external_semaphore_fd",
+                       });
+        }
+        if !self.export_handle_types.superset_of(&handle_type) {
+            return Err(ExternalSyncError::HandleTypeNotEnabled);
+        }
+
+        unsafe {
+            let vk = self.device.pointers();
+
+            let infos = vk::SemaphoreGetFdInfoKHR {
+                sType: vk::STRUCTURE_TYPE_SEMAPHORE_GET_FD_INFO_KHR,
+                pNext: ptr::null(),
+                semaphore: self.semaphore,
+                handleType: handle_type.to_bits(),
+            };
+
+            let mut output = MaybeUninit::uninit();
+            check_errors(vk.GetSemaphoreFdKHR(self.device.internal_object(),
+                                              &infos,
+                                              output.as_mut_ptr()))?;
+            Ok(File::from_raw_fd(output.assume_init()))
+        }
+    }
+
+    /// Imports a payload that was exported as a POSIX file descriptor into this semaphore.
+    ///
+    /// If `temporary` is true, the imported payload only replaces the current one until the
+    /// semaphore is waited upon. A `sync_fd` can only be imported temporarily.
+    ///
+    /// On success, the file descriptor is owned by the Vulkan implementation. The semaphore is then
+    /// destroyed when dropped, instead of being put back in the pool it may come from.
+    ///
+    /// # Safety
+    ///
+    /// - The semaphore must not be in use by a pending queue operation.
+    /// - For `opaque_fd`, the payload must come from a device with the same device and driver
+    ///   UUIDs as this one.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `handle_type` doesn't have exactly one of `opaque_fd` or `sync_fd` set.
+    /// - Panics if `handle_type` is `sync_fd` and `temporary` is false.
+    ///
+    #[cfg(unix)]
+    pub unsafe fn import_fd(&mut self, handle_type: ExternalSemaphoreHandleType,
+                            temporary: bool, file: File)
+                            -> Result<(), ExternalSyncError> {
+        assert!(handle_type.is_single_fd(),
+                "only `opaque_fd` or `sync_fd` can be imported from a file descriptor");
+        assert!(temporary || !handle_type.sync_fd,
+                "a `sync_fd` can only be imported temporarily");
+
+        if !self.device.loaded_extensions().khr_external_semaphore_fd {
+            return Err(ExternalSyncError::MissingExtension {
+                           name: "VK_KHR_external_semaphore_fd",
+                       });
+        }
+
+        let vk = self.device.pointers();
+
+        let infos = vk::ImportSemaphoreFdInfoKHR {
+            sType: vk::STRUCTURE_TYPE_IMPORT_SEMAPHORE_FD_INFO_KHR,
+            pNext: ptr::null(),
+            semaphore: self.semaphore,
+            flags: if temporary {
+                vk::SEMAPHORE_IMPORT_TEMPORARY_BIT_KHR
+            } else {
+                0
+            },
+            handleType: handle_type.to_bits(),
+            fd: file.as_raw_fd(),
+        };
+
+        check_errors(vk.ImportSemaphoreFdKHR(self.device.internal_object(), &infos))?;
+        // The implementation now owns the file descriptor.
+        let _ = file.into_raw_fd();
+        // The payload of the semaphore must not be shared with the next user of the pool.
+        self.must_put_in_pool = false;
+        Ok(())
+    }
 }
 
 unsafe impl DeviceOwned for Semaphore {
@@ -130,17 +293,19 @@ impl<D> Drop for Semaphore<D>
 #[cfg(test)]
 mod tests {
     use VulkanObject;
+    use sync::ExternalSemaphoreHandleType;
+    use sync::ExternalSyncError;
     use sync::Semaphore;
 
     #[test]
     fn semaphore_create() {
-        let (device, _) = gfx_dev_and_queue!();
+        let (device, _) = mock_dev_and_queue!();
         let _ = Semaphore::alloc(device.clone());
     }
 
     #[test]
     fn semaphore_pool() {
-        let (device, _) = gfx_dev_and_queue!();
+        let (device, _) = mock_dev_and_queue!();
 
         assert_eq!(device.semaphore_pool().lock().unwrap().len(), 0);
         let sem1_internal_obj = {
@@ -154,4 +319,56 @@ mod tests {
         assert_eq!(device.semaphore_pool().lock().unwrap().len(), 0);
         assert_eq!(sem2.internal_object(), sem1_internal_obj);
     }
+
+    #[test]
+    fn semaphore_exportable_requires_extension() {
+        let (device, _) = mock_dev_and_queue!();
+
+        match Semaphore::alloc_exportable(device.clone(), ExternalSemaphoreHandleType::posix()) {
+            Err(ExternalSyncError::MissingExtension { .. }) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn imported_semaphore_not_put_in_pool() {
+        let (device, _) = mock_dev_and_queue!(extensions: [khr_external_semaphore,
+                                                           khr_external_semaphore_fd]);
+        let handle_type = ExternalSemaphoreHandleType::posix();
+        let exported = Semaphore::alloc_exportable(device.clone(), handle_type).unwrap();
+        let file = exported.export_fd(handle_type).unwrap();
+
+        let mut semaphore = Semaphore::from_pool(device.clone()).unwrap();
+        unsafe {
+            semaphore.import_fd(handle_type, false, file).unwrap();
+        }
+        drop(semaphore);
+
+        assert_eq!(device.semaphore_pool().lock().unwrap().len(), 0);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn failed_import_closes_fd() {
+        use std::fs::File;
+        use std::io::Read;
+        use std::os::unix::io::{FromRawFd, IntoRawFd};
+        use std::os::unix::net::UnixStream;
+
+        let (device, _) = mock_dev_and_queue!(extensions: [khr_external_semaphore,
+                                                           khr_external_semaphore_fd]);
+        let mut semaphore = Semaphore::alloc(device.clone()).unwrap();
+
+        // The other end of the socket reaches the end of the stream once the fd is closed.
+        let (ours, mut theirs) = UnixStream::pair().unwrap();
+        let file = unsafe { File::from_raw_fd(ours.into_raw_fd()) };
+        match unsafe { semaphore.import_fd(ExternalSemaphoreHandleType::posix(), false, file) } {
+            Err(ExternalSyncError::InvalidExternalHandle) => (),
+            _ => panic!(),
+        }
+
+        theirs.set_nonblocking(true).unwrap();
+        assert_eq!(theirs.read(&mut [0; 1]).unwrap(), 0);
+    }
 }
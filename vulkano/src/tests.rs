@@ -66,6 +66,112 @@ macro_rules! gfx_dev_and_queue {
     });
 }
 
+/// Same as `gfx_dev_and_queue!`, but uses the in-process mock of Vulkan, which works everywhere.
+///
+/// The recorder of the mock can be obtained with `mock::Recorder::of(device.instance())`. Device
+/// extensions can be enabled with `mock_dev_and_queue!(extensions: [khr_external_fence_fd])`, and
+/// instance extensions with `mock_dev_and_queue!(instance_extensions: [ext_debug_utils],
+/// extensions: [])`.
+macro_rules! mock_dev_and_queue {
+    ($($feature:ident),*) => ({
+        mock_dev_and_queue!(extensions: [] $(, $feature)*)
+    });
+
+    (extensions: [$($extension:ident),*] $(, $feature:ident)*) => ({
+        mock_dev_and_queue!(instance_extensions: [], extensions: [$($extension),*]
+                            $(, $feature)*)
+    });
+
+    (instance_extensions: [$($iext:ident),*], extensions: [$($extension:ident),*]
+     $(, $feature:ident)*) => ({
+        use device::Device;
+        use device::DeviceExtensions;
+        use features::Features;
+        use instance::Instance;
+        use instance::InstanceExtensions;
+        use instance::PhysicalDevice;
+        use instance::loader::FunctionPointers;
+        use mock::MockIcd;
+
+        let instance_extensions = InstanceExtensions {
+            $(
+                $iext: true,
+            )*
+            .. InstanceExtensions::none()
+        };
+
+        let loader = FunctionPointers::new(Box::new(MockIcd) as Box<_>);
+        let instance = Instance::with_loader(loader, None, &instance_extensions, None).unwrap();
+        let physical = PhysicalDevice::enumerate(&instance).next().unwrap();
+        let queue = physical.queue_families().next().unwrap();
+
+        let features = Features {
+            $(
+                $feature: true,
+            )*
+            .. Features::none()
+        };
+
+        let extensions = DeviceExtensions {
+            $(
+                $extension: true,
+            )*
+            .. DeviceExtensions::none()
+        };
+
+        let (device, mut queues) = Device::new(physical, &features, &extensions,
+                                               [(queue, 0.5)].iter().cloned())
+            .unwrap();
+
+        (device, queues.next().unwrap())
+    });
+}
+
+/// Same as `mock_dev_and_queue!`, but also returns a queue of the second queue family of the mock,
+/// which only supports transfer operations.
+macro_rules! mock_dev_and_transfer_queue {
+    () => ({
+        use device::Device;
+        use device::DeviceExtensions;
+        use features::Features;
+        use instance::Instance;
+        use instance::InstanceExtensions;
+        use instance::PhysicalDevice;
+        use instance::loader::FunctionPointers;
+        use mock::MockIcd;
+
+        let loader = FunctionPointers::new(Box::new(MockIcd) as Box<_>);
+        let instance = Instance::with_loader(loader, None, &InstanceExtensions::none(), None)
+            .unwrap();
+        let physical = PhysicalDevice::enumerate(&instance).next().unwrap();
+        let families = physical.queue_families().collect::<Vec<_>>();
+
+        let (device, mut queues) = Device::new(physical, &Features::none(),
+                                               &DeviceExtensions::none(),
+                                               [(families[0], 0.5), (families[1], 0.5)]
+                                                   .iter()
+                                                   .cloned())
+            .unwrap();
+
+        (device, queues.next().unwrap(), queues.next().unwrap())
+    });
+}
+
+/// Serializes a value to JSON, deserializes it back and checks that the result is equal to the
+/// original.
+#[cfg(feature = "serde")]
+macro_rules! assert_serde_round_trip {
+    ($value:expr) => ({
+        fn same_type<T>(_: &T, _: &T) {}
+
+        let value = $value;
+        let json = ::serde_json::to_string(&value).unwrap();
+        let back = ::serde_json::from_str(&json).unwrap();
+        same_type(&value, &back);
+        assert_eq!(value, back);
+    });
+}
+
 macro_rules! assert_should_panic {
     ($msg:expr, $code:block) => ({
         let res = ::std::panic::catch_unwind(|| {